@@ -0,0 +1,460 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Delay before the startup backfill, so it doesn't compete with the first window opening
+const BACKFILL_DELAY: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityRange {
+    Week,
+    Month,
+    Quarter,
+    Year,
+    /// From the first recorded day
+    All,
+}
+
+#[derive(Default, Clone, Copy, serde::Serialize)]
+pub struct ActivityCounts {
+    /// Lines submitted at a shell prompt in the app's terminals
+    commands: u64,
+    /// Claude Code transcripts started, plus headless runs of other agent CLIs
+    agent_sessions: u64,
+    /// Files changed by tracked agent sessions
+    files_changed: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_read_tokens: u64,
+    cache_creation_tokens: u64,
+    /// Estimated from token usage at list prices
+    cost_usd: f64,
+}
+
+impl ActivityCounts {
+    fn add(&mut self, other: &ActivityCounts) {
+        self.commands += other.commands;
+        self.agent_sessions += other.agent_sessions;
+        self.files_changed += other.files_changed;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct DayActivity {
+    /// YYYY-MM-DD, local time
+    day: String,
+    #[serde(flatten)]
+    counts: ActivityCounts,
+}
+
+#[derive(serde::Serialize)]
+pub struct ActivitySummary {
+    /// Every day of the range, oldest first, including days with no activity
+    days: Vec<DayActivity>,
+    totals: ActivityCounts,
+}
+
+#[derive(Clone)]
+pub struct ActivityManager {
+    /// Whether each PTY has a partly typed line, so bare Enter presses aren't counted
+    typing: Arc<Mutex<HashMap<u32, bool>>>,
+    /// Held while ingesting, so concurrent refreshes don't count the same lines twice
+    refreshing: Arc<Mutex<()>>,
+}
+
+impl ActivityManager {
+    pub fn new() -> Self {
+        Self {
+            typing: Arc::new(Mutex::new(HashMap::new())),
+            refreshing: Arc::new(Mutex::new(())),
+        }
+    }
+}
+
+fn db_path() -> PathBuf {
+    crate::ade_path("activity.sqlite")
+}
+
+fn open_db() -> Result<Connection, String> {
+    let path = db_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create dir: {}", e))?;
+    }
+    let conn = Connection::open(&path).map_err(|e| format!("Failed to open activity db: {}", e))?;
+    let _ = conn.busy_timeout(Duration::from_secs(5));
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS days (
+             day TEXT PRIMARY KEY,
+             commands INTEGER NOT NULL DEFAULT 0,
+             agent_sessions INTEGER NOT NULL DEFAULT 0,
+             files_changed INTEGER NOT NULL DEFAULT 0,
+             input_tokens INTEGER NOT NULL DEFAULT 0,
+             output_tokens INTEGER NOT NULL DEFAULT 0,
+             cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+             cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+             cost_usd REAL NOT NULL DEFAULT 0
+         );
+         CREATE TABLE IF NOT EXISTS transcripts (
+             path TEXT PRIMARY KEY,
+             offset INTEGER NOT NULL,
+             counted INTEGER NOT NULL,
+             last_message TEXT
+         );
+         CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);",
+    )
+    .map_err(|e| format!("Failed to initialise activity db: {}", e))?;
+    Ok(conn)
+}
+
+fn day_of_ms(ms: u64) -> Option<String> {
+    use chrono::TimeZone;
+    let time = chrono::Local.timestamp_millis_opt(ms as i64).single()?;
+    Some(time.format("%Y-%m-%d").to_string())
+}
+
+fn day_of_timestamp(timestamp: &str) -> Option<String> {
+    let time = chrono::DateTime::parse_from_rfc3339(timestamp).ok()?;
+    Some(
+        time.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d")
+            .to_string(),
+    )
+}
+
+fn add_to_day(conn: &Connection, day: &str, counts: &ActivityCounts) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO days (day, commands, agent_sessions, files_changed, input_tokens,
+             output_tokens, cache_read_tokens, cache_creation_tokens, cost_usd)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(day) DO UPDATE SET
+             commands = commands + excluded.commands,
+             agent_sessions = agent_sessions + excluded.agent_sessions,
+             files_changed = files_changed + excluded.files_changed,
+             input_tokens = input_tokens + excluded.input_tokens,
+             output_tokens = output_tokens + excluded.output_tokens,
+             cache_read_tokens = cache_read_tokens + excluded.cache_read_tokens,
+             cache_creation_tokens = cache_creation_tokens + excluded.cache_creation_tokens,
+             cost_usd = cost_usd + excluded.cost_usd",
+        params![
+            day,
+            counts.commands as i64,
+            counts.agent_sessions as i64,
+            counts.files_changed as i64,
+            counts.input_tokens as i64,
+            counts.output_tokens as i64,
+            counts.cache_read_tokens as i64,
+            counts.cache_creation_tokens as i64,
+            counts.cost_usd,
+        ],
+    )
+    .map(|_| ())
+}
+
+/// Add `counts` to today's bucket
+fn record_today(counts: ActivityCounts) {
+    let Some(day) = day_of_ms(crate::now_ms()) else {
+        return;
+    };
+    let result =
+        open_db().and_then(|conn| add_to_day(&conn, &day, &counts).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        tracing::warn!(error = %e, "Failed to record activity");
+    }
+}
+
+/// Count a headless run of an agent CLI. Claude Code runs are left to its transcripts,
+/// which are counted however the session was started.
+pub(crate) fn record_agent_run(tool: &str) {
+    if tool == "claude" {
+        return;
+    }
+    std::thread::spawn(|| {
+        record_today(ActivityCounts {
+            agent_sessions: 1,
+            ..Default::default()
+        })
+    });
+}
+
+/// New lines of one Claude Code transcript since the last refresh
+fn ingest_transcript(
+    conn: &Connection,
+    path: &std::path::Path,
+    days: &mut HashMap<String, ActivityCounts>,
+) -> Result<(), String> {
+    let key = path.to_string_lossy().to_string();
+    let (offset, counted, mut last_message) = conn
+        .query_row(
+            "SELECT offset, counted, last_message FROM transcripts WHERE path = ?1",
+            [&key],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as u64,
+                    row.get::<_, bool>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or((0, false, None));
+    let len = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if len <= offset {
+        return Ok(());
+    }
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| e.to_string())?;
+    let mut bytes = Vec::new();
+    file.take(len - offset)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+    // A line still being written is left for the next refresh
+    let Some(end) = bytes.iter().rposition(|&b| b == b'\n') else {
+        return Ok(());
+    };
+    let mut counted = counted;
+    for line in String::from_utf8_lossy(&bytes[..=end]).lines() {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        let Some(day) = entry
+            .get("timestamp")
+            .and_then(|t| t.as_str())
+            .and_then(day_of_timestamp)
+        else {
+            continue;
+        };
+        let bucket = days.entry(day).or_default();
+        if !counted {
+            bucket.agent_sessions += 1;
+            counted = true;
+        }
+        if entry.get("type").and_then(|t| t.as_str()) != Some("assistant") {
+            continue;
+        }
+        let Some(message) = entry.get("message") else {
+            continue;
+        };
+        // Each content block of a message is its own line, repeating the message's usage
+        let id = message.get("id").and_then(|i| i.as_str()).map(String::from);
+        if id.is_some() && id == last_message {
+            continue;
+        }
+        last_message = id;
+        let Some(usage) = message.get("usage") else {
+            continue;
+        };
+        let usage = crate::sessions::TokenUsage::from_json(usage);
+        let model = message.get("model").and_then(|m| m.as_str());
+        bucket.input_tokens += usage.input;
+        bucket.output_tokens += usage.output;
+        bucket.cache_read_tokens += usage.cache_read;
+        bucket.cache_creation_tokens += usage.cache_creation;
+        bucket.cost_usd += crate::budget::estimate(&usage, model).cost_usd;
+    }
+    conn.execute(
+        "INSERT OR REPLACE INTO transcripts (path, offset, counted, last_message)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![key, (offset + end as u64 + 1) as i64, counted, last_message],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+impl ActivityManager {
+    /// Count the lines `data` submits in PTY `pty_id`. Called before the input is written,
+    /// while a command about to start isn't yet the shell's foreground process.
+    pub(crate) fn observe_input(&self, pty_id: u32, shell_pid: Option<u32>, data: &[u8]) {
+        let mut submitted = 0;
+        {
+            let mut typing = self.typing.lock().unwrap();
+            let typed = typing.entry(pty_id).or_default();
+            for &byte in data {
+                match byte {
+                    b'\r' | b'\n' => {
+                        if *typed {
+                            submitted += 1;
+                        }
+                        *typed = false;
+                    }
+                    // Ctrl-C and Ctrl-U abandon the line
+                    0x03 | 0x15 => *typed = false,
+                    0x20..=0x7e | 0x80.. => *typed = true,
+                    _ => {}
+                }
+            }
+        }
+        if submitted == 0 {
+            return;
+        }
+        // Input to a program running in the terminal (an editor, an agent) isn't a command
+        if shell_pid.is_some_and(|pid| crate::pty::get_foreground_pid(pid).is_some()) {
+            return;
+        }
+        std::thread::spawn(move || {
+            record_today(ActivityCounts {
+                commands: submitted,
+                ..Default::default()
+            })
+        });
+    }
+
+    pub(crate) fn forget_pty(&self, pty_id: u32) {
+        self.typing.lock().unwrap().remove(&pty_id);
+    }
+
+    /// Fold whatever was logged since the last refresh into the daily buckets: new lines of
+    /// Claude Code transcripts and newly finished session-change records
+    fn refresh(&self) -> Result<(), String> {
+        let _guard = self.refreshing.lock().unwrap();
+        let mut conn = open_db()?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let mut days: HashMap<String, ActivityCounts> = HashMap::new();
+
+        let pattern = crate::sessions::projects_dir().join("*").join("*.jsonl");
+        for path in glob::glob(&pattern.to_string_lossy())
+            .map_err(|e| e.to_string())?
+            .flatten()
+        {
+            if let Err(e) = ingest_transcript(&tx, &path, &mut days) {
+                tracing::warn!(path = %path.display(), error = %e, "Failed to read transcript");
+            }
+        }
+
+        let changes_through: u64 = tx
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'changes_through'",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        let mut latest = changes_through;
+        for (ended_at, files) in crate::session_changes::finished_since(changes_through) {
+            if let Some(day) = day_of_ms(ended_at) {
+                days.entry(day).or_default().files_changed += files as u64;
+            }
+            latest = latest.max(ended_at);
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('changes_through', ?1)",
+            [latest.to_string()],
+        )
+        .map_err(|e| e.to_string())?;
+
+        for (day, counts) in &days {
+            add_to_day(&tx, day, counts).map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+}
+
+fn summary(range: ActivityRange) -> Result<ActivitySummary, String> {
+    let conn = open_db()?;
+    let today = chrono::Local::now().date_naive();
+    let from = match range {
+        ActivityRange::Week => today - chrono::Days::new(6),
+        ActivityRange::Month => today - chrono::Days::new(29),
+        ActivityRange::Quarter => today - chrono::Days::new(89),
+        ActivityRange::Year => today - chrono::Days::new(364),
+        ActivityRange::All => conn
+            .query_row("SELECT MIN(day) FROM days", [], |row| {
+                row.get::<_, Option<String>>(0)
+            })
+            .map_err(|e| e.to_string())?
+            .and_then(|day| chrono::NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok())
+            .unwrap_or(today)
+            .min(today),
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT day, commands, agent_sessions, files_changed, input_tokens, output_tokens,
+                 cache_read_tokens, cache_creation_tokens, cost_usd
+             FROM days WHERE day >= ?1 AND day <= ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            params![
+                from.format("%Y-%m-%d").to_string(),
+                today.format("%Y-%m-%d").to_string()
+            ],
+            |row| {
+                let n = |i: usize| row.get::<_, i64>(i).map(|v| v.max(0) as u64);
+                Ok((
+                    row.get::<_, String>(0)?,
+                    ActivityCounts {
+                        commands: n(1)?,
+                        agent_sessions: n(2)?,
+                        files_changed: n(3)?,
+                        input_tokens: n(4)?,
+                        output_tokens: n(5)?,
+                        cache_read_tokens: n(6)?,
+                        cache_creation_tokens: n(7)?,
+                        cost_usd: row.get(8)?,
+                    },
+                ))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    let mut stored: HashMap<String, ActivityCounts> = HashMap::new();
+    for row in rows {
+        let (day, counts) = row.map_err(|e| e.to_string())?;
+        stored.insert(day, counts);
+    }
+
+    let mut days = Vec::new();
+    let mut totals = ActivityCounts::default();
+    let mut date = from;
+    while date <= today {
+        let day = date.format("%Y-%m-%d").to_string();
+        let counts = stored.remove(&day).unwrap_or_default();
+        totals.add(&counts);
+        days.push(DayActivity { day, counts });
+        date = date + chrono::Days::new(1);
+    }
+    Ok(ActivitySummary { days, totals })
+}
+
+/// Fill the daily buckets from existing logs soon after launch, so the first dashboard view
+/// doesn't wait on a full backfill
+pub fn start(app: &tauri::App) {
+    use tauri::Manager;
+    let manager = app.state::<ActivityManager>().inner().clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(BACKFILL_DELAY);
+        crate::power::wait_for_full_power();
+        if let Err(e) = manager.refresh() {
+            tracing::warn!(error = %e, "Failed to refresh activity");
+        }
+    });
+}
+
+/// Commands run, agent sessions, files changed and token usage per day over `range`. Only
+/// logs written since the last call are read; the totals come from the daily buckets.
+#[tauri::command]
+pub async fn get_activity_summary(
+    state: tauri::State<'_, ActivityManager>,
+    range: ActivityRange,
+) -> Result<ActivitySummary, String> {
+    let manager = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        manager.refresh()?;
+        summary(range)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,536 @@
+use crate::agents::{AgentEvent, RunAgentOptions};
+use crate::sessions::TokenUsage;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// How a CLI can continue earlier work
+#[derive(Clone, Copy, Default, serde::Serialize)]
+pub struct ResumeSupport {
+    /// Resume a specific session by id
+    pub session: bool,
+    /// Continue the most recent session in the working directory
+    pub last: bool,
+}
+
+/// Token usage reported by one line of output, for budget accounting. Lines that repeat a
+/// `message_id` replace its earlier usage instead of adding to it.
+pub(crate) struct Usage {
+    pub message_id: String,
+    pub usage: TokenUsage,
+    pub model: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct Parsed {
+    pub events: Vec<AgentEvent>,
+    pub usage: Option<Usage>,
+}
+
+impl From<Vec<AgentEvent>> for Parsed {
+    fn from(events: Vec<AgentEvent>) -> Self {
+        Parsed {
+            events,
+            usage: None,
+        }
+    }
+}
+
+/// Output of a probe command such as `--help`, used where flags vary between CLI versions
+pub(crate) type Help<'a> = &'a dyn Fn(&[&str]) -> String;
+
+/// One agent CLI's headless mode: how to launch it, how to read what it prints and how it
+/// resumes. An adapter is created per run, so it can keep state across lines.
+pub(crate) trait AgentAdapter: Send {
+    fn resume(&self) -> ResumeSupport;
+
+    /// Arguments for running `prompt` non-interactively. The prompt goes after `--` or joined
+    /// to its flag with `=`, so one starting with `-`, like a bullet list, isn't read as options.
+    fn args(&mut self, prompt: &str, options: &RunAgentOptions, help: Help) -> Vec<String>;
+
+    /// Events for one line of stdout
+    fn parse_line(&mut self, line: &str) -> Parsed;
+
+    /// Arguments, after any `--model`, that start the CLI's terminal UI with `prompt` as the
+    /// first message; None when it can't take one
+    fn interactive_args(&self, _prompt: &str) -> Option<Vec<String>> {
+        None
+    }
+}
+
+/// The adapter for `tool`, or None when it has no headless mode we can drive
+pub(crate) fn adapter(tool: &str) -> Option<Box<dyn AgentAdapter>> {
+    Some(match tool {
+        "claude" | "cursor-agent" => Box::new(Claude::default()),
+        "codex" => Box::new(Codex::default()),
+        "aider" => Box::new(Text {
+            command: &["--no-pretty", "--no-stream"],
+            prompt_flag: Some("--message"),
+            resume_last: Some("--restore-chat-history"),
+        }),
+        "gemini" => Box::new(Gemini::default()),
+        "opencode" => Box::new(Text {
+            command: &["run"],
+            prompt_flag: None,
+            resume_last: None,
+        }),
+        "goose" => Box::new(Text {
+            command: &["run"],
+            prompt_flag: Some("--text"),
+            resume_last: None,
+        }),
+        _ => return None,
+    })
+}
+
+fn text_of(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).map(String::from)
+}
+
+fn plain_line(line: &str) -> Parsed {
+    vec![AgentEvent::Text {
+        text: format!("{}\n", line),
+    }]
+    .into()
+}
+
+fn push_model(args: &mut Vec<String>, options: &RunAgentOptions) {
+    if let Some(model) = &options.model {
+        args.extend(["--model".to_string(), model.clone()]);
+    }
+}
+
+/// Tool results are a string or a list of text blocks
+fn result_text(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(blocks)) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Claude Code's `--output-format stream-json`, which cursor-agent also speaks
+#[derive(Default)]
+struct Claude {
+    /// With partial messages enabled, text arrives as deltas and the complete assistant
+    /// message repeats it, so that copy is dropped
+    saw_deltas: bool,
+}
+
+impl AgentAdapter for Claude {
+    fn resume(&self) -> ResumeSupport {
+        ResumeSupport {
+            session: true,
+            last: true,
+        }
+    }
+
+    fn args(&mut self, prompt: &str, options: &RunAgentOptions, help: Help) -> Vec<String> {
+        let mut args: Vec<String> = ["-p", "--output-format", "stream-json", "--verbose"]
+            .map(String::from)
+            .into();
+        if help(&["--help"]).contains("--include-partial-messages") {
+            args.push("--include-partial-messages".to_string());
+        }
+        if let Some(session) = &options.session_id {
+            args.extend(["--resume".to_string(), session.clone()]);
+        } else if options.resume_last {
+            args.push("--continue".to_string());
+        }
+        push_model(&mut args, options);
+        args.extend(["--".to_string(), prompt.to_string()]);
+        args
+    }
+
+    fn parse_line(&mut self, line: &str) -> Parsed {
+        let Ok(line) = serde_json::from_str::<Value>(line) else {
+            return plain_line(line);
+        };
+        let mut events = Vec::new();
+        let mut usage = None;
+        match line.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+            "system" if text_of(&line, "subtype").as_deref() == Some("init") => {
+                events.push(AgentEvent::Started {
+                    session_id: text_of(&line, "session_id"),
+                    model: text_of(&line, "model"),
+                });
+            }
+            "stream_event" => {
+                let delta = line.pointer("/event/delta");
+                if let Some(text) = delta
+                    .filter(|d| d.get("type").and_then(|t| t.as_str()) == Some("text_delta"))
+                    .and_then(|d| text_of(d, "text"))
+                {
+                    self.saw_deltas = true;
+                    events.push(AgentEvent::Text { text });
+                }
+            }
+            "assistant" => {
+                let message = line.get("message");
+                if let Some((id, tokens)) =
+                    message.and_then(|m| Some((m.get("id")?.as_str()?, m.get("usage")?)))
+                {
+                    usage = Some(Usage {
+                        message_id: id.to_string(),
+                        usage: TokenUsage::from_json(tokens),
+                        model: message.and_then(|m| text_of(m, "model")),
+                    });
+                }
+                let blocks = line.pointer("/message/content").and_then(|c| c.as_array());
+                for block in blocks.into_iter().flatten() {
+                    match block.get("type").and_then(|t| t.as_str()) {
+                        Some("text") if !self.saw_deltas => {
+                            if let Some(text) = text_of(block, "text") {
+                                events.push(AgentEvent::Text { text });
+                            }
+                        }
+                        Some("tool_use") => events.push(AgentEvent::ToolCall {
+                            id: text_of(block, "id").unwrap_or_default(),
+                            name: text_of(block, "name").unwrap_or_default(),
+                            input: block.get("input").cloned().unwrap_or_default(),
+                        }),
+                        _ => {}
+                    }
+                }
+            }
+            "user" => {
+                let blocks = line.pointer("/message/content").and_then(|c| c.as_array());
+                for block in blocks.into_iter().flatten() {
+                    if block.get("type").and_then(|t| t.as_str()) == Some("tool_result") {
+                        events.push(AgentEvent::ToolResult {
+                            tool_use_id: text_of(block, "tool_use_id").unwrap_or_default(),
+                            content: result_text(block.get("content")),
+                            is_error: block
+                                .get("is_error")
+                                .and_then(|e| e.as_bool())
+                                .unwrap_or(false),
+                        });
+                    }
+                }
+            }
+            "result" => events.push(AgentEvent::Result {
+                text: text_of(&line, "result"),
+                session_id: text_of(&line, "session_id"),
+                is_error: line
+                    .get("is_error")
+                    .and_then(|e| e.as_bool())
+                    .unwrap_or(false),
+                cost_usd: line.get("total_cost_usd").and_then(|c| c.as_f64()),
+                duration_ms: line.get("duration_ms").and_then(|d| d.as_u64()),
+                num_turns: line.get("num_turns").and_then(|n| n.as_u64()),
+                usage: line.get("usage").map(TokenUsage::from_json),
+            }),
+            _ => {}
+        }
+        Parsed { events, usage }
+    }
+
+    fn interactive_args(&self, prompt: &str) -> Option<Vec<String>> {
+        Some(vec!["--".to_string(), prompt.to_string()])
+    }
+}
+
+/// `codex exec`, with `--json` event output on versions that have it
+#[derive(Default)]
+struct Codex {
+    json: bool,
+    thread_id: Option<String>,
+    last_message: Option<String>,
+    /// Items whose ToolCall has been sent, so completion only adds the result
+    started: HashSet<String>,
+    turns: u32,
+}
+
+impl Codex {
+    /// (tool name, input) of an item that stands for a tool call
+    fn tool_call(item: &Value) -> Option<(String, Value)> {
+        match item.get("type").and_then(|t| t.as_str())? {
+            "command_execution" => Some((
+                "Bash".to_string(),
+                serde_json::json!({ "command": item.get("command") }),
+            )),
+            "file_change" => Some((
+                "Edit".to_string(),
+                serde_json::json!({ "changes": item.get("changes") }),
+            )),
+            "mcp_tool_call" => Some((
+                format!(
+                    "{}/{}",
+                    text_of(item, "server").unwrap_or_default(),
+                    text_of(item, "tool").unwrap_or_default()
+                ),
+                item.get("arguments").cloned().unwrap_or_default(),
+            )),
+            "web_search" => Some((
+                "WebSearch".to_string(),
+                serde_json::json!({ "query": item.get("query") }),
+            )),
+            _ => None,
+        }
+    }
+
+    fn item_events(&mut self, kind: &str, item: &Value) -> Vec<AgentEvent> {
+        let id = text_of(item, "id").unwrap_or_default();
+        if item.get("type").and_then(|t| t.as_str()) == Some("agent_message") {
+            if kind != "item.completed" {
+                return Vec::new();
+            }
+            let text = text_of(item, "text").unwrap_or_default();
+            self.last_message = Some(text.clone());
+            return vec![AgentEvent::Text {
+                text: format!("{}\n", text),
+            }];
+        }
+        let Some((name, input)) = Self::tool_call(item) else {
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+        if self.started.insert(id.clone()) {
+            events.push(AgentEvent::ToolCall {
+                id: id.clone(),
+                name,
+                input,
+            });
+        }
+        if kind == "item.completed" {
+            let failed = text_of(item, "status").as_deref() == Some("failed")
+                || item
+                    .get("exit_code")
+                    .and_then(|c| c.as_i64())
+                    .is_some_and(|c| c != 0);
+            events.push(AgentEvent::ToolResult {
+                tool_use_id: id,
+                content: text_of(item, "aggregated_output").unwrap_or_default(),
+                is_error: failed,
+            });
+        }
+        events
+    }
+
+    fn result(
+        &self,
+        is_error: bool,
+        text: Option<String>,
+        usage: Option<TokenUsage>,
+    ) -> AgentEvent {
+        AgentEvent::Result {
+            text,
+            session_id: self.thread_id.clone(),
+            is_error,
+            cost_usd: None,
+            duration_ms: None,
+            num_turns: Some(self.turns as u64),
+            usage,
+        }
+    }
+}
+
+impl AgentAdapter for Codex {
+    fn resume(&self) -> ResumeSupport {
+        ResumeSupport {
+            session: true,
+            last: true,
+        }
+    }
+
+    fn args(&mut self, prompt: &str, options: &RunAgentOptions, help: Help) -> Vec<String> {
+        let mut args = vec!["exec".to_string()];
+        self.json = help(&["exec", "--help"]).contains("--json");
+        if self.json {
+            args.push("--json".to_string());
+        }
+        push_model(&mut args, options);
+        if let Some(session) = &options.session_id {
+            args.extend(["resume".to_string(), session.clone()]);
+        } else if options.resume_last {
+            args.extend(["resume", "--last"].map(String::from));
+        }
+        args.extend(["--".to_string(), prompt.to_string()]);
+        args
+    }
+
+    fn parse_line(&mut self, line: &str) -> Parsed {
+        let json = if self.json {
+            serde_json::from_str::<Value>(line).ok()
+        } else {
+            None
+        };
+        let Some(line) = json else {
+            return plain_line(line);
+        };
+        let event_type = text_of(&line, "type").unwrap_or_default();
+        match event_type.as_str() {
+            "thread.started" => {
+                self.thread_id = text_of(&line, "thread_id");
+                vec![AgentEvent::Started {
+                    session_id: self.thread_id.clone(),
+                    model: None,
+                }]
+                .into()
+            }
+            "turn.started" => {
+                self.turns += 1;
+                Parsed::default()
+            }
+            "item.started" | "item.updated" | "item.completed" => match line.get("item") {
+                Some(item) => self.item_events(&event_type, item).into(),
+                None => Parsed::default(),
+            },
+            "turn.completed" => {
+                let usage = line.get("usage").map(|u| TokenUsage {
+                    // Codex counts cached tokens inside input_tokens
+                    input: u["input_tokens"]
+                        .as_u64()
+                        .unwrap_or(0)
+                        .saturating_sub(u["cached_input_tokens"].as_u64().unwrap_or(0)),
+                    output: u["output_tokens"].as_u64().unwrap_or(0),
+                    cache_read: u["cached_input_tokens"].as_u64().unwrap_or(0),
+                    cache_creation: 0,
+                });
+                Parsed {
+                    events: vec![self.result(false, self.last_message.clone(), usage)],
+                    usage: usage.map(|usage| Usage {
+                        message_id: format!("turn-{}", self.turns),
+                        usage,
+                        model: None,
+                    }),
+                }
+            }
+            "turn.failed" | "error" => {
+                let message = line
+                    .pointer("/error/message")
+                    .or_else(|| line.get("message"))
+                    .and_then(|m| m.as_str())
+                    .map(String::from);
+                vec![self.result(true, message, None)].into()
+            }
+            _ => Parsed::default(),
+        }
+    }
+
+    fn interactive_args(&self, prompt: &str) -> Option<Vec<String>> {
+        Some(vec!["--".to_string(), prompt.to_string()])
+    }
+}
+
+/// Gemini CLI, with `--output-format stream-json` on versions that have it
+#[derive(Default)]
+struct Gemini {
+    stream_json: bool,
+}
+
+impl AgentAdapter for Gemini {
+    fn resume(&self) -> ResumeSupport {
+        ResumeSupport::default()
+    }
+
+    fn args(&mut self, prompt: &str, options: &RunAgentOptions, help: Help) -> Vec<String> {
+        let mut args = vec![format!("--prompt={}", prompt)];
+        self.stream_json = help(&["--help"]).contains("stream-json");
+        if self.stream_json {
+            args.extend(["--output-format", "stream-json"].map(String::from));
+        }
+        push_model(&mut args, options);
+        args
+    }
+
+    fn parse_line(&mut self, line: &str) -> Parsed {
+        let json = if self.stream_json {
+            serde_json::from_str::<Value>(line).ok()
+        } else {
+            None
+        };
+        let Some(line) = json else {
+            return plain_line(line);
+        };
+        let event = match line.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+            "init" => AgentEvent::Started {
+                session_id: text_of(&line, "session_id"),
+                model: text_of(&line, "model"),
+            },
+            "message" if text_of(&line, "role").as_deref() == Some("assistant") => {
+                AgentEvent::Text {
+                    text: text_of(&line, "content").unwrap_or_default(),
+                }
+            }
+            "tool_use" => AgentEvent::ToolCall {
+                id: text_of(&line, "tool_id").unwrap_or_default(),
+                name: text_of(&line, "tool_name").unwrap_or_default(),
+                input: line.get("parameters").cloned().unwrap_or_default(),
+            },
+            "tool_result" => AgentEvent::ToolResult {
+                tool_use_id: text_of(&line, "tool_id").unwrap_or_default(),
+                content: text_of(&line, "output").unwrap_or_default(),
+                is_error: text_of(&line, "status").as_deref() == Some("error"),
+            },
+            "result" => {
+                let stats = line.get("stats");
+                let usage = stats.map(TokenUsage::from_json);
+                // Gemini only reports usage for the whole run, at the end
+                return Parsed {
+                    usage: usage.map(|usage| Usage {
+                        message_id: "result".to_string(),
+                        usage,
+                        model: None,
+                    }),
+                    events: vec![AgentEvent::Result {
+                        text: None,
+                        session_id: None,
+                        is_error: text_of(&line, "status").as_deref() != Some("success"),
+                        cost_usd: None,
+                        duration_ms: stats
+                            .and_then(|s| s.get("duration_ms"))
+                            .and_then(|d| d.as_u64()),
+                        num_turns: None,
+                        usage,
+                    }],
+                };
+            }
+            _ => return Parsed::default(),
+        };
+        vec![event].into()
+    }
+
+    fn interactive_args(&self, prompt: &str) -> Option<Vec<String>> {
+        Some(vec![format!("--prompt-interactive={}", prompt)])
+    }
+}
+
+/// CLIs whose headless output is plain text (aider, opencode, goose), forwarded line by line
+struct Text {
+    /// Subcommand and flags
+    command: &'static [&'static str],
+    /// Flag taking the prompt; without one it is the last argument, after `--`
+    prompt_flag: Option<&'static str>,
+    /// Flag that continues the chat kept in the working directory
+    resume_last: Option<&'static str>,
+}
+
+impl AgentAdapter for Text {
+    fn resume(&self) -> ResumeSupport {
+        ResumeSupport {
+            session: false,
+            last: self.resume_last.is_some(),
+        }
+    }
+
+    fn args(&mut self, prompt: &str, options: &RunAgentOptions, _help: Help) -> Vec<String> {
+        let mut args: Vec<String> = self.command.iter().map(|a| a.to_string()).collect();
+        if let (true, Some(flag)) = (options.resume_last, self.resume_last) {
+            args.push(flag.to_string());
+        }
+        push_model(&mut args, options);
+        match self.prompt_flag {
+            Some(flag) => args.push(format!("{}={}", flag, prompt)),
+            None => args.extend(["--".to_string(), prompt.to_string()]),
+        }
+        args
+    }
+
+    fn parse_line(&mut self, line: &str) -> Parsed {
+        plain_line(line)
+    }
+}
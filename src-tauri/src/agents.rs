@@ -0,0 +1,482 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::ipc::Channel;
+
+/// `--version`/`--help` of a node-based CLI can take a few seconds on a cold start
+const PROBE_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// (id, display name, help-text marker of a non-interactive mode)
+const AGENT_TOOLS: [(&str, &str, &str); 8] = [
+    ("claude", "Claude Code", "--print"),
+    ("codex", "Codex CLI", "exec"),
+    ("gemini", "Gemini CLI", "--prompt"),
+    ("aider", "Aider", "--message"),
+    ("opencode", "OpenCode", "run"),
+    ("cursor-agent", "Cursor Agent", "--print"),
+    ("goose", "Goose", "run"),
+    ("ollama", "Ollama", "run"),
+];
+
+#[derive(serde::Serialize)]
+pub struct AgentTool {
+    id: String,
+    name: String,
+    installed: bool,
+    path: Option<String>,
+    version: Option<String>,
+    /// Can take a prompt and exit without a terminal UI
+    headless: bool,
+    /// Supports `--output-format stream-json`
+    stream_json: bool,
+    /// How headless runs can continue earlier work
+    resume: crate::agent_adapters::ResumeSupport,
+    /// Every long option listed in `--help`
+    flags: Vec<String>,
+}
+
+/// Combined stdout+stderr of a short-lived command, or None if it fails to start or hangs
+fn probe_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut child = std::process::Command::new(program)
+        .args(args)
+        .env("NO_COLOR", "1")
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .ok()?;
+    let mut stdout = child.stdout.take()?;
+    let mut stderr = child.stderr.take()?;
+    // Read on threads so a chatty process can't block on a full pipe while we wait
+    let out = std::thread::spawn(move || {
+        let mut buf = String::new();
+        let _ = stdout.read_to_string(&mut buf);
+        buf
+    });
+    let err = std::thread::spawn(move || {
+        let mut buf = String::new();
+        let _ = stderr.read_to_string(&mut buf);
+        buf
+    });
+    let deadline = Instant::now() + PROBE_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(20)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+    let mut text = out.join().unwrap_or_default();
+    text.push_str(&err.join().unwrap_or_default());
+    Some(text)
+}
+
+/// Version a program reports for `--version`
+pub(crate) fn probe_version(program: &str) -> Option<String> {
+    let out = probe_output(program, &["--version"])?;
+    let line = out
+        .lines()
+        .find(|l| !l.trim().is_empty())?
+        .trim()
+        .to_string();
+    // "1.0.3 (Claude Code)", "codex-cli 0.20.0", "ollama version is 0.3.1" → first dotted number
+    let re = regex::Regex::new(r"\d+\.\d+(?:\.\d+)?(?:[-+][0-9A-Za-z.-]+)?").unwrap();
+    Some(
+        re.find(&line)
+            .map(|m| m.as_str().to_string())
+            .unwrap_or(line),
+    )
+}
+
+fn long_flags(help: &str) -> Vec<String> {
+    let re = regex::Regex::new(r"--[a-z][a-z0-9-]*[a-z0-9]").unwrap();
+    let mut flags: Vec<String> = re.find_iter(help).map(|m| m.as_str().to_string()).collect();
+    flags.sort();
+    flags.dedup();
+    flags
+}
+
+fn probe(id: &str, name: &str, headless_marker: &str) -> AgentTool {
+    let mut tool = AgentTool {
+        id: id.to_string(),
+        name: name.to_string(),
+        installed: false,
+        path: None,
+        version: None,
+        headless: false,
+        stream_json: false,
+        resume: crate::agent_adapters::adapter(id)
+            .map(|a| a.resume())
+            .unwrap_or_default(),
+        flags: Vec::new(),
+    };
+    let Ok(path) = crate::check_command_exists(id.to_string()) else {
+        return tool;
+    };
+    tool.installed = true;
+    tool.version = probe_version(&path);
+    if let Some(help) = probe_output(&path, &["--help"]) {
+        tool.flags = long_flags(&help);
+        tool.headless = if headless_marker.starts_with("--") {
+            tool.flags.iter().any(|f| f == headless_marker)
+        } else {
+            // Subcommands are listed at the start of a help line
+            help.lines()
+                .any(|l| l.split_whitespace().next() == Some(headless_marker))
+        };
+        tool.stream_json = help.contains("stream-json");
+    }
+    tool.path = Some(path);
+    tool
+}
+
+/// Probe every known agent CLI in parallel
+#[tauri::command]
+pub async fn detect_agent_tools() -> Result<Vec<AgentTool>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let handles: Vec<_> = AGENT_TOOLS
+            .iter()
+            .map(|&(id, name, marker)| std::thread::spawn(move || probe(id, name, marker)))
+            .collect();
+        Ok(handles.into_iter().filter_map(|h| h.join().ok()).collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct RunAgentOptions {
+    pub(crate) cwd: Option<String>,
+    pub(crate) model: Option<String>,
+    /// Continue an earlier session (tools whose `resume.session` is set)
+    pub(crate) session_id: Option<String>,
+    /// Continue the most recent session in `cwd` (tools whose `resume.last` is set)
+    #[serde(default)]
+    pub(crate) resume_last: bool,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum AgentEvent {
+    #[serde(rename = "started")]
+    Started {
+        session_id: Option<String>,
+        model: Option<String>,
+    },
+    /// Incremental assistant text
+    #[serde(rename = "text")]
+    Text { text: String },
+    #[serde(rename = "tool_call")]
+    ToolCall {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    #[serde(rename = "tool_result")]
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        is_error: bool,
+    },
+    /// Final event of a run
+    #[serde(rename = "result")]
+    Result {
+        text: Option<String>,
+        session_id: Option<String>,
+        is_error: bool,
+        cost_usd: Option<f64>,
+        duration_ms: Option<u64>,
+        num_turns: Option<u64>,
+        usage: Option<crate::sessions::TokenUsage>,
+    },
+    #[serde(rename = "stderr")]
+    Stderr { text: String },
+    /// The run went over a budget and is paused until resume_budget_run or stop_budget_run
+    #[serde(rename = "budget_exceeded")]
+    BudgetExceeded(crate::budget::BudgetExceeded),
+    #[serde(rename = "exit")]
+    Exit { code: Option<i32> },
+}
+
+#[derive(Clone)]
+pub struct AgentRunManager {
+    runs: Arc<Mutex<HashMap<u32, std::process::Child>>>,
+    /// Runs stopped by pause_all, to be continued by resume_all
+    paused: Arc<Mutex<Vec<u32>>>,
+    next_id: Arc<Mutex<u32>>,
+    budget: crate::budget::BudgetManager,
+}
+
+impl AgentRunManager {
+    pub fn new(budget: crate::budget::BudgetManager) -> Self {
+        Self {
+            runs: Arc::new(Mutex::new(HashMap::new())),
+            paused: Arc::new(Mutex::new(Vec::new())),
+            next_id: Arc::new(Mutex::new(1)),
+            budget,
+        }
+    }
+}
+
+/// Receives a headless run's events; the last one is always `exit`
+pub(crate) type AgentEventSink = Arc<dyn Fn(AgentEvent) + Send + Sync>;
+
+impl AgentRunManager {
+    /// Spawn a headless agent run and pass its events to `on_event` from reader threads.
+    /// Shared by run_agent and the worktree orchestrator.
+    pub(crate) fn start(
+        &self,
+        tracker: crate::session_changes::SessionChangeTracker,
+        tool: &str,
+        prompt: &str,
+        options: RunAgentOptions,
+        on_event: AgentEventSink,
+    ) -> Result<u32, String> {
+        let mut adapter = crate::agent_adapters::adapter(tool)
+            .ok_or_else(|| format!("Headless runs are not supported for {}", tool))?;
+        let resume = adapter.resume();
+        if options.session_id.is_some() && !resume.session {
+            return Err(format!("{} can't resume a session by id", tool));
+        }
+        if options.resume_last && !resume.last {
+            return Err(format!("{} can't continue its last session", tool));
+        }
+        let path = crate::check_command_exists(tool.to_string())
+            .map_err(|_| format!("{} is not installed", tool))?;
+        let args = adapter.args(prompt, &options, &|probe_args| {
+            probe_output(&path, probe_args).unwrap_or_default()
+        });
+
+        let id = {
+            let mut next = self.next_id.lock().unwrap();
+            let id = *next;
+            *next += 1;
+            id
+        };
+        // Snapshot before the agent starts so all of its edits are attributed. New sessions get
+        // their id from the first event, so they are tracked under the run id until then.
+        let mut tracking_key = options
+            .session_id
+            .clone()
+            .unwrap_or_else(|| format!("run-{}", id));
+        let tracked = match &options.cwd {
+            Some(cwd) => tracker.begin(&tracking_key, cwd).is_ok(),
+            None => false,
+        };
+        if let Some(cwd) = &options.cwd {
+            // Outside a git repo there is nothing to review against
+            let _ = crate::review::begin(cwd);
+        }
+
+        let mut cmd = std::process::Command::new(&path);
+        cmd.args(&args)
+            .env("NO_COLOR", "1")
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        if let Some(cwd) = &options.cwd {
+            cmd.current_dir(crate::git::resolve_repo(cwd));
+        }
+        crate::secrets::apply_agent_env(&mut cmd);
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                tracker.discard(&tracking_key);
+                return Err(format!("Failed to start {}: {}", tool, e));
+            }
+        };
+        crate::activity::record_agent_run(tool);
+        let stdout = child.stdout.take().unwrap();
+        let stderr = child.stderr.take().unwrap();
+        let budget_key = format!("run-{}", id);
+        let label: String = prompt
+            .lines()
+            .next()
+            .unwrap_or("")
+            .chars()
+            .take(60)
+            .collect();
+        self.budget.track_process(
+            &budget_key,
+            &format!("{}: {}", tool, label),
+            child.id(),
+            on_event.clone(),
+        );
+        self.runs.lock().unwrap().insert(id, child);
+
+        let sink = on_event.clone();
+        let stderr_reader = std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if !line.trim().is_empty() {
+                    sink(AgentEvent::Stderr { text: line });
+                }
+            }
+        });
+
+        let runs = self.runs.clone();
+        let paused = self.paused.clone();
+        let budget = self.budget.clone();
+        crate::crash::spawn("agents", format!("agent:{}", id), move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let parsed = adapter.parse_line(&line);
+                if let Some(usage) = &parsed.usage {
+                    budget.record(
+                        &budget_key,
+                        &usage.message_id,
+                        &usage.usage,
+                        usage.model.as_deref(),
+                    );
+                }
+                for event in parsed.events {
+                    if let AgentEvent::Started {
+                        session_id: Some(session_id),
+                        ..
+                    }
+                    | AgentEvent::Result {
+                        session_id: Some(session_id),
+                        ..
+                    } = &event
+                    {
+                        if tracked && *session_id != tracking_key {
+                            tracker.rename(&tracking_key, session_id);
+                            tracking_key = session_id.clone();
+                        }
+                    }
+                    if let AgentEvent::Result {
+                        cost_usd: Some(cost_usd),
+                        ..
+                    } = &event
+                    {
+                        budget.settle(&budget_key, *cost_usd);
+                    }
+                    on_event(event);
+                }
+            }
+            let _ = stderr_reader.join();
+            budget.finish(&budget_key);
+            let child = runs.lock().unwrap().remove(&id);
+            paused.lock().unwrap().retain(|run| *run != id);
+            let code = child.and_then(|mut c| c.wait().ok()).and_then(|s| s.code());
+            if tracked {
+                // A run that never reported a session id has nothing to key its changes on
+                if tracking_key.starts_with("run-") {
+                    tracker.discard(&tracking_key);
+                } else {
+                    tracker.finish(&tracking_key);
+                }
+            }
+            on_event(AgentEvent::Exit { code });
+            Ok(())
+        });
+
+        Ok(id)
+    }
+
+    /// (running, of which paused) headless runs
+    pub(crate) fn counts(&self) -> (usize, usize) {
+        let running = self.runs.lock().unwrap().len();
+        (running, self.paused.lock().unwrap().len())
+    }
+
+    /// Stop every running headless agent with SIGSTOP, leaving alone those a budget already
+    /// paused. Returns how many were paused.
+    pub(crate) fn pause_all(&self) -> Result<usize, String> {
+        let runs = self.runs.lock().unwrap();
+        let mut paused = self.paused.lock().unwrap();
+        let mut count = 0;
+        for (id, child) in runs.iter() {
+            if paused.contains(id) || self.budget.run_paused(&format!("run-{}", id)) {
+                continue;
+            }
+            crate::budget::signal(child.id(), "STOP")?;
+            paused.push(*id);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Continue the runs pause_all stopped. Returns how many were resumed.
+    pub(crate) fn resume_all(&self) -> Result<usize, String> {
+        let runs = self.runs.lock().unwrap();
+        let mut paused = self.paused.lock().unwrap();
+        let mut count = 0;
+        for id in paused.drain(..) {
+            if let Some(child) = runs.get(&id) {
+                crate::budget::signal(child.id(), "CONT")?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    pub(crate) fn cancel(&self, id: u32) -> Result<(), String> {
+        if let Some(child) = self.runs.lock().unwrap().get_mut(&id) {
+            child
+                .kill()
+                .map_err(|e| format!("Failed to stop agent: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Run the agent CLI `tool` (an id from detect_agent_tools) non-interactively and stream its
+/// output as the same typed events whichever CLI it is. Returns a run id for cancel_agent; the
+/// last event is always `exit`.
+#[tauri::command]
+pub async fn run_agent(
+    state: tauri::State<'_, AgentRunManager>,
+    changes: tauri::State<'_, crate::session_changes::SessionChangeManager>,
+    tool: String,
+    prompt: String,
+    options: Option<RunAgentOptions>,
+    on_event: Channel<AgentEvent>,
+) -> Result<u32, String> {
+    let manager = state.inner().clone();
+    let tracker = changes.tracker();
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut options = options.unwrap_or_default();
+        if options.model.is_none() {
+            let root = options
+                .cwd
+                .as_deref()
+                .and_then(crate::project_settings::project_root_for);
+            options.model = crate::project_settings::load(root.as_deref())
+                .agent
+                .models
+                .remove(&tool);
+        }
+        manager.start(
+            tracker,
+            &tool,
+            &prompt,
+            options,
+            Arc::new(move |event| {
+                let _ = on_event.send(event);
+            }),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn cancel_agent(state: tauri::State<'_, AgentRunManager>, id: u32) -> Result<(), String> {
+    state.cancel(id)
+}
+
+/// Pause every running headless agent; returns how many were paused
+#[tauri::command]
+pub fn pause_all_agents(state: tauri::State<'_, AgentRunManager>) -> Result<usize, String> {
+    state.pause_all()
+}
+
+/// Continue the agents pause_all_agents paused; returns how many were resumed
+#[tauri::command]
+pub fn resume_all_agents(state: tauri::State<'_, AgentRunManager>) -> Result<usize, String> {
+    state.resume_all()
+}
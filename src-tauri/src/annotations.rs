@@ -0,0 +1,125 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+
+const DEFAULT_TAGS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+const MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+#[derive(serde::Serialize)]
+pub struct Annotation {
+    path: String,
+    line: u32,
+    column: u32,
+    tag: String,
+    /// Owner from `TODO(name):` style annotations
+    owner: Option<String>,
+    text: String,
+    blame: Option<crate::git::BlameLine>,
+}
+
+fn build_pattern(tags: &[String]) -> Result<Regex, String> {
+    let alternation = tags
+        .iter()
+        .map(|t| regex::escape(t))
+        .collect::<Vec<_>>()
+        .join("|");
+    // Only match tags that follow a comment marker, so identifiers like `TODO_LIST` don't count
+    Regex::new(&format!(
+        r"(?://+|#+|/\*+|^\s*\*|--|<!--|;+)\s*\b({})\b(?:\(([^)]*)\))?:?\s*(.*)",
+        alternation
+    ))
+    .map_err(|e| format!("Invalid tag pattern: {}", e))
+}
+
+fn clean_text(text: &str) -> String {
+    text.trim()
+        .trim_end_matches("*/")
+        .trim_end_matches("-->")
+        .trim()
+        .to_string()
+}
+
+#[tauri::command]
+pub async fn scan_annotations(
+    root: String,
+    tags: Option<Vec<String>>,
+    with_blame: Option<bool>,
+    limit: Option<usize>,
+) -> Result<Vec<Annotation>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root_path = Path::new(&root);
+        if !root_path.is_dir() {
+            return Err(format!("Not a directory: {}", root));
+        }
+        let tags = tags
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| DEFAULT_TAGS.iter().map(|t| t.to_string()).collect());
+        let pattern = build_pattern(&tags)?;
+        let limit = limit.unwrap_or(5000);
+
+        let mut annotations: Vec<Annotation> = Vec::new();
+        // Same walker ripgrep uses: honours .gitignore, .ignore and global git excludes
+        let walker = ignore::WalkBuilder::new(root_path).build();
+        for entry in walker.flatten() {
+            if !entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+                continue;
+            }
+            let path = entry.path();
+            if entry
+                .metadata()
+                .map(|m| m.len() > MAX_FILE_SIZE)
+                .unwrap_or(true)
+            {
+                continue;
+            }
+            let content = match std::fs::read_to_string(path) {
+                Ok(c) => c,
+                Err(_) => continue, // binary or unreadable
+            };
+
+            let mut found: Vec<Annotation> = Vec::new();
+            for (i, line) in content.lines().enumerate() {
+                if let Some(caps) = pattern.captures(line) {
+                    let tag = caps.get(1).unwrap();
+                    found.push(Annotation {
+                        path: path.to_string_lossy().to_string(),
+                        line: i as u32 + 1,
+                        column: line[..tag.start()].chars().count() as u32 + 1,
+                        tag: tag.as_str().to_string(),
+                        owner: caps.get(2).map(|m| m.as_str().trim().to_string()),
+                        text: caps
+                            .get(3)
+                            .map(|m| clean_text(m.as_str()))
+                            .unwrap_or_default(),
+                        blame: None,
+                    });
+                    if annotations.len() + found.len() >= limit {
+                        break;
+                    }
+                }
+            }
+            if found.is_empty() {
+                continue;
+            }
+
+            if with_blame.unwrap_or(false) {
+                let ranges: Vec<(u32, u32)> = found.iter().map(|a| (a.line, a.line)).collect();
+                // Untracked files or a non-git root simply get no blame
+                let blame: HashMap<u32, crate::git::BlameLine> =
+                    crate::git::blame(&root, &path.to_string_lossy(), &ranges)
+                        .map(|lines| lines.into_iter().map(|b| (b.line, b)).collect())
+                        .unwrap_or_default();
+                for annotation in &mut found {
+                    annotation.blame = blame.get(&annotation.line).cloned();
+                }
+            }
+            annotations.extend(found);
+            if annotations.len() >= limit {
+                break;
+            }
+        }
+        Ok(annotations)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,288 @@
+//! Files dropped onto the window, turned into references a prompt can include. Dropped files are
+//! checked, typed by their content (falling back to the extension), and copied to
+//! ~/.ade/attachments or the project's .ade/attachments so they outlive a Downloads cleanup.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_MAX_SIZE: u64 = 20 * 1024 * 1024;
+/// Bytes read to detect the type
+const SNIFF_BYTES: usize = 8192;
+
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyInto {
+    /// ~/.ade/attachments
+    #[default]
+    Attachments,
+    /// <root>/.ade/attachments, so agents in the project can read them
+    Project,
+    /// Reference the files where they are
+    None,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct IngestOptions {
+    copy_into: Option<CopyInto>,
+    /// The project, required to copy into it; files already inside are referenced in place
+    root: Option<String>,
+    /// Bytes; larger files are rejected (20 MB by default)
+    max_size: Option<u64>,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentKind {
+    Image,
+    /// PDFs and office documents
+    Document,
+    /// Source code and other plain text
+    Text,
+    Directory,
+    Other,
+}
+
+#[derive(serde::Serialize)]
+pub struct Attachment {
+    name: String,
+    /// Where it was dropped from
+    original_path: String,
+    /// Where it is now, after any copy
+    path: String,
+    /// Relative to the project root, when it's inside the project
+    relative_path: Option<String>,
+    mime: String,
+    kind: AttachmentKind,
+    size: u64,
+    /// What to insert into a prompt: "@relative/path" inside the project, else the absolute path
+    reference: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct RejectedFile {
+    path: String,
+    reason: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct IngestResult {
+    accepted: Vec<Attachment>,
+    rejected: Vec<RejectedFile>,
+}
+
+/// (mime, kind) from the leading bytes of a file, for formats with a signature
+fn sniff(head: &[u8]) -> Option<(&'static str, AttachmentKind)> {
+    let signatures: &[(&[u8], &str, AttachmentKind)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png", AttachmentKind::Image),
+        (b"\xff\xd8\xff", "image/jpeg", AttachmentKind::Image),
+        (b"GIF87a", "image/gif", AttachmentKind::Image),
+        (b"GIF89a", "image/gif", AttachmentKind::Image),
+        (b"%PDF-", "application/pdf", AttachmentKind::Document),
+        (b"PK\x03\x04", "application/zip", AttachmentKind::Other),
+        (b"\x1f\x8b", "application/gzip", AttachmentKind::Other),
+    ];
+    if head.len() >= 12 && &head[..4] == b"RIFF" && &head[8..12] == b"WEBP" {
+        return Some(("image/webp", AttachmentKind::Image));
+    }
+    signatures
+        .iter()
+        .find(|(magic, _, _)| head.starts_with(magic))
+        .map(|(_, mime, kind)| (*mime, *kind))
+}
+
+/// (mime, kind) from the extension
+fn by_extension(path: &Path) -> Option<(&'static str, AttachmentKind)> {
+    let ext = path.extension()?.to_string_lossy().to_ascii_lowercase();
+    let found = match ext.as_str() {
+        "png" => ("image/png", AttachmentKind::Image),
+        "jpg" | "jpeg" => ("image/jpeg", AttachmentKind::Image),
+        "gif" => ("image/gif", AttachmentKind::Image),
+        "webp" => ("image/webp", AttachmentKind::Image),
+        "svg" => ("image/svg+xml", AttachmentKind::Image),
+        "pdf" => ("application/pdf", AttachmentKind::Document),
+        "docx" => (
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            AttachmentKind::Document,
+        ),
+        "xlsx" => (
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            AttachmentKind::Document,
+        ),
+        "pptx" => (
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+            AttachmentKind::Document,
+        ),
+        "md" | "markdown" => ("text/markdown", AttachmentKind::Text),
+        "json" => ("application/json", AttachmentKind::Text),
+        "csv" => ("text/csv", AttachmentKind::Text),
+        "html" | "htm" => ("text/html", AttachmentKind::Text),
+        _ => return None,
+    };
+    Some(found)
+}
+
+fn detect(path: &Path) -> Result<(String, AttachmentKind), String> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    std::fs::File::open(path)
+        .and_then(|file| file.take(SNIFF_BYTES as u64).read_to_end(&mut head))
+        .map_err(|e| format!("Failed to read: {}", e))?;
+    // Office documents are zips underneath, so the extension is more telling
+    let (mime, kind) = match (sniff(&head), by_extension(path)) {
+        (Some((_, AttachmentKind::Other)), Some(found)) => found,
+        (Some(found), _) | (None, Some(found)) => found,
+        (None, None) => match !head.contains(&0) && std::str::from_utf8(&head).is_ok() {
+            true => ("text/plain", AttachmentKind::Text),
+            false => ("application/octet-stream", AttachmentKind::Other),
+        },
+    };
+    Ok((mime.to_string(), kind))
+}
+
+fn human_size(bytes: u64) -> String {
+    const MB: u64 = 1024 * 1024;
+    match bytes {
+        b if b >= MB => format!("{:.1} MB", b as f64 / MB as f64),
+        b if b >= 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{} bytes", b),
+    }
+}
+
+/// `dir/name`, or `dir/stem (2).ext` and so on when that's taken
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let path = Path::new(name);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (2..)
+        .map(|n| dir.join(format!("{} ({}){}", stem, n, ext)))
+        .find(|candidate| !candidate.exists())
+        .unwrap()
+}
+
+/// Whether two files have the same size and contents, to skip copying one twice
+fn same_file(a: &Path, b: &Path) -> bool {
+    let size = |p: &Path| std::fs::metadata(p).map(|m| m.len()).ok();
+    size(a).is_some() && size(a) == size(b) && std::fs::read(a).ok() == std::fs::read(b).ok()
+}
+
+fn ingest(
+    original: &str,
+    copy_into: CopyInto,
+    root: Option<&Path>,
+    max_size: u64,
+) -> Result<Attachment, String> {
+    let source = std::fs::canonicalize(original).map_err(|_| "Not found".to_string())?;
+    let metadata = std::fs::metadata(&source).map_err(|e| format!("Failed to read: {}", e))?;
+    let name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("Not a file")?;
+    let inside_root = |path: &Path| {
+        root.and_then(|root| {
+            path.strip_prefix(root)
+                .ok()
+                .map(|p| p.to_string_lossy().to_string())
+        })
+    };
+
+    let (path, mime, kind, size) = if metadata.is_dir() {
+        (
+            source,
+            "inode/directory".to_string(),
+            AttachmentKind::Directory,
+            0,
+        )
+    } else if metadata.is_file() {
+        if metadata.len() > max_size {
+            return Err(format!("Larger than {}", human_size(max_size)));
+        }
+        let (mime, kind) = detect(&source)?;
+        let dir = match copy_into {
+            _ if inside_root(&source).is_some() => None,
+            CopyInto::Attachments => Some(crate::ade_path("attachments")),
+            CopyInto::Project => root.map(|root| root.join(".ade").join("attachments")),
+            CopyInto::None => None,
+        };
+        let path = match dir {
+            Some(dir) => {
+                std::fs::create_dir_all(&dir)
+                    .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+                let existing = dir.join(&name);
+                match same_file(&source, &existing) {
+                    true => existing,
+                    false => {
+                        let target = unique_path(&dir, &name);
+                        std::fs::copy(&source, &target)
+                            .map_err(|e| format!("Failed to copy: {}", e))?;
+                        target
+                    }
+                }
+            }
+            None => source,
+        };
+        (path, mime, kind, metadata.len())
+    } else {
+        return Err("Not a regular file".to_string());
+    };
+
+    let relative_path = inside_root(&path);
+    let path = path.to_string_lossy().to_string();
+    Ok(Attachment {
+        name,
+        original_path: original.to_string(),
+        reference: match &relative_path {
+            Some(relative) => format!("@{}", relative),
+            None => path.clone(),
+        },
+        path,
+        relative_path,
+        mime,
+        kind,
+        size,
+    })
+}
+
+/// Check and copy dropped files, returning a reference for each one accepted and the reason
+/// for each one rejected
+#[tauri::command]
+pub async fn ingest_dropped_files(
+    paths: Vec<String>,
+    options: Option<IngestOptions>,
+) -> Result<IngestResult, String> {
+    let options = options.unwrap_or_default();
+    let copy_into = options.copy_into.unwrap_or_default();
+    let root = options
+        .root
+        .map(|root| {
+            std::fs::canonicalize(&root).map_err(|e| format!("Failed to open {}: {}", root, e))
+        })
+        .transpose()?;
+    if copy_into == CopyInto::Project && root.is_none() {
+        return Err("Copying into the project needs its root".to_string());
+    }
+    let max_size = options.max_size.unwrap_or(DEFAULT_MAX_SIZE);
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut result = IngestResult {
+            accepted: Vec::new(),
+            rejected: Vec::new(),
+        };
+        for path in paths {
+            match ingest(&path, copy_into, root.as_deref(), max_size) {
+                Ok(attachment) => result.accepted.push(attachment),
+                Err(reason) => result.rejected.push(RejectedFile { path, reason }),
+            }
+        }
+        result
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,21 @@
+use std::io::Write;
+
+/// Append-only record of destructive actions, one JSON object per line in ~/.ade/audit.jsonl
+pub(crate) fn record(action: &str, details: serde_json::Value) {
+    let path = crate::ade_path("audit.jsonl");
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let entry = serde_json::json!({
+        "at": crate::now_ms(),
+        "action": action,
+        "details": details,
+    });
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+    {
+        let _ = writeln!(file, "{}", entry);
+    }
+}
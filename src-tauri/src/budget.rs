@@ -0,0 +1,646 @@
+use crate::agents::{AgentEvent, AgentEventSink};
+use crate::sessions::TokenUsage;
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter};
+
+/// How often watched transcripts are read for new usage
+const POLL: Duration = Duration::from_secs(2);
+/// A transcript written this long before the watch began still counts as the session's
+const TRANSCRIPT_SLACK: Duration = Duration::from_secs(5);
+
+/// Zero or more ceilings; a run stops at whichever it reaches first
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+pub struct Limit {
+    max_tokens: Option<u64>,
+    max_cost_usd: Option<f64>,
+}
+
+impl Limit {
+    fn exceeded_by(&self, spend: &Spend) -> bool {
+        self.max_tokens.is_some_and(|max| spend.tokens >= max)
+            || self.max_cost_usd.is_some_and(|max| spend.cost_usd >= max)
+    }
+
+    /// Room for one more `step` past what has been spent
+    fn extend(&self, spend: &Spend, step: &Limit) -> Limit {
+        Limit {
+            max_tokens: self
+                .max_tokens
+                .map(|max| max.max(spend.tokens) + step.max_tokens.unwrap_or(max)),
+            max_cost_usd: self
+                .max_cost_usd
+                .map(|max| max.max(spend.cost_usd) + step.max_cost_usd.unwrap_or(max)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+pub struct Budgets {
+    #[serde(default)]
+    per_task: Limit,
+    #[serde(default)]
+    per_day: Limit,
+}
+
+/// Tokens exclude cache reads, which are cheap and would dominate any token ceiling. Cost is
+/// estimated from list prices until a headless run reports its real total.
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+pub struct Spend {
+    tokens: u64,
+    pub(crate) cost_usd: f64,
+}
+
+impl Spend {
+    fn add(&mut self, other: &Spend) {
+        self.tokens += other.tokens;
+        self.cost_usd += other.cost_usd;
+    }
+
+    /// What `self` adds over `earlier`, for usage that is reported again as it grows
+    fn since(&self, earlier: &Spend) -> Spend {
+        Spend {
+            tokens: self.tokens.saturating_sub(earlier.tokens),
+            cost_usd: (self.cost_usd - earlier.cost_usd).max(0.0),
+        }
+    }
+}
+
+/// Rough list prices in USD per million input and output tokens, by model family
+fn prices(model: Option<&str>) -> (f64, f64) {
+    let model = model.unwrap_or("").to_lowercase();
+    if model.contains("opus") {
+        (15.0, 75.0)
+    } else if model.contains("haiku") {
+        (1.0, 5.0)
+    } else {
+        (3.0, 15.0)
+    }
+}
+
+pub(crate) fn estimate(usage: &TokenUsage, model: Option<&str>) -> Spend {
+    let (input, output) = prices(model);
+    // Cache writes cost 1.25x input, cache reads 0.1x
+    let cost = usage.input as f64 * input
+        + usage.cache_creation as f64 * input * 1.25
+        + usage.cache_read as f64 * input * 0.1
+        + usage.output as f64 * output;
+    Spend {
+        tokens: usage.input + usage.output + usage.cache_creation,
+        cost_usd: cost / 1_000_000.0,
+    }
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct DaySpend {
+    date: String,
+    #[serde(flatten)]
+    spend: Spend,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    Task,
+    Day,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct BudgetExceeded {
+    /// Pass to resume_budget_run or stop_budget_run
+    key: String,
+    label: String,
+    scope: BudgetScope,
+    /// The run's own spend
+    spent: Spend,
+    /// Everything spent today, this run included
+    today: Spend,
+    limit: Limit,
+    /// False when the process could not be stopped; PTY input is held back either way
+    paused: bool,
+}
+
+enum Target {
+    /// Headless run: the agent process itself
+    Process(u32),
+    /// Interactive session: whatever is in the foreground of the PTY's shell
+    Pty { id: u32, shell_pid: u32 },
+}
+
+/// Claude Code transcript being tailed for an interactive session
+#[derive(Clone)]
+struct Watch {
+    project: String,
+    since: SystemTime,
+    /// Transcripts already there when the watch began, and their length then. A resumed session
+    /// appends to its old transcript, whose earlier usage isn't this run's.
+    existing: HashMap<PathBuf, u64>,
+    transcript: Option<PathBuf>,
+    offset: u64,
+}
+
+/// Length of each transcript in `project` now
+fn transcript_lengths(project: &str) -> HashMap<PathBuf, u64> {
+    std::fs::read_dir(crate::sessions::project_dir(project))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+        .filter_map(|p| Some((p.clone(), std::fs::metadata(&p).ok()?.len())))
+        .collect()
+}
+
+struct Run {
+    label: String,
+    target: Target,
+    watch: Option<Watch>,
+    spend: Spend,
+    /// Latest spend per message id; usage is repeated for every block of a message
+    messages: HashMap<String, Spend>,
+    /// Raised each time the user lets the run continue past it
+    task_limit: Limit,
+    /// The user agreed to let this run go on past today's budget
+    day_approved: bool,
+    paused: Option<BudgetExceeded>,
+    /// Process stopped by the pause, to be continued on resume
+    stopped_pid: Option<u32>,
+    on_exceeded: Option<AgentEventSink>,
+}
+
+#[derive(serde::Serialize)]
+pub struct RunBudget {
+    key: String,
+    label: String,
+    spent: Spend,
+    limit: Limit,
+    paused: Option<BudgetScope>,
+}
+
+#[derive(serde::Serialize)]
+pub struct BudgetStatus {
+    budgets: Budgets,
+    today: Spend,
+    runs: Vec<RunBudget>,
+}
+
+/// Token and cost ceilings for agent runs. Headless runs report usage as they stream; interactive
+/// Claude Code sessions are followed through their transcripts. A run that goes over is paused
+/// until the user confirms it may continue.
+#[derive(Clone)]
+pub struct BudgetManager {
+    budgets: Arc<Mutex<Budgets>>,
+    today: Arc<Mutex<DaySpend>>,
+    runs: Arc<Mutex<HashMap<String, Run>>>,
+    app: Arc<OnceLock<AppHandle>>,
+}
+
+fn budgets_path() -> PathBuf {
+    crate::ade_path("budgets.json")
+}
+
+fn spend_path() -> PathBuf {
+    crate::ade_path("budget-spend.json")
+}
+
+fn load<T: serde::de::DeserializeOwned + Default>(path: &PathBuf) -> T {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save<T: serde::Serialize>(path: &PathBuf, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn date_today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// Send `signal` (STOP, CONT, TERM, KILL) to a process
+pub(crate) fn signal(pid: u32, signal: &str) -> Result<(), String> {
+    if !cfg!(unix) {
+        return Err("Pausing agents is only supported on macOS and Linux".to_string());
+    }
+    let status = std::process::Command::new("kill")
+        .args([&format!("-{}", signal), "--", &pid.to_string()])
+        .status()
+        .map_err(|e| format!("Failed to run kill: {}", e))?;
+    if !status.success() {
+        return Err(format!("Failed to signal process {}", pid));
+    }
+    Ok(())
+}
+
+impl BudgetManager {
+    pub fn new() -> Self {
+        Self {
+            budgets: Arc::new(Mutex::new(load(&budgets_path()))),
+            today: Arc::new(Mutex::new(load(&spend_path()))),
+            runs: Arc::new(Mutex::new(HashMap::new())),
+            app: Arc::new(OnceLock::new()),
+        }
+    }
+
+    fn today_spend(&self) -> Spend {
+        let mut today = self.today.lock().unwrap();
+        if today.date != date_today() {
+            *today = DaySpend {
+                date: date_today(),
+                spend: Spend::default(),
+            };
+        }
+        today.spend
+    }
+
+    fn add_today(&self, spend: &Spend) -> Spend {
+        self.today_spend();
+        let mut today = self.today.lock().unwrap();
+        today.spend.add(spend);
+        if let Err(e) = save(&spend_path(), &*today) {
+            tracing::error!(error = %e, "Failed to save budget spend");
+        }
+        today.spend
+    }
+
+    fn insert(&self, key: &str, label: &str, target: Target, watch: Option<Watch>) {
+        let per_task = self.budgets.lock().unwrap().per_task;
+        self.runs.lock().unwrap().insert(
+            key.to_string(),
+            Run {
+                label: label.to_string(),
+                target,
+                watch,
+                spend: Spend::default(),
+                messages: HashMap::new(),
+                task_limit: per_task,
+                day_approved: false,
+                paused: None,
+                stopped_pid: None,
+                on_exceeded: None,
+            },
+        );
+    }
+
+    /// Start counting a headless run's usage; `on_event` also receives its BudgetExceeded event
+    pub(crate) fn track_process(&self, key: &str, label: &str, pid: u32, on_event: AgentEventSink) {
+        self.insert(key, label, Target::Process(pid), None);
+        if let Some(run) = self.runs.lock().unwrap().get_mut(key) {
+            run.on_exceeded = Some(on_event);
+        }
+    }
+
+    /// Add usage reported for `message_id` (repeats replace the earlier figure) and pause the
+    /// run if that takes it over its own or today's budget
+    pub(crate) fn record(
+        &self,
+        key: &str,
+        message_id: &str,
+        usage: &TokenUsage,
+        model: Option<&str>,
+    ) {
+        let spend = estimate(usage, model);
+        let added = {
+            let mut runs = self.runs.lock().unwrap();
+            let Some(run) = runs.get_mut(key) else {
+                return;
+            };
+            let earlier = run.messages.insert(message_id.to_string(), spend);
+            let added = spend.since(&earlier.unwrap_or_default());
+            run.spend.add(&added);
+            added
+        };
+        let today = self.add_today(&added);
+        self.check(key, today);
+    }
+
+    /// Replace the estimate with the cost the agent reported for the whole run
+    pub(crate) fn settle(&self, key: &str, cost_usd: f64) {
+        let delta = {
+            let mut runs = self.runs.lock().unwrap();
+            let Some(run) = runs.get_mut(key) else {
+                return;
+            };
+            let delta = cost_usd - run.spend.cost_usd;
+            run.spend.cost_usd = cost_usd;
+            delta
+        };
+        self.add_today(&Spend {
+            tokens: 0,
+            cost_usd: delta,
+        });
+    }
+
+    pub(crate) fn finish(&self, key: &str) {
+        self.runs.lock().unwrap().remove(key);
+    }
+
+    /// Whether the run tracked as `key` is paused for going over its budget
+    pub(crate) fn run_paused(&self, key: &str) -> bool {
+        self.runs
+            .lock()
+            .unwrap()
+            .get(key)
+            .is_some_and(|run| run.paused.is_some())
+    }
+
+    /// Whether typing into `pty_id` is held back by a budget pause
+    pub(crate) fn input_paused(&self, pty_id: u32) -> bool {
+        self.runs.lock().unwrap().values().any(|run| {
+            run.paused.is_some() && matches!(run.target, Target::Pty { id, .. } if id == pty_id)
+        })
+    }
+
+    fn check(&self, key: &str, today: Spend) {
+        let budgets = *self.budgets.lock().unwrap();
+        let (exceeded, sink) = {
+            let mut runs = self.runs.lock().unwrap();
+            let Some(run) = runs.get_mut(key) else {
+                return;
+            };
+            if run.paused.is_some() {
+                return;
+            }
+            let (scope, limit) = if run.task_limit.exceeded_by(&run.spend) {
+                (BudgetScope::Task, run.task_limit)
+            } else if !run.day_approved && budgets.per_day.exceeded_by(&today) {
+                (BudgetScope::Day, budgets.per_day)
+            } else {
+                return;
+            };
+            let pid = match run.target {
+                Target::Process(pid) => Some(pid),
+                Target::Pty { shell_pid, .. } => crate::pty::get_foreground_pid(shell_pid),
+            };
+            let stopped = pid.filter(|pid| match signal(*pid, "STOP") {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!(pid = *pid, error = %e, "Failed to pause agent");
+                    false
+                }
+            });
+            run.stopped_pid = stopped;
+            let exceeded = BudgetExceeded {
+                key: key.to_string(),
+                label: run.label.clone(),
+                scope,
+                spent: run.spend,
+                today,
+                limit,
+                paused: stopped.is_some(),
+            };
+            run.paused = Some(exceeded.clone());
+            (exceeded, run.on_exceeded.clone())
+        };
+
+        if let Some(sink) = sink {
+            sink(AgentEvent::BudgetExceeded(exceeded.clone()));
+        }
+        if let Some(app) = self.app.get() {
+            let _ = app.emit("budget-exceeded", &exceeded);
+            let what = match exceeded.scope {
+                BudgetScope::Task => "its task budget",
+                BudgetScope::Day => "today's budget",
+            };
+            crate::notifications::notify_background(
+                app,
+                crate::notifications::AgentSignal::NeedsInput,
+                &format!("{} paused", exceeded.label),
+                &format!("The agent went over {}. Confirm to let it continue.", what),
+            );
+        }
+    }
+
+    /// Newest transcript in the session's project written since the watch began and not
+    /// already followed by another session
+    fn find_transcript(&self, watch: &Watch) -> Option<PathBuf> {
+        let claimed: Vec<PathBuf> = self
+            .runs
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|run| run.watch.as_ref()?.transcript.clone())
+            .collect();
+        let since = watch.since.checked_sub(TRANSCRIPT_SLACK)?;
+        std::fs::read_dir(crate::sessions::project_dir(&watch.project))
+            .ok()?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+            .filter(|p| !claimed.contains(p))
+            .filter_map(|p| {
+                let modified = std::fs::metadata(&p).and_then(|m| m.modified()).ok()?;
+                (modified >= since).then_some((modified, p))
+            })
+            .max_by_key(|(modified, _)| *modified)
+            .map(|(_, p)| p)
+    }
+
+    /// Read what watched transcripts gained since the last poll
+    fn poll_transcripts(&self) {
+        let watches: Vec<(String, Watch)> = self
+            .runs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(key, run)| Some((key.clone(), run.watch.clone()?)))
+            .collect();
+        for (key, mut watch) in watches {
+            if watch.transcript.is_none() {
+                watch.transcript = self.find_transcript(&watch);
+                watch.offset = watch
+                    .transcript
+                    .as_ref()
+                    .and_then(|path| watch.existing.get(path))
+                    .copied()
+                    .unwrap_or(0);
+            }
+            let Some(path) = watch.transcript.clone() else {
+                continue;
+            };
+            let Ok(text) = read_from(&path, &mut watch.offset) else {
+                continue;
+            };
+            if let Some(run) = self.runs.lock().unwrap().get_mut(&key) {
+                run.watch = Some(watch);
+            }
+            for line in text.lines() {
+                let Ok(entry) = serde_json::from_str::<serde_json::Value>(line) else {
+                    continue;
+                };
+                if entry.get("type").and_then(|t| t.as_str()) != Some("assistant") {
+                    continue;
+                }
+                let message = &entry["message"];
+                let (Some(id), Some(usage)) = (message["id"].as_str(), message.get("usage")) else {
+                    continue;
+                };
+                self.record(
+                    &key,
+                    id,
+                    &TokenUsage::from_json(usage),
+                    message["model"].as_str(),
+                );
+            }
+        }
+    }
+}
+
+/// Complete lines appended to `path` after `offset`, moving `offset` past them
+fn read_from(path: &PathBuf, offset: &mut u64) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    file.seek(SeekFrom::Start(*offset))
+        .map_err(|e| e.to_string())?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    // A line still being written is picked up on the next poll
+    let end = bytes.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+    *offset += end as u64;
+    Ok(String::from_utf8_lossy(&bytes[..end]).to_string())
+}
+
+/// Start following watched transcripts; events need the app handle
+pub fn start(app: &tauri::App) {
+    use tauri::Manager;
+    let app = app.handle().clone();
+    let manager = app.state::<BudgetManager>().inner().clone();
+    let _ = manager.app.set(app);
+    std::thread::spawn(move || loop {
+        manager.poll_transcripts();
+        std::thread::sleep(POLL);
+    });
+}
+
+#[tauri::command]
+pub fn get_budgets(state: tauri::State<'_, BudgetManager>) -> Budgets {
+    *state.budgets.lock().unwrap()
+}
+
+/// Replace the budgets. Runs already in progress keep the per-task limit they started with.
+#[tauri::command]
+pub fn set_budgets(
+    state: tauri::State<'_, BudgetManager>,
+    budgets: Budgets,
+) -> Result<Budgets, String> {
+    for limit in [budgets.per_task, budgets.per_day] {
+        if limit
+            .max_cost_usd
+            .is_some_and(|max| !max.is_finite() || max <= 0.0)
+            || limit.max_tokens == Some(0)
+        {
+            return Err("Budgets must be greater than zero".to_string());
+        }
+    }
+    save(&budgets_path(), &budgets)?;
+    *state.budgets.lock().unwrap() = budgets;
+    Ok(budgets)
+}
+
+/// Budgets, today's spend, and what each tracked run has used
+#[tauri::command]
+pub fn get_budget_status(state: tauri::State<'_, BudgetManager>) -> BudgetStatus {
+    let today = state.today_spend();
+    let mut runs: Vec<RunBudget> = state
+        .runs
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(key, run)| RunBudget {
+            key: key.clone(),
+            label: run.label.clone(),
+            spent: run.spend,
+            limit: run.task_limit,
+            paused: run.paused.as_ref().map(|p| p.scope),
+        })
+        .collect();
+    runs.sort_by(|a, b| a.key.cmp(&b.key));
+    BudgetStatus {
+        budgets: *state.budgets.lock().unwrap(),
+        today,
+        runs,
+    }
+}
+
+/// Follow the Claude Code session started in `pty_id` through its transcript in `project`
+#[tauri::command]
+pub fn watch_agent_budget(
+    state: tauri::State<'_, BudgetManager>,
+    pty: tauri::State<'_, crate::pty::PtyManager>,
+    pty_id: u32,
+    project: String,
+    label: String,
+) -> Result<String, String> {
+    let shell_pid = pty.pid(pty_id).ok_or("PTY not found")?;
+    let key = format!("pty-{}", pty_id);
+    let watch = Watch {
+        existing: transcript_lengths(&project),
+        project,
+        since: SystemTime::now(),
+        transcript: None,
+        offset: 0,
+    };
+    state.insert(
+        &key,
+        &label,
+        Target::Pty {
+            id: pty_id,
+            shell_pid,
+        },
+        Some(watch),
+    );
+    Ok(key)
+}
+
+#[tauri::command]
+pub fn unwatch_agent_budget(state: tauri::State<'_, BudgetManager>, pty_id: u32) {
+    let key = format!("pty-{}", pty_id);
+    let run = state.runs.lock().unwrap().remove(&key);
+    if let Some(pid) = run.and_then(|r| r.stopped_pid) {
+        let _ = signal(pid, "CONT");
+    }
+}
+
+/// The user's go-ahead for a paused run. It continues until it spends another per-task budget,
+/// or, when paused for the daily budget, for the rest of the run.
+#[tauri::command]
+pub fn resume_budget_run(
+    state: tauri::State<'_, BudgetManager>,
+    key: String,
+) -> Result<(), String> {
+    let per_task = state.budgets.lock().unwrap().per_task;
+    let mut runs = state.runs.lock().unwrap();
+    let run = runs.get_mut(&key).ok_or("Run not found")?;
+    let paused = run.paused.take().ok_or("Run is not paused")?;
+    match paused.scope {
+        BudgetScope::Task => run.task_limit = run.task_limit.extend(&run.spend, &per_task),
+        BudgetScope::Day => run.day_approved = true,
+    }
+    if let Some(pid) = run.stopped_pid.take() {
+        signal(pid, "CONT")?;
+    }
+    Ok(())
+}
+
+/// End a paused run instead of continuing it: a headless agent is killed, an interactive one is
+/// terminated and the shell gets its input back
+#[tauri::command]
+pub fn stop_budget_run(state: tauri::State<'_, BudgetManager>, key: String) -> Result<(), String> {
+    let run = state.runs.lock().unwrap().remove(&key);
+    let run = run.ok_or("Run not found")?;
+    match run.target {
+        Target::Process(pid) => signal(pid, "KILL"),
+        Target::Pty { .. } => match run.stopped_pid {
+            Some(pid) => signal(pid, "TERM").and_then(|_| signal(pid, "CONT")),
+            None => Ok(()),
+        },
+    }
+}
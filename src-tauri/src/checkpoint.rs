@@ -0,0 +1,239 @@
+use crate::git::{git_command, resolve_repo, run_git, run_git_command, toplevel, FileDiff};
+use std::path::Path;
+
+/// Checkpoints are shadow commits under this ref namespace; they never touch branches or the index
+const CHECKPOINT_REFS: &str = "refs/ade/checkpoints";
+/// Older checkpoints beyond this count are pruned when a new one is created
+const MAX_CHECKPOINTS: usize = 50;
+
+#[derive(serde::Serialize)]
+pub struct Checkpoint {
+    pub(crate) id: String,
+    pub(crate) commit: String,
+    /// HEAD at the time the checkpoint was taken (None before the first commit)
+    head: Option<String>,
+    label: String,
+    /// Unix seconds
+    created_at: i64,
+}
+
+/// Write a tree object for the whole working tree (tracked + untracked, minus ignored files)
+/// using a throwaway index so the user's staging area is left alone.
+pub(crate) fn snapshot_tree(root: &str) -> Result<String, String> {
+    let (index, tmp_index) = crate::git::temp_index(root, "checkpoint")?;
+    // Starting from the real index keeps git's stat cache, so only changed files get hashed
+    if Path::new(&index).exists() {
+        std::fs::copy(&index, &tmp_index).map_err(|e| format!("Failed to copy index: {}", e))?;
+    }
+
+    let result = run_git_command(
+        git_command(root)
+            .env("GIT_INDEX_FILE", &tmp_index)
+            .args(["add", "--all", "--", "."]),
+    )
+    .and_then(|_| {
+        run_git_command(
+            git_command(root)
+                .env("GIT_INDEX_FILE", &tmp_index)
+                .arg("write-tree"),
+        )
+    });
+    let _ = std::fs::remove_file(&tmp_index);
+    Ok(String::from_utf8_lossy(&result?).trim().to_string())
+}
+
+fn head_commit(root: &str) -> Option<String> {
+    run_git(root, ["rev-parse", "--verify", "--quiet", "HEAD"])
+        .ok()
+        .map(|out| String::from_utf8_lossy(&out).trim().to_string())
+}
+
+fn checkpoint_ref(id: &str) -> Result<String, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid checkpoint id: {}", id));
+    }
+    Ok(format!("{}/{}", CHECKPOINT_REFS, id))
+}
+
+fn list(root: &str) -> Result<Vec<Checkpoint>, String> {
+    let output = run_git(
+        root,
+        [
+            "for-each-ref",
+            "--sort=-refname",
+            "--format=%(refname:lstrip=3)%1f%(objectname)%1f%(parent)%1f%(creatordate:unix)%1f%(subject)",
+            CHECKPOINT_REFS,
+        ],
+    )?;
+    let text = String::from_utf8_lossy(&output);
+    let mut checkpoints: Vec<Checkpoint> = text
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(5, '\x1f').collect();
+            if fields.len() < 5 {
+                return None;
+            }
+            Some(Checkpoint {
+                id: fields[0].to_string(),
+                commit: fields[1].to_string(),
+                head: Some(fields[2].to_string()).filter(|p| !p.is_empty()),
+                label: fields[4].to_string(),
+                created_at: fields[3].parse().unwrap_or(0),
+            })
+        })
+        .collect();
+    // Ids are millisecond timestamps; refname sorting is lexical, so sort numerically
+    checkpoints.sort_by_key(|c| std::cmp::Reverse(c.id.parse::<u64>().unwrap_or(0)));
+    Ok(checkpoints)
+}
+
+pub(crate) fn create(root: &str, label: &str) -> Result<Checkpoint, String> {
+    let tree = snapshot_tree(root)?;
+    let head = head_commit(root);
+    let label = if label.trim().is_empty() {
+        "Checkpoint"
+    } else {
+        label.trim()
+    };
+
+    let mut cmd = git_command(root);
+    // Fixed identity: the repo may have no user configured, and these commits are never shared
+    cmd.env("GIT_AUTHOR_NAME", "ADE")
+        .env("GIT_AUTHOR_EMAIL", "ade@localhost")
+        .env("GIT_COMMITTER_NAME", "ADE")
+        .env("GIT_COMMITTER_EMAIL", "ade@localhost")
+        .args(["commit-tree", &tree, "-m", label]);
+    if let Some(head) = &head {
+        cmd.args(["-p", head]);
+    }
+    let commit = String::from_utf8_lossy(&run_git_command(&mut cmd)?)
+        .trim()
+        .to_string();
+
+    let mut id = crate::now_ms();
+    // Two checkpoints in the same millisecond would otherwise overwrite each other
+    while run_git(
+        root,
+        [
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &checkpoint_ref(&id.to_string())?,
+        ],
+    )
+    .is_ok()
+    {
+        id += 1;
+    }
+    let id = id.to_string();
+    run_git(root, ["update-ref", &checkpoint_ref(&id)?, &commit])?;
+
+    for old in list(root)?.into_iter().skip(MAX_CHECKPOINTS) {
+        let _ = run_git(root, ["update-ref", "-d", &checkpoint_ref(&old.id)?]);
+    }
+
+    Ok(Checkpoint {
+        id,
+        commit,
+        head,
+        label: label.to_string(),
+        created_at: (crate::now_ms() / 1000) as i64,
+    })
+}
+
+#[tauri::command]
+pub async fn create_checkpoint(repo: String, label: Option<String>) -> Result<Checkpoint, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        create(&root, label.as_deref().unwrap_or(""))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_checkpoints(repo: String) -> Result<Vec<Checkpoint>, String> {
+    tauri::async_runtime::spawn_blocking(move || list(&toplevel(&resolve_repo(&repo))?))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Diff from the checkpoint to the current working tree (including untracked files)
+#[tauri::command]
+pub async fn diff_against_checkpoint(
+    repo: String,
+    id: String,
+    path: Option<String>,
+) -> Result<Vec<FileDiff>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let checkpoint = checkpoint_ref(&id)?;
+        let current = snapshot_tree(&root)?;
+        let mut args = crate::git::diff_args(None);
+        args.extend([
+            format!("{}^{{tree}}", checkpoint),
+            current,
+            "--".to_string(),
+        ]);
+        if let Some(path) = path {
+            args.push(crate::git::repo_relative(&root, &path));
+        }
+        Ok(crate::git::parse_diff(&run_git(&root, &args)?))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Restore the working tree to a checkpoint. Branches, HEAD and the index are left as they are;
+/// the current state is checkpointed first so the revert itself can be undone.
+#[tauri::command]
+pub async fn revert_to_checkpoint(repo: String, id: String) -> Result<Checkpoint, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let checkpoint = checkpoint_ref(&id)?;
+        run_git(&root, ["rev-parse", "--verify", "--quiet", &checkpoint])
+            .map_err(|_| format!("Checkpoint not found: {}", id))?;
+
+        let backup = create(&root, &format!("Before revert to {}", id))?;
+
+        // Files created since the checkpoint have to go
+        let added = run_git(
+            &root,
+            [
+                "diff",
+                "--name-only",
+                "-z",
+                "--no-renames",
+                "--diff-filter=A",
+                &format!("{}^{{tree}}", checkpoint),
+                &format!("{}^{{tree}}", backup.commit),
+            ],
+        )?;
+        for path in String::from_utf8_lossy(&added)
+            .split('\0')
+            .filter(|p| !p.is_empty())
+        {
+            let _ = std::fs::remove_file(Path::new(&root).join(path));
+        }
+
+        // Write every file from the checkpoint tree via a throwaway index
+        let (_, tmp_index) = crate::git::temp_index(&root, "revert")?;
+        let result = run_git_command(
+            git_command(&root)
+                .env("GIT_INDEX_FILE", &tmp_index)
+                .args(["read-tree", &checkpoint]),
+        )
+        .and_then(|_| {
+            run_git_command(git_command(&root).env("GIT_INDEX_FILE", &tmp_index).args([
+                "checkout-index",
+                "--all",
+                "--force",
+            ]))
+        });
+        let _ = std::fs::remove_file(&tmp_index);
+        result?;
+        Ok(backup)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,449 @@
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+/// Claude Code settings file for a scope: `user` (~/.claude/settings.json), `project`
+/// (<project>/.claude/settings.json, shared) or `local` (<project>/.claude/settings.local.json)
+pub(crate) fn settings_path(scope: &str, project: Option<&str>) -> Result<PathBuf, String> {
+    let project_dir = || -> Result<PathBuf, String> {
+        let project = project.ok_or_else(|| format!("Scope {} needs a project path", scope))?;
+        Ok(PathBuf::from(crate::git::resolve_repo(project)).join(".claude"))
+    };
+    match scope {
+        "user" => Ok(PathBuf::from(crate::get_home_dir())
+            .join(".claude")
+            .join("settings.json")),
+        "project" => Ok(project_dir()?.join("settings.json")),
+        "local" => Ok(project_dir()?.join("settings.local.json")),
+        _ => Err(format!("Unknown settings scope: {}", scope)),
+    }
+}
+
+pub(crate) fn read_json(path: &std::path::Path) -> Result<Value, String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) if content.trim().is_empty() => Ok(Value::Object(Map::new())),
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Value::Object(Map::new())),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Write via a temp file and rename so a crash never leaves a half-written config
+pub(crate) fn write_json(path: &std::path::Path, value: &Value) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension(format!("json.tmp-{}", std::process::id()));
+    std::fs::write(&tmp, content + "\n")
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// RFC 7396 JSON merge patch: objects merge recursively, `null` deletes, anything else replaces
+pub(crate) fn merge_patch(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let target = target.as_object_mut().unwrap();
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(&key);
+        } else {
+            merge_patch(target.entry(key).or_insert(Value::Null), value);
+        }
+    }
+}
+
+pub(crate) type ValueCheck = fn(&Value) -> bool;
+
+pub(crate) fn expect_strings(key: &str, value: &Value) -> Result<(), String> {
+    match value.as_array() {
+        Some(items) if items.iter().all(Value::is_string) => Ok(()),
+        _ => Err(format!("{} must be an array of strings", key)),
+    }
+}
+
+pub(crate) fn expect_map_of(key: &str, value: &Value, check: ValueCheck, what: &str) -> Result<(), String> {
+    match value.as_object() {
+        Some(map) if map.values().all(check) => Ok(()),
+        _ => Err(format!("{} must be an object of {}", key, what)),
+    }
+}
+
+/// Type-check the keys Claude Code knows about; unknown keys pass through untouched so newer
+/// settings aren't rejected
+fn validate_settings(settings: &Value) -> Result<(), String> {
+    let Some(settings) = settings.as_object() else {
+        return Err("Settings must be a JSON object".to_string());
+    };
+    for (key, value) in settings {
+        let scalar: Option<(ValueCheck, &str)> = match key.as_str() {
+            "model" | "apiKeyHelper" | "outputStyle" | "forceLoginMethod" => {
+                Some((Value::is_string, "a string"))
+            }
+            "includeCoAuthoredBy"
+            | "alwaysThinkingEnabled"
+            | "enableAllProjectMcpServers"
+            | "disableAllHooks" => Some((Value::is_boolean, "true or false")),
+            "cleanupPeriodDays" => Some((Value::is_u64, "a non-negative integer")),
+            "hooks" | "statusLine" => Some((Value::is_object, "an object")),
+            _ => None,
+        };
+        if let Some((check, what)) = scalar {
+            if !check(value) {
+                return Err(format!("{} must be {}", key, what));
+            }
+            continue;
+        }
+        match key.as_str() {
+            "env" => expect_map_of(key, value, Value::is_string, "strings")?,
+            "enabledPlugins" => expect_map_of(key, value, Value::is_boolean, "booleans")?,
+            "enabledMcpjsonServers" | "disabledMcpjsonServers" => expect_strings(key, value)?,
+            "permissions" => {
+                let Some(permissions) = value.as_object() else {
+                    return Err("permissions must be an object".to_string());
+                };
+                for (name, rule) in permissions {
+                    match name.as_str() {
+                        "allow" | "deny" | "ask" | "additionalDirectories" => {
+                            expect_strings(&format!("permissions.{}", name), rule)?
+                        }
+                        "defaultMode" => {
+                            let modes = ["default", "acceptEdits", "plan", "bypassPermissions"];
+                            if !rule.as_str().is_some_and(|m| modes.contains(&m)) {
+                                return Err(format!(
+                                    "permissions.defaultMode must be one of {}",
+                                    modes.join(", ")
+                                ));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Settings for one scope; a missing file reads as `{}`
+#[tauri::command]
+pub fn read_claude_settings(scope: String, project: Option<String>) -> Result<Value, String> {
+    read_json(&settings_path(&scope, project.as_deref())?)
+}
+
+/// Apply `patch` as a JSON merge patch and write the result back. The merged settings are
+/// validated first, so an invalid patch leaves the file untouched. Returns the new settings.
+#[tauri::command]
+pub fn write_claude_settings(
+    scope: String,
+    patch: Value,
+    project: Option<String>,
+) -> Result<Value, String> {
+    let path = settings_path(&scope, project.as_deref())?;
+    let mut settings = read_json(&path)?;
+    merge_patch(&mut settings, patch);
+    validate_settings(&settings)?;
+    write_json(&path, &settings)?;
+    Ok(settings)
+}
+
+/// One MCP server entry as Claude Code stores it under `mcpServers`
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct McpServerDefinition {
+    pub(crate) name: String,
+    /// stdio, sse or http
+    #[serde(default = "default_transport")]
+    pub(crate) transport: String,
+    #[serde(default)]
+    pub(crate) command: Option<String>,
+    #[serde(default)]
+    pub(crate) args: Vec<String>,
+    #[serde(default)]
+    pub(crate) env: std::collections::BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) url: Option<String>,
+    #[serde(default)]
+    pub(crate) headers: std::collections::BTreeMap<String, String>,
+    /// Launched through the MCP traffic inspector (stdio only); `command` and `args` stay the
+    /// real server's
+    #[serde(default)]
+    pub(crate) inspect: bool,
+}
+
+fn default_transport() -> String {
+    "stdio".to_string()
+}
+
+#[derive(serde::Serialize)]
+pub struct McpServer {
+    /// user, project or local
+    scope: String,
+    #[serde(flatten)]
+    definition: McpServerDefinition,
+}
+
+impl McpServerDefinition {
+    fn from_json(name: &str, value: &Value) -> Self {
+        let text = |key: &str| value.get(key).and_then(Value::as_str).map(String::from);
+        let map = |key: &str| {
+            value
+                .get(key)
+                .and_then(Value::as_object)
+                .map(|m| {
+                    m.iter()
+                        .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let mut command = text("command");
+        let mut args: Vec<String> = value
+            .get("args")
+            .and_then(Value::as_array)
+            .map(|a| {
+                a.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let inspected = crate::mcp_proxy::unwrap(&args);
+        let inspect = inspected.is_some();
+        if let Some((real_command, real_args)) = inspected {
+            command = Some(real_command);
+            args = real_args;
+        }
+        McpServerDefinition {
+            name: name.to_string(),
+            transport: text("type").unwrap_or_else(default_transport),
+            command,
+            args,
+            env: map("env"),
+            url: text("url"),
+            headers: map("headers"),
+            inspect,
+        }
+    }
+
+    fn to_json(&self) -> Result<Value, String> {
+        let mut entry = Map::new();
+        entry.insert("type".to_string(), Value::from(self.transport.as_str()));
+        if self.transport == "stdio" {
+            let (command, args) = match &self.command {
+                Some(command) if self.inspect => {
+                    let (command, args) = crate::mcp_proxy::wrap(&self.name, command, &self.args)?;
+                    (Some(command), args)
+                }
+                _ => (self.command.clone(), self.args.clone()),
+            };
+            entry.insert("command".to_string(), Value::from(command));
+            entry.insert("args".to_string(), Value::from(args));
+            if !self.env.is_empty() {
+                entry.insert("env".to_string(), serde_json::json!(self.env));
+            }
+        } else {
+            entry.insert("url".to_string(), Value::from(self.url.clone()));
+            if !self.headers.is_empty() {
+                entry.insert("headers".to_string(), serde_json::json!(self.headers));
+            }
+        }
+        Ok(Value::Object(entry))
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() || self.name.contains(char::is_whitespace) {
+            return Err(format!("Invalid MCP server name: {:?}", self.name));
+        }
+        let present = |v: &Option<String>| v.as_deref().is_some_and(|s| !s.trim().is_empty());
+        if self.inspect && self.transport != "stdio" {
+            return Err("Only stdio MCP servers can be inspected".to_string());
+        }
+        match self.transport.as_str() {
+            "stdio" if !present(&self.command) => Err("stdio servers need a command".to_string()),
+            "sse" | "http" if !present(&self.url) => {
+                Err(format!("{} servers need a url", self.transport))
+            }
+            "stdio" | "sse" | "http" => Ok(()),
+            other => Err(format!("Unknown MCP transport: {}", other)),
+        }
+    }
+}
+
+/// Where a scope keeps its servers: `user` is top-level in ~/.claude.json, `local` is the
+/// project's entry inside ~/.claude.json, and `project` is the shared <project>/.mcp.json
+fn mcp_location(scope: &str, project: Option<&str>) -> Result<(PathBuf, Vec<String>), String> {
+    let project = || -> Result<String, String> {
+        let project = project.ok_or_else(|| format!("Scope {} needs a project path", scope))?;
+        Ok(crate::git::resolve_repo(project)
+            .trim_end_matches('/')
+            .to_string())
+    };
+    let claude_json = PathBuf::from(crate::get_home_dir()).join(".claude.json");
+    match scope {
+        "user" => Ok((claude_json, vec!["mcpServers".to_string()])),
+        "local" => Ok((
+            claude_json,
+            vec!["projects".to_string(), project()?, "mcpServers".to_string()],
+        )),
+        "project" => Ok((
+            PathBuf::from(project()?).join(".mcp.json"),
+            vec!["mcpServers".to_string()],
+        )),
+        _ => Err(format!("Unknown MCP scope: {}", scope)),
+    }
+}
+
+fn servers_at<'a>(config: &'a Value, keys: &[String]) -> Option<&'a Map<String, Value>> {
+    keys.iter()
+        .try_fold(config, |value, key| value.get(key))?
+        .as_object()
+}
+
+fn servers_at_mut<'a>(config: &'a mut Value, keys: &[String]) -> &'a mut Map<String, Value> {
+    let mut value = config;
+    for key in keys {
+        if !value.is_object() {
+            *value = Value::Object(Map::new());
+        }
+        value = value
+            .as_object_mut()
+            .unwrap()
+            .entry(key.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+    value.as_object_mut().unwrap()
+}
+
+/// Read-modify-write the servers map of one scope, leaving the rest of the file as it was
+fn edit_servers(
+    scope: &str,
+    project: Option<&str>,
+    edit: impl FnOnce(&mut Map<String, Value>) -> Result<(), String>,
+) -> Result<(), String> {
+    let (path, keys) = mcp_location(scope, project)?;
+    let mut config = read_json(&path)?;
+    edit(servers_at_mut(&mut config, &keys))?;
+    write_json(&path, &config)
+}
+
+/// Rewrite one existing server definition in place
+pub(crate) fn edit_server(
+    scope: &str,
+    project: Option<&str>,
+    name: &str,
+    edit: impl FnOnce(&mut McpServerDefinition) -> Result<(), String>,
+) -> Result<(), String> {
+    edit_servers(scope, project, |servers| {
+        let value = servers
+            .get_mut(name)
+            .ok_or_else(|| format!("MCP server {} not found in {} scope", name, scope))?;
+        let mut server = McpServerDefinition::from_json(name, value);
+        edit(&mut server)?;
+        server.validate()?;
+        *value = server.to_json()?;
+        Ok(())
+    })
+}
+
+/// Servers from every scope; project scopes are included when `project` is given
+#[tauri::command]
+pub fn list_mcp_servers(project: Option<String>) -> Result<Vec<McpServer>, String> {
+    let scopes: &[&str] = if project.is_some() {
+        &["local", "project", "user"]
+    } else {
+        &["user"]
+    };
+    let mut servers = Vec::new();
+    for scope in scopes {
+        let (path, keys) = mcp_location(scope, project.as_deref())?;
+        let config = read_json(&path)?;
+        if let Some(map) = servers_at(&config, &keys) {
+            servers.extend(map.iter().map(|(name, value)| McpServer {
+                scope: scope.to_string(),
+                definition: McpServerDefinition::from_json(name, value),
+            }));
+        }
+    }
+    Ok(servers)
+}
+
+#[tauri::command]
+pub fn add_mcp_server(
+    scope: String,
+    server: McpServerDefinition,
+    project: Option<String>,
+) -> Result<(), String> {
+    server.validate()?;
+    edit_servers(&scope, project.as_deref(), |servers| {
+        if servers.contains_key(&server.name) {
+            return Err(format!(
+                "MCP server {} already exists in {} scope",
+                server.name, scope
+            ));
+        }
+        servers.insert(server.name.clone(), server.to_json()?);
+        Ok(())
+    })
+}
+
+/// Replace the server called `name`; the definition may carry a new name to rename it
+#[tauri::command]
+pub fn update_mcp_server(
+    scope: String,
+    name: String,
+    server: McpServerDefinition,
+    project: Option<String>,
+) -> Result<(), String> {
+    server.validate()?;
+    edit_servers(&scope, project.as_deref(), |servers| {
+        if servers.remove(&name).is_none() {
+            return Err(format!("MCP server {} not found in {} scope", name, scope));
+        }
+        if servers.contains_key(&server.name) {
+            return Err(format!(
+                "MCP server {} already exists in {} scope",
+                server.name, scope
+            ));
+        }
+        servers.insert(server.name.clone(), server.to_json()?);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn remove_mcp_server(
+    scope: String,
+    name: String,
+    project: Option<String>,
+) -> Result<(), String> {
+    edit_servers(&scope, project.as_deref(), |servers| {
+        servers
+            .remove(&name)
+            .map(|_| ())
+            .ok_or_else(|| format!("MCP server {} not found in {} scope", name, scope))
+    })
+}
+
+/// Add or replace a user-scope server, rewriting ~/.claude.json only when the entry changes
+pub(crate) fn ensure_user_mcp_server(server: &McpServerDefinition) -> Result<(), String> {
+    let (path, keys) = mcp_location("user", None)?;
+    let config = read_json(&path)?;
+    let entry = server.to_json()?;
+    if servers_at(&config, &keys).and_then(|s| s.get(&server.name)) == Some(&entry) {
+        return Ok(());
+    }
+    edit_servers("user", None, |servers| {
+        servers.insert(server.name.clone(), entry);
+        Ok(())
+    })
+}
@@ -0,0 +1,533 @@
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Directories never worth describing or scanning for manifests
+const SKIP_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "out",
+    "vendor",
+    "venv",
+    "__pycache__",
+    "coverage",
+];
+
+/// Descriptions for conventional top-level directories
+const KNOWN_DIRS: &[(&str, &str)] = &[
+    ("src", "Source code"),
+    ("lib", "Library code"),
+    ("app", "Application code"),
+    ("cmd", "Command entry points"),
+    ("pkg", "Packages"),
+    ("internal", "Internal packages"),
+    ("crates", "Workspace crates"),
+    ("packages", "Workspace packages"),
+    ("src-tauri", "Tauri (Rust) backend"),
+    ("tests", "Tests"),
+    ("test", "Tests"),
+    ("spec", "Tests"),
+    ("benches", "Benchmarks"),
+    ("examples", "Examples"),
+    ("docs", "Documentation"),
+    ("scripts", "Scripts"),
+    ("public", "Static assets"),
+    ("assets", "Static assets"),
+    ("static", "Static assets"),
+    ("migrations", "Database migrations"),
+    ("config", "Configuration"),
+];
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct ClaudeMdSection {
+    /// Heading text without the leading `#`s
+    heading: String,
+    body: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct ProjectCommand {
+    /// build, test, lint, format, dev, or run
+    pub(crate) kind: String,
+    pub(crate) command: String,
+    /// Directory the command runs in, relative to the root ("." for the root itself)
+    pub(crate) dir: String,
+}
+
+/// One manifest found while profiling
+#[derive(serde::Serialize)]
+pub struct DetectedProject {
+    /// rust, node, python, go, java, kotlin, ruby, php, or cpp
+    pub(crate) kind: String,
+    /// Package name from the manifest, when it declares one
+    pub(crate) name: Option<String>,
+    /// Relative to the root ("." for the root itself)
+    pub(crate) dir: String,
+    pub(crate) manifest: String,
+    pub(crate) package_manager: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct LayoutEntry {
+    path: String,
+    description: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct ClaudeMdDraft {
+    path: String,
+    /// Whether a CLAUDE.md already exists; the draft is never written by generate
+    exists: bool,
+    languages: Vec<String>,
+    build_tools: Vec<String>,
+    commands: Vec<ProjectCommand>,
+    layout: Vec<LayoutEntry>,
+    sections: Vec<ClaudeMdSection>,
+    content: String,
+}
+
+#[derive(Default)]
+pub(crate) struct Profile {
+    pub(crate) languages: Vec<String>,
+    pub(crate) build_tools: Vec<String>,
+    pub(crate) commands: Vec<ProjectCommand>,
+    pub(crate) projects: Vec<DetectedProject>,
+}
+
+impl Profile {
+    fn language(&mut self, name: &str) {
+        if !self.languages.iter().any(|l| l == name) {
+            self.languages.push(name.to_string());
+        }
+    }
+
+    fn tool(&mut self, name: &str) {
+        if !self.build_tools.iter().any(|t| t == name) {
+            self.build_tools.push(name.to_string());
+        }
+    }
+
+    fn command(&mut self, kind: &str, command: String, dir: &str) {
+        self.commands.push(ProjectCommand {
+            kind: kind.to_string(),
+            command,
+            dir: dir.to_string(),
+        });
+    }
+
+    fn project(
+        &mut self,
+        kind: &str,
+        manifest: &str,
+        name: Option<String>,
+        package_manager: &str,
+        dir: &str,
+    ) {
+        self.projects.push(DetectedProject {
+            kind: kind.to_string(),
+            name,
+            dir: dir.to_string(),
+            manifest: manifest.to_string(),
+            package_manager: package_manager.to_string(),
+        });
+    }
+}
+
+fn read(dir: &Path, file: &str) -> Option<String> {
+    std::fs::read_to_string(dir.join(file)).ok()
+}
+
+/// `key = "value"` from a TOML `[section]`, without a TOML parser
+pub(crate) fn toml_value(toml: &str, section: &str, key: &str) -> Option<String> {
+    let header = format!("[{}]", section);
+    let mut in_section = false;
+    for line in toml.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_section = line == header;
+        } else if in_section {
+            let Some((k, v)) = line.split_once('=') else {
+                continue;
+            };
+            if k.trim() == key {
+                let v = v.trim();
+                return Some(v.trim_matches(|c| c == '"' || c == '\'').to_string())
+                    .filter(|v| !v.is_empty());
+            }
+        }
+    }
+    None
+}
+
+/// Record the language, build tool and commands of the manifests found directly in `dir`
+fn inspect_dir(profile: &mut Profile, dir: &Path, rel: &str) {
+    let exists = |file: &str| dir.join(file).exists();
+
+    if let Some(cargo) = read(dir, "Cargo.toml") {
+        profile.language("Rust");
+        profile.tool("cargo");
+        let name = toml_value(&cargo, "package", "name");
+        profile.project("rust", "Cargo.toml", name, "cargo", rel);
+        let scope = if cargo.contains("[workspace]") {
+            " --workspace"
+        } else {
+            ""
+        };
+        profile.command("build", format!("cargo build{}", scope), rel);
+        profile.command("test", format!("cargo test{}", scope), rel);
+        profile.command(
+            "lint",
+            format!("cargo clippy{} --all-targets -- -D warnings", scope),
+            rel,
+        );
+        profile.command("format", "cargo fmt".to_string(), rel);
+        if exists("src/main.rs") || cargo.contains("[[bin]]") {
+            profile.command("run", "cargo run".to_string(), rel);
+        }
+    }
+
+    if let Some(package) = read(dir, "package.json") {
+        let package: Value = serde_json::from_str(&package).unwrap_or(Value::Null);
+        profile.language(if exists("tsconfig.json") {
+            "TypeScript"
+        } else {
+            "JavaScript"
+        });
+        let manager = if exists("pnpm-lock.yaml") {
+            "pnpm"
+        } else if exists("yarn.lock") {
+            "yarn"
+        } else if exists("bun.lockb") || exists("bun.lock") {
+            "bun"
+        } else {
+            "npm"
+        };
+        profile.tool(manager);
+        let name = package
+            .get("name")
+            .and_then(Value::as_str)
+            .map(String::from);
+        profile.project("node", "package.json", name, manager, rel);
+        let scripts = package.get("scripts").and_then(Value::as_object);
+        for (kind, names) in [
+            ("build", &["build"][..]),
+            ("test", &["test"][..]),
+            ("lint", &["lint", "typecheck"][..]),
+            ("format", &["format", "fmt"][..]),
+            ("dev", &["dev", "start"][..]),
+        ] {
+            let script = names
+                .iter()
+                .find(|name| scripts.is_some_and(|s| s.contains_key(**name)));
+            if let Some(script) = script {
+                profile.command(kind, format!("{} run {}", manager, script), rel);
+            }
+        }
+    }
+
+    if let Some(go_mod) = read(dir, "go.mod") {
+        profile.language("Go");
+        profile.tool("go");
+        let module = go_mod
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("module "))
+            .map(|m| m.trim().to_string());
+        profile.project("go", "go.mod", module, "go", rel);
+        if exists("main.go") {
+            profile.command("run", "go run .".to_string(), rel);
+        }
+        profile.command("build", "go build ./...".to_string(), rel);
+        profile.command("test", "go test ./...".to_string(), rel);
+        profile.command("lint", "go vet ./...".to_string(), rel);
+    }
+
+    let pyproject = read(dir, "pyproject.toml");
+    if pyproject.is_some() || exists("setup.py") || exists("requirements.txt") {
+        profile.language("Python");
+        let (manager, runner) = if exists("uv.lock") {
+            ("uv", "uv run ")
+        } else if exists("poetry.lock") {
+            ("poetry", "poetry run ")
+        } else {
+            ("pip", "")
+        };
+        profile.tool(manager);
+        let manifest = if pyproject.is_some() {
+            "pyproject.toml"
+        } else if exists("setup.py") {
+            "setup.py"
+        } else {
+            "requirements.txt"
+        };
+        let name = pyproject.as_deref().and_then(|p| {
+            toml_value(p, "project", "name").or_else(|| toml_value(p, "tool.poetry", "name"))
+        });
+        profile.project("python", manifest, name, manager, rel);
+        profile.command("test", format!("{}pytest", runner), rel);
+        // The first console script is the project's entry point
+        let script = pyproject.as_deref().and_then(|p| {
+            let section = p.split("[project.scripts]").nth(1)?;
+            let line = section.lines().map(str::trim).find(|l| l.contains('='))?;
+            Some(line.split('=').next()?.trim().trim_matches('"').to_string())
+                .filter(|s| !s.starts_with('['))
+        });
+        if let Some(script) = script {
+            profile.command("run", format!("{}{}", runner, script), rel);
+        }
+        if pyproject.is_some_and(|p| p.contains("[tool.ruff")) {
+            profile.command("lint", format!("{}ruff check .", runner), rel);
+            profile.command("format", format!("{}ruff format .", runner), rel);
+        }
+    }
+
+    if exists("pom.xml") {
+        profile.language("Java");
+        profile.tool("maven");
+        profile.project("java", "pom.xml", None, "maven", rel);
+        profile.command("build", "mvn package".to_string(), rel);
+        profile.command("test", "mvn test".to_string(), rel);
+    } else if exists("build.gradle") || exists("build.gradle.kts") {
+        profile.language(if exists("build.gradle.kts") {
+            "Kotlin"
+        } else {
+            "Java"
+        });
+        profile.tool("gradle");
+        let (kind, manifest) = if exists("build.gradle.kts") {
+            ("kotlin", "build.gradle.kts")
+        } else {
+            ("java", "build.gradle")
+        };
+        profile.project(kind, manifest, None, "gradle", rel);
+        let gradle = if exists("gradlew") {
+            "./gradlew"
+        } else {
+            "gradle"
+        };
+        profile.command("build", format!("{} build", gradle), rel);
+        profile.command("test", format!("{} test", gradle), rel);
+    }
+
+    if exists("Gemfile") {
+        profile.language("Ruby");
+        profile.tool("bundler");
+        profile.project("ruby", "Gemfile", None, "bundler", rel);
+    }
+    if exists("composer.json") {
+        profile.language("PHP");
+        profile.tool("composer");
+        profile.project("php", "composer.json", None, "composer", rel);
+    }
+    if exists("CMakeLists.txt") {
+        profile.language("C/C++");
+        profile.tool("cmake");
+        profile.project("cpp", "CMakeLists.txt", None, "cmake", rel);
+        profile.command(
+            "build",
+            "cmake -B build && cmake --build build".to_string(),
+            rel,
+        );
+    }
+
+    // Makefile targets fill in whatever the manifests above didn't cover
+    if let Some(makefile) = read(dir, "Makefile") {
+        profile.tool("make");
+        for kind in ["build", "test", "lint", "format"] {
+            let covered = profile
+                .commands
+                .iter()
+                .any(|c| c.kind == kind && c.dir == rel);
+            let defined = makefile.lines().any(|line| {
+                line.split_once(':')
+                    .is_some_and(|(target, rest)| target == kind && !rest.starts_with('='))
+            });
+            if defined && !covered {
+                profile.command(kind, format!("make {}", kind), rel);
+            }
+        }
+    }
+}
+
+fn visible_dirs(root: &Path) -> Vec<String> {
+    let mut dirs: Vec<String> = std::fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|name| !name.starts_with('.') && !SKIP_DIRS.contains(&name.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+fn render_sections(
+    profile: &Profile,
+    layout: &[LayoutEntry],
+    project_name: &str,
+) -> Vec<ClaudeMdSection> {
+    let mut sections = Vec::new();
+
+    let mut overview = format!("{} is", project_name);
+    if profile.languages.is_empty() {
+        overview.push_str(" a project");
+    } else {
+        overview.push_str(&format!(" a {} project", profile.languages.join(" / ")));
+    }
+    if !profile.build_tools.is_empty() {
+        overview.push_str(&format!(" built with {}", profile.build_tools.join(", ")));
+    }
+    overview.push_str(".\n");
+    sections.push(ClaudeMdSection {
+        heading: "Overview".to_string(),
+        body: overview,
+    });
+
+    if !profile.commands.is_empty() {
+        let mut body = String::new();
+        for command in &profile.commands {
+            let line = if command.dir == "." {
+                command.command.clone()
+            } else {
+                format!("cd {} && {}", command.dir, command.command)
+            };
+            body.push_str(&format!("- {}: `{}`\n", command.kind, line));
+        }
+        sections.push(ClaudeMdSection {
+            heading: "Commands".to_string(),
+            body,
+        });
+    }
+
+    if !layout.is_empty() {
+        let body: String = layout
+            .iter()
+            .map(|entry| match &entry.description {
+                Some(description) => format!("- `{}/` — {}\n", entry.path, description),
+                None => format!("- `{}/`\n", entry.path),
+            })
+            .collect();
+        sections.push(ClaudeMdSection {
+            heading: "Layout".to_string(),
+            body,
+        });
+    }
+
+    sections
+}
+
+/// Profile the manifests at `root` and one level down. Also returns the top-level directories.
+pub(crate) fn profile_project(root: &Path) -> (Profile, Vec<String>) {
+    let mut profile = Profile::default();
+    inspect_dir(&mut profile, root, ".");
+    let dirs = visible_dirs(root);
+    for dir in &dirs {
+        inspect_dir(&mut profile, &root.join(dir), dir);
+    }
+    (profile, dirs)
+}
+
+fn claude_md_path(root: &str) -> PathBuf {
+    PathBuf::from(crate::git::resolve_repo(root)).join("CLAUDE.md")
+}
+
+/// Draft a CLAUDE.md from the project's manifests (root and one level down) and its
+/// top-level directories. Nothing is written; pass the sections to `merge_claude_md`.
+#[tauri::command]
+pub fn generate_claude_md(root: String) -> Result<ClaudeMdDraft, String> {
+    let root_path = PathBuf::from(crate::git::resolve_repo(&root));
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root_path.display()));
+    }
+    let (profile, dirs) = profile_project(&root_path);
+
+    let layout: Vec<LayoutEntry> = dirs
+        .into_iter()
+        .map(|dir| LayoutEntry {
+            description: KNOWN_DIRS
+                .iter()
+                .find(|(name, _)| *name == dir)
+                .map(|(_, description)| description.to_string()),
+            path: dir,
+        })
+        .collect();
+    let project_name = root_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "This".to_string());
+    let sections = render_sections(&profile, &layout, &project_name);
+
+    let mut content = String::from(
+        "# CLAUDE.md\n\nThis file provides guidance to Claude Code when working with code in this repository.\n",
+    );
+    for section in &sections {
+        content.push_str(&format!("\n## {}\n\n{}", section.heading, section.body));
+    }
+
+    let path = claude_md_path(&root);
+    Ok(ClaudeMdDraft {
+        exists: path.exists(),
+        path: path.to_string_lossy().to_string(),
+        languages: profile.languages,
+        build_tools: profile.build_tools,
+        commands: profile.commands,
+        layout,
+        sections,
+        content,
+    })
+}
+
+/// Update sections of CLAUDE.md by heading (case-insensitive, any level) and return the new
+/// content. Only a section's own text is replaced, up to the next heading of any level, so
+/// subsections and everything outside the named sections stay as written. Sections that
+/// don't exist yet are appended as `##` headings; the file is created if missing.
+#[tauri::command]
+pub fn merge_claude_md(root: String, sections: Vec<ClaudeMdSection>) -> Result<String, String> {
+    let path = claude_md_path(&root);
+    let mut content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => "# CLAUDE.md\n".to_string(),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    for section in &sections {
+        let heading = section.heading.trim();
+        if heading.is_empty() {
+            return Err("Section heading must not be empty".to_string());
+        }
+        let mut body = section.body.trim_end().to_string();
+        body.push('\n');
+
+        let headings = crate::markdown::headings(&content);
+        let found = headings
+            .iter()
+            .position(|h| h.text.eq_ignore_ascii_case(heading));
+        match found {
+            Some(index) => {
+                let start = headings[index].end;
+                let end = headings
+                    .get(index + 1)
+                    .map(|h| h.start)
+                    .unwrap_or(content.len());
+                // Keep a blank line before whatever heading follows
+                let tail = if end < content.len() { "\n" } else { "" };
+                content.replace_range(start..end, &format!("\n{}{}", body, tail));
+            }
+            None => {
+                if !content.ends_with('\n') {
+                    content.push('\n');
+                }
+                content.push_str(&format!("\n## {}\n\n{}", heading, body));
+            }
+        }
+    }
+
+    let tmp = path.with_extension(format!("md.tmp-{}", std::process::id()));
+    std::fs::write(&tmp, &content)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    std::fs::rename(&tmp, &path)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(content)
+}
@@ -0,0 +1,403 @@
+//! Clipboard history: text and images copied anywhere are kept (in memory, newest first) so they
+//! can be pasted again into a terminal or an agent prompt. Text that looks like a secret, by the
+//! built-in patterns or the user's own, is never recorded. Images are saved as PNGs under
+//! ~/.ade/clipboard so their path can be pasted; the folder is emptied on startup.
+
+use regex::Regex;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::image::Image;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+const POLL: Duration = Duration::from_millis(1500);
+const DEFAULT_MAX_ITEMS: usize = 50;
+const MAX_ITEMS_LIMIT: usize = 500;
+/// Longer text is left out of the history
+const MAX_TEXT_BYTES: usize = 1024 * 1024;
+const PREVIEW_CHARS: usize = 200;
+
+/// API keys, tokens and private keys by their well-known shapes, plus assignments like
+/// `password=...`
+const SECRET_PATTERNS: &[&str] = &[
+    r"\bAKIA[0-9A-Z]{16}",
+    r"\bgh[pousr]_[A-Za-z0-9]{36,}",
+    r"\bgithub_pat_[A-Za-z0-9_]{22,}",
+    r"\bsk-ant-[A-Za-z0-9_-]{20,}",
+    r"\bsk-(proj-)?[A-Za-z0-9_-]{20,}",
+    r"\bxox[abprs]-[A-Za-z0-9-]{10,}",
+    r"\bAIza[0-9A-Za-z_-]{35}",
+    r"\beyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}",
+    r"-----BEGIN [A-Z ]*PRIVATE KEY-----",
+    r"(?i)(password|passwd|secret|token|api[_-]?key)\s*[:=]\s*\S{8,}",
+];
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ClipboardSettings {
+    enabled: bool,
+    /// Items kept, oldest dropped first
+    max_items: usize,
+    /// Regexes of text never to record, on top of the built-in secret patterns
+    exclude_patterns: Vec<String>,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_items: DEFAULT_MAX_ITEMS,
+            exclude_patterns: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum ClipContent {
+    #[serde(rename = "text")]
+    Text { text: String },
+    #[serde(rename = "image")]
+    Image {
+        width: u32,
+        height: u32,
+        /// The PNG under ~/.ade/clipboard
+        path: String,
+    },
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct ClipItem {
+    id: u32,
+    content: ClipContent,
+    /// The start of the text, or "Image 1920×1080"
+    preview: String,
+    copied_at: u64,
+    /// To tell copies apart without comparing them
+    #[serde(skip)]
+    hash: u64,
+}
+
+#[derive(Clone)]
+pub struct ClipboardHistoryManager {
+    items: Arc<Mutex<VecDeque<ClipItem>>>,
+    settings: Arc<Mutex<ClipboardSettings>>,
+    /// Built-in and user patterns, compiled
+    excludes: Arc<Mutex<Vec<Regex>>>,
+    next_id: Arc<Mutex<u32>>,
+    /// Hash of what was on the clipboard at the last poll
+    last_seen: Arc<Mutex<Option<u64>>>,
+}
+
+fn settings_path() -> PathBuf {
+    crate::ade_path("clipboard.json")
+}
+
+fn images_dir() -> PathBuf {
+    crate::ade_path("clipboard")
+}
+
+fn compile_excludes(settings: &ClipboardSettings) -> Result<Vec<Regex>, String> {
+    SECRET_PATTERNS
+        .iter()
+        .map(|p| p.to_string())
+        .chain(settings.exclude_patterns.iter().cloned())
+        .map(|pattern| {
+            Regex::new(&pattern).map_err(|e| format!("Invalid pattern {}: {}", pattern, e))
+        })
+        .collect()
+}
+
+fn hash_of(kind: &str, bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    kind.hash(&mut hasher);
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn save_png(path: &std::path::Path, image: &Image) -> Result<(), String> {
+    let file = std::fs::File::create(path)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut encoder =
+        png::Encoder::new(std::io::BufWriter::new(file), image.width(), image.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(image.rgba()))
+        .map_err(|e| format!("Failed to save {}: {}", path.display(), e))
+}
+
+fn preview(text: &str) -> String {
+    let line = text.trim().lines().next().unwrap_or_default();
+    let mut preview: String = line.chars().take(PREVIEW_CHARS).collect();
+    if preview.len() < text.trim().len() {
+        preview.push('…');
+    }
+    preview
+}
+
+/// Quote a path for a POSIX shell when it needs it
+fn shell_quoted(path: &str) -> String {
+    match path
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "/._-~+".contains(c))
+    {
+        true => path.to_string(),
+        false => format!("'{}'", path.replace('\'', r"'\''")),
+    }
+}
+
+/// Drop the oldest items beyond `max_items`, with their images
+fn trim(items: &mut VecDeque<ClipItem>, max_items: usize) {
+    while items.len() > max_items {
+        if let Some(ClipItem {
+            content: ClipContent::Image { path, .. },
+            ..
+        }) = items.pop_back()
+        {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl ClipboardHistoryManager {
+    pub fn new() -> Self {
+        let settings: ClipboardSettings = std::fs::read_to_string(settings_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        // A user pattern that no longer compiles shouldn't turn off the built-in ones
+        let excludes = compile_excludes(&settings)
+            .unwrap_or_else(|_| compile_excludes(&ClipboardSettings::default()).unwrap());
+        Self {
+            items: Arc::new(Mutex::new(VecDeque::new())),
+            settings: Arc::new(Mutex::new(settings)),
+            excludes: Arc::new(Mutex::new(excludes)),
+            next_id: Arc::new(Mutex::new(1)),
+            last_seen: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Move an earlier copy of the same thing back on top; whether there was one
+    fn bump(&self, hash: u64) -> bool {
+        let mut items = self.items.lock().unwrap();
+        let Some(index) = items.iter().position(|item| item.hash == hash) else {
+            return false;
+        };
+        let mut item = items.remove(index).unwrap();
+        item.copied_at = crate::now_ms();
+        items.push_front(item);
+        true
+    }
+
+    /// Add a new item on top, trimming to max_items
+    fn push(&self, content: ClipContent, preview: String, hash: u64) {
+        if self.bump(hash) {
+            return;
+        }
+        let max_items = self.settings.lock().unwrap().max_items;
+        let mut items = self.items.lock().unwrap();
+        let id = {
+            let mut next = self.next_id.lock().unwrap();
+            let id = *next;
+            *next += 1;
+            id
+        };
+        items.push_front(ClipItem {
+            id,
+            content,
+            preview,
+            copied_at: crate::now_ms(),
+            hash,
+        });
+        trim(&mut items, max_items);
+    }
+
+    /// Record what's on the clipboard if it changed since the last poll; whether it was added
+    fn poll(&self, app: &AppHandle) -> bool {
+        if !self.settings.lock().unwrap().enabled {
+            return false;
+        }
+        let clipboard = app.clipboard();
+        let text = clipboard.read_text().ok().filter(|t| !t.trim().is_empty());
+        let image = match text {
+            Some(_) => None,
+            None => match clipboard.read_image() {
+                Ok(image) if image.width() > 0 && image.height() > 0 => Some(image),
+                _ => return false,
+            },
+        };
+        let hash = match (&text, &image) {
+            (Some(text), _) => hash_of("text", text.as_bytes()),
+            (None, Some(image)) => hash_of("image", image.rgba()),
+            (None, None) => return false,
+        };
+        {
+            let mut last_seen = self.last_seen.lock().unwrap();
+            if *last_seen == Some(hash) {
+                return false;
+            }
+            *last_seen = Some(hash);
+        }
+
+        if let Some(text) = text {
+            if text.len() > MAX_TEXT_BYTES
+                || self
+                    .excludes
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .any(|pattern| pattern.is_match(&text))
+            {
+                return false;
+            }
+            let preview = preview(&text);
+            self.push(ClipContent::Text { text }, preview, hash);
+            return true;
+        }
+        let Some(image) = image else {
+            return false;
+        };
+        if self.bump(hash) {
+            return true;
+        }
+        let dir = images_dir();
+        let path = dir.join(format!("{}-{:x}.png", crate::now_ms(), hash));
+        let saved = std::fs::create_dir_all(&dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| save_png(&path, &image));
+        if let Err(e) = saved {
+            tracing::warn!(error = %e, "Failed to save clipboard image");
+            return false;
+        }
+        let (width, height) = (image.width(), image.height());
+        self.push(
+            ClipContent::Image {
+                width,
+                height,
+                path: path.to_string_lossy().to_string(),
+            },
+            format!("Image {}×{}", width, height),
+            hash,
+        );
+        true
+    }
+}
+
+/// Empty the image folder left from the last session and watch the clipboard, emitting
+/// `clipboard-history-changed` when an item is added
+pub fn start(app: &tauri::App) {
+    use tauri::Manager;
+    let _ = std::fs::remove_dir_all(images_dir());
+    let manager = app.state::<ClipboardHistoryManager>().inner().clone();
+    let app = app.handle().clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(crate::power::scaled(POLL));
+        if manager.poll(&app) {
+            let _ = app.emit("clipboard-history-changed", ());
+        }
+    });
+}
+
+/// Newest first
+#[tauri::command]
+pub fn get_clipboard_history(state: tauri::State<'_, ClipboardHistoryManager>) -> Vec<ClipItem> {
+    state.items.lock().unwrap().iter().cloned().collect()
+}
+
+/// Paste an item into the PTY `pty_id` as if typed: text without its trailing newline, so a
+/// command isn't run straight away, and images as the path of their PNG, which agent CLIs accept
+/// as an attachment. Without a PTY the item goes back on the system clipboard.
+#[tauri::command]
+pub fn paste_history_item(
+    app: AppHandle,
+    state: tauri::State<'_, ClipboardHistoryManager>,
+    pty: tauri::State<'_, crate::pty::PtyManager>,
+    id: u32,
+    pty_id: Option<u32>,
+) -> Result<(), String> {
+    let item = state
+        .items
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|item| item.id == id)
+        .cloned()
+        .ok_or_else(|| format!("No clipboard history item {}", id))?;
+    match (pty_id, &item.content) {
+        (Some(pty_id), ClipContent::Text { text }) => {
+            pty.write(pty_id, text.trim_end_matches(['\r', '\n']).as_bytes())
+        }
+        (Some(pty_id), ClipContent::Image { path, .. }) => {
+            pty.write(pty_id, format!("{} ", shell_quoted(path)).as_bytes())
+        }
+        (None, ClipContent::Text { text }) => app
+            .clipboard()
+            .write_text(text.clone())
+            .map_err(|e| format!("Failed to copy to the clipboard: {}", e)),
+        (None, ClipContent::Image { path, .. }) => {
+            let file =
+                std::fs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            let mut reader = png::Decoder::new(std::io::BufReader::new(file))
+                .read_info()
+                .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            let mut rgba = vec![0; reader.output_buffer_size()];
+            let frame = reader
+                .next_frame(&mut rgba)
+                .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            rgba.truncate(frame.buffer_size());
+            app.clipboard()
+                .write_image(&Image::new_owned(rgba, frame.width, frame.height))
+                .map_err(|e| format!("Failed to copy to the clipboard: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub fn clear_clipboard_history(state: tauri::State<'_, ClipboardHistoryManager>) {
+    let mut items = state.items.lock().unwrap();
+    trim(&mut items, 0);
+}
+
+#[tauri::command]
+pub fn get_clipboard_settings(
+    state: tauri::State<'_, ClipboardHistoryManager>,
+) -> ClipboardSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+/// Turn the history on or off, resize it, or change the excluded patterns; turning it off clears
+/// it
+#[tauri::command]
+pub fn set_clipboard_settings(
+    state: tauri::State<'_, ClipboardHistoryManager>,
+    mut settings: ClipboardSettings,
+) -> Result<ClipboardSettings, String> {
+    settings.max_items = settings.max_items.clamp(1, MAX_ITEMS_LIMIT);
+    let excludes = compile_excludes(&settings)?;
+
+    let path = settings_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to save clipboard settings: {}", e))?;
+
+    *state.excludes.lock().unwrap() = excludes;
+    let mut items = state.items.lock().unwrap();
+    trim(
+        &mut items,
+        match settings.enabled {
+            true => settings.max_items,
+            false => 0,
+        },
+    );
+    *state.settings.lock().unwrap() = settings.clone();
+    Ok(settings)
+}
@@ -0,0 +1,358 @@
+//! Two-phase confirmation for operations that can't be undone: deleting a directory with
+//! everything in it, force-pushing, and signalling processes the app didn't start. Lifting a
+//! project's read-only mode and changing a policy go through it too, since what those limit
+//! could otherwise undo them.
+//! request_confirmation describes the operation and returns a token; only execute_confirmed
+//! with that token, within a minute, carries it out. Both steps look at the target, and the
+//! second refuses when it has changed in between.
+
+use crate::projects::ProjectManager;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::ipc::Channel;
+
+/// How long a confirmation token stays valid
+const TOKEN_TTL: Duration = Duration::from_secs(60);
+/// Entries counted for a delete's description before giving up
+const MAX_COUNTED: usize = 100_000;
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type")]
+pub enum DestructiveOp {
+    /// A file or directory and everything under it
+    #[serde(rename = "delete_path")]
+    DeletePath { path: String },
+    /// `git push --force-with-lease`
+    #[serde(rename = "force_push")]
+    ForcePush {
+        repo: String,
+        /// origin when not given
+        remote: Option<String>,
+        /// git's configured behaviour when not given
+        branch: Option<String>,
+        set_upstream: Option<bool>,
+    },
+    /// TERM when no signal is given
+    #[serde(rename = "kill_process")]
+    KillProcess { pid: u32, signal: Option<String> },
+    /// Whatever listens on a port, TERM and then KILL
+    #[serde(rename = "free_port")]
+    FreePort { port: u16 },
+    /// A read-only project made writable again
+    #[serde(rename = "make_writable")]
+    MakeWritable { path: String },
+    /// A change to the "policy" settings, as a JSON merge patch
+    #[serde(rename = "set_policy")]
+    SetPolicy {
+        /// The project's settings; the user's when not given
+        root: Option<String>,
+        patch: serde_json::Value,
+    },
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct Confirmation {
+    pub(crate) token: String,
+    op: DestructiveOp,
+    /// What will happen, for the user, e.g. "Force-push main to origin, dropping 2 commits there"
+    summary: String,
+    expires_at: u64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(tag = "type")]
+pub enum ConfirmedOutcome {
+    #[serde(rename = "deleted")]
+    Deleted { path: String },
+    #[serde(rename = "pushed")]
+    Pushed(crate::git::RemoteResult),
+    #[serde(rename = "killed")]
+    Killed { pid: u32, signal: String },
+    #[serde(rename = "freed")]
+    Freed { port: u16, pids: Vec<u32> },
+    #[serde(rename = "made_writable")]
+    MadeWritable { path: String },
+    #[serde(rename = "policy_set")]
+    PolicySet {
+        settings: crate::project_settings::ProjectSettingsLayers,
+    },
+}
+
+struct Pending {
+    op: DestructiveOp,
+    /// What the operation would act on when confirmation was asked for
+    target: String,
+    issued_at: Instant,
+}
+
+#[derive(Clone)]
+pub struct ConfirmationManager {
+    pending: Arc<Mutex<HashMap<String, Pending>>>,
+    projects: ProjectManager,
+}
+
+impl ConfirmationManager {
+    pub fn new(projects: ProjectManager) -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            projects,
+        }
+    }
+
+    /// Describe `op` and hold it until its token is redeemed or expires
+    pub(crate) fn request(&self, op: DestructiveOp) -> Result<Confirmation, String> {
+        let (summary, target) = prepare(&op, &self.projects)?;
+        let token = crate::ide_mcp::random_token()?;
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, p| p.issued_at.elapsed() < TOKEN_TTL);
+        pending.insert(
+            token.clone(),
+            Pending {
+                op: op.clone(),
+                target,
+                issued_at: Instant::now(),
+            },
+        );
+        Ok(Confirmation {
+            token,
+            op,
+            summary,
+            expires_at: crate::now_ms() + TOKEN_TTL.as_millis() as u64,
+        })
+    }
+
+    /// The operation `token` was issued for, without using it up
+    pub(crate) fn peek(&self, token: &str) -> Option<DestructiveOp> {
+        let pending = self.pending.lock().unwrap();
+        Some(pending.get(token)?.op.clone())
+    }
+
+    /// The operation `token` confirms, if it is still what was confirmed. A token is good for
+    /// one try, whether or not it succeeds.
+    pub(crate) fn redeem(&self, token: &str) -> Result<DestructiveOp, String> {
+        let pending = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(token)
+            .filter(|p| p.issued_at.elapsed() < TOKEN_TTL)
+            .ok_or_else(|| "The confirmation has expired or was already used".to_string())?;
+        let (_, target) = prepare(&pending.op, &self.projects)?;
+        if target != pending.target {
+            return Err("The target changed since confirmation was asked for".to_string());
+        }
+        Ok(pending.op)
+    }
+}
+
+fn expand(path: &str) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) => PathBuf::from(format!("{}{}", crate::get_home_dir(), rest)),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Files and folders under `dir`, not following symlinks; None past MAX_COUNTED
+fn count_entries(dir: &Path) -> Option<(usize, usize)> {
+    let (mut files, mut dirs) = (0, 0);
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() => {
+                    dirs += 1;
+                    stack.push(entry.path());
+                }
+                _ => files += 1,
+            }
+            if files + dirs > MAX_COUNTED {
+                return None;
+            }
+        }
+    }
+    Some((files, dirs))
+}
+
+/// A description of `op` for the user, and what it acts on as of now
+fn prepare(op: &DestructiveOp, projects: &ProjectManager) -> Result<(String, String), String> {
+    match op {
+        DestructiveOp::DeletePath { path } => {
+            let expanded = expand(path);
+            if !expanded.is_absolute() {
+                return Err(format!("Path must be absolute: {}", path));
+            }
+            let meta = std::fs::symlink_metadata(&expanded)
+                .map_err(|e| format!("Cannot delete {}: {}", path, e))?;
+            let resolved = std::fs::canonicalize(&expanded)
+                .map_err(|e| format!("Cannot delete {}: {}", path, e))?;
+            let home = std::fs::canonicalize(crate::get_home_dir()).unwrap_or_default();
+            let protected = resolved.parent().is_none() || home.starts_with(&resolved);
+            if !meta.is_symlink() && protected {
+                return Err(format!("Refusing to delete {}", resolved.display()));
+            }
+            let summary = match (meta.is_dir(), count_entries(&expanded)) {
+                (false, _) => format!("Delete {}", expanded.display()),
+                (true, Some((files, dirs))) => format!(
+                    "Delete {} and everything in it: {} files in {} folders",
+                    expanded.display(),
+                    files,
+                    dirs
+                ),
+                (true, None) => format!(
+                    "Delete {} and everything in it: more than {} files and folders",
+                    expanded.display(),
+                    MAX_COUNTED
+                ),
+            };
+            let target = format!("{}:{}", resolved.display(), meta.is_dir());
+            Ok((summary, target))
+        }
+        DestructiveOp::ForcePush {
+            repo,
+            remote,
+            branch,
+            ..
+        } => {
+            let root = crate::git::toplevel(&crate::git::resolve_repo(repo))?;
+            let remote = remote.as_deref().unwrap_or("origin");
+            crate::git::check_rev(remote)?;
+            let branch = match branch {
+                Some(branch) => {
+                    crate::git::check_rev(branch)?;
+                    branch.clone()
+                }
+                None => git_line(&root, &["rev-parse", "--abbrev-ref", "HEAD"])?,
+            };
+            let head = git_line(&root, &["rev-parse", "HEAD"])?;
+            // Commits the remote has that would be dropped, as of the last fetch
+            let dropped = git_line(
+                &root,
+                &[
+                    "rev-list",
+                    "--count",
+                    &format!("HEAD..refs/remotes/{}/{}", remote, branch),
+                ],
+            )
+            .ok()
+            .and_then(|count| count.parse::<u32>().ok())
+            .unwrap_or(0);
+            let mut summary = format!("Force-push {} to {}", branch, remote);
+            if dropped > 0 {
+                summary.push_str(&format!(
+                    ", dropping {} commit{} there",
+                    dropped,
+                    if dropped == 1 { "" } else { "s" }
+                ));
+            }
+            Ok((summary, format!("{}:{}", branch, head)))
+        }
+        DestructiveOp::KillProcess { pid, signal } => {
+            let signal = crate::processes::parse_signal(signal.clone())?;
+            let command = crate::processes::signal_target(*pid)?;
+            Ok((
+                format!("Send SIG{} to {} ({})", signal, pid, command),
+                command,
+            ))
+        }
+        DestructiveOp::FreePort { port } => crate::processes::free_port_target(*port),
+        DestructiveOp::MakeWritable { path } => {
+            let path = projects.read_only_project(path)?;
+            Ok((format!("Make {} writable again", path), path))
+        }
+        DestructiveOp::SetPolicy { root, patch } => {
+            let current = crate::project_settings::policy_at(root.as_deref())?;
+            let scope = match root {
+                Some(root) => format!("the policy of {}", root),
+                None => "the user policy".to_string(),
+            };
+            Ok((format!("Change {}: {}", scope, patch), current.to_string()))
+        }
+    }
+}
+
+fn git_line(root: &str, args: &[&str]) -> Result<String, String> {
+    let out = crate::git::run_git(root, args)?;
+    Ok(String::from_utf8_lossy(&out).trim().to_string())
+}
+
+fn execute(
+    app: &tauri::AppHandle,
+    op: DestructiveOp,
+    projects: &ProjectManager,
+    on_event: &Channel<crate::git::RemoteEvent>,
+) -> Result<ConfirmedOutcome, String> {
+    match op {
+        DestructiveOp::DeletePath { path } => {
+            let expanded = expand(&path);
+            let meta = std::fs::symlink_metadata(&expanded)
+                .map_err(|e| format!("Failed to delete {}: {}", path, e))?;
+            // A symlink goes, not what it points to
+            let result = match meta.is_dir() {
+                true => std::fs::remove_dir_all(&expanded),
+                false => std::fs::remove_file(&expanded),
+            };
+            result.map_err(|e| format!("Failed to delete {}: {}", path, e))?;
+            Ok(ConfirmedOutcome::Deleted { path })
+        }
+        DestructiveOp::ForcePush {
+            repo,
+            remote,
+            branch,
+            set_upstream,
+        } => crate::git::force_push(&repo, remote, branch, set_upstream, on_event)
+            .map(ConfirmedOutcome::Pushed),
+        DestructiveOp::KillProcess { pid, signal } => {
+            let signal = crate::processes::parse_signal(signal)?;
+            crate::processes::send_signal(pid, &signal)?;
+            Ok(ConfirmedOutcome::Killed { pid, signal })
+        }
+        DestructiveOp::FreePort { port } => {
+            let pids = crate::processes::free(port)?;
+            Ok(ConfirmedOutcome::Freed { port, pids })
+        }
+        DestructiveOp::MakeWritable { path } => {
+            let path = projects.read_only_project(&path)?;
+            projects.make_writable(&path)?;
+            Ok(ConfirmedOutcome::MadeWritable { path })
+        }
+        DestructiveOp::SetPolicy { root, patch } => {
+            let patch = serde_json::json!({ "policy": patch });
+            crate::project_settings::update(app, root, patch, true)
+                .map(|settings| ConfirmedOutcome::PolicySet { settings })
+        }
+    }
+}
+
+/// Ask to carry out `op`: returns what it would do and a token for execute_confirmed, good
+/// for a minute
+#[tauri::command]
+pub async fn request_confirmation(
+    state: tauri::State<'_, ConfirmationManager>,
+    op: DestructiveOp,
+) -> Result<Confirmation, String> {
+    let manager = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || manager.request(op))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Carry out the operation `token` was issued for. `on_event` gets git's progress for a
+/// force-push and nothing for the others.
+#[tauri::command]
+pub async fn execute_confirmed(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, ConfirmationManager>,
+    token: String,
+    on_event: Channel<crate::git::RemoteEvent>,
+) -> Result<ConfirmedOutcome, String> {
+    let manager = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let op = manager.redeem(&token)?;
+        tracing::info!(op = %serde_json::to_string(&op).unwrap_or_default(), "Confirmed");
+        execute(&app, op, &manager.projects, &on_event)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
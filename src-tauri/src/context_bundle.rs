@@ -0,0 +1,426 @@
+use crate::tokens::Encoding;
+use std::path::{Path, PathBuf};
+
+/// Larger files are reported as skipped rather than bundled
+const MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+/// A file whose share of the budget is below this is left out instead of cut to a stub
+const MIN_FILE_TOKENS: usize = 64;
+/// Part of a truncated file's allowance spent on its start; the rest keeps the end
+const HEAD_SHARE: f64 = 0.75;
+
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleFormat {
+    #[default]
+    Markdown,
+    Xml,
+    Plain,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct BundleOptions {
+    /// Remove comments, trailing whitespace and runs of blank lines
+    #[serde(default)]
+    strip_comments: bool,
+    max_tokens: Option<usize>,
+    #[serde(default)]
+    format: BundleFormat,
+    /// Picks the tokenizer, as in count_tokens
+    model: Option<String>,
+    /// Headers show paths relative to this directory
+    root: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct BundledFile {
+    path: String,
+    /// Tokens of the file's content as it appears in the bundle
+    tokens: usize,
+    /// Tokens after comment stripping but before truncation
+    original_tokens: usize,
+    truncated: bool,
+    /// Left out because the budget ran out
+    omitted: bool,
+    error: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct ContextBundle {
+    /// Temp file holding the bundle
+    path: String,
+    tokens: usize,
+    encoding: Encoding,
+    approximate: bool,
+    files: Vec<BundledFile>,
+}
+
+struct Source {
+    display: String,
+    /// Why the file couldn't be read; such files take no part in the budget
+    error: Option<String>,
+    language: &'static str,
+    lines: Vec<String>,
+    line_tokens: Vec<usize>,
+    header_tokens: usize,
+}
+
+/// Files named in `paths`, with directories expanded through the gitignore-aware walker
+fn expand_paths(paths: &[String]) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        let path = PathBuf::from(crate::git::resolve_repo(path));
+        if path.is_dir() {
+            let mut found: Vec<PathBuf> = ignore::WalkBuilder::new(&path)
+                .build()
+                .flatten()
+                .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+                .map(|e| e.into_path())
+                .collect();
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(path);
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|p| seen.insert(p.clone()));
+    files
+}
+
+fn fence_language(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "rs" => "rust",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "jsx" | "mjs" | "cjs" => "javascript",
+        "py" | "pyi" => "python",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" => "cpp",
+        "cs" => "csharp",
+        "rb" => "ruby",
+        "php" => "php",
+        "sh" | "bash" | "zsh" => "bash",
+        "sql" => "sql",
+        "json" => "json",
+        "toml" => "toml",
+        "yaml" | "yml" => "yaml",
+        "md" => "markdown",
+        "html" => "html",
+        "css" => "css",
+        _ => "",
+    }
+}
+
+/// Line comment marker for languages without a bundled grammar; only whole-line comments are
+/// removed for these, since a marker mid-line may sit inside a string
+fn line_comment_marker(path: &Path) -> Option<&'static str> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "sh" | "bash" | "zsh" | "fish" | "rb" | "pl" | "r" | "toml" | "yaml" | "yml" => Some("#"),
+        "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "java" | "kt" | "kts" | "swift" | "cs"
+        | "scala" | "php" | "dart" => Some("//"),
+        "sql" | "lua" | "hs" => Some("--"),
+        _ => None,
+    }
+}
+
+fn comment_ranges(node: tree_sitter::Node, out: &mut Vec<std::ops::Range<usize>>) {
+    if node.kind().contains("comment") {
+        out.push(node.byte_range());
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        comment_ranges(child, out);
+    }
+}
+
+/// `content` without comments, trailing whitespace or repeated blank lines
+fn strip_comments(path: &Path, content: &str) -> String {
+    // Comment text is removed but its line breaks kept, so lines stay aligned with the original
+    let mut stripped = String::with_capacity(content.len());
+    let parsed = crate::outline::language_for(&path.to_string_lossy()).and_then(|(_, lang)| {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&lang).ok()?;
+        parser.parse(content, None)
+    });
+    match parsed {
+        Some(tree) => {
+            let mut ranges = Vec::new();
+            comment_ranges(tree.root_node(), &mut ranges);
+            let mut pos = 0;
+            for range in ranges {
+                // Keep a shebang line
+                if range.start == 0 && content.starts_with("#!") {
+                    continue;
+                }
+                stripped.push_str(&content[pos..range.start]);
+                stripped.extend(content[range.clone()].matches('\n'));
+                pos = range.end;
+            }
+            stripped.push_str(&content[pos..]);
+        }
+        None => {
+            let marker = line_comment_marker(path);
+            for (i, line) in content.lines().enumerate() {
+                let comment = marker.is_some_and(|marker| {
+                    line.trim_start().starts_with(marker) && !(i == 0 && line.starts_with("#!"))
+                });
+                if !comment {
+                    stripped.push_str(line);
+                }
+                stripped.push('\n');
+            }
+        }
+    }
+
+    let mut out = String::with_capacity(stripped.len());
+    let mut blank_run = 0;
+    for (original, line) in content.lines().zip(stripped.lines()) {
+        let line = line.trim_end();
+        if line.trim_start().is_empty() {
+            // Lines that only held a comment go entirely
+            if !original.trim().is_empty() {
+                continue;
+            }
+            blank_run += 1;
+            if blank_run > 1 || out.is_empty() {
+                continue;
+            }
+            out.push('\n');
+            continue;
+        }
+        blank_run = 0;
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+fn header(format: BundleFormat, display: &str, language: &str) -> String {
+    match format {
+        BundleFormat::Markdown => format!("## {}\n\n```{}\n", display, language),
+        BundleFormat::Xml => format!("<file path=\"{}\">\n", xml_escape(display)),
+        BundleFormat::Plain => format!("==> {} <==\n", display),
+    }
+}
+
+fn footer(format: BundleFormat) -> &'static str {
+    match format {
+        BundleFormat::Markdown => "```\n\n",
+        BundleFormat::Xml => "</file>\n\n",
+        BundleFormat::Plain => "\n",
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn omission_marker(lines: usize) -> String {
+    format!("… {} lines omitted …\n", lines)
+}
+
+/// Split `available` tokens across files so small files stay whole and the large ones share
+/// what is left evenly. Returns each file's allowance.
+fn allot(sizes: &[usize], available: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| sizes[i]);
+    let mut allowance = vec![0; sizes.len()];
+    let mut remaining = available;
+    for (done, &i) in order.iter().enumerate() {
+        let share = remaining / (sizes.len() - done);
+        allowance[i] = sizes[i].min(share);
+        remaining -= allowance[i];
+    }
+    allowance
+}
+
+/// The file's lines cut to about `allowance` tokens: the start, an omission marker, then the end
+fn truncate(source: &Source, allowance: usize, encoding: Encoding) -> Result<String, String> {
+    let marker_tokens = crate::tokens::count(&omission_marker(source.lines.len()), encoding)?;
+    let budget = allowance.saturating_sub(marker_tokens);
+    let head_budget = (budget as f64 * HEAD_SHARE) as usize;
+
+    let mut head = 0;
+    let mut used = 0;
+    while head < source.lines.len() && used + source.line_tokens[head] <= head_budget {
+        used += source.line_tokens[head];
+        head += 1;
+    }
+    let mut tail = source.lines.len();
+    while tail > head && used + source.line_tokens[tail - 1] <= budget {
+        used += source.line_tokens[tail - 1];
+        tail -= 1;
+    }
+
+    let mut text = source.lines[..head].concat();
+    text.push_str(&omission_marker(tail - head));
+    text.push_str(&source.lines[tail..].concat());
+    Ok(text)
+}
+
+/// Concatenate files (directories are expanded) into one document for an agent, with a header
+/// per file. With `max_tokens` set, files that don't fit are cut to their start and end, or
+/// left out once their share gets too small. The bundle is written to a temp file.
+#[tauri::command]
+pub async fn build_context_bundle(
+    paths: Vec<String>,
+    options: Option<BundleOptions>,
+) -> Result<ContextBundle, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let (encoding, approximate) = crate::tokens::encoding_for_model(options.model.as_deref());
+        let root = options.root.as_deref().map(|r| {
+            crate::git::resolve_repo(r)
+                .trim_end_matches('/')
+                .to_string()
+        });
+
+        let mut sources = Vec::new();
+        for path in expand_paths(&paths) {
+            let display = match &root {
+                Some(root) => crate::git::repo_relative(root, &path.to_string_lossy()),
+                None => path.to_string_lossy().to_string(),
+            };
+            let content = std::fs::metadata(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+                .and_then(|meta| {
+                    if meta.len() > MAX_FILE_SIZE {
+                        Err(format!("File too large ({} bytes)", meta.len()))
+                    } else {
+                        std::fs::read_to_string(&path)
+                            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+                    }
+                });
+            let content = match content {
+                Ok(content) if options.strip_comments => strip_comments(&path, &content),
+                Ok(content) => content,
+                Err(e) => {
+                    sources.push(Source {
+                        display,
+                        error: Some(e),
+                        language: "",
+                        lines: Vec::new(),
+                        line_tokens: Vec::new(),
+                        header_tokens: 0,
+                    });
+                    continue;
+                }
+            };
+
+            let mut lines: Vec<String> = content.split_inclusive('\n').map(String::from).collect();
+            if let Some(last) = lines.last_mut() {
+                if !last.ends_with('\n') {
+                    last.push('\n');
+                }
+            }
+            let line_tokens = lines
+                .iter()
+                .map(|l| crate::tokens::count(l, encoding))
+                .collect::<Result<Vec<_>, _>>()?;
+            let language = fence_language(&path);
+            let header_tokens = crate::tokens::count(
+                &format!(
+                    "{}{}",
+                    header(options.format, &display, language),
+                    footer(options.format)
+                ),
+                encoding,
+            )?;
+            sources.push(Source {
+                display,
+                error: None,
+                language,
+                lines,
+                line_tokens,
+                header_tokens,
+            });
+        }
+
+        let sizes: Vec<usize> = sources.iter().map(|s| s.line_tokens.iter().sum()).collect();
+        let allowances = match options.max_tokens {
+            Some(max) => {
+                let headers: usize = sources.iter().map(|s| s.header_tokens).sum();
+                allot(&sizes, max.saturating_sub(headers))
+            }
+            None => sizes.clone(),
+        };
+
+        let mut bundle = String::new();
+        let mut files = Vec::new();
+        for ((source, size), allowance) in sources.iter().zip(sizes).zip(allowances) {
+            if let Some(error) = &source.error {
+                files.push(BundledFile {
+                    path: source.display.clone(),
+                    tokens: 0,
+                    original_tokens: 0,
+                    truncated: false,
+                    omitted: true,
+                    error: Some(error.clone()),
+                });
+                continue;
+            }
+            let (text, truncated, omitted) = if allowance >= size {
+                (source.lines.concat(), false, false)
+            } else if allowance < MIN_FILE_TOKENS {
+                (String::new(), false, true)
+            } else {
+                (truncate(source, allowance, encoding)?, true, false)
+            };
+            let mut tokens = 0;
+            if !omitted {
+                tokens = crate::tokens::count(&text, encoding)?;
+                bundle.push_str(&header(options.format, &source.display, source.language));
+                bundle.push_str(&text);
+                bundle.push_str(footer(options.format));
+            }
+            files.push(BundledFile {
+                path: source.display.clone(),
+                tokens,
+                original_tokens: size,
+                truncated,
+                omitted,
+                error: None,
+            });
+        }
+
+        let dir = std::env::temp_dir().join("ade-context");
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let extension = match options.format {
+            BundleFormat::Markdown => "md",
+            BundleFormat::Xml => "xml",
+            BundleFormat::Plain => "txt",
+        };
+        let path = dir.join(format!("context-{}.{}", crate::now_ms(), extension));
+        std::fs::write(&path, &bundle)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+        Ok(ContextBundle {
+            path: path.to_string_lossy().to_string(),
+            tokens: crate::tokens::count(&bundle, encoding)?,
+            encoding,
+            approximate,
+            files,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,195 @@
+//! Crash capture. A panic anywhere in the backend is written to ~/.ade/crashes with its backtrace
+//! and what the thread was working on, and reported to the frontend as a `backend-error` event,
+//! so a dead terminal shows as crashed instead of going quiet. Long-lived threads are started
+//! with `spawn`, which also reports the errors they return.
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+
+/// Crash reports kept on disk
+const REPORTS_KEPT: usize = 20;
+/// Errors kept for get_backend_errors
+const ERRORS_KEPT: usize = 50;
+
+/// Where panics and thread errors go; the reporter thread emits them
+static REPORTER: OnceLock<Mutex<Sender<BackendError>>> = OnceLock::new();
+/// This session's errors, for a frontend that wasn't listening yet
+static ERRORS: Mutex<Vec<BackendError>> = Mutex::new(Vec::new());
+
+thread_local! {
+    /// (subsystem, context) of threads started with `spawn`
+    static CONTEXT: RefCell<Option<(&'static str, String)>> = const { RefCell::new(None) };
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct BackendError {
+    /// The part of the backend that failed, like "terminal" or "tasks"
+    subsystem: String,
+    /// What the thread was working on, like "pty:3" or "task:12"
+    context: Option<String>,
+    message: String,
+    /// A panic rather than an error the thread returned
+    panic: bool,
+    /// The crash report, for panics
+    report_path: Option<String>,
+    timestamp: u64,
+}
+
+#[derive(serde::Serialize)]
+struct CrashReport<'a> {
+    timestamp: u64,
+    app_version: &'a str,
+    os: &'a str,
+    arch: &'a str,
+    thread: Option<&'a str>,
+    subsystem: &'a str,
+    context: Option<&'a str>,
+    message: &'a str,
+    /// file:line:column
+    location: Option<String>,
+    backtrace: String,
+}
+
+fn crashes_dir() -> PathBuf {
+    crate::ade_path("crashes")
+}
+
+fn report(error: BackendError) {
+    if let Some(reporter) = REPORTER.get() {
+        let _ = reporter
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .send(error);
+    }
+}
+
+/// Write a crash report, dropping the oldest beyond REPORTS_KEPT; its path
+fn write_report(report: &CrashReport) -> Option<PathBuf> {
+    let dir = crashes_dir();
+    std::fs::create_dir_all(&dir).ok()?;
+    let path = dir.join(format!("{}-{}.json", report.timestamp, report.subsystem));
+    std::fs::write(&path, serde_json::to_string_pretty(report).ok()?).ok()?;
+
+    let mut reports: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    // Names start with the timestamp
+    reports.sort();
+    let excess = reports.len().saturating_sub(REPORTS_KEPT);
+    for old in &reports[..excess] {
+        let _ = std::fs::remove_file(old);
+    }
+    Some(path)
+}
+
+fn on_panic(info: &std::panic::PanicHookInfo, app_version: &str) {
+    let message = match info.payload().downcast_ref::<&str>() {
+        Some(s) => s.to_string(),
+        None => match info.payload().downcast_ref::<String>() {
+            Some(s) => s.clone(),
+            None => "Unknown panic".to_string(),
+        },
+    };
+    let thread = std::thread::current();
+    let (subsystem, context) = CONTEXT
+        .try_with(|context| context.borrow().clone())
+        .ok()
+        .flatten()
+        .map_or(("backend", None), |(subsystem, context)| {
+            (subsystem, Some(context))
+        });
+    let timestamp = crate::now_ms();
+    let report_path = write_report(&CrashReport {
+        timestamp,
+        app_version,
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        thread: thread.name(),
+        subsystem,
+        context: context.as_deref(),
+        message: &message,
+        location: info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+    });
+    report(BackendError {
+        subsystem: subsystem.to_string(),
+        context,
+        message,
+        panic: true,
+        report_path: report_path.map(|p| p.to_string_lossy().to_string()),
+        timestamp,
+    });
+}
+
+/// Start a named thread for `subsystem` working on `context`, like "pty:3". A panic in it is
+/// recorded with that context, and an error it returns is logged and reported.
+pub(crate) fn spawn<F>(
+    subsystem: &'static str,
+    context: String,
+    f: F,
+) -> std::thread::JoinHandle<()>
+where
+    F: FnOnce() -> Result<(), String> + Send + 'static,
+{
+    std::thread::Builder::new()
+        .name(format!("{} {}", subsystem, context))
+        .spawn(move || {
+            CONTEXT.with(|c| *c.borrow_mut() = Some((subsystem, context.clone())));
+            if let Err(message) = f() {
+                tracing::error!(subsystem, context, error = %message, "Background thread failed");
+                report(BackendError {
+                    subsystem: subsystem.to_string(),
+                    context: Some(context),
+                    message,
+                    panic: false,
+                    report_path: None,
+                    timestamp: crate::now_ms(),
+                });
+            }
+        })
+        .expect("failed to spawn thread")
+}
+
+/// Install the panic hook and start emitting `backend-error` events. Called before the other
+/// background work starts.
+pub fn start(app: &tauri::App) {
+    let (sender, receiver) = mpsc::channel::<BackendError>();
+    if REPORTER.set(Mutex::new(sender)).is_err() {
+        return;
+    }
+
+    let handle: AppHandle = app.handle().clone();
+    let app_version = handle.package_info().version.to_string();
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        on_panic(info, &app_version);
+        previous(info);
+    }));
+
+    // Emitting from the panicking thread could deadlock on a lock it holds
+    std::thread::spawn(move || {
+        for error in receiver {
+            {
+                let mut errors = ERRORS.lock().unwrap();
+                errors.push(error.clone());
+                let excess = errors.len().saturating_sub(ERRORS_KEPT);
+                errors.drain(..excess);
+            }
+            let _ = handle.emit("backend-error", error);
+        }
+    });
+}
+
+/// Panics and thread errors since the app started, oldest first
+#[tauri::command]
+pub fn get_backend_errors() -> Vec<BackendError> {
+    ERRORS.lock().unwrap().clone()
+}
@@ -0,0 +1,198 @@
+//! ade:// links, so terminal output, docs and other tools can point into the app:
+//!
+//! - `ade://open?path=/abs/file.rs&line=12&column=4` opens a file (or a folder as a project)
+//! - `ade://run-task?root=/abs/project&task=build` offers to run a task from list_tasks
+//! - `ade://focus-pane?pane=<id>` selects a pane; clicking a notification opens one
+//!
+//! Links are parsed and checked here and handed to the frontend as "deep-link" events. Those
+//! arriving before the frontend has called take_pending_deep_links, like the one that launched
+//! the app, are held until it does. On Linux and Windows a link opened while the app is running
+//! starts a second instance, which the single-instance plugin stops after forwarding the link
+//! here.
+
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+const SCHEME: &str = "ade";
+const FOCUS_PANE: &str = "focus-pane";
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum DeepLink {
+    #[serde(rename = "open")]
+    Open {
+        path: String,
+        /// 1-based
+        line: Option<u32>,
+        column: Option<u32>,
+        is_dir: bool,
+    },
+    /// Anything can open a link, so the frontend should confirm before running `command`
+    #[serde(rename = "run-task")]
+    RunTask {
+        root: String,
+        /// Task id, as run_task takes it
+        task: String,
+        command: String,
+    },
+    /// A link that couldn't be followed
+    #[serde(rename = "invalid")]
+    Invalid { url: String, error: String },
+}
+
+/// Links waiting for the frontend; None once it has taken them, after which links are emitted
+static PENDING: Mutex<Option<Vec<DeepLink>>> = Mutex::new(Some(Vec::new()));
+
+/// An absolute path from a link, with `~` expanded; None for relative ones
+fn link_path(value: &str) -> Option<String> {
+    let path = match value.strip_prefix("~/") {
+        Some(rest) => Path::new(&crate::get_home_dir()).join(rest),
+        None => Path::new(value).to_path_buf(),
+    };
+    path.is_absolute()
+        .then(|| path.to_string_lossy().to_string())
+}
+
+fn number(value: Option<&String>, name: &str) -> Result<Option<u32>, String> {
+    value
+        .map(|v| v.parse().map_err(|_| format!("Invalid {}: {}", name, v)))
+        .transpose()
+}
+
+/// The link a notification about `pane_id` opens when clicked
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) fn focus_pane_link(pane_id: &str) -> String {
+    url::Url::parse_with_params(&format!("{}://{}", SCHEME, FOCUS_PANE), [("pane", pane_id)])
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+/// ade://open?… has the action as host; ade:open?… and ade:///open?… as the path
+fn action(url: &url::Url) -> &str {
+    url.host_str()
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| url.path().trim_matches('/'))
+}
+
+fn parse_url(url: &url::Url) -> Result<DeepLink, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Not an {}:// link", SCHEME));
+    }
+    let action = action(url);
+    let params: std::collections::HashMap<String, String> =
+        url.query_pairs().into_owned().collect();
+    let param = |name: &str| {
+        params
+            .get(name)
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| format!("Missing {}", name))
+    };
+    match action {
+        "open" => {
+            let value = param("path")?;
+            let path =
+                link_path(value).ok_or_else(|| format!("Not an absolute path: {}", value))?;
+            let meta = std::fs::metadata(&path).map_err(|_| format!("No such file: {}", path))?;
+            Ok(DeepLink::Open {
+                line: number(params.get("line"), "line")?,
+                column: number(params.get("column"), "column")?,
+                is_dir: meta.is_dir(),
+                path,
+            })
+        }
+        "run-task" => {
+            let value = param("root")?;
+            let root =
+                link_path(value).ok_or_else(|| format!("Not an absolute path: {}", value))?;
+            if !Path::new(&root).is_dir() {
+                return Err(format!("Not a directory: {}", root));
+            }
+            let task = crate::tasks::find(&root, param("task")?)?;
+            Ok(DeepLink::RunTask {
+                root,
+                task: task.id,
+                command: task.command,
+            })
+        }
+        "" => Err("Missing action".to_string()),
+        other => Err(format!("Unknown action: {}", other)),
+    }
+}
+
+fn parse(url: &url::Url) -> DeepLink {
+    parse_url(url).unwrap_or_else(|error| DeepLink::Invalid {
+        url: url.to_string(),
+        error,
+    })
+}
+
+/// Emit `url`'s link, or hold it until the frontend is listening
+fn dispatch(app: &AppHandle, url: &url::Url) {
+    // A clicked notification; panes don't outlive the app, so there is nothing to hold
+    if url.scheme() == SCHEME && action(url) == FOCUS_PANE {
+        if let Some((_, pane_id)) = url.query_pairs().find(|(name, _)| name == "pane") {
+            crate::notifications::focus_pane(app, pane_id.to_string());
+        }
+        return;
+    }
+    let link = parse(url);
+    let mut pending = PENDING.lock().unwrap();
+    match pending.as_mut() {
+        Some(links) => links.push(link),
+        None => {
+            let _ = app.emit("deep-link", link);
+        }
+    }
+}
+
+/// Listen for links, starting with any the app was launched with
+pub fn start(app: &tauri::App) {
+    // Installed builds register the scheme on install; this covers dev builds
+    #[cfg(any(target_os = "linux", windows))]
+    let _ = app.deep_link().register_all();
+
+    let handle = app.handle().clone();
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in &urls {
+            dispatch(&handle, url);
+        }
+    }
+    app.deep_link().on_open_url(move |event| {
+        for url in &event.urls() {
+            dispatch(&handle, url);
+        }
+    });
+}
+
+/// A second launch, e.g. for a link on Linux or Windows: its links arrive through on_open_url,
+/// so all that is left is bringing this instance's window forward
+pub fn on_second_instance(app: &AppHandle, _argv: Vec<String>, _cwd: String) {
+    use tauri::Manager;
+    let window = app
+        .get_webview_window("main")
+        .or_else(|| app.webview_windows().into_values().next());
+    if let Some(window) = window {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Links that arrived before the frontend was listening. Later links come as "deep-link" events.
+#[tauri::command]
+pub fn take_pending_deep_links() -> Vec<DeepLink> {
+    PENDING.lock().unwrap().take().unwrap_or_default()
+}
+
+/// Parse a link without following it, e.g. one clicked in terminal output
+#[tauri::command]
+pub async fn parse_deep_link(url: String) -> Result<DeepLink, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let url = url::Url::parse(&url).map_err(|e| format!("Invalid link {}: {}", url, e))?;
+        parse_url(&url)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
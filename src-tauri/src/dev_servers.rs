@@ -0,0 +1,183 @@
+//! Dev servers noticed in task and terminal output ("Local: http://localhost:5173/",
+//! "Listening on port 3000"), so the preview pane can open one as soon as it is up.
+
+use crate::diagnostics::OutputLines;
+use regex::Regex;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(200);
+
+#[derive(Clone, serde::Serialize)]
+pub struct DevServerDetected {
+    port: u16,
+    url: String,
+    /// The terminal it was started in
+    pty_id: Option<u32>,
+    /// The run_task run it was started by
+    task_run_id: Option<u32>,
+    detected_at: u64,
+}
+
+struct Patterns {
+    /// A loopback or wildcard URL with a port
+    url: Regex,
+    /// "listening on port 3000", "listening on :8080"
+    port: Regex,
+    /// Words that mark a line as a server announcing itself rather than, say, a log of a request
+    announcement: Regex,
+}
+
+static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+
+fn patterns() -> &'static Patterns {
+    PATTERNS.get_or_init(|| Patterns {
+        url: Regex::new(
+            r#"(https?)://(localhost|127\.0\.0\.1|0\.0\.0\.0|\[::1?\]):(\d{2,5})(/[^\s'"]*)?"#,
+        )
+        .unwrap(),
+        port: Regex::new(r"(?i)\blistening on (?:port\s*)?:?(\d{2,5})\b").unwrap(),
+        announcement: Regex::new(
+            r"(?i)\b(local|listening|running|started|starting|server|ready|available|serving)\b",
+        )
+        .unwrap(),
+    })
+}
+
+/// The dev server a line of output announces, as (port, url)
+fn detect(line: &str) -> Option<(u16, String)> {
+    let patterns = patterns();
+    if !patterns.announcement.is_match(line) {
+        return None;
+    }
+    if let Some(c) = patterns.url.captures(line) {
+        let port = c[3].parse().ok()?;
+        // A server bound to every interface is still opened through localhost
+        let host = match &c[2] {
+            "0.0.0.0" | "[::]" => "localhost",
+            host => host,
+        };
+        let path = c.get(4).map(|m| m.as_str()).unwrap_or("/");
+        return Some((port, format!("{}://{}:{}{}", &c[1], host, port, path)));
+    }
+    let port = patterns.port.captures(line)?[1].parse().ok()?;
+    Some((port, format!("http://localhost:{}/", port)))
+}
+
+/// Whether something accepts connections on the port, over IPv4 or IPv6
+pub(crate) fn is_listening(port: u16) -> bool {
+    ("localhost", port)
+        .to_socket_addrs()
+        .map(|mut addrs| {
+            addrs.any(|addr| TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).is_ok())
+        })
+        .unwrap_or(false)
+}
+
+#[derive(Clone)]
+pub struct DevServerManager {
+    servers: Arc<Mutex<Vec<DevServerDetected>>>,
+    app: Arc<OnceLock<AppHandle>>,
+}
+
+impl DevServerManager {
+    pub fn new() -> Self {
+        Self {
+            servers: Arc::new(Mutex::new(Vec::new())),
+            app: Arc::new(OnceLock::new()),
+        }
+    }
+
+    pub(crate) fn detector(
+        &self,
+        pty_id: Option<u32>,
+        task_run_id: Option<u32>,
+    ) -> DevServerDetector {
+        DevServerDetector {
+            manager: self.clone(),
+            pty_id,
+            task_run_id,
+            lines: OutputLines::default(),
+        }
+    }
+
+    fn detected(&self, server: DevServerDetected) {
+        {
+            let mut servers = self.servers.lock().unwrap();
+            // Servers print their URL more than once (on restart, in a banner and a summary)
+            let known = servers.iter().any(|s| {
+                s.port == server.port
+                    && s.pty_id == server.pty_id
+                    && s.task_run_id == server.task_run_id
+            });
+            if known {
+                return;
+            }
+            servers.retain(|s| s.port != server.port);
+            servers.push(server.clone());
+        }
+        if let Some(app) = self.app.get() {
+            let _ = app.emit("dev-server-detected", server);
+        }
+    }
+
+    /// Drop the servers a terminal or task started, once it has exited
+    pub(crate) fn forget(&self, pty_id: Option<u32>, task_run_id: Option<u32>) {
+        self.servers
+            .lock()
+            .unwrap()
+            .retain(|s| s.pty_id != pty_id || s.task_run_id != task_run_id);
+    }
+}
+
+/// Watches one terminal's or task run's output
+pub(crate) struct DevServerDetector {
+    manager: DevServerManager,
+    pty_id: Option<u32>,
+    task_run_id: Option<u32>,
+    lines: OutputLines,
+}
+
+impl DevServerDetector {
+    pub(crate) fn feed(&mut self, text: &str) {
+        for line in self.lines.feed(text) {
+            if let Some((port, url)) = detect(&line) {
+                self.manager.detected(DevServerDetected {
+                    port,
+                    url,
+                    pty_id: self.pty_id,
+                    task_run_id: self.task_run_id,
+                    detected_at: crate::now_ms(),
+                });
+            }
+        }
+    }
+}
+
+pub fn start(app: &tauri::App) {
+    use tauri::Manager;
+    let _ = app
+        .state::<DevServerManager>()
+        .app
+        .set(app.handle().clone());
+}
+
+/// Dev servers detected in open terminals and running tasks that still accept connections,
+/// newest first
+#[tauri::command]
+pub async fn list_dev_servers(
+    state: tauri::State<'_, DevServerManager>,
+) -> Result<Vec<DevServerDetected>, String> {
+    let servers = state.servers.lock().unwrap().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        servers
+            .into_iter()
+            .rev()
+            .filter(|s| is_listening(s.port))
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
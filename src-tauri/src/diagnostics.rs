@@ -0,0 +1,408 @@
+//! Compiler, linter and test errors picked out of task and terminal output (rustc/cargo, tsc,
+//! eslint, pytest), kept as a problems list per project so errors can be jumped to without a
+//! language server.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+
+/// Longer lines are skipped rather than matched; a terminal that never ends a line (a redrawn
+/// progress bar) is dropped once it grows past this
+const MAX_LINE: usize = 4096;
+
+#[derive(Clone, PartialEq, serde::Serialize)]
+pub struct Diagnostic {
+    /// Absolute when the path could be resolved against the output's working directory
+    file: String,
+    line: u32,
+    column: Option<u32>,
+    /// error or warning
+    severity: String,
+    message: String,
+    /// E0308, TS2322, the eslint rule, or the Python exception type
+    code: Option<String>,
+    /// rustc, tsc, eslint or pytest
+    tool: String,
+    /// The output it came from: `task:<root>:<task>` or `pty:<id>`
+    origin: String,
+}
+
+impl Diagnostic {
+    fn same_problem(&self, other: &Diagnostic) -> bool {
+        self.file == other.file
+            && self.line == other.line
+            && self.column == other.column
+            && self.message == other.message
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+struct DiagnosticsChanged {
+    origin: String,
+    /// Files whose problems were added or removed, to tell which projects need refreshing
+    files: Vec<String>,
+}
+
+enum Parsed {
+    Diagnostic(Diagnostic),
+    /// A build or test run printed its summary
+    RunEnd,
+}
+
+struct Patterns {
+    ansi: Regex,
+    rustc_header: Regex,
+    rustc_location: Regex,
+    tsc: Regex,
+    tsc_pretty: Regex,
+    eslint_file: Regex,
+    eslint_message: Regex,
+    pytest: Regex,
+    /// cargo's `Finished`/`could not compile`, tsc's `Found N errors`, eslint's `✖ N problems`
+    /// and pytest's `=== N passed in 1.2s ===`
+    run_end: Regex,
+}
+
+static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+
+fn patterns() -> &'static Patterns {
+    PATTERNS.get_or_init(|| Patterns {
+        ansi: Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]")
+            .unwrap(),
+        rustc_header: Regex::new(r"^(error|warning)(?:\[([\w:-]+)\])?: (.+)$").unwrap(),
+        rustc_location: Regex::new(r"^\s*--> (.+?):(\d+):(\d+)$").unwrap(),
+        tsc: Regex::new(r"^(\S.*?)\((\d+),(\d+)\): (error|warning) (TS\d+): (.+)$").unwrap(),
+        tsc_pretty: Regex::new(r"^(\S.*?):(\d+):(\d+) - (error|warning) (TS\d+): (.+)$").unwrap(),
+        eslint_file: Regex::new(r"^(\S.*\.(?:[cm]?[jt]sx?|vue|svelte|astro))$").unwrap(),
+        eslint_message: Regex::new(
+            r"^\s+(\d+):(\d+)\s+(error|warning)\s+(.+?)(?:\s{2,}([@\w/-]+))?$",
+        )
+        .unwrap(),
+        pytest: Regex::new(
+            r"^(\S+\.py):(\d+): (\w*(?:Error|Exception|Failed|Failure))\b:?\s*(.*)$",
+        )
+        .unwrap(),
+        run_end: Regex::new(
+            r"^\s*Finished .+ in [\d.]+s|^error: could not compile|^Found \d+ errors?\b|^✖ \d+ problems?|^=+ .*\bin [\d.]+s\b.*=+$",
+        )
+        .unwrap(),
+    })
+}
+
+/// Splits output arriving in arbitrary chunks into lines as they'd read on screen: colors and
+/// other escape sequences removed, and only what's left after carriage returns redraw the line.
+/// Partial lines are held until they end.
+#[derive(Default)]
+pub(crate) struct OutputLines {
+    partial: String,
+}
+
+impl OutputLines {
+    pub(crate) fn feed(&mut self, text: &str) -> Vec<String> {
+        self.partial.push_str(text);
+        let mut lines = Vec::new();
+        while let Some(end) = self.partial.find('\n') {
+            let raw: String = self.partial.drain(..=end).collect();
+            if raw.len() > MAX_LINE {
+                // Still a line, so multi-line matches don't span it
+                lines.push(String::new());
+                continue;
+            }
+            let stripped = patterns().ansi.replace_all(&raw, "");
+            let line = stripped.trim_end_matches(['\r', '\n']);
+            lines.push(line.rsplit('\r').next().unwrap_or(line).to_string());
+        }
+        if self.partial.len() > MAX_LINE {
+            self.partial.clear();
+        }
+        lines
+    }
+}
+
+/// Line-by-line matcher for the supported tools' error formats
+#[derive(Default)]
+struct DiagnosticParser {
+    lines: OutputLines,
+    /// A rustc `error[E..]: message` line waiting for its `--> file:line:col`
+    rustc: Option<(String, Option<String>, String)>,
+    /// File heading the current block of eslint's default (stylish) output
+    eslint_file: Option<String>,
+}
+
+fn diagnostic(
+    tool: &str,
+    file: &str,
+    line: &str,
+    column: Option<&str>,
+    severity: &str,
+    message: &str,
+    code: Option<&str>,
+) -> Option<Parsed> {
+    Some(Parsed::Diagnostic(Diagnostic {
+        file: file.to_string(),
+        line: line.parse().ok()?,
+        column: column.and_then(|c| c.parse().ok()),
+        severity: severity.to_string(),
+        message: message.trim().to_string(),
+        code: code.filter(|c| !c.is_empty()).map(String::from),
+        tool: tool.to_string(),
+        origin: String::new(),
+    }))
+}
+
+impl DiagnosticParser {
+    fn feed(&mut self, text: &str) -> Vec<Parsed> {
+        let lines = self.lines.feed(text);
+        lines.iter().filter_map(|line| self.line(line)).collect()
+    }
+
+    fn line(&mut self, line: &str) -> Option<Parsed> {
+        let rustc = self.rustc.take();
+        let patterns = patterns();
+
+        if let Some((severity, code, message)) = rustc {
+            if let Some(c) = patterns.rustc_location.captures(line) {
+                return diagnostic(
+                    "rustc",
+                    &c[1],
+                    &c[2],
+                    Some(&c[3]),
+                    &severity,
+                    &message,
+                    code.as_deref(),
+                );
+            }
+        }
+        if patterns.run_end.is_match(line) {
+            self.eslint_file = None;
+            return Some(Parsed::RunEnd);
+        }
+        if let Some(c) = patterns.rustc_header.captures(line) {
+            self.rustc = Some((
+                c[1].to_string(),
+                c.get(2).map(|m| m.as_str().to_string()),
+                c[3].to_string(),
+            ));
+            return None;
+        }
+        if let Some(c) = patterns
+            .tsc
+            .captures(line)
+            .or_else(|| patterns.tsc_pretty.captures(line))
+        {
+            return diagnostic("tsc", &c[1], &c[2], Some(&c[3]), &c[4], &c[6], Some(&c[5]));
+        }
+        if let Some(file) = &self.eslint_file {
+            if let Some(c) = patterns.eslint_message.captures(line) {
+                let rule = c.get(5).map(|m| m.as_str());
+                return diagnostic("eslint", file, &c[1], Some(&c[2]), &c[3], &c[4], rule);
+            }
+        }
+        if let Some(c) = patterns.eslint_file.captures(line) {
+            self.eslint_file = Some(c[1].to_string());
+            return None;
+        }
+        if line.trim().is_empty() {
+            self.eslint_file = None;
+            return None;
+        }
+        if let Some(c) = patterns.pytest.captures(line) {
+            let message = match &c[4] {
+                "" => c[3].to_string(),
+                detail => detail.to_string(),
+            };
+            return diagnostic("pytest", &c[1], &c[2], None, "error", &message, Some(&c[3]));
+        }
+        None
+    }
+}
+
+#[derive(Clone)]
+pub struct DiagnosticsManager {
+    /// Problems by the output they came from
+    origins: Arc<Mutex<HashMap<String, Vec<Diagnostic>>>>,
+    app: Arc<OnceLock<AppHandle>>,
+}
+
+impl DiagnosticsManager {
+    pub fn new() -> Self {
+        Self {
+            origins: Arc::new(Mutex::new(HashMap::new())),
+            app: Arc::new(OnceLock::new()),
+        }
+    }
+
+    fn changed(&self, origin: &str, diagnostics: &[Diagnostic]) {
+        let mut files: Vec<String> = diagnostics.iter().map(|d| d.file.clone()).collect();
+        files.sort();
+        files.dedup();
+        if let (Some(app), false) = (self.app.get(), files.is_empty()) {
+            let _ = app.emit(
+                "diagnostics-changed",
+                DiagnosticsChanged {
+                    origin: origin.to_string(),
+                    files,
+                },
+            );
+        }
+    }
+
+    fn add(&self, origin: &str, found: Vec<Diagnostic>) {
+        let added: Vec<Diagnostic> = {
+            let mut origins = self.origins.lock().unwrap();
+            let existing = origins.entry(origin.to_string()).or_default();
+            let added: Vec<Diagnostic> = found
+                .into_iter()
+                .filter(|d| !existing.iter().any(|e| e.same_problem(d)))
+                .collect();
+            existing.extend(added.iter().cloned());
+            added
+        };
+        self.changed(origin, &added);
+    }
+
+    fn replace(&self, origin: &str, found: Vec<Diagnostic>) {
+        let mut affected = found.clone();
+        let previous = self
+            .origins
+            .lock()
+            .unwrap()
+            .insert(origin.to_string(), found);
+        affected.extend(previous.unwrap_or_default());
+        self.changed(origin, &affected);
+    }
+
+    /// Forget what `origin` reported, e.g. when its terminal closes or its task reruns
+    pub(crate) fn clear(&self, origin: &str) {
+        let removed = self.origins.lock().unwrap().remove(origin);
+        self.changed(origin, &removed.unwrap_or_default());
+    }
+
+    /// Collects a task run's problems; `finish` replaces the previous run's with them
+    pub(crate) fn task_collector(&self, origin: String, cwd: String) -> DiagnosticsCollector {
+        DiagnosticsCollector {
+            manager: self.clone(),
+            origin,
+            cwd,
+            shell_pid: None,
+            parser: DiagnosticParser::default(),
+            found: Vec::new(),
+        }
+    }
+
+    /// Collects a terminal's problems. A terminal runs many builds, so each time a run prints
+    /// its summary, its problems replace those of the one before. Relative paths are resolved
+    /// against the shell's current directory.
+    pub(crate) fn terminal_collector(
+        &self,
+        origin: String,
+        cwd: String,
+        shell_pid: Option<u32>,
+    ) -> DiagnosticsCollector {
+        DiagnosticsCollector {
+            shell_pid,
+            ..self.task_collector(origin, cwd)
+        }
+    }
+}
+
+pub(crate) struct DiagnosticsCollector {
+    manager: DiagnosticsManager,
+    origin: String,
+    cwd: String,
+    shell_pid: Option<u32>,
+    parser: DiagnosticParser,
+    /// Found since the collector started, or since a terminal's last run ended
+    found: Vec<Diagnostic>,
+}
+
+impl DiagnosticsCollector {
+    fn current_dir(&self) -> String {
+        self.shell_pid
+            .and_then(|pid| {
+                let foreground = crate::pty::get_foreground_pid(pid).unwrap_or(pid);
+                crate::pty::process_cwd(foreground).ok()
+            })
+            .unwrap_or_else(|| self.cwd.clone())
+    }
+
+    pub(crate) fn feed(&mut self, text: &str) {
+        let parsed = self.parser.feed(text);
+        if parsed.is_empty() {
+            return;
+        }
+        let mut cwd = None;
+        let mut new = Vec::new();
+        for parsed in parsed {
+            match parsed {
+                Parsed::Diagnostic(mut diagnostic) => {
+                    let cwd = cwd.get_or_insert_with(|| self.current_dir());
+                    let file = diagnostic.file.trim_start_matches("./");
+                    diagnostic.file = Path::new(cwd).join(file).to_string_lossy().to_string();
+                    diagnostic.origin = self.origin.clone();
+                    self.found.push(diagnostic.clone());
+                    new.push(diagnostic);
+                }
+                Parsed::RunEnd if self.shell_pid.is_some() => {
+                    new.clear();
+                    self.finish();
+                }
+                Parsed::RunEnd => {}
+            }
+        }
+        if !new.is_empty() {
+            self.manager.add(&self.origin, new);
+        }
+    }
+
+    /// Make what was found since the last run ended the origin's problems
+    pub(crate) fn finish(&mut self) {
+        self.manager
+            .replace(&self.origin, std::mem::take(&mut self.found));
+    }
+}
+
+pub fn start(app: &tauri::App) {
+    use tauri::Manager;
+    let _ = app
+        .state::<DiagnosticsManager>()
+        .app
+        .set(app.handle().clone());
+}
+
+/// Problems in files under `root` from every task and terminal, by file and line
+#[tauri::command]
+pub fn get_diagnostics(
+    state: tauri::State<'_, DiagnosticsManager>,
+    root: String,
+) -> Vec<Diagnostic> {
+    let root = crate::git::resolve_repo(&root);
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    for diagnostic in state.origins.lock().unwrap().values().flatten() {
+        let duplicate = diagnostics.iter().any(|d| d.same_problem(diagnostic));
+        if Path::new(&diagnostic.file).starts_with(&root) && !duplicate {
+            diagnostics.push(diagnostic.clone());
+        }
+    }
+    diagnostics.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+    diagnostics
+}
+
+/// Drop the problems in files under `root`; they come back when a build reports them again
+#[tauri::command]
+pub fn clear_diagnostics(state: tauri::State<'_, DiagnosticsManager>, root: String) {
+    let root = crate::git::resolve_repo(&root);
+    let mut removed: Vec<(String, Vec<Diagnostic>)> = Vec::new();
+    for (origin, diagnostics) in state.origins.lock().unwrap().iter_mut() {
+        let (under, rest) = diagnostics
+            .drain(..)
+            .partition(|d| Path::new(&d.file).starts_with(&root));
+        *diagnostics = rest;
+        removed.push((origin.clone(), under));
+    }
+    for (origin, diagnostics) in removed {
+        state.changed(&origin, &diagnostics);
+    }
+}
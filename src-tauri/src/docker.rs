@@ -0,0 +1,288 @@
+//! Docker containers through the docker CLI, so containerized dev environments can be listed,
+//! followed and given files without leaving the app. Terminals inside a container are ordinary
+//! terminals running `docker exec -it`.
+
+use std::collections::HashMap;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::ipc::Channel;
+
+const DEFAULT_TAIL: u32 = 500;
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const COMPOSE_PROJECT_LABEL: &str = "com.docker.compose.project";
+const COMPOSE_SERVICE_LABEL: &str = "com.docker.compose.service";
+
+#[derive(Clone, serde::Serialize)]
+pub struct Container {
+    id: String,
+    name: String,
+    image: String,
+    /// created, running, paused, restarting, exited or dead
+    state: String,
+    /// As docker ps shows it, e.g. "Up 2 hours"
+    status: String,
+    ports: String,
+    created_at: String,
+    compose_project: Option<String>,
+    compose_service: Option<String>,
+}
+
+/// A line of `docker ps --format '{{json .}}'`
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PsLine {
+    #[serde(rename = "ID")]
+    id: String,
+    names: String,
+    image: String,
+    #[serde(default)]
+    state: String,
+    status: String,
+    #[serde(default)]
+    ports: String,
+    #[serde(default)]
+    created_at: String,
+    #[serde(default)]
+    labels: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum ContainerLogEvent {
+    /// A line the container wrote to stdout
+    #[serde(rename = "stdout")]
+    Stdout { text: String },
+    #[serde(rename = "stderr")]
+    Stderr { text: String },
+    /// Always the last event: the logs ran out, the container stopped while following, or
+    /// stop_container_logs was called
+    #[serde(rename = "finished")]
+    Finished { error: Option<String> },
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct ContainerLogsOptions {
+    /// Lines from the end to start with; 500 by default
+    tail: Option<u32>,
+    /// Keep streaming new lines until stopped
+    #[serde(default)]
+    follow: bool,
+    /// Only lines since this timestamp (RFC 3339) or duration ("10m")
+    since: Option<String>,
+    #[serde(default)]
+    timestamps: bool,
+}
+
+/// Running `docker logs` processes
+#[derive(Clone)]
+pub struct DockerManager {
+    streams: Arc<Mutex<HashMap<u32, Child>>>,
+    next_id: Arc<Mutex<u32>>,
+}
+
+impl DockerManager {
+    pub fn new() -> Self {
+        Self {
+            streams: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+
+    /// Exit status of a log stream, None while it runs; Some(None) once stopped
+    fn try_wait(&self, id: u32) -> Option<Option<std::process::ExitStatus>> {
+        let mut streams = self.streams.lock().unwrap();
+        let Some(child) = streams.get_mut(&id) else {
+            return Some(None);
+        };
+        child.try_wait().ok().flatten().map(Some)
+    }
+}
+
+fn docker_program() -> Result<String, String> {
+    crate::which::find("docker")
+        .map(|path| path.to_string_lossy().to_string())
+        .ok_or_else(|| "docker is not installed".to_string())
+}
+
+/// Container ids and names go to docker as arguments, so one starting with a dash would be an
+/// option
+fn check_container(id: &str) -> Result<(), String> {
+    let valid = id.chars().next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    match valid {
+        true => Ok(()),
+        false => Err(format!("Invalid container: {}", id)),
+    }
+}
+
+/// Run docker and return its stdout, or the last thing it said on stderr
+fn docker(args: &[&str]) -> Result<String, String> {
+    let output = Command::new(docker_program()?)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run docker: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .unwrap_or("docker failed")
+            .to_string();
+        return Err(reason);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn label<'a>(labels: &'a str, key: &str) -> Option<&'a str> {
+    labels
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v)
+}
+
+/// Every container, running or not, running ones first
+#[tauri::command]
+pub async fn list_containers() -> Result<Vec<Container>, String> {
+    let stdout = tauri::async_runtime::spawn_blocking(|| {
+        docker(&["ps", "--all", "--no-trunc", "--format", "{{json .}}"])
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("Failed to list containers: {}", e))?;
+
+    let mut containers: Vec<Container> = stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<PsLine>(line).ok())
+        .map(|ps| Container {
+            compose_project: label(&ps.labels, COMPOSE_PROJECT_LABEL).map(str::to_string),
+            compose_service: label(&ps.labels, COMPOSE_SERVICE_LABEL).map(str::to_string),
+            id: ps.id,
+            name: ps.names,
+            image: ps.image,
+            state: ps.state,
+            status: ps.status,
+            ports: ps.ports,
+            created_at: ps.created_at,
+        })
+        .collect();
+    containers.sort_by_key(|c| c.state != "running");
+    Ok(containers)
+}
+
+/// Stream a container's logs, stdout and stderr apart. Resolves to an id for
+/// stop_container_logs once docker has started.
+#[tauri::command]
+pub fn container_logs(
+    state: tauri::State<'_, DockerManager>,
+    id: String,
+    options: Option<ContainerLogsOptions>,
+    on_event: Channel<ContainerLogEvent>,
+) -> Result<u32, String> {
+    check_container(&id)?;
+    let options = options.unwrap_or_default();
+    let mut cmd = Command::new(docker_program()?);
+    cmd.args(["logs", "--tail"])
+        .arg(options.tail.unwrap_or(DEFAULT_TAIL).to_string());
+    if options.follow {
+        cmd.arg("--follow");
+    }
+    if options.timestamps {
+        cmd.arg("--timestamps");
+    }
+    if let Some(since) = &options.since {
+        cmd.arg(format!("--since={}", since));
+    }
+    cmd.arg(&id)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run docker: {}", e))?;
+
+    let stream_id = {
+        let mut next = state.next_id.lock().unwrap();
+        let id = *next;
+        *next += 1;
+        id
+    };
+    // docker's own complaints (no such container) arrive on stderr too, so keep the last line
+    let last_stderr = Arc::new(Mutex::new(None::<String>));
+    let on_line = {
+        let on_event = on_event.clone();
+        let last_stderr = last_stderr.clone();
+        move |stream, text: String| {
+            if stream == "stderr" {
+                *last_stderr.lock().unwrap() = Some(text.trim().to_string());
+            }
+            let _ = on_event.send(match stream {
+                "stdout" => ContainerLogEvent::Stdout { text },
+                _ => ContainerLogEvent::Stderr { text },
+            });
+        }
+    };
+    let readers = crate::subprocess::stream_lines(&mut child, on_line);
+    state.streams.lock().unwrap().insert(stream_id, child);
+
+    let manager = state.inner().clone();
+    crate::crash::spawn("docker", format!("logs:{}", id), move || {
+        let status = loop {
+            if let Some(status) = manager.try_wait(stream_id) {
+                break status;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        };
+        manager.streams.lock().unwrap().remove(&stream_id);
+        for reader in readers {
+            let _ = reader.join();
+        }
+        let error = match status {
+            Some(status) if !status.success() => Some(
+                last_stderr
+                    .lock()
+                    .unwrap()
+                    .take()
+                    .unwrap_or_else(|| format!("docker logs exited with {}", status)),
+            ),
+            _ => None,
+        };
+        let _ = on_event.send(ContainerLogEvent::Finished { error });
+        Ok(())
+    });
+    Ok(stream_id)
+}
+
+#[tauri::command]
+pub fn stop_container_logs(state: tauri::State<'_, DockerManager>, id: u32) {
+    if let Some(mut child) = state.streams.lock().unwrap().remove(&id) {
+        let _ = crate::subprocess::signal_group(&mut child, "KILL");
+        let _ = child.wait();
+    }
+}
+
+/// Copy a file or directory from this machine to `dest` inside the container, as docker cp
+/// does: a directory is copied into `dest` when that exists, else becomes it
+#[tauri::command]
+pub async fn copy_to_container(id: String, src: String, dest: String) -> Result<(), String> {
+    check_container(&id)?;
+    if !std::path::Path::new(&src).exists() {
+        return Err(format!("Not found: {}", src));
+    }
+    if !dest.starts_with('/') {
+        return Err(format!("Container path must be absolute: {}", dest));
+    }
+    let target = format!("{}:{}", id, dest);
+    tauri::async_runtime::spawn_blocking(move || docker(&["cp", "--", &src, &target]))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to copy to {}: {}", id, e))?;
+    Ok(())
+}
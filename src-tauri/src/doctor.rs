@@ -0,0 +1,345 @@
+//! Checks of the environment the app depends on, for the onboarding screen: the tools it runs,
+//! the shell terminals start, the PATH it was launched with, file watch limits and disk space.
+
+use crate::which::{run_login_shell, Semver, SHELL_TIMEOUT};
+use std::time::Duration;
+
+/// A login shell that takes longer than this makes every new terminal feel slow
+const SLOW_SHELL: Duration = Duration::from_secs(2);
+/// Below these, large repositories stop being watched completely
+const MIN_INOTIFY_WATCHES: u64 = 65_536;
+const LOW_DISK_BYTES: u64 = 5 * 1024 * 1024 * 1024;
+const CRITICAL_DISK_BYTES: u64 = 1024 * 1024 * 1024;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(serde::Serialize)]
+pub struct DoctorFix {
+    label: String,
+    /// Shell command that fixes it, for the user to run (or review and run) in a terminal
+    command: Option<String>,
+    url: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct DoctorCheck {
+    id: String,
+    title: String,
+    status: CheckStatus,
+    detail: String,
+    /// What to do about a warning or failure
+    fix: Option<DoctorFix>,
+}
+
+fn check(id: &str, title: &str, status: CheckStatus, detail: String) -> DoctorCheck {
+    DoctorCheck {
+        id: id.to_string(),
+        title: title.to_string(),
+        status,
+        detail,
+        fix: None,
+    }
+}
+
+impl DoctorCheck {
+    /// Attach a fix, unless the check passed
+    fn fix(mut self, label: &str, command: Option<&str>, url: Option<&str>) -> Self {
+        if matches!(self.status, CheckStatus::Pass) {
+            return self;
+        }
+        self.fix = Some(DoctorFix {
+            label: label.to_string(),
+            command: command.map(String::from),
+            url: url.map(String::from),
+        });
+        self
+    }
+}
+
+/// Whether a tool is installed and at least `minimum` (major, minor)
+fn tool_check(
+    id: &str,
+    title: &str,
+    command: &str,
+    minimum: (u64, u64),
+    missing: CheckStatus,
+) -> DoctorCheck {
+    let Some(path) = crate::which::find(command) else {
+        return check(id, title, missing, format!("{} was not found", command));
+    };
+    let version = crate::which::version(command);
+    match version
+        .as_deref()
+        .and_then(Semver::parse)
+        .map(|v| (v.major, v.minor))
+    {
+        Some(found) if found < minimum => check(
+            id,
+            title,
+            CheckStatus::Warn,
+            format!(
+                "{} {} is older than {}.{}, the oldest version known to work",
+                command,
+                version.unwrap_or_default(),
+                minimum.0,
+                minimum.1
+            ),
+        ),
+        _ => check(
+            id,
+            title,
+            CheckStatus::Pass,
+            format!(
+                "{} {} at {}",
+                command,
+                version.unwrap_or_else(|| "(unknown version)".to_string()),
+                path.display()
+            ),
+        ),
+    }
+}
+
+fn git_check() -> DoctorCheck {
+    let check = tool_check("git", "Git", "git", (2, 23), CheckStatus::Fail);
+    if cfg!(target_os = "macos") {
+        check.fix(
+            "Install the Xcode command line tools",
+            Some("xcode-select --install"),
+            None,
+        )
+    } else {
+        check.fix("Install git", None, Some("https://git-scm.com/downloads"))
+    }
+}
+
+fn node_check() -> DoctorCheck {
+    // Node is needed by the Claude CLI and by package.json tasks, but not by the app itself
+    tool_check("node", "Node.js", "node", (18, 0), CheckStatus::Warn).fix(
+        "Install Node.js 18 or newer",
+        None,
+        Some("https://nodejs.org/en/download"),
+    )
+}
+
+fn claude_check() -> DoctorCheck {
+    tool_check(
+        "claude",
+        "Claude Code CLI",
+        "claude",
+        (1, 0),
+        CheckStatus::Warn,
+    )
+    .fix(
+        "Install the Claude Code CLI",
+        Some("npm install -g @anthropic-ai/claude-code"),
+        None,
+    )
+}
+
+fn login_shell() -> String {
+    crate::project_settings::load(None)
+        .shell
+        .or_else(|| std::env::var("SHELL").ok())
+        .unwrap_or_else(|| "/bin/zsh".to_string())
+}
+
+/// The shell terminals open with starts, and quickly
+fn shell_check(shell: &str) -> DoctorCheck {
+    let title = "Terminal shell";
+    if !std::path::Path::new(shell).is_file() {
+        return check(
+            "shell",
+            title,
+            CheckStatus::Fail,
+            format!("{} does not exist", shell),
+        )
+        .fix("Choose an installed shell in the settings", None, None);
+    }
+    match run_login_shell(shell, "exit 0") {
+        None => check(
+            "shell",
+            title,
+            CheckStatus::Fail,
+            format!(
+                "{} -l failed or didn't finish within {}s; check your shell startup files",
+                shell,
+                SHELL_TIMEOUT.as_secs()
+            ),
+        ),
+        Some((_, took)) if took > SLOW_SHELL => check(
+            "shell",
+            title,
+            CheckStatus::Warn,
+            format!(
+                "{} takes {:.1}s to start, so every new terminal waits that long",
+                shell,
+                took.as_secs_f64()
+            ),
+        )
+        .fix(
+            "Profile your shell startup files",
+            Some(&format!("time {} -lic exit", shell)),
+            None,
+        ),
+        Some((_, took)) => check(
+            "shell",
+            title,
+            CheckStatus::Pass,
+            format!("{} starts in {:.1}s", shell, took.as_secs_f64()),
+        ),
+    }
+}
+
+/// Launched from Finder or the Dock, the app gets a minimal PATH instead of the one the user's
+/// shell sets up
+fn path_check(shell: &str) -> DoctorCheck {
+    let title = "PATH";
+    let app_path = std::env::var("PATH").unwrap_or_default();
+    let Some((shell_path, _)) = run_login_shell(shell, "printf %s \"$PATH\"") else {
+        return check(
+            "path",
+            title,
+            CheckStatus::Warn,
+            "Couldn't read the PATH your login shell sets".to_string(),
+        );
+    };
+    let app_dirs: Vec<&str> = app_path.split(':').collect();
+    let missing: Vec<&str> = shell_path
+        .split(':')
+        .filter(|dir| !dir.is_empty() && !app_dirs.contains(dir))
+        .filter(|dir| std::path::Path::new(dir).is_dir())
+        .collect();
+    if missing.is_empty() {
+        return check(
+            "path",
+            title,
+            CheckStatus::Pass,
+            "The app's PATH includes everything your login shell adds".to_string(),
+        );
+    }
+    let detail = format!(
+        "The app was started with a shorter PATH than your shell's, probably from Finder or the \
+         Dock. Tools in {} are found through fallbacks, but programs they start may not be.",
+        missing.join(", ")
+    );
+    let check = check("path", title, CheckStatus::Warn, detail);
+    if cfg!(target_os = "macos") {
+        let command = format!("sudo launchctl config user path '{}'", shell_path);
+        check.fix(
+            "Give apps your shell's PATH (takes effect after a restart)",
+            Some(&command),
+            None,
+        )
+    } else {
+        check.fix("Start the app from a terminal", None, None)
+    }
+}
+
+/// The filesystem watcher needs one inotify watch per directory on Linux
+fn watch_limit_check() -> DoctorCheck {
+    let title = "File watch limit";
+    if !cfg!(target_os = "linux") {
+        return check(
+            "watch-limit",
+            title,
+            CheckStatus::Pass,
+            "File events come from the OS without a per-directory limit".to_string(),
+        );
+    }
+    let limit = std::fs::read_to_string("/proc/sys/fs/inotify/max_user_watches")
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok());
+    match limit {
+        Some(limit) if limit < MIN_INOTIFY_WATCHES => check(
+            "watch-limit",
+            title,
+            CheckStatus::Warn,
+            format!(
+                "fs.inotify.max_user_watches is {}; large repositories won't be watched completely",
+                limit
+            ),
+        )
+        .fix(
+            "Raise the limit",
+            Some(
+                "echo fs.inotify.max_user_watches=524288 | sudo tee /etc/sysctl.d/60-inotify.conf && sudo sysctl --system",
+            ),
+            None,
+        ),
+        Some(limit) => check(
+            "watch-limit",
+            title,
+            CheckStatus::Pass,
+            format!("fs.inotify.max_user_watches is {}", limit),
+        ),
+        None => check(
+            "watch-limit",
+            title,
+            CheckStatus::Warn,
+            "Couldn't read fs.inotify.max_user_watches".to_string(),
+        ),
+    }
+}
+
+fn disk_check() -> DoctorCheck {
+    let title = "Disk space";
+    let home = crate::get_home_dir();
+    let Some((_, free)) = crate::system_info::disk_space(&home) else {
+        return check(
+            "disk",
+            title,
+            CheckStatus::Warn,
+            "Couldn't read the free space of your home volume".to_string(),
+        );
+    };
+    let gb = free as f64 / (1024.0 * 1024.0 * 1024.0);
+    let detail = format!("{:.1} GB free on the volume holding {}", gb, home);
+    let status = if free < CRITICAL_DISK_BYTES {
+        CheckStatus::Fail
+    } else if free < LOW_DISK_BYTES {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Pass
+    };
+    // Checkpoints, indexes and logs live under ~/.ade
+    check("disk", title, status, detail).fix(
+        "Free up space; the app's own data is in ~/.ade",
+        Some("du -sh ~/.ade/*"),
+        None,
+    )
+}
+
+/// Check the tools, shell, PATH, watch limits and disk space the app relies on. Each result
+/// says pass, warn or fail, why, and how to fix it.
+#[tauri::command]
+pub async fn run_doctor() -> Result<Vec<DoctorCheck>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        // Re-checking after following a fix should see its effect
+        crate::which::invalidate();
+        let shell = login_shell();
+        // Version probes and shell startups are slow, so the checks run side by side
+        std::thread::scope(|scope| {
+            let checks = [
+                scope.spawn(git_check),
+                scope.spawn(node_check),
+                scope.spawn(claude_check),
+                scope.spawn(|| shell_check(&shell)),
+                scope.spawn(|| path_check(&shell)),
+                scope.spawn(watch_limit_check),
+                scope.spawn(disk_check),
+            ];
+            checks
+                .into_iter()
+                .filter_map(|check| check.join().ok())
+                .collect()
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
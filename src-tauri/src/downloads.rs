@@ -0,0 +1,324 @@
+//! File downloads with progress, cancellation and resume. Data goes to `<dest>.part` and is
+//! renamed into place once complete and verified, so an interrupted download picks up where it
+//! stopped on the next attempt with a Range request. The ETag (or Last-Modified) the partial
+//! file came with is kept beside it and sent as If-Range, so a file that changed on the server
+//! since is downloaded whole rather than spliced. Also used by the updater.
+
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::ipc::Channel;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long the server may go quiet, waiting for the response or between reads, before the
+/// download fails. The blocking client applies its timeout to each wait, not the whole body.
+const STALL_TIMEOUT: Duration = Duration::from_secs(30);
+/// Progress events are sent at most this often
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Default, serde::Deserialize)]
+pub struct DownloadOptions {
+    /// Hex SHA-256 the file must match; a mismatch discards it
+    sha256: Option<String>,
+    /// Continue from a partial download left by an earlier attempt (true by default)
+    resume: Option<bool>,
+    /// Replace `dest` if it exists
+    overwrite: Option<bool>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum DownloadEvent {
+    #[serde(rename = "started")]
+    Started {
+        total_bytes: Option<u64>,
+        /// Bytes kept from an earlier attempt
+        resumed_from: u64,
+    },
+    #[serde(rename = "progress")]
+    Progress {
+        downloaded_bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    #[serde(rename = "finished")]
+    Finished { path: String, sha256: String },
+    #[serde(rename = "failed")]
+    Failed { message: String },
+    #[serde(rename = "cancelled")]
+    Cancelled {},
+}
+
+#[derive(Clone)]
+pub struct DownloadManager {
+    /// Cancel flags of downloads in progress
+    active: Arc<Mutex<HashMap<u32, Arc<AtomicBool>>>>,
+    next_id: Arc<Mutex<u32>>,
+}
+
+impl DownloadManager {
+    pub fn new() -> Self {
+        Self {
+            active: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+}
+
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+/// Where the validator of `<dest>.part` is kept
+fn validator_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part.validator");
+    dest.with_file_name(name)
+}
+
+/// What identifies this version of the file for If-Range: a strong ETag, else Last-Modified
+fn validator(response: &reqwest::blocking::Response) -> Option<String> {
+    let header = |name| response.headers().get(name)?.to_str().ok();
+    header(reqwest::header::ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(reqwest::header::LAST_MODIFIED))
+        .map(str::to_string)
+}
+
+/// Whether a 206 response starts at `offset`, as a resumed download needs
+fn starts_at(response: &reqwest::blocking::Response, offset: u64) -> bool {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|range| range.to_str().ok())
+        .and_then(|range| range.strip_prefix("bytes "))
+        .and_then(|range| range.split_once('-'))
+        .and_then(|(start, _)| start.trim().parse::<u64>().ok())
+        == Some(offset)
+}
+
+/// Feed what an earlier attempt downloaded into `hasher`; its length
+fn hash_existing(path: &Path, hasher: &mut Sha256) -> Result<u64, String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    std::io::copy(&mut file, hasher)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// Download `url` to `dest`, resuming from `<dest>.part` when `resume` is set, sending
+/// `started` and `progress` events. The hex SHA-256 of the file, or None when `cancel` was set.
+pub(crate) fn fetch(
+    url: &str,
+    dest: &Path,
+    expected_sha256: Option<&str>,
+    resume: bool,
+    cancel: &AtomicBool,
+    on_event: &mut dyn FnMut(DownloadEvent),
+) -> Result<Option<String>, String> {
+    let partial = partial_path(dest);
+    let client = reqwest::blocking::Client::builder()
+        .user_agent(concat!("better-terminal/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(STALL_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    // Without a validator there is no telling whether the partial file is of this version
+    let saved_validator = std::fs::read_to_string(validator_path(dest)).ok();
+    let mut offset = match (resume, &saved_validator) {
+        (true, Some(_)) => std::fs::metadata(&partial).map_or(0, |m| m.len()),
+        _ => 0,
+    };
+    let mut request = client.get(url);
+    if let (true, Some(validator)) = (offset > 0, &saved_validator) {
+        request = request
+            .header(reqwest::header::RANGE, format!("bytes={}-", offset))
+            .header(reqwest::header::IF_RANGE, validator.trim());
+    }
+    let mut response = request
+        .send()
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE
+        || (resumed && !starts_at(&response, offset))
+    {
+        // The partial file is no prefix of this one (or already whole); start over
+        response = client
+            .get(url)
+            .send()
+            .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    }
+    if !response.status().is_success() {
+        return Err(format!("Failed to download {}: {}", url, response.status()));
+    }
+    // A server that ignores the Range header, or whose file changed, sends the whole file
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        offset = 0;
+        let path = validator_path(dest);
+        match validator(&response) {
+            Some(validator) => std::fs::write(&path, validator)
+                .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?,
+            None => {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    let mut hasher = Sha256::new();
+    let mut file = match offset {
+        0 => std::fs::File::create(&partial),
+        _ => {
+            hash_existing(&partial, &mut hasher)?;
+            std::fs::OpenOptions::new().append(true).open(&partial)
+        }
+    }
+    .map_err(|e| format!("Failed to create {}: {}", partial.display(), e))?;
+    let total_bytes = response.content_length().map(|len| len + offset);
+    on_event(DownloadEvent::Started {
+        total_bytes,
+        resumed_from: offset,
+    });
+
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut downloaded_bytes = offset;
+    let mut last_progress = Instant::now();
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let n = response
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n])
+            .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+        hasher.update(&buf[..n]);
+        downloaded_bytes += n as u64;
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            on_event(DownloadEvent::Progress {
+                downloaded_bytes,
+                total_bytes,
+            });
+        }
+    }
+    file.flush()
+        .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+    if total_bytes.is_some_and(|total| total != downloaded_bytes) {
+        return Err(format!("Download of {} was cut short", url));
+    }
+
+    let sha256 = format!("{:x}", hasher.finalize());
+    if let Some(expected) = expected_sha256 {
+        if !sha256.eq_ignore_ascii_case(expected.trim()) {
+            let _ = std::fs::remove_file(&partial);
+            let _ = std::fs::remove_file(validator_path(dest));
+            return Err(format!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                url, expected, sha256
+            ));
+        }
+    }
+    std::fs::rename(&partial, dest)
+        .map_err(|e| format!("Failed to save {}: {}", dest.display(), e))?;
+    let _ = std::fs::remove_file(validator_path(dest));
+    Ok(Some(sha256))
+}
+
+/// The file to write: `dest` itself, or the URL's file name inside it when it's a directory
+fn resolve_dest(url: &reqwest::Url, dest: &str) -> Result<PathBuf, String> {
+    let dest = match dest.strip_prefix("~/") {
+        Some(rest) => Path::new(&crate::get_home_dir()).join(rest),
+        None => PathBuf::from(dest),
+    };
+    if !dest.is_absolute() {
+        return Err(format!("Not an absolute path: {}", dest.display()));
+    }
+    if !dest.is_dir() {
+        return Ok(dest);
+    }
+    let name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty() && *name != "..")
+        .ok_or_else(|| format!("No file name in {}; give the full path to save to", url))?;
+    Ok(dest.join(name))
+}
+
+/// Start downloading `url` to `dest` (a file, or a directory to save into under the URL's file
+/// name) and return its id for cancel_download. Progress and the outcome arrive on `on_event`.
+#[tauri::command]
+pub fn download_file(
+    state: tauri::State<'_, DownloadManager>,
+    url: String,
+    dest: String,
+    options: Option<DownloadOptions>,
+    on_event: Channel<DownloadEvent>,
+) -> Result<u32, String> {
+    let options = options.unwrap_or_default();
+    let parsed = reqwest::Url::parse(&url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!(
+            "Only http and https URLs can be downloaded: {}",
+            url
+        ));
+    }
+    let dest = resolve_dest(&parsed, &dest)?;
+    if dest.exists() && !options.overwrite.unwrap_or(false) {
+        return Err(format!("{} already exists", dest.display()));
+    }
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let id = {
+        let mut next = state.next_id.lock().unwrap();
+        let id = *next;
+        *next += 1;
+        id
+    };
+    let cancel = Arc::new(AtomicBool::new(false));
+    state.active.lock().unwrap().insert(id, cancel.clone());
+
+    let active = state.active.clone();
+    crate::crash::spawn("downloads", format!("download:{}", id), move || {
+        let mut send = |event| {
+            let _ = on_event.send(event);
+        };
+        let result = fetch(
+            &url,
+            &dest,
+            options.sha256.as_deref(),
+            options.resume.unwrap_or(true),
+            &cancel,
+            &mut send,
+        );
+        active.lock().unwrap().remove(&id);
+        send(match result {
+            Ok(Some(sha256)) => DownloadEvent::Finished {
+                path: dest.to_string_lossy().to_string(),
+                sha256,
+            },
+            Ok(None) => DownloadEvent::Cancelled {},
+            Err(message) => DownloadEvent::Failed { message },
+        });
+        Ok(())
+    });
+    Ok(id)
+}
+
+/// Stop a download; what it fetched so far is kept for a later resume
+#[tauri::command]
+pub fn cancel_download(state: tauri::State<'_, DownloadManager>, id: u32) {
+    if let Some(cancel) = state.active.lock().unwrap().get(&id) {
+        cancel.store(true, Ordering::Relaxed);
+    }
+}
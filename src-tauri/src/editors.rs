@@ -0,0 +1,284 @@
+//! Handing files to other apps: revealing them in the platform's file manager and opening them
+//! in the user's editor, at a line when the editor supports it. GUI editors are launched
+//! detached; terminal editors run in a new PTY the frontend attaches to.
+
+use crate::pty::{PtyManager, PtySink};
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// How an editor is told where to put the cursor
+#[derive(Clone, Copy)]
+enum Goto {
+    /// `--goto file:line:column`
+    Flag,
+    /// `file:line:column`
+    Suffix,
+    /// `--line line file`
+    LineFlag,
+    /// `+line file`
+    Plus,
+}
+
+struct Editor {
+    /// Name used in settings and by open_in_editor
+    name: &'static str,
+    program: &'static str,
+    /// Runs inside a terminal rather than opening its own window
+    terminal: bool,
+    goto: Goto,
+    /// macOS app to fall back to when the command line launcher isn't installed
+    mac_app: Option<&'static str>,
+}
+
+const fn gui(
+    name: &'static str,
+    program: &'static str,
+    goto: Goto,
+    mac_app: &'static str,
+) -> Editor {
+    Editor {
+        name,
+        program,
+        terminal: false,
+        goto,
+        mac_app: Some(mac_app),
+    }
+}
+
+const fn tui(name: &'static str, program: &'static str, goto: Goto) -> Editor {
+    Editor {
+        name,
+        program,
+        terminal: true,
+        goto,
+        mac_app: None,
+    }
+}
+
+const EDITORS: &[Editor] = &[
+    gui("vscode", "code", Goto::Flag, "Visual Studio Code"),
+    gui("cursor", "cursor", Goto::Flag, "Cursor"),
+    gui("windsurf", "windsurf", Goto::Flag, "Windsurf"),
+    gui("zed", "zed", Goto::Suffix, "Zed"),
+    gui("sublime", "subl", Goto::Suffix, "Sublime Text"),
+    gui("idea", "idea", Goto::LineFlag, "IntelliJ IDEA"),
+    tui("vim", "vim", Goto::Plus),
+    tui("nvim", "nvim", Goto::Plus),
+    tui("helix", "hx", Goto::Suffix),
+    tui("nano", "nano", Goto::Plus),
+    tui("emacs", "emacs", Goto::Plus),
+];
+
+#[derive(Default, serde::Deserialize)]
+pub struct OpenInEditorOptions {
+    /// An editor from list_editors; the `editor` setting, else the system default app,
+    /// when omitted
+    editor: Option<String>,
+    /// 1-based
+    line: Option<u32>,
+    column: Option<u32>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(tag = "type")]
+pub enum OpenedIn {
+    /// Launched in its own window
+    #[serde(rename = "external")]
+    External { editor: String },
+    /// Running in a new PTY; attach_pty shows it
+    #[serde(rename = "terminal")]
+    Terminal { editor: String, pty_id: u32 },
+}
+
+#[derive(serde::Serialize)]
+pub struct EditorInfo {
+    name: String,
+    program: String,
+    terminal: bool,
+    installed: bool,
+}
+
+fn launch(program: &Path, args: &[String]) -> Result<(), String> {
+    let mut child = std::process::Command::new(program)
+        .args(args)
+        // Launchers like `code` are scripts that look up their runtime on PATH
+        .env("PATH", crate::which::full_path())
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program.display(), e))?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Run a system tool that ships with the platform
+fn launch_system(program: &str, args: &[&str]) -> Result<(), String> {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    launch(Path::new(program), &args)
+}
+
+fn goto_args(goto: Goto, path: &str, line: Option<u32>, column: Option<u32>) -> Vec<String> {
+    let Some(line) = line else {
+        return vec![path.to_string()];
+    };
+    let position = match column {
+        Some(column) => format!("{}:{}:{}", path, line, column),
+        None => format!("{}:{}", path, line),
+    };
+    match goto {
+        Goto::Flag => vec!["--goto".to_string(), position],
+        Goto::Suffix => vec![position],
+        Goto::LineFlag => vec!["--line".to_string(), line.to_string(), path.to_string()],
+        Goto::Plus => vec![format!("+{}", line), path.to_string()],
+    }
+}
+
+/// Open `path` with the app the platform associates with it
+fn open_default(path: &str) -> Result<(), String> {
+    if cfg!(target_os = "macos") {
+        launch_system("/usr/bin/open", &[path])
+    } else if cfg!(windows) {
+        launch_system("explorer", &[path])
+    } else {
+        launch_system("xdg-open", &[path])
+    }
+}
+
+/// Show `path` selected in Finder, Explorer or the Linux file manager. Linux file managers
+/// that don't implement the FileManager1 interface open the containing folder instead.
+#[tauri::command]
+pub fn reveal_in_file_manager(path: String) -> Result<(), String> {
+    let target = Path::new(&path);
+    if !target.exists() {
+        return Err(format!("No such file: {}", path));
+    }
+    if cfg!(target_os = "macos") {
+        return launch_system("/usr/bin/open", &["-R", &path]);
+    }
+    if cfg!(windows) {
+        return launch_system("explorer", &[&format!("/select,{}", path)]);
+    }
+    let uri =
+        url::Url::from_file_path(target).map_err(|_| format!("Not an absolute path: {}", path))?;
+    let shown = std::process::Command::new("dbus-send")
+        .args([
+            "--session",
+            "--print-reply",
+            "--dest=org.freedesktop.FileManager1",
+            "--type=method_call",
+            "/org/freedesktop/FileManager1",
+            "org.freedesktop.FileManager1.ShowItems",
+            &format!("array:string:{}", uri),
+            "string:",
+        ])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success());
+    if shown {
+        return Ok(());
+    }
+    let folder = match target.is_dir() {
+        true => target,
+        false => target.parent().unwrap_or(target),
+    };
+    open_default(&folder.to_string_lossy())
+}
+
+/// Open `path` in an editor: the one asked for, else the `editor` setting, else the system
+/// default app. Only editors list_editors knows are run. Terminal editors like vim start in a
+/// new PTY, whose id is returned for attach_pty.
+#[tauri::command]
+pub async fn open_in_editor(
+    pty: tauri::State<'_, PtyManager>,
+    path: String,
+    options: Option<OpenInEditorOptions>,
+) -> Result<OpenedIn, String> {
+    let options = options.unwrap_or_default();
+    if !Path::new(&path).exists() {
+        return Err(format!("No such file: {}", path));
+    }
+    let lookup_path = path.clone();
+    let requested = options.editor.clone();
+    let found = tauri::async_runtime::spawn_blocking(move || {
+        let name = requested.or_else(|| {
+            let root = crate::project_settings::project_root_for(&lookup_path);
+            crate::project_settings::load(root.as_deref()).editor
+        });
+        let Some(name) = name else {
+            return Ok(None);
+        };
+        let editor = EDITORS
+            .iter()
+            .find(|e| e.name == name)
+            .ok_or_else(|| format!("Unknown editor: {}", name))?;
+        Ok::<_, String>(Some((editor, crate::which::find(editor.program))))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let Some((editor, program)) = found else {
+        open_default(&path)?;
+        return Ok(OpenedIn::External {
+            editor: "default".to_string(),
+        });
+    };
+    let name = editor.name.to_string();
+    let Some(program) = program else {
+        // The app may be installed without its command line launcher
+        if let Some(app) = editor.mac_app.filter(|_| cfg!(target_os = "macos")) {
+            launch_system("/usr/bin/open", &["-a", app, &path])?;
+            return Ok(OpenedIn::External { editor: name });
+        }
+        return Err(format!("{} is not installed", name));
+    };
+    let args = goto_args(editor.goto, &path, options.line, options.column);
+
+    if !editor.terminal {
+        launch(&program, &args)?;
+        return Ok(OpenedIn::External { editor: name });
+    }
+    let cwd = Path::new(&path)
+        .parent()
+        .map(|dir| dir.to_string_lossy().to_string());
+    let file_name = Path::new(&path)
+        .file_name()
+        .map_or(path.clone(), |n| n.to_string_lossy().to_string());
+    let pty_id = pty.spawn(
+        24,
+        80,
+        cwd,
+        Some(format!("{} {}", name, file_name)),
+        None,
+        PtySink::Buffer(VecDeque::new()),
+    )?;
+    // exec, so the terminal closes with the editor
+    let command: Vec<String> = std::iter::once(program.to_string_lossy().to_string())
+        .chain(args)
+        .map(|arg| crate::shell_quote(&arg))
+        .collect();
+    pty.write(pty_id, format!("exec {}\r", command.join(" ")).as_bytes())?;
+    Ok(OpenedIn::Terminal {
+        editor: name,
+        pty_id,
+    })
+}
+
+/// The editors open_in_editor knows, and whether each is installed
+#[tauri::command]
+pub async fn list_editors() -> Result<Vec<EditorInfo>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        EDITORS
+            .iter()
+            .map(|editor| EditorInfo {
+                name: editor.name.to_string(),
+                program: editor.program.to_string(),
+                terminal: editor.terminal,
+                installed: crate::which::find(editor.program).is_some(),
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,347 @@
+//! Reading and editing .env files without handing their values to the frontend. Values come
+//! back masked; entries sent back without a value keep the one on disk, so a file can be edited
+//! and saved without its secrets ever being revealed.
+
+use std::io::Write;
+use std::path::Path;
+
+/// One line of a .env file (a quoted value may span several)
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type")]
+pub enum EnvLine {
+    #[serde(rename = "entry")]
+    Entry {
+        /// Position in the file as read; None for lines added in the editor
+        #[serde(default)]
+        line: Option<usize>,
+        key: String,
+        /// None when masked. Sent back as None, the value on disk is kept.
+        #[serde(default)]
+        value: Option<String>,
+        /// Written as `export KEY=value`
+        #[serde(default)]
+        export: bool,
+        #[serde(default)]
+        masked: bool,
+    },
+    #[serde(rename = "comment")]
+    Comment {
+        #[serde(default)]
+        line: Option<usize>,
+        /// None for a commented-out assignment, which may hold a secret too
+        #[serde(default)]
+        text: Option<String>,
+        /// Variable of a commented-out assignment
+        #[serde(default)]
+        key: Option<String>,
+    },
+    #[serde(rename = "blank")]
+    Blank {},
+    /// A line that isn't valid dotenv syntax, kept as it is. Its text is withheld.
+    #[serde(rename = "invalid")]
+    Invalid { line: usize },
+}
+
+#[derive(serde::Serialize)]
+pub struct EnvFile {
+    path: String,
+    exists: bool,
+    lines: Vec<EnvLine>,
+}
+
+enum Kind {
+    Entry {
+        key: String,
+        value: String,
+        export: bool,
+    },
+    Comment {
+        /// Variable, when the comment is a commented-out assignment with a value
+        assignment: Option<String>,
+    },
+    Blank,
+    Invalid,
+}
+
+struct Parsed {
+    /// The line as written, so unchanged lines are saved exactly as they were
+    raw: String,
+    kind: Kind,
+}
+
+fn resolve(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("{}/{}", crate::get_home_dir(), rest),
+        None => path.to_string(),
+    }
+}
+
+fn is_key(key: &str) -> bool {
+    key.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+/// `[export] KEY=rest` split into (key, export, rest)
+fn split_assignment(text: &str) -> Option<(&str, bool, &str)> {
+    let text = text.trim_start();
+    let (export, text) = match text.strip_prefix("export ") {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, text),
+    };
+    let (key, rest) = text.split_once('=')?;
+    let key = key.trim_end();
+    is_key(key).then_some((key, export, rest.trim_start()))
+}
+
+/// The value at the start of `text`, or None when a quote is still open
+fn parse_value(text: &str) -> Option<String> {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some('"') => {
+            let mut value = String::new();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => return Some(value),
+                    '\\' => match chars.next()? {
+                        'n' => value.push('\n'),
+                        'r' => value.push('\r'),
+                        't' => value.push('\t'),
+                        other => value.push(other),
+                    },
+                    c => value.push(c),
+                }
+            }
+            None
+        }
+        Some(quote @ ('\'' | '`')) => {
+            let rest = chars.as_str();
+            rest.find(quote).map(|end| rest[..end].to_string())
+        }
+        _ => {
+            // An unquoted value ends at a ` #` comment
+            let end = text
+                .char_indices()
+                .find(|&(i, c)| c == '#' && (i == 0 || text[..i].ends_with([' ', '\t'])))
+                .map_or(text.len(), |(i, _)| i);
+            Some(text[..end].trim_end().to_string())
+        }
+    }
+}
+
+fn parse(content: &str) -> Vec<Parsed> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut parsed = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+        let trimmed = line.trim();
+        let kind = if trimmed.is_empty() {
+            Kind::Blank
+        } else if let Some(comment) = trimmed.strip_prefix('#') {
+            let assignment = split_assignment(comment)
+                .filter(|(_, _, rest)| parse_value(rest).is_some_and(|v| !v.is_empty()))
+                .map(|(key, _, _)| key.to_string());
+            Kind::Comment { assignment }
+        } else if let Some((key, export, rest)) = split_assignment(line) {
+            // A quoted value may run over several lines
+            let mut raw = line.to_string();
+            let mut text = rest.to_string();
+            let mut end = i;
+            let mut value = parse_value(&text);
+            while value.is_none() && end < lines.len() {
+                raw = format!("{}\n{}", raw, lines[end]);
+                text = format!("{}\n{}", text, lines[end]);
+                end += 1;
+                value = parse_value(&text);
+            }
+            if let Some(value) = value {
+                i = end;
+                parsed.push(Parsed {
+                    raw,
+                    kind: Kind::Entry {
+                        key: key.to_string(),
+                        value,
+                        export,
+                    },
+                });
+                continue;
+            }
+            // A quote that never closes: keep the line and read on from the next one
+            Kind::Invalid
+        } else {
+            Kind::Invalid
+        };
+        parsed.push(Parsed {
+            raw: line.to_string(),
+            kind,
+        });
+    }
+    parsed
+}
+
+/// `KEY=value`, quoted when the value wouldn't read back as written otherwise
+fn format_entry(key: &str, value: &str, export: bool) -> String {
+    let prefix = if export { "export " } else { "" };
+    // Whitespace is quoted too, so the file can still be sourced by a shell
+    let plain = !value.contains([' ', '\t', '#', '"', '\'', '`', '\\', '\n', '\r']);
+    let value = if plain {
+        value.to_string()
+    } else if !value.contains(['\'', '\n', '\r']) {
+        format!("'{}'", value)
+    } else {
+        let escaped = value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+        format!("\"{}\"", escaped)
+    };
+    format!("{}{}={}", prefix, key, value)
+}
+
+fn read_parsed(path: &str) -> Result<Option<Vec<Parsed>>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(parse(&content))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read {}: {}", path, e)),
+    }
+}
+
+/// The lines of a dotenv file with every non-empty value masked. A missing file reads as empty.
+#[tauri::command]
+pub fn read_env_file(path: String) -> Result<EnvFile, String> {
+    let resolved = resolve(&path);
+    let parsed = read_parsed(&resolved)?;
+    let exists = parsed.is_some();
+    let lines = parsed
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .map(|(line, parsed)| match parsed.kind {
+            Kind::Entry { key, value, export } => EnvLine::Entry {
+                line: Some(line),
+                key,
+                masked: !value.is_empty(),
+                value: Some(value).filter(String::is_empty),
+                export,
+            },
+            Kind::Comment { assignment } => EnvLine::Comment {
+                line: Some(line),
+                text: assignment.is_none().then_some(parsed.raw),
+                key: assignment,
+            },
+            Kind::Blank => EnvLine::Blank {},
+            Kind::Invalid => EnvLine::Invalid { line },
+        })
+        .collect();
+    Ok(EnvFile {
+        path: resolved,
+        exists,
+        lines,
+    })
+}
+
+/// The value of `key`, for when the user explicitly asks to see it
+#[tauri::command]
+pub fn reveal_env_value(path: String, key: String) -> Result<Option<String>, String> {
+    let parsed = read_parsed(&resolve(&path))?.unwrap_or_default();
+    Ok(parsed
+        .into_iter()
+        .rev()
+        .find_map(|parsed| match parsed.kind {
+            Kind::Entry { key: k, value, .. } if k == key => Some(value),
+            _ => None,
+        }))
+}
+
+/// Replace the file with `entries`, in order. Entries without a value, and comments and invalid
+/// lines without text, keep what is on disk at their `line`. New files are readable by the
+/// owner only. Returns the file as read_env_file would.
+#[tauri::command]
+pub fn write_env_file(path: String, entries: Vec<EnvLine>) -> Result<EnvFile, String> {
+    let resolved = resolve(&path);
+    let current = read_parsed(&resolved)?.unwrap_or_default();
+    let changed = || format!("{} changed on disk; reload it before saving", path);
+    let mut out: Vec<String> = Vec::new();
+    for entry in entries {
+        match entry {
+            EnvLine::Entry {
+                line,
+                key,
+                value,
+                export,
+                ..
+            } => {
+                if !is_key(&key) {
+                    return Err(format!("Invalid variable name: {}", key));
+                }
+                if let Some(value) = value {
+                    out.push(format_entry(&key, &value, export));
+                    continue;
+                }
+                let is_key_entry = |parsed: &&Parsed| matches!(&parsed.kind, Kind::Entry { key: k, .. } if *k == key);
+                let original = line
+                    .and_then(|line| current.get(line))
+                    .filter(is_key_entry)
+                    .or_else(|| current.iter().rev().find(is_key_entry))
+                    .ok_or_else(|| format!("No value for {} in {}", key, path))?;
+                match &original.kind {
+                    Kind::Entry {
+                        value,
+                        export: was_exported,
+                        ..
+                    } if *was_exported != export => out.push(format_entry(&key, value, export)),
+                    _ => out.push(original.raw.clone()),
+                }
+            }
+            EnvLine::Comment {
+                text: Some(text), ..
+            } => {
+                for text in text.lines() {
+                    match text.trim_start().starts_with('#') {
+                        true => out.push(text.to_string()),
+                        false => out.push(format!("# {}", text)),
+                    }
+                }
+            }
+            EnvLine::Comment {
+                line, text: None, ..
+            } => match line.and_then(|line| current.get(line)) {
+                Some(parsed) if matches!(parsed.kind, Kind::Comment { .. }) => {
+                    out.push(parsed.raw.clone())
+                }
+                _ => return Err(changed()),
+            },
+            EnvLine::Blank {} => out.push(String::new()),
+            EnvLine::Invalid { line } => match current.get(line) {
+                Some(parsed) if matches!(parsed.kind, Kind::Invalid) => {
+                    out.push(parsed.raw.clone())
+                }
+                _ => return Err(changed()),
+            },
+        }
+    }
+
+    let mut content = out.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    if let Some(parent) = Path::new(&resolved).parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(&resolved)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", resolved, e))?;
+    read_env_file(path)
+}
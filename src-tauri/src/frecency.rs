@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Number of recent accesses kept per file for scoring
+const MAX_VISITS: usize = 10;
+/// Entries kept per project root; the lowest-scoring ones are evicted first
+const MAX_ENTRIES_PER_ROOT: usize = 2000;
+/// Minimum interval between disk writes triggered by watcher edits
+const SAVE_INTERVAL_MS: u64 = 5000;
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessKind {
+    Open,
+    Edit,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct Visit {
+    at: u64,
+    kind: AccessKind,
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+struct FileStats {
+    count: u64,
+    visits: Vec<Visit>,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct FrecencyData {
+    roots: HashMap<String, HashMap<String, FileStats>>,
+}
+
+struct FrecencyStore {
+    data: FrecencyData,
+    dirty: bool,
+    last_saved: u64,
+}
+
+impl FrecencyStore {
+    fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        let path = crate::ade_path("frecency.json");
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string(&self.data) {
+            if std::fs::write(&path, json).is_ok() {
+                self.dirty = false;
+                self.last_saved = crate::now_ms();
+            }
+        }
+    }
+}
+
+/// Cloneable handle for recording file accesses from other subsystems (e.g. the index watcher)
+#[derive(Clone)]
+pub struct FrecencyRecorder {
+    store: Arc<Mutex<FrecencyStore>>,
+}
+
+impl FrecencyRecorder {
+    pub fn record(&self, root: &str, path: &str, kind: AccessKind) {
+        let now = crate::now_ms();
+        let mut store = self.store.lock().unwrap();
+        let files = store.data.roots.entry(root.to_string()).or_default();
+        let stats = files.entry(path.to_string()).or_default();
+
+        // Editors save in bursts; collapse repeated accesses within a minute into one visit
+        let is_burst = stats
+            .visits
+            .last()
+            .map(|v| v.kind == kind && now.saturating_sub(v.at) < 60_000)
+            .unwrap_or(false);
+        if !is_burst {
+            stats.count += 1;
+            stats.visits.push(Visit { at: now, kind });
+            if stats.visits.len() > MAX_VISITS {
+                stats.visits.remove(0);
+            }
+        }
+
+        if files.len() > MAX_ENTRIES_PER_ROOT {
+            let mut scored: Vec<(String, f64)> = files
+                .iter()
+                .map(|(p, s)| (p.clone(), score(s, now)))
+                .collect();
+            scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+            for (p, _) in scored.into_iter().take(files.len() - MAX_ENTRIES_PER_ROOT) {
+                files.remove(&p);
+            }
+        }
+
+        store.dirty = true;
+        if now.saturating_sub(store.last_saved) >= SAVE_INTERVAL_MS {
+            store.save();
+        }
+    }
+
+    pub fn forget(&self, root: &str, path: &str) {
+        let mut store = self.store.lock().unwrap();
+        if let Some(files) = store.data.roots.get_mut(root) {
+            if files.remove(path).is_some() {
+                store.dirty = true;
+            }
+        }
+    }
+}
+
+pub struct FrecencyManager {
+    recorder: FrecencyRecorder,
+}
+
+/// Write out what changed since the last save, on exit
+pub fn flush(app: &tauri::AppHandle) {
+    use tauri::Manager;
+    let manager = app.state::<FrecencyManager>();
+    manager.recorder.store.lock().unwrap().save();
+}
+
+impl FrecencyManager {
+    pub fn new() -> Self {
+        let data = std::fs::read_to_string(crate::ade_path("frecency.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            recorder: FrecencyRecorder {
+                store: Arc::new(Mutex::new(FrecencyStore {
+                    data,
+                    dirty: false,
+                    last_saved: 0,
+                })),
+            },
+        }
+    }
+
+    pub fn recorder(&self) -> FrecencyRecorder {
+        self.recorder.clone()
+    }
+}
+
+/// Firefox-style frecency: total access count times the average recency weight of recent visits
+fn score(stats: &FileStats, now: u64) -> f64 {
+    if stats.visits.is_empty() {
+        return 0.0;
+    }
+    let hour = 60 * 60 * 1000;
+    let weights: f64 = stats
+        .visits
+        .iter()
+        .map(|v| {
+            let age = now.saturating_sub(v.at);
+            let recency = if age < 4 * hour {
+                100.0
+            } else if age < 24 * hour {
+                70.0
+            } else if age < 3 * 24 * hour {
+                50.0
+            } else if age < 7 * 24 * hour {
+                30.0
+            } else if age < 30 * 24 * hour {
+                10.0
+            } else {
+                2.0
+            };
+            // Edits signal stronger intent than a passing open
+            match v.kind {
+                AccessKind::Edit => recency * 1.5,
+                AccessKind::Open => recency,
+            }
+        })
+        .sum();
+    stats.count as f64 * weights / stats.visits.len() as f64
+}
+
+#[derive(serde::Serialize)]
+pub struct FrequentFile {
+    path: String,
+    score: f64,
+    count: u64,
+    last_access: u64,
+}
+
+#[tauri::command]
+pub fn record_file_access(
+    state: tauri::State<'_, FrecencyManager>,
+    root: String,
+    path: String,
+    kind: Option<AccessKind>,
+) -> Result<(), String> {
+    state
+        .recorder
+        .record(&root, &path, kind.unwrap_or(AccessKind::Open));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_frequent_files(
+    state: tauri::State<'_, FrecencyManager>,
+    root: String,
+    limit: Option<usize>,
+) -> Result<Vec<FrequentFile>, String> {
+    let now = crate::now_ms();
+    let mut store = state.recorder.store.lock().unwrap();
+    store.save();
+
+    let mut files: Vec<FrequentFile> = store
+        .data
+        .roots
+        .get(&root)
+        .map(|files| {
+            files
+                .iter()
+                // Files deleted since they were recorded are not worth suggesting
+                .filter(|(path, _)| std::path::Path::new(path).exists())
+                .map(|(path, stats)| FrequentFile {
+                    path: path.clone(),
+                    score: score(stats, now),
+                    count: stats.count,
+                    last_access: stats.visits.last().map(|v| v.at).unwrap_or(0),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort_by(|a, b| b.score.total_cmp(&a.score));
+    files.truncate(limit.unwrap_or(20));
+    Ok(files)
+}
+
+#[tauri::command]
+pub fn forget_file_access(
+    state: tauri::State<'_, FrecencyManager>,
+    root: String,
+    path: String,
+) -> Result<(), String> {
+    state.recorder.forget(&root, &path);
+    state.recorder.store.lock().unwrap().save();
+    Ok(())
+}
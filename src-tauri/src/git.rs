@@ -0,0 +1,2122 @@
+use std::path::Path;
+
+pub(crate) fn resolve_repo(repo: &str) -> String {
+    if repo.starts_with("~/") {
+        let home = crate::get_home_dir();
+        repo.replacen("~", &home, 1)
+    } else {
+        repo.to_string()
+    }
+}
+
+pub(crate) fn git_command(repo: &str) -> std::process::Command {
+    let mut cmd = std::process::Command::new("git");
+    cmd.current_dir(repo)
+        // Never block on an editor or credential prompt; there is no terminal attached
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_EDITOR", "true")
+        .args(["-c", "core.quotepath=off"]);
+    cmd
+}
+
+/// Run a prepared git command and return stdout, or git's stderr as the error
+pub(crate) fn run_git_command(cmd: &mut std::process::Command) -> Result<Vec<u8>, String> {
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        // Some failures (e.g. merge conflicts) are only reported on stdout
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = if stderr.trim().is_empty() {
+            String::from_utf8_lossy(&output.stdout).to_string()
+        } else {
+            stderr.to_string()
+        };
+        return Err(format!("git failed: {}", message.trim()));
+    }
+    Ok(output.stdout)
+}
+
+/// Run git in `repo` and return stdout, or git's stderr as the error
+pub(crate) fn run_git<I, S>(repo: &str, args: I) -> Result<Vec<u8>, String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<std::ffi::OsStr>,
+{
+    run_git_command(git_command(repo).args(args))
+}
+
+/// `root`'s index file, and a path beside it for a throwaway index named after `purpose`.
+/// Each call gets its own, so snapshots running side by side don't share one.
+pub(crate) fn temp_index(root: &str, purpose: &str) -> Result<(String, String), String> {
+    static NEXT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let index = run_git(
+        root,
+        ["rev-parse", "--path-format=absolute", "--git-path", "index"],
+    )?;
+    let index = String::from_utf8_lossy(&index).trim().to_string();
+    let tmp_index = format!(
+        "{}.ade-{}-{}-{}",
+        index,
+        purpose,
+        std::process::id(),
+        NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    );
+    Ok((index, tmp_index))
+}
+
+#[derive(serde::Serialize)]
+pub struct StatusEntry {
+    path: String,
+    /// Source path for renames and copies
+    orig_path: Option<String>,
+    /// Porcelain status letters: `.` unchanged, M, T, A, D, R, C, U (unmerged), `?` untracked
+    index: String,
+    worktree: String,
+    staged: bool,
+    unstaged: bool,
+    untracked: bool,
+    conflicted: bool,
+    /// Only reported when status is run with `--ignored`
+    ignored: bool,
+    /// Rename/copy similarity percentage
+    similarity: Option<u32>,
+}
+
+#[derive(serde::Serialize)]
+pub struct GitStatus {
+    root: String,
+    branch: Option<String>,
+    head: Option<String>,
+    upstream: Option<String>,
+    ahead: u32,
+    behind: u32,
+    entries: Vec<StatusEntry>,
+}
+
+fn parse_status(root: String, output: &[u8]) -> GitStatus {
+    let text = String::from_utf8_lossy(output);
+    let mut status = GitStatus {
+        root,
+        branch: None,
+        head: None,
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+        entries: Vec::new(),
+    };
+
+    // With -z every record is NUL-terminated and paths are never quoted
+    let mut records = text.split('\0').filter(|r| !r.is_empty());
+    while let Some(record) = records.next() {
+        if let Some(header) = record.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => status.head = Some(value.to_string()),
+                "branch.head" if value != "(detached)" => status.branch = Some(value.to_string()),
+                "branch.upstream" => status.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for part in value.split_whitespace() {
+                        if let Some(n) = part.strip_prefix('+') {
+                            status.ahead = n.parse().unwrap_or(0);
+                        } else if let Some(n) = part.strip_prefix('-') {
+                            status.behind = n.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let (kind, rest) = record.split_once(' ').unwrap_or((record, ""));
+        let entry = match kind {
+            // 1 XY sub mH mI mW hH hI path
+            "1" => {
+                let fields: Vec<&str> = rest.splitn(8, ' ').collect();
+                if fields.len() < 8 {
+                    continue;
+                }
+                changed_entry(fields[0], fields[7].to_string(), None, None)
+            }
+            // 2 XY sub mH mI mW hH hI Xscore path, followed by the original path record
+            "2" => {
+                let fields: Vec<&str> = rest.splitn(9, ' ').collect();
+                if fields.len() < 9 {
+                    continue;
+                }
+                let similarity = fields[7][1..].parse().ok();
+                let orig_path = records.next().map(String::from);
+                changed_entry(fields[0], fields[8].to_string(), orig_path, similarity)
+            }
+            // u XY sub m1 m2 m3 mW h1 h2 h3 path
+            "u" => {
+                let fields: Vec<&str> = rest.splitn(10, ' ').collect();
+                if fields.len() < 10 {
+                    continue;
+                }
+                let (index, worktree) = split_xy(fields[0]);
+                StatusEntry {
+                    path: fields[9].to_string(),
+                    orig_path: None,
+                    index,
+                    worktree,
+                    staged: false,
+                    unstaged: false,
+                    untracked: false,
+                    conflicted: true,
+                    ignored: false,
+                    similarity: None,
+                }
+            }
+            "?" => StatusEntry {
+                path: rest.to_string(),
+                orig_path: None,
+                index: "?".to_string(),
+                worktree: "?".to_string(),
+                staged: false,
+                unstaged: false,
+                untracked: true,
+                conflicted: false,
+                ignored: false,
+                similarity: None,
+            },
+            "!" => StatusEntry {
+                path: rest.to_string(),
+                orig_path: None,
+                index: "!".to_string(),
+                worktree: "!".to_string(),
+                staged: false,
+                unstaged: false,
+                untracked: false,
+                conflicted: false,
+                ignored: true,
+                similarity: None,
+            },
+            _ => continue,
+        };
+        status.entries.push(entry);
+    }
+    status
+}
+
+fn split_xy(xy: &str) -> (String, String) {
+    let mut chars = xy.chars();
+    let x = chars.next().unwrap_or('.');
+    let y = chars.next().unwrap_or('.');
+    (x.to_string(), y.to_string())
+}
+
+fn changed_entry(
+    xy: &str,
+    path: String,
+    orig_path: Option<String>,
+    similarity: Option<u32>,
+) -> StatusEntry {
+    let (index, worktree) = split_xy(xy);
+    StatusEntry {
+        path,
+        orig_path,
+        staged: index != ".",
+        unstaged: worktree != ".",
+        index,
+        worktree,
+        untracked: false,
+        conflicted: false,
+        ignored: false,
+        similarity,
+    }
+}
+
+/// Top-level directory of the repository containing `repo`
+pub(crate) fn toplevel(repo: &str) -> Result<String, String> {
+    let out = run_git(repo, ["rev-parse", "--show-toplevel"])?;
+    Ok(String::from_utf8_lossy(&out).trim().to_string())
+}
+
+/// Paths from the frontend are usually absolute; git wants them relative to the repo root
+pub(crate) fn repo_relative(root: &str, path: &str) -> String {
+    let path = resolve_repo(path);
+    Path::new(&path)
+        .strip_prefix(root)
+        .map(|rel| rel.to_string_lossy().to_string())
+        .unwrap_or(path)
+}
+
+fn status(root: String) -> Result<GitStatus, String> {
+    let output = run_git(
+        &root,
+        [
+            "status",
+            "--porcelain=v2",
+            "--branch",
+            "--untracked-files=all",
+            "--find-renames",
+            "-z",
+        ],
+    )?;
+    Ok(parse_status(root, &output))
+}
+
+#[tauri::command]
+pub async fn git_status(repo: String) -> Result<GitStatus, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let repo = resolve_repo(&repo);
+        if !Path::new(&repo).is_dir() {
+            return Err(format!("Not a directory: {}", repo));
+        }
+        status(toplevel(&repo)?)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The same markers `git status` uses to report an in-progress operation
+pub(crate) fn operation_in_progress(git_dir: &Path) -> Option<String> {
+    let operation =
+        if git_dir.join("rebase-merge").is_dir() || git_dir.join("rebase-apply").is_dir() {
+            "rebase"
+        } else if git_dir.join("MERGE_HEAD").is_file() {
+            "merge"
+        } else if git_dir.join("CHERRY_PICK_HEAD").is_file() {
+            "cherry_pick"
+        } else if git_dir.join("REVERT_HEAD").is_file() {
+            "revert"
+        } else if git_dir.join("BISECT_LOG").is_file() {
+            "bisect"
+        } else {
+            return None;
+        };
+    Some(operation.to_string())
+}
+
+#[derive(serde::Serialize)]
+pub struct SyncState {
+    /// None when HEAD is detached
+    branch: Option<String>,
+    head: Option<String>,
+    upstream: Option<String>,
+    ahead: u32,
+    behind: u32,
+    /// rebase, merge, cherry_pick, revert or bisect
+    operation: Option<String>,
+    /// On a branch with an upstream, nothing in progress, and nothing to pull first
+    can_push: bool,
+}
+
+/// Branch/upstream position and in-progress operation, without scanning the working tree
+#[tauri::command]
+pub async fn git_sync_state(repo: String) -> Result<SyncState, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let output = run_git(
+            &root,
+            [
+                "status",
+                "--porcelain=v2",
+                "--branch",
+                "--untracked-files=no",
+                "--ignore-submodules=all",
+                "-z",
+            ],
+        )?;
+        let status = parse_status(root.clone(), &output);
+        let git_dir = run_git(&root, ["rev-parse", "--path-format=absolute", "--git-dir"])?;
+        let git_dir = String::from_utf8_lossy(&git_dir).trim().to_string();
+        let operation = operation_in_progress(Path::new(&git_dir));
+        Ok(SyncState {
+            can_push: status.branch.is_some()
+                && status.upstream.is_some()
+                && operation.is_none()
+                && status.behind == 0,
+            branch: status.branch,
+            head: status.head,
+            upstream: status.upstream,
+            ahead: status.ahead,
+            behind: status.behind,
+            operation,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct DiffOptions {
+    /// Limit the diff to one file or directory (relative to the repo root or absolute)
+    path: Option<String>,
+    /// Diff the index against HEAD instead of the working tree against the index
+    staged: Option<bool>,
+    /// Diff against this commit/branch instead of the index
+    base: Option<String>,
+    /// Context lines around each change (git's default is 3)
+    context: Option<u32>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffLineKind {
+    Context,
+    Add,
+    Delete,
+}
+
+#[derive(serde::Serialize)]
+pub struct DiffLine {
+    kind: DiffLineKind,
+    old_line: Option<u32>,
+    new_line: Option<u32>,
+    content: String,
+    /// Set on the last line of a file that has no trailing newline
+    no_newline: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct DiffHunk {
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+    /// Text after the closing `@@`, usually the enclosing function
+    header: String,
+    lines: Vec<DiffLine>,
+}
+
+#[derive(serde::Serialize)]
+pub struct FileDiff {
+    /// None for added files
+    pub(crate) old_path: Option<String>,
+    /// None for deleted files
+    pub(crate) new_path: Option<String>,
+    /// added, deleted, renamed, copied or modified; unchanged for a preview that changes nothing
+    status: String,
+    binary: bool,
+    hunks: Vec<DiffHunk>,
+}
+
+impl FileDiff {
+    fn from_header(line: &str) -> Self {
+        // `diff --git a/<old> b/<new>`; only a fallback, the ---/+++ lines are authoritative
+        let paths = line.trim_start_matches("diff --git ");
+        let (old, new) = match paths.find(" b/") {
+            Some(i) => (&paths[..i], &paths[i + 1..]),
+            None => (paths, paths),
+        };
+        FileDiff {
+            old_path: Some(old.trim_start_matches("a/").to_string()),
+            new_path: Some(new.trim_start_matches("b/").to_string()),
+            status: "modified".to_string(),
+            binary: false,
+            hunks: Vec::new(),
+        }
+    }
+}
+
+/// Parse `@@ -a,b +c,d @@ header`
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+    let rest = line.strip_prefix("@@ ")?;
+    let (ranges, header) = rest.split_once(" @@").unwrap_or((rest, ""));
+    let mut parts = ranges.split_whitespace();
+    let range = |s: &str| -> Option<(u32, u32)> {
+        match s.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((s.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = range(parts.next()?.strip_prefix('-')?)?;
+    let (new_start, new_lines) = range(parts.next()?.strip_prefix('+')?)?;
+    Some(DiffHunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        header: header.trim().to_string(),
+        lines: Vec::new(),
+    })
+}
+
+fn diff_path(raw: &str, prefix: &str) -> Option<String> {
+    // git appends a tab after names containing spaces
+    let raw = raw.trim_end_matches('\t');
+    if raw == "/dev/null" {
+        return None;
+    }
+    Some(raw.strip_prefix(prefix).unwrap_or(raw).to_string())
+}
+
+pub(crate) fn parse_diff(output: &[u8]) -> Vec<FileDiff> {
+    let text = String::from_utf8_lossy(output);
+    let mut files: Vec<FileDiff> = Vec::new();
+    // Remaining old/new lines in the current hunk; header-looking lines inside a hunk are content
+    let mut old_remaining = 0u32;
+    let mut new_remaining = 0u32;
+    let mut old_line = 0u32;
+    let mut new_line = 0u32;
+
+    for line in text.lines() {
+        if old_remaining > 0 || new_remaining > 0 {
+            let Some(hunk) = files.last_mut().and_then(|f| f.hunks.last_mut()) else {
+                continue;
+            };
+            let (kind, content) = match line.split_at_checked(1) {
+                Some(("+", c)) => (DiffLineKind::Add, c),
+                Some(("-", c)) => (DiffLineKind::Delete, c),
+                Some((" ", c)) => (DiffLineKind::Context, c),
+                Some(("\\", _)) => {
+                    if let Some(last) = hunk.lines.last_mut() {
+                        last.no_newline = true;
+                    }
+                    continue;
+                }
+                // Blank context lines can lose their leading space
+                None => (DiffLineKind::Context, ""),
+                _ => {
+                    old_remaining = 0;
+                    new_remaining = 0;
+                    continue;
+                }
+            };
+            let (old, new) = match kind {
+                DiffLineKind::Add => {
+                    new_remaining = new_remaining.saturating_sub(1);
+                    new_line += 1;
+                    (None, Some(new_line - 1))
+                }
+                DiffLineKind::Delete => {
+                    old_remaining = old_remaining.saturating_sub(1);
+                    old_line += 1;
+                    (Some(old_line - 1), None)
+                }
+                DiffLineKind::Context => {
+                    old_remaining = old_remaining.saturating_sub(1);
+                    new_remaining = new_remaining.saturating_sub(1);
+                    old_line += 1;
+                    new_line += 1;
+                    (Some(old_line - 1), Some(new_line - 1))
+                }
+            };
+            hunk.lines.push(DiffLine {
+                kind,
+                old_line: old,
+                new_line: new,
+                content: content.to_string(),
+                no_newline: false,
+            });
+            continue;
+        }
+
+        if line.starts_with("diff --git ") {
+            files.push(FileDiff::from_header(line));
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with("\\") {
+            // `\ No newline at end of file` applies to the line before it
+            if let Some(last) = file.hunks.last_mut().and_then(|h| h.lines.last_mut()) {
+                last.no_newline = true;
+            }
+        } else if let Some(hunk) = parse_hunk_header(line) {
+            old_remaining = hunk.old_lines;
+            new_remaining = hunk.new_lines;
+            old_line = hunk.old_start;
+            new_line = hunk.new_start;
+            file.hunks.push(hunk);
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            file.old_path = diff_path(path, "a/");
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            file.new_path = diff_path(path, "b/");
+        } else if line.starts_with("new file mode") {
+            file.status = "added".to_string();
+            file.old_path = None;
+        } else if line.starts_with("deleted file mode") {
+            file.status = "deleted".to_string();
+            file.new_path = None;
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            file.status = "renamed".to_string();
+            file.old_path = Some(path.to_string());
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            file.new_path = Some(path.to_string());
+        } else if let Some(path) = line.strip_prefix("copy from ") {
+            file.status = "copied".to_string();
+            file.old_path = Some(path.to_string());
+        } else if let Some(path) = line.strip_prefix("copy to ") {
+            file.new_path = Some(path.to_string());
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            file.binary = true;
+        }
+    }
+    files
+}
+
+/// `git diff` arguments shared by every diff command; output is what `parse_diff` expects
+pub(crate) fn diff_args(context: Option<u32>) -> Vec<String> {
+    vec![
+        "diff".into(),
+        "--no-color".into(),
+        "--no-ext-diff".into(),
+        "--find-renames".into(),
+        "--src-prefix=a/".into(),
+        "--dst-prefix=b/".into(),
+        format!("--unified={}", context.unwrap_or(3)),
+    ]
+}
+
+fn is_untracked(root: &str, path: &str) -> bool {
+    run_git(root, ["ls-files", "--error-unmatch", "--", path]).is_err()
+}
+
+#[tauri::command]
+pub async fn git_diff(repo: String, options: Option<DiffOptions>) -> Result<Vec<FileDiff>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let root = toplevel(&resolve_repo(&repo))?;
+        let path = options.path.map(|p| repo_relative(&root, &p));
+
+        let mut args = diff_args(options.context);
+
+        // New files the agent created aren't in the index yet; show them as all-added
+        if let Some(path) = &path {
+            let is_file = Path::new(&root).join(path).is_file();
+            if !options.staged.unwrap_or(false) && is_file && is_untracked(&root, path) {
+                args.extend([
+                    "--no-index".into(),
+                    "--".into(),
+                    "/dev/null".into(),
+                    path.clone(),
+                ]);
+                let output = git_command(&root)
+                    .args(&args)
+                    .output()
+                    .map_err(|e| format!("Failed to run git: {}", e))?;
+                // `--no-index` exits 1 when the files differ
+                if output.status.code() != Some(1) && !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    return Err(format!("git failed: {}", stderr.trim()));
+                }
+                return Ok(parse_diff(&output.stdout));
+            }
+        }
+
+        if options.staged.unwrap_or(false) {
+            args.push("--cached".into());
+        }
+        if let Some(base) = options.base {
+            if base.starts_with('-') {
+                return Err(format!("Invalid base revision: {}", base));
+            }
+            args.push(base);
+        }
+        args.push("--".into());
+        if let Some(path) = path {
+            args.push(path);
+        }
+        let output = run_git(&root, &args)?;
+        Ok(parse_diff(&output))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// What writing `new` over `old` (None when the file doesn't exist yet) would change, reported
+/// for `path`. Previews a write without making it.
+pub(crate) fn diff_contents(
+    path: &str,
+    old: Option<&[u8]>,
+    new: &[u8],
+) -> Result<FileDiff, String> {
+    let dir = std::env::temp_dir().join(format!("ade-preview-{}", crate::ide_mcp::random_token()?));
+    let diff = (|| {
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create temp dir: {}", e))?;
+        let new_file = dir.join("new");
+        std::fs::write(&new_file, new).map_err(|e| format!("Failed to write temp file: {}", e))?;
+        let old_file = match old {
+            Some(old) => {
+                let file = dir.join("old");
+                std::fs::write(&file, old)
+                    .map_err(|e| format!("Failed to write temp file: {}", e))?;
+                file.to_string_lossy().to_string()
+            }
+            None => "/dev/null".to_string(),
+        };
+        let mut args = diff_args(None);
+        args.extend([
+            "--no-index".into(),
+            "--".into(),
+            old_file,
+            new_file.to_string_lossy().to_string(),
+        ]);
+        let output = git_command(&dir.to_string_lossy())
+            .args(&args)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+        // `--no-index` exits 1 when the files differ
+        if output.status.code() != Some(1) && !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("git failed: {}", stderr.trim()));
+        }
+        Ok(parse_diff(&output.stdout).into_iter().next())
+    })();
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut diff = diff?.unwrap_or_else(|| FileDiff {
+        old_path: None,
+        new_path: None,
+        status: "unchanged".to_string(),
+        binary: false,
+        hunks: Vec::new(),
+    });
+    diff.old_path = old.map(|_| path.to_string());
+    diff.new_path = Some(path.to_string());
+    Ok(diff)
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct CommitOptions {
+    amend: Option<bool>,
+    signoff: Option<bool>,
+}
+
+#[derive(serde::Serialize)]
+pub struct CommitResult {
+    commit: String,
+    summary: String,
+}
+
+fn pathspecs(root: &str, paths: &[String]) -> Result<Vec<String>, String> {
+    if paths.is_empty() {
+        return Err("No paths given".to_string());
+    }
+    Ok(paths.iter().map(|p| repo_relative(root, p)).collect())
+}
+
+#[tauri::command]
+pub async fn git_stage(repo: String, paths: Vec<String>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let mut args = vec!["add".to_string(), "--all".to_string(), "--".to_string()];
+        args.extend(pathspecs(&root, &paths)?);
+        run_git(&root, &args).map(|_| ())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn git_unstage(repo: String, paths: Vec<String>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let has_head = run_git(&root, ["rev-parse", "--verify", "--quiet", "HEAD"]).is_ok();
+        let mut args: Vec<String> = if has_head {
+            vec!["restore".into(), "--staged".into()]
+        } else {
+            // Nothing to restore from before the first commit; just drop the entries from the index
+            vec![
+                "rm".into(),
+                "--cached".into(),
+                "-r".into(),
+                "--quiet".into(),
+            ]
+        };
+        args.push("--".into());
+        args.extend(pathspecs(&root, &paths)?);
+        run_git(&root, &args).map(|_| ())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn git_commit(
+    repo: String,
+    message: String,
+    options: Option<CommitOptions>,
+) -> Result<CommitResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let amend = options.amend.unwrap_or(false);
+        if message.trim().is_empty() && !amend {
+            return Err("Commit message is empty".to_string());
+        }
+        let root = toplevel(&resolve_repo(&repo))?;
+
+        let mut args = vec!["commit".to_string()];
+        if amend {
+            args.push("--amend".into());
+        }
+        if options.signoff.unwrap_or(false) {
+            args.push("--signoff".into());
+        }
+        if message.trim().is_empty() {
+            // Amend with an empty message keeps the existing one
+            args.push("--no-edit".into());
+        } else {
+            args.push("--message".into());
+            args.push(message);
+        }
+        run_git(&root, &args)?;
+
+        let out = run_git(&root, ["log", "-1", "--format=%H%n%s"])?;
+        let out = String::from_utf8_lossy(&out);
+        let (commit, summary) = out.trim_end().split_once('\n').unwrap_or((out.trim(), ""));
+        Ok(CommitResult {
+            commit: commit.to_string(),
+            summary: summary.to_string(),
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct LogOptions {
+    /// Only commits touching this file or directory
+    path: Option<String>,
+    limit: Option<usize>,
+    skip: Option<usize>,
+    author: Option<String>,
+    /// Anything `git log --since` accepts ("2 weeks ago", "2024-01-01", …)
+    since: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct CommitInfo {
+    hash: String,
+    parents: Vec<String>,
+    author: String,
+    author_email: String,
+    /// Unix seconds
+    date: i64,
+    subject: String,
+    files_changed: u32,
+    insertions: u32,
+    deletions: u32,
+}
+
+/// Parse ` 3 files changed, 10 insertions(+), 2 deletions(-)`
+fn parse_shortstat(line: &str, commit: &mut CommitInfo) {
+    for part in line.split(',') {
+        let mut words = part.split_whitespace();
+        let (Some(n), Some(what)) = (words.next(), words.next()) else {
+            continue;
+        };
+        let n = n.parse().unwrap_or(0);
+        if what.starts_with("file") {
+            commit.files_changed = n;
+        } else if what.starts_with("insertion") {
+            commit.insertions = n;
+        } else if what.starts_with("deletion") {
+            commit.deletions = n;
+        }
+    }
+}
+
+/// Record separator before each commit, unit separator between fields
+const LOG_FORMAT: &str = "--format=%x1e%H%x1f%P%x1f%an%x1f%ae%x1f%at%x1f%s";
+
+fn parse_commit_header(header: &str) -> Option<CommitInfo> {
+    let fields: Vec<&str> = header.split('\x1f').collect();
+    if fields.len() < 6 {
+        return None;
+    }
+    Some(CommitInfo {
+        hash: fields[0].to_string(),
+        parents: fields[1].split_whitespace().map(String::from).collect(),
+        author: fields[2].to_string(),
+        author_email: fields[3].to_string(),
+        date: fields[4].parse().unwrap_or(0),
+        subject: fields[5].to_string(),
+        files_changed: 0,
+        insertions: 0,
+        deletions: 0,
+    })
+}
+
+#[tauri::command]
+pub async fn git_log(repo: String, options: Option<LogOptions>) -> Result<Vec<CommitInfo>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let root = toplevel(&resolve_repo(&repo))?;
+        // A repo without commits has no history rather than an error
+        if run_git(&root, ["rev-parse", "--verify", "--quiet", "HEAD"]).is_err() {
+            return Ok(Vec::new());
+        }
+
+        let mut args: Vec<String> = vec![
+            "log".into(),
+            "--no-color".into(),
+            "--shortstat".into(),
+            LOG_FORMAT.into(),
+            format!("--max-count={}", options.limit.unwrap_or(50)),
+            format!("--skip={}", options.skip.unwrap_or(0)),
+        ];
+        if let Some(author) = options.author {
+            args.push(format!("--author={}", author));
+        }
+        if let Some(since) = options.since {
+            args.push(format!("--since={}", since));
+        }
+        if let Some(path) = options.path {
+            let path = repo_relative(&root, &path);
+            // Follow a single file through renames
+            if Path::new(&root).join(&path).is_file() {
+                args.push("--follow".into());
+            }
+            args.push("--".into());
+            args.push(path);
+        }
+
+        let output = run_git(&root, &args)?;
+        let text = String::from_utf8_lossy(&output);
+        let mut commits = Vec::new();
+        for record in text.split('\x1e').filter(|r| !r.trim().is_empty()) {
+            let (header, rest) = record.split_once('\n').unwrap_or((record, ""));
+            let Some(mut commit) = parse_commit_header(header) else {
+                continue;
+            };
+            if let Some(stat) = rest.lines().find(|l| l.contains("changed")) {
+                parse_shortstat(stat, &mut commit);
+            }
+            commits.push(commit);
+        }
+        Ok(commits)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct BlameOptions {
+    /// Inclusive 1-based line range
+    range: Option<(u32, u32)>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct BlameLine {
+    pub(crate) line: u32,
+    commit: String,
+    author: String,
+    author_email: String,
+    /// Unix seconds
+    author_time: i64,
+    summary: String,
+    /// Line has local changes that aren't committed yet
+    uncommitted: bool,
+    content: String,
+}
+
+/// Run `git blame --line-porcelain` over the given 1-based inclusive ranges (whole file if empty)
+pub(crate) fn blame(
+    root: &str,
+    path: &str,
+    ranges: &[(u32, u32)],
+) -> Result<Vec<BlameLine>, String> {
+    let mut args = vec!["blame".to_string(), "--line-porcelain".to_string()];
+    for (start, end) in ranges {
+        args.push("-L".into());
+        args.push(format!("{},{}", start, end));
+    }
+    args.push("--".into());
+    args.push(repo_relative(root, path));
+    let output = run_git(root, &args)?;
+
+    let stdout = String::from_utf8_lossy(&output);
+    let mut lines = Vec::new();
+    let mut current: Option<BlameLine> = None;
+    for line in stdout.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            // Content line ends the entry for this source line
+            if let Some(mut entry) = current.take() {
+                entry.content = content.to_string();
+                lines.push(entry);
+            }
+            continue;
+        }
+        match current.as_mut() {
+            None => {
+                // Header: <sha> <orig-line> <final-line> [<count>]
+                let mut parts = line.split_whitespace();
+                if let (Some(sha), Some(_), Some(final_line)) =
+                    (parts.next(), parts.next(), parts.next())
+                {
+                    if let Ok(final_line) = final_line.parse() {
+                        current = Some(BlameLine {
+                            line: final_line,
+                            commit: sha.to_string(),
+                            author: String::new(),
+                            author_email: String::new(),
+                            author_time: 0,
+                            summary: String::new(),
+                            uncommitted: sha.bytes().all(|b| b == b'0'),
+                            content: String::new(),
+                        });
+                    }
+                }
+            }
+            Some(entry) => {
+                if let Some(author) = line.strip_prefix("author ") {
+                    entry.author = author.to_string();
+                } else if let Some(email) = line.strip_prefix("author-mail ") {
+                    entry.author_email = email.trim_matches(['<', '>']).to_string();
+                } else if let Some(time) = line.strip_prefix("author-time ") {
+                    entry.author_time = time.parse().unwrap_or(0);
+                } else if let Some(summary) = line.strip_prefix("summary ") {
+                    entry.summary = summary.to_string();
+                }
+            }
+        }
+    }
+    Ok(lines)
+}
+
+#[tauri::command]
+pub async fn git_blame(
+    repo: String,
+    path: String,
+    options: Option<BlameOptions>,
+) -> Result<Vec<BlameLine>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let root = toplevel(&resolve_repo(&repo))?;
+        let ranges: Vec<(u32, u32)> = options
+            .range
+            .map(|(start, end)| (start.max(1), end.max(start.max(1))))
+            .into_iter()
+            .collect();
+        blame(&root, &path, &ranges)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct StashOptions {
+    include_untracked: Option<bool>,
+}
+
+#[derive(serde::Serialize)]
+pub struct StashEntry {
+    /// Position in the stash list (`stash@{index}`)
+    index: usize,
+    hash: String,
+    /// Branch the stash was made on
+    branch: Option<String>,
+    message: String,
+    /// Unix seconds
+    date: i64,
+}
+
+fn stash_list(root: &str) -> Result<Vec<StashEntry>, String> {
+    let output = run_git(root, ["stash", "list", "--format=%gd%x1f%H%x1f%ct%x1f%gs"])?;
+    let text = String::from_utf8_lossy(&output);
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let fields: Vec<&str> = line.splitn(4, '\x1f').collect();
+        if fields.len() < 4 {
+            continue;
+        }
+        let Some(index) = fields[0]
+            .strip_prefix("stash@{")
+            .and_then(|s| s.strip_suffix('}'))
+            .and_then(|s| s.parse().ok())
+        else {
+            continue;
+        };
+        // Reflog subject is "On <branch>: <message>" or "WIP on <branch>: <sha> <subject>"
+        let subject = fields[3];
+        let (branch, message) = match subject
+            .strip_prefix("WIP on ")
+            .or_else(|| subject.strip_prefix("On "))
+            .and_then(|s| s.split_once(": "))
+        {
+            Some((branch, message)) => (Some(branch.to_string()), message.to_string()),
+            None => (None, subject.to_string()),
+        };
+        entries.push(StashEntry {
+            index,
+            hash: fields[1].to_string(),
+            branch,
+            message,
+            date: fields[2].parse().unwrap_or(0),
+        });
+    }
+    Ok(entries)
+}
+
+/// Stash local changes. Returns the new entry, or None if there was nothing to stash.
+#[tauri::command]
+pub async fn git_stash_save(
+    repo: String,
+    message: Option<String>,
+    options: Option<StashOptions>,
+) -> Result<Option<StashEntry>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let root = toplevel(&resolve_repo(&repo))?;
+        let before = run_git(&root, ["rev-parse", "--verify", "--quiet", "refs/stash"]).ok();
+
+        let mut args = vec!["stash".to_string(), "push".to_string()];
+        if options.include_untracked.unwrap_or(false) {
+            args.push("--include-untracked".into());
+        }
+        if let Some(message) = message.filter(|m| !m.trim().is_empty()) {
+            args.push("--message".into());
+            args.push(message);
+        }
+        run_git(&root, &args)?;
+
+        // `git stash push` succeeds without creating anything when the tree is clean
+        let after = run_git(&root, ["rev-parse", "--verify", "--quiet", "refs/stash"]).ok();
+        if after.is_none() || after == before {
+            return Ok(None);
+        }
+        Ok(stash_list(&root)?.into_iter().next())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn git_stash_list(repo: String) -> Result<Vec<StashEntry>, String> {
+    tauri::async_runtime::spawn_blocking(move || stash_list(&toplevel(&resolve_repo(&repo))?))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Apply and drop a stash. On conflict git keeps the entry and the error carries its output.
+#[tauri::command]
+pub async fn git_stash_pop(repo: String, index: Option<usize>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let stash = format!("stash@{{{}}}", index.unwrap_or(0));
+        run_git(&root, ["stash", "pop", stash.as_str()]).map(|_| ())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(serde::Serialize)]
+pub struct ConflictedFile {
+    path: String,
+    /// both_modified, both_added, both_deleted, added_by_us, added_by_them, deleted_by_us, deleted_by_them
+    kind: String,
+    /// Number of marker blocks still in the file (0 once resolved or if the file is gone)
+    markers: usize,
+}
+
+#[derive(serde::Serialize)]
+#[serde(tag = "type")]
+pub enum ConflictRegion {
+    /// Text outside any conflict, exactly as in the file
+    #[serde(rename = "common")]
+    Common { start_line: u32, text: String },
+    #[serde(rename = "conflict")]
+    Conflict {
+        /// Line of the `<<<<<<<` marker
+        start_line: u32,
+        /// Line of the `>>>>>>>` marker
+        end_line: u32,
+        ours_label: String,
+        ours: String,
+        /// Present with `merge.conflictStyle = diff3` or `zdiff3`
+        base_label: Option<String>,
+        base: Option<String>,
+        theirs_label: String,
+        theirs: String,
+    },
+}
+
+#[derive(serde::Serialize)]
+pub struct ConflictContent {
+    path: String,
+    conflicts: usize,
+    regions: Vec<ConflictRegion>,
+}
+
+fn conflict_kind(xy: &str) -> &'static str {
+    match xy {
+        "DD" => "both_deleted",
+        "AU" => "added_by_us",
+        "UD" => "deleted_by_them",
+        "UA" => "added_by_them",
+        "DU" => "deleted_by_us",
+        "AA" => "both_added",
+        _ => "both_modified",
+    }
+}
+
+/// Text after a conflict marker, e.g. `HEAD` in `<<<<<<< HEAD`
+fn marker_label(line: &str) -> String {
+    line[7..].trim().to_string()
+}
+
+#[derive(PartialEq)]
+enum ConflictSection {
+    Ours,
+    Base,
+    Theirs,
+}
+
+struct OpenConflict {
+    start_line: u32,
+    section: ConflictSection,
+    ours_label: String,
+    ours: String,
+    base_label: Option<String>,
+    base: Option<String>,
+    theirs: String,
+}
+
+fn parse_conflicts(content: &str) -> Result<Vec<ConflictRegion>, String> {
+    let mut regions = Vec::new();
+    let mut common = String::new();
+    let mut common_start = 1;
+    let mut open: Option<OpenConflict> = None;
+
+    for (i, raw) in content.split_inclusive('\n').enumerate() {
+        let line_no = i as u32 + 1;
+        let line = raw.trim_end_matches(['\n', '\r']);
+        let Some(conflict) = open.as_mut() else {
+            if line.starts_with("<<<<<<<") {
+                if !common.is_empty() {
+                    regions.push(ConflictRegion::Common {
+                        start_line: common_start,
+                        text: std::mem::take(&mut common),
+                    });
+                }
+                open = Some(OpenConflict {
+                    start_line: line_no,
+                    section: ConflictSection::Ours,
+                    ours_label: marker_label(line),
+                    ours: String::new(),
+                    base_label: None,
+                    base: None,
+                    theirs: String::new(),
+                });
+            } else {
+                if common.is_empty() {
+                    common_start = line_no;
+                }
+                common.push_str(raw);
+            }
+            continue;
+        };
+
+        if line.starts_with("|||||||") && conflict.section == ConflictSection::Ours {
+            conflict.section = ConflictSection::Base;
+            conflict.base_label = Some(marker_label(line));
+            conflict.base = Some(String::new());
+        } else if line.starts_with("=======") && conflict.section != ConflictSection::Theirs {
+            conflict.section = ConflictSection::Theirs;
+        } else if line.starts_with(">>>>>>>") && conflict.section == ConflictSection::Theirs {
+            let conflict = open.take().unwrap();
+            regions.push(ConflictRegion::Conflict {
+                start_line: conflict.start_line,
+                end_line: line_no,
+                ours_label: conflict.ours_label,
+                ours: conflict.ours,
+                base_label: conflict.base_label,
+                base: conflict.base,
+                theirs_label: marker_label(line),
+                theirs: conflict.theirs,
+            });
+        } else {
+            match conflict.section {
+                ConflictSection::Ours => conflict.ours.push_str(raw),
+                ConflictSection::Base => {
+                    conflict.base.get_or_insert_with(String::new).push_str(raw)
+                }
+                ConflictSection::Theirs => conflict.theirs.push_str(raw),
+            }
+        }
+    }
+
+    if let Some(conflict) = open {
+        return Err(format!(
+            "Unterminated conflict starting at line {}",
+            conflict.start_line
+        ));
+    }
+    if !common.is_empty() {
+        regions.push(ConflictRegion::Common {
+            start_line: common_start,
+            text: common,
+        });
+    }
+    Ok(regions)
+}
+
+fn count_conflicts(regions: &[ConflictRegion]) -> usize {
+    regions
+        .iter()
+        .filter(|r| matches!(r, ConflictRegion::Conflict { .. }))
+        .count()
+}
+
+#[tauri::command]
+pub async fn git_conflicts(repo: String) -> Result<Vec<ConflictedFile>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let status = status(root.clone())?;
+        Ok(status
+            .entries
+            .into_iter()
+            .filter(|e| e.conflicted)
+            .map(|e| {
+                let xy = format!("{}{}", e.index, e.worktree);
+                let markers = std::fs::read_to_string(Path::new(&root).join(&e.path))
+                    .ok()
+                    .and_then(|c| parse_conflicts(&c).ok())
+                    .map(|r| count_conflicts(&r))
+                    .unwrap_or(0);
+                ConflictedFile {
+                    path: e.path,
+                    kind: conflict_kind(&xy).to_string(),
+                    markers,
+                }
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn read_conflict(path: String) -> Result<ConflictContent, String> {
+    let resolved = resolve_repo(&path);
+    let content = std::fs::read_to_string(&resolved)
+        .map_err(|e| format!("Failed to read {}: {}", resolved, e))?;
+    let regions = parse_conflicts(&content)?;
+    Ok(ConflictContent {
+        path: resolved,
+        conflicts: count_conflicts(&regions),
+        regions,
+    })
+}
+
+#[derive(serde::Serialize)]
+pub struct Worktree {
+    path: String,
+    head: Option<String>,
+    /// Short branch name; None when detached
+    branch: Option<String>,
+    /// The repository's main working tree
+    is_main: bool,
+    bare: bool,
+    locked: bool,
+    prunable: bool,
+}
+
+fn worktree_list(root: &str) -> Result<Vec<Worktree>, String> {
+    let output = run_git(root, ["worktree", "list", "--porcelain", "-z"])?;
+    let text = String::from_utf8_lossy(&output);
+    let mut worktrees: Vec<Worktree> = Vec::new();
+    // Attributes are NUL-terminated; an empty attribute ends each worktree
+    for attr in text.split('\0') {
+        if let Some(path) = attr.strip_prefix("worktree ") {
+            worktrees.push(Worktree {
+                path: path.to_string(),
+                head: None,
+                branch: None,
+                // git always lists the main worktree first
+                is_main: worktrees.is_empty(),
+                bare: false,
+                locked: false,
+                prunable: false,
+            });
+            continue;
+        }
+        let Some(worktree) = worktrees.last_mut() else {
+            continue;
+        };
+        let (key, value) = attr.split_once(' ').unwrap_or((attr, ""));
+        match key {
+            "HEAD" => worktree.head = Some(value.to_string()),
+            "branch" => {
+                let branch = value.strip_prefix("refs/heads/").unwrap_or(value);
+                worktree.branch = Some(branch.to_string());
+            }
+            "bare" => worktree.bare = true,
+            "locked" => worktree.locked = true,
+            "prunable" => worktree.prunable = true,
+            _ => {}
+        }
+    }
+    Ok(worktrees)
+}
+
+/// Check out `branch` into a new worktree at `path`, creating the branch from HEAD if needed
+#[tauri::command]
+pub async fn git_worktree_add(
+    repo: String,
+    branch: String,
+    path: String,
+) -> Result<Worktree, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        if branch.is_empty() || branch.starts_with('-') {
+            return Err(format!("Invalid branch name: {}", branch));
+        }
+        let path = resolve_repo(&path);
+        let target = Path::new(&root).join(&path);
+        if target.exists() {
+            return Err(format!("Path already exists: {}", target.display()));
+        }
+        let target = target.to_string_lossy().to_string();
+
+        let branch_ref = format!("refs/heads/{}", branch);
+        let exists = run_git(
+            &root,
+            ["rev-parse", "--verify", "--quiet", branch_ref.as_str()],
+        )
+        .is_ok();
+        let args: Vec<&str> = if exists {
+            vec!["worktree", "add", "--", &target, &branch]
+        } else {
+            vec!["worktree", "add", "-b", &branch, "--", &target]
+        };
+        run_git(&root, &args)?;
+
+        // git may canonicalize the path (symlinks, `..`), so match on the branch
+        worktree_list(&root)?
+            .into_iter()
+            .find(|w| w.branch.as_deref() == Some(branch.as_str()))
+            .ok_or_else(|| format!("Worktree for {} not found after creation", branch))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn git_worktree_list(repo: String) -> Result<Vec<Worktree>, String> {
+    tauri::async_runtime::spawn_blocking(move || worktree_list(&toplevel(&resolve_repo(&repo))?))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Remove a linked worktree. Without `force`, git refuses if it has uncommitted changes.
+#[tauri::command]
+pub async fn git_worktree_remove(
+    repo: String,
+    path: String,
+    force: Option<bool>,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let path = resolve_repo(&path);
+        let mut args = vec!["worktree", "remove"];
+        if force.unwrap_or(false) {
+            args.push("--force");
+        }
+        args.push("--");
+        args.push(&path);
+        run_git(&root, &args)?;
+        // Drop bookkeeping for worktrees whose directories were deleted by hand
+        let _ = run_git(&root, ["worktree", "prune"]);
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct DiffRangeOptions {
+    path: Option<String>,
+    /// Diff from the merge base of the two revisions (`from...to`), like a pull request view
+    merge_base: Option<bool>,
+    context: Option<u32>,
+}
+
+pub(crate) fn check_rev(rev: &str) -> Result<(), String> {
+    if rev.is_empty() || rev.starts_with('-') {
+        return Err(format!("Invalid revision: {}", rev));
+    }
+    Ok(())
+}
+
+/// Diff between two revisions, e.g. `HEAD~3` → `HEAD` or `main` → `feature`
+#[tauri::command]
+pub async fn git_diff_range(
+    repo: String,
+    from_rev: String,
+    to_rev: String,
+    options: Option<DiffRangeOptions>,
+) -> Result<Vec<FileDiff>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        check_rev(&from_rev)?;
+        check_rev(&to_rev)?;
+        let root = toplevel(&resolve_repo(&repo))?;
+
+        let mut args = diff_args(options.context);
+        if options.merge_base.unwrap_or(false) {
+            args.push(format!("{}...{}", from_rev, to_rev));
+        } else {
+            args.push(from_rev);
+            args.push(to_rev);
+        }
+        args.push("--".into());
+        if let Some(path) = options.path {
+            args.push(repo_relative(&root, &path));
+        }
+        Ok(parse_diff(&run_git(&root, &args)?))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// `@@ -12,7 +12,8 @@ fn foo` → `@@ -12,7 +12,8 @@`; ranges written as `-12` and `-12,1` are equivalent
+fn normalize_hunk_header(header: &str) -> Option<String> {
+    let hunk = parse_hunk_header(header.trim())?;
+    Some(format!(
+        "@@ -{},{} +{},{} @@",
+        hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+    ))
+}
+
+/// Build a single-hunk patch for `path` from the working tree diff, with the `context` lines
+/// the hunk header came from
+fn hunk_patch(
+    root: &str,
+    path: &str,
+    hunk_header: &str,
+    context: Option<u32>,
+) -> Result<String, String> {
+    let wanted = normalize_hunk_header(hunk_header)
+        .ok_or_else(|| format!("Invalid hunk header: {}", hunk_header))?;
+    let mut args = diff_args(context);
+    args.push("--".into());
+    args.push(path.to_string());
+    let output = run_git(root, &args)?;
+    let diff = String::from_utf8_lossy(&output);
+
+    // Everything before the first hunk is the file header (diff --git, index, ---/+++)
+    let mut header = String::new();
+    let mut hunk: Option<String> = None;
+    let mut seen_hunk = false;
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("@@ ") {
+            if hunk.is_some() {
+                break;
+            }
+            seen_hunk = true;
+            if normalize_hunk_header(line).as_deref() == Some(wanted.as_str()) {
+                hunk = Some(line.to_string());
+            }
+        } else if !seen_hunk {
+            header.push_str(line);
+        } else if let Some(hunk) = hunk.as_mut() {
+            hunk.push_str(line);
+        }
+    }
+    let hunk = hunk.ok_or_else(|| {
+        format!(
+            "Hunk {} not found in {}; the file may have changed",
+            wanted, path
+        )
+    })?;
+    Ok(header + &hunk)
+}
+
+/// Run git with `input` on stdin and return the raw output so callers can interpret exit codes
+fn git_with_input(
+    root: &str,
+    args: &[&str],
+    input: Vec<u8>,
+) -> Result<std::process::Output, String> {
+    use std::io::Write;
+    let mut child = git_command(root)
+        .args(args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    // Feed stdin from another thread so a full stdout pipe can't deadlock us
+    let writer = child
+        .stdin
+        .take()
+        .map(|mut stdin| std::thread::spawn(move || stdin.write_all(&input)));
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    Ok(output)
+}
+
+fn apply_patch(root: &str, patch: &str, args: &[&str], context: Option<u32>) -> Result<(), String> {
+    let mut apply_args = vec!["apply"];
+    apply_args.extend_from_slice(args);
+    // Hunks without context lines are refused otherwise
+    if context == Some(0) {
+        apply_args.push("--unidiff-zero");
+    }
+    apply_args.push("-");
+    let output = git_with_input(root, &apply_args, patch.as_bytes().to_vec())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git apply failed: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
+/// Stage one hunk of a file's unstaged changes. `hunk_header` is the `@@ -a,b +c,d @@` line from git_diff.
+/// `context` must be what that git_diff was given.
+#[tauri::command]
+pub async fn git_stage_hunk(
+    repo: String,
+    path: String,
+    hunk_header: String,
+    context: Option<u32>,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let path = repo_relative(&root, &path);
+        let patch = hunk_patch(&root, &path, &hunk_header, context)?;
+        apply_patch(&root, &patch, &["--cached"], context)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Throw away one hunk of a file's unstaged changes, restoring that part of the working tree.
+/// `context` must be what the git_diff the hunk came from was given.
+#[tauri::command]
+pub async fn git_discard_hunk(
+    repo: String,
+    path: String,
+    hunk_header: String,
+    context: Option<u32>,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let path = repo_relative(&root, &path);
+        let patch = hunk_patch(&root, &path, &hunk_header, context)?;
+        apply_patch(&root, &patch, &["--reverse"], context)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Root of the repository containing `path` (a file or directory), or None outside a repo
+pub(crate) fn repo_root(path: &str) -> Option<String> {
+    let path = resolve_repo(path);
+    let path = Path::new(&path);
+    let dir = if path.is_dir() { path } else { path.parent()? };
+    toplevel(&dir.to_string_lossy()).ok()
+}
+
+/// Which of `paths` git ignores (tracked files never count as ignored, matching git status)
+pub(crate) fn ignored_paths(root: &str, paths: &[String]) -> Result<Vec<String>, String> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    let relative: Vec<String> = paths.iter().map(|p| repo_relative(root, p)).collect();
+    let mut input = Vec::new();
+    for path in &relative {
+        input.extend_from_slice(path.as_bytes());
+        input.push(0);
+    }
+    let output = git_with_input(root, &["check-ignore", "-z", "--stdin"], input)?;
+    // Exit code 1 just means nothing matched
+    if !output.status.success() && output.status.code() != Some(1) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git failed: {}", stderr.trim()));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let ignored: std::collections::HashSet<&str> =
+        stdout.split('\0').filter(|p| !p.is_empty()).collect();
+    Ok(paths
+        .iter()
+        .zip(&relative)
+        .filter(|(_, rel)| ignored.contains(rel.as_str()))
+        .map(|(path, _)| path.clone())
+        .collect())
+}
+
+/// Rank used when several entries fold into one directory; higher wins
+fn flag_rank(flag: &str) -> u8 {
+    match flag {
+        "conflicted" => 4,
+        "untracked" => 2,
+        "ignored" => 1,
+        _ => 3,
+    }
+}
+
+fn entry_flag(entry: &StatusEntry) -> &'static str {
+    if entry.conflicted {
+        return "conflicted";
+    }
+    if entry.untracked {
+        return "untracked";
+    }
+    if entry.ignored {
+        return "ignored";
+    }
+    let letter = if entry.worktree != "." {
+        &entry.worktree
+    } else {
+        &entry.index
+    };
+    match letter.as_str() {
+        "A" => "added",
+        "D" => "deleted",
+        "R" | "C" => "renamed",
+        _ => "modified",
+    }
+}
+
+/// Status flag (modified, added, deleted, renamed, untracked, ignored, conflicted) for each
+/// direct child of `dir` that has one, from a single `git status` over the directory.
+/// Directories take the most significant flag of anything beneath them; unchanged entries and
+/// directories outside a repository are simply absent.
+pub(crate) fn directory_status(dir: &str) -> std::collections::HashMap<String, String> {
+    let mut flags: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+    let Ok(prefix) = run_git(dir, ["rev-parse", "--show-prefix"]) else {
+        return flags;
+    };
+    let prefix = String::from_utf8_lossy(&prefix).trim().to_string();
+    // Untracked and ignored directories are reported as `dir/` rather than file by file
+    let Ok(output) = run_git(
+        dir,
+        [
+            "status",
+            "--porcelain=v2",
+            "--untracked-files=normal",
+            "--ignored=matching",
+            "-z",
+            "--",
+            ".",
+        ],
+    ) else {
+        return flags;
+    };
+    for entry in parse_status(String::new(), &output).entries {
+        let Some(rel) = entry.path.strip_prefix(prefix.as_str()) else {
+            continue;
+        };
+        let (child, nested) = match rel.trim_end_matches('/').split_once('/') {
+            Some((child, _)) => (child, true),
+            None => (rel.trim_end_matches('/'), false),
+        };
+        if child.is_empty() {
+            continue;
+        }
+        let flag = match entry_flag(&entry) {
+            // An ignored file doesn't make its tracked parent directory ignored
+            "ignored" if nested => continue,
+            "added" | "deleted" | "renamed" if nested => "modified",
+            flag => flag,
+        };
+        let current = flags.get(child).map(|f| flag_rank(f)).unwrap_or(0);
+        if flag_rank(flag) > current {
+            flags.insert(child.to_string(), flag.to_string());
+        }
+    }
+    flags
+}
+
+#[tauri::command]
+pub async fn git_repo_root(path: String) -> Result<Option<String>, String> {
+    tauri::async_runtime::spawn_blocking(move || repo_root(&path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Returns the subset of `paths` that are ignored, in the form they were given
+#[tauri::command]
+pub async fn git_check_ignore(repo: String, paths: Vec<String>) -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        ignored_paths(&root, &paths)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct RemoteOptions {
+    /// Branch to fetch/pull/push; defaults to git's configured behaviour
+    branch: Option<String>,
+    /// push: set the pushed branch's upstream (`-u`)
+    set_upstream: Option<bool>,
+    /// push: `--force-with-lease`; refused here, as a force-push needs request_confirmation
+    force_with_lease: Option<bool>,
+    /// pull: rebase instead of merge
+    rebase: Option<bool>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum RemoteEvent {
+    #[serde(rename = "progress")]
+    Progress {
+        /// e.g. "Receiving objects", "Writing objects"
+        phase: String,
+        percent: u32,
+        current: u64,
+        total: u64,
+    },
+    /// Any other output line, including `remote:` messages such as pull request links
+    #[serde(rename = "message")]
+    Message { text: String },
+}
+
+#[derive(serde::Serialize)]
+pub struct RemoteResult {
+    messages: Vec<String>,
+}
+
+static PROGRESS_RE: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+
+fn parse_progress(line: &str) -> Option<RemoteEvent> {
+    let re = PROGRESS_RE.get_or_init(|| {
+        regex::Regex::new(r"^(?:remote: )?([A-Za-z ]+):\s+(\d+)% \((\d+)/(\d+)\)").unwrap()
+    });
+    let caps = re.captures(line)?;
+    Some(RemoteEvent::Progress {
+        phase: caps[1].trim().to_string(),
+        percent: caps[2].parse().ok()?,
+        current: caps[3].parse().ok()?,
+        total: caps[4].parse().ok()?,
+    })
+}
+
+/// Run a fetch/pull/push, streaming git's progress output to `on_event`.
+/// Authentication goes through the user's ssh-agent and credential helpers; git never prompts.
+fn run_remote(
+    root: &str,
+    args: &[String],
+    on_event: &tauri::ipc::Channel<RemoteEvent>,
+) -> Result<RemoteResult, String> {
+    use std::io::Read;
+    let mut cmd = git_command(root);
+    cmd.args(args)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+    // Without a terminal ssh would fail on passphrase/host-key prompts anyway; make it fail fast
+    let has_ssh_command = std::env::var_os("GIT_SSH_COMMAND").is_some()
+        || run_git(root, ["config", "core.sshCommand"]).is_ok();
+    if !has_ssh_command {
+        cmd.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+    }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    // Drain stdout from another thread so a full stdout pipe can't stall git while we read stderr
+    let mut stdout = child.stdout.take().unwrap();
+    let stdout_reader = std::thread::spawn(move || {
+        let mut out = Vec::new();
+        let _ = stdout.read_to_end(&mut out);
+        out
+    });
+
+    // Progress is written to stderr, with `\r` between updates of the same line
+    let mut stderr = child.stderr.take().unwrap();
+    let mut messages = Vec::new();
+    let mut pending = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = match stderr.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        for &byte in &buf[..n] {
+            if byte != b'\r' && byte != b'\n' {
+                pending.push(byte);
+                continue;
+            }
+            let line = String::from_utf8_lossy(&pending).trim_end().to_string();
+            pending.clear();
+            if line.is_empty() {
+                continue;
+            }
+            match parse_progress(&line) {
+                Some(progress) => {
+                    let _ = on_event.send(progress);
+                }
+                None => {
+                    let _ = on_event.send(RemoteEvent::Message { text: line.clone() });
+                    messages.push(line);
+                }
+            }
+        }
+    }
+    if !pending.is_empty() {
+        messages.push(String::from_utf8_lossy(&pending).trim_end().to_string());
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    let stdout = stdout_reader.join().unwrap_or_default();
+    messages.extend(
+        String::from_utf8_lossy(&stdout)
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(String::from),
+    );
+    if !status.success() {
+        let detail = messages
+            .iter()
+            .filter(|m| !m.starts_with("remote: "))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n");
+        let auth = [
+            "Permission denied",
+            "Authentication failed",
+            "could not read Username",
+        ]
+        .iter()
+        .any(|needle| detail.contains(needle));
+        if auth {
+            return Err(format!(
+                "Authentication failed; check your ssh-agent or git credential helper: {}",
+                detail
+            ));
+        }
+        return Err(format!("git failed: {}", detail));
+    }
+    Ok(RemoteResult { messages })
+}
+
+fn remote_args(
+    command: &str,
+    remote: Option<String>,
+    options: &RemoteOptions,
+) -> Result<Vec<String>, String> {
+    let remote = remote.unwrap_or_else(|| "origin".to_string());
+    check_rev(&remote)?;
+    let mut args = vec![command.to_string(), "--progress".to_string()];
+    match command {
+        "pull" if options.rebase.unwrap_or(false) => args.push("--rebase".into()),
+        "push" => {
+            if options.set_upstream.unwrap_or(false) {
+                args.push("--set-upstream".into());
+            }
+            if options.force_with_lease.unwrap_or(false) {
+                args.push("--force-with-lease".into());
+            }
+        }
+        _ => {}
+    }
+    args.push(remote);
+    if let Some(branch) = &options.branch {
+        check_rev(branch)?;
+        args.push(branch.clone());
+    }
+    Ok(args)
+}
+
+#[tauri::command]
+pub async fn git_fetch(
+    repo: String,
+    remote: Option<String>,
+    options: Option<RemoteOptions>,
+    on_event: tauri::ipc::Channel<RemoteEvent>,
+) -> Result<RemoteResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let args = remote_args("fetch", remote, &options.unwrap_or_default())?;
+        run_remote(&root, &args, &on_event)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn git_pull(
+    repo: String,
+    remote: Option<String>,
+    options: Option<RemoteOptions>,
+    on_event: tauri::ipc::Channel<RemoteEvent>,
+) -> Result<RemoteResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let args = remote_args("pull", remote, &options.unwrap_or_default())?;
+        run_remote(&root, &args, &on_event)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn git_push(
+    repo: String,
+    remote: Option<String>,
+    options: Option<RemoteOptions>,
+    on_event: tauri::ipc::Channel<RemoteEvent>,
+) -> Result<RemoteResult, String> {
+    let options = options.unwrap_or_default();
+    if options.force_with_lease.unwrap_or(false) {
+        return Err(
+            "Force-pushing needs confirmation: request_confirmation with a force_push op"
+                .to_string(),
+        );
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let args = remote_args("push", remote, &options)?;
+        run_remote(&root, &args, &on_event)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// `git push --force-with-lease`, once request_confirmation's token has been redeemed
+pub(crate) fn force_push(
+    repo: &str,
+    remote: Option<String>,
+    branch: Option<String>,
+    set_upstream: Option<bool>,
+    on_event: &tauri::ipc::Channel<RemoteEvent>,
+) -> Result<RemoteResult, String> {
+    let root = toplevel(&resolve_repo(repo))?;
+    let options = RemoteOptions {
+        branch,
+        set_upstream,
+        force_with_lease: Some(true),
+        rebase: None,
+    };
+    let args = remote_args("push", remote, &options)?;
+    run_remote(&root, &args, on_event)
+}
+
+#[derive(serde::Serialize)]
+pub struct FileRevision {
+    #[serde(flatten)]
+    commit: CommitInfo,
+    /// The file's path in this commit (differs from today's path across renames)
+    path: String,
+    /// This commit's patch for the file; None for merge commits that didn't change it
+    diff: Option<FileDiff>,
+}
+
+/// Commits that touched `path`, newest first, each with that file's patch
+#[tauri::command]
+pub async fn git_file_history(
+    repo: String,
+    path: String,
+    limit: Option<usize>,
+) -> Result<Vec<FileRevision>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        if run_git(&root, ["rev-parse", "--verify", "--quiet", "HEAD"]).is_err() {
+            return Ok(Vec::new());
+        }
+        let path = repo_relative(&root, &path);
+        let max_count = format!("--max-count={}", limit.unwrap_or(50));
+        let output = run_git(
+            &root,
+            [
+                "log",
+                "--no-color",
+                "--no-ext-diff",
+                "--src-prefix=a/",
+                "--dst-prefix=b/",
+                "--follow",
+                "--patch",
+                LOG_FORMAT,
+                &max_count,
+                "--",
+                &path,
+            ],
+        )?;
+        let text = String::from_utf8_lossy(&output);
+
+        let mut revisions = Vec::new();
+        for record in text.split('\x1e').filter(|r| !r.trim().is_empty()) {
+            let (header, patch) = record.split_once('\n').unwrap_or((record, ""));
+            let Some(mut commit) = parse_commit_header(header) else {
+                continue;
+            };
+            let diff = parse_diff(patch.as_bytes()).into_iter().next();
+            if let Some(diff) = &diff {
+                commit.files_changed = 1;
+                for line in diff.hunks.iter().flat_map(|h| &h.lines) {
+                    match line.kind {
+                        DiffLineKind::Add => commit.insertions += 1,
+                        DiffLineKind::Delete => commit.deletions += 1,
+                        DiffLineKind::Context => {}
+                    }
+                }
+            }
+            let path = diff
+                .as_ref()
+                .and_then(|d| d.new_path.clone().or_else(|| d.old_path.clone()))
+                .unwrap_or_else(|| path.clone());
+            revisions.push(FileRevision { commit, path, diff });
+        }
+        Ok(revisions)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct RestoreOptions {
+    /// Also reset the staged version (index) of the paths, not just the working tree
+    staged: Option<bool>,
+    /// Restore from this revision instead of the index (or HEAD when `staged`)
+    source_rev: Option<String>,
+}
+
+/// Discard changes to `paths`. Files git doesn't track are deleted. The discarded state is
+/// checkpointed first and logged to the audit log so it can be recovered with revert_to_checkpoint.
+#[tauri::command]
+pub async fn git_restore(
+    repo: String,
+    paths: Vec<String>,
+    options: Option<RestoreOptions>,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let root = toplevel(&resolve_repo(&repo))?;
+        let paths = pathspecs(&root, &paths)?;
+        if let Some(rev) = &options.source_rev {
+            check_rev(rev)?;
+        }
+
+        let backup =
+            crate::checkpoint::create(&root, &format!("Before restore of {}", paths.join(", ")))?;
+        crate::audit::record(
+            "git_restore",
+            serde_json::json!({
+                "repo": root,
+                "paths": paths,
+                "staged": options.staged.unwrap_or(false),
+                "source_rev": options.source_rev,
+                "checkpoint": backup.id,
+            }),
+        );
+
+        let (untracked, tracked): (Vec<String>, Vec<String>) = paths
+            .into_iter()
+            .partition(|p| Path::new(&root).join(p).is_file() && is_untracked(&root, p));
+        for path in &untracked {
+            std::fs::remove_file(Path::new(&root).join(path))
+                .map_err(|e| format!("Failed to remove {}: {}", path, e))?;
+        }
+        if tracked.is_empty() {
+            return Ok(());
+        }
+
+        let mut args = vec!["restore".to_string()];
+        if options.staged.unwrap_or(false) {
+            args.push("--staged".into());
+            args.push("--worktree".into());
+        }
+        if let Some(rev) = options.source_rev {
+            args.push(format!("--source={}", rev));
+        }
+        args.push("--".into());
+        args.extend(tracked);
+        run_git(&root, &args).map(|_| ())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
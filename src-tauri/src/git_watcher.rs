@@ -0,0 +1,273 @@
+use crate::git::{operation_in_progress, resolve_repo, run_git, toplevel};
+use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tauri::ipc::Channel;
+
+/// Git writes several files per operation; wait for this much quiet before re-reading state
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum GitEvent {
+    /// Sent once when watching starts
+    #[serde(rename = "state")]
+    State {
+        branch: Option<String>,
+        head: Option<String>,
+        operation: Option<String>,
+    },
+    #[serde(rename = "branch_changed")]
+    BranchChanged {
+        from: Option<String>,
+        to: Option<String>,
+        head: Option<String>,
+    },
+    /// HEAD moved on the same branch: commit, amend, reset, pull…
+    #[serde(rename = "head_moved")]
+    HeadMoved {
+        branch: Option<String>,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    #[serde(rename = "index_changed")]
+    IndexChanged,
+    /// Refs other than HEAD were updated (fetch, tag, branch create/delete)
+    #[serde(rename = "refs_changed")]
+    RefsChanged { refs: Vec<String> },
+    #[serde(rename = "operation_started")]
+    OperationStarted { operation: String },
+    #[serde(rename = "operation_ended")]
+    OperationEnded { operation: String },
+    #[serde(rename = "error")]
+    Error { message: String },
+}
+
+#[derive(Clone, PartialEq)]
+struct RepoState {
+    branch: Option<String>,
+    head: Option<String>,
+    operation: Option<String>,
+    index_modified: Option<SystemTime>,
+}
+
+fn read_state(root: &str, git_dir: &Path) -> RepoState {
+    let text = |out: Vec<u8>| String::from_utf8_lossy(&out).trim().to_string();
+    RepoState {
+        branch: run_git(root, ["symbolic-ref", "--short", "-q", "HEAD"])
+            .ok()
+            .map(text),
+        head: run_git(root, ["rev-parse", "--verify", "--quiet", "HEAD"])
+            .ok()
+            .map(text),
+        operation: operation_in_progress(git_dir),
+        index_modified: std::fs::metadata(git_dir.join("index"))
+            .and_then(|m| m.modified())
+            .ok(),
+    }
+}
+
+fn emit_changes(channel: &Channel<GitEvent>, old: &RepoState, new: &RepoState, refs: Vec<String>) {
+    if old.operation != new.operation {
+        if let Some(operation) = &old.operation {
+            let _ = channel.send(GitEvent::OperationEnded {
+                operation: operation.clone(),
+            });
+        }
+        if let Some(operation) = &new.operation {
+            let _ = channel.send(GitEvent::OperationStarted {
+                operation: operation.clone(),
+            });
+        }
+    }
+    if old.branch != new.branch {
+        let _ = channel.send(GitEvent::BranchChanged {
+            from: old.branch.clone(),
+            to: new.branch.clone(),
+            head: new.head.clone(),
+        });
+    } else if old.head != new.head {
+        let _ = channel.send(GitEvent::HeadMoved {
+            branch: new.branch.clone(),
+            from: old.head.clone(),
+            to: new.head.clone(),
+        });
+    }
+    if old.index_modified != new.index_modified {
+        let _ = channel.send(GitEvent::IndexChanged);
+    }
+    if !refs.is_empty() {
+        let _ = channel.send(GitEvent::RefsChanged { refs });
+    }
+}
+
+/// `refs/heads/main` for paths inside a refs directory; packed-refs changes report the file itself
+fn ref_name(path: &Path, git_dirs: &[PathBuf]) -> Option<String> {
+    for dir in git_dirs {
+        if let Ok(rel) = path.strip_prefix(dir) {
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            if rel.starts_with("refs/") && !rel.ends_with(".lock") {
+                return Some(rel);
+            }
+            if rel == "packed-refs" {
+                return Some(rel);
+            }
+        }
+    }
+    None
+}
+
+struct GitWatcherEntry {
+    _watcher: RecommendedWatcher,
+    /// notify may deliver a few queued events after the watcher is dropped
+    stop: Arc<AtomicBool>,
+}
+
+pub struct GitWatcherManager {
+    watchers: Arc<Mutex<HashMap<u32, GitWatcherEntry>>>,
+    next_id: Arc<Mutex<u32>>,
+}
+
+impl GitWatcherManager {
+    pub fn new() -> Self {
+        Self {
+            watchers: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn watch_git(
+    state: tauri::State<'_, GitWatcherManager>,
+    repo: String,
+    on_event: Channel<GitEvent>,
+) -> Result<u32, String> {
+    let root = toplevel(&resolve_repo(&repo))?;
+    let path_of = |arg: &str| -> Result<PathBuf, String> {
+        let out = run_git(&root, ["rev-parse", "--path-format=absolute", arg])?;
+        Ok(PathBuf::from(String::from_utf8_lossy(&out).trim()))
+    };
+    // Linked worktrees keep HEAD/index in their own dir but share refs with the main repo
+    let git_dir = path_of("--git-dir")?;
+    let common_dir = path_of("--git-common-dir")?;
+
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+    let channel = on_event.clone();
+    let mut watcher = RecommendedWatcher::new(
+        move |res: Result<notify::Event, notify::Error>| match res {
+            Ok(event) => {
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+            Err(e) => {
+                let _ = channel.send(GitEvent::Error {
+                    message: e.to_string(),
+                });
+            }
+        },
+        Config::default(),
+    )
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+    watcher
+        .watch(&git_dir, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", git_dir.display(), e))?;
+    if common_dir != git_dir {
+        watcher
+            .watch(&common_dir.join("refs"), RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch {}: {}", common_dir.display(), e))?;
+        let _ = watcher.watch(&common_dir.join("packed-refs"), RecursiveMode::NonRecursive);
+    }
+
+    let initial = read_state(&root, &git_dir);
+    let _ = on_event.send(GitEvent::State {
+        branch: initial.branch.clone(),
+        head: initial.head.clone(),
+        operation: initial.operation.clone(),
+    });
+
+    let channel = on_event.clone();
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = stop.clone();
+    // Event paths come back canonicalized on some platforms (e.g. /private/var on macOS)
+    let git_dirs: Vec<PathBuf> = [git_dir.clone(), common_dir]
+        .into_iter()
+        .map(|d| std::fs::canonicalize(&d).unwrap_or(d))
+        .collect();
+    std::thread::spawn(move || {
+        let mut current = initial;
+        // Ends when unwatch_git drops the watcher (and with it the sender) or sets the stop flag
+        while let Ok(first) = rx.recv() {
+            let mut paths = vec![first];
+            while let Ok(path) = rx.recv_timeout(crate::power::scaled(DEBOUNCE)) {
+                paths.push(path);
+            }
+            // Object writes and reflogs are noise on their own
+            let relevant = paths.iter().any(|p| {
+                !p.components()
+                    .any(|c| c.as_os_str() == "objects" || c.as_os_str() == "logs")
+            });
+            if !relevant {
+                continue;
+            }
+            if thread_stop.load(Ordering::Relaxed) {
+                break;
+            }
+
+            let next = read_state(&root, &git_dir);
+            // The checked-out branch moving is already reported as head_moved/branch_changed
+            let checked_out: Vec<String> = [&current.branch, &next.branch]
+                .into_iter()
+                .flatten()
+                .map(|b| format!("refs/heads/{}", b))
+                .collect();
+            let refs: Vec<String> = paths
+                .iter()
+                .filter(|p| !p.is_dir())
+                .filter_map(|p| ref_name(p, &git_dirs))
+                .filter(|r| !checked_out.contains(r))
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            if next != current || !refs.is_empty() {
+                emit_changes(&channel, &current, &next, refs);
+                current = next;
+            }
+        }
+    });
+
+    let id = {
+        let mut next = state.next_id.lock().unwrap();
+        let id = *next;
+        *next += 1;
+        id
+    };
+
+    {
+        let mut watchers = state.watchers.lock().unwrap();
+        watchers.insert(
+            id,
+            GitWatcherEntry {
+                _watcher: watcher,
+                stop,
+            },
+        );
+    }
+
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn unwatch_git(state: tauri::State<'_, GitWatcherManager>, id: u32) -> Result<(), String> {
+    let mut watchers = state.watchers.lock().unwrap();
+    if let Some(entry) = watchers.remove(&id) {
+        entry.stop.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
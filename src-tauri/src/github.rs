@@ -0,0 +1,617 @@
+use crate::git::{parse_diff, resolve_repo, FileDiff};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const PR_FIELDS: &str =
+    "number,title,author,headRefName,baseRefName,state,url,isDraft,updatedAt,reviewDecision,additions,deletions";
+const API: &str = "https://api.github.com";
+/// Keychain entry holding a personal access token for the REST API
+const TOKEN_SECRET: &str = "github-token";
+/// Cached responses are served without asking GitHub for this long; older ones are revalidated
+/// with their ETag, which doesn't count against the rate limit
+const CACHE_TTL: Duration = Duration::from_secs(60);
+const MAX_CACHED: usize = 200;
+/// Comments are fetched 100 to a page, up to this many pages
+const MAX_COMMENT_PAGES: usize = 10;
+const API_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Run the authenticated `gh` CLI in `repo`; gh resolves owner/name from the git remotes
+fn gh(repo: &str, args: &[&str]) -> Result<Vec<u8>, String> {
+    let gh = crate::check_command_exists("gh".to_string())
+        .map_err(|_| "GitHub CLI (gh) is not installed".to_string())?;
+    let output = std::process::Command::new(gh)
+        .current_dir(repo)
+        .env("GH_PROMPT_DISABLED", "1")
+        .env("GH_NO_UPDATE_NOTIFIER", "1")
+        .env("NO_COLOR", "1")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run gh: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("gh auth login") {
+            return Err("GitHub CLI is not authenticated; run `gh auth login`".to_string());
+        }
+        return Err(format!("gh failed: {}", stderr.trim()));
+    }
+    Ok(output.stdout)
+}
+
+fn gh_json<T: serde::de::DeserializeOwned>(repo: &str, args: &[&str]) -> Result<T, String> {
+    let output = gh(repo, args)?;
+    serde_json::from_slice(&output).map_err(|e| format!("Failed to parse gh output: {}", e))
+}
+
+#[derive(Default, serde::Deserialize)]
+struct GhAuthor {
+    #[serde(default)]
+    login: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GhPullRequest {
+    number: u64,
+    title: String,
+    #[serde(default)]
+    author: GhAuthor,
+    head_ref_name: String,
+    base_ref_name: String,
+    state: String,
+    url: String,
+    is_draft: bool,
+    updated_at: String,
+    review_decision: Option<String>,
+    #[serde(default)]
+    additions: u64,
+    #[serde(default)]
+    deletions: u64,
+}
+
+#[derive(serde::Serialize)]
+pub struct PullRequest {
+    number: u64,
+    title: String,
+    author: String,
+    head_ref: String,
+    base_ref: String,
+    /// OPEN, CLOSED or MERGED
+    state: String,
+    url: String,
+    is_draft: bool,
+    updated_at: String,
+    /// APPROVED, CHANGES_REQUESTED, REVIEW_REQUIRED, or None when no review is required
+    review_decision: Option<String>,
+    additions: u64,
+    deletions: u64,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GhComment {
+    #[serde(default)]
+    author: GhAuthor,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default, alias = "submittedAt")]
+    created_at: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GhPrConversation {
+    #[serde(default)]
+    comments: Vec<GhComment>,
+    #[serde(default)]
+    reviews: Vec<GhComment>,
+}
+
+/// Inline review comment from the REST API (snake_case fields)
+#[derive(serde::Deserialize)]
+struct GhReviewComment {
+    user: Option<GhAuthor>,
+    body: String,
+    path: String,
+    line: Option<u32>,
+    original_line: Option<u32>,
+    diff_hunk: String,
+    created_at: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct PrComment {
+    author: String,
+    body: String,
+    /// comment, review or inline
+    kind: String,
+    /// Review state (APPROVED, CHANGES_REQUESTED, COMMENTED) for reviews
+    state: Option<String>,
+    /// File and line for inline comments
+    path: Option<String>,
+    line: Option<u32>,
+    diff_hunk: Option<String>,
+    created_at: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct PrDiff {
+    number: u64,
+    files: Vec<FileDiff>,
+    comments: Vec<PrComment>,
+}
+
+#[tauri::command]
+pub async fn gh_list_prs(
+    repo: String,
+    state: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<PullRequest>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let repo = resolve_repo(&repo);
+        let state = state.unwrap_or_else(|| "open".to_string());
+        let limit = limit.unwrap_or(30).to_string();
+        let prs: Vec<GhPullRequest> = gh_json(
+            &repo,
+            &[
+                "pr", "list", "--state", &state, "--limit", &limit, "--json", PR_FIELDS,
+            ],
+        )?;
+        Ok(prs
+            .into_iter()
+            .map(|pr| PullRequest {
+                number: pr.number,
+                title: pr.title,
+                author: pr.author.login,
+                head_ref: pr.head_ref_name,
+                base_ref: pr.base_ref_name,
+                state: pr.state,
+                url: pr.url,
+                is_draft: pr.is_draft,
+                updated_at: pr.updated_at,
+                review_decision: pr.review_decision.filter(|d| !d.is_empty()),
+                additions: pr.additions,
+                deletions: pr.deletions,
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// A PR's diff as structured hunks, plus its conversation and inline review comments
+#[tauri::command]
+pub async fn gh_pr_diff(repo: String, number: u64) -> Result<PrDiff, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let repo = resolve_repo(&repo);
+        let num = number.to_string();
+        let diff = gh(&repo, &["pr", "diff", &num, "--color=never"])?;
+
+        let conversation: GhPrConversation =
+            gh_json(&repo, &["pr", "view", &num, "--json", "comments,reviews"])?;
+        let mut comments: Vec<PrComment> = Vec::new();
+        for (kind, list) in [
+            ("comment", conversation.comments),
+            ("review", conversation.reviews),
+        ] {
+            // Reviews that only carry inline comments have an empty body
+            for c in list.into_iter().filter(|c| !c.body.trim().is_empty()) {
+                comments.push(PrComment {
+                    author: c.author.login,
+                    body: c.body,
+                    kind: kind.to_string(),
+                    state: c.state,
+                    path: None,
+                    line: None,
+                    diff_hunk: None,
+                    created_at: c.created_at,
+                });
+            }
+        }
+
+        // `{owner}/{repo}` is filled in by gh from the current repository
+        let endpoint = format!("repos/{{owner}}/{{repo}}/pulls/{}/comments", number);
+        // Paginated arrays are emitted back to back, so flatten to one object per line
+        let inline = gh(&repo, &["api", "--paginate", &endpoint, "--jq", ".[]"])?;
+        let inline = String::from_utf8_lossy(&inline);
+        let inline = inline
+            .lines()
+            .filter_map(|line| serde_json::from_str::<GhReviewComment>(line).ok());
+        comments.extend(inline.map(|c| PrComment {
+            author: c.user.map(|u| u.login).unwrap_or_default(),
+            body: c.body,
+            kind: "inline".to_string(),
+            state: None,
+            path: Some(c.path),
+            // Comments on outdated code only have the original line
+            line: c.line.or(c.original_line),
+            diff_hunk: Some(c.diff_hunk),
+            created_at: c.created_at,
+        }));
+        comments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+        Ok(PrDiff {
+            number,
+            files: parse_diff(&diff),
+            comments,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+struct Cached {
+    etag: Option<String>,
+    body: serde_json::Value,
+    /// The Link header's next page
+    next: Option<String>,
+    fetched_at: Instant,
+}
+
+static CACHE: OnceLock<Mutex<HashMap<String, Cached>>> = OnceLock::new();
+
+fn cache() -> &'static Mutex<HashMap<String, Cached>> {
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The token from the keychain, else the one the gh CLI is logged in with. Public repositories
+/// work without one, at a lower rate limit.
+fn token() -> Option<String> {
+    if let Some(token) = crate::secrets::secret(TOKEN_SECRET) {
+        return Some(token.trim().to_string());
+    }
+    let gh = crate::check_command_exists("gh".to_string()).ok()?;
+    let output = std::process::Command::new(gh)
+        .args(["auth", "token"])
+        .env("GH_PROMPT_DISABLED", "1")
+        .output()
+        .ok()?;
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !token.is_empty()).then_some(token)
+}
+
+/// owner/name for `repo`: a local checkout (read from its origin remote) or owner/name itself
+fn repo_slug(repo: &str) -> Result<String, String> {
+    let path = resolve_repo(repo);
+    if std::path::Path::new(&path).is_dir() {
+        let url = crate::git::run_git(&path, ["remote", "get-url", "origin"])
+            .map_err(|_| format!("{} has no origin remote", repo))?;
+        let url = String::from_utf8_lossy(&url).trim().to_string();
+        // git@github.com:owner/name.git, https://github.com/owner/name(.git)
+        let slug = url
+            .split_once("github.com")
+            .map(|(_, rest)| rest.trim_start_matches([':', '/']))
+            .map(|rest| rest.trim_end_matches('/').trim_end_matches(".git"))
+            .filter(|slug| slug.split('/').count() == 2);
+        return slug
+            .map(str::to_string)
+            .ok_or_else(|| format!("{} is not a GitHub repository", repo));
+    }
+    let valid = |part: &str| {
+        !part.is_empty()
+            && !part.starts_with('-')
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    match repo.split_once('/') {
+        Some((owner, name)) if valid(owner) && valid(name) => Ok(repo.to_string()),
+        _ => Err(format!("Not a repository: {}", repo)),
+    }
+}
+
+/// The rel="next" URL of a Link header
+fn next_link(link: &str) -> Option<String> {
+    link.split(',').find_map(|part| {
+        let (url, rel) = part.split_once(';')?;
+        rel.contains("rel=\"next\"").then(|| {
+            url.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string()
+        })
+    })
+}
+
+/// GET a REST API URL, from the cache while it is fresh (unless `refresh`); the body and the
+/// next page's URL
+fn api_get(url: &str, refresh: bool) -> Result<(serde_json::Value, Option<String>), String> {
+    let etag = {
+        let cache = cache().lock().unwrap();
+        match cache.get(url) {
+            Some(cached) if !refresh && cached.fetched_at.elapsed() < CACHE_TTL => {
+                return Ok((cached.body.clone(), cached.next.clone()));
+            }
+            Some(cached) => cached.etag.clone(),
+            None => None,
+        }
+    };
+    let client = reqwest::blocking::Client::builder()
+        // GitHub's API rejects requests without one
+        .user_agent(concat!("better-terminal/", env!("CARGO_PKG_VERSION")))
+        .timeout(API_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let token = token();
+    let mut request = client
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", "2022-11-28");
+    if let Some(token) = &token {
+        request = request.bearer_auth(token);
+    }
+    if let Some(etag) = &etag {
+        request = request.header("If-None-Match", etag);
+    }
+    let response = request
+        .send()
+        .map_err(|e| format!("Failed to reach GitHub: {}", e))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_MODIFIED {
+        let mut cache = cache().lock().unwrap();
+        if let Some(cached) = cache.get_mut(url) {
+            cached.fetched_at = Instant::now();
+            return Ok((cached.body.clone(), cached.next.clone()));
+        }
+    }
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let rate_limited = header("x-ratelimit-remaining").as_deref() == Some("0");
+    match status.as_u16() {
+        200 => {}
+        401 => return Err("GitHub rejected the token".to_string()),
+        403 | 429 if rate_limited => {
+            return Err(match token {
+                Some(_) => "GitHub API rate limit reached; try again later".to_string(),
+                None => "GitHub API rate limit reached; add a GitHub token to raise it".to_string(),
+            })
+        }
+        404 if token.is_none() => {
+            return Err("Not found on GitHub (private repositories need a token)".to_string())
+        }
+        404 => return Err("Not found on GitHub".to_string()),
+        _ => return Err(format!("GitHub request failed: {}", status)),
+    }
+    let next = header("link").as_deref().and_then(next_link);
+    let etag = header("etag");
+    let body: serde_json::Value = response
+        .json()
+        .map_err(|e| format!("Failed to parse GitHub response: {}", e))?;
+
+    let mut cache = cache().lock().unwrap();
+    if cache.len() >= MAX_CACHED {
+        let oldest = cache
+            .iter()
+            .min_by_key(|(_, cached)| cached.fetched_at)
+            .map(|(url, _)| url.clone());
+        if let Some(oldest) = oldest {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(
+        url.to_string(),
+        Cached {
+            etag,
+            body: body.clone(),
+            next: next.clone(),
+            fetched_at: Instant::now(),
+        },
+    );
+    Ok((body, next))
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct IssueFilters {
+    /// open (the default), closed or all
+    state: Option<String>,
+    /// Issues with every one of these labels
+    #[serde(default)]
+    labels: Vec<String>,
+    /// A login, "none" or "*"
+    assignee: Option<String>,
+    creator: Option<String>,
+    mentioned: Option<String>,
+    /// A milestone number, "none" or "*"
+    milestone: Option<String>,
+    /// created, updated (the default) or comments
+    sort: Option<String>,
+    /// asc or desc (the default)
+    direction: Option<String>,
+    /// Only issues updated at or after this ISO 8601 timestamp
+    since: Option<String>,
+    /// 30 by default, at most 100
+    per_page: Option<u32>,
+    page: Option<u32>,
+    /// The issues API lists pull requests too; they are left out unless asked for
+    #[serde(default)]
+    include_pull_requests: bool,
+    /// Ask GitHub even when the cached response is recent
+    #[serde(default)]
+    refresh: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct GhLabel {
+    name: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GhIssue {
+    number: u64,
+    title: String,
+    state: String,
+    user: Option<GhAuthor>,
+    #[serde(default)]
+    labels: Vec<GhLabel>,
+    #[serde(default)]
+    assignees: Vec<GhAuthor>,
+    #[serde(default)]
+    comments: u64,
+    html_url: String,
+    pull_request: Option<serde_json::Value>,
+    body: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct Issue {
+    number: u64,
+    title: String,
+    /// open or closed
+    state: String,
+    author: String,
+    labels: Vec<String>,
+    assignees: Vec<String>,
+    comment_count: u64,
+    url: String,
+    is_pull_request: bool,
+    body: String,
+    created_at: String,
+    updated_at: String,
+}
+
+impl From<GhIssue> for Issue {
+    fn from(issue: GhIssue) -> Self {
+        Issue {
+            number: issue.number,
+            title: issue.title,
+            state: issue.state,
+            author: issue.user.map(|u| u.login).unwrap_or_default(),
+            labels: issue.labels.into_iter().map(|l| l.name).collect(),
+            assignees: issue.assignees.into_iter().map(|a| a.login).collect(),
+            comment_count: issue.comments,
+            url: issue.html_url,
+            is_pull_request: issue.pull_request.is_some(),
+            body: issue.body.unwrap_or_default(),
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct GhIssueComment {
+    user: Option<GhAuthor>,
+    #[serde(default)]
+    body: String,
+    html_url: String,
+    created_at: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct IssueComment {
+    author: String,
+    body: String,
+    url: String,
+    created_at: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct IssueDetail {
+    #[serde(flatten)]
+    issue: Issue,
+    /// Oldest first
+    comments: Vec<IssueComment>,
+}
+
+/// Issues of `repo` (a local checkout or owner/name) through the REST API, with the token from
+/// the keychain or the gh CLI. Responses are cached for a minute, then revalidated.
+#[tauri::command]
+pub async fn github_list_issues(
+    repo: String,
+    filters: Option<IssueFilters>,
+) -> Result<Vec<Issue>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let filters = filters.unwrap_or_default();
+        let slug = repo_slug(&repo)?;
+        let mut params: Vec<(&str, String)> = vec![
+            ("state", filters.state.unwrap_or_else(|| "open".to_string())),
+            (
+                "per_page",
+                filters.per_page.unwrap_or(30).min(100).to_string(),
+            ),
+            ("page", filters.page.unwrap_or(1).to_string()),
+        ];
+        if !filters.labels.is_empty() {
+            params.push(("labels", filters.labels.join(",")));
+        }
+        for (name, value) in [
+            ("assignee", filters.assignee),
+            ("creator", filters.creator),
+            ("mentioned", filters.mentioned),
+            ("milestone", filters.milestone),
+            ("sort", filters.sort),
+            ("direction", filters.direction),
+            ("since", filters.since),
+        ] {
+            if let Some(value) = value {
+                params.push((name, value));
+            }
+        }
+        let url =
+            reqwest::Url::parse_with_params(&format!("{}/repos/{}/issues", API, slug), &params)
+                .map_err(|e| e.to_string())?;
+        let (body, _) = api_get(url.as_str(), filters.refresh)?;
+        let issues: Vec<GhIssue> =
+            serde_json::from_value(body).map_err(|e| format!("Failed to parse issues: {}", e))?;
+        Ok(issues
+            .into_iter()
+            .filter(|issue| filters.include_pull_requests || issue.pull_request.is_none())
+            .map(Issue::from)
+            .collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// One issue or pull request with its conversation, ready to hand to an agent as a task
+#[tauri::command]
+pub async fn github_get_issue(
+    repo: String,
+    number: u64,
+    refresh: Option<bool>,
+) -> Result<IssueDetail, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let refresh = refresh.unwrap_or(false);
+        let slug = repo_slug(&repo)?;
+        let (body, _) = api_get(
+            &format!("{}/repos/{}/issues/{}", API, slug, number),
+            refresh,
+        )?;
+        let issue: GhIssue =
+            serde_json::from_value(body).map_err(|e| format!("Failed to parse issue: {}", e))?;
+
+        let mut comments = Vec::new();
+        let mut next = Some(format!(
+            "{}/repos/{}/issues/{}/comments?per_page=100",
+            API, slug, number
+        ));
+        for _ in 0..MAX_COMMENT_PAGES {
+            let Some(url) = next.take() else { break };
+            let (body, more) = api_get(&url, refresh)?;
+            let page: Vec<GhIssueComment> = serde_json::from_value(body)
+                .map_err(|e| format!("Failed to parse comments: {}", e))?;
+            comments.extend(page.into_iter().map(|c| IssueComment {
+                author: c.user.map(|u| u.login).unwrap_or_default(),
+                body: c.body,
+                url: c.html_url,
+                created_at: c.created_at,
+            }));
+            next = more;
+        }
+        Ok(IssueDetail {
+            issue: issue.into(),
+            comments,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
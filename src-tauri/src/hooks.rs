@@ -0,0 +1,505 @@
+use crate::claude_config::{read_json, settings_path, write_json};
+use serde_json::{Map, Value};
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+/// Hook events Claude Code fires, and whether each takes a matcher
+const HOOK_EVENTS: &[(&str, bool)] = &[
+    ("PreToolUse", true),
+    ("PostToolUse", true),
+    ("Notification", false),
+    ("UserPromptSubmit", false),
+    ("Stop", false),
+    ("SubagentStop", false),
+    ("PreCompact", true),
+    ("SessionStart", true),
+    ("SessionEnd", false),
+];
+/// Claude Code's own default for hooks without a timeout
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// One command hook, flattened out of its event and matcher group
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct HookDefinition {
+    event: String,
+    /// Tool name regex for tool events, trigger/source for PreCompact/SessionStart;
+    /// empty or missing matches everything
+    #[serde(default)]
+    matcher: Option<String>,
+    command: String,
+    /// Seconds
+    #[serde(default)]
+    timeout: Option<u64>,
+}
+
+#[derive(serde::Serialize)]
+pub struct HookIssue {
+    /// error or warning
+    severity: String,
+    event: String,
+    matcher: Option<String>,
+    command: Option<String>,
+    message: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct HookTestResult {
+    exit_code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    duration_ms: u64,
+    timed_out: bool,
+    /// Exit code 2 blocks the action and feeds stderr back to Claude
+    blocked: bool,
+    /// stdout parsed as JSON, for hooks that answer with a decision object
+    output_json: Option<Value>,
+}
+
+fn event_takes_matcher(event: &str) -> Option<bool> {
+    HOOK_EVENTS
+        .iter()
+        .find(|(name, _)| *name == event)
+        .map(|(_, matcher)| *matcher)
+}
+
+/// Empty and `*` both mean "match everything"
+fn normalize_matcher(matcher: Option<&str>) -> Option<String> {
+    matcher
+        .map(str::trim)
+        .filter(|m| !m.is_empty() && *m != "*")
+        .map(String::from)
+}
+
+impl HookDefinition {
+    fn validate(&self) -> Result<(), String> {
+        let takes_matcher = event_takes_matcher(&self.event)
+            .ok_or_else(|| format!("Unknown hook event: {}", self.event))?;
+        if self.command.trim().is_empty() {
+            return Err("Hook command must not be empty".to_string());
+        }
+        if self.timeout == Some(0) {
+            return Err("Hook timeout must be at least 1 second".to_string());
+        }
+        match normalize_matcher(self.matcher.as_deref()) {
+            Some(_) if !takes_matcher => Err(format!("{} hooks don't take a matcher", self.event)),
+            Some(matcher) => regex::Regex::new(&matcher)
+                .map(|_| ())
+                .map_err(|e| format!("Invalid matcher {}: {}", matcher, e)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn group_matcher(group: &Value) -> Option<String> {
+    normalize_matcher(group.get("matcher").and_then(Value::as_str))
+}
+
+/// Every command hook in the settings, in file order
+fn flatten_hooks(settings: &Value) -> Vec<HookDefinition> {
+    let mut hooks = Vec::new();
+    let Some(events) = settings.get("hooks").and_then(Value::as_object) else {
+        return hooks;
+    };
+    for (event, groups) in events {
+        for group in groups.as_array().into_iter().flatten() {
+            let entries = group.get("hooks").and_then(Value::as_array);
+            for entry in entries.into_iter().flatten() {
+                let Some(command) = entry.get("command").and_then(Value::as_str) else {
+                    continue;
+                };
+                hooks.push(HookDefinition {
+                    event: event.clone(),
+                    matcher: group_matcher(group),
+                    command: command.to_string(),
+                    timeout: entry.get("timeout").and_then(Value::as_u64),
+                });
+            }
+        }
+    }
+    hooks
+}
+
+/// Check the structure Claude Code expects, reporting every problem rather than the first
+fn hook_issues(settings: &Value) -> Vec<HookIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |severity: &str,
+                     event: &str,
+                     matcher: Option<String>,
+                     command: Option<&str>,
+                     message: String| {
+        issues.push(HookIssue {
+            severity: severity.to_string(),
+            event: event.to_string(),
+            matcher,
+            command: command.map(String::from),
+            message,
+        })
+    };
+    let Some(hooks) = settings.get("hooks") else {
+        return issues;
+    };
+    let Some(events) = hooks.as_object() else {
+        issue(
+            "error",
+            "",
+            None,
+            None,
+            "hooks must be an object".to_string(),
+        );
+        return issues;
+    };
+    for (event, groups) in events {
+        let Some(takes_matcher) = event_takes_matcher(event) else {
+            issue(
+                "error",
+                event,
+                None,
+                None,
+                format!("Unknown hook event: {}", event),
+            );
+            continue;
+        };
+        let Some(groups) = groups.as_array() else {
+            issue(
+                "error",
+                event,
+                None,
+                None,
+                format!("{} must be an array", event),
+            );
+            continue;
+        };
+        for group in groups {
+            let matcher = group_matcher(group);
+            if let Some(m) = &matcher {
+                if !takes_matcher {
+                    issue(
+                        "warning",
+                        event,
+                        matcher.clone(),
+                        None,
+                        format!("{} hooks ignore the matcher", event),
+                    );
+                } else if let Err(e) = regex::Regex::new(m) {
+                    issue(
+                        "error",
+                        event,
+                        matcher.clone(),
+                        None,
+                        format!("Invalid matcher: {}", e),
+                    );
+                }
+            }
+            let Some(entries) = group.get("hooks").and_then(Value::as_array) else {
+                issue(
+                    "error",
+                    event,
+                    matcher.clone(),
+                    None,
+                    "Missing hooks array".to_string(),
+                );
+                continue;
+            };
+            for entry in entries {
+                let command = entry.get("command").and_then(Value::as_str);
+                match entry.get("type").and_then(Value::as_str) {
+                    Some("command") => {}
+                    Some(other) => issue(
+                        "warning",
+                        event,
+                        matcher.clone(),
+                        command,
+                        format!("Unsupported hook type: {}", other),
+                    ),
+                    None => issue(
+                        "error",
+                        event,
+                        matcher.clone(),
+                        command,
+                        "Hook is missing type".to_string(),
+                    ),
+                }
+                if command.is_none_or(|c| c.trim().is_empty()) {
+                    issue(
+                        "error",
+                        event,
+                        matcher.clone(),
+                        None,
+                        "Hook has no command".to_string(),
+                    );
+                }
+                if let Some(timeout) = entry.get("timeout") {
+                    if timeout.as_u64().is_none_or(|t| t == 0) {
+                        issue(
+                            "error",
+                            event,
+                            matcher.clone(),
+                            command,
+                            "timeout must be a positive number of seconds".to_string(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+    issues
+}
+
+/// Read-modify-write the `hooks` object of one settings scope
+fn edit_hooks(
+    scope: &str,
+    project: Option<&str>,
+    edit: impl FnOnce(&mut Map<String, Value>) -> Result<(), String>,
+) -> Result<Vec<HookDefinition>, String> {
+    let path = settings_path(scope, project)?;
+    let mut settings = read_json(&path)?;
+    let Some(root) = settings.as_object_mut() else {
+        return Err(format!("{} is not a JSON object", path.display()));
+    };
+    let hooks = root
+        .entry("hooks")
+        .or_insert_with(|| Value::Object(Map::new()));
+    if !hooks.is_object() {
+        return Err("hooks must be an object".to_string());
+    }
+    let hooks = hooks.as_object_mut().unwrap();
+    edit(hooks)?;
+    if hooks.is_empty() {
+        root.remove("hooks");
+    }
+    write_json(&path, &settings)?;
+    Ok(flatten_hooks(&settings))
+}
+
+/// Command hooks configured in one settings scope
+#[tauri::command]
+pub fn list_claude_hooks(
+    scope: String,
+    project: Option<String>,
+) -> Result<Vec<HookDefinition>, String> {
+    let settings = read_json(&settings_path(&scope, project.as_deref())?)?;
+    Ok(flatten_hooks(&settings))
+}
+
+#[tauri::command]
+pub fn validate_claude_hooks(
+    scope: String,
+    project: Option<String>,
+) -> Result<Vec<HookIssue>, String> {
+    let settings = read_json(&settings_path(&scope, project.as_deref())?)?;
+    Ok(hook_issues(&settings))
+}
+
+/// Add a command hook to the group with the same matcher, creating the group if needed.
+/// Returns the scope's hooks afterwards.
+#[tauri::command]
+pub fn add_claude_hook(
+    scope: String,
+    hook: HookDefinition,
+    project: Option<String>,
+) -> Result<Vec<HookDefinition>, String> {
+    hook.validate()?;
+    let matcher = normalize_matcher(hook.matcher.as_deref());
+    edit_hooks(&scope, project.as_deref(), |events| {
+        let groups = events
+            .entry(hook.event.clone())
+            .or_insert_with(|| Value::Array(Vec::new()));
+        let Some(groups) = groups.as_array_mut() else {
+            return Err(format!("hooks.{} must be an array", hook.event));
+        };
+        let position = groups.iter().position(|g| group_matcher(g) == matcher);
+        let group = match position {
+            Some(index) => &mut groups[index],
+            None => {
+                let mut group = Map::new();
+                if let Some(matcher) = &matcher {
+                    group.insert("matcher".to_string(), Value::from(matcher.as_str()));
+                }
+                group.insert("hooks".to_string(), Value::Array(Vec::new()));
+                groups.push(Value::Object(group));
+                groups.last_mut().unwrap()
+            }
+        };
+        let Some(entries) = group.get_mut("hooks").and_then(Value::as_array_mut) else {
+            return Err(format!(
+                "hooks.{} has a group without a hooks array",
+                hook.event
+            ));
+        };
+        if entries
+            .iter()
+            .any(|e| e.get("command").and_then(Value::as_str) == Some(hook.command.as_str()))
+        {
+            return Err("This hook already exists".to_string());
+        }
+        let mut entry = serde_json::json!({ "type": "command", "command": hook.command });
+        if let Some(timeout) = hook.timeout {
+            entry["timeout"] = Value::from(timeout);
+        }
+        entries.push(entry);
+        Ok(())
+    })
+}
+
+/// Remove a command hook, dropping its group and event when they end up empty
+#[tauri::command]
+pub fn remove_claude_hook(
+    scope: String,
+    hook: HookDefinition,
+    project: Option<String>,
+) -> Result<Vec<HookDefinition>, String> {
+    let matcher = normalize_matcher(hook.matcher.as_deref());
+    edit_hooks(&scope, project.as_deref(), |events| {
+        let mut removed = false;
+        if let Some(groups) = events.get_mut(&hook.event).and_then(Value::as_array_mut) {
+            for group in groups.iter_mut().filter(|g| group_matcher(g) == matcher) {
+                if let Some(entries) = group.get_mut("hooks").and_then(Value::as_array_mut) {
+                    let before = entries.len();
+                    entries.retain(|e| {
+                        e.get("command").and_then(Value::as_str) != Some(hook.command.as_str())
+                    });
+                    removed |= entries.len() != before;
+                }
+            }
+            groups.retain(|g| {
+                g.get("hooks")
+                    .and_then(Value::as_array)
+                    .is_none_or(|entries| !entries.is_empty())
+            });
+            if groups.is_empty() {
+                events.remove(&hook.event);
+            }
+        }
+        if removed {
+            Ok(())
+        } else {
+            Err(format!("Hook not found under {}", hook.event))
+        }
+    })
+}
+
+/// The stdin Claude Code sends for an event, with plausible placeholder values
+fn sample_payload(event: &str, cwd: &str) -> Value {
+    let mut payload = serde_json::json!({
+        "session_id": "00000000-0000-0000-0000-000000000000",
+        "transcript_path": "",
+        "cwd": cwd,
+        "hook_event_name": event,
+    });
+    let extra = match event {
+        "PreToolUse" => serde_json::json!({
+            "tool_name": "Bash",
+            "tool_input": { "command": "echo hello", "description": "Print hello" },
+        }),
+        "PostToolUse" => serde_json::json!({
+            "tool_name": "Bash",
+            "tool_input": { "command": "echo hello", "description": "Print hello" },
+            "tool_response": { "stdout": "hello\n", "stderr": "", "interrupted": false },
+        }),
+        "Notification" => {
+            serde_json::json!({ "message": "Claude needs your permission to use Bash" })
+        }
+        "UserPromptSubmit" => {
+            serde_json::json!({ "prompt": "Write a function that adds two numbers" })
+        }
+        "Stop" | "SubagentStop" => serde_json::json!({ "stop_hook_active": false }),
+        "PreCompact" => serde_json::json!({ "trigger": "manual", "custom_instructions": "" }),
+        "SessionStart" => serde_json::json!({ "source": "startup" }),
+        "SessionEnd" => serde_json::json!({ "reason": "exit" }),
+        _ => Value::Null,
+    };
+    if let (Some(payload), Value::Object(extra)) = (payload.as_object_mut(), extra) {
+        payload.extend(extra);
+    }
+    payload
+}
+
+/// Run a hook command the way Claude Code does: through the shell, with the event payload on
+/// stdin and CLAUDE_PROJECT_DIR set. Fields in `payload` override the sample for the event.
+#[tauri::command]
+pub async fn test_hook(
+    event: String,
+    command: String,
+    payload: Option<Value>,
+    cwd: Option<String>,
+    timeout: Option<u64>,
+) -> Result<HookTestResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if event_takes_matcher(&event).is_none() {
+            return Err(format!("Unknown hook event: {}", event));
+        }
+        let cwd = crate::git::resolve_repo(&cwd.unwrap_or_else(crate::get_home_dir));
+        let mut input = sample_payload(&event, &cwd);
+        if let (Some(input), Some(Value::Object(overrides))) = (input.as_object_mut(), payload) {
+            input.extend(overrides);
+        }
+
+        let started = Instant::now();
+        let mut cmd = std::process::Command::new("sh");
+        // Own process group, so a timeout also kills whatever the hook spawned
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+        let mut child = cmd
+            .arg("-c")
+            .arg(&command)
+            .current_dir(&cwd)
+            .env("CLAUDE_PROJECT_DIR", &cwd)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run hook: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // A hook that never reads stdin closes the pipe early; that's not an error
+            let _ = stdin.write_all(input.to_string().as_bytes());
+        }
+        let mut stdout = child.stdout.take().unwrap();
+        let mut stderr = child.stderr.take().unwrap();
+        let out = std::thread::spawn(move || {
+            let mut buf = String::new();
+            let _ = stdout.read_to_string(&mut buf);
+            buf
+        });
+        let err = std::thread::spawn(move || {
+            let mut buf = String::new();
+            let _ = stderr.read_to_string(&mut buf);
+            buf
+        });
+
+        let deadline = started + Duration::from_secs(timeout.unwrap_or(DEFAULT_TIMEOUT_SECS));
+        let mut timed_out = false;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Some(status),
+                Ok(None) if Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(20))
+                }
+                Ok(None) => {
+                    timed_out = true;
+                    #[cfg(unix)]
+                    let _ = std::process::Command::new("kill")
+                        .args(["-KILL", "--", &format!("-{}", child.id())])
+                        .status();
+                    let _ = child.kill();
+                    break child.wait().ok();
+                }
+                Err(e) => return Err(format!("Failed to wait for hook: {}", e)),
+            }
+        };
+        let stdout = out.join().unwrap_or_default();
+        let exit_code = status.and_then(|s| s.code()).filter(|_| !timed_out);
+        Ok(HookTestResult {
+            exit_code,
+            output_json: serde_json::from_str(stdout.trim())
+                .ok()
+                .filter(Value::is_object),
+            stdout,
+            stderr: err.join().unwrap_or_default(),
+            duration_ms: started.elapsed().as_millis() as u64,
+            timed_out,
+            blocked: exit_code == Some(2),
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
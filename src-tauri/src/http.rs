@@ -0,0 +1,151 @@
+//! HTTP requests on behalf of the frontend, free of the webview's CORS rules. Bodies stream
+//! back in chunks as they arrive and are cut off past a size limit, so a large or endless
+//! response can't exhaust memory. Proxies come from the system settings and the usual
+//! HTTP(S)_PROXY variables.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::time::{Duration, Instant};
+use tauri::ipc::Channel;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+const CHUNK_BYTES: usize = 64 * 1024;
+
+#[derive(Default, serde::Deserialize)]
+pub struct HttpOptions {
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    body: Option<String>,
+    /// For the whole request, body included (30 by default)
+    timeout_secs: Option<u64>,
+    /// Body bytes read before stopping (10 MB by default)
+    max_bytes: Option<u64>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum HttpEvent {
+    /// Status and headers, before any of the body
+    #[serde(rename = "response")]
+    Response {
+        status: u16,
+        /// After redirects
+        url: String,
+        /// Repeated headers are joined with ", "
+        headers: BTreeMap<String, String>,
+    },
+    #[serde(rename = "chunk")]
+    Chunk { data: Vec<u8> },
+}
+
+#[derive(serde::Serialize)]
+pub struct HttpSummary {
+    status: u16,
+    url: String,
+    bytes: u64,
+    /// The body went past max_bytes and was cut off
+    truncated: bool,
+    duration_ms: u64,
+}
+
+/// Send a request and stream the response to `on_chunk`: a `response` event, then `chunk`
+/// events with the body. Resolves once the body has been read; HTTP error statuses aren't
+/// errors here.
+#[tauri::command]
+pub async fn http_request(
+    method: String,
+    url: String,
+    options: Option<HttpOptions>,
+    on_chunk: Channel<HttpEvent>,
+) -> Result<HttpSummary, String> {
+    let options = options.unwrap_or_default();
+    let method = reqwest::Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+        .map_err(|_| format!("Invalid method: {}", method))?;
+    let parsed = reqwest::Url::parse(&url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Only http and https URLs can be fetched: {}", url));
+    }
+    let headers = crate::mcp::header_map(&options.headers)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        let client = reqwest::blocking::Client::builder()
+            .user_agent(concat!("better-terminal/", env!("CARGO_PKG_VERSION")))
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(
+                options
+                    .timeout_secs
+                    .map_or(DEFAULT_TIMEOUT, Duration::from_secs),
+            )
+            .build()
+            .map_err(|e| e.to_string())?;
+        let mut request = client.request(method, parsed).headers(headers);
+        if let Some(body) = options.body {
+            request = request.body(body);
+        }
+        let mut response = request
+            .send()
+            .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+
+        let status = response.status().as_u16();
+        let final_url = response.url().to_string();
+        let mut response_headers: BTreeMap<String, String> = BTreeMap::new();
+        for (name, value) in response.headers() {
+            let value = String::from_utf8_lossy(value.as_bytes()).to_string();
+            response_headers
+                .entry(name.to_string())
+                .and_modify(|existing| {
+                    existing.push_str(", ");
+                    existing.push_str(&value);
+                })
+                .or_insert(value);
+        }
+        on_chunk
+            .send(HttpEvent::Response {
+                status,
+                url: final_url.clone(),
+                headers: response_headers,
+            })
+            .map_err(|e| e.to_string())?;
+
+        let max_bytes = options.max_bytes.unwrap_or(DEFAULT_MAX_BYTES);
+        let mut buf = vec![0u8; CHUNK_BYTES];
+        let mut bytes = 0u64;
+        let mut truncated = false;
+        loop {
+            let n = response
+                .read(&mut buf)
+                .map_err(|e| format!("Failed to read the response from {}: {}", url, e))?;
+            if n == 0 {
+                break;
+            }
+            let allowed = (max_bytes - bytes).min(n as u64) as usize;
+            if allowed > 0 {
+                // A closed channel means the caller went away; stop reading
+                if on_chunk
+                    .send(HttpEvent::Chunk {
+                        data: buf[..allowed].to_vec(),
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+                bytes += allowed as u64;
+            }
+            if allowed < n {
+                truncated = true;
+                break;
+            }
+        }
+        Ok(HttpSummary {
+            status,
+            url: final_url,
+            bytes,
+            truncated,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
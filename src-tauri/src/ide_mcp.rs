@@ -0,0 +1,477 @@
+use crate::claude_config::McpServerDefinition;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Kept stable so the entry registered in ~/.claude.json rarely has to change
+const PREFERRED_PORT: u16 = 39217;
+const SERVER_NAME: &str = "better-terminal";
+const SUPPORTED_VERSIONS: [&str; 3] = ["2024-11-05", "2025-03-26", "2025-06-18"];
+const MAX_BODY: usize = 4 * 1024 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// Task output beyond this is cut from the front; the end of a build log is what matters
+const MAX_TASK_OUTPUT: usize = 32 * 1024;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Selection {
+    path: Option<String>,
+    text: String,
+    start_line: u32,
+    end_line: u32,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Diagnostic {
+    line: u32,
+    column: u32,
+    /// error, warning, info or hint
+    severity: String,
+    message: String,
+    source: Option<String>,
+}
+
+#[derive(Default)]
+struct IdeSnapshot {
+    selection: Option<Selection>,
+    diagnostics: HashMap<String, Vec<Diagnostic>>,
+}
+
+/// Editor state reported by the frontend, served to agents over MCP
+#[derive(Default)]
+pub struct IdeContext {
+    snapshot: Arc<Mutex<IdeSnapshot>>,
+}
+
+#[tauri::command]
+pub fn set_ide_selection(
+    state: tauri::State<'_, IdeContext>,
+    selection: Option<Selection>,
+) -> Result<(), String> {
+    state.snapshot.lock().unwrap().selection = selection.filter(|s| !s.text.is_empty());
+    Ok(())
+}
+
+/// Replace the diagnostics for one file; an empty list clears it
+#[tauri::command]
+pub fn set_ide_diagnostics(
+    state: tauri::State<'_, IdeContext>,
+    path: String,
+    diagnostics: Vec<Diagnostic>,
+) -> Result<(), String> {
+    let mut snapshot = state.snapshot.lock().unwrap();
+    if diagnostics.is_empty() {
+        snapshot.diagnostics.remove(&path);
+    } else {
+        snapshot.diagnostics.insert(path, diagnostics);
+    }
+    Ok(())
+}
+
+struct Server {
+    app: AppHandle,
+    snapshot: Arc<Mutex<IdeSnapshot>>,
+    token: String,
+    port: u16,
+}
+
+fn tools() -> Value {
+    json!([
+        {
+            "name": "open_file",
+            "description": "Open a file in an editor tab of the IDE so the user can see it",
+            "inputSchema": {
+                "type": "object",
+                "properties": {"path": {"type": "string", "description": "Absolute path"}},
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "get_selection",
+            "description": "Text currently selected in the IDE's editor, with its file and line range",
+            "inputSchema": {"type": "object", "properties": {}}
+        },
+        {
+            "name": "get_diagnostics",
+            "description": "Errors and warnings the IDE's editor reports for open files",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string", "description": "Only this file (absolute path)"}
+                }
+            }
+        },
+        {
+            "name": "search_index",
+            "description": "Query the IDE's project index: fuzzy file names, symbol definitions, or text",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "root": {"type": "string", "description": "Project root (absolute path)"},
+                    "query": {"type": "string"},
+                    "kind": {"type": "string", "enum": ["files", "symbols", "text"]},
+                    "limit": {"type": "integer"}
+                },
+                "required": ["root", "query"]
+            }
+        },
+        {
+            "name": "list_tasks",
+            "description": "Tasks runnable in a directory: package.json scripts, Makefile targets, justfile recipes and Cargo aliases",
+            "inputSchema": {
+                "type": "object",
+                "properties": {"cwd": {"type": "string", "description": "Absolute path"}},
+                "required": ["cwd"]
+            }
+        },
+        {
+            "name": "run_task",
+            "description": "Run a task from list_tasks and return its exit code and output",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "cwd": {"type": "string", "description": "Absolute path"},
+                    "task": {"type": "string"}
+                },
+                "required": ["cwd", "task"]
+            }
+        }
+    ])
+}
+
+fn str_arg<'a>(args: &'a Value, key: &str) -> Result<&'a str, String> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("Missing argument: {}", key))
+}
+
+fn to_text<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| e.to_string())
+}
+
+fn run_task(cwd: &str, task: &str) -> Result<String, String> {
+    let task = crate::tasks::find(cwd, task)?;
+    let program = crate::check_command_exists(task.program.clone())
+        .map_err(|_| format!("{} is not installed", task.program))?;
+    let output = std::process::Command::new(program)
+        .args(&task.args)
+        .current_dir(cwd)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", task.command, e))?;
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    if text.len() > MAX_TASK_OUTPUT {
+        let mut cut = text.len() - MAX_TASK_OUTPUT;
+        while !text.is_char_boundary(cut) {
+            cut += 1;
+        }
+        text = format!("…(output truncated)\n{}", &text[cut..]);
+    }
+    Ok(format!(
+        "exit code: {}\n\n{}",
+        output
+            .status
+            .code()
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "killed".to_string()),
+        text
+    ))
+}
+
+fn call_tool(server: &Server, name: &str, args: &Value) -> Result<String, String> {
+    match name {
+        "open_file" => {
+            let path = crate::git::resolve_repo(str_arg(args, "path")?);
+            if !std::path::Path::new(&path).is_file() {
+                return Err(format!("Not a file: {}", path));
+            }
+            server
+                .app
+                .emit("ide-open-file", json!({ "path": path }))
+                .map_err(|e| e.to_string())?;
+            Ok(format!("Opened {}", path))
+        }
+        "get_selection" => match &server.snapshot.lock().unwrap().selection {
+            Some(selection) => to_text(selection),
+            None => Ok("Nothing is selected".to_string()),
+        },
+        "get_diagnostics" => {
+            let snapshot = server.snapshot.lock().unwrap();
+            match args.get("path").and_then(Value::as_str) {
+                Some(path) => to_text(&snapshot.diagnostics.get(path).cloned().unwrap_or_default()),
+                None => to_text(&snapshot.diagnostics),
+            }
+        }
+        "search_index" => {
+            let root = crate::git::resolve_repo(str_arg(args, "root")?);
+            let query = str_arg(args, "query")?.to_string();
+            let limit = args
+                .get("limit")
+                .and_then(Value::as_u64)
+                .map(|l| l as usize);
+            let state = server.app.state::<crate::index::IndexManager>();
+            match args.get("kind").and_then(Value::as_str).unwrap_or("files") {
+                "files" => to_text(&crate::index::search_files(state, root, query, limit)?),
+                "symbols" => to_text(&crate::index::search_symbols(
+                    state,
+                    root,
+                    query,
+                    limit,
+                    Some(2),
+                )?),
+                "text" => {
+                    let remote = server.app.state::<crate::remote::RemoteManager>();
+                    let mut matches = tauri::async_runtime::block_on(crate::index::search_text(
+                        state, remote, root, query, None, None,
+                    ))?;
+                    matches.truncate(limit.unwrap_or(100));
+                    to_text(&matches)
+                }
+                other => Err(format!("Unknown search kind: {}", other)),
+            }
+        }
+        "list_tasks" => {
+            let cwd = crate::git::resolve_repo(str_arg(args, "cwd")?);
+            let names: Vec<String> = crate::tasks::discover(&cwd)
+                .into_iter()
+                .map(|task| format!("{} ({})", task.id, task.command))
+                .collect();
+            Ok(if names.is_empty() {
+                "No package.json scripts, Makefile targets, justfile recipes or Cargo aliases"
+                    .to_string()
+            } else {
+                names.join("\n")
+            })
+        }
+        "run_task" => {
+            let cwd = crate::git::resolve_repo(str_arg(args, "cwd")?);
+            let task = str_arg(args, "task")?;
+            let args = serde_json::json!({ "root": cwd, "task": task });
+            crate::policy::ensure_allowed(&server.app, "run_task", args)?;
+            run_task(&cwd, task)
+        }
+        _ => Err(format!("Unknown tool: {}", name)),
+    }
+}
+
+/// Response for one JSON-RPC message; None for notifications
+fn handle_message(server: &Server, message: &Value) -> Option<Value> {
+    let id = message.get("id")?.clone();
+    let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let result = match method {
+        "initialize" => {
+            let requested = params.get("protocolVersion").and_then(Value::as_str);
+            let version = requested
+                .filter(|v| SUPPORTED_VERSIONS.contains(v))
+                .unwrap_or(SUPPORTED_VERSIONS[1]);
+            json!({
+                "protocolVersion": version,
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION")},
+            })
+        }
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tools() }),
+        "tools/call" => {
+            let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+            let args = params.get("arguments").cloned().unwrap_or(json!({}));
+            // Tool failures are results the model should see, not protocol errors
+            let (text, is_error) = match call_tool(server, name, &args) {
+                Ok(text) => (text, false),
+                Err(e) => (e, true),
+            };
+            json!({"content": [{"type": "text", "text": text}], "isError": is_error})
+        }
+        _ => {
+            return Some(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": -32601, "message": format!("Method not found: {}", method)},
+            }))
+        }
+    };
+    Some(json!({"jsonrpc": "2.0", "id": id, "result": result}))
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: Option<&Value>) -> std::io::Result<()> {
+    let body = body.map(|b| b.to_string()).unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Minimal streamable-HTTP endpoint: one JSON-RPC POST per connection, JSON responses only
+fn handle_connection(server: &Server, mut stream: TcpStream) -> std::io::Result<()> {
+    // A client that goes quiet doesn't hold its thread forever
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let Some(request) = crate::preview_proxy::read_head(&mut reader)? else {
+        return Ok(());
+    };
+
+    if request.path.split('?').next() != Some("/mcp") {
+        return respond(&mut stream, "404 Not Found", None);
+    }
+    // Reject DNS-rebinding requests from web pages: the Host must be loopback
+    let host = request.header("host").unwrap_or("");
+    let host_ok = [
+        format!("127.0.0.1:{}", server.port),
+        format!("localhost:{}", server.port),
+    ]
+    .contains(&host.to_string());
+    if !host_ok {
+        return respond(&mut stream, "403 Forbidden", None);
+    }
+    if request.header("authorization") != Some(format!("Bearer {}", server.token).as_str()) {
+        return respond(&mut stream, "401 Unauthorized", None);
+    }
+    match request.method.as_str() {
+        "POST" => {}
+        "DELETE" => return respond(&mut stream, "200 OK", None),
+        // No server-initiated messages, so there is no GET event stream
+        _ => return respond(&mut stream, "405 Method Not Allowed", None),
+    }
+
+    let length: usize = request
+        .header("content-length")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(0);
+    if length > MAX_BODY {
+        return respond(&mut stream, "413 Payload Too Large", None);
+    }
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body)?;
+    let Ok(message) = serde_json::from_slice::<Value>(&body) else {
+        let error = json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": {"code": -32700, "message": "Parse error"},
+        });
+        return respond(&mut stream, "400 Bad Request", Some(&error));
+    };
+    let response = match &message {
+        Value::Array(batch) => {
+            let responses: Vec<Value> = batch
+                .iter()
+                .filter_map(|m| handle_message(server, m))
+                .collect();
+            Some(Value::Array(responses)).filter(|r| !r.as_array().unwrap().is_empty())
+        }
+        single => handle_message(server, single),
+    };
+    match response {
+        Some(response) => respond(&mut stream, "200 OK", Some(&response)),
+        None => respond(&mut stream, "202 Accepted", None),
+    }
+}
+
+/// 32 hex characters from the OS's random source, for use as a shared secret. Err when there is
+/// none, rather than something guessable.
+pub(crate) fn random_token() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|e| format!("No secure random source: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Shared secret for the Authorization header, kept across restarts so the registration is stable
+fn load_token() -> Result<String, String> {
+    let path = crate::ade_path("mcp-token");
+    if let Ok(token) = std::fs::read_to_string(&path) {
+        let token = token.trim();
+        if token.len() >= 32 {
+            return Ok(token.to_string());
+        }
+    }
+    let token = random_token()?;
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    // Created readable by the user only, never briefly readable by others
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    if let Ok(mut file) = options.open(&path) {
+        // mode only applies to a new file; a too-short one left behind keeps its own
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = file.set_permissions(std::fs::Permissions::from_mode(0o600));
+        }
+        let _ = file.write_all(token.as_bytes());
+    }
+    Ok(token)
+}
+
+/// Start the IDE's MCP server on loopback and register it with Claude Code (user scope).
+/// Registration is skipped when Claude Code has never run, so no config file is created.
+pub fn start(app: &tauri::App) {
+    let app = app.handle().clone();
+    let snapshot = app.state::<IdeContext>().snapshot.clone();
+    std::thread::spawn(move || {
+        let listener = match TcpListener::bind(("127.0.0.1", PREFERRED_PORT))
+            .or_else(|_| TcpListener::bind(("127.0.0.1", 0)))
+        {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to start IDE MCP server");
+                return;
+            }
+        };
+        let port = match listener.local_addr() {
+            Ok(addr) => addr.port(),
+            Err(_) => return,
+        };
+        let token = match load_token() {
+            Ok(token) => token,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to start IDE MCP server");
+                return;
+            }
+        };
+        let server = Arc::new(Server {
+            app,
+            snapshot,
+            token,
+            port,
+        });
+
+        let claude_json = std::path::Path::new(&crate::get_home_dir()).join(".claude.json");
+        if claude_json.exists() {
+            let definition = McpServerDefinition {
+                name: SERVER_NAME.to_string(),
+                transport: "http".to_string(),
+                command: None,
+                args: Vec::new(),
+                env: Default::default(),
+                url: Some(format!("http://127.0.0.1:{}/mcp", port)),
+                headers: [(
+                    "Authorization".to_string(),
+                    format!("Bearer {}", server.token),
+                )]
+                .into(),
+                inspect: false,
+            };
+            if let Err(e) = crate::claude_config::ensure_user_mcp_server(&definition) {
+                tracing::warn!(error = %e, "Failed to register IDE MCP server");
+            }
+        }
+
+        for stream in listener.incoming().flatten() {
+            let server = server.clone();
+            std::thread::spawn(move || {
+                let _ = handle_connection(&server, stream);
+            });
+        }
+    });
+}
@@ -0,0 +1,973 @@
+use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::frecency::{AccessKind, FrecencyRecorder};
+use crate::now_ms;
+
+/// Directories never worth indexing (build output, dependencies, VCS data)
+pub(crate) const SKIP_DIRS: &[&str] = &[
+    "node_modules",
+    ".git",
+    "target",
+    "dist",
+    "build",
+    "__pycache__",
+    ".next",
+    ".cache",
+];
+
+/// Files larger than this are indexed by path only, without symbol extraction
+const MAX_SYMBOL_FILE_SIZE: u64 = 1024 * 1024;
+/// Bump when the on-disk snapshot layout changes; older snapshots are ignored
+const SNAPSHOT_VERSION: u32 = 1;
+/// Minimum interval between snapshot writes triggered by watcher updates
+const PERSIST_INTERVAL_MS: u64 = 30_000;
+/// More files than this modified within BULK_WINDOW_MS is a checkout, formatter or build
+/// rather than the user editing, and stops counting toward frecency
+const BULK_EDITS: usize = 5;
+const BULK_WINDOW_MS: u64 = 2000;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Symbol {
+    name: String,
+    kind: String,
+    line: u32,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct IndexedFile {
+    size: u64,
+    modified: u64,
+    symbols: Vec<Symbol>,
+}
+
+struct FileIndex {
+    files: HashMap<String, IndexedFile>,
+    /// Paths reported by the watcher that still need re-indexing, with the time they were reported
+    dirty: HashMap<String, u64>,
+    built_at: u64,
+    build_ms: u64,
+    updates_applied: u64,
+    last_update_at: Option<u64>,
+    source: IndexSource,
+    /// Snapshot entries re-indexed on load because their size or mtime changed
+    revalidated: usize,
+    persisted_at: Option<u64>,
+    _watcher: Option<RecommendedWatcher>,
+}
+
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexSource {
+    /// Built by walking the project
+    Walk,
+    /// Loaded from ~/.ade/index and validated against file mtimes
+    Snapshot,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct IndexSnapshot {
+    version: u32,
+    root: String,
+    saved_at: u64,
+    files: HashMap<String, IndexedFile>,
+}
+
+pub struct IndexManager {
+    indexes: Arc<Mutex<HashMap<String, FileIndex>>>,
+    /// Held per root while its index is built, so concurrent first searches build it once
+    building: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+    frecency: FrecencyRecorder,
+}
+
+impl IndexManager {
+    pub fn new(frecency: FrecencyRecorder) -> Self {
+        Self {
+            indexes: Arc::new(Mutex::new(HashMap::new())),
+            building: Arc::new(Mutex::new(HashMap::new())),
+            frecency,
+        }
+    }
+
+    fn build_guard(&self, root: &str) -> Arc<Mutex<()>> {
+        self.building
+            .lock()
+            .unwrap()
+            .entry(root.to_string())
+            .or_default()
+            .clone()
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct IndexStatus {
+    root: String,
+    indexed: bool,
+    file_count: usize,
+    symbol_count: usize,
+    /// Files with pending watcher events not yet applied to the index
+    dirty_count: usize,
+    /// Fraction of indexed files that are up to date (1.0 when nothing is pending)
+    coverage: f64,
+    /// Age of the oldest pending watcher event, if any
+    oldest_dirty_ms: Option<u64>,
+    built_at: u64,
+    build_ms: u64,
+    updates_applied: u64,
+    last_update_at: Option<u64>,
+    source: Option<IndexSource>,
+    revalidated: usize,
+    persisted_at: Option<u64>,
+}
+
+#[derive(serde::Serialize)]
+pub struct FileMatch {
+    path: String,
+    size: u64,
+    modified: u64,
+    score: i64,
+}
+
+#[derive(serde::Serialize)]
+pub struct SymbolMatch {
+    path: String,
+    #[serde(flatten)]
+    symbol: Symbol,
+    /// Lines surrounding the declaration, when requested
+    context: Vec<ContextLine>,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct TextSearchOptions {
+    regex: Option<bool>,
+    case_sensitive: Option<bool>,
+    whole_word: Option<bool>,
+    /// Context lines to include before/after each matching line
+    before: Option<usize>,
+    after: Option<usize>,
+    limit: Option<usize>,
+    max_per_file: Option<usize>,
+}
+
+#[derive(serde::Serialize)]
+pub struct ContextLine {
+    line: u32,
+    text: String,
+}
+
+/// One match within a line. Offsets let the UI highlight without re-scanning the file.
+#[derive(serde::Serialize)]
+pub struct MatchRange {
+    /// Absolute byte offsets in the file
+    byte_start: usize,
+    byte_end: usize,
+    /// Byte offsets relative to the start of the line
+    line_byte_start: usize,
+    line_byte_end: usize,
+    /// UTF-16 offsets relative to the start of the line (JS string indices)
+    utf16_start: usize,
+    utf16_end: usize,
+}
+
+#[derive(serde::Serialize)]
+pub struct TextMatch {
+    path: String,
+    line: u32,
+    text: String,
+    matches: Vec<MatchRange>,
+    before: Vec<ContextLine>,
+    after: Vec<ContextLine>,
+}
+
+fn modified_ms(meta: &std::fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Directories left out of the index: hidden ones and SKIP_DIRS
+fn is_skipped_dir(name: &str) -> bool {
+    name.starts_with('.') || SKIP_DIRS.contains(&name)
+}
+
+/// Whether a watcher event for `path` is inside, or is, a directory walk_paths leaves out
+fn is_skipped(path: &Path, root: &Path) -> bool {
+    let rel = path.strip_prefix(root).unwrap_or(path);
+    let names: Vec<_> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    let Some((last, dirs)) = names.split_last() else {
+        return false;
+    };
+    dirs.iter().any(|name| is_skipped_dir(name)) || (path.is_dir() && is_skipped_dir(last))
+}
+
+fn index_file(path: &Path) -> Option<IndexedFile> {
+    let meta = std::fs::metadata(path).ok()?;
+    if !meta.is_file() {
+        return None;
+    }
+    let symbols = if meta.len() <= MAX_SYMBOL_FILE_SIZE {
+        std::fs::read_to_string(path)
+            .map(|content| extract_symbols(path, &content))
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    Some(IndexedFile {
+        size: meta.len(),
+        modified: modified_ms(&meta),
+        symbols,
+    })
+}
+
+/// Collect indexable file paths under `dir` without reading them
+fn walk_paths(dir: &Path, paths: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let file_type = match entry.file_type() {
+            Ok(t) => t,
+            Err(_) => continue,
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            if is_skipped_dir(&name) {
+                continue;
+            }
+            walk_paths(&path, paths);
+        } else if file_type.is_file() {
+            paths.push(path);
+        }
+    }
+}
+
+fn walk(dir: &Path, files: &mut HashMap<String, IndexedFile>) {
+    let mut paths = Vec::new();
+    walk_paths(dir, &mut paths);
+    for path in paths {
+        if let Some(indexed) = index_file(&path) {
+            files.insert(path.to_string_lossy().to_string(), indexed);
+        }
+    }
+}
+
+fn snapshot_path(root: &str) -> PathBuf {
+    crate::ade_path(&format!("index/{}/files.json", crate::project_hash(root)))
+}
+
+fn save_snapshot(root: &str, files: &HashMap<String, IndexedFile>) -> Result<u64, String> {
+    let path = snapshot_path(root);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create dir: {}", e))?;
+    }
+    let saved_at = now_ms();
+    let json = serde_json::to_string(&IndexSnapshot {
+        version: SNAPSHOT_VERSION,
+        root: root.to_string(),
+        saved_at,
+        files: files.clone(),
+    })
+    .map_err(|e| e.to_string())?;
+    // Write-then-rename so a crash mid-write never leaves a truncated snapshot
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("Failed to write index snapshot: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to write index snapshot: {}", e))?;
+    Ok(saved_at)
+}
+
+/// Load a persisted index, re-indexing entries whose size or mtime no longer match.
+/// Returns the files and how many were re-indexed.
+fn load_snapshot(root: &str) -> Option<(HashMap<String, IndexedFile>, usize)> {
+    let content = std::fs::read_to_string(snapshot_path(root)).ok()?;
+    let snapshot: IndexSnapshot = serde_json::from_str(&content).ok()?;
+    if snapshot.version != SNAPSHOT_VERSION || snapshot.root != root {
+        return None;
+    }
+    let mut revalidated = 0;
+    let mut files = HashMap::with_capacity(snapshot.files.len());
+    for (path, cached) in snapshot.files {
+        let meta = match std::fs::metadata(&path) {
+            Ok(m) if m.is_file() => m,
+            _ => continue, // deleted while the app was closed
+        };
+        if meta.len() == cached.size && modified_ms(&meta) == cached.modified {
+            files.insert(path, cached);
+        } else if let Some(indexed) = index_file(Path::new(&path)) {
+            revalidated += 1;
+            files.insert(path, indexed);
+        }
+    }
+    Some((files, revalidated))
+}
+
+/// Line-based symbol extraction — cheap enough to run on every watcher event
+fn extract_symbols(path: &Path, content: &str) -> Vec<Symbol> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let keywords: &[(&str, &str)] = match ext {
+        "rs" => &[
+            ("fn ", "function"),
+            ("struct ", "struct"),
+            ("enum ", "enum"),
+            ("trait ", "trait"),
+            ("impl ", "impl"),
+            ("mod ", "module"),
+            ("type ", "type"),
+        ],
+        "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs" => &[
+            ("function ", "function"),
+            ("class ", "class"),
+            ("interface ", "interface"),
+            ("type ", "type"),
+            ("enum ", "enum"),
+            ("const ", "constant"),
+        ],
+        "py" => &[("def ", "function"), ("class ", "class")],
+        "go" => &[("func ", "function"), ("type ", "type")],
+        _ => return Vec::new(),
+    };
+    let modifiers = [
+        "pub(crate) ",
+        "pub ",
+        "export default ",
+        "export ",
+        "async ",
+        "unsafe ",
+        "declare ",
+        "abstract ",
+        "default ",
+    ];
+
+    let mut symbols = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        // Only top-level-ish declarations: skip deeply indented lines
+        let indent = line.len() - line.trim_start().len();
+        if indent > 4 {
+            continue;
+        }
+        let mut rest = line.trim_start();
+        while let Some(m) = modifiers.iter().find(|m| rest.starts_with(*m)) {
+            rest = &rest[m.len()..];
+        }
+        for (kw, kind) in keywords {
+            if let Some(after) = rest.strip_prefix(kw) {
+                // Go methods: `func (r *Recv) Name(`
+                let after = if *kw == "func " && after.starts_with('(') {
+                    after
+                        .split_once(')')
+                        .map(|(_, r)| r.trim_start())
+                        .unwrap_or(after)
+                } else {
+                    after
+                };
+                let name: String = after
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+                    .collect();
+                if !name.is_empty() {
+                    symbols.push(Symbol {
+                        name,
+                        kind: kind.to_string(),
+                        line: i as u32 + 1,
+                    });
+                }
+                break;
+            }
+        }
+    }
+    symbols
+}
+
+/// Apply pending watcher events to an index
+fn apply_dirty(index: &mut FileIndex) {
+    let dirty: Vec<String> = index.dirty.drain().map(|(p, _)| p).collect();
+    let count = dirty.len();
+    for path in dirty {
+        match index_file(Path::new(&path)) {
+            Some(indexed) => {
+                index.files.insert(path, indexed);
+            }
+            None => {
+                // Removed file, or a directory — drop it and anything beneath it
+                let prefix = format!("{}{}", path, std::path::MAIN_SEPARATOR);
+                index
+                    .files
+                    .retain(|p, _| p != &path && !p.starts_with(&prefix));
+                let dir = Path::new(&path);
+                if dir.is_dir() {
+                    walk(dir, &mut index.files);
+                }
+            }
+        }
+    }
+    if count > 0 {
+        index.updates_applied += count as u64;
+        index.last_update_at = Some(now_ms());
+    }
+}
+
+/// Persist the index if it changed since the last snapshot and enough time has passed
+fn maybe_persist(root: &str, index: &mut FileIndex) {
+    let changed_since_save = match (index.last_update_at, index.persisted_at) {
+        (Some(updated), Some(saved)) => updated > saved,
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+    let due = index
+        .persisted_at
+        .map(|saved| now_ms().saturating_sub(saved) >= PERSIST_INTERVAL_MS)
+        .unwrap_or(true);
+    if changed_since_save && due {
+        if let Ok(saved_at) = save_snapshot(root, &index.files) {
+            index.persisted_at = Some(saved_at);
+        }
+    }
+}
+
+/// Build (or load) the index for `root` and start its watcher. With `use_snapshot`,
+/// a persisted index is reused after mtime validation instead of re-walking the tree.
+fn build_index(state: &IndexManager, root: &str, use_snapshot: bool) -> Result<(), String> {
+    let root_path = PathBuf::from(root);
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+
+    let started = now_ms();
+    let snapshot = if use_snapshot {
+        load_snapshot(root)
+    } else {
+        None
+    };
+    let (files, source, revalidated) = match snapshot {
+        Some((files, revalidated)) => (files, IndexSource::Snapshot, revalidated),
+        None => {
+            let mut files = HashMap::new();
+            walk(&root_path, &mut files);
+            (files, IndexSource::Walk, 0)
+        }
+    };
+    let persisted_at = match source {
+        IndexSource::Walk => save_snapshot(root, &files).ok(),
+        IndexSource::Snapshot => None,
+    };
+
+    let indexes_ref = state.indexes.clone();
+    let frecency = state.frecency.clone();
+    let root_key = root.to_string();
+    let watch_root = root_path.clone();
+    // Start of the current window of modifications, and how many there were in it
+    let mut edit_window = (0u64, 0usize);
+    let watcher = RecommendedWatcher::new(
+        move |res: Result<notify::Event, notify::Error>| {
+            let event = match res {
+                Ok(e) => e,
+                Err(_) => return,
+            };
+            if !matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                return;
+            }
+            let now = now_ms();
+            let paths: Vec<PathBuf> = event
+                .paths
+                .into_iter()
+                .filter(|path| !is_skipped(path, &watch_root))
+                .collect();
+            let edits: Vec<&PathBuf> = match event.kind {
+                EventKind::Modify(_) => paths.iter().filter(|path| path.is_file()).collect(),
+                _ => Vec::new(),
+            };
+            if !edits.is_empty() {
+                if now.saturating_sub(edit_window.0) > BULK_WINDOW_MS {
+                    edit_window = (now, 0);
+                }
+                edit_window.1 += edits.len();
+            }
+            {
+                let mut indexes = indexes_ref.lock().unwrap();
+                if let Some(index) = indexes.get_mut(&root_key) {
+                    for path in &paths {
+                        index
+                            .dirty
+                            .entry(path.to_string_lossy().to_string())
+                            .or_insert(now);
+                    }
+                }
+            }
+            if edit_window.1 <= BULK_EDITS {
+                for path in edits {
+                    frecency.record(&root_key, &path.to_string_lossy(), AccessKind::Edit);
+                }
+            }
+        },
+        Config::default(),
+    )
+    .and_then(|mut w| w.watch(&root_path, RecursiveMode::Recursive).map(|_| w))
+    .ok(); // without a watcher the index still works, it just goes stale
+
+    let mut indexes = state.indexes.lock().unwrap();
+    indexes.insert(
+        root.to_string(),
+        FileIndex {
+            files,
+            dirty: HashMap::new(),
+            built_at: now_ms(),
+            build_ms: now_ms().saturating_sub(started),
+            updates_applied: 0,
+            last_update_at: None,
+            source,
+            revalidated,
+            persisted_at,
+            _watcher: watcher,
+        },
+    );
+    drop(indexes);
+
+    // Files created while the app was closed aren't in the snapshot; find them in the
+    // background (stat only, no reads) and queue them like watcher events
+    if let IndexSource::Snapshot = source {
+        let indexes_ref = state.indexes.clone();
+        let root_key = root.to_string();
+        std::thread::spawn(move || {
+            crate::power::wait_for_full_power();
+            let mut paths = Vec::new();
+            walk_paths(&root_path, &mut paths);
+            let mut indexes = indexes_ref.lock().unwrap();
+            if let Some(index) = indexes.get_mut(&root_key) {
+                let now = now_ms();
+                for path in paths {
+                    let key = path.to_string_lossy().to_string();
+                    if !index.files.contains_key(&key) {
+                        index.dirty.entry(key).or_insert(now);
+                    }
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Load the indexes of the last opened and pinned projects in the background, so their first
+/// search doesn't wait for the snapshot to be read and checked
+pub fn start(app: &tauri::App) {
+    use tauri::Manager;
+    let projects = app.state::<crate::projects::ProjectManager>();
+    let mut roots: Vec<String> = projects.last_opened().into_iter().collect();
+    for root in projects.pinned() {
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    let app = app.handle().clone();
+    crate::crash::spawn("index", "warm".to_string(), move || {
+        let state = app.state::<IndexManager>();
+        for root in roots.iter().filter(|root| Path::new(root).is_dir()) {
+            if let Err(e) = with_index(&state, root, |_| ()) {
+                tracing::warn!(root = %root, error = %e, "Failed to load index");
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Run `f` against an up-to-date index for `root`, building it on first use
+fn with_index<T>(
+    state: &IndexManager,
+    root: &str,
+    f: impl FnOnce(&FileIndex) -> T,
+) -> Result<T, String> {
+    {
+        let guard = state.build_guard(root);
+        let _building = guard.lock().unwrap();
+        let exists = state.indexes.lock().unwrap().contains_key(root);
+        if !exists {
+            build_index(state, root, true)?;
+        }
+    }
+    let mut indexes = state.indexes.lock().unwrap();
+    let index = indexes.get_mut(root).ok_or("Index not found")?;
+    apply_dirty(index);
+    maybe_persist(root, index);
+    Ok(f(index))
+}
+
+/// Subsequence fuzzy match on the path relative to root; higher is better
+fn fuzzy_score(candidate: &str, query: &str) -> Option<i64> {
+    let candidate_lower = candidate.to_lowercase();
+    let mut score: i64 = 0;
+    let mut last_match: Option<usize> = None;
+    let mut chars = candidate_lower.char_indices();
+    for q in query.to_lowercase().chars() {
+        let (idx, _) = chars.find(|(_, c)| *c == q)?;
+        score += match last_match {
+            Some(prev) if idx == prev + 1 => 5, // consecutive characters
+            _ => 1,
+        };
+        last_match = Some(idx);
+    }
+    // Prefer matches in the file name and shorter paths
+    let file_name_start = candidate_lower.rfind('/').map(|i| i + 1).unwrap_or(0);
+    if candidate_lower[file_name_start..].contains(&query.to_lowercase()) {
+        score += 20;
+    }
+    Some(score * 100 - candidate.len() as i64)
+}
+
+/// Force a full re-walk of `root`, replacing any loaded or persisted index
+#[tauri::command]
+pub fn index_directory(
+    state: tauri::State<'_, IndexManager>,
+    root: String,
+) -> Result<IndexStatus, String> {
+    {
+        let guard = state.build_guard(&root);
+        let _building = guard.lock().unwrap();
+        build_index(&state, &root, false)?;
+    }
+    index_status(state, root)
+}
+
+#[tauri::command]
+pub fn index_status(
+    state: tauri::State<'_, IndexManager>,
+    root: String,
+) -> Result<IndexStatus, String> {
+    let indexes = state.indexes.lock().unwrap();
+    let now = now_ms();
+    let status = match indexes.get(&root) {
+        Some(index) => {
+            let file_count = index.files.len();
+            let dirty_count = index.dirty.len();
+            let coverage = if file_count == 0 {
+                1.0
+            } else {
+                (file_count.saturating_sub(dirty_count)) as f64 / file_count as f64
+            };
+            IndexStatus {
+                root,
+                indexed: true,
+                file_count,
+                symbol_count: index.files.values().map(|f| f.symbols.len()).sum(),
+                dirty_count,
+                coverage,
+                oldest_dirty_ms: index.dirty.values().min().map(|t| now.saturating_sub(*t)),
+                built_at: index.built_at,
+                build_ms: index.build_ms,
+                updates_applied: index.updates_applied,
+                last_update_at: index.last_update_at,
+                source: Some(index.source),
+                revalidated: index.revalidated,
+                persisted_at: index.persisted_at,
+            }
+        }
+        None => IndexStatus {
+            root,
+            indexed: false,
+            file_count: 0,
+            symbol_count: 0,
+            dirty_count: 0,
+            coverage: 0.0,
+            oldest_dirty_ms: None,
+            built_at: 0,
+            build_ms: 0,
+            updates_applied: 0,
+            last_update_at: None,
+            source: None,
+            revalidated: 0,
+            persisted_at: None,
+        },
+    };
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn drop_index(
+    state: tauri::State<'_, IndexManager>,
+    root: String,
+    delete_snapshot: Option<bool>,
+) -> Result<(), String> {
+    let mut indexes = state.indexes.lock().unwrap();
+    if let Some(mut index) = indexes.remove(&root) {
+        apply_dirty(&mut index);
+        if index.last_update_at.is_some() {
+            let _ = save_snapshot(&root, &index.files);
+        }
+    }
+    if delete_snapshot.unwrap_or(false) {
+        if let Some(dir) = snapshot_path(&root).parent() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn search_files(
+    state: tauri::State<'_, IndexManager>,
+    root: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<FileMatch>, String> {
+    let limit = limit.unwrap_or(50);
+    let prefix = format!("{}/", root.trim_end_matches('/'));
+    with_index(&state, &root, |index| {
+        let mut matches: Vec<FileMatch> = index
+            .files
+            .iter()
+            .filter_map(|(path, file)| {
+                let rel = path.strip_prefix(&prefix).unwrap_or(path);
+                fuzzy_score(rel, &query).map(|score| FileMatch {
+                    path: path.clone(),
+                    size: file.size,
+                    modified: file.modified,
+                    score,
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+        matches.truncate(limit);
+        matches
+    })
+}
+
+#[tauri::command]
+pub fn search_symbols(
+    state: tauri::State<'_, IndexManager>,
+    root: String,
+    query: String,
+    limit: Option<usize>,
+    context: Option<usize>,
+) -> Result<Vec<SymbolMatch>, String> {
+    let limit = limit.unwrap_or(50);
+    let query_lower = query.to_lowercase();
+    let mut matches = with_index(&state, &root, |index| {
+        let mut matches: Vec<SymbolMatch> = index
+            .files
+            .iter()
+            .flat_map(|(path, file)| {
+                file.symbols
+                    .iter()
+                    .filter(|s| s.name.to_lowercase().contains(&query_lower))
+                    .map(move |s| SymbolMatch {
+                        path: path.clone(),
+                        symbol: s.clone(),
+                        context: Vec::new(),
+                    })
+            })
+            .collect();
+        // Exact and prefix matches first, then shortest names
+        matches.sort_by_key(|m| {
+            let name = m.symbol.name.to_lowercase();
+            (
+                name != query_lower,
+                !name.starts_with(&query_lower),
+                name.len(),
+            )
+        });
+        matches.truncate(limit);
+        matches
+    })?;
+
+    if let Some(context) = context.filter(|c| *c > 0) {
+        let mut cache: HashMap<String, Vec<String>> = HashMap::new();
+        for m in &mut matches {
+            let lines = cache.entry(m.path.clone()).or_insert_with(|| {
+                std::fs::read_to_string(&m.path)
+                    .map(|c| c.lines().map(String::from).collect())
+                    .unwrap_or_default()
+            });
+            let idx = m.symbol.line as usize - 1;
+            m.context = context_lines(lines, idx.saturating_sub(context), idx + context + 1);
+        }
+    }
+    Ok(matches)
+}
+
+fn context_lines<S: AsRef<str>>(lines: &[S], from: usize, to: usize) -> Vec<ContextLine> {
+    (from..to.min(lines.len()))
+        .map(|i| ContextLine {
+            line: i as u32 + 1,
+            text: lines[i].as_ref().to_string(),
+        })
+        .collect()
+}
+
+fn utf16_len(s: &str) -> usize {
+    s.chars().map(char::len_utf16).sum()
+}
+
+/// Add a file's matching lines to `results`; true once there are `limit` of them
+#[allow(clippy::too_many_arguments)]
+fn match_lines(
+    path: &str,
+    content: &str,
+    re: &regex::Regex,
+    before: usize,
+    after: usize,
+    max_per_file: usize,
+    limit: usize,
+    results: &mut Vec<TextMatch>,
+) -> bool {
+    if !re.is_match(content) {
+        return false;
+    }
+    // (byte offset of line start, line text without its terminator)
+    let mut lines: Vec<(usize, &str)> = Vec::new();
+    let mut offset = 0;
+    for raw in content.split_inclusive('\n') {
+        let text = raw.trim_end_matches('\n');
+        lines.push((offset, text.strip_suffix('\r').unwrap_or(text)));
+        offset += raw.len();
+    }
+    let texts: Vec<&str> = lines.iter().map(|(_, t)| *t).collect();
+
+    let mut in_file = 0;
+    for (i, (line_start, line)) in lines.iter().enumerate() {
+        let matches: Vec<MatchRange> = re
+            .find_iter(line)
+            .filter(|m| !m.is_empty())
+            .map(|m| MatchRange {
+                byte_start: line_start + m.start(),
+                byte_end: line_start + m.end(),
+                line_byte_start: m.start(),
+                line_byte_end: m.end(),
+                utf16_start: utf16_len(&line[..m.start()]),
+                utf16_end: utf16_len(&line[..m.end()]),
+            })
+            .collect();
+        if matches.is_empty() {
+            continue;
+        }
+        results.push(TextMatch {
+            path: path.to_string(),
+            line: i as u32 + 1,
+            text: line.to_string(),
+            matches,
+            before: context_lines(&texts, i.saturating_sub(before), i),
+            after: context_lines(&texts, i + 1, i + 1 + after),
+        });
+        in_file += 1;
+        if results.len() >= limit {
+            return true;
+        }
+        if in_file >= max_per_file {
+            break;
+        }
+    }
+    false
+}
+
+/// Search file contents across the indexed files (ripgrep-style: one result per matching line).
+/// With a `location`, `root` is a directory on that host, searched without an index.
+#[tauri::command]
+pub async fn search_text(
+    state: tauri::State<'_, IndexManager>,
+    remote: tauri::State<'_, crate::remote::RemoteManager>,
+    root: String,
+    query: String,
+    options: Option<TextSearchOptions>,
+    location: Option<crate::remote::Location>,
+) -> Result<Vec<TextMatch>, String> {
+    let options = options.unwrap_or_default();
+    let mut pattern = if options.regex.unwrap_or(false) {
+        query.clone()
+    } else {
+        regex::escape(&query)
+    };
+    if options.whole_word.unwrap_or(false) {
+        pattern = format!(r"\b(?:{})\b", pattern);
+    }
+    let re = regex::RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive.unwrap_or(false))
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {}", e))?;
+    let before = options.before.unwrap_or(0);
+    let after = options.after.unwrap_or(0);
+    let limit = options.limit.unwrap_or(500);
+    let max_per_file = options.max_per_file.unwrap_or(100);
+
+    if let Some(location) = location {
+        let remote = remote.inner().clone();
+        return tauri::async_runtime::spawn_blocking(move || {
+            // grep on the host narrows it down to the files worth reading
+            let paths = crate::remote::grep_files(
+                &location,
+                &root,
+                &query,
+                options.regex.unwrap_or(false),
+                options.case_sensitive.unwrap_or(false),
+                options.whole_word.unwrap_or(false),
+            )?;
+            remote.with(&location, |sftp| {
+                let mut results = Vec::new();
+                for path in &paths {
+                    let Ok(content) = sftp.read(path).map(String::from_utf8) else {
+                        continue;
+                    };
+                    let Ok(content) = content else { continue };
+                    if match_lines(
+                        path,
+                        &content,
+                        &re,
+                        before,
+                        after,
+                        max_per_file,
+                        limit,
+                        &mut results,
+                    ) {
+                        break;
+                    }
+                }
+                Ok(results)
+            })
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    // Snapshot the file list, then scan without holding the index lock
+    let mut paths: Vec<String> = with_index(&state, &root, |index| {
+        index
+            .files
+            .iter()
+            .filter(|(_, f)| f.size <= MAX_SYMBOL_FILE_SIZE)
+            .map(|(p, _)| p.clone())
+            .collect()
+    })?;
+    paths.sort();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut results = Vec::new();
+        for path in paths {
+            let content = match std::fs::read_to_string(&path) {
+                Ok(c) => c,
+                Err(_) => continue, // binary or unreadable
+            };
+            if match_lines(
+                &path,
+                &content,
+                &re,
+                before,
+                after,
+                max_per_file,
+                limit,
+                &mut results,
+            ) {
+                break;
+            }
+        }
+        results
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
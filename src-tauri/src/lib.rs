@@ -1,5 +1,78 @@
+mod activity;
+mod agent_adapters;
+mod agents;
+mod annotations;
+mod attachments;
+mod audit;
+mod budget;
+mod checkpoint;
+mod claude_config;
+mod claude_md;
+mod clipboard_history;
+mod confirm;
+mod context_bundle;
+mod crash;
+mod deep_link;
+mod dev_servers;
+mod diagnostics;
+mod docker;
+mod doctor;
+mod downloads;
+mod editors;
+mod env_files;
+mod frecency;
+mod git;
+mod git_watcher;
+mod github;
+mod hooks;
+mod http;
+mod ide_mcp;
+mod index;
+mod logging;
+mod markdown;
+mod mcp;
+mod mcp_proxy;
+mod notifications;
+mod orchestrator;
+mod outline;
+mod permissions;
+mod plugin_marketplace;
+mod plugins;
+mod policy;
+mod port_forward;
+mod power;
+mod preview_proxy;
+mod processes;
+mod project_detect;
+mod project_settings;
+mod projects;
+mod prompts;
 mod pty;
+mod quick_ask;
+mod remote;
+mod review;
+mod scheduler;
+mod screenshot;
+mod secrets;
+mod semantic;
+mod session_changes;
+mod session_restore;
+mod sessions;
+mod shortcuts;
+mod slash_commands;
+mod subprocess;
+mod system_info;
+mod tasks;
+mod tokens;
+mod tool_install;
+mod tray;
+mod tunnel;
+mod updates;
 mod watcher;
+mod webhook;
+mod websocket;
+mod which;
+mod workspaces;
 
 #[derive(serde::Serialize)]
 struct FileEntry {
@@ -9,10 +82,21 @@ struct FileEntry {
     size: u64,
     extension: Option<String>,
     is_hidden: bool,
+    /// modified, added, deleted, renamed, untracked, ignored or conflicted; only filled in when
+    /// requested and the directory is inside a git repository
+    git_status: Option<String>,
 }
 
 #[tauri::command]
-fn list_directory(path: String) -> Result<Vec<FileEntry>, String> {
+async fn list_directory(
+    remote: tauri::State<'_, remote::RemoteManager>,
+    path: String,
+    git_status: Option<bool>,
+    location: Option<remote::Location>,
+) -> Result<Vec<FileEntry>, String> {
+    if let Some(location) = location {
+        return remote.run(location, move |sftp| list_remote_directory(sftp, &path)).await;
+    }
     let resolved = if path.starts_with("~/") {
         let home = get_home_dir();
         path.replacen("~", &home, 1)
@@ -22,14 +106,22 @@ fn list_directory(path: String) -> Result<Vec<FileEntry>, String> {
         path.clone()
     };
 
-    let skip_names: std::collections::HashSet<&str> = [
-        "node_modules", ".git", "target", "dist", ".DS_Store",
-        "__pycache__", ".next", ".cache",
-    ].iter().copied().collect();
+    let skip_names: std::collections::HashSet<&str> = remote::SKIPPED_DIRS
+        .iter()
+        .chain([".DS_Store"].iter())
+        .copied()
+        .collect();
 
     let entries = std::fs::read_dir(&resolved)
         .map_err(|e| format!("Failed to read directory {}: {}", resolved, e))?;
 
+    // One git status for the whole directory instead of a lookup per entry
+    let mut git_flags = if git_status.unwrap_or(false) {
+        git::directory_status(&resolved)
+    } else {
+        std::collections::HashMap::new()
+    };
+
     let mut files: Vec<FileEntry> = Vec::new();
     for entry in entries.flatten() {
         let name = entry.file_name().to_string_lossy().to_string();
@@ -43,6 +135,7 @@ fn list_directory(path: String) -> Result<Vec<FileEntry>, String> {
         let entry_path = entry.path();
         let extension = entry_path.extension().map(|e| e.to_string_lossy().to_string());
         let is_hidden = name.starts_with('.');
+        let git_status = git_flags.remove(&name);
         files.push(FileEntry {
             name,
             path: entry_path.to_string_lossy().to_string(),
@@ -50,6 +143,7 @@ fn list_directory(path: String) -> Result<Vec<FileEntry>, String> {
             size: meta.len(),
             extension,
             is_hidden,
+            git_status,
         });
     }
 
@@ -62,57 +156,37 @@ fn list_directory(path: String) -> Result<Vec<FileEntry>, String> {
     Ok(files)
 }
 
+/// list_directory for a directory on another machine, without git status
+fn list_remote_directory(sftp: &mut remote::Sftp, path: &str) -> Result<Vec<FileEntry>, String> {
+    let dir = sftp.resolve(path);
+    let mut files: Vec<FileEntry> = sftp
+        .list(&dir)?
+        .into_iter()
+        .filter(|(name, _)| !remote::SKIPPED_DIRS.contains(&name.as_str()) && name != ".DS_Store")
+        .map(|(name, attrs)| FileEntry {
+            path: format!("{}/{}", dir.trim_end_matches('/'), name),
+            is_dir: attrs.is_dir(),
+            size: attrs.size.unwrap_or(0),
+            extension: std::path::Path::new(&name)
+                .extension()
+                .map(|e| e.to_string_lossy().to_string()),
+            is_hidden: name.starts_with('.'),
+            git_status: None,
+            name,
+        })
+        .collect();
+    files.sort_by(|a, b| {
+        b.is_dir.cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    Ok(files)
+}
+
 #[tauri::command]
 fn check_command_exists(command: String) -> Result<String, String> {
-    // Get home directory — try multiple methods for Finder-launched apps
-    let home = get_home_dir();
-
-    let search_dirs = [
-        format!("{}/.local/bin", home),
-        format!("{}/.cargo/bin", home),
-        format!("{}/bin", home),
-        format!("{}/.nvm/versions/node/*/bin", home),
-        "/usr/local/bin".to_string(),
-        "/opt/homebrew/bin".to_string(),
-        "/usr/bin".to_string(),
-        "/bin".to_string(),
-    ];
-
-    // Check each directory directly for the binary
-    for dir in &search_dirs {
-        if dir.contains('*') {
-            if let Ok(entries) = glob::glob(&format!("{}/{}", dir, command)) {
-                for entry in entries.flatten() {
-                    if entry.exists() {
-                        return Ok(entry.to_string_lossy().to_string());
-                    }
-                }
-            }
-        } else {
-            let path = format!("{}/{}", dir, command);
-            if std::path::Path::new(&path).exists() {
-                return Ok(path);
-            }
-        }
-    }
-
-    // Fallback: use zsh login shell (macOS default) to resolve PATH
-    for shell in &["/bin/zsh", "/bin/bash", "/bin/sh"] {
-        let shell_check = std::process::Command::new(shell)
-            .args(["-lc", &format!("which {}", command)])
-            .env("HOME", &home)
-            .output();
-        if let Ok(output) = shell_check {
-            if output.status.success() {
-                let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                if !path.is_empty() {
-                    return Ok(path);
-                }
-            }
-        }
-    }
-
-    Err(format!("{} not found in {} or PATH", command, home))
+    which::find(&command)
+        .map(|path| path.to_string_lossy().to_string())
+        .ok_or_else(|| format!("{} not found in {} or PATH", command, get_home_dir()))
 }
 
 fn get_home_dir() -> String {
@@ -163,6 +237,35 @@ fn get_home_dir() -> String {
     "/Users/unknown".to_string()
 }
 
+/// Milliseconds since the Unix epoch
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Path inside the app's data directory (~/.ade)
+fn ade_path(relative: &str) -> std::path::PathBuf {
+    std::path::Path::new(&get_home_dir()).join(".ade").join(relative)
+}
+
+/// Stable short hash of a project root, used to key per-project data under ~/.ade
+fn project_hash(root: &str) -> String {
+    // FNV-1a: stable across Rust versions, unlike DefaultHasher
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in root.trim_end_matches('/').bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Single-quote `text` for a POSIX shell command line
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
 fn whoami() -> String {
     std::process::Command::new("/usr/bin/whoami")
         .output()
@@ -170,33 +273,66 @@ fn whoami() -> String {
         .unwrap_or_default()
 }
 
+/// Write `content` to `path`, creating parent directories. With `dry_run`, nothing is written
+/// and the diff against what is there now comes back instead.
 #[tauri::command]
-fn check_claude_plugin(plugin_name: String) -> Result<bool, String> {
-    let home = std::env::var("HOME").map_err(|_| "HOME not set".to_string())?;
-    let path = format!("{}/.claude/plugins/installed_plugins.json", home);
-    let content = std::fs::read_to_string(&path)
-        .map_err(|_| "No installed plugins file".to_string())?;
-    Ok(content.contains(&plugin_name))
-}
-
-#[tauri::command]
-fn write_text_file(path: String, content: String) -> Result<(), String> {
-    let expanded = if path.starts_with('~') {
+async fn write_text_file(
+    remote: tauri::State<'_, remote::RemoteManager>,
+    path: String,
+    content: String,
+    location: Option<remote::Location>,
+    dry_run: Option<bool>,
+) -> Result<Option<git::FileDiff>, String> {
+    let expanded = if path.starts_with('~') && location.is_none() {
         let home = get_home_dir();
         path.replacen("~", &home, 1)
     } else {
         path.clone()
     };
+    if dry_run.unwrap_or(false) {
+        let current = match location {
+            Some(location) => {
+                remote
+                    .run(location, move |sftp| match sftp.stat(&expanded)? {
+                        Some(_) => sftp.read(&expanded).map(Some),
+                        None => Ok(None),
+                    })
+                    .await?
+            }
+            None => match std::fs::read(&expanded) {
+                Ok(bytes) => Some(bytes),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(format!("Failed to read file: {}", e)),
+            },
+        };
+        let diff = tauri::async_runtime::spawn_blocking(move || {
+            git::diff_contents(&path, current.as_deref(), content.as_bytes())
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        return Ok(Some(diff));
+    }
+    if let Some(location) = location {
+        remote.run(location, move |sftp| sftp.write(&path, content.as_bytes())).await?;
+        return Ok(None);
+    }
     // Ensure parent dir exists
     if let Some(parent) = std::path::Path::new(&expanded).parent() {
         std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create parent dir: {}", e))?;
     }
     std::fs::write(&expanded, content).map_err(|e| format!("Failed to write file: {}", e))?;
-    Ok(())
+    Ok(None)
 }
 
 #[tauri::command]
-fn create_directory(path: String) -> Result<String, String> {
+async fn create_directory(
+    remote: tauri::State<'_, remote::RemoteManager>,
+    path: String,
+    location: Option<remote::Location>,
+) -> Result<String, String> {
+    if let Some(location) = location {
+        return remote.run(location, move |sftp| sftp.mkdir_all(&path)).await;
+    }
     let expanded = if path.starts_with('~') {
         let home = get_home_dir();
         path.replacen("~", &home, 1)
@@ -207,6 +343,30 @@ fn create_directory(path: String) -> Result<String, String> {
     Ok(expanded)
 }
 
+/// Delete a file, symlink or empty directory. A directory with anything in it goes only
+/// through request_confirmation and execute_confirmed.
+#[tauri::command]
+async fn delete_path(path: String) -> Result<(), String> {
+    let expanded = if path.starts_with('~') {
+        let home = get_home_dir();
+        path.replacen("~", &home, 1)
+    } else {
+        path.clone()
+    };
+    let meta = std::fs::symlink_metadata(&expanded).map_err(|e| format!("Failed to delete {}: {}", path, e))?;
+    if !meta.is_dir() {
+        return std::fs::remove_file(&expanded).map_err(|e| format!("Failed to delete {}: {}", path, e));
+    }
+    let empty = std::fs::read_dir(&expanded).map(|mut entries| entries.next().is_none()).unwrap_or(false);
+    if !empty {
+        return Err(format!(
+            "{} is not empty; deleting it needs confirmation: request_confirmation with a delete_path op",
+            path
+        ));
+    }
+    std::fs::remove_dir(&expanded).map_err(|e| format!("Failed to delete {}: {}", path, e))
+}
+
 #[tauri::command]
 fn save_temp_image(base64_data: String, extension: String) -> Result<String, String> {
     use std::io::Write;
@@ -259,7 +419,15 @@ fn base64_decode(input: &str) -> Result<Vec<u8>, String> {
 }
 
 #[tauri::command]
-fn read_file_base64(path: String) -> Result<String, String> {
+async fn read_file_base64(
+    remote: tauri::State<'_, remote::RemoteManager>,
+    path: String,
+    location: Option<remote::Location>,
+) -> Result<String, String> {
+    if let Some(location) = location {
+        let bytes = remote.run(location, move |sftp| sftp.read(&path)).await?;
+        return Ok(base64_encode(&bytes));
+    }
     let resolved = if path.starts_with("~/") {
         let home = get_home_dir();
         path.replacen("~", &home, 1)
@@ -267,6 +435,10 @@ fn read_file_base64(path: String) -> Result<String, String> {
         path.clone()
     };
     let bytes = std::fs::read(&resolved).map_err(|e| format!("Failed to read {}: {}", resolved, e))?;
+    Ok(base64_encode(&bytes))
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
     // Simple base64 encode
     let table = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
     let mut result = String::new();
@@ -288,11 +460,19 @@ fn read_file_base64(path: String) -> Result<String, String> {
             result.push('=');
         }
     }
-    Ok(result)
+    result
 }
 
 #[tauri::command]
-fn read_file(path: String) -> Result<String, String> {
+async fn read_file(
+    remote: tauri::State<'_, remote::RemoteManager>,
+    path: String,
+    location: Option<remote::Location>,
+) -> Result<String, String> {
+    if let Some(location) = location {
+        let bytes = remote.run(location, move |sftp| sftp.read(&path)).await?;
+        return String::from_utf8(bytes).map_err(|_| "Not a text file".to_string());
+    }
     let resolved = if path.starts_with("~/") {
         let home = get_home_dir();
         path.replacen("~", &home, 1)
@@ -327,32 +507,351 @@ fn list_md_files(dir: String) -> Result<Vec<String>, String> {
     Ok(files)
 }
 
+/// Run as the MCP traffic inspector instead of the app when launched with `--mcp-proxy`;
+/// returns the exit code in that case
+pub fn mcp_proxy_main() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    mcp_proxy::proxy_main(&args)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    logging::init();
+    let frecency = frecency::FrecencyManager::new();
+    let projects = projects::ProjectManager::new();
+    let budgets = budget::BudgetManager::new();
+    let agent_runs = agents::AgentRunManager::new(budgets.clone());
+    let session_changes = session_changes::SessionChangeManager::new();
+    let diagnostics = diagnostics::DiagnosticsManager::new();
+    let dev_servers = dev_servers::DevServerManager::new();
     tauri::Builder::default()
+        // First, so a second launch hands its links over before it sets anything else up
+        .plugin(tauri_plugin_single_instance::init(deep_link::on_second_instance))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_clipboard_manager::init())
-        .manage(pty::PtyManager::new())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(shortcuts::handle)
+                .build(),
+        )
+        .plugin(tauri_plugin_deep_link::init())
+        .manage(pty::PtyManager::new(
+            diagnostics.clone(),
+            dev_servers.clone(),
+        ))
         .manage(watcher::WatcherManager::new())
-        .invoke_handler(tauri::generate_handler![
+        .manage(index::IndexManager::new(frecency.recorder()))
+        .manage(frecency)
+        .manage(semantic::SemanticManager::new())
+        .manage(git_watcher::GitWatcherManager::new())
+        .manage(ide_mcp::IdeContext::default())
+        .manage(scheduler::SchedulerManager::new(
+            agent_runs.clone(),
+            session_changes.tracker(),
+        ))
+        .manage(agent_runs)
+        .manage(session_changes)
+        .manage(orchestrator::OrchestratorManager::new())
+        .manage(notifications::NotificationManager::new())
+        .manage(budgets)
+        .manage(activity::ActivityManager::new())
+        .manage(mcp_proxy::McpProxyManager::new())
+        .manage(projects.clone())
+        .manage(session_restore::SessionRestoreManager::new())
+        .manage(tasks::TaskManager::new(
+            diagnostics.clone(),
+            dev_servers.clone(),
+        ))
+        .manage(diagnostics)
+        .manage(dev_servers)
+        .manage(subprocess::CommandManager::new())
+        .manage(shortcuts::ShortcutManager::new())
+        .manage(power::PowerManager::new())
+        .manage(clipboard_history::ClipboardHistoryManager::new())
+        .manage(downloads::DownloadManager::new())
+        .manage(websocket::WebSocketManager::new())
+        .manage(preview_proxy::PreviewProxyManager::new())
+        .manage(port_forward::PortForwardManager::new())
+        .manage(remote::RemoteManager::new())
+        .manage(docker::DockerManager::new())
+        .manage(tunnel::TunnelManager::new())
+        .manage(webhook::WebhookManager::new())
+        .manage(confirm::ConfirmationManager::new(projects))
+        .setup(|app| {
+            // Before anything else starts threads, so their panics are captured
+            crash::start(app);
+            // Next, so the other background work starts out throttled on battery
+            power::start(app);
+            ide_mcp::start(app);
+            scheduler::start(app);
+            budget::start(app);
+            activity::start(app);
+            mcp_proxy::start(app);
+            session_restore::start(app);
+            diagnostics::start(app);
+            dev_servers::start(app);
+            shortcuts::start(app);
+            clipboard_history::start(app);
+            tray::start(app);
+            deep_link::start(app);
+            which::start(app);
+            index::start(app);
+            Ok(())
+        })
+        .invoke_handler(policy::enforce(tauri::generate_handler![
             pty::create_pty,
             pty::write_pty,
             pty::resize_pty,
             pty::reattach_pty,
+            pty::attach_pty,
             pty::kill_pty,
             pty::get_pty_cwd,
             watcher::watch_directory,
             watcher::unwatch_directory,
+            index::index_directory,
+            index::index_status,
+            index::drop_index,
+            index::search_files,
+            index::search_symbols,
+            index::search_text,
+            markdown::parse_markdown_outline,
+            outline::get_code_outline,
+            annotations::scan_annotations,
+            git::git_repo_root,
+            git::git_check_ignore,
+            git::git_status,
+            git::git_sync_state,
+            git::git_diff,
+            git::git_diff_range,
+            git::git_stage,
+            git::git_unstage,
+            git::git_stage_hunk,
+            git::git_discard_hunk,
+            git::git_restore,
+            git::git_commit,
+            git::git_log,
+            git::git_file_history,
+            git::git_blame,
+            git::git_stash_save,
+            git::git_stash_list,
+            git::git_stash_pop,
+            git::git_conflicts,
+            git::read_conflict,
+            git::git_worktree_add,
+            git::git_worktree_list,
+            git::git_worktree_remove,
+            git::git_fetch,
+            git::git_pull,
+            git::git_push,
+            git_watcher::watch_git,
+            git_watcher::unwatch_git,
+            github::gh_list_prs,
+            github::gh_pr_diff,
+            github::github_list_issues,
+            github::github_get_issue,
+            checkpoint::create_checkpoint,
+            checkpoint::list_checkpoints,
+            checkpoint::diff_against_checkpoint,
+            checkpoint::revert_to_checkpoint,
+            frecency::record_file_access,
+            frecency::get_frequent_files,
+            frecency::forget_file_access,
+            tokens::count_tokens,
+            semantic::semantic_index,
+            semantic::semantic_search,
+            semantic::semantic_status,
+            semantic::semantic_disable,
             check_command_exists,
-            check_claude_plugin,
+            which::resolve_command,
+            which::get_tool_versions,
+            tool_install::list_install_methods,
+            tool_install::install_tool,
+            processes::list_processes,
+            processes::kill_process,
+            processes::get_port_usage,
+            processes::free_port,
+            shortcuts::list_shortcuts,
+            shortcuts::register_shortcut,
+            shortcuts::unregister_shortcut,
+            deep_link::take_pending_deep_links,
+            deep_link::parse_deep_link,
+            editors::reveal_in_file_manager,
+            editors::open_in_editor,
+            editors::list_editors,
+            system_info::get_system_info,
+            power::get_power_status,
+            power::set_low_power_mode,
+            updates::check_for_updates,
+            updates::download_update,
+            logging::get_recent_logs,
+            logging::get_log_level,
+            logging::set_log_level,
+            crash::get_backend_errors,
+            clipboard_history::get_clipboard_history,
+            clipboard_history::paste_history_item,
+            clipboard_history::clear_clipboard_history,
+            clipboard_history::get_clipboard_settings,
+            clipboard_history::set_clipboard_settings,
+            attachments::ingest_dropped_files,
+            screenshot::capture_screenshot,
+            http::http_request,
+            downloads::download_file,
+            downloads::cancel_download,
+            websocket::ws_connect,
+            websocket::ws_send,
+            websocket::ws_close,
+            preview_proxy::start_preview_proxy,
+            preview_proxy::stop_preview_proxy,
+            port_forward::forward_port,
+            port_forward::list_forwards,
+            port_forward::stop_forward,
+            remote::list_ssh_hosts,
+            remote::disconnect_remote,
+            docker::list_containers,
+            docker::container_logs,
+            docker::stop_container_logs,
+            docker::copy_to_container,
+            tunnel::start_tunnel,
+            tunnel::list_tunnels,
+            tunnel::stop_tunnel,
+            webhook::start_webhook_listener,
+            webhook::list_webhook_listeners,
+            webhook::stop_webhook_listener,
+            policy::get_effective_policy,
+            confirm::request_confirmation,
+            confirm::execute_confirmed,
+            agents::detect_agent_tools,
+            agents::run_agent,
+            agents::cancel_agent,
+            agents::pause_all_agents,
+            agents::resume_all_agents,
+            slash_commands::list_slash_commands,
+            slash_commands::list_slash_command_templates,
+            slash_commands::create_slash_command,
+            slash_commands::rename_slash_command,
+            slash_commands::delete_slash_command,
+            claude_md::generate_claude_md,
+            claude_md::merge_claude_md,
+            hooks::list_claude_hooks,
+            hooks::validate_claude_hooks,
+            hooks::add_claude_hook,
+            hooks::remove_claude_hook,
+            hooks::test_hook,
+            permissions::get_tool_permissions,
+            permissions::set_tool_permissions,
+            permissions::validate_permission_rule,
+            permissions::would_allow,
+            session_changes::start_session_tracking,
+            session_changes::stop_session_tracking,
+            session_changes::get_session_changes,
+            review::begin_review,
+            review::list_pending_reviews,
+            review::approve_change,
+            review::revert_change,
+            prompts::list_prompts,
+            prompts::create_prompt,
+            prompts::update_prompt,
+            prompts::delete_prompt,
+            prompts::render_prompt,
+            context_bundle::build_context_bundle,
+            orchestrator::start_orchestration,
+            orchestrator::get_orchestration,
+            orchestrator::list_orchestrations,
+            orchestrator::attach_orchestration_pty,
+            orchestrator::set_orchestration_task_status,
+            orchestrator::cancel_orchestration,
+            orchestrator::collect_results,
+            orchestrator::remove_orchestration,
+            scheduler::list_schedules,
+            scheduler::create_schedule,
+            scheduler::update_schedule,
+            scheduler::set_schedule_enabled,
+            scheduler::delete_schedule,
+            scheduler::run_schedule_now,
+            scheduler::get_schedule_history,
+            scheduler::preview_cron,
+            notifications::notify_agent_event,
+            budget::get_budgets,
+            budget::set_budgets,
+            budget::get_budget_status,
+            budget::watch_agent_budget,
+            budget::unwatch_agent_budget,
+            budget::resume_budget_run,
+            budget::stop_budget_run,
+            secrets::store_secret,
+            secrets::get_secret,
+            secrets::delete_secret,
+            quick_ask::quick_ask,
+            activity::get_activity_summary,
+            mcp_proxy::inspect_mcp_server,
+            mcp_proxy::watch_mcp_traffic,
+            mcp_proxy::unwatch_mcp_traffic,
+            mcp_proxy::read_mcp_traffic_log,
+            mcp_proxy::clear_mcp_traffic_log,
+            plugin_marketplace::search_claude_plugins,
+            plugin_marketplace::get_plugin_details,
+            projects::list_recent_projects,
+            projects::record_project_open,
+            projects::pin_project,
+            projects::set_project_read_only,
+            projects::rename_project,
+            projects::remove_recent,
+            project_settings::get_project_settings,
+            project_settings::set_project_settings,
+            project_settings::apply_env_profile,
+            session_restore::set_session_files,
+            session_restore::restore_last_session,
+            project_detect::detect_project,
+            tasks::list_tasks,
+            tasks::run_task,
+            tasks::cancel_task,
+            tasks::task_history,
+            workspaces::list_workspace_packages,
+            diagnostics::get_diagnostics,
+            diagnostics::clear_diagnostics,
+            dev_servers::list_dev_servers,
+            env_files::read_env_file,
+            env_files::write_env_file,
+            env_files::reveal_env_value,
+            doctor::run_doctor,
+            subprocess::run_command,
+            subprocess::cancel_command,
+            claude_config::read_claude_settings,
+            claude_config::write_claude_settings,
+            claude_config::list_mcp_servers,
+            claude_config::add_mcp_server,
+            claude_config::update_mcp_server,
+            claude_config::remove_mcp_server,
+            mcp::test_mcp_server,
+            ide_mcp::set_ide_selection,
+            ide_mcp::set_ide_diagnostics,
+            sessions::list_agent_sessions,
+            sessions::read_agent_session,
+            sessions::export_session,
+            sessions::list_resumable_sessions,
+            sessions::resume_session_in_pty,
+            plugins::check_claude_plugin,
+            plugins::list_claude_plugins,
+            plugins::install_claude_plugin,
+            plugins::remove_claude_plugin,
+            plugins::update_claude_plugins,
             create_directory,
+            delete_path,
             write_text_file,
             save_temp_image,
             read_file,
             read_file_base64,
             list_md_files,
             list_directory,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        ]))
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                session_restore::save(app);
+                frecency::flush(app);
+                port_forward::stop_all(app);
+                tunnel::stop_all(app);
+            }
+        });
 }
@@ -0,0 +1,228 @@
+//! Structured logging through `tracing`: events go to daily-rotated JSON-lines files in
+//! ~/.ade/logs, a week of them kept, and to stderr in debug builds. The level can be changed at
+//! runtime and persists in ~/.ade/logging.json; get_recent_logs reads the files back so a bug
+//! report can include them.
+
+use serde_json::{Map, Value};
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, Layer, Registry};
+
+const FILE_PREFIX: &str = "ade";
+const FILES_KEPT: usize = 7;
+const DEFAULT_LIMIT: usize = 200;
+const MAX_LIMIT: usize = 5000;
+
+/// Changes the level of the running subscriber
+static LEVEL: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+/// Flushes the file writer when dropped, so it lives as long as the process
+static GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+#[derive(Clone, Copy, Default, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+
+    /// From the level names in the log files, like "WARN"
+    fn parse(name: &str) -> Option<LogLevel> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Some(LogLevel::Error),
+            "warn" => Some(LogLevel::Warn),
+            "info" => Some(LogLevel::Info),
+            "debug" => Some(LogLevel::Debug),
+            "trace" => Some(LogLevel::Trace),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct LogSettings {
+    level: LogLevel,
+}
+
+#[derive(serde::Serialize)]
+pub struct LogEntry {
+    /// RFC 3339
+    timestamp: String,
+    level: LogLevel,
+    /// Module that logged it
+    target: String,
+    message: String,
+    /// Structured fields besides the message, like `error`
+    fields: Map<String, Value>,
+}
+
+fn logs_dir() -> PathBuf {
+    crate::ade_path("logs")
+}
+
+fn settings_path() -> PathBuf {
+    crate::ade_path("logging.json")
+}
+
+fn saved_level() -> LogLevel {
+    std::fs::read_to_string(settings_path())
+        .ok()
+        .and_then(|content| serde_json::from_str::<LogSettings>(&content).ok())
+        .unwrap_or_default()
+        .level
+}
+
+/// Install the subscriber and a panic hook that logs panics. Called first thing in `run`;
+/// without a writable logs folder, logging goes to stderr only.
+pub fn init() {
+    let (level, handle) = reload::Layer::new(saved_level().filter());
+    let file = std::fs::create_dir_all(logs_dir())
+        .ok()
+        .and_then(|_| {
+            RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(FILE_PREFIX)
+                .filename_suffix("log")
+                .max_log_files(FILES_KEPT)
+                .build(logs_dir())
+                .ok()
+        })
+        .map(|appender| {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = GUARD.set(guard);
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_ansi(false)
+                .with_writer(writer)
+        });
+    let stderr = (cfg!(debug_assertions) || file.is_none())
+        .then(|| tracing_subscriber::fmt::layer().with_writer(std::io::stderr));
+    let installed = tracing_subscriber::registry()
+        .with(level)
+        .with(file.map(|layer| layer.boxed()))
+        .with(stderr)
+        .try_init();
+    if installed.is_err() {
+        return;
+    }
+    let _ = LEVEL.set(handle);
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        tracing::error!(panic = %info, "Panicked");
+        default_hook(info);
+    }));
+}
+
+fn parse_entry(line: &str) -> Option<LogEntry> {
+    let mut json: Map<String, Value> = serde_json::from_str(line).ok()?;
+    let mut fields = match json.remove("fields") {
+        Some(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    };
+    let text = |value: Option<Value>| match value {
+        Some(Value::String(s)) => s,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    Some(LogEntry {
+        timestamp: text(json.remove("timestamp")),
+        level: LogLevel::parse(json.get("level")?.as_str()?)?,
+        target: text(json.remove("target")),
+        message: text(fields.remove("message")),
+        fields,
+    })
+}
+
+/// The newest `limit` entries (200 by default) at `level` or more severe, oldest first, read
+/// from the log files of the last few days
+#[tauri::command]
+pub async fn get_recent_logs(
+    level: Option<LogLevel>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let level = level.unwrap_or(LogLevel::Trace);
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        let mut files: Vec<PathBuf> = match std::fs::read_dir(logs_dir()) {
+            Ok(entries) => entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with(FILE_PREFIX))
+                })
+                .collect(),
+            Err(_) => return Ok(Vec::new()),
+        };
+        // Names end in the date, so newest sorts last
+        files.sort();
+        let mut entries = Vec::new();
+        for path in files.iter().rev() {
+            let file = std::fs::File::open(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let lines: Vec<String> = std::io::BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .collect();
+            let matching = lines
+                .iter()
+                .rev()
+                .filter_map(|line| parse_entry(line))
+                .filter(|entry| entry.level <= level);
+            entries.extend(matching.take(limit - entries.len()));
+            if entries.len() >= limit {
+                break;
+            }
+        }
+        entries.reverse();
+        Ok(entries)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn get_log_level() -> LogLevel {
+    saved_level()
+}
+
+/// Log at `level` and more severe from now on, including after a restart
+#[tauri::command]
+pub fn set_log_level(level: LogLevel) -> Result<LogLevel, String> {
+    if let Some(handle) = LEVEL.get() {
+        handle
+            .modify(|filter| *filter = level.filter())
+            .map_err(|e| format!("Failed to change the log level: {}", e))?;
+    }
+    let path = settings_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&LogSettings { level }).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to save log settings: {}", e))?;
+    tracing::info!(level = %level.filter(), "Log level changed");
+    Ok(level)
+}
@@ -1,14 +1,9 @@
-mod pty;
+// Prevents additional console window on Windows in release, DO NOT REMOVE!!
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
-    tauri::Builder::default()
-        .manage(pty::PtyManager::new())
-        .invoke_handler(tauri::generate_handler![
-            pty::create_pty,
-            pty::write_pty,
-            pty::resize_pty,
-            pty::kill_pty,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+    if let Some(code) = better_terminal_lib::mcp_proxy_main() {
+        std::process::exit(code);
+    }
+    better_terminal_lib::run()
 }
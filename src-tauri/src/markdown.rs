@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+
+#[derive(serde::Serialize)]
+pub struct Heading {
+    pub(crate) level: u8,
+    pub(crate) text: String,
+    /// GitHub-style anchor slug, de-duplicated with -1, -2… suffixes
+    slug: String,
+    line: u32,
+    /// Byte range of the heading line itself
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    /// Byte range of the whole section, up to the next heading of the same or higher level
+    section_end: usize,
+}
+
+#[derive(serde::Serialize)]
+pub struct CodeBlock {
+    language: Option<String>,
+    line: u32,
+    start: usize,
+    end: usize,
+}
+
+#[derive(serde::Serialize)]
+pub struct Link {
+    text: String,
+    url: String,
+    line: u32,
+    start: usize,
+    end: usize,
+}
+
+#[derive(serde::Serialize)]
+pub struct MarkdownOutline {
+    path: String,
+    headings: Vec<Heading>,
+    code_blocks: Vec<CodeBlock>,
+    links: Vec<Link>,
+}
+
+#[tauri::command]
+pub fn parse_markdown_outline(path: String) -> Result<MarkdownOutline, String> {
+    let resolved = if path.starts_with("~/") {
+        let home = crate::get_home_dir();
+        path.replacen("~", &home, 1)
+    } else {
+        path.clone()
+    };
+    let content = std::fs::read_to_string(&resolved)
+        .map_err(|e| format!("Failed to read {}: {}", resolved, e))?;
+    let (headings, code_blocks, links) = parse_outline(&content);
+    Ok(MarkdownOutline {
+        path: resolved,
+        headings,
+        code_blocks,
+        links,
+    })
+}
+
+/// Headings of a document, ignoring any inside fenced code
+pub(crate) fn headings(content: &str) -> Vec<Heading> {
+    parse_outline(content).0
+}
+
+fn parse_outline(content: &str) -> (Vec<Heading>, Vec<CodeBlock>, Vec<Link>) {
+    let mut headings: Vec<Heading> = Vec::new();
+    let mut code_blocks: Vec<CodeBlock> = Vec::new();
+    let mut links: Vec<Link> = Vec::new();
+    let mut slug_counts: HashMap<String, u32> = HashMap::new();
+
+    // Open fence: (marker char, marker length, language, line, start offset)
+    let mut fence: Option<(char, usize, Option<String>, u32, usize)> = None;
+    let mut prev_line: Option<(&str, usize, u32)> = None;
+    let mut offset = 0;
+
+    for (i, raw_line) in content.split_inclusive('\n').enumerate() {
+        let line_no = i as u32 + 1;
+        let start = offset;
+        offset += raw_line.len();
+        let line = raw_line.trim_end_matches(['\n', '\r']);
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+
+        // Fenced code blocks
+        if let Some((marker, len, _, _, _)) = &fence {
+            let closing: String = std::iter::repeat_n(*marker, *len).collect();
+            if indent < 4
+                && trimmed.starts_with(&closing)
+                && trimmed.trim_start_matches(*marker).trim().is_empty()
+            {
+                let (_, _, language, open_line, open_start) = fence.take().unwrap();
+                code_blocks.push(CodeBlock {
+                    language,
+                    line: open_line,
+                    start: open_start,
+                    end: offset,
+                });
+            }
+            continue;
+        }
+        if indent < 4 {
+            if let Some(marker) = trimmed.chars().next().filter(|c| *c == '`' || *c == '~') {
+                let len = trimmed.chars().take_while(|c| *c == marker).count();
+                if len >= 3 {
+                    let info = trimmed[len..].trim();
+                    let language = info
+                        .split_whitespace()
+                        .next()
+                        .map(|l| l.trim_matches(['{', '}', '.']).to_string())
+                        .filter(|l| !l.is_empty());
+                    fence = Some((marker, len, language, line_no, start));
+                    prev_line = None;
+                    continue;
+                }
+            }
+        }
+
+        // ATX headings: `# Title`
+        if indent < 4 && trimmed.starts_with('#') {
+            let level = trimmed.chars().take_while(|c| *c == '#').count();
+            let rest = &trimmed[level..];
+            if level <= 6 && (rest.is_empty() || rest.starts_with(' ') || rest.starts_with('\t')) {
+                // A closing run of #s counts only after a space, so `# C#` stays "C#"
+                let body = rest.trim();
+                let unclosed = body.trim_end_matches('#');
+                let text = if unclosed.is_empty() || unclosed.ends_with([' ', '\t']) {
+                    unclosed.trim_end()
+                } else {
+                    body
+                };
+                push_heading(
+                    &mut headings,
+                    &mut slug_counts,
+                    level as u8,
+                    text.to_string(),
+                    line_no,
+                    start,
+                    offset,
+                );
+                prev_line = None;
+                continue;
+            }
+        }
+
+        // Setext headings: a paragraph line underlined with === or ---
+        if indent < 4 && !trimmed.is_empty() {
+            let underline = trimmed.trim_end();
+            let is_h1 = underline.chars().all(|c| c == '=');
+            let is_h2 = underline.chars().all(|c| c == '-') && underline.len() >= 2;
+            if let (true, Some((text, text_start, text_line))) = (is_h1 || is_h2, prev_line) {
+                push_heading(
+                    &mut headings,
+                    &mut slug_counts,
+                    if is_h1 { 1 } else { 2 },
+                    text.trim().to_string(),
+                    text_line,
+                    text_start,
+                    offset,
+                );
+                prev_line = None;
+                continue;
+            }
+        }
+
+        extract_links(line, start, line_no, &mut links);
+        prev_line = if trimmed.is_empty() || indent >= 4 {
+            None
+        } else {
+            Some((line, start, line_no))
+        };
+    }
+
+    // Unterminated fence runs to end of document
+    if let Some((_, _, language, line, start)) = fence {
+        code_blocks.push(CodeBlock {
+            language,
+            line,
+            start,
+            end: content.len(),
+        });
+    }
+
+    // Each section runs until the next heading of the same or higher level
+    for i in 0..headings.len() {
+        let level = headings[i].level;
+        headings[i].section_end = headings[i + 1..]
+            .iter()
+            .find(|h| h.level <= level)
+            .map(|h| h.start)
+            .unwrap_or(content.len());
+    }
+
+    (headings, code_blocks, links)
+}
+
+fn push_heading(
+    headings: &mut Vec<Heading>,
+    slug_counts: &mut HashMap<String, u32>,
+    level: u8,
+    text: String,
+    line: u32,
+    start: usize,
+    end: usize,
+) {
+    let base = slugify(&text);
+    let count = slug_counts.entry(base.clone()).or_insert(0);
+    let slug = if *count == 0 {
+        base
+    } else {
+        format!("{}-{}", base, count)
+    };
+    *count += 1;
+    headings.push(Heading {
+        level,
+        text,
+        slug,
+        line,
+        start,
+        end,
+        section_end: end,
+    });
+}
+
+fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter_map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                Some(c)
+            } else if c == ' ' {
+                Some('-')
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Find inline `[text](url)` links and `<scheme://…>` autolinks, skipping code spans
+fn extract_links(line: &str, line_start: usize, line_no: u32, links: &mut Vec<Link>) {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'`' => {
+                // Skip the code span up to the matching run of backticks
+                let run = bytes[i..].iter().take_while(|b| **b == b'`').count();
+                let fence = &line[i..i + run];
+                i = match line[i + run..].find(fence) {
+                    Some(close) => i + run + close + run,
+                    None => i + run,
+                };
+            }
+            b'[' => {
+                let parsed = line[i + 1..].find("](").and_then(|text_len| {
+                    let text_end = i + 1 + text_len;
+                    let url_start = text_end + 2;
+                    line[url_start..]
+                        .find(')')
+                        .map(|url_len| (text_end, url_start, url_start + url_len))
+                });
+                match parsed {
+                    Some((text_end, url_start, url_end)) => {
+                        // Drop an optional title: [text](url "title")
+                        let url = line[url_start..url_end]
+                            .split_whitespace()
+                            .next()
+                            .unwrap_or("")
+                            .trim_matches(['<', '>']);
+                        let text = &line[i + 1..text_end];
+                        let start = if i > 0 && bytes[i - 1] == b'!' {
+                            i - 1
+                        } else {
+                            i
+                        };
+                        links.push(Link {
+                            text: text.to_string(),
+                            url: url.to_string(),
+                            line: line_no,
+                            start: line_start + start,
+                            end: line_start + url_end + 1,
+                        });
+                        i = url_end + 1;
+                    }
+                    None => i += 1,
+                }
+            }
+            b'<' => {
+                let close = line[i + 1..].find('>');
+                match close {
+                    Some(len)
+                        if line[i + 1..i + 1 + len].contains("://")
+                            && !line[i + 1..i + 1 + len].contains(' ') =>
+                    {
+                        let url = &line[i + 1..i + 1 + len];
+                        links.push(Link {
+                            text: url.to_string(),
+                            url: url.to_string(),
+                            line: line_no,
+                            start: line_start + i,
+                            end: line_start + i + len + 2,
+                        });
+                        i += len + 2;
+                    }
+                    _ => i += 1,
+                }
+            }
+            _ => i += 1,
+        }
+    }
+}
@@ -0,0 +1,428 @@
+use crate::claude_config::McpServerDefinition;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+const PROTOCOL_VERSION: &str = "2025-03-26";
+/// Servers started through npx/uvx may download packages on first run
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(serde::Serialize)]
+pub struct McpTool {
+    name: String,
+    description: Option<String>,
+}
+
+#[derive(Default, serde::Serialize)]
+pub struct McpHealth {
+    ok: bool,
+    server_name: Option<String>,
+    server_version: Option<String>,
+    protocol_version: Option<String>,
+    tools: Vec<McpTool>,
+    /// Time from launch/connect until the initialize response
+    initialize_ms: u64,
+    total_ms: u64,
+    error: Option<String>,
+    /// Last lines the server wrote to stderr (stdio servers only); usually explains a crash
+    stderr: Vec<String>,
+}
+
+/// A connected MCP transport. Incoming JSON-RPC messages arrive on `messages` for every kind.
+enum Transport {
+    Stdio {
+        child: std::process::Child,
+        stdin: std::process::ChildStdin,
+    },
+    /// Streamable HTTP: each request is a POST whose response is JSON or an event stream
+    Http {
+        client: reqwest::blocking::Client,
+        url: String,
+        headers: reqwest::header::HeaderMap,
+        session: Option<String>,
+        /// Responses come back on the POST itself, so they're fed into `messages` from here
+        sender: mpsc::Sender<Value>,
+    },
+    /// Legacy SSE: a long-lived GET stream, with requests POSTed to the endpoint it announces
+    Sse {
+        client: reqwest::blocking::Client,
+        endpoint: String,
+        headers: reqwest::header::HeaderMap,
+    },
+}
+
+struct Session {
+    transport: Transport,
+    messages: mpsc::Receiver<Value>,
+    next_id: u64,
+}
+
+/// `data:` payloads from an event stream, with the event name of each
+pub(crate) fn read_events(reader: impl BufRead, mut on_event: impl FnMut(&str, String) -> bool) {
+    let mut event = String::new();
+    let mut data = String::new();
+    for line in reader.lines().map_while(Result::ok) {
+        if line.is_empty() {
+            if !data.is_empty() && !on_event(&event, std::mem::take(&mut data)) {
+                return;
+            }
+            event.clear();
+        } else if let Some(name) = line.strip_prefix("event:") {
+            event = name.trim().to_string();
+        } else if let Some(chunk) = line.strip_prefix("data:") {
+            if !data.is_empty() {
+                data.push('\n');
+            }
+            data.push_str(chunk.trim_start());
+        }
+    }
+    if !data.is_empty() {
+        on_event(&event, data);
+    }
+}
+
+pub(crate) fn header_map(
+    headers: &std::collections::BTreeMap<String, String>,
+) -> Result<reqwest::header::HeaderMap, String> {
+    let mut map = reqwest::header::HeaderMap::new();
+    for (name, value) in headers {
+        let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| format!("Invalid header {}: {}", name, e))?;
+        let value = reqwest::header::HeaderValue::from_str(value)
+            .map_err(|e| format!("Invalid header value for {}: {}", name, e))?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+impl Session {
+    fn connect(
+        definition: &McpServerDefinition,
+        stderr_lines: &std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    ) -> Result<Self, String> {
+        // Reader threads hold the senders, so `messages` disconnects when a stdio server exits
+        let (sender, messages) = mpsc::channel::<Value>();
+        let transport = match definition.transport.as_str() {
+            "stdio" => {
+                let command = definition.command.as_deref().unwrap_or_default();
+                // GUI apps don't inherit the shell PATH, so resolve bare names like `npx` ourselves
+                let program = if command.contains('/') {
+                    command.to_string()
+                } else {
+                    crate::check_command_exists(command.to_string())
+                        .unwrap_or_else(|_| command.to_string())
+                };
+                let mut child = std::process::Command::new(&program)
+                    .args(&definition.args)
+                    .envs(&definition.env)
+                    .stdin(std::process::Stdio::piped())
+                    .stdout(std::process::Stdio::piped())
+                    .stderr(std::process::Stdio::piped())
+                    .spawn()
+                    .map_err(|e| format!("Failed to start {}: {}", command, e))?;
+                let stdout = child.stdout.take().unwrap();
+                let tx = sender.clone();
+                std::thread::spawn(move || {
+                    // Servers sometimes log to stdout; anything that isn't JSON is skipped
+                    for line in std::io::BufReader::new(stdout)
+                        .lines()
+                        .map_while(Result::ok)
+                    {
+                        if let Ok(message) = serde_json::from_str::<Value>(&line) {
+                            if tx.send(message).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                });
+                let stderr = child.stderr.take().unwrap();
+                let lines = stderr_lines.clone();
+                std::thread::spawn(move || {
+                    for line in std::io::BufReader::new(stderr)
+                        .lines()
+                        .map_while(Result::ok)
+                    {
+                        let mut lines = lines.lock().unwrap();
+                        lines.push(line);
+                        if lines.len() > 50 {
+                            lines.remove(0);
+                        }
+                    }
+                });
+                let stdin = child.stdin.take().unwrap();
+                Transport::Stdio { child, stdin }
+            }
+            "http" | "sse" => {
+                let url = definition.url.clone().unwrap_or_default();
+                let headers = header_map(&definition.headers)?;
+                if definition.transport == "http" {
+                    let client = reqwest::blocking::Client::builder()
+                        .timeout(RESPONSE_TIMEOUT)
+                        .build()
+                        .map_err(|e| e.to_string())?;
+                    Transport::Http {
+                        client,
+                        url,
+                        headers,
+                        session: None,
+                        sender: sender.clone(),
+                    }
+                } else {
+                    // The stream stays open for the whole check, so no overall timeout here
+                    let client = reqwest::blocking::Client::builder()
+                        .timeout(None)
+                        .build()
+                        .map_err(|e| e.to_string())?;
+                    let response = client
+                        .get(&url)
+                        .headers(headers.clone())
+                        .header("Accept", "text/event-stream")
+                        .send()
+                        .and_then(|r| r.error_for_status())
+                        .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+                    let (endpoint_tx, endpoint_rx) = mpsc::channel::<String>();
+                    let tx = sender.clone();
+                    std::thread::spawn(move || {
+                        read_events(std::io::BufReader::new(response), |event, data| {
+                            if event == "endpoint" {
+                                let _ = endpoint_tx.send(data);
+                                return true;
+                            }
+                            match serde_json::from_str::<Value>(&data) {
+                                Ok(message) => tx.send(message).is_ok(),
+                                Err(_) => true,
+                            }
+                        });
+                    });
+                    let endpoint = endpoint_rx
+                        .recv_timeout(RESPONSE_TIMEOUT)
+                        .map_err(|_| "Server never announced its message endpoint".to_string())?;
+                    let endpoint = reqwest::Url::parse(&url)
+                        .and_then(|base| base.join(&endpoint))
+                        .map_err(|e| format!("Invalid endpoint {}: {}", endpoint, e))?
+                        .to_string();
+                    Transport::Sse {
+                        client,
+                        endpoint,
+                        headers,
+                    }
+                }
+            }
+            other => return Err(format!("Unknown MCP transport: {}", other)),
+        };
+        Ok(Session {
+            transport,
+            messages,
+            next_id: 1,
+        })
+    }
+
+    fn send(&mut self, message: &Value) -> Result<(), String> {
+        match &mut self.transport {
+            Transport::Stdio { stdin, .. } => writeln!(stdin, "{}", message)
+                .and_then(|_| stdin.flush())
+                .map_err(|e| format!("Server closed its input: {}", e)),
+            Transport::Sse {
+                client,
+                endpoint,
+                headers,
+            } => client
+                .post(endpoint.as_str())
+                .headers(headers.clone())
+                .json(message)
+                .send()
+                .and_then(|r| r.error_for_status())
+                .map(|_| ())
+                .map_err(|e| format!("Request failed: {}", e)),
+            Transport::Http {
+                client,
+                url,
+                headers,
+                session,
+                sender,
+            } => {
+                let mut request = client
+                    .post(url.as_str())
+                    .headers(headers.clone())
+                    .header("Accept", "application/json, text/event-stream")
+                    .json(message);
+                if let Some(session) = session.as_deref() {
+                    request = request.header("Mcp-Session-Id", session);
+                }
+                let response = request
+                    .send()
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| format!("Request failed: {}", e))?;
+                if let Some(id) = response.headers().get("Mcp-Session-Id") {
+                    *session = id.to_str().ok().map(String::from);
+                }
+                let is_stream = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .is_some_and(|v| v.starts_with("text/event-stream"));
+                if is_stream {
+                    read_events(std::io::BufReader::new(response), |_, data| {
+                        if let Ok(message) = serde_json::from_str::<Value>(&data) {
+                            let _ = sender.send(message);
+                        }
+                        true
+                    });
+                } else {
+                    // Notifications get 202 Accepted with an empty body
+                    let body = response.text().unwrap_or_default();
+                    if let Ok(message) = serde_json::from_str::<Value>(&body) {
+                        let _ = sender.send(message);
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}))?;
+        let deadline = Instant::now() + RESPONSE_TIMEOUT;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let message = match self.messages.recv_timeout(remaining) {
+                Ok(message) => message,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    return Err(format!(
+                        "No response to {} within {:?}",
+                        method, RESPONSE_TIMEOUT
+                    ))
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err(format!("Server exited before answering {}", method))
+                }
+            };
+            // Skip notifications and server-initiated requests (logging, sampling…)
+            if message.get("id").and_then(Value::as_u64) != Some(id)
+                || message.get("method").is_some()
+            {
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                let text = error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error");
+                return Err(format!("{} failed: {}", method, text));
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+
+    fn notify(&mut self, method: &str) -> Result<(), String> {
+        self.send(&json!({"jsonrpc": "2.0", "method": method}))
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        match &mut self.transport {
+            Transport::Stdio { child, .. } => {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            Transport::Http {
+                client,
+                url,
+                headers,
+                session: Some(session),
+                ..
+            } => {
+                // Let the server free the session rather than waiting for it to expire
+                let _ = client
+                    .delete(url.as_str())
+                    .headers(headers.clone())
+                    .header("Mcp-Session-Id", session.as_str())
+                    .send();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check(
+    definition: &McpServerDefinition,
+    health: &mut McpHealth,
+    stderr: &std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+) -> Result<(), String> {
+    let started = Instant::now();
+    let mut session = Session::connect(definition, stderr)?;
+    let init = session.request(
+        "initialize",
+        json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": {"name": "better-terminal", "version": env!("CARGO_PKG_VERSION")},
+        }),
+    )?;
+    health.initialize_ms = started.elapsed().as_millis() as u64;
+    let text = |v: &Value, key: &str| v.get(key).and_then(Value::as_str).map(String::from);
+    health.protocol_version = text(&init, "protocolVersion");
+    if let Some(info) = init.get("serverInfo") {
+        health.server_name = text(info, "name");
+        health.server_version = text(info, "version");
+    }
+    session.notify("notifications/initialized")?;
+
+    if init.pointer("/capabilities/tools").is_some() {
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let page = session.request("tools/list", params)?;
+            for tool in page
+                .get("tools")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(name) = text(tool, "name") {
+                    health.tools.push(McpTool {
+                        name,
+                        description: text(tool, "description"),
+                    });
+                }
+            }
+            cursor = text(&page, "nextCursor");
+            if cursor.is_none() {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Start (or connect to) a server, run the initialize handshake and list its tools. Failures are
+/// reported in the result rather than as an error so the UI can show stderr and timings.
+#[tauri::command]
+pub async fn test_mcp_server(definition: McpServerDefinition) -> Result<McpHealth, String> {
+    definition.validate()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        let stderr = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut health = McpHealth::default();
+        if let Err(e) = check(&definition, &mut health, &stderr) {
+            health.error = Some(e);
+        }
+        health.ok = health.error.is_none();
+        health.total_ms = started.elapsed().as_millis() as u64;
+        // Give the reader a moment to collect the last lines of a crashing server
+        if !health.ok {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        health.stderr = stderr.lock().unwrap().clone();
+        Ok(health)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
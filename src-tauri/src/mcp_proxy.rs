@@ -0,0 +1,466 @@
+//! MCP traffic inspector. A stdio server marked for inspection is registered with Claude Code
+//! as this app's binary run with `--mcp-proxy <name> -- <command> <args...>`; in that mode it
+//! starts the real server, relays stdin/stdout untouched, and logs every JSON-RPC message with
+//! timing to ~/.ade/mcp-proxy/<name>.jsonl and to the running app, which streams it live.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::ipc::Channel;
+
+pub(crate) const PROXY_FLAG: &str = "--mcp-proxy";
+/// A log larger than this is rotated to <name>.jsonl.1 when the next session starts
+const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+const DEFAULT_LOG_LIMIT: usize = 500;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct McpTrafficEvent {
+    server: String,
+    at: u64,
+    /// to_server, to_client, or stderr
+    direction: String,
+    /// request, response, notification, stderr, or invalid (stdout that isn't JSON-RPC,
+    /// which breaks the client)
+    kind: String,
+    method: Option<String>,
+    id: Option<Value>,
+    /// Time since the matching request, on responses
+    duration_ms: Option<u64>,
+    is_error: bool,
+    /// The JSON-RPC message; a string for stderr and invalid lines
+    message: Value,
+}
+
+/// Where the app listens for events from proxy processes, written at startup
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Endpoint {
+    port: u16,
+    token: String,
+}
+
+fn proxy_dir() -> PathBuf {
+    crate::ade_path("mcp-proxy")
+}
+
+fn log_path(server: &str) -> PathBuf {
+    let file: String = server
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    proxy_dir().join(format!("{}.jsonl", file))
+}
+
+fn endpoint_path() -> PathBuf {
+    proxy_dir().join("endpoint.json")
+}
+
+/// Command and args that launch `command` through the inspector
+pub(crate) fn wrap(
+    name: &str,
+    command: &str,
+    args: &[String],
+) -> Result<(String, Vec<String>), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to locate the app: {}", e))?;
+    let mut wrapped = vec![
+        PROXY_FLAG.to_string(),
+        name.to_string(),
+        "--".to_string(),
+        command.to_string(),
+    ];
+    wrapped.extend(args.iter().cloned());
+    Ok((exe.to_string_lossy().to_string(), wrapped))
+}
+
+/// The real command and args of a wrapped server, or None if `args` aren't a proxy invocation
+pub(crate) fn unwrap(args: &[String]) -> Option<(String, Vec<String>)> {
+    if args.first().map(String::as_str) != Some(PROXY_FLAG) || args.get(2)? != "--" {
+        return None;
+    }
+    Some((args.get(3)?.clone(), args[4..].to_vec()))
+}
+
+/// Matches responses to their requests, in both directions, to time them
+#[derive(Default)]
+struct Pending {
+    requests: HashMap<(bool, String), (Instant, String)>,
+}
+
+/// Writes events to the server's log file and, when the app is running, to the app
+struct Logger {
+    server: String,
+    file: Option<std::fs::File>,
+    app: Option<TcpStream>,
+    pending: Pending,
+}
+
+impl Logger {
+    fn open(server: &str) -> Self {
+        let path = log_path(server);
+        let _ = std::fs::create_dir_all(proxy_dir());
+        if std::fs::metadata(&path).is_ok_and(|m| m.len() > MAX_LOG_BYTES) {
+            let _ = std::fs::rename(&path, path.with_extension("jsonl.1"));
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .ok();
+        let app = std::fs::read_to_string(endpoint_path())
+            .ok()
+            .and_then(|json| serde_json::from_str::<Endpoint>(&json).ok())
+            .and_then(|endpoint| {
+                let mut stream = TcpStream::connect(("127.0.0.1", endpoint.port)).ok()?;
+                let hello = serde_json::json!({ "token": endpoint.token, "server": server });
+                writeln!(stream, "{}", hello).ok()?;
+                Some(stream)
+            });
+        Logger {
+            server: server.to_string(),
+            file,
+            app,
+            pending: Pending::default(),
+        }
+    }
+
+    fn emit(&mut self, event: McpTrafficEvent) {
+        let Ok(line) = serde_json::to_string(&event) else {
+            return;
+        };
+        if let Some(file) = &mut self.file {
+            let _ = writeln!(file, "{}", line);
+        }
+        // The app may quit while the server keeps running; carry on with the file only
+        if let Some(app) = &mut self.app {
+            if writeln!(app, "{}", line).is_err() {
+                self.app = None;
+            }
+        }
+    }
+
+    /// Log one line sent toward the server (`to_server`) or the client
+    fn message(&mut self, to_server: bool, line: &str) {
+        let direction = if to_server { "to_server" } else { "to_client" };
+        let at = crate::now_ms();
+        let messages = match serde_json::from_str::<Value>(line) {
+            Ok(Value::Array(batch)) => batch,
+            Ok(message @ Value::Object(_)) => vec![message],
+            _ => {
+                self.emit(McpTrafficEvent {
+                    server: self.server.clone(),
+                    at,
+                    direction: direction.to_string(),
+                    kind: "invalid".to_string(),
+                    method: None,
+                    id: None,
+                    duration_ms: None,
+                    is_error: true,
+                    message: Value::from(line),
+                });
+                return;
+            }
+        };
+        for message in messages {
+            let method = message
+                .get("method")
+                .and_then(Value::as_str)
+                .map(String::from);
+            let id = message.get("id").filter(|id| !id.is_null()).cloned();
+            let mut duration_ms = None;
+            let mut response_method = None;
+            let kind = match (&method, &id) {
+                (Some(method), Some(id)) => {
+                    self.pending.requests.insert(
+                        (to_server, id.to_string()),
+                        (Instant::now(), method.clone()),
+                    );
+                    "request"
+                }
+                (Some(_), None) => "notification",
+                (None, id) => {
+                    // A response answers a request that travelled the other way
+                    if let Some((sent, method)) = id
+                        .as_ref()
+                        .and_then(|id| self.pending.requests.remove(&(!to_server, id.to_string())))
+                    {
+                        duration_ms = Some(sent.elapsed().as_millis() as u64);
+                        response_method = Some(method);
+                    }
+                    "response"
+                }
+            };
+            self.emit(McpTrafficEvent {
+                server: self.server.clone(),
+                at,
+                direction: direction.to_string(),
+                kind: kind.to_string(),
+                method: method.or(response_method),
+                id,
+                duration_ms,
+                is_error: message.get("error").is_some(),
+                message,
+            });
+        }
+    }
+
+    fn stderr(&mut self, line: &str) {
+        self.emit(McpTrafficEvent {
+            server: self.server.clone(),
+            at: crate::now_ms(),
+            direction: "stderr".to_string(),
+            kind: "stderr".to_string(),
+            method: None,
+            id: None,
+            duration_ms: None,
+            is_error: false,
+            message: Value::from(line),
+        });
+    }
+}
+
+/// Copy lines from `from` to `to` unchanged, handing each to `log` after it is forwarded
+fn relay(from: impl std::io::Read, mut to: impl Write, mut log: impl FnMut(&str)) {
+    let mut reader = BufReader::new(from);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+        if to.write_all(&line).and_then(|_| to.flush()).is_err() {
+            return;
+        }
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end_matches(['\r', '\n']);
+        if !text.is_empty() {
+            log(text);
+        }
+    }
+}
+
+fn run_proxy(server: &str, command: &str, args: &[String]) -> Result<i32, String> {
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", command, e))?;
+    let logger = Arc::new(Mutex::new(Logger::open(server)));
+
+    let child_stdin = child.stdin.take().unwrap();
+    let to_server = logger.clone();
+    // Not joined: the client may keep stdin open after the server exits
+    std::thread::spawn(move || {
+        relay(std::io::stdin(), child_stdin, |line| {
+            to_server.lock().unwrap().message(true, line)
+        });
+    });
+    let child_stderr = child.stderr.take().unwrap();
+    let stderr_logger = logger.clone();
+    let stderr = std::thread::spawn(move || {
+        relay(child_stderr, std::io::stderr(), |line| {
+            stderr_logger.lock().unwrap().stderr(line)
+        });
+    });
+    relay(child.stdout.take().unwrap(), std::io::stdout(), |line| {
+        logger.lock().unwrap().message(false, line)
+    });
+    let _ = stderr.join();
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to wait for {}: {}", command, e))?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Run as the inspector when the process was started with `--mcp-proxy`; returns the exit
+/// code, or None to start the app normally
+pub fn proxy_main(args: &[String]) -> Option<i32> {
+    if args.first().map(String::as_str) != Some(PROXY_FLAG) {
+        return None;
+    }
+    let server = args.get(1).cloned().unwrap_or_default();
+    let Some((command, rest)) = unwrap(args) else {
+        eprintln!("Usage: {} <name> -- <command> [args...]", PROXY_FLAG);
+        return Some(2);
+    };
+    Some(run_proxy(&server, &command, &rest).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        1
+    }))
+}
+
+type Subscribers = HashMap<u32, Channel<McpTrafficEvent>>;
+
+pub struct McpProxyManager {
+    subscribers: Arc<Mutex<Subscribers>>,
+    next_id: Arc<Mutex<u32>>,
+}
+
+impl McpProxyManager {
+    pub fn new() -> Self {
+        Self {
+            subscribers: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+}
+
+/// Accept events from proxy processes and pass them to every watcher
+fn handle_proxy(stream: TcpStream, token: &str, subscribers: &Mutex<Subscribers>) {
+    let mut lines = BufReader::new(stream).lines().map_while(Result::ok);
+    let authorized = lines
+        .next()
+        .and_then(|hello| serde_json::from_str::<Value>(&hello).ok())
+        .is_some_and(|hello| hello.get("token").and_then(Value::as_str) == Some(token));
+    if !authorized {
+        return;
+    }
+    for line in lines {
+        let Ok(event) = serde_json::from_str::<McpTrafficEvent>(&line) else {
+            continue;
+        };
+        subscribers
+            .lock()
+            .unwrap()
+            .retain(|_, channel| channel.send(event.clone()).is_ok());
+    }
+}
+
+/// Listen on loopback for proxy processes and publish the port for them
+pub fn start(app: &tauri::App) {
+    use tauri::Manager;
+    let subscribers = app.state::<McpProxyManager>().subscribers.clone();
+    std::thread::spawn(move || {
+        let listener = match TcpListener::bind(("127.0.0.1", 0)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to start MCP inspector");
+                return;
+            }
+        };
+        let Ok(addr) = listener.local_addr() else {
+            return;
+        };
+        let token = match crate::ide_mcp::random_token() {
+            Ok(token) => token,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to start MCP inspector");
+                return;
+            }
+        };
+        let endpoint = Endpoint {
+            port: addr.port(),
+            token,
+        };
+        let written = std::fs::create_dir_all(proxy_dir()).and_then(|_| {
+            std::fs::write(
+                endpoint_path(),
+                serde_json::to_string(&endpoint).unwrap_or_default(),
+            )
+        });
+        if let Err(e) = written {
+            tracing::error!(error = %e, "Failed to publish MCP inspector endpoint");
+            return;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ =
+                std::fs::set_permissions(endpoint_path(), std::fs::Permissions::from_mode(0o600));
+        }
+        let token = Arc::new(endpoint.token);
+        for stream in listener.incoming().flatten() {
+            let token = token.clone();
+            let subscribers = subscribers.clone();
+            std::thread::spawn(move || handle_proxy(stream, &token, &subscribers));
+        }
+    });
+}
+
+/// Route the stdio MCP server `name` in `scope` through the traffic inspector, or back to
+/// launching it directly. Claude Code picks the change up in its next session.
+#[tauri::command]
+pub fn inspect_mcp_server(
+    scope: String,
+    name: String,
+    enabled: bool,
+    project: Option<String>,
+) -> Result<(), String> {
+    crate::claude_config::edit_server(&scope, project.as_deref(), &name, |server| {
+        if enabled && server.transport != "stdio" {
+            return Err("Only stdio MCP servers can be inspected".to_string());
+        }
+        server.inspect = enabled;
+        Ok(())
+    })
+}
+
+/// Stream traffic from every inspected server as it happens. Returns an id for
+/// unwatch_mcp_traffic.
+#[tauri::command]
+pub fn watch_mcp_traffic(
+    state: tauri::State<'_, McpProxyManager>,
+    on_event: Channel<McpTrafficEvent>,
+) -> u32 {
+    let id = {
+        let mut next = state.next_id.lock().unwrap();
+        let id = *next;
+        *next += 1;
+        id
+    };
+    state.subscribers.lock().unwrap().insert(id, on_event);
+    id
+}
+
+#[tauri::command]
+pub fn unwatch_mcp_traffic(state: tauri::State<'_, McpProxyManager>, id: u32) {
+    state.subscribers.lock().unwrap().remove(&id);
+}
+
+/// The last `limit` logged events of server `name`, oldest first
+#[tauri::command]
+pub async fn read_mcp_traffic_log(
+    name: String,
+    limit: Option<usize>,
+) -> Result<Vec<McpTrafficEvent>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let content = match std::fs::read_to_string(log_path(&name)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read traffic log: {}", e)),
+        };
+        let limit = limit.unwrap_or(DEFAULT_LOG_LIMIT);
+        let mut events: Vec<McpTrafficEvent> = content
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .take(limit)
+            .collect();
+        events.reverse();
+        Ok(events)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn clear_mcp_traffic_log(name: String) -> Result<(), String> {
+    match std::fs::remove_file(log_path(&name)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Failed to clear traffic log: {}", e))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Repeats of the same signal from the same pane within this window are dropped
+const COOLDOWN_MS: u64 = 10_000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentSignal {
+    Finished,
+    Error,
+    NeedsInput,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct FocusPane {
+    pane_id: String,
+}
+
+/// When each (pane, signal) pair last raised a notification
+type LastSent = HashMap<(Option<String>, AgentSignal), u64>;
+
+pub struct NotificationManager {
+    last_sent: Arc<Mutex<LastSent>>,
+}
+
+impl NotificationManager {
+    pub fn new() -> Self {
+        Self {
+            last_sent: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+fn app_focused(app: &AppHandle) -> bool {
+    app.webview_windows()
+        .values()
+        .any(|w| w.is_focused().unwrap_or(false))
+}
+
+/// Ask the window holding `pane_id` to come forward and select it
+pub(crate) fn focus_pane(app: &AppHandle, pane_id: String) {
+    let _ = app.emit("focus-pane", FocusPane { pane_id });
+}
+
+/// Raise a notification for a background agent. Unless `force` is set, nothing is shown while
+/// one of the app's windows has focus or when the same pane sent the same signal moments ago.
+/// Returns whether a notification was raised.
+pub(crate) fn notify(
+    app: &AppHandle,
+    manager: &NotificationManager,
+    pane_id: Option<String>,
+    signal: AgentSignal,
+    title: &str,
+    body: &str,
+    force: bool,
+) -> bool {
+    if !force && app_focused(app) {
+        return false;
+    }
+    let now = crate::now_ms();
+    {
+        let mut last_sent = manager.last_sent.lock().unwrap();
+        let key = (pane_id.clone(), signal);
+        if !force
+            && last_sent
+                .get(&key)
+                .is_some_and(|sent| now.saturating_sub(*sent) < COOLDOWN_MS)
+        {
+            return false;
+        }
+        last_sent.retain(|_, sent| now.saturating_sub(*sent) < COOLDOWN_MS);
+        last_sent.insert(key, now);
+    }
+
+    let app = app.clone();
+    let title = title.to_string();
+    let body = body.to_string();
+    std::thread::spawn(move || {
+        if let Err(e) = show(&app, &title, &body, pane_id) {
+            tracing::warn!(error = %e, "Failed to show notification");
+        }
+    });
+    true
+}
+
+/// Notify about work with no pane of its own, such as scheduled runs and orchestrations
+pub(crate) fn notify_background(app: &AppHandle, signal: AgentSignal, title: &str, body: &str) {
+    if let Some(manager) = app.try_state::<NotificationManager>() {
+        notify(app, &manager, None, signal, title, body, false);
+    }
+}
+
+/// notify-send with a "Show" action; with --wait it prints the action name when clicked
+#[cfg(target_os = "linux")]
+fn show(app: &AppHandle, title: &str, body: &str, pane_id: Option<String>) -> Result<(), String> {
+    let app_name = app
+        .config()
+        .product_name
+        .clone()
+        .unwrap_or_else(|| "ADE".to_string());
+    let send = |with_action: bool| {
+        let mut cmd = Command::new("notify-send");
+        cmd.args(["--app-name", &app_name]);
+        if with_action {
+            cmd.args(["--action=focus=Show", "--wait"]);
+        }
+        cmd.args(["--", title, body])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to run notify-send: {}", e))
+    };
+    let Some(pane_id) = pane_id else {
+        return send(false).map(|_| ());
+    };
+    let output = send(true)?;
+    if !output.status.success() {
+        // libnotify before 0.7.9 has no --action
+        return send(false).map(|_| ());
+    }
+    if String::from_utf8_lossy(&output.stdout).trim() == "focus" {
+        focus_pane(app, pane_id);
+    }
+    Ok(())
+}
+
+/// terminal-notifier opens the pane's ade:// link when clicked; without it, osascript
+/// notifications open Script Editor instead and no pane is focused
+#[cfg(target_os = "macos")]
+fn show(app: &AppHandle, title: &str, body: &str, pane_id: Option<String>) -> Result<(), String> {
+    let status = if crate::check_command_exists("terminal-notifier".to_string()).is_ok() {
+        let group = format!("ade-{}", pane_id.as_deref().unwrap_or("agent"));
+        let mut cmd = Command::new("terminal-notifier");
+        cmd.args(["-title", title, "-message", body, "-group", &group]);
+        match &pane_id {
+            Some(pane_id) => cmd.args(["-open", &crate::deep_link::focus_pane_link(pane_id)]),
+            None => cmd.args(["-activate", &app.config().identifier]),
+        };
+        cmd.stdin(Stdio::null()).stdout(Stdio::null()).status()
+    } else {
+        // Passed through the environment so nothing needs AppleScript escaping
+        Command::new("osascript")
+            .args([
+                "-e",
+                "display notification (system attribute \"ADE_NOTIFY_BODY\") \
+                 with title (system attribute \"ADE_NOTIFY_TITLE\")",
+            ])
+            .env("ADE_NOTIFY_TITLE", title)
+            .env("ADE_NOTIFY_BODY", body)
+            .stdin(Stdio::null())
+            .status()
+    };
+    match status {
+        Ok(s) if s.success() => Ok(()),
+        Ok(s) => Err(format!("Notifier exited with {}", s)),
+        Err(e) => Err(format!("Failed to run notifier: {}", e)),
+    }
+}
+
+/// A toast under the app's AppUserModelID, which the installer registers as the identifier;
+/// clicking it opens the pane's ade:// link, or just activates the app
+#[cfg(target_os = "windows")]
+fn show(app: &AppHandle, title: &str, body: &str, pane_id: Option<String>) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    const SCRIPT: &str = r#"
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null
+$xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$text = $xml.GetElementsByTagName('text')
+$text.Item(0).AppendChild($xml.CreateTextNode($env:ADE_NOTIFY_TITLE)) > $null
+$text.Item(1).AppendChild($xml.CreateTextNode($env:ADE_NOTIFY_BODY)) > $null
+if ($env:ADE_NOTIFY_LINK) {
+  $xml.DocumentElement.SetAttribute('activationType', 'protocol')
+  $xml.DocumentElement.SetAttribute('launch', $env:ADE_NOTIFY_LINK)
+}
+$toast = [Windows.UI.Notifications.ToastNotification]::new($xml)
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier($env:ADE_NOTIFY_APP).Show($toast)
+"#;
+    let status = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("ADE_NOTIFY_TITLE", title)
+        .env("ADE_NOTIFY_BODY", body)
+        .env("ADE_NOTIFY_APP", &app.config().identifier)
+        .env(
+            "ADE_NOTIFY_LINK",
+            pane_id
+                .as_deref()
+                .map(crate::deep_link::focus_pane_link)
+                .unwrap_or_default(),
+        )
+        .creation_flags(CREATE_NO_WINDOW)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to run powershell: {}", e))?;
+    if !status.success() {
+        return Err(format!("powershell exited with {}", status));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn show(_app: &AppHandle, _title: &str, _body: &str, _pane: Option<String>) -> Result<(), String> {
+    Err("Notifications are not supported on this platform".to_string())
+}
+
+/// Raise a native notification when an agent in `pane_id` finishes, fails or asks a question
+/// while the app is in the background. Returns whether one was shown.
+#[tauri::command]
+pub fn notify_agent_event(
+    app: AppHandle,
+    state: tauri::State<'_, NotificationManager>,
+    pane_id: Option<String>,
+    signal: AgentSignal,
+    title: String,
+    body: Option<String>,
+    force: Option<bool>,
+) -> bool {
+    notify(
+        &app,
+        &state,
+        pane_id,
+        signal,
+        &title,
+        body.as_deref().unwrap_or(""),
+        force.unwrap_or(false),
+    )
+}
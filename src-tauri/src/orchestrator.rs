@@ -0,0 +1,732 @@
+use crate::agents::{AgentEvent, AgentRunManager, RunAgentOptions};
+use crate::git::{resolve_repo, run_git, toplevel, FileDiff};
+use crate::notifications::AgentSignal;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::ipc::Channel;
+use tauri::Manager;
+
+#[derive(serde::Deserialize)]
+pub struct OrchestrationTaskSpec {
+    prompt: String,
+    /// Short label; defaults to the start of the prompt
+    name: Option<String>,
+    /// Branch for the task's worktree; generated under `ade/` when omitted
+    branch: Option<String>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Runner {
+    /// Spawn the agent CLI headlessly, as run_agent does
+    #[default]
+    Headless,
+    /// The frontend opens a terminal in each worktree, types `command` and attaches the PTY
+    Pty,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct OrchestrationOptions {
+    /// Agent CLI id from detect_agent_tools; defaults to claude
+    tool: Option<String>,
+    model: Option<String>,
+    #[serde(default)]
+    runner: Runner,
+    /// Revision the worktrees start from; defaults to HEAD
+    base: Option<String>,
+    /// Where worktrees are created; defaults to `<repo>-worktrees` next to the repository
+    worktree_dir: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl TaskStatus {
+    fn is_done(self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed | Self::Cancelled)
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct OrchestratedTask {
+    index: usize,
+    name: String,
+    prompt: String,
+    branch: String,
+    /// None when the worktree couldn't be created
+    worktree: Option<String>,
+    status: TaskStatus,
+    /// Headless run id, for cancel_agent
+    run_id: Option<u32>,
+    /// Terminal running the agent, in PTY mode
+    pty_id: Option<u32>,
+    /// Shell command that starts the agent interactively, in PTY mode
+    command: Option<String>,
+    session_id: Option<String>,
+    cost_usd: Option<f64>,
+    exit_code: Option<i32>,
+    error: Option<String>,
+    started_at: Option<u64>,
+    finished_at: Option<u64>,
+    /// The run's final result reported an error even if the CLI exited cleanly
+    #[serde(skip)]
+    result_error: bool,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct Orchestration {
+    id: u32,
+    repo: String,
+    /// Commit every worktree started from
+    base: String,
+    tool: String,
+    runner: Runner,
+    created_at: u64,
+    tasks: Vec<OrchestratedTask>,
+}
+
+#[derive(Clone, Default, serde::Serialize)]
+pub struct OrchestrationProgress {
+    total: usize,
+    pending: usize,
+    running: usize,
+    succeeded: usize,
+    failed: usize,
+    cancelled: usize,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum OrchestrationEvent {
+    #[serde(rename = "task_status")]
+    TaskStatus {
+        index: usize,
+        status: TaskStatus,
+        error: Option<String>,
+    },
+    /// An event from a headless task's agent run
+    #[serde(rename = "agent")]
+    Agent { index: usize, event: AgentEvent },
+    #[serde(rename = "progress")]
+    Progress(OrchestrationProgress),
+    /// Every task has finished, failed or been cancelled
+    #[serde(rename = "finished")]
+    Finished(OrchestrationProgress),
+}
+
+#[derive(serde::Serialize)]
+pub struct TaskResult {
+    index: usize,
+    name: String,
+    branch: String,
+    worktree: Option<String>,
+    status: TaskStatus,
+    /// Commits on the branch since the base
+    commits: u32,
+    /// Committed and uncommitted changes relative to the base, untracked files included
+    files: Vec<FileDiff>,
+    error: Option<String>,
+}
+
+struct Entry {
+    orchestration: Orchestration,
+    channel: Channel<OrchestrationEvent>,
+    app: tauri::AppHandle,
+}
+
+type Entries = Arc<Mutex<HashMap<u32, Entry>>>;
+
+pub struct OrchestratorManager {
+    entries: Entries,
+    next_id: Arc<Mutex<u32>>,
+}
+
+impl OrchestratorManager {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+
+    /// The repository orchestration `id` works in
+    pub(crate) fn repo(&self, id: u32) -> Option<String> {
+        let entries = self.entries.lock().unwrap();
+        Some(entries.get(&id)?.orchestration.repo.clone())
+    }
+}
+
+fn progress(tasks: &[OrchestratedTask]) -> OrchestrationProgress {
+    let mut progress = OrchestrationProgress {
+        total: tasks.len(),
+        ..Default::default()
+    };
+    for task in tasks {
+        match task.status {
+            TaskStatus::Pending => progress.pending += 1,
+            TaskStatus::Running => progress.running += 1,
+            TaskStatus::Succeeded => progress.succeeded += 1,
+            TaskStatus::Failed => progress.failed += 1,
+            TaskStatus::Cancelled => progress.cancelled += 1,
+        }
+    }
+    progress
+}
+
+/// Move a task to `status` and tell the frontend, with a `finished` event once none are left
+fn set_status(entry: &mut Entry, index: usize, status: TaskStatus, error: Option<String>) {
+    let Some(task) = entry.orchestration.tasks.get_mut(index) else {
+        return;
+    };
+    if task.status == status {
+        return;
+    }
+    task.status = status;
+    if error.is_some() {
+        task.error = error.clone();
+    }
+    match status {
+        TaskStatus::Running => task.started_at = Some(crate::now_ms()),
+        s if s.is_done() => task.finished_at = Some(crate::now_ms()),
+        _ => {}
+    }
+    let _ = entry.channel.send(OrchestrationEvent::TaskStatus {
+        index,
+        status,
+        error,
+    });
+    let progress = progress(&entry.orchestration.tasks);
+    let done = progress.pending == 0 && progress.running == 0;
+    let _ = entry
+        .channel
+        .send(OrchestrationEvent::Progress(progress.clone()));
+    if done {
+        let (signal, outcome) = if progress.failed > 0 {
+            (
+                AgentSignal::Error,
+                format!("{} of {} tasks failed", progress.failed, progress.total),
+            )
+        } else {
+            (
+                AgentSignal::Finished,
+                format!("{} tasks done", progress.total),
+            )
+        };
+        crate::notifications::notify_background(
+            &entry.app,
+            signal,
+            "Orchestration finished",
+            &outcome,
+        );
+        let _ = entry.channel.send(OrchestrationEvent::Finished(progress));
+    }
+}
+
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= 32 {
+            break;
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Command line that starts `tool` interactively with `prompt` as its first message
+fn interactive_command(tool: &str, prompt: &str, model: Option<&str>) -> Result<String, String> {
+    let args = crate::agent_adapters::adapter(tool)
+        .and_then(|a| a.interactive_args(prompt))
+        .ok_or_else(|| format!("{} can't be started with a prompt in a terminal", tool))?;
+    let mut command = tool.to_string();
+    if let Some(model) = model {
+        command.push_str(&format!(" --model {}", crate::shell_quote(model)));
+    }
+    for arg in args {
+        command.push(' ');
+        command.push_str(&crate::shell_quote(&arg));
+    }
+    Ok(command)
+}
+
+fn add_worktree(root: &str, base: &str, branch: &str, path: &Path) -> Result<String, String> {
+    if path.exists() {
+        return Err(format!("Path already exists: {}", path.display()));
+    }
+    let path = path.to_string_lossy().to_string();
+    run_git(root, ["worktree", "add", "-b", branch, "--", &path, base])?;
+    Ok(path)
+}
+
+/// Start one agent per task, each in a new worktree on its own branch from the same base.
+/// Status changes, agent events and aggregate progress stream through `on_event`; in PTY mode
+/// the tasks wait for attach_orchestration_pty.
+#[tauri::command]
+pub async fn start_orchestration(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, OrchestratorManager>,
+    repo: String,
+    tasks: Vec<OrchestrationTaskSpec>,
+    options: Option<OrchestrationOptions>,
+    on_event: Channel<OrchestrationEvent>,
+) -> Result<Orchestration, String> {
+    if tasks.is_empty() {
+        return Err("No tasks given".to_string());
+    }
+    let options = options.unwrap_or_default();
+    let tool = options.tool.clone().unwrap_or_else(|| "claude".to_string());
+    // What would fail the whole start is checked before any worktree is made, so a failed
+    // start leaves none behind
+    let check_tool = tool.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::check_command_exists(check_tool.clone())
+            .map_err(|_| format!("{} is not installed", check_tool))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let commands = tasks
+        .iter()
+        .map(|spec| match options.runner {
+            Runner::Pty => {
+                interactive_command(&tool, &spec.prompt, options.model.as_deref()).map(Some)
+            }
+            Runner::Headless => Ok(None),
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let id = {
+        let mut next = state.next_id.lock().unwrap();
+        let id = *next;
+        *next += 1;
+        id
+    };
+
+    let base = options.base.clone().unwrap_or_else(|| "HEAD".to_string());
+    let worktree_dir = options.worktree_dir.clone();
+    let specs: Vec<(String, Option<String>)> = tasks
+        .iter()
+        .map(|t| (t.prompt.clone(), t.branch.clone()))
+        .collect();
+    let (root, base, created) = tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        if base.starts_with('-') {
+            return Err(format!("Invalid revision: {}", base));
+        }
+        let base = run_git(
+            &root,
+            ["rev-parse", "--verify", &format!("{}^{{commit}}", base)],
+        )
+        .map(|out| String::from_utf8_lossy(&out).trim().to_string())
+        .map_err(|_| format!("Unknown revision: {}", base))?;
+        let dir = match worktree_dir {
+            Some(dir) => std::path::PathBuf::from(resolve_repo(&dir)),
+            None => {
+                let root_path = Path::new(&root);
+                let name = root_path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "repo".to_string());
+                root_path
+                    .parent()
+                    .unwrap_or(root_path)
+                    .join(format!("{}-worktrees", name))
+            }
+        };
+        // Seconds keep generated branch names unique across app restarts
+        let stamp = crate::now_ms() / 1000;
+        let created: Vec<(String, Result<String, String>)> = specs
+            .iter()
+            .enumerate()
+            .map(|(i, (prompt, branch))| {
+                let branch = branch.clone().unwrap_or_else(|| {
+                    format!("ade/orch-{}/{}-{}", stamp, i + 1, slugify(prompt))
+                        .trim_end_matches('-')
+                        .to_string()
+                });
+                if branch.is_empty() || branch.starts_with('-') {
+                    return (
+                        branch.clone(),
+                        Err(format!("Invalid branch name: {}", branch)),
+                    );
+                }
+                let path = dir.join(branch.replace('/', "-"));
+                let worktree = add_worktree(&root, &base, &branch, &path);
+                (branch, worktree)
+            })
+            .collect();
+        Ok((root, base, created))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let mut orchestration = Orchestration {
+        id,
+        repo: root,
+        base,
+        tool: tool.clone(),
+        runner: options.runner,
+        created_at: crate::now_ms(),
+        tasks: Vec::new(),
+    };
+    let specs = tasks.into_iter().zip(commands).zip(created);
+    for (index, ((spec, command), (branch, worktree))) in specs.enumerate() {
+        let name = spec
+            .name
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| {
+                spec.prompt
+                    .lines()
+                    .next()
+                    .unwrap_or("")
+                    .chars()
+                    .take(60)
+                    .collect()
+            });
+        let (worktree, error) = match worktree {
+            Ok(path) => (Some(path), None),
+            Err(e) => (None, Some(e)),
+        };
+        let command = command.filter(|_| worktree.is_some());
+        orchestration.tasks.push(OrchestratedTask {
+            index,
+            name,
+            prompt: spec.prompt,
+            branch,
+            status: if worktree.is_some() {
+                TaskStatus::Pending
+            } else {
+                TaskStatus::Failed
+            },
+            worktree,
+            run_id: None,
+            pty_id: None,
+            command,
+            session_id: None,
+            cost_usd: None,
+            exit_code: None,
+            error,
+            started_at: None,
+            finished_at: None,
+            result_error: false,
+        });
+    }
+    state.entries.lock().unwrap().insert(
+        id,
+        Entry {
+            orchestration,
+            channel: on_event,
+            app: app.clone(),
+        },
+    );
+
+    if options.runner == Runner::Headless {
+        let pending: Vec<(usize, String, String)> = {
+            let entries = state.entries.lock().unwrap();
+            entries[&id]
+                .orchestration
+                .tasks
+                .iter()
+                .filter_map(|t| Some((t.index, t.prompt.clone(), t.worktree.clone()?)))
+                .collect()
+        };
+        // Starting a run spawns the CLI, so off the async runtime
+        let orchestrations = state.entries.clone();
+        let app = app.clone();
+        let model = options.model.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            for (index, prompt, worktree) in pending {
+                let entries = orchestrations.clone();
+                let on_event: crate::agents::AgentEventSink = Arc::new(move |event| {
+                    let mut entries = entries.lock().unwrap();
+                    let Some(entry) = entries.get_mut(&id) else {
+                        return;
+                    };
+                    let task = &mut entry.orchestration.tasks[index];
+                    match &event {
+                        AgentEvent::Started { session_id, .. } => {
+                            task.session_id = session_id.clone().or(task.session_id.take());
+                        }
+                        AgentEvent::Result {
+                            session_id,
+                            is_error,
+                            cost_usd,
+                            ..
+                        } => {
+                            task.session_id = session_id.clone().or(task.session_id.take());
+                            task.cost_usd = *cost_usd;
+                            task.result_error = *is_error;
+                        }
+                        AgentEvent::Exit { code } => {
+                            task.exit_code = *code;
+                            // A cancelled task keeps its status
+                            if !task.status.is_done() {
+                                let status = if *code == Some(0) && !task.result_error {
+                                    TaskStatus::Succeeded
+                                } else {
+                                    TaskStatus::Failed
+                                };
+                                set_status(entry, index, status, None);
+                            }
+                        }
+                        _ => {}
+                    }
+                    let _ = entry
+                        .channel
+                        .send(OrchestrationEvent::Agent { index, event });
+                });
+                let run_options = RunAgentOptions {
+                    cwd: Some(worktree),
+                    model: model.clone(),
+                    ..Default::default()
+                };
+                let tracker = app
+                    .state::<crate::session_changes::SessionChangeManager>()
+                    .tracker();
+                let result = app.state::<AgentRunManager>().start(
+                    tracker,
+                    &tool,
+                    &prompt,
+                    run_options,
+                    on_event,
+                );
+                let mut entries = orchestrations.lock().unwrap();
+                let entry = entries.get_mut(&id).ok_or("Orchestration removed")?;
+                match result {
+                    Ok(run_id) => {
+                        entry.orchestration.tasks[index].run_id = Some(run_id);
+                        // The run may already have exited
+                        if entry.orchestration.tasks[index].status == TaskStatus::Pending {
+                            set_status(entry, index, TaskStatus::Running, None);
+                        }
+                    }
+                    Err(e) => set_status(entry, index, TaskStatus::Failed, Some(e)),
+                }
+            }
+            Ok::<_, String>(())
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+    }
+
+    let entries = state.entries.lock().unwrap();
+    let entry = &entries[&id];
+    let _ = entry.channel.send(OrchestrationEvent::Progress(progress(
+        &entry.orchestration.tasks,
+    )));
+    Ok(entry.orchestration.clone())
+}
+
+#[tauri::command]
+pub fn get_orchestration(
+    state: tauri::State<'_, OrchestratorManager>,
+    id: u32,
+) -> Result<Orchestration, String> {
+    let entries = state.entries.lock().unwrap();
+    entries
+        .get(&id)
+        .map(|e| e.orchestration.clone())
+        .ok_or_else(|| format!("Orchestration not found: {}", id))
+}
+
+#[tauri::command]
+pub fn list_orchestrations(
+    state: tauri::State<'_, OrchestratorManager>,
+) -> Result<Vec<Orchestration>, String> {
+    let entries = state.entries.lock().unwrap();
+    let mut list: Vec<Orchestration> = entries.values().map(|e| e.orchestration.clone()).collect();
+    list.sort_by_key(|o| o.id);
+    Ok(list)
+}
+
+/// Record the terminal a PTY-mode task's agent was started in; the task counts as running
+#[tauri::command]
+pub fn attach_orchestration_pty(
+    state: tauri::State<'_, OrchestratorManager>,
+    id: u32,
+    index: usize,
+    pty_id: u32,
+) -> Result<(), String> {
+    let mut entries = state.entries.lock().unwrap();
+    let entry = entries
+        .get_mut(&id)
+        .ok_or_else(|| format!("Orchestration not found: {}", id))?;
+    let task = entry
+        .orchestration
+        .tasks
+        .get_mut(index)
+        .ok_or_else(|| format!("No task {}", index))?;
+    if task.status != TaskStatus::Pending {
+        return Err(format!("Task {} is not waiting for a terminal", index));
+    }
+    task.pty_id = Some(pty_id);
+    set_status(entry, index, TaskStatus::Running, None);
+    Ok(())
+}
+
+/// Report a PTY-mode task's outcome, which only the frontend can observe
+#[tauri::command]
+pub fn set_orchestration_task_status(
+    state: tauri::State<'_, OrchestratorManager>,
+    id: u32,
+    index: usize,
+    status: TaskStatus,
+    error: Option<String>,
+) -> Result<(), String> {
+    let mut entries = state.entries.lock().unwrap();
+    let entry = entries
+        .get_mut(&id)
+        .ok_or_else(|| format!("Orchestration not found: {}", id))?;
+    if index >= entry.orchestration.tasks.len() {
+        return Err(format!("No task {}", index));
+    }
+    set_status(entry, index, status, error);
+    Ok(())
+}
+
+/// Stop every unfinished task. Headless runs are killed; PTY-mode terminals are left to the
+/// frontend. Worktrees stay for collect_results.
+#[tauri::command]
+pub fn cancel_orchestration(
+    state: tauri::State<'_, OrchestratorManager>,
+    agents: tauri::State<'_, AgentRunManager>,
+    id: u32,
+) -> Result<(), String> {
+    let run_ids: Vec<u32> = {
+        let mut entries = state.entries.lock().unwrap();
+        let entry = entries
+            .get_mut(&id)
+            .ok_or_else(|| format!("Orchestration not found: {}", id))?;
+        let unfinished: Vec<usize> = entry
+            .orchestration
+            .tasks
+            .iter()
+            .filter(|t| !t.status.is_done())
+            .map(|t| t.index)
+            .collect();
+        let run_ids = unfinished
+            .iter()
+            .filter_map(|&i| entry.orchestration.tasks[i].run_id)
+            .collect();
+        for index in unfinished {
+            set_status(entry, index, TaskStatus::Cancelled, None);
+        }
+        run_ids
+    };
+    // Killed outside the lock: each run's exit event takes it
+    for run_id in run_ids {
+        agents.cancel(run_id)?;
+    }
+    Ok(())
+}
+
+/// Per-worktree diffs against the orchestration's base, for review and merging
+#[tauri::command]
+pub async fn collect_results(
+    state: tauri::State<'_, OrchestratorManager>,
+    id: u32,
+) -> Result<Vec<TaskResult>, String> {
+    let orchestration = {
+        let entries = state.entries.lock().unwrap();
+        entries
+            .get(&id)
+            .map(|e| e.orchestration.clone())
+            .ok_or_else(|| format!("Orchestration not found: {}", id))?
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let base = &orchestration.base;
+        Ok(orchestration
+            .tasks
+            .into_iter()
+            .map(|task| {
+                let mut error = task.error.clone();
+                let mut files = Vec::new();
+                let mut commits = 0;
+                if let Some(worktree) = task.worktree.as_deref().filter(|w| Path::new(w).is_dir()) {
+                    commits = run_git(
+                        worktree,
+                        ["rev-list", "--count", &format!("{}..HEAD", base)],
+                    )
+                    .ok()
+                    .and_then(|out| String::from_utf8_lossy(&out).trim().parse().ok())
+                    .unwrap_or(0);
+                    let diff = crate::checkpoint::snapshot_tree(worktree).and_then(|tree| {
+                        let mut args = crate::git::diff_args(None);
+                        args.extend([format!("{}^{{tree}}", base), tree]);
+                        run_git(worktree, &args)
+                    });
+                    match diff {
+                        Ok(output) => files = crate::git::parse_diff(&output),
+                        Err(e) => error = Some(e),
+                    }
+                }
+                TaskResult {
+                    index: task.index,
+                    name: task.name,
+                    branch: task.branch,
+                    worktree: task.worktree,
+                    status: task.status,
+                    commits,
+                    files,
+                    error,
+                }
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Remove a finished orchestration's worktrees (discarding uncommitted work) and forget it.
+/// Branches are kept unless `delete_branches` is set.
+#[tauri::command]
+pub async fn remove_orchestration(
+    state: tauri::State<'_, OrchestratorManager>,
+    id: u32,
+    delete_branches: Option<bool>,
+) -> Result<(), String> {
+    let orchestration = {
+        let entries = state.entries.lock().unwrap();
+        let entry = entries
+            .get(&id)
+            .ok_or_else(|| format!("Orchestration not found: {}", id))?;
+        if entry
+            .orchestration
+            .tasks
+            .iter()
+            .any(|t| t.status == TaskStatus::Running)
+        {
+            return Err("Cancel the orchestration before removing it".to_string());
+        }
+        entry.orchestration.clone()
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = &orchestration.repo;
+        for task in &orchestration.tasks {
+            if let Some(worktree) = &task.worktree {
+                if Path::new(worktree).exists() {
+                    run_git(root, ["worktree", "remove", "--force", "--", worktree])?;
+                }
+            }
+            if delete_branches.unwrap_or(false) {
+                let _ = run_git(root, ["branch", "-D", &task.branch]);
+            }
+        }
+        let _ = run_git(root, ["worktree", "prune"]);
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    state.entries.lock().unwrap().remove(&id);
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use tree_sitter::{Language, Node, Parser};
+
+/// Outlining is meant for the open file; anything bigger is almost certainly generated
+const MAX_OUTLINE_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+#[derive(serde::Serialize)]
+pub struct OutlineItem {
+    name: String,
+    kind: String,
+    start_line: u32,
+    start_column: u32,
+    end_line: u32,
+    end_column: u32,
+    start_byte: usize,
+    end_byte: usize,
+    doc: Option<String>,
+    children: Vec<OutlineItem>,
+}
+
+#[derive(serde::Serialize)]
+pub struct CodeOutline {
+    path: String,
+    language: String,
+    items: Vec<OutlineItem>,
+}
+
+pub(crate) fn language_for(path: &str) -> Option<(&'static str, Language)> {
+    let ext = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let lang = match ext.as_str() {
+        "rs" => ("rust", tree_sitter_rust::LANGUAGE.into()),
+        "ts" | "mts" | "cts" => (
+            "typescript",
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        ),
+        "tsx" => ("tsx", tree_sitter_typescript::LANGUAGE_TSX.into()),
+        "js" | "jsx" | "mjs" | "cjs" => ("javascript", tree_sitter_javascript::LANGUAGE.into()),
+        "py" | "pyi" => ("python", tree_sitter_python::LANGUAGE.into()),
+        "go" => ("go", tree_sitter_go::LANGUAGE.into()),
+        _ => return None,
+    };
+    Some(lang)
+}
+
+/// Map a syntax node kind to an outline kind, or None if it isn't an outline entry
+fn outline_kind(node_kind: &str) -> Option<&'static str> {
+    let kind = match node_kind {
+        "function_item"
+        | "function_declaration"
+        | "function_definition"
+        | "generator_function_declaration" => "function",
+        "method_definition" | "method_declaration" | "function_signature_item" => "method",
+        "struct_item" => "struct",
+        "enum_item" | "enum_declaration" => "enum",
+        "trait_item" => "trait",
+        "impl_item" => "impl",
+        "mod_item" | "internal_module" => "module",
+        "type_item" | "type_alias_declaration" | "type_spec" => "type",
+        "const_item" | "static_item" => "constant",
+        "macro_definition" => "macro",
+        "class_declaration" | "abstract_class_declaration" | "class_definition" | "class" => {
+            "class"
+        }
+        "interface_declaration" => "interface",
+        _ => return None,
+    };
+    Some(kind)
+}
+
+fn node_text<'a>(node: Node, source: &'a [u8]) -> &'a str {
+    node.utf8_text(source).unwrap_or("")
+}
+
+fn item_name(node: Node, source: &[u8]) -> Option<String> {
+    if node.kind() == "impl_item" {
+        // `impl Trait for Type` → "Trait for Type", `impl Type` → "Type"
+        let ty = node
+            .child_by_field_name("type")
+            .map(|n| node_text(n, source))?;
+        return Some(match node.child_by_field_name("trait") {
+            Some(t) => format!("{} for {}", node_text(t, source), ty),
+            None => ty.to_string(),
+        });
+    }
+    node.child_by_field_name("name")
+        .map(|n| node_text(n, source).to_string())
+}
+
+/// Collect the comment block directly above a node (`///`, `/** */`, `#`), or a Python docstring
+fn doc_comment(node: Node, source: &[u8], language: &str) -> Option<String> {
+    if language == "python" {
+        let body = node.child_by_field_name("body")?;
+        let first = body.named_child(0)?;
+        if first.kind() == "expression_statement" {
+            let string = first.named_child(0).filter(|n| n.kind() == "string")?;
+            let text = node_text(string, source);
+            let trimmed = text
+                .trim_start_matches(['r', 'u', 'b', 'R', 'U', 'B'])
+                .trim_matches('"')
+                .trim_matches('\'')
+                .trim();
+            return Some(trimmed.to_string()).filter(|s| !s.is_empty());
+        }
+        return None;
+    }
+
+    // Decorators/attributes and `export` wrappers sit between the comment and the item
+    let mut anchor = node;
+    while let Some(parent) = anchor.parent() {
+        if matches!(
+            parent.kind(),
+            "export_statement" | "decorated_definition" | "type_declaration"
+        ) {
+            anchor = parent;
+        } else {
+            break;
+        }
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut expected_row = anchor.start_position().row;
+    let mut sibling = anchor.prev_sibling();
+    while let Some(prev) = sibling {
+        let kind = prev.kind();
+        if kind == "attribute_item" {
+            expected_row = prev.start_position().row;
+            sibling = prev.prev_sibling();
+            continue;
+        }
+        let is_comment = matches!(kind, "line_comment" | "block_comment" | "comment");
+        if !is_comment || prev.end_position().row + 1 < expected_row {
+            break;
+        }
+        let text = node_text(prev, source);
+        // Plain `//` comments in Rust aren't docs
+        if language == "rust" && kind == "line_comment" && !text.starts_with("///") {
+            break;
+        }
+        lines.push(clean_comment(text));
+        expected_row = prev.start_position().row;
+        sibling = prev.prev_sibling();
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    lines.reverse();
+    Some(lines.join("\n").trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn clean_comment(text: &str) -> String {
+    let text = text.trim();
+    if let Some(block) = text.strip_prefix("/*") {
+        return block
+            .trim_end_matches("*/")
+            .lines()
+            .map(|l| l.trim().trim_start_matches('*').trim())
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    text.trim_start_matches('/')
+        .trim_start_matches('!')
+        .trim_start_matches('#')
+        .trim()
+        .to_string()
+}
+
+fn collect(node: Node, source: &[u8], language: &str, out: &mut Vec<OutlineItem>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let kind = outline_kind(child.kind()).or_else(|| arrow_function_kind(child));
+        match kind {
+            Some(kind) => {
+                let name = match child.kind() {
+                    "lexical_declaration" | "variable_declaration" => child
+                        .named_child(0)
+                        .and_then(|d| d.child_by_field_name("name"))
+                        .map(|n| node_text(n, source).to_string()),
+                    _ => item_name(child, source),
+                };
+                let Some(name) = name else {
+                    collect(child, source, language, out);
+                    continue;
+                };
+                let mut children = Vec::new();
+                if let Some(body) = child.child_by_field_name("body") {
+                    collect(body, source, language, &mut children);
+                }
+                let start = child.start_position();
+                let end = child.end_position();
+                out.push(OutlineItem {
+                    name,
+                    kind: kind.to_string(),
+                    start_line: start.row as u32 + 1,
+                    start_column: start.column as u32,
+                    end_line: end.row as u32 + 1,
+                    end_column: end.column as u32,
+                    start_byte: child.start_byte(),
+                    end_byte: child.end_byte(),
+                    doc: doc_comment(child, source, language),
+                    children,
+                });
+            }
+            // Descend through wrappers (export statements, decorators, Go type declarations…)
+            None => {
+                if matches!(
+                    child.kind(),
+                    "export_statement"
+                        | "decorated_definition"
+                        | "type_declaration"
+                        | "declaration_list"
+                        | "program"
+                        | "module"
+                        | "source_file"
+                ) {
+                    collect(child, source, language, out);
+                }
+            }
+        }
+    }
+}
+
+/// `const handler = () => {}` / `const f = function () {}` count as functions
+fn arrow_function_kind(node: Node) -> Option<&'static str> {
+    if !matches!(node.kind(), "lexical_declaration" | "variable_declaration") {
+        return None;
+    }
+    let declarator = node.named_child(0)?;
+    let value = declarator.child_by_field_name("value")?;
+    matches!(
+        value.kind(),
+        "arrow_function" | "function_expression" | "function"
+    )
+    .then_some("function")
+}
+
+#[tauri::command]
+pub fn get_code_outline(path: String) -> Result<CodeOutline, String> {
+    let resolved = if path.starts_with("~/") {
+        let home = crate::get_home_dir();
+        path.replacen("~", &home, 1)
+    } else {
+        path.clone()
+    };
+    let (language_name, language) =
+        language_for(&resolved).ok_or_else(|| format!("No outline support for {}", resolved))?;
+
+    let meta =
+        std::fs::metadata(&resolved).map_err(|e| format!("Failed to read {}: {}", resolved, e))?;
+    if meta.len() > MAX_OUTLINE_FILE_SIZE {
+        return Err(format!("File too large to outline ({} bytes)", meta.len()));
+    }
+    let source =
+        std::fs::read(&resolved).map_err(|e| format!("Failed to read {}: {}", resolved, e))?;
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&language)
+        .map_err(|e| format!("Failed to load {} grammar: {}", language_name, e))?;
+    let tree = parser
+        .parse(&source, None)
+        .ok_or_else(|| format!("Failed to parse {}", resolved))?;
+
+    let mut items = Vec::new();
+    collect(tree.root_node(), &source, language_name, &mut items);
+    Ok(CodeOutline {
+        path: resolved,
+        language: language_name.to_string(),
+        items,
+    })
+}
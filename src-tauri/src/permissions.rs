@@ -0,0 +1,482 @@
+use crate::claude_config::{read_json, settings_path, write_json};
+use serde_json::{Map, Value};
+use std::path::{Component, Path, PathBuf};
+
+const KNOWN_TOOLS: &[&str] = &[
+    "Bash",
+    "BashOutput",
+    "KillShell",
+    "Read",
+    "Edit",
+    "MultiEdit",
+    "Write",
+    "NotebookEdit",
+    "NotebookRead",
+    "Glob",
+    "Grep",
+    "LS",
+    "WebFetch",
+    "WebSearch",
+    "Task",
+    "TodoWrite",
+    "ExitPlanMode",
+    "SlashCommand",
+    "Skill",
+];
+/// Read rules cover every tool that reads files, Edit rules every tool that writes them
+const READ_TOOLS: &[&str] = &["Read", "Glob", "Grep", "LS", "NotebookRead"];
+const EDIT_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+/// Checked in this order; the first list with a matching rule decides
+const LISTS: [&str; 3] = ["deny", "ask", "allow"];
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct ToolPermissionRules {
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    deny: Vec<String>,
+    #[serde(default)]
+    ask: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct ToolPermissions {
+    scope: String,
+    #[serde(flatten)]
+    rules: ToolPermissionRules,
+    default_mode: Option<String>,
+    additional_directories: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct RuleCheck {
+    rule: String,
+    valid: bool,
+    tool: Option<String>,
+    specifier: Option<String>,
+    error: Option<String>,
+    /// Valid but probably not what was meant, e.g. an unknown tool name
+    warning: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct PermissionDecision {
+    /// allow, deny, ask, or default when no rule matches (Claude Code then asks,
+    /// unless the permission mode says otherwise)
+    decision: String,
+    rule: Option<String>,
+    scope: Option<String>,
+    /// For compound shell commands, the part that decided
+    command_part: Option<String>,
+}
+
+struct Rule {
+    text: String,
+    tool: String,
+    specifier: Option<String>,
+}
+
+/// `Tool` or `Tool(specifier)`
+fn parse_rule(rule: &str) -> Result<Rule, String> {
+    let text = rule.trim();
+    let (tool, specifier) = match text.find('(') {
+        Some(open) => {
+            let Some(inner) = text[open + 1..].strip_suffix(')') else {
+                return Err("Missing closing parenthesis".to_string());
+            };
+            if inner.trim().is_empty() {
+                return Err("Empty specifier; drop the parentheses to match every use".to_string());
+            }
+            (&text[..open], Some(inner.to_string()))
+        }
+        None if text.contains(')') => return Err("Unexpected closing parenthesis".to_string()),
+        None => (text, None),
+    };
+    if tool.is_empty()
+        || !tool
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '*')
+    {
+        return Err(format!("Invalid tool name: {:?}", tool));
+    }
+    if tool.starts_with("mcp__") && specifier.is_some() {
+        return Err("MCP rules take no specifier; use mcp__server__tool".to_string());
+    }
+    if let Some(spec) = &specifier {
+        match tool {
+            "Bash" if spec.contains(":*") && !spec.ends_with(":*") => {
+                return Err(":* is only allowed at the end of a Bash rule".to_string());
+            }
+            "WebFetch" if !spec.starts_with("domain:") => {
+                return Err("WebFetch rules look like WebFetch(domain:example.com)".to_string());
+            }
+            _ => {}
+        }
+        if READ_TOOLS.contains(&tool) || EDIT_TOOLS.contains(&tool) {
+            glob::Pattern::new(spec.trim_start_matches('/'))
+                .map_err(|e| format!("Invalid path pattern: {}", e))?;
+        }
+    }
+    Ok(Rule {
+        text: text.to_string(),
+        tool: tool.to_string(),
+        specifier,
+    })
+}
+
+fn check_rule(rule: &str) -> RuleCheck {
+    match parse_rule(rule) {
+        Ok(parsed) => {
+            let known =
+                KNOWN_TOOLS.contains(&parsed.tool.as_str()) || parsed.tool.starts_with("mcp__");
+            RuleCheck {
+                rule: parsed.text,
+                valid: true,
+                warning: (!known).then(|| format!("Unknown tool: {}", parsed.tool)),
+                tool: Some(parsed.tool),
+                specifier: parsed.specifier,
+                error: None,
+            }
+        }
+        Err(error) => RuleCheck {
+            rule: rule.to_string(),
+            valid: false,
+            tool: None,
+            specifier: None,
+            error: Some(error),
+            warning: None,
+        },
+    }
+}
+
+fn strings_at(permissions: Option<&Value>, key: &str) -> Vec<String> {
+    permissions
+        .and_then(|p| p.get(key))
+        .and_then(Value::as_array)
+        .map(|a| {
+            a.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn read_permissions(scope: &str, project: Option<&str>) -> Result<ToolPermissions, String> {
+    let settings = read_json(&settings_path(scope, project)?)?;
+    let permissions = settings.get("permissions");
+    Ok(ToolPermissions {
+        scope: scope.to_string(),
+        rules: ToolPermissionRules {
+            allow: strings_at(permissions, "allow"),
+            deny: strings_at(permissions, "deny"),
+            ask: strings_at(permissions, "ask"),
+        },
+        default_mode: permissions
+            .and_then(|p| p.get("defaultMode"))
+            .and_then(Value::as_str)
+            .map(String::from),
+        additional_directories: strings_at(permissions, "additionalDirectories"),
+    })
+}
+
+/// Allow/deny/ask rules and related permission settings for one settings scope
+#[tauri::command]
+pub fn get_tool_permissions(
+    scope: String,
+    project: Option<String>,
+) -> Result<ToolPermissions, String> {
+    read_permissions(&scope, project.as_deref())
+}
+
+/// Replace the allow/deny/ask lists of a scope. Every rule is checked first and all syntax
+/// errors are reported together; other permission keys like defaultMode are kept.
+#[tauri::command]
+pub fn set_tool_permissions(
+    scope: String,
+    rules: ToolPermissionRules,
+    project: Option<String>,
+) -> Result<ToolPermissions, String> {
+    let errors: Vec<String> = [&rules.allow, &rules.deny, &rules.ask]
+        .into_iter()
+        .flatten()
+        .map(|rule| check_rule(rule))
+        .filter_map(|check| Some(format!("{}: {}", check.rule, check.error?)))
+        .collect();
+    if !errors.is_empty() {
+        return Err(format!("Invalid permission rules:\n{}", errors.join("\n")));
+    }
+
+    let path = settings_path(&scope, project.as_deref())?;
+    let mut settings = read_json(&path)?;
+    let Some(root) = settings.as_object_mut() else {
+        return Err(format!("{} is not a JSON object", path.display()));
+    };
+    let permissions = root
+        .entry("permissions")
+        .or_insert_with(|| Value::Object(Map::new()));
+    if !permissions.is_object() {
+        *permissions = Value::Object(Map::new());
+    }
+    let permissions = permissions.as_object_mut().unwrap();
+    for (key, list) in [
+        ("allow", &rules.allow),
+        ("deny", &rules.deny),
+        ("ask", &rules.ask),
+    ] {
+        let list: Vec<&str> = list.iter().map(|r| r.trim()).collect();
+        if list.is_empty() {
+            permissions.remove(key);
+        } else {
+            permissions.insert(key.to_string(), Value::from(list));
+        }
+    }
+    if permissions.is_empty() {
+        root.remove("permissions");
+    }
+    write_json(&path, &settings)?;
+    read_permissions(&scope, project.as_deref())
+}
+
+/// Check one rule's syntax as it is typed
+#[tauri::command]
+pub fn validate_permission_rule(rule: String) -> RuleCheck {
+    check_rule(&rule)
+}
+
+/// Resolve `.` and `..` without touching the filesystem, since the path may not exist yet
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Turn a rule's gitignore-style pattern into an absolute glob: `//x` is absolute, `~/x` is
+/// under home, `/x` and `./x` are under the project, and a bare name matches at any depth
+fn absolute_pattern(spec: &str, root: &Path) -> String {
+    let home = crate::get_home_dir();
+    let mut pattern = if let Some(abs) = spec.strip_prefix("//") {
+        format!("/{}", abs)
+    } else if let Some(rest) = spec.strip_prefix("~/") {
+        format!("{}/{}", home, rest)
+    } else if let Some(rest) = spec.strip_prefix('/').or_else(|| spec.strip_prefix("./")) {
+        format!("{}/{}", root.display(), rest)
+    } else if spec.trim_end_matches('/').contains('/') {
+        format!("{}/{}", root.display(), spec)
+    } else {
+        format!("{}/**/{}", root.display(), spec)
+    };
+    // A directory pattern covers everything inside it
+    if pattern.ends_with('/') {
+        pattern.push_str("**");
+    }
+    pattern
+}
+
+fn path_matches(spec: &str, path: &str, root: &Path) -> bool {
+    let target = if Path::new(path).is_absolute() {
+        normalize(Path::new(path))
+    } else {
+        normalize(&root.join(path))
+    };
+    let options = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    let pattern = absolute_pattern(spec, root);
+    let matches = |pattern: &str| {
+        glob::Pattern::new(pattern).is_ok_and(|p| p.matches_path_with(&target, options))
+    };
+    // A pattern naming a directory also covers the files under it
+    matches(&pattern) || (!pattern.ends_with("**") && matches(&format!("{}/**", pattern)))
+}
+
+/// The input field a tool's specifier is matched against
+fn primary_input(tool: &str, input: &Value) -> Option<String> {
+    if let Value::String(text) = input {
+        return Some(text.clone());
+    }
+    let keys: &[&str] = match tool {
+        "Bash" => &["command"],
+        "WebFetch" => &["url"],
+        "Glob" | "Grep" | "LS" => &["path", "pattern"],
+        "NotebookEdit" | "NotebookRead" => &["notebook_path"],
+        "Task" => &["subagent_type"],
+        _ => &["file_path", "path"],
+    };
+    keys.iter()
+        .find_map(|key| input.get(*key).and_then(Value::as_str))
+        .map(String::from)
+}
+
+fn host_of(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+/// Does `rule` cover a call of `tool` whose specifier-relevant input is `value`
+fn rule_matches(rule: &Rule, tool: &str, value: Option<&str>, root: &Path) -> bool {
+    if let Some(server) = rule.tool.strip_suffix("__*") {
+        return tool.starts_with(&format!("{}__", server));
+    }
+    if rule.tool.starts_with("mcp__") {
+        // mcp__server covers every tool of that server
+        return tool == rule.tool || tool.starts_with(&format!("{}__", rule.tool));
+    }
+    let same_family =
+        |family: &[&str]| family.contains(&rule.tool.as_str()) && family.contains(&tool);
+    if rule.tool != tool && !same_family(READ_TOOLS) && !same_family(EDIT_TOOLS) {
+        return false;
+    }
+    let Some(spec) = &rule.specifier else {
+        return true;
+    };
+    let Some(value) = value else {
+        return false;
+    };
+    match rule.tool.as_str() {
+        "Bash" => match spec.strip_suffix(":*") {
+            Some(prefix) => value.starts_with(prefix),
+            None => value == spec,
+        },
+        "WebFetch" => {
+            let domain = spec.trim_start_matches("domain:").to_lowercase();
+            host_of(value).is_some_and(|host| match domain.strip_prefix("*.") {
+                Some(parent) => host.ends_with(&format!(".{}", parent)),
+                None => host == domain,
+            })
+        }
+        t if READ_TOOLS.contains(&t) || EDIT_TOOLS.contains(&t) => path_matches(spec, value, root),
+        _ => value == spec,
+    }
+}
+
+/// Split a shell command on `&&`, `||`, `;`, `|` and newlines; each part is checked on its own,
+/// as Claude Code does, so `safe && rm -rf x` isn't allowed by a rule for `safe`
+fn command_parts(command: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        let separator = match c {
+            ';' | '\n' => true,
+            '&' if chars.peek() == Some(&'&') => {
+                chars.next();
+                true
+            }
+            '|' => {
+                if chars.peek() == Some(&'|') {
+                    chars.next();
+                }
+                true
+            }
+            _ => false,
+        };
+        if separator {
+            parts.push(std::mem::take(&mut current));
+        } else {
+            current.push(c);
+        }
+    }
+    parts.push(current);
+    parts
+        .into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// Dry-run Claude Code's permission check for one tool call. Rules come from every scope
+/// (user, plus project and local when `project` is given), or only from `rules` when set so
+/// unsaved edits can be previewed. Deny beats ask beats allow; a compound shell command is
+/// allowed only if every part is.
+#[tauri::command]
+pub fn would_allow(
+    tool: String,
+    input: Value,
+    project: Option<String>,
+    rules: Option<ToolPermissionRules>,
+) -> Result<PermissionDecision, String> {
+    let mut lists: Vec<(String, &str, Vec<String>)> = Vec::new();
+    let mut sets = Vec::new();
+    match rules {
+        Some(rules) => sets.push(("draft".to_string(), rules)),
+        None => {
+            let scopes: &[&str] = if project.is_some() {
+                &["user", "project", "local"]
+            } else {
+                &["user"]
+            };
+            for scope in scopes {
+                let permissions = read_permissions(scope, project.as_deref())?;
+                sets.push((scope.to_string(), permissions.rules));
+            }
+        }
+    }
+    for (scope, rules) in sets {
+        lists.push((scope.clone(), "deny", rules.deny));
+        lists.push((scope.clone(), "ask", rules.ask));
+        lists.push((scope, "allow", rules.allow));
+    }
+    let parsed: Vec<(String, &str, Rule)> = lists
+        .into_iter()
+        .flat_map(|(scope, list, rules)| {
+            rules
+                .into_iter()
+                .filter_map(move |r| parse_rule(&r).ok().map(|rule| (scope.clone(), list, rule)))
+        })
+        .collect();
+
+    let root = PathBuf::from(crate::git::resolve_repo(
+        &project.unwrap_or_else(crate::get_home_dir),
+    ));
+    let value = primary_input(&tool, &input);
+    let parts = match (&value, tool.as_str()) {
+        (Some(command), "Bash") => command_parts(command),
+        _ => vec![value.clone().unwrap_or_default()],
+    };
+    let first_match = |list: &str, part: &str| {
+        let value = value.as_ref().map(|_| part);
+        parsed
+            .iter()
+            .find(|(_, l, rule)| *l == list && rule_matches(rule, &tool, value, &root))
+    };
+    let decision = |list: &str, hit: Option<&(String, &str, Rule)>, part: Option<&String>| {
+        PermissionDecision {
+            decision: list.to_string(),
+            rule: hit.map(|(_, _, rule)| rule.text.clone()),
+            scope: hit.map(|(scope, _, _)| scope.clone()),
+            command_part: part.filter(|_| parts.len() > 1).cloned(),
+        }
+    };
+
+    // Any denied or ask-listed part decides for the whole call
+    for list in &LISTS[..2] {
+        for part in &parts {
+            if let Some(hit) = first_match(list, part) {
+                return Ok(decision(list, Some(hit), Some(part)));
+            }
+        }
+    }
+    let mut last_allow = None;
+    for part in &parts {
+        match first_match(LISTS[2], part) {
+            Some(hit) => last_allow = Some(hit),
+            None => return Ok(decision("default", None, Some(part))),
+        }
+    }
+    Ok(match last_allow {
+        Some(hit) => decision("allow", Some(hit), None),
+        None => decision("default", None, None),
+    })
+}
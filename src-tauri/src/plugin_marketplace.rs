@@ -0,0 +1,385 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Listed even before the user adds any marketplace
+const DEFAULT_MARKETPLACES: &[(&str, &str)] = &[(
+    "claude-plugins-official",
+    "anthropics/claude-plugins-official",
+)];
+/// Cached listings younger than this are used without fetching
+const CACHE_TTL_MS: u64 = 6 * 60 * 60 * 1000;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_README_BYTES: usize = 200_000;
+
+/// Marketplace manifests as last fetched, kept so browsing works offline
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct MarketplaceCache {
+    fetched_at: u64,
+    marketplaces: BTreeMap<String, CachedMarketplace>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct CachedMarketplace {
+    /// GitHub `owner/repo`, when the marketplace lives on GitHub
+    repo: Option<String>,
+    /// Local clone made by `claude plugin marketplace add`
+    install_location: Option<String>,
+    manifest: Value,
+}
+
+#[derive(serde::Serialize)]
+pub struct MarketplacePluginInfo {
+    /// `name@marketplace`, as passed to install_claude_plugin
+    id: String,
+    name: String,
+    marketplace: String,
+    description: Option<String>,
+    version: Option<String>,
+    author: Option<String>,
+    category: Option<String>,
+    keywords: Vec<String>,
+    homepage: Option<String>,
+    installed: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct PluginSearchResults {
+    plugins: Vec<MarketplacePluginInfo>,
+    /// When the listings were last fetched
+    fetched_at: u64,
+    /// A fetch failed, so some listings may be out of date
+    offline: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct MarketplacePluginDetails {
+    #[serde(flatten)]
+    info: MarketplacePluginInfo,
+    installed_version: Option<String>,
+    license: Option<String>,
+    repository: Option<String>,
+    /// The manifest's `source`: a path inside the marketplace or a repository object
+    source: Value,
+    readme: Option<String>,
+    /// The plugin's full marketplace entry
+    entry: Value,
+}
+
+fn cache_path() -> PathBuf {
+    crate::ade_path("plugin-marketplaces.json")
+}
+
+fn read_cache() -> MarketplaceCache {
+    std::fs::read_to_string(cache_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn write_cache(cache: &MarketplaceCache) {
+    let path = cache_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string(cache) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// `owner/repo` from a GitHub shorthand or URL
+fn github_repo(source: &str) -> Option<String> {
+    let source = source.trim().trim_end_matches('/').trim_end_matches(".git");
+    let path = source
+        .strip_prefix("https://github.com/")
+        .or_else(|| source.strip_prefix("git@github.com:"))
+        .unwrap_or(source);
+    let mut parts = path.split('/');
+    let (owner, repo) = (parts.next()?, parts.next()?);
+    if parts.next().is_some() || owner.is_empty() || repo.is_empty() || path.contains(':') {
+        return None;
+    }
+    Some(format!("{}/{}", owner, repo))
+}
+
+fn client() -> Result<reqwest::blocking::Client, String> {
+    reqwest::blocking::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+fn fetch_text(client: &reqwest::blocking::Client, url: &str) -> Result<String, String> {
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch {}: {}", url, response.status()));
+    }
+    response
+        .text()
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))
+}
+
+fn raw_url(repo: &str, path: &str) -> String {
+    format!("https://raw.githubusercontent.com/{}/HEAD/{}", repo, path)
+}
+
+fn read_manifest(dir: &Path) -> Option<Value> {
+    let path = dir.join(".claude-plugin").join("marketplace.json");
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Refresh every marketplace, preferring GitHub, then the local clone, then the old cache.
+/// Returns whether any marketplace could only be served from the cache.
+fn refresh(cache: &mut MarketplaceCache) -> bool {
+    let mut sources: BTreeMap<String, (Option<String>, Option<String>)> = DEFAULT_MARKETPLACES
+        .iter()
+        .map(|(name, repo)| (name.to_string(), (Some(repo.to_string()), None)))
+        .collect();
+    for (name, known) in crate::plugins::known_marketplaces() {
+        let repo = [&known.source.repo, &known.source.url]
+            .into_iter()
+            .flatten()
+            .find_map(|s| github_repo(s));
+        sources.insert(name, (repo, Some(known.install_location)));
+    }
+
+    let client = client().ok();
+    let mut offline = false;
+    let mut marketplaces = BTreeMap::new();
+    for (name, (repo, install_location)) in sources {
+        let fetched = repo
+            .as_deref()
+            .zip(client.as_ref())
+            .and_then(|(repo, client)| {
+                let url = raw_url(repo, ".claude-plugin/marketplace.json");
+                serde_json::from_str::<Value>(&fetch_text(client, &url).ok()?).ok()
+            });
+        let manifest = fetched.or_else(|| {
+            offline |= repo.is_some();
+            install_location
+                .as_deref()
+                .and_then(|dir| read_manifest(Path::new(dir)))
+        });
+        let manifest = match manifest {
+            Some(manifest) => manifest,
+            None => match cache.marketplaces.remove(&name) {
+                Some(cached) => cached.manifest,
+                None => continue,
+            },
+        };
+        marketplaces.insert(
+            name,
+            CachedMarketplace {
+                repo,
+                install_location,
+                manifest,
+            },
+        );
+    }
+    cache.marketplaces = marketplaces;
+    cache.fetched_at = crate::now_ms();
+    offline
+}
+
+/// The cached listings, refreshed first when stale or when `force` is set
+fn load(force: bool) -> (MarketplaceCache, bool) {
+    let mut cache = read_cache();
+    let stale = crate::now_ms().saturating_sub(cache.fetched_at) > CACHE_TTL_MS;
+    if !(force || stale || cache.marketplaces.is_empty()) {
+        return (cache, false);
+    }
+    let offline = refresh(&mut cache);
+    write_cache(&cache);
+    (cache, offline)
+}
+
+fn text(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(String::from)
+}
+
+fn info(
+    marketplace: &str,
+    entry: &Value,
+    installed: &BTreeMap<String, Option<String>>,
+) -> Option<MarketplacePluginInfo> {
+    let name = text(entry, "name")?;
+    let id = format!("{}@{}", name, marketplace);
+    // Authors are either a string or { name, email, url }
+    let author = entry
+        .get("author")
+        .and_then(|a| a.as_str().or_else(|| a.get("name")?.as_str()))
+        .map(String::from);
+    let keywords = ["keywords", "tags"]
+        .iter()
+        .filter_map(|key| entry.get(key)?.as_array())
+        .flatten()
+        .filter_map(|k| k.as_str().map(String::from))
+        .collect();
+    Some(MarketplacePluginInfo {
+        installed: installed.contains_key(&id),
+        id,
+        name,
+        marketplace: marketplace.to_string(),
+        description: text(entry, "description"),
+        version: text(entry, "version"),
+        author,
+        category: text(entry, "category"),
+        keywords,
+        homepage: text(entry, "homepage"),
+    })
+}
+
+fn entries(cache: &MarketplaceCache) -> impl Iterator<Item = (&String, &Value)> {
+    cache.marketplaces.iter().flat_map(|(name, marketplace)| {
+        marketplace
+            .manifest
+            .get("plugins")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(move |entry| (name, entry))
+    })
+}
+
+/// Installed plugin ids and their versions
+fn installed() -> BTreeMap<String, Option<String>> {
+    crate::plugins::read_plugins()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| (p.id, p.version))
+        .collect()
+}
+
+/// Lower is better; None when some query term matches nothing
+fn rank(plugin: &MarketplacePluginInfo, terms: &[String]) -> Option<u8> {
+    let name = plugin.name.to_lowercase();
+    let rest = [&plugin.description, &plugin.category, &plugin.author]
+        .into_iter()
+        .flatten()
+        .chain(&plugin.keywords)
+        .map(|s| s.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("\n");
+    terms.iter().try_fold(0, |best, term| {
+        let score = if name == *term {
+            0
+        } else if name.starts_with(term.as_str()) {
+            1
+        } else if name.contains(term.as_str()) {
+            2
+        } else if rest.contains(term.as_str()) {
+            3
+        } else {
+            return None;
+        };
+        Some(best.max(score))
+    })
+}
+
+/// Search every known marketplace by name, description, category, author and keywords. An
+/// empty query lists everything. Listings are cached for offline use and refetched every few
+/// hours, or now when `refresh` is set.
+#[tauri::command]
+pub async fn search_claude_plugins(
+    query: String,
+    refresh: Option<bool>,
+) -> Result<PluginSearchResults, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (cache, offline) = load(refresh.unwrap_or(false));
+        let installed = installed();
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let mut plugins: Vec<(u8, MarketplacePluginInfo)> = entries(&cache)
+            .filter_map(|(marketplace, entry)| info(marketplace, entry, &installed))
+            .filter_map(|plugin| Some((rank(&plugin, &terms)?, plugin)))
+            .collect();
+        plugins.sort_by(|(a, x), (b, y)| a.cmp(b).then_with(|| x.id.cmp(&y.id)));
+        Ok(PluginSearchResults {
+            plugins: plugins.into_iter().map(|(_, plugin)| plugin).collect(),
+            fetched_at: cache.fetched_at,
+            offline,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The plugin's README: from the marketplace's local clone when it has one, otherwise from
+/// GitHub
+fn readme(marketplace: &CachedMarketplace, source: &Value) -> Option<String> {
+    let mut readme = match source {
+        Value::String(path) => {
+            let path = path.trim_start_matches("./").trim_end_matches('/');
+            let local = marketplace.install_location.as_deref().and_then(|dir| {
+                std::fs::read_to_string(Path::new(dir).join(path).join("README.md")).ok()
+            });
+            local.or_else(|| {
+                let repo = marketplace.repo.as_deref()?;
+                let path = if path.is_empty() {
+                    "README.md".to_string()
+                } else {
+                    format!("{}/README.md", path)
+                };
+                fetch_text(&client().ok()?, &raw_url(repo, &path)).ok()
+            })?
+        }
+        Value::Object(source) => {
+            let repo = source
+                .get("repo")
+                .or_else(|| source.get("url"))
+                .and_then(Value::as_str)
+                .and_then(github_repo)?;
+            fetch_text(&client().ok()?, &raw_url(&repo, "README.md")).ok()?
+        }
+        _ => return None,
+    };
+    if readme.len() > MAX_README_BYTES {
+        let mut end = MAX_README_BYTES;
+        while !readme.is_char_boundary(end) {
+            end -= 1;
+        }
+        readme.truncate(end);
+    }
+    Some(readme)
+}
+
+/// Full marketplace entry and README for a plugin, by `name@marketplace` or bare name
+#[tauri::command]
+pub async fn get_plugin_details(name: String) -> Result<MarketplacePluginDetails, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (cache, _) = load(false);
+        let (plugin, marketplace) = match name.split_once('@') {
+            Some((plugin, marketplace)) => (plugin, Some(marketplace)),
+            None => (name.as_str(), None),
+        };
+        let (marketplace, entry) = entries(&cache)
+            .find(|(m, entry)| {
+                marketplace.is_none_or(|wanted| wanted == m.as_str())
+                    && entry.get("name").and_then(Value::as_str) == Some(plugin)
+            })
+            .ok_or_else(|| format!("Plugin not found in any marketplace: {}", name))?;
+        let installed = installed();
+        let info = info(marketplace, entry, &installed)
+            .ok_or_else(|| format!("Plugin not found in any marketplace: {}", name))?;
+        let source = entry.get("source").cloned().unwrap_or(Value::Null);
+        // Repositories are either a URL or { type, url }
+        let repository = entry
+            .get("repository")
+            .and_then(|r| r.as_str().or_else(|| r.get("url")?.as_str()))
+            .map(String::from);
+        Ok(MarketplacePluginDetails {
+            installed_version: installed.get(&info.id).cloned().flatten(),
+            info,
+            license: text(entry, "license"),
+            repository,
+            readme: readme(&cache.marketplaces[marketplace], &source),
+            source,
+            entry: entry.clone(),
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
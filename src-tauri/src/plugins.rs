@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::ipc::Channel;
+
+/// One install record; v2 files keep a list of these per plugin (one per scope)
+#[derive(Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InstallRecord {
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    install_path: Option<String>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum InstallEntry {
+    Many(Vec<InstallRecord>),
+    One(InstallRecord),
+}
+
+#[derive(serde::Deserialize)]
+struct InstalledPlugins {
+    #[serde(default)]
+    plugins: HashMap<String, InstallEntry>,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClaudeSettings {
+    #[serde(default)]
+    enabled_plugins: HashMap<String, bool>,
+}
+
+#[derive(serde::Serialize)]
+pub struct ClaudePlugin {
+    /// Full id, `name@marketplace`
+    pub(crate) id: String,
+    name: String,
+    marketplace: Option<String>,
+    pub(crate) version: Option<String>,
+    enabled: bool,
+    install_path: Option<String>,
+    scope: Option<String>,
+}
+
+pub(crate) fn read_plugins() -> Result<Vec<ClaudePlugin>, String> {
+    let home = crate::get_home_dir();
+    let path = format!("{}/.claude/plugins/installed_plugins.json", home);
+    let content =
+        std::fs::read_to_string(&path).map_err(|_| "No installed plugins file".to_string())?;
+    let installed: InstalledPlugins = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse installed plugins: {}", e))?;
+    // Enabled state lives in the user settings, not the install manifest
+    let settings: ClaudeSettings =
+        std::fs::read_to_string(format!("{}/.claude/settings.json", home))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+
+    let mut plugins: Vec<ClaudePlugin> = installed
+        .plugins
+        .into_iter()
+        .map(|(id, entry)| {
+            let record = match entry {
+                InstallEntry::Many(records) => records.into_iter().next().unwrap_or_default(),
+                InstallEntry::One(record) => record,
+            };
+            let (name, marketplace) = match id.split_once('@') {
+                Some((name, marketplace)) => (name.to_string(), Some(marketplace.to_string())),
+                None => (id.clone(), None),
+            };
+            ClaudePlugin {
+                // Installed plugins are on unless explicitly switched off
+                enabled: settings.enabled_plugins.get(&id).copied().unwrap_or(true),
+                id,
+                name,
+                marketplace,
+                version: record.version,
+                install_path: record.install_path,
+                scope: record.scope,
+            }
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(plugins)
+}
+
+#[tauri::command]
+pub fn list_claude_plugins() -> Result<Vec<ClaudePlugin>, String> {
+    read_plugins()
+}
+
+/// Whether a plugin is installed, by full `name@marketplace` id or by bare name
+#[tauri::command]
+pub fn check_claude_plugin(plugin_name: String) -> Result<bool, String> {
+    Ok(read_plugins()?
+        .iter()
+        .any(|p| p.id == plugin_name || p.name == plugin_name))
+}
+
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginErrorKind {
+    ClaudeNotInstalled,
+    NotFound,
+    /// A repository was given whose marketplace offers several plugins
+    Ambiguous,
+    Network,
+    Failed,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum PluginEvent {
+    /// A claude CLI invocation is starting
+    #[serde(rename = "step")]
+    Step { label: String },
+    #[serde(rename = "output")]
+    Output { text: String },
+    #[serde(rename = "error")]
+    Error {
+        kind: PluginErrorKind,
+        message: String,
+    },
+}
+
+fn fail(on_event: &Channel<PluginEvent>, kind: PluginErrorKind, message: String) -> String {
+    let _ = on_event.send(PluginEvent::Error {
+        kind,
+        message: message.clone(),
+    });
+    message
+}
+
+fn classify(output: &str) -> PluginErrorKind {
+    let lower = output.to_lowercase();
+    if lower.contains("not found")
+        || lower.contains("not installed")
+        || lower.contains("unknown plugin")
+    {
+        PluginErrorKind::NotFound
+    } else if [
+        "enotfound",
+        "econnrefused",
+        "etimedout",
+        "network",
+        "could not resolve",
+    ]
+    .iter()
+    .any(|needle| lower.contains(needle))
+    {
+        PluginErrorKind::Network
+    } else {
+        PluginErrorKind::Failed
+    }
+}
+
+/// Run `claude plugin …`, streaming its output line by line
+fn run_claude(args: &[&str], on_event: &Channel<PluginEvent>) -> Result<(), String> {
+    use std::io::BufRead;
+    let claude = crate::check_command_exists("claude".to_string()).map_err(|_| {
+        fail(
+            on_event,
+            PluginErrorKind::ClaudeNotInstalled,
+            "Claude Code CLI (claude) is not installed".to_string(),
+        )
+    })?;
+    let _ = on_event.send(PluginEvent::Step {
+        label: format!("claude {}", args.join(" ")),
+    });
+    let mut child = std::process::Command::new(claude)
+        .args(args)
+        .env("NO_COLOR", "1")
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            fail(
+                on_event,
+                PluginErrorKind::Failed,
+                format!("Failed to run claude: {}", e),
+            )
+        })?;
+
+    let stderr = child.stderr.take().unwrap();
+    let stderr_channel = on_event.clone();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut lines = Vec::new();
+        for line in std::io::BufReader::new(stderr)
+            .lines()
+            .map_while(Result::ok)
+        {
+            if !line.trim().is_empty() {
+                let _ = stderr_channel.send(PluginEvent::Output { text: line.clone() });
+                lines.push(line);
+            }
+        }
+        lines
+    });
+    let mut output = Vec::new();
+    let stdout = child.stdout.take().unwrap();
+    for line in std::io::BufReader::new(stdout)
+        .lines()
+        .map_while(Result::ok)
+    {
+        if !line.trim().is_empty() {
+            let _ = on_event.send(PluginEvent::Output { text: line.clone() });
+            output.push(line);
+        }
+    }
+    output.extend(stderr_reader.join().unwrap_or_default());
+
+    let status = child.wait().map_err(|e| {
+        fail(
+            on_event,
+            PluginErrorKind::Failed,
+            format!("Failed to run claude: {}", e),
+        )
+    })?;
+    if !status.success() {
+        let detail = output.join("\n");
+        return Err(fail(
+            on_event,
+            classify(&detail),
+            format!("claude plugin failed: {}", detail.trim()),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(serde::Deserialize)]
+pub(crate) struct MarketplaceSource {
+    #[serde(default)]
+    pub(crate) repo: Option<String>,
+    #[serde(default)]
+    pub(crate) url: Option<String>,
+    #[serde(default)]
+    path: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KnownMarketplace {
+    pub(crate) source: MarketplaceSource,
+    pub(crate) install_location: String,
+}
+
+#[derive(serde::Deserialize)]
+struct MarketplaceManifest {
+    #[serde(default)]
+    plugins: Vec<MarketplacePlugin>,
+}
+
+#[derive(serde::Deserialize)]
+struct MarketplacePlugin {
+    name: String,
+}
+
+/// Marketplaces added to Claude Code, by name
+pub(crate) fn known_marketplaces() -> HashMap<String, KnownMarketplace> {
+    let path = format!(
+        "{}/.claude/plugins/known_marketplaces.json",
+        crate::get_home_dir()
+    );
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Marketplace name and plugin names for a marketplace added from `repo`
+fn marketplace_for(repo: &str) -> Option<(String, Vec<String>)> {
+    let known = known_marketplaces();
+    let wanted = repo.trim_end_matches(".git").trim_end_matches('/');
+    let (name, marketplace) = known.into_iter().find(|(_, m)| {
+        [&m.source.repo, &m.source.url, &m.source.path]
+            .into_iter()
+            .flatten()
+            .any(|s| {
+                let s = s.trim_end_matches(".git").trim_end_matches('/');
+                s == wanted || s.ends_with(&format!("/{}", wanted))
+            })
+    })?;
+    let manifest = Path::new(&marketplace.install_location)
+        .join(".claude-plugin")
+        .join("marketplace.json");
+    let manifest: MarketplaceManifest =
+        serde_json::from_str(&std::fs::read_to_string(manifest).ok()?).ok()?;
+    Some((name, manifest.plugins.into_iter().map(|p| p.name).collect()))
+}
+
+/// Install `name@marketplace`, or add a marketplace repository (`owner/repo`, git URL or local
+/// path) and install its plugin. Returns the updated plugin list.
+#[tauri::command]
+pub async fn install_claude_plugin(
+    name_or_repo: String,
+    on_event: Channel<PluginEvent>,
+) -> Result<Vec<ClaudePlugin>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let target = name_or_repo.trim();
+        let is_repo = !target.contains('@') || target.contains("://") || target.starts_with("git@");
+        let is_repo = is_repo && (target.contains('/') || target.ends_with(".git"));
+        let plugin = if is_repo {
+            run_claude(&["plugin", "marketplace", "add", target], &on_event)?;
+            let (marketplace, plugins) = marketplace_for(target).ok_or_else(|| {
+                fail(
+                    &on_event,
+                    PluginErrorKind::NotFound,
+                    format!("No marketplace was registered for {}", target),
+                )
+            })?;
+            match plugins.as_slice() {
+                [name] => format!("{}@{}", name, marketplace),
+                [] => {
+                    return Err(fail(
+                        &on_event,
+                        PluginErrorKind::NotFound,
+                        format!("Marketplace {} lists no plugins", marketplace),
+                    ))
+                }
+                _ => {
+                    return Err(fail(
+                        &on_event,
+                        PluginErrorKind::Ambiguous,
+                        format!(
+                            "Marketplace {} offers several plugins ({}); install one as name@{}",
+                            marketplace,
+                            plugins.join(", "),
+                            marketplace
+                        ),
+                    ))
+                }
+            }
+        } else {
+            target.to_string()
+        };
+        run_claude(&["plugin", "install", &plugin], &on_event)?;
+        read_plugins()
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Uninstall by full id or bare name. Returns the updated plugin list.
+#[tauri::command]
+pub async fn remove_claude_plugin(
+    name: String,
+    on_event: Channel<PluginEvent>,
+) -> Result<Vec<ClaudePlugin>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let id = read_plugins()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|p| p.id == name || p.name == name)
+            .map(|p| p.id)
+            .ok_or_else(|| {
+                fail(
+                    &on_event,
+                    PluginErrorKind::NotFound,
+                    format!("Plugin not installed: {}", name),
+                )
+            })?;
+        run_claude(&["plugin", "uninstall", &id], &on_event)?;
+        read_plugins()
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Refresh every marketplace, then update each installed plugin. One failing plugin doesn't stop
+/// the rest; its error is reported on the channel.
+#[tauri::command]
+pub async fn update_claude_plugins(
+    on_event: Channel<PluginEvent>,
+) -> Result<Vec<ClaudePlugin>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        run_claude(&["plugin", "marketplace", "update"], &on_event)?;
+        for plugin in read_plugins()? {
+            let _ = run_claude(&["plugin", "update", &plugin.id], &on_event);
+        }
+        read_plugins()
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,518 @@
+//! Per-project limits on what the frontend may ask the backend to do, checked in the invoke
+//! handler before any command runs, and before runs that start elsewhere. They are set under
+//! "policy" in .ade/settings.json: the user's apply everywhere, and a project's can only add to
+//! them, since the project's file is itself writable from inside the project. Changing either
+//! takes a set_policy confirmation. A command is judged by the project its path is in, or the
+//! one opened last when it names none. Projects can also be made read-only, in the project
+//! registry rather than their own files.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::Manager;
+
+/// Commands that write to a path, with where in their arguments the path is (a JSON pointer)
+/// and, for those that take a relative path, the argument it is relative to
+const PATH_WRITES: &[(&str, &str, Option<&str>)] = &[
+    ("write_text_file", "/path", None),
+    ("create_directory", "/path", None),
+    ("delete_path", "/path", None),
+    ("write_env_file", "/path", None),
+    ("download_file", "/dest", None),
+    ("git_worktree_add", "/path", Some("repo")),
+    ("request_confirmation", "/op/path", None),
+    ("execute_confirmed", "/op/path", None),
+];
+
+/// Commands that change a project's files or git state, with where in their arguments the path
+/// they act on may be; the first present counts
+const MUTATING: &[(&str, &[&str])] = &[
+    ("write_text_file", &["/path"]),
+    ("create_directory", &["/path"]),
+    ("delete_path", &["/path"]),
+    ("write_env_file", &["/path"]),
+    ("download_file", &["/dest"]),
+    ("merge_claude_md", &["/root"]),
+    ("set_project_settings", &["/root"]),
+    ("git_stage", &["/repo"]),
+    ("git_unstage", &["/repo"]),
+    ("git_stage_hunk", &["/repo"]),
+    ("git_discard_hunk", &["/repo"]),
+    ("git_restore", &["/repo"]),
+    ("git_commit", &["/repo"]),
+    ("git_stash_save", &["/repo"]),
+    ("git_stash_pop", &["/repo"]),
+    ("git_worktree_add", &["/repo"]),
+    ("git_worktree_remove", &["/repo"]),
+    ("git_pull", &["/repo"]),
+    ("git_fetch", &["/repo"]),
+    ("git_push", &["/repo"]),
+    ("revert_to_checkpoint", &["/repo"]),
+    ("revert_change", &["/repo"]),
+    ("approve_change", &["/repo"]),
+    ("create_checkpoint", &["/repo"]),
+    (
+        "request_confirmation",
+        &["/op/path", "/op/repo", "/op/root"],
+    ),
+    ("execute_confirmed", &["/op/path", "/op/repo", "/op/root"]),
+    ("run_command", &["/options/cwd"]),
+    ("run_task", &["/root"]),
+    ("run_agent", &["/options/cwd"]),
+    ("start_orchestration", &["/repo"]),
+    // Filled in from the orchestration, in enforce
+    ("remove_orchestration", &["/repo"]),
+    ("test_hook", &["/cwd"]),
+    ("create_schedule", &["/schedule/action/cwd"]),
+    ("update_schedule", &["/schedule/action/cwd"]),
+    // Filled in from the schedule, in enforce
+    ("run_schedule_now", &["/cwd"]),
+];
+
+/// Commands that change a project's files only for some values of one argument: the path's
+/// pointer, the argument's, and the values that make the command write into the project
+const MUTATING_WHEN: &[(&str, &str, &str, &[&str])] = &[
+    (
+        "ingest_dropped_files",
+        "/options/root",
+        "/options/copy_into",
+        &["project"],
+    ),
+    ("create_slash_command", "/project", "/scope", &["project"]),
+    ("rename_slash_command", "/project", "/scope", &["project"]),
+    ("delete_slash_command", "/project", "/scope", &["project"]),
+    (
+        "add_claude_hook",
+        "/project",
+        "/scope",
+        &["project", "local"],
+    ),
+    (
+        "remove_claude_hook",
+        "/project",
+        "/scope",
+        &["project", "local"],
+    ),
+    (
+        "write_claude_settings",
+        "/project",
+        "/scope",
+        &["project", "local"],
+    ),
+    ("add_mcp_server", "/project", "/scope", &["project"]),
+    ("update_mcp_server", "/project", "/scope", &["project"]),
+    ("remove_mcp_server", "/project", "/scope", &["project"]),
+    (
+        "set_tool_permissions",
+        "/project",
+        "/scope",
+        &["project", "local"],
+    ),
+];
+
+/// Commands that type into a terminal, with the argument holding its id
+const PTY_WRITES: &[(&str, &str)] = &[
+    ("write_pty", "/id"),
+    ("resume_session_in_pty", "/ptyId"),
+    ("paste_history_item", "/ptyId"),
+];
+
+/// The commands each request_confirmation op stands in for, so disabling one disables both.
+/// kill_process and free_port take their op's token themselves, so they count as the op too.
+const CONFIRMED_OPS: &[(&str, &str)] = &[
+    ("delete_path", "delete_path"),
+    ("force_push", "git_push"),
+    ("kill_process", "kill_process"),
+    ("free_port", "kill_process"),
+    ("make_writable", "set_project_read_only"),
+    ("set_policy", "set_project_settings"),
+];
+
+/// Commands that run a program named by an argument
+const PROGRAM_RUNS: &[(&str, &str)] = &[("run_command", "program")];
+
+/// Commands that act on another machine when given a `location`
+const TAKES_LOCATION: &[&str] = &[
+    "write_text_file",
+    "create_directory",
+    "read_file",
+    "read_file_base64",
+    "list_directory",
+    "search_text",
+    "watch_directory",
+    "create_pty",
+];
+
+/// Whether the command works on a remote host, outside anything a local project can bound.
+/// Other commands would ignore a location argument, so it counts for none of them.
+fn is_remote(command: &str, args: &serde_json::Value) -> bool {
+    TAKES_LOCATION.contains(&command) && !args["location"].is_null()
+}
+
+/// A write asked only for its diff (write_text_file's dry_run) changes nothing. Other commands
+/// ignore a dryRun argument, so it counts for no other.
+fn is_dry_run(command: &str, args: &serde_json::Value) -> bool {
+    command == "write_text_file" && args["dryRun"].as_bool() == Some(true)
+}
+
+/// The type of operation a request_confirmation or execute_confirmed is for
+fn confirmed_op<'a>(command: &str, args: &'a serde_json::Value) -> Option<&'a str> {
+    args["op"]["type"]
+        .as_str()
+        .filter(|_| matches!(command, "request_confirmation" | "execute_confirmed"))
+}
+
+/// Lifting read-only mode (a make_writable confirmation) changes the project registry, not
+/// the project, so read-only mode and workspace_only_writes don't stop it
+fn is_make_writable(command: &str, args: &serde_json::Value) -> bool {
+    confirmed_op(command, args) == Some("make_writable")
+}
+
+/// Never blocked, so a policy can always be inspected
+const ALWAYS_ALLOWED: &[&str] = &["get_effective_policy"];
+
+/// Still allowed while a policy can't be read, so it can be fixed from the app, as is a
+/// set_policy confirmation
+const REPAIR_COMMANDS: &[&str] = &[
+    "get_effective_policy",
+    "get_project_settings",
+    "set_project_settings",
+];
+
+fn is_repair(command: &str, args: &serde_json::Value) -> bool {
+    REPAIR_COMMANDS.contains(&command) || confirmed_op(command, args) == Some("set_policy")
+}
+
+#[derive(Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub(crate) struct PolicySettings {
+    /// Commands refused outright, by name, e.g. "run_command"
+    disabled_commands: Vec<String>,
+    /// Programs run_command may start, by the name it is given; any when None. Scheduled
+    /// shell commands count as run_command of sh. Tasks, terminals and tool installs run
+    /// programs too but aren't limited by this: disable run_task, create_pty or install_tool
+    /// to stop them.
+    allowed_programs: Option<Vec<String>>,
+    /// Refuse writes to paths outside the project
+    workspace_only_writes: bool,
+}
+
+impl PolicySettings {
+    /// The user's policy with a project's limits added; nothing in `project` lifts any of them
+    fn tightened(mut self, project: PolicySettings) -> Self {
+        for command in project.disabled_commands {
+            if !self.disabled_commands.contains(&command) {
+                self.disabled_commands.push(command);
+            }
+        }
+        self.allowed_programs = match (self.allowed_programs, project.allowed_programs) {
+            (Some(user), Some(project)) => {
+                Some(user.into_iter().filter(|p| project.contains(p)).collect())
+            }
+            (user, project) => user.or(project),
+        };
+        self.workspace_only_writes |= project.workspace_only_writes;
+        self
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct EffectivePolicy {
+    /// The project the policy comes from; None when only user settings apply
+    project: Option<String>,
+    #[serde(flatten)]
+    settings: PolicySettings,
+    /// The project is read-only
+    read_only: bool,
+}
+
+/// What a refused command rejects with, in place of the usual error string
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type", rename = "permission_denied")]
+pub struct PermissionDenied {
+    command: String,
+    /// disabled_commands, allowed_programs, workspace_only_writes, read_only, or
+    /// invalid_policy when the settings can't be read
+    rule: String,
+    message: String,
+    project: Option<String>,
+}
+
+struct Cached {
+    project: Option<String>,
+    modified: [Option<SystemTime>; 2],
+    settings: Result<PolicySettings, String>,
+}
+
+/// The last policy loaded, so commands sent per keystroke don't reparse settings each time
+static CACHE: OnceLock<Mutex<Option<Cached>>> = OnceLock::new();
+
+/// The user's policy tightened by `project`'s. Err when either can't be read, which refuses
+/// commands rather than dropping the limits.
+fn load(project: Option<&str>) -> Result<PolicySettings, String> {
+    let [user, project_policy] = crate::project_settings::policy_layers(project)?;
+    let parse = |layer: serde_json::Value, scope: &str| match layer {
+        serde_json::Value::Null => Ok(PolicySettings::default()),
+        layer => serde_json::from_value::<PolicySettings>(layer)
+            .map_err(|e| format!("Invalid policy in the {} settings: {}", scope, e)),
+    };
+    Ok(parse(user, "user")?.tightened(parse(project_policy, "project")?))
+}
+
+fn policy_for(project: Option<&str>) -> Result<PolicySettings, String> {
+    let modified = crate::project_settings::modified(project);
+    let mut cache = CACHE.get_or_init(|| Mutex::new(None)).lock().unwrap();
+    if let Some(cached) = cache.as_ref() {
+        if cached.project.as_deref() == project && cached.modified == modified {
+            return cached.settings.clone();
+        }
+    }
+    let settings = load(project);
+    *cache = Some(Cached {
+        project: project.map(str::to_string),
+        modified,
+        settings: settings.clone(),
+    });
+    settings
+}
+
+/// `path` made absolute and free of symlinks as far as it exists, so a link inside the project
+/// can't stand in for a place outside it
+fn resolve(path: &str, base: Option<&str>) -> Option<PathBuf> {
+    let path = match path.strip_prefix('~') {
+        Some(rest) => PathBuf::from(format!("{}{}", crate::get_home_dir(), rest)),
+        None => PathBuf::from(path),
+    };
+    let path = match (path.is_absolute(), base) {
+        (true, _) => path,
+        (false, Some(base)) => Path::new(&crate::git::resolve_repo(base)).join(path),
+        (false, None) => return None,
+    };
+    let path = crate::permissions::normalize(&path);
+    let existing = path.ancestors().find(|dir| dir.exists())?;
+    let rest = path.strip_prefix(existing).ok()?;
+    Some(std::fs::canonicalize(existing).ok()?.join(rest))
+}
+
+/// Why `command` may not run with `args` under `policy`, if it may not
+fn check(
+    command: &str,
+    args: &serde_json::Value,
+    project: Option<&str>,
+    policy: &PolicySettings,
+) -> Option<(&'static str, String)> {
+    if ALWAYS_ALLOWED.contains(&command) {
+        return None;
+    }
+    let op = confirmed_op(command, args);
+    let stands_for = CONFIRMED_OPS
+        .iter()
+        .find(|(name, _)| *name == op.unwrap_or(command))
+        .map(|(_, command)| *command);
+    let disabled = policy
+        .disabled_commands
+        .iter()
+        .find(|c| *c == command || Some(c.as_str()) == stands_for);
+    if let Some(disabled) = disabled {
+        return Some((
+            "disabled_commands",
+            format!("{} is disabled for this project", disabled),
+        ));
+    }
+    if let Some(allowed) = &policy.allowed_programs {
+        let arg = PROGRAM_RUNS.iter().find(|(name, _)| *name == command);
+        if let Some(program) = arg.and_then(|(_, arg)| args[arg].as_str()) {
+            if !allowed.iter().any(|p| p == program) {
+                return Some((
+                    "allowed_programs",
+                    format!("{} is not an allowed program for this project", program),
+                ));
+            }
+        }
+    }
+    if policy.workspace_only_writes
+        && !is_dry_run(command, args)
+        && !is_make_writable(command, args)
+    {
+        let write = PATH_WRITES.iter().find(|(name, _, _)| *name == command);
+        let remote = is_remote(command, args);
+        let path = write
+            .filter(|_| !remote)
+            .and_then(|(_, arg, base)| Some((args.pointer(arg)?.as_str()?, base)));
+        if let Some((path, base)) = path {
+            let base = base.and_then(|base| args[base].as_str());
+            let root = project.and_then(|root| resolve(root, None));
+            let inside = match (resolve(path, base), root) {
+                (Some(path), Some(root)) => path.starts_with(root),
+                _ => false,
+            };
+            if !inside {
+                return Some((
+                    "workspace_only_writes",
+                    match project {
+                        Some(root) => format!("{} is outside the project {}", path, root),
+                        None => format!("{} is outside every project", path),
+                    },
+                ));
+            }
+        }
+    }
+    None
+}
+
+/// The local path a changing command acts on, from its arguments; None for commands that
+/// change nothing here. A terminal counts as the directory it was started in.
+fn changed_path(
+    command: &str,
+    args: &serde_json::Value,
+    pty_cwd: impl Fn(u32) -> Option<String>,
+) -> Option<PathBuf> {
+    if is_remote(command, args) || is_dry_run(command, args) || is_make_writable(command, args) {
+        return None;
+    }
+    let path = match MUTATING.iter().find(|(name, _)| *name == command) {
+        Some((_, pointers)) => pointers
+            .iter()
+            .find_map(|pointer| args.pointer(pointer)?.as_str())
+            .map(str::to_string),
+        None => match MUTATING_WHEN.iter().find(|(name, ..)| *name == command) {
+            Some((_, path, arg, values)) => {
+                if !values.contains(&args.pointer(arg)?.as_str()?) {
+                    return None;
+                }
+                args.pointer(path)?.as_str().map(str::to_string)
+            }
+            None => {
+                let (_, arg) = PTY_WRITES.iter().find(|(name, _)| *name == command)?;
+                pty_cwd(args.pointer(arg)?.as_u64()? as u32)
+            }
+        },
+    };
+    resolve(&path?, None)
+}
+
+/// The innermost of `roots` that `path` is in
+fn containing(path: &Path, roots: &[String]) -> Option<String> {
+    roots
+        .iter()
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.len())
+        .cloned()
+}
+
+/// Why `command` may not run with `args`, if it may not: the project it changes is read-only,
+/// or the policy of the project it concerns refuses it
+fn denial(
+    app: &tauri::AppHandle,
+    command: &str,
+    mut args: serde_json::Value,
+) -> Option<PermissionDenied> {
+    let projects = app.state::<crate::projects::ProjectManager>();
+    // Judge a confirmed operation by what it will do
+    if command == "execute_confirmed" {
+        let token = args["token"].as_str().unwrap_or_default();
+        let confirmations = app.state::<crate::confirm::ConfirmationManager>();
+        if let Some(op) = confirmations.peek(token) {
+            args["op"] = serde_json::to_value(op).unwrap_or_default();
+        }
+    }
+    // Commands naming what they act on by id, judged by where that is
+    if command == "remove_orchestration" {
+        let id = args["id"].as_u64().unwrap_or_default() as u32;
+        let orchestrations = app.state::<crate::orchestrator::OrchestratorManager>();
+        args["repo"] = orchestrations.repo(id).into();
+    }
+    if command == "run_schedule_now" {
+        let id = args["id"].as_str().unwrap_or_default();
+        let schedules = app.state::<crate::scheduler::SchedulerManager>();
+        args["cwd"] = schedules.cwd(id).into();
+    }
+    let pty_cwd = |id| app.state::<crate::pty::PtyManager>().start_cwd(id);
+    let path = changed_path(command, &args, pty_cwd);
+    let denied = |rule: &str, message: String, project: Option<String>| PermissionDenied {
+        command: command.to_string(),
+        rule: rule.to_string(),
+        message,
+        project,
+    };
+    let read_only = path
+        .as_deref()
+        .and_then(|path| containing(path, &projects.read_only()));
+    // The project the command changes something in, else the one being worked on
+    let project = match &path {
+        Some(path) => containing(path, &projects.paths()),
+        None => projects.last_opened(),
+    };
+    match (read_only, policy_for(project.as_deref())) {
+        (Some(root), _) => Some(denied(
+            "read_only",
+            format!("{} is read-only", root),
+            Some(root),
+        )),
+        (None, Err(e)) if !is_repair(command, &args) => Some(denied("invalid_policy", e, project)),
+        (None, Err(_)) => None,
+        (None, Ok(policy)) => check(command, &args, project.as_deref(), &policy)
+            .map(|(rule, message)| denied(rule, message, project)),
+    }
+}
+
+/// Check what runs outside the invoke handler (the IDE MCP server's tools, scheduled runs) as
+/// the command it amounts to, with the arguments that command would take
+pub(crate) fn ensure_allowed(
+    app: &tauri::AppHandle,
+    command: &str,
+    args: serde_json::Value,
+) -> Result<(), String> {
+    match denial(app, command, args) {
+        Some(denied) => {
+            tracing::warn!(command = %command, rule = %denied.rule, "{}", denied.message);
+            Err(denied.message)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Wrap the app's invoke handler so every command passes the policy first; a refused one
+/// rejects with a PermissionDenied and never reaches its handler
+pub fn enforce(
+    handler: impl Fn(Invoke) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke) -> bool + Send + Sync + 'static {
+    move |invoke: Invoke| {
+        let command = invoke.message.command().to_string();
+        let args = match invoke.message.payload() {
+            InvokeBody::Json(args) => args.clone(),
+            _ => serde_json::Value::Null,
+        };
+        let app = invoke.message.webview_ref().app_handle().clone();
+        match denial(&app, &command, args) {
+            Some(denied) => {
+                tracing::warn!(command = %command, rule = %denied.rule, "{}", denied.message);
+                invoke.resolver.reject(denied);
+                true
+            }
+            None => handler(invoke),
+        }
+    }
+}
+
+/// The policy commands are checked against: `root`'s, or the last opened project's when None
+#[tauri::command]
+pub fn get_effective_policy(
+    projects: tauri::State<'_, crate::projects::ProjectManager>,
+    root: Option<String>,
+) -> Result<EffectivePolicy, String> {
+    let project = root.or_else(|| projects.last_opened());
+    let read_only = project.as_ref().is_some_and(|project| {
+        let project = resolve(project, None);
+        projects
+            .read_only()
+            .iter()
+            .any(|root| project.as_deref() == Some(Path::new(root)))
+    });
+    Ok(EffectivePolicy {
+        settings: policy_for(project.as_deref())?,
+        project,
+        read_only,
+    })
+}
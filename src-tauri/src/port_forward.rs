@@ -0,0 +1,330 @@
+//! SSH local port forwards (`ssh -L`), so a dev server or database on a remote machine can be
+//! previewed here. Each forward runs the system ssh client, which brings ~/.ssh/config and the
+//! agent along, with keepalives; when the connection drops it is started again with backoff.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const POLL: Duration = Duration::from_millis(250);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// ssh errors that retrying won't fix
+const PERMANENT_ERRORS: [&str; 4] = [
+    "Permission denied",
+    "Host key verification failed",
+    "Could not resolve hostname",
+    "Bad configuration option",
+];
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForwardStatus {
+    Connecting,
+    Connected,
+    /// Dropped, and waiting to try again
+    Reconnecting,
+    /// Gave up on an error retrying won't fix
+    Failed,
+    Stopped,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct PortForward {
+    id: u32,
+    host: String,
+    remote_port: u16,
+    local_port: u16,
+    status: ForwardStatus,
+    /// Why the last connection ended
+    error: Option<String>,
+    reconnects: u32,
+    connected_at: Option<u64>,
+}
+
+struct Entry {
+    forward: PortForward,
+    /// The ssh process of the current attempt
+    child: Option<Child>,
+}
+
+#[derive(Clone)]
+pub struct PortForwardManager {
+    forwards: Arc<Mutex<HashMap<u32, Entry>>>,
+    next_id: Arc<Mutex<u32>>,
+}
+
+impl PortForwardManager {
+    pub fn new() -> Self {
+        Self {
+            forwards: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+
+    /// Change a forward and tell the frontend; false once it has been stopped
+    fn update(&self, app: &AppHandle, id: u32, change: impl FnOnce(&mut Entry)) -> bool {
+        let forward = {
+            let mut forwards = self.forwards.lock().unwrap();
+            let Some(entry) = forwards.get_mut(&id) else {
+                return false;
+            };
+            change(entry);
+            entry.forward.clone()
+        };
+        let _ = app.emit("port-forward-changed", forward);
+        true
+    }
+
+    /// Exit status of the current attempt, None while ssh runs (or when stopped)
+    fn try_wait(&self, id: u32) -> Option<std::process::ExitStatus> {
+        let mut forwards = self.forwards.lock().unwrap();
+        let child = forwards.get_mut(&id)?.child.as_mut()?;
+        child.try_wait().ok().flatten()
+    }
+
+    fn is_stopped(&self, id: u32) -> bool {
+        !self.forwards.lock().unwrap().contains_key(&id)
+    }
+}
+
+fn ssh_command(ssh: &str, host: &str, remote_port: u16, local_port: u16) -> Command {
+    let mut cmd = Command::new(ssh);
+    cmd.args([
+        "-N",
+        "-T",
+        // Nobody can answer a password or host key prompt
+        "-o",
+        "BatchMode=yes",
+        // Exit rather than run on without the forward when the local port is taken
+        "-o",
+        "ExitOnForwardFailure=yes",
+        "-o",
+        "ServerAliveInterval=15",
+        "-o",
+        "ServerAliveCountMax=3",
+        "-o",
+        "ConnectTimeout=15",
+        "-L",
+        &format!("127.0.0.1:{}:localhost:{}", local_port, remote_port),
+        host,
+    ])
+    .stdin(Stdio::null())
+    .stdout(Stdio::null())
+    .stderr(Stdio::piped());
+    cmd
+}
+
+/// The last thing ssh complained about
+fn last_error(child: &mut Child) -> Option<String> {
+    let mut stderr = String::new();
+    child.stderr.take()?.read_to_string(&mut stderr).ok()?;
+    stderr
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty() && !line.starts_with("Warning: Permanently added"))
+        .map(|line| line.to_string())
+}
+
+/// Keep the forward up until it is stopped or fails for good
+fn supervise(
+    manager: &PortForwardManager,
+    app: &AppHandle,
+    id: u32,
+    ssh: &str,
+) -> Result<(), String> {
+    let (host, remote_port, local_port) = {
+        let forwards = manager.forwards.lock().unwrap();
+        let Some(entry) = forwards.get(&id) else {
+            return Ok(());
+        };
+        let f = &entry.forward;
+        (f.host.clone(), f.remote_port, f.local_port)
+    };
+    let mut backoff = Duration::from_secs(1);
+    loop {
+        let mut child = ssh_command(ssh, &host, remote_port, local_port)
+            .spawn()
+            .map_err(|e| format!("Failed to run ssh: {}", e))?;
+        match manager.forwards.lock().unwrap().get_mut(&id) {
+            Some(entry) => entry.child = Some(child),
+            // Stopped while ssh was starting
+            None => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(());
+            }
+        }
+
+        let mut connected = false;
+        let status = loop {
+            if let Some(status) = manager.try_wait(id) {
+                break Some(status);
+            }
+            if manager.is_stopped(id) {
+                break None;
+            }
+            // ssh listens only once it's connected and the forward is set up
+            if !connected && crate::dev_servers::is_listening(local_port) {
+                connected = true;
+                backoff = Duration::from_secs(1);
+                manager.update(app, id, |entry| {
+                    entry.forward.status = ForwardStatus::Connected;
+                    entry.forward.error = None;
+                    entry.forward.connected_at = Some(crate::now_ms());
+                });
+            }
+            std::thread::sleep(POLL);
+        };
+        let Some(status) = status else {
+            return Ok(());
+        };
+
+        let error = {
+            let mut forwards = manager.forwards.lock().unwrap();
+            let Some(entry) = forwards.get_mut(&id) else {
+                return Ok(());
+            };
+            entry
+                .child
+                .take()
+                .and_then(|mut child| last_error(&mut child))
+        };
+        let permanent = error
+            .as_deref()
+            .is_some_and(|e| PERMANENT_ERRORS.iter().any(|p| e.contains(p)));
+        let still_running = manager.update(app, id, |entry| {
+            entry.forward.status = match permanent {
+                true => ForwardStatus::Failed,
+                false => ForwardStatus::Reconnecting,
+            };
+            entry.forward.error =
+                Some(error.unwrap_or_else(|| format!("ssh exited with {}", status)));
+            entry.forward.connected_at = None;
+        });
+        if !still_running || permanent {
+            return Ok(());
+        }
+
+        // Wait out the backoff, a little at a time so a stop isn't held up
+        let mut waited = Duration::ZERO;
+        while waited < backoff {
+            if manager.is_stopped(id) {
+                return Ok(());
+            }
+            std::thread::sleep(POLL);
+            waited += POLL;
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+        if !manager.update(app, id, |entry| entry.forward.reconnects += 1) {
+            return Ok(());
+        }
+    }
+}
+
+/// Forward `local_port` on this machine (the remote port when not given) to `remote_port` on
+/// `host`, a name from ~/.ssh/config or user@hostname. Progress arrives as port-forward-changed
+/// events.
+#[tauri::command]
+pub fn forward_port(
+    app: AppHandle,
+    state: tauri::State<'_, PortForwardManager>,
+    host: String,
+    remote_port: u16,
+    local_port: Option<u16>,
+) -> Result<PortForward, String> {
+    let host = host.trim().to_string();
+    crate::remote::check_host(&host)?;
+    let local_port = local_port.unwrap_or(remote_port);
+    if TcpListener::bind(("127.0.0.1", local_port)).is_err() {
+        return Err(format!("Port {} is already in use", local_port));
+    }
+    let ssh = crate::which::find("ssh")
+        .ok_or("ssh is not installed")?
+        .to_string_lossy()
+        .to_string();
+
+    let id = {
+        let mut next = state.next_id.lock().unwrap();
+        let id = *next;
+        *next += 1;
+        id
+    };
+    let forward = PortForward {
+        id,
+        host,
+        remote_port,
+        local_port,
+        status: ForwardStatus::Connecting,
+        error: None,
+        reconnects: 0,
+        connected_at: None,
+    };
+    state.forwards.lock().unwrap().insert(
+        id,
+        Entry {
+            forward: forward.clone(),
+            child: None,
+        },
+    );
+
+    let manager = state.inner().clone();
+    crate::crash::spawn("port_forward", format!("forward:{}", id), move || {
+        let result = supervise(&manager, &app, id, &ssh);
+        if let Err(message) = &result {
+            manager.update(&app, id, |entry| {
+                entry.forward.status = ForwardStatus::Failed;
+                entry.forward.error = Some(message.clone());
+            });
+        }
+        result
+    });
+    Ok(forward)
+}
+
+/// Every forward, including failed ones until they are stopped, oldest first
+#[tauri::command]
+pub fn list_forwards(state: tauri::State<'_, PortForwardManager>) -> Vec<PortForward> {
+    let mut forwards: Vec<PortForward> = state
+        .forwards
+        .lock()
+        .unwrap()
+        .values()
+        .map(|entry| entry.forward.clone())
+        .collect();
+    forwards.sort_by_key(|f| f.id);
+    forwards
+}
+
+#[tauri::command]
+pub fn stop_forward(app: AppHandle, state: tauri::State<'_, PortForwardManager>, id: u32) {
+    let Some(mut entry) = state.forwards.lock().unwrap().remove(&id) else {
+        return;
+    };
+    if let Some(mut child) = entry.child.take() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    entry.forward.status = ForwardStatus::Stopped;
+    entry.forward.connected_at = None;
+    let _ = app.emit("port-forward-changed", entry.forward);
+}
+
+/// End every forward's ssh process, when the app exits
+pub fn stop_all(app: &AppHandle) {
+    use tauri::Manager;
+    let forwards: Vec<Entry> = app
+        .state::<PortForwardManager>()
+        .forwards
+        .lock()
+        .unwrap()
+        .drain()
+        .map(|(_, entry)| entry)
+        .collect();
+    for mut child in forwards.into_iter().filter_map(|entry| entry.child) {
+        let _ = child.kill();
+    }
+}
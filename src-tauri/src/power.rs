@@ -0,0 +1,245 @@
+//! Power awareness, so an idle app on battery doesn't keep the fans spinning. In low-power mode,
+//! on by default while running on battery, watcher debounces and background polling slow down
+//! and background indexing waits. The mode persists in ~/.ade/power.json.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const POLL: Duration = Duration::from_secs(30);
+/// How much longer debounces and polling intervals get in low-power mode
+const SLOWDOWN: u32 = 4;
+/// How often paused work checks whether it may continue
+const RESUME_CHECK: Duration = Duration::from_secs(5);
+
+/// Read on hot paths, so kept outside the manager
+static LOW_POWER: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LowPowerMode {
+    /// On while running on battery
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerSource {
+    Ac,
+    Battery,
+    /// No battery information, e.g. a desktop without one
+    Unknown,
+}
+
+#[derive(Clone, PartialEq, serde::Serialize)]
+pub struct PowerStatus {
+    source: PowerSource,
+    battery_percent: Option<u8>,
+    mode: LowPowerMode,
+    /// Whether the app is throttling now
+    low_power: bool,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct PowerSettings {
+    low_power_mode: LowPowerMode,
+}
+
+#[derive(Clone)]
+pub struct PowerManager {
+    mode: Arc<Mutex<LowPowerMode>>,
+    /// Last status read, to notice changes
+    status: Arc<Mutex<Option<PowerStatus>>>,
+}
+
+fn settings_path() -> std::path::PathBuf {
+    crate::ade_path("power.json")
+}
+
+/// Whether the app is in low-power mode
+pub(crate) fn low_power() -> bool {
+    LOW_POWER.load(Ordering::Relaxed)
+}
+
+/// `interval` stretched while in low-power mode, for debounces and polling loops
+pub(crate) fn scaled(interval: Duration) -> Duration {
+    match low_power() {
+        true => interval * SLOWDOWN,
+        false => interval,
+    }
+}
+
+/// Block until low-power mode ends; background work calls this between steps
+pub(crate) fn wait_for_full_power() {
+    while low_power() {
+        std::thread::sleep(RESUME_CHECK);
+    }
+}
+
+/// From `pmset -g batt`, which says "Now drawing from 'Battery Power'" (or 'AC Power') and lists
+/// each battery with its charge, like "-InternalBattery-0 (id=…) 87%; discharging"
+fn mac_power() -> (PowerSource, Option<u8>) {
+    let Ok(output) = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+    else {
+        return (PowerSource::Unknown, None);
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let percent = stdout.lines().skip(1).find_map(|line| {
+        let before = line.split('%').next()?;
+        before.rsplit(char::is_whitespace).next()?.parse().ok()
+    });
+    let source = if stdout.contains("'Battery Power'") {
+        PowerSource::Battery
+    } else if stdout.contains("'AC Power'") && percent.is_some() {
+        PowerSource::Ac
+    } else {
+        PowerSource::Unknown
+    };
+    (source, percent)
+}
+
+/// From /sys/class/power_supply: a Mains supply that is online means AC
+fn linux_power() -> (PowerSource, Option<u8>) {
+    let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+        return (PowerSource::Unknown, None);
+    };
+    let read = |dir: &std::path::Path, file: &str| {
+        std::fs::read_to_string(dir.join(file))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+    let mut on_mains = false;
+    let mut battery: Option<(u8, bool)> = None;
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        match read(&dir, "type").as_str() {
+            "Mains" | "USB" => on_mains |= read(&dir, "online") == "1",
+            // Skip peripherals like wireless mice, which report their own batteries
+            "Battery" if read(&dir, "scope") != "Device" => {
+                let percent = read(&dir, "capacity").parse().unwrap_or(0);
+                let discharging = read(&dir, "status") == "Discharging";
+                battery.get_or_insert((percent, discharging));
+            }
+            _ => {}
+        }
+    }
+    match battery {
+        Some((percent, discharging)) if discharging || !on_mains => {
+            (PowerSource::Battery, Some(percent))
+        }
+        Some((percent, _)) => (PowerSource::Ac, Some(percent)),
+        None => (PowerSource::Unknown, None),
+    }
+}
+
+fn read_power() -> (PowerSource, Option<u8>) {
+    if cfg!(target_os = "macos") {
+        mac_power()
+    } else if cfg!(target_os = "linux") {
+        linux_power()
+    } else {
+        (PowerSource::Unknown, None)
+    }
+}
+
+impl PowerManager {
+    pub fn new() -> Self {
+        let settings: PowerSettings = std::fs::read_to_string(settings_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            mode: Arc::new(Mutex::new(settings.low_power_mode)),
+            status: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Read the power source and apply the mode; the status, and whether it changed since the last
+    /// read
+    fn refresh(&self) -> (PowerStatus, bool) {
+        let (source, battery_percent) = read_power();
+        let mode = *self.mode.lock().unwrap();
+        let low_power = match mode {
+            LowPowerMode::Auto => source == PowerSource::Battery,
+            LowPowerMode::On => true,
+            LowPowerMode::Off => false,
+        };
+        LOW_POWER.store(low_power, Ordering::Relaxed);
+        let status = PowerStatus {
+            source,
+            battery_percent,
+            mode,
+            low_power,
+        };
+        let mut last = self.status.lock().unwrap();
+        let changed = last.as_ref() != Some(&status);
+        *last = Some(status.clone());
+        (status, changed)
+    }
+}
+
+fn notify(app: &AppHandle, status: &PowerStatus) {
+    let _ = app.emit("power-status-changed", status.clone());
+}
+
+/// Read the power source now and then every 30 seconds, emitting `power-status-changed` when
+/// it or the low-power state changes
+pub fn start(app: &tauri::App) {
+    use tauri::Manager;
+    let manager = app.state::<PowerManager>().inner().clone();
+    manager.refresh();
+    let app = app.handle().clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL);
+        let (status, changed) = manager.refresh();
+        if changed {
+            notify(&app, &status);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_power_status(
+    state: tauri::State<'_, PowerManager>,
+) -> Result<PowerStatus, String> {
+    let manager = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || manager.refresh().0)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Choose when low-power mode is on: auto (on battery), on or off
+#[tauri::command]
+pub async fn set_low_power_mode(
+    app: AppHandle,
+    state: tauri::State<'_, PowerManager>,
+    mode: LowPowerMode,
+) -> Result<PowerStatus, String> {
+    let path = settings_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&PowerSettings {
+        low_power_mode: mode,
+    })
+    .map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to save power settings: {}", e))?;
+    *state.mode.lock().unwrap() = mode;
+
+    let manager = state.inner().clone();
+    let (status, changed) = tauri::async_runtime::spawn_blocking(move || manager.refresh())
+        .await
+        .map_err(|e| e.to_string())?;
+    if changed {
+        notify(&app, &status);
+    }
+    Ok(status)
+}
@@ -0,0 +1,568 @@
+//! A loopback HTTP proxy in front of a dev server for the preview pane. It strips the headers
+//! that stop a page from being framed (X-Frame-Options, CSP frame-ancestors), tunnels WebSocket
+//! upgrades so HMR keeps working, and can inject a script that reloads the page when files in
+//! the project change. One request per connection; responses end by closing it.
+
+use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Paths under this prefix are answered by the proxy itself
+const OWN_PREFIX: &str = "/__ade/";
+const RELOAD_SCRIPT: &str = r#"(() => {
+  const events = new EventSource("/__ade/events");
+  events.onmessage = (event) => {
+    if (event.data === "reload") location.reload();
+  };
+})();
+"#;
+const MAX_HEAD: usize = 64 * 1024;
+/// How long a client may leave a request unfinished
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a reload event may take to reach a tab before the tab is dropped
+const EVENT_WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+/// HTML pages larger than this are passed through without the reload script
+const MAX_INJECT: u64 = 10 * 1024 * 1024;
+/// File changes closer together than this cause one reload
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(150);
+/// Never worth reloading for, on top of the project's watcher.ignore
+const IGNORED_DIRS: [&str; 3] = ["node_modules", ".git", "target"];
+/// Hop-by-hop headers, which apply to one connection and aren't forwarded
+const HOP_BY_HOP: [&str; 8] = [
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+#[derive(Default, serde::Deserialize)]
+pub struct PreviewProxyOptions {
+    /// Inject a script into HTML pages that reloads them when files under this directory change
+    watch_dir: Option<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct PreviewProxyInfo {
+    id: u32,
+    /// Where the proxy listens
+    port: u16,
+    /// The dev server it forwards to
+    target_port: u16,
+    url: String,
+}
+
+/// What the connection threads of one proxy share
+struct Shared {
+    port: u16,
+    target_port: u16,
+    inject: bool,
+    client: reqwest::blocking::Client,
+    /// Open /__ade/events streams of the pages to reload
+    reload_clients: Mutex<Vec<TcpStream>>,
+}
+
+impl Shared {
+    fn reload(&self) {
+        self.reload_clients
+            .lock()
+            .unwrap()
+            .retain_mut(|stream| stream.write_all(b"data: reload\n\n").is_ok());
+    }
+
+    /// Whether a request's Host names this proxy. Any other name resolving to 127.0.0.1 is a
+    /// page from elsewhere reaching the dev server by DNS rebinding.
+    fn is_own_host(&self, host: Option<&str>) -> bool {
+        host.is_some_and(|host| {
+            ["localhost", "127.0.0.1"]
+                .iter()
+                .any(|name| host.eq_ignore_ascii_case(&format!("{}:{}", name, self.port)))
+        })
+    }
+
+    /// Point an origin or URL of the proxy at the dev server, or the other way around
+    fn rewrite_origin(&self, value: &str, to_target: bool) -> String {
+        let (from, to) = match to_target {
+            true => (self.port, self.target_port),
+            false => (self.target_port, self.port),
+        };
+        ["localhost", "127.0.0.1"]
+            .iter()
+            .fold(value.to_string(), |value, host| {
+                value.replace(
+                    &format!("//{}:{}", host, from),
+                    &format!("//{}:{}", host, to),
+                )
+            })
+    }
+}
+
+struct PreviewProxy {
+    info: PreviewProxyInfo,
+    shared: Arc<Shared>,
+    stop: Arc<AtomicBool>,
+    _watcher: Option<RecommendedWatcher>,
+}
+
+#[derive(Clone)]
+pub struct PreviewProxyManager {
+    proxies: Arc<Mutex<HashMap<u32, PreviewProxy>>>,
+    next_id: Arc<Mutex<u32>>,
+}
+
+impl PreviewProxyManager {
+    pub fn new() -> Self {
+        Self {
+            proxies: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+}
+
+pub(crate) struct Request {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) headers: Vec<(String, String)>,
+}
+
+impl Request {
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+pub(crate) fn read_head(reader: &mut BufReader<TcpStream>) -> std::io::Result<Option<Request>> {
+    let mut request_line = String::new();
+    if reader
+        .by_ref()
+        .take(MAX_HEAD as u64)
+        .read_line(&mut request_line)?
+        == 0
+    {
+        return Ok(None);
+    }
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Ok(None);
+    };
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        headers: Vec::new(),
+    };
+    let mut size = request_line.len();
+    loop {
+        let mut line = String::new();
+        // Bounded, so one endless line can't grow past MAX_HEAD either
+        let left = MAX_HEAD.saturating_sub(size) as u64 + 1;
+        let n = reader.by_ref().take(left).read_line(&mut line)?;
+        size += n;
+        if n == 0 || line.trim().is_empty() || size > MAX_HEAD {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            request
+                .headers
+                .push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    Ok(Some(request))
+}
+
+pub(crate) fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+}
+
+/// The CSP without its frame-ancestors directive; None when nothing else is left
+fn without_frame_ancestors(policy: &str) -> Option<String> {
+    let kept: Vec<&str> = policy
+        .split(';')
+        .map(str::trim)
+        .filter(|directive| {
+            !directive.is_empty()
+                && !directive
+                    .to_ascii_lowercase()
+                    .starts_with("frame-ancestors")
+        })
+        .collect();
+    Some(kept.join("; ")).filter(|policy| !policy.is_empty())
+}
+
+/// The page with the reload script added at the end of its body
+fn inject_script(html: &str) -> String {
+    let tag = format!("<script src=\"{}reload.js\"></script>", OWN_PREFIX);
+    match html.to_ascii_lowercase().rfind("</body>") {
+        Some(at) => format!("{}{}{}", &html[..at], tag, &html[at..]),
+        None => format!("{}{}", html, tag),
+    }
+}
+
+/// Hand a WebSocket (or other) upgrade to the dev server and relay bytes both ways until
+/// either side hangs up
+fn tunnel(
+    shared: &Shared,
+    request: &Request,
+    mut reader: BufReader<TcpStream>,
+) -> std::io::Result<()> {
+    let target = ("localhost", shared.target_port)
+        .to_socket_addrs()?
+        .find_map(|addr| TcpStream::connect_timeout(&addr, Duration::from_secs(5)).ok());
+    let Some(mut upstream) = target else {
+        respond(
+            reader.get_mut(),
+            "502 Bad Gateway",
+            "text/plain",
+            "Dev server is not responding",
+        );
+        return Ok(());
+    };
+    let mut head = format!("{} {} HTTP/1.1\r\n", request.method, request.path);
+    for (name, value) in &request.headers {
+        let value = match name.to_ascii_lowercase().as_str() {
+            "host" => format!("localhost:{}", shared.target_port),
+            "origin" => shared.rewrite_origin(value, true),
+            _ => value.clone(),
+        };
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    upstream.write_all(head.as_bytes())?;
+    // Anything the client sent right after the head
+    upstream.write_all(reader.buffer())?;
+
+    let mut client = reader.into_inner();
+    let mut client_out = client.try_clone()?;
+    let mut upstream_in = upstream.try_clone()?;
+    let downstream = std::thread::spawn(move || {
+        let _ = std::io::copy(&mut upstream_in, &mut client_out);
+        let _ = client_out.shutdown(Shutdown::Both);
+    });
+    let _ = std::io::copy(&mut client, &mut upstream);
+    let _ = upstream.shutdown(Shutdown::Both);
+    let _ = downstream.join();
+    Ok(())
+}
+
+fn forward(
+    shared: &Shared,
+    request: &Request,
+    mut reader: BufReader<TcpStream>,
+) -> std::io::Result<()> {
+    let method =
+        reqwest::Method::from_bytes(request.method.as_bytes()).unwrap_or(reqwest::Method::GET);
+    let url = format!("http://localhost:{}{}", shared.target_port, request.path);
+    let mut builder = shared.client.request(method, &url);
+    for (name, value) in &request.headers {
+        let lower = name.to_ascii_lowercase();
+        let value = match lower.as_str() {
+            _ if HOP_BY_HOP.contains(&lower.as_str()) => continue,
+            "host" | "content-length" => continue,
+            // Keep HTML uncompressed so the script can go in
+            "accept-encoding" if shared.inject => continue,
+            "origin" | "referer" => shared.rewrite_origin(value, true),
+            _ => value.clone(),
+        };
+        builder = builder.header(name.as_str(), value);
+    }
+    if shared.inject {
+        builder = builder.header("Accept-Encoding", "identity");
+    }
+    if let Some(length) = request.header("content-length") {
+        let length: u64 = length.parse().unwrap_or(0);
+        let body = reader.get_ref().try_clone()?;
+        // What the reader buffered past the head comes first
+        let buffered = reader.buffer().to_vec();
+        let body = std::io::Cursor::new(buffered).chain(body).take(length);
+        builder = builder.body(reqwest::blocking::Body::sized(body, length));
+    } else if request.header("transfer-encoding").is_some() {
+        respond(
+            reader.get_mut(),
+            "411 Length Required",
+            "text/plain",
+            "Chunked request bodies aren't supported by the preview proxy",
+        );
+        return Ok(());
+    }
+
+    let stream = reader.get_mut();
+    let mut response = match builder.send() {
+        Ok(response) => response,
+        Err(_) => {
+            // Try again shortly, for a dev server that is restarting
+            let page = format!(
+                "<!doctype html><meta http-equiv=\"refresh\" content=\"2\"><p>Waiting for the dev server on port {}…</p>",
+                shared.target_port
+            );
+            respond(stream, "502 Bad Gateway", "text/html; charset=utf-8", &page);
+            return Ok(());
+        }
+    };
+
+    let status = response.status();
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.to_ascii_lowercase().starts_with("text/html"));
+    let encoded = response
+        .headers()
+        .get(reqwest::header::CONTENT_ENCODING)
+        .is_some_and(|v| v != "identity");
+    let inject = shared.inject
+        && is_html
+        && !encoded
+        && request.method != "HEAD"
+        && response
+            .content_length()
+            .is_none_or(|len| len <= MAX_INJECT);
+
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\n",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("")
+    );
+    for (name, value) in response.headers() {
+        let lower = name.as_str();
+        if HOP_BY_HOP.contains(&lower) || lower == "x-frame-options" {
+            continue;
+        }
+        if inject && lower == "content-length" {
+            continue;
+        }
+        let value = String::from_utf8_lossy(value.as_bytes()).to_string();
+        let value = match lower {
+            "content-security-policy" | "content-security-policy-report-only" => {
+                match without_frame_ancestors(&value) {
+                    Some(policy) => policy,
+                    None => continue,
+                }
+            }
+            "location" => shared.rewrite_origin(&value, false),
+            _ => value,
+        };
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("Connection: close\r\n");
+
+    if inject {
+        let mut body = Vec::new();
+        response
+            .read_to_end(&mut body)
+            .map_err(std::io::Error::other)?;
+        let page = inject_script(&String::from_utf8_lossy(&body));
+        head.push_str(&format!("Content-Length: {}\r\n\r\n", page.len()));
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(page.as_bytes())?;
+    } else {
+        head.push_str("\r\n");
+        stream.write_all(head.as_bytes())?;
+        // Without a Content-Length the body ends when the connection closes
+        std::io::copy(&mut response, stream)?;
+    }
+    stream.flush()
+}
+
+fn handle_connection(shared: &Shared, stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let Some(request) = read_head(&mut reader)? else {
+        return Ok(());
+    };
+    if !shared.is_own_host(request.header("host")) {
+        respond(
+            reader.get_mut(),
+            "421 Misdirected Request",
+            "text/plain",
+            "The preview proxy only answers to localhost",
+        );
+        return Ok(());
+    }
+    match request.path.split('?').next().unwrap_or("") {
+        "/__ade/reload.js" => {
+            respond(
+                reader.get_mut(),
+                "200 OK",
+                "application/javascript",
+                RELOAD_SCRIPT,
+            );
+            Ok(())
+        }
+        "/__ade/events" => {
+            let mut stream = reader.into_inner();
+            // Written to while reload_clients is held, so one stalled tab can't hold up the rest
+            stream.set_write_timeout(Some(EVENT_WRITE_TIMEOUT))?;
+            stream.write_all(
+                b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n: connected\n\n",
+            )?;
+            shared.reload_clients.lock().unwrap().push(stream);
+            Ok(())
+        }
+        path if path.starts_with(OWN_PREFIX) => {
+            respond(reader.get_mut(), "404 Not Found", "text/plain", "Not found");
+            Ok(())
+        }
+        _ if request.header("upgrade").is_some() => {
+            // A websocket, such as a dev server's hot reload, can sit idle for long
+            reader.get_ref().set_read_timeout(None)?;
+            tunnel(shared, &request, reader)
+        }
+        _ => forward(shared, &request, reader),
+    }
+}
+
+/// Watch `dir` and reload the proxied pages when something in it changes
+fn watch(dir: &str, shared: Arc<Shared>) -> Result<RecommendedWatcher, String> {
+    let root = PathBuf::from(dir);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", dir));
+    }
+    let settings_root = crate::project_settings::project_root_for(dir);
+    let ignore: Vec<glob::Pattern> = crate::project_settings::load(settings_root.as_deref())
+        .watcher
+        .ignore
+        .iter()
+        .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+        .collect();
+    let relevant = move |path: &Path| {
+        let relative = path.strip_prefix(&root).unwrap_or(path);
+        let ignored_dir = relative
+            .components()
+            .any(|c| IGNORED_DIRS.iter().any(|dir| c.as_os_str() == *dir));
+        !ignored_dir && !ignore.iter().any(|pattern| pattern.matches_path(relative))
+    };
+
+    let (sender, receiver) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = RecommendedWatcher::new(sender, Config::default())
+        .map_err(|e| format!("Failed to create watcher: {}", e))?;
+    watcher
+        .watch(Path::new(dir), RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", dir, e))?;
+
+    // Ends once the watcher, and with it the sender, is dropped
+    std::thread::spawn(move || {
+        while let Ok(event) = receiver.recv() {
+            let changed = |event: &notify::Result<notify::Event>| {
+                event.as_ref().is_ok_and(|event| {
+                    !event.kind.is_access() && event.paths.iter().any(|p| relevant(p))
+                })
+            };
+            if !changed(&event) {
+                continue;
+            }
+            // Let a save that touches several files settle
+            while receiver.recv_timeout(RELOAD_DEBOUNCE).is_ok() {}
+            shared.reload();
+        }
+    });
+    Ok(watcher)
+}
+
+/// Start a proxy for the dev server on `port` and return where it listens. Starting one for a
+/// port that already has a proxy returns that one.
+#[tauri::command]
+pub fn start_preview_proxy(
+    state: tauri::State<'_, PreviewProxyManager>,
+    port: u16,
+    options: Option<PreviewProxyOptions>,
+) -> Result<PreviewProxyInfo, String> {
+    let options = options.unwrap_or_default();
+    let mut proxies = state.proxies.lock().unwrap();
+    if let Some(proxy) = proxies.values().find(|p| p.info.target_port == port) {
+        return Ok(proxy.info.clone());
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", 0))
+        .map_err(|e| format!("Failed to start preview proxy: {}", e))?;
+    let proxy_port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to start preview proxy: {}", e))?
+        .port();
+    let client = reqwest::blocking::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .timeout(None)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let shared = Arc::new(Shared {
+        port: proxy_port,
+        target_port: port,
+        inject: options.watch_dir.is_some(),
+        client,
+        reload_clients: Mutex::new(Vec::new()),
+    });
+    let watcher = options
+        .watch_dir
+        .as_deref()
+        .map(|dir| watch(dir, shared.clone()))
+        .transpose()?;
+
+    let id = {
+        let mut next = state.next_id.lock().unwrap();
+        let id = *next;
+        *next += 1;
+        id
+    };
+    let stop = Arc::new(AtomicBool::new(false));
+    let info = PreviewProxyInfo {
+        id,
+        port: proxy_port,
+        target_port: port,
+        url: format!("http://localhost:{}/", proxy_port),
+    };
+    proxies.insert(
+        id,
+        PreviewProxy {
+            info: info.clone(),
+            shared: shared.clone(),
+            stop: stop.clone(),
+            _watcher: watcher,
+        },
+    );
+
+    crate::crash::spawn("preview_proxy", format!("preview:{}", id), move || {
+        for stream in listener.incoming() {
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+            let Ok(stream) = stream else { continue };
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                let _ = handle_connection(&shared, stream);
+            });
+        }
+        Ok(())
+    });
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn stop_preview_proxy(state: tauri::State<'_, PreviewProxyManager>, id: u32) {
+    let Some(proxy) = state.proxies.lock().unwrap().remove(&id) else {
+        return;
+    };
+    proxy.stop.store(true, Ordering::Relaxed);
+    // Wake the accept loop so it sees the flag
+    let _ = TcpStream::connect(("127.0.0.1", proxy.info.port));
+    for stream in proxy.shared.reload_clients.lock().unwrap().drain(..) {
+        let _ = stream.shutdown(Shutdown::Both);
+    }
+}
@@ -0,0 +1,385 @@
+//! Finding and stopping processes, mainly the dev servers and agents the app started itself.
+//! Processes the app didn't start can be killed too, and ports freed, but only with a
+//! confirmation token from a first kill_process or free_port call (or request_confirmation), so
+//! a stray click can't take down something unrelated.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const SIGNALS: [&str; 4] = ["TERM", "INT", "HUP", "KILL"];
+/// How long processes get to release a port after SIGTERM before they're killed
+const FREE_PORT_GRACE: Duration = Duration::from_secs(3);
+
+#[derive(Clone, serde::Serialize)]
+pub struct ProcessInfo {
+    pid: u32,
+    ppid: u32,
+    user: String,
+    /// Executable name
+    name: String,
+    /// Full command line
+    command: String,
+    cpu_percent: f32,
+    memory_bytes: u64,
+    /// Seconds since it started
+    elapsed_secs: u64,
+    /// A descendant of the app: a terminal's shell and what runs in it, a task, an agent
+    spawned_by_app: bool,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct ListProcessesOptions {
+    /// Matches the pid, or part of the command line (case-insensitive)
+    filter: Option<String>,
+    /// Only processes the app started, directly or not
+    #[serde(default)]
+    spawned_by_app: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(tag = "type")]
+pub enum KillOutcome {
+    #[serde(rename = "killed")]
+    Killed { pid: u32, signal: String },
+    /// The app didn't start this process: call again with `token` to go ahead
+    #[serde(rename = "confirm")]
+    Confirm {
+        pid: u32,
+        command: String,
+        user: String,
+        token: String,
+    },
+}
+
+#[derive(serde::Serialize)]
+pub struct PortUsage {
+    port: u16,
+    /// Something accepts connections on it, even if its owner isn't visible to this user
+    in_use: bool,
+    /// Processes listening on it
+    processes: Vec<ProcessInfo>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(tag = "type")]
+pub enum FreePortOutcome {
+    #[serde(rename = "freed")]
+    Freed { port: u16, pids: Vec<u32> },
+    /// Call again with `token` to stop these processes
+    #[serde(rename = "confirm")]
+    Confirm {
+        port: u16,
+        processes: Vec<ProcessInfo>,
+        token: String,
+    },
+}
+
+/// `[[dd-]hh:]mm:ss` from `ps -o etime` in seconds
+fn parse_etime(etime: &str) -> u64 {
+    let (days, clock) = match etime.split_once('-') {
+        Some((days, clock)) => (days.parse().unwrap_or(0), clock),
+        None => (0, etime),
+    };
+    let clock = clock.split(':').fold(0u64, |secs, part| {
+        secs * 60 + part.parse::<u64>().unwrap_or(0)
+    });
+    days * 86_400 + clock
+}
+
+/// Every process, by pid
+fn snapshot() -> Result<Vec<ProcessInfo>, String> {
+    if cfg!(windows) {
+        return Err("Listing processes isn't supported on Windows".to_string());
+    }
+    let output = std::process::Command::new("ps")
+        .args(["-axww", "-o", "pid=,ppid=,user=,pcpu=,rss=,etime=,args="])
+        .output()
+        .map_err(|e| format!("Failed to list processes: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut processes: Vec<ProcessInfo> = stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            let user = fields.next()?.to_string();
+            let cpu_percent = fields.next()?.parse().unwrap_or(0.0);
+            let rss_kb: u64 = fields.next()?.parse().unwrap_or(0);
+            let elapsed_secs = parse_etime(fields.next()?);
+            let command = fields.collect::<Vec<_>>().join(" ");
+            let name = command
+                .split_whitespace()
+                .next()
+                .map(|program| program.rsplit('/').next().unwrap_or(program))
+                .unwrap_or_default()
+                .to_string();
+            Some(ProcessInfo {
+                pid,
+                ppid,
+                user,
+                name,
+                command,
+                cpu_percent,
+                memory_bytes: rss_kb * 1024,
+                elapsed_secs,
+                spawned_by_app: false,
+            })
+        })
+        .collect();
+
+    let parents: HashMap<u32, u32> = processes.iter().map(|p| (p.pid, p.ppid)).collect();
+    let app = std::process::id();
+    for process in &mut processes {
+        let mut pid = process.ppid;
+        // Bounded, in case a pid was reused between ps reading two entries
+        for _ in 0..64 {
+            if pid == app {
+                process.spawned_by_app = true;
+                break;
+            }
+            match parents.get(&pid) {
+                Some(&parent) if parent != pid && pid > 1 => pid = parent,
+                _ => break,
+            }
+        }
+    }
+    Ok(processes)
+}
+
+pub(crate) fn send_signal(pid: u32, signal: &str) -> Result<(), String> {
+    let output = std::process::Command::new("kill")
+        .args([&format!("-{}", signal), &pid.to_string()])
+        .output()
+        .map_err(|e| format!("Failed to run kill: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to signal process {}: {}",
+            pid,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Pids listening on TCP `port`, from lsof (only those of processes this user can see)
+fn listening_pids(port: u16) -> Vec<u32> {
+    let output = std::process::Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-Fp"])
+        .output();
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+    let mut pids: Vec<u32> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_prefix('p')?.parse().ok())
+        .collect();
+    pids.dedup();
+    pids
+}
+
+fn port_usage(port: u16) -> Result<PortUsage, String> {
+    let pids = listening_pids(port);
+    let processes: Vec<ProcessInfo> = match pids.is_empty() {
+        true => Vec::new(),
+        false => snapshot()?
+            .into_iter()
+            .filter(|p| pids.contains(&p.pid))
+            .collect(),
+    };
+    Ok(PortUsage {
+        port,
+        in_use: !processes.is_empty() || crate::dev_servers::is_listening(port),
+        processes,
+    })
+}
+
+/// Running processes, the app's own descendants first and then by CPU use
+#[tauri::command]
+pub async fn list_processes(
+    options: Option<ListProcessesOptions>,
+) -> Result<Vec<ProcessInfo>, String> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        let filter = options.filter.as_deref().map(str::to_lowercase);
+        let app = std::process::id();
+        let mut processes: Vec<ProcessInfo> = snapshot()?
+            .into_iter()
+            // Leave out the app and the ps that just ran
+            .filter(|p| p.pid != app && !(p.ppid == app && p.name == "ps"))
+            .filter(|p| p.spawned_by_app || !options.spawned_by_app)
+            .filter(|p| {
+                filter.as_ref().is_none_or(|filter| {
+                    p.pid.to_string() == *filter || p.command.to_lowercase().contains(filter)
+                })
+            })
+            .collect();
+        processes.sort_by(|a, b| {
+            b.spawned_by_app
+                .cmp(&a.spawned_by_app)
+                .then(b.cpu_percent.total_cmp(&a.cpu_percent))
+        });
+        Ok(processes)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// TERM when not given; INT, HUP and KILL with or without the SIG prefix
+pub(crate) fn parse_signal(signal: Option<String>) -> Result<String, String> {
+    let signal = signal.unwrap_or_else(|| "TERM".to_string()).to_uppercase();
+    let signal = signal.trim_start_matches("SIG").to_string();
+    if !SIGNALS.contains(&signal.as_str()) {
+        return Err(format!("Unsupported signal: {}", signal));
+    }
+    Ok(signal)
+}
+
+/// The command line of `pid`, a process that may be signalled
+pub(crate) fn signal_target(pid: u32) -> Result<String, String> {
+    if pid <= 1 || pid == std::process::id() {
+        return Err(format!("Refusing to signal process {}", pid));
+    }
+    snapshot()?
+        .into_iter()
+        .find(|p| p.pid == pid)
+        .map(|p| p.command)
+        .ok_or_else(|| format!("No process {}", pid))
+}
+
+/// Send `signal` (TERM by default, or INT, HUP, KILL) to `pid`. Processes the app started are
+/// signalled right away; for any other the first call returns a `confirm` outcome, and the
+/// signal is only sent when called again with its token within a minute. The token works with
+/// execute_confirmed too.
+#[tauri::command]
+pub async fn kill_process(
+    confirmations: tauri::State<'_, crate::confirm::ConfirmationManager>,
+    pid: u32,
+    signal: Option<String>,
+    token: Option<String>,
+) -> Result<KillOutcome, String> {
+    let signal = parse_signal(signal)?;
+    if pid <= 1 || pid == std::process::id() {
+        return Err(format!("Refusing to signal process {}", pid));
+    }
+    let confirmations = confirmations.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let process = snapshot()?
+            .into_iter()
+            .find(|p| p.pid == pid)
+            .ok_or_else(|| format!("No process {}", pid))?;
+        if !process.spawned_by_app {
+            let op = crate::confirm::DestructiveOp::KillProcess {
+                pid,
+                signal: Some(signal.clone()),
+            };
+            let Some(token) = token else {
+                return Ok(KillOutcome::Confirm {
+                    pid,
+                    command: process.command,
+                    user: process.user,
+                    token: confirmations.request(op)?.token,
+                });
+            };
+            if confirmations.redeem(&token)? != op {
+                return Err(format!("That confirmation isn't for signalling {}", pid));
+            }
+        }
+        send_signal(pid, &signal)?;
+        Ok(KillOutcome::Killed { pid, signal })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Which processes listen on `port`
+#[tauri::command]
+pub async fn get_port_usage(port: u16) -> Result<PortUsage, String> {
+    tauri::async_runtime::spawn_blocking(move || port_usage(port))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// The processes listening on `port`, which free_port would stop
+fn port_listeners(port: u16) -> Result<Vec<ProcessInfo>, String> {
+    let usage = port_usage(port)?;
+    if usage.processes.is_empty() && usage.in_use {
+        return Err(format!(
+            "Port {} is used by a process this user can't see or stop",
+            port
+        ));
+    }
+    if usage.processes.iter().any(|p| p.pid == std::process::id()) {
+        return Err(format!("Port {} is used by this app", port));
+    }
+    Ok(usage.processes)
+}
+
+/// What freeing `port` would do, for the user, and the processes it acts on as of now
+pub(crate) fn free_port_target(port: u16) -> Result<(String, String), String> {
+    let processes = port_listeners(port)?;
+    if processes.is_empty() {
+        return Err(format!("Nothing listens on port {}", port));
+    }
+    let names: Vec<String> = processes
+        .iter()
+        .map(|p| format!("{} ({})", p.name, p.pid))
+        .collect();
+    let pids: Vec<u32> = processes.iter().map(|p| p.pid).collect();
+    Ok((
+        format!("Stop {} listening on port {}", names.join(", "), port),
+        format!("{:?}", pids),
+    ))
+}
+
+/// SIGTERM to what listens on `port`, then SIGKILL to those still there after a few seconds.
+/// Returns the pids stopped.
+pub(crate) fn free(port: u16) -> Result<Vec<u32>, String> {
+    let pids: Vec<u32> = port_listeners(port)?.iter().map(|p| p.pid).collect();
+    for pid in &pids {
+        send_signal(*pid, "TERM")?;
+    }
+    let deadline = Instant::now() + FREE_PORT_GRACE;
+    while Instant::now() < deadline && !listening_pids(port).is_empty() {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    for pid in listening_pids(port).iter().filter(|pid| pids.contains(pid)) {
+        let _ = send_signal(*pid, "KILL");
+    }
+    Ok(pids)
+}
+
+/// Stop whatever listens on `port`. The first call returns a `confirm` outcome listing the
+/// processes; called again with its token within a minute, they get SIGTERM, then SIGKILL if
+/// the port is still taken after a few seconds. The token works with execute_confirmed too.
+#[tauri::command]
+pub async fn free_port(
+    confirmations: tauri::State<'_, crate::confirm::ConfirmationManager>,
+    port: u16,
+    token: Option<String>,
+) -> Result<FreePortOutcome, String> {
+    let confirmations = confirmations.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let processes = port_listeners(port)?;
+        if processes.is_empty() {
+            return Ok(FreePortOutcome::Freed {
+                port,
+                pids: Vec::new(),
+            });
+        }
+        let op = crate::confirm::DestructiveOp::FreePort { port };
+        let Some(token) = token else {
+            return Ok(FreePortOutcome::Confirm {
+                port,
+                processes,
+                token: confirmations.request(op)?.token,
+            });
+        };
+        if confirmations.redeem(&token)? != op {
+            return Err(format!("That confirmation isn't for freeing port {}", port));
+        }
+        let pids = free(port)?;
+        Ok(FreePortOutcome::Freed { port, pids })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
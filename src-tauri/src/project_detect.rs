@@ -0,0 +1,49 @@
+use crate::claude_md::{DetectedProject, ProjectCommand};
+use std::path::PathBuf;
+
+#[derive(serde::Serialize)]
+pub struct ProjectDetection {
+    /// Distinct project kinds, root manifests first
+    types: Vec<String>,
+    /// Package manager of the root project, else of the first nested one
+    package_manager: Option<String>,
+    languages: Vec<String>,
+    projects: Vec<DetectedProject>,
+    /// Suggested build, test, lint, format, dev and run commands
+    commands: Vec<ProjectCommand>,
+}
+
+/// Inspect the manifests at `root` and one level down (package.json, Cargo.toml,
+/// pyproject.toml, go.mod and others) for project types, package managers and the commands
+/// that build, test and run them
+#[tauri::command]
+pub async fn detect_project(root: String) -> Result<ProjectDetection, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = PathBuf::from(crate::git::resolve_repo(&root));
+        if !root.is_dir() {
+            return Err(format!("Not a directory: {}", root.display()));
+        }
+        let (profile, _) = crate::claude_md::profile_project(&root);
+        let mut types: Vec<String> = Vec::new();
+        for project in &profile.projects {
+            if !types.contains(&project.kind) {
+                types.push(project.kind.clone());
+            }
+        }
+        let package_manager = profile
+            .projects
+            .iter()
+            .find(|p| p.dir == ".")
+            .or(profile.projects.first())
+            .map(|p| p.package_manager.clone());
+        Ok(ProjectDetection {
+            types,
+            package_manager,
+            languages: profile.languages,
+            projects: profile.projects,
+            commands: profile.commands,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
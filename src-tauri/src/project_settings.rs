@@ -0,0 +1,359 @@
+use crate::claude_config::{expect_map_of, expect_strings, merge_patch, read_json, write_json};
+use crate::pty::PtyManager;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+/// The settings keys this app reads, parsed from the merged user and project files. shell,
+/// editor and env_sets come from the user's file only.
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+pub(crate) struct ProjectSettings {
+    /// Shell for new terminals instead of $SHELL
+    pub(crate) shell: Option<String>,
+    /// Editor open_in_editor uses when none is asked for, like "vscode" or "nvim"
+    pub(crate) editor: Option<String>,
+    /// Named groups of environment variables
+    pub(crate) env_sets: HashMap<String, HashMap<String, String>>,
+    /// Env set applied to new terminals and tasks that don't pick their own
+    pub(crate) default_env_set: Option<String>,
+    /// Env set per task id, applied when the task runs
+    pub(crate) task_env: HashMap<String, String>,
+    pub(crate) watcher: WatcherSettings,
+    pub(crate) agent: AgentDefaults,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+pub(crate) struct WatcherSettings {
+    /// Globs, relative to the watched directory, whose changes are not reported
+    pub(crate) ignore: Vec<String>,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+pub(crate) struct AgentDefaults {
+    /// Agent CLI preselected for new runs
+    pub(crate) tool: Option<String>,
+    /// Model per agent CLI, used when a run doesn't name one
+    pub(crate) models: HashMap<String, String>,
+}
+
+impl ProjectSettings {
+    /// Variables of the default env set, if one is chosen
+    pub(crate) fn default_env(&self) -> Option<&HashMap<String, String>> {
+        self.env_sets.get(self.default_env_set.as_deref()?)
+    }
+
+    /// Variables of the named env set
+    pub(crate) fn env_set(&self, name: &str) -> Result<&HashMap<String, String>, String> {
+        self.env_sets
+            .get(name)
+            .ok_or_else(|| format!("Unknown env set: {}", name))
+    }
+
+    /// Variables for a terminal or task: the env set it asked for, else the default
+    pub(crate) fn env_for(
+        &self,
+        name: Option<&str>,
+    ) -> Result<Option<&HashMap<String, String>>, String> {
+        match name {
+            Some(name) => self.env_set(name).map(Some),
+            None => Ok(self.default_env()),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct ProjectSettingsLayers {
+    /// User settings with the project's applied on top, less the keys only the user may set
+    effective: Value,
+    user: Value,
+    /// None when no project root was given
+    project: Option<Value>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct SettingsChanged {
+    /// None for the user-global settings, which affect every project
+    root: Option<String>,
+}
+
+/// Keys only the user's settings may set: they pick what runs and with which environment, and
+/// a project's file comes with whatever was cloned. A project can still choose among the
+/// user's env sets.
+const USER_ONLY: &[&str] = &["shell", "editor", "env_sets"];
+
+fn user_path() -> PathBuf {
+    crate::ade_path("settings.json")
+}
+
+fn project_path(root: &str) -> PathBuf {
+    PathBuf::from(crate::git::resolve_repo(root))
+        .join(".ade")
+        .join("settings.json")
+}
+
+/// The nearest directory at or above `path` with its own .ade/settings.json. The home
+/// directory is skipped: its .ade holds the user-global settings.
+pub(crate) fn project_root_for(path: &str) -> Option<String> {
+    let home = PathBuf::from(crate::get_home_dir());
+    Path::new(&crate::git::resolve_repo(path))
+        .ancestors()
+        .filter(|dir| *dir != home)
+        .find(|dir| dir.join(".ade").join("settings.json").is_file())
+        .map(|dir| dir.to_string_lossy().to_string())
+}
+
+fn merged(user: &Value, project: Option<&Value>) -> Value {
+    let mut effective = user.clone();
+    if let Some(project) = project {
+        let mut project = project.clone();
+        if let Some(project) = project.as_object_mut() {
+            project.retain(|key, _| !USER_ONLY.contains(&key.as_str()));
+        }
+        merge_patch(&mut effective, project);
+    }
+    effective
+}
+
+/// Settings in effect for `root`, or just the user's when it is None. Unreadable or invalid
+/// files read as empty so a bad edit never stops a terminal from opening.
+pub(crate) fn load(root: Option<&str>) -> ProjectSettings {
+    let user = read_json(&user_path()).unwrap_or(Value::Null);
+    let project = root.and_then(|root| read_json(&project_path(root)).ok());
+    serde_json::from_value(merged(&user, project.as_ref())).unwrap_or_default()
+}
+
+/// The "policy" values of the user's settings and of `root`'s (null where there is none),
+/// unmerged: policy.rs combines them so that a project can only add limits. Err when either
+/// file can't be read or parsed.
+pub(crate) fn policy_layers(root: Option<&str>) -> Result<[Value; 2], String> {
+    let user = read_json(&user_path())?;
+    let project = match root {
+        Some(root) => read_json(&project_path(root))?,
+        None => Value::Null,
+    };
+    Ok([user["policy"].clone(), project["policy"].clone()])
+}
+
+/// When the settings files for `root` last changed, to tell whether what was read is stale
+pub(crate) fn modified(root: Option<&str>) -> [Option<std::time::SystemTime>; 2] {
+    let mtime = |path: PathBuf| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    [
+        mtime(user_path()),
+        root.and_then(|root| mtime(project_path(root))),
+    ]
+}
+
+fn expect_string(key: &str, value: &Value) -> Result<(), String> {
+    if value.is_string() {
+        Ok(())
+    } else {
+        Err(format!("{} must be a string", key))
+    }
+}
+
+fn expect_object<'a>(key: &str, value: &'a Value) -> Result<&'a Map<String, Value>, String> {
+    value
+        .as_object()
+        .ok_or_else(|| format!("{} must be an object", key))
+}
+
+/// Type-check the known keys; others pass through so newer settings aren't rejected
+fn validate(settings: &Value) -> Result<(), String> {
+    let settings = expect_object("Settings", settings)?;
+    for (key, value) in settings {
+        match key.as_str() {
+            "shell" | "default_env_set" | "editor" => expect_string(key, value)?,
+            "env_sets" => {
+                for (name, set) in expect_object(key, value)? {
+                    expect_map_of(
+                        &format!("env_sets.{}", name),
+                        set,
+                        Value::is_string,
+                        "strings",
+                    )?;
+                }
+            }
+            "task_env" => expect_map_of(key, value, Value::is_string, "strings")?,
+            "watcher" => {
+                if let Some(ignore) = expect_object(key, value)?.get("ignore") {
+                    expect_strings("watcher.ignore", ignore)?;
+                    for pattern in ignore.as_array().into_iter().flatten() {
+                        let pattern = pattern.as_str().unwrap_or_default();
+                        glob::Pattern::new(pattern).map_err(|e| {
+                            format!("Invalid watcher.ignore glob {}: {}", pattern, e)
+                        })?;
+                    }
+                }
+            }
+            "agent" => {
+                let agent = expect_object(key, value)?;
+                if let Some(tool) = agent.get("tool") {
+                    expect_string("agent.tool", tool)?;
+                }
+                if let Some(models) = agent.get("models") {
+                    expect_map_of("agent.models", models, Value::is_string, "strings")?;
+                }
+            }
+            "policy" => {
+                let policy = expect_object(key, value)?;
+                for list in ["disabled_commands", "allowed_programs"] {
+                    if let Some(names) = policy.get(list) {
+                        expect_strings(&format!("policy.{}", list), names)?;
+                    }
+                }
+                if let Some(flag) = policy.get("workspace_only_writes") {
+                    if !flag.is_boolean() {
+                        return Err(
+                            "policy.workspace_only_writes must be true or false".to_string()
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// The effective settings must also hang together, e.g. a project may pick an env set the
+/// user defined
+fn validate_effective(settings: &Value) -> Result<(), String> {
+    let known = |name: &str| {
+        settings
+            .get("env_sets")
+            .and_then(|sets| sets.get(name))
+            .is_some()
+    };
+    if let Some(name) = settings.get("default_env_set").and_then(Value::as_str) {
+        if !known(name) {
+            return Err(format!(
+                "default_env_set names an unknown env set: {}",
+                name
+            ));
+        }
+    }
+    let task_env = settings.get("task_env").and_then(Value::as_object);
+    for (task, name) in task_env.into_iter().flatten() {
+        let name = name.as_str().unwrap_or_default();
+        if !known(name) {
+            return Err(format!(
+                "task_env.{} names an unknown env set: {}",
+                task, name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The user-global settings (~/.ade/settings.json), the project's (<root>/.ade/settings.json)
+/// and the two merged
+#[tauri::command]
+pub fn get_project_settings(root: Option<String>) -> Result<ProjectSettingsLayers, String> {
+    let user = read_json(&user_path())?;
+    let project = root
+        .as_deref()
+        .map(|root| read_json(&project_path(root)))
+        .transpose()?;
+    Ok(ProjectSettingsLayers {
+        effective: merged(&user, project.as_ref()),
+        user,
+        project,
+    })
+}
+
+/// The "policy" key as it is in the project's settings file, or the user's when `root` is None
+pub(crate) fn policy_at(root: Option<&str>) -> Result<Value, String> {
+    let path = match root {
+        Some(root) => project_path(root),
+        None => user_path(),
+    };
+    Ok(read_json(&path)?
+        .get("policy")
+        .cloned()
+        .unwrap_or(Value::Null))
+}
+
+/// set_project_settings, which refuses to change the policy unless `policy_confirmed`
+pub(crate) fn update(
+    app: &AppHandle,
+    root: Option<String>,
+    patch: Value,
+    policy_confirmed: bool,
+) -> Result<ProjectSettingsLayers, String> {
+    let path = match root.as_deref() {
+        Some(root) => project_path(root),
+        None => user_path(),
+    };
+    let mut settings = read_json(&path)?;
+    let policy = settings.get("policy").cloned();
+    merge_patch(&mut settings, patch);
+    // The policy limits what may call this, so changing it takes a set_policy confirmation
+    if !policy_confirmed && settings.get("policy") != policy.as_ref() {
+        return Err("Changing the policy needs a set_policy confirmation".to_string());
+    }
+    validate(&settings)?;
+    if root.is_some() {
+        if let Some(key) = USER_ONLY.iter().find(|key| settings.get(**key).is_some()) {
+            return Err(format!("{} can only be set in the user settings", key));
+        }
+    }
+    let (user, project) = match root {
+        Some(_) => (read_json(&user_path())?, Some(&settings)),
+        None => (settings.clone(), None),
+    };
+    validate_effective(&merged(&user, project))?;
+    write_json(&path, &settings)?;
+    let _ = app.emit(
+        "project-settings-changed",
+        SettingsChanged { root: root.clone() },
+    );
+    get_project_settings(root)
+}
+
+/// Apply `patch` as a JSON merge patch to the project's settings, or to the user-global ones
+/// when `root` is None. Invalid results leave the file untouched, and so does a change to
+/// "policy", which goes through a set_policy confirmation instead. Emits
+/// `project-settings-changed` and returns the new layers.
+#[tauri::command]
+pub fn set_project_settings(
+    app: AppHandle,
+    root: Option<String>,
+    patch: Value,
+) -> Result<ProjectSettingsLayers, String> {
+    update(&app, root, patch, false)
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvTarget {
+    /// A running terminal
+    Pty(u32),
+    /// A task in `root`, by its list_tasks id
+    Task { root: String, task: String },
+}
+
+/// Load an env set into a running terminal, or make it the one a task runs with from now on
+/// (saved in the project settings). For a task, None goes back to the default env set.
+#[tauri::command]
+pub fn apply_env_profile(
+    app: AppHandle,
+    pty: tauri::State<'_, PtyManager>,
+    target: EnvTarget,
+    profile: Option<String>,
+) -> Result<(), String> {
+    match target {
+        EnvTarget::Pty(id) => {
+            let profile = profile
+                .ok_or("A terminal's variables can't be unset; open a new terminal instead")?;
+            pty.apply_env_set(id, &profile)
+        }
+        EnvTarget::Task { root, task } => {
+            let patch = serde_json::json!({ "task_env": { task: profile } });
+            set_project_settings(app, Some(root), patch).map(|_| ())
+        }
+    }
+}
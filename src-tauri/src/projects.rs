@@ -0,0 +1,304 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Unpinned projects kept, most recently opened first; pinned and read-only ones are never
+/// dropped
+const MAX_RECENT: usize = 50;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProjectEntry {
+    pub(crate) path: String,
+    /// Display name; the folder name unless renamed
+    pub(crate) name: String,
+    last_opened: u64,
+    #[serde(default)]
+    pinned: bool,
+    /// Changes to its files, git state and terminals are refused; see policy.rs
+    #[serde(default)]
+    read_only: bool,
+    /// Branch checked out when the project was last listed or opened; None outside git
+    #[serde(default)]
+    branch: Option<String>,
+    /// Whether the folder still exists; not stored
+    #[serde(default, skip_deserializing)]
+    exists: bool,
+}
+
+#[derive(Clone)]
+pub struct ProjectManager {
+    projects: Arc<Mutex<Vec<ProjectEntry>>>,
+}
+
+fn registry_path() -> std::path::PathBuf {
+    crate::ade_path("projects.json")
+}
+
+impl ProjectManager {
+    pub fn new() -> Self {
+        let projects = std::fs::read_to_string(registry_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            projects: Arc::new(Mutex::new(projects)),
+        }
+    }
+
+    /// Up to `limit` existing projects, pinned first, then most recently opened
+    pub(crate) fn recent(&self, limit: usize) -> Vec<ProjectEntry> {
+        let mut projects = self.projects.lock().unwrap().clone();
+        projects.retain(|p| Path::new(&p.path).is_dir());
+        projects.sort_by(|a, b| {
+            b.pinned
+                .cmp(&a.pinned)
+                .then(b.last_opened.cmp(&a.last_opened))
+        });
+        projects.truncate(limit);
+        projects
+    }
+
+    /// The project opened most recently, whose settings apply to commands that don't name one
+    pub(crate) fn last_opened(&self) -> Option<String> {
+        let projects = self.projects.lock().unwrap();
+        projects
+            .iter()
+            .max_by_key(|p| p.last_opened)
+            .map(|p| p.path.clone())
+    }
+
+    /// Every registered project's folder
+    pub(crate) fn paths(&self) -> Vec<String> {
+        let projects = self.projects.lock().unwrap();
+        projects.iter().map(|p| p.path.clone()).collect()
+    }
+
+    /// Pinned projects' folders
+    pub(crate) fn pinned(&self) -> Vec<String> {
+        let projects = self.projects.lock().unwrap();
+        projects
+            .iter()
+            .filter(|p| p.pinned)
+            .map(|p| p.path.clone())
+            .collect()
+    }
+
+    /// Projects marked read-only
+    pub(crate) fn read_only(&self) -> Vec<String> {
+        let projects = self.projects.lock().unwrap();
+        projects
+            .iter()
+            .filter(|p| p.read_only)
+            .map(|p| p.path.clone())
+            .collect()
+    }
+
+    /// `path` as registered, if it is a read-only project. Its folder may be gone, so it can
+    /// still be made writable and then removed.
+    pub(crate) fn read_only_project(&self, path: &str) -> Result<String, String> {
+        let path = normalize(path).unwrap_or_else(|_| path.to_string());
+        if !self.read_only().contains(&path) {
+            return Err(format!("{} is not a read-only project", path));
+        }
+        Ok(path)
+    }
+
+    /// Lift read-only mode; only reached through a confirmed make_writable operation
+    pub(crate) fn make_writable(&self, path: &str) -> Result<(), String> {
+        self.update(|projects| {
+            for project in projects.iter_mut().filter(|p| p.path == path) {
+                project.read_only = false;
+            }
+        })
+    }
+
+    /// Apply `edit` to the registry, trim it and write it back
+    fn update<T>(&self, edit: impl FnOnce(&mut Vec<ProjectEntry>) -> T) -> Result<T, String> {
+        let mut projects = self.projects.lock().unwrap();
+        let result = edit(&mut projects);
+        projects.sort_by_key(|p| std::cmp::Reverse(p.last_opened));
+        let mut unpinned = 0;
+        // Read-only ones stay too, so the protection doesn't quietly lapse
+        projects.retain(|p| {
+            let kept = p.pinned || p.read_only;
+            unpinned += usize::from(!kept);
+            kept || unpinned <= MAX_RECENT
+        });
+        let path = registry_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(&*projects).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to save projects: {}", e))?;
+        Ok(result)
+    }
+}
+
+/// Registry key for a project folder: absolute, without a trailing slash
+fn normalize(path: &str) -> Result<String, String> {
+    let path = crate::git::resolve_repo(path.trim());
+    let canonical =
+        std::fs::canonicalize(&path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    if !canonical.is_dir() {
+        return Err(format!("Not a directory: {}", path));
+    }
+    Ok(canonical.to_string_lossy().to_string())
+}
+
+fn folder_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn current_branch(path: &str) -> Option<String> {
+    let output = crate::git::run_git(path, ["rev-parse", "--abbrev-ref", "HEAD"]).ok()?;
+    let branch = String::from_utf8_lossy(&output).trim().to_string();
+    // Detached HEAD reports "HEAD"
+    Some(branch).filter(|b| !b.is_empty() && b != "HEAD")
+}
+
+/// The entry for `path`, added if it isn't registered yet
+fn entry<'a>(projects: &'a mut Vec<ProjectEntry>, path: &str) -> &'a mut ProjectEntry {
+    match projects.iter().position(|p| p.path == path) {
+        Some(index) => &mut projects[index],
+        None => {
+            projects.push(ProjectEntry {
+                path: path.to_string(),
+                name: folder_name(path),
+                last_opened: crate::now_ms(),
+                pinned: false,
+                read_only: false,
+                branch: None,
+                exists: true,
+            });
+            projects.last_mut().unwrap()
+        }
+    }
+}
+
+/// Pinned projects first, then the rest by when they were last opened. Branches are refreshed
+/// from disk.
+#[tauri::command]
+pub async fn list_recent_projects(
+    state: tauri::State<'_, ProjectManager>,
+) -> Result<Vec<ProjectEntry>, String> {
+    let manager = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut projects = manager.projects.lock().unwrap().clone();
+        for project in &mut projects {
+            project.exists = Path::new(&project.path).is_dir();
+            // A missing folder keeps its last known branch
+            if project.exists {
+                project.branch = current_branch(&project.path);
+            }
+        }
+        let branches: Vec<(String, Option<String>)> = projects
+            .iter()
+            .map(|p| (p.path.clone(), p.branch.clone()))
+            .collect();
+        manager.update(|stored| {
+            for project in stored.iter_mut() {
+                if let Some((_, branch)) = branches.iter().find(|(path, _)| *path == project.path) {
+                    project.branch = branch.clone();
+                }
+            }
+        })?;
+        projects.sort_by(|a, b| {
+            b.pinned
+                .cmp(&a.pinned)
+                .then(b.last_opened.cmp(&a.last_opened))
+        });
+        Ok(projects)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Register a project as opened now, adding it if needed
+#[tauri::command]
+pub fn record_project_open(
+    state: tauri::State<'_, ProjectManager>,
+    path: String,
+) -> Result<(), String> {
+    let path = normalize(&path)?;
+    let branch = current_branch(&path);
+    state.update(|projects| {
+        let project = entry(projects, &path);
+        project.last_opened = crate::now_ms();
+        project.branch = branch;
+    })
+}
+
+/// Pin a project to the top of the list, or unpin it with `pinned: false`
+#[tauri::command]
+pub fn pin_project(
+    state: tauri::State<'_, ProjectManager>,
+    path: String,
+    pinned: Option<bool>,
+) -> Result<(), String> {
+    if !pinned.unwrap_or(true) {
+        // Unpinning leaves the registry alone otherwise, and works for folders that are gone
+        return state.update(|projects| {
+            for project in projects.iter_mut().filter(|p| p.path == path) {
+                project.pinned = false;
+            }
+        });
+    }
+    let path = normalize(&path)?;
+    state.update(|projects| entry(projects, &path).pinned = true)
+}
+
+/// Make a project read-only. While read-only, the backend refuses commands that change its
+/// files, git state or terminals; reading, searching and viewing still work. Making it writable
+/// again takes a make_writable confirmation, so what it guards against can't simply undo it.
+#[tauri::command]
+pub fn set_project_read_only(
+    state: tauri::State<'_, ProjectManager>,
+    path: String,
+    read_only: bool,
+) -> Result<(), String> {
+    let path = normalize(&path)?;
+    if !read_only && state.read_only().contains(&path) {
+        return Err(
+            "Making a project writable again needs a make_writable confirmation".to_string(),
+        );
+    }
+    state.update(|projects| entry(projects, &path).read_only = read_only)
+}
+
+/// Set the display name; an empty name goes back to the folder name
+#[tauri::command]
+pub fn rename_project(
+    state: tauri::State<'_, ProjectManager>,
+    path: String,
+    name: String,
+) -> Result<(), String> {
+    state.update(|projects| {
+        let project = projects
+            .iter_mut()
+            .find(|p| p.path == path)
+            .ok_or_else(|| format!("Project not found: {}", path))?;
+        let name = name.trim();
+        project.name = if name.is_empty() {
+            folder_name(&project.path)
+        } else {
+            name.to_string()
+        };
+        Ok(())
+    })?
+}
+
+/// Forget a project, pinned or not. Nothing on disk is touched. A read-only project has to be
+/// made writable first, so the protection doesn't go with it.
+#[tauri::command]
+pub fn remove_recent(state: tauri::State<'_, ProjectManager>, path: String) -> Result<(), String> {
+    if state.read_only().contains(&path) {
+        return Err(format!(
+            "{} is read-only; make it writable before removing it",
+            path
+        ));
+    }
+    state.update(|projects| projects.retain(|p| p.path != path))
+}
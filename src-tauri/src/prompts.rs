@@ -0,0 +1,323 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A rendered diff longer than this is cut, keeping the start
+const MAX_DIFF_CHARS: usize = 100_000;
+/// Selected files larger than this are listed but not inlined
+const MAX_INLINE_FILE_SIZE: u64 = 256 * 1024;
+/// Filled in from the render context rather than from caller-supplied values
+const BUILTIN_VARIABLES: &[&str] = &[
+    "project_name",
+    "project_path",
+    "branch",
+    "selected_files",
+    "selected_file_contents",
+    "current_diff",
+    "date",
+];
+
+#[derive(serde::Serialize)]
+pub struct PromptTemplate {
+    name: String,
+    path: String,
+    description: Option<String>,
+    /// `{{placeholders}}` used in the body, in order of first use
+    variables: Vec<String>,
+    body: String,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct RenderContext {
+    /// Project root for project_name, branch and current_diff
+    project: Option<String>,
+    #[serde(default)]
+    selected_files: Vec<String>,
+    /// Values for custom placeholders; these also override built-ins
+    #[serde(default)]
+    variables: HashMap<String, String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct RenderedPrompt {
+    text: String,
+    /// Placeholders left in the text because nothing supplied a value
+    missing: Vec<String>,
+}
+
+fn prompts_dir() -> PathBuf {
+    crate::ade_path("prompts")
+}
+
+fn prompt_path(name: &str) -> Result<PathBuf, String> {
+    let name = name.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid prompt name: {}", name));
+    }
+    Ok(prompts_dir().join(format!("{}.md", name)))
+}
+
+fn placeholder_re() -> regex::Regex {
+    regex::Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap()
+}
+
+fn placeholders(body: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for capture in placeholder_re().captures_iter(body) {
+        let name = capture[1].to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn read_prompt(path: &Path) -> Result<PromptTemplate, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let frontmatter = crate::slash_commands::parse_frontmatter(&content);
+    let body = crate::slash_commands::strip_frontmatter(&content).to_string();
+    Ok(PromptTemplate {
+        name: path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path: path.to_string_lossy().to_string(),
+        description: frontmatter
+            .get("description")
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+            .map(String::from),
+        variables: placeholders(&body),
+        body,
+    })
+}
+
+fn write_prompt(path: &Path, description: Option<&str>, body: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let mut content = String::new();
+    if let Some(description) = description.map(str::trim).filter(|d| !d.is_empty()) {
+        // Newlines would break the frontmatter line
+        content.push_str(&format!(
+            "---\ndescription: {}\n---\n\n",
+            description.replace('\n', " ")
+        ));
+    }
+    content.push_str(body);
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[tauri::command]
+pub fn list_prompts() -> Result<Vec<PromptTemplate>, String> {
+    let Ok(entries) = std::fs::read_dir(prompts_dir()) else {
+        return Ok(Vec::new());
+    };
+    let mut prompts: Vec<PromptTemplate> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("md"))
+        .filter_map(|p| read_prompt(&p).ok())
+        .collect();
+    prompts.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(prompts)
+}
+
+#[tauri::command]
+pub fn create_prompt(
+    name: String,
+    body: String,
+    description: Option<String>,
+) -> Result<PromptTemplate, String> {
+    let path = prompt_path(&name)?;
+    if path.exists() {
+        return Err(format!("Prompt already exists: {}", name.trim()));
+    }
+    write_prompt(&path, description.as_deref(), &body)?;
+    read_prompt(&path)
+}
+
+/// Replace a prompt's body and description; `new_name` renames it
+#[tauri::command]
+pub fn update_prompt(
+    name: String,
+    body: String,
+    description: Option<String>,
+    new_name: Option<String>,
+) -> Result<PromptTemplate, String> {
+    let path = prompt_path(&name)?;
+    if !path.is_file() {
+        return Err(format!("Prompt not found: {}", name));
+    }
+    let target = match new_name.as_deref().map(str::trim) {
+        Some(new_name) if new_name != name.trim() => {
+            let target = prompt_path(new_name)?;
+            if target.exists() {
+                return Err(format!("Prompt already exists: {}", new_name));
+            }
+            target
+        }
+        _ => path.clone(),
+    };
+    write_prompt(&target, description.as_deref(), &body)?;
+    if target != path {
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    }
+    read_prompt(&target)
+}
+
+#[tauri::command]
+pub fn delete_prompt(name: String) -> Result<(), String> {
+    let path = prompt_path(&name)?;
+    if !path.is_file() {
+        return Err(format!("Prompt not found: {}", name));
+    }
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete prompt: {}", e))
+}
+
+fn git_text(root: &str, args: &[&str]) -> Option<String> {
+    crate::git::run_git(root, args)
+        .ok()
+        .map(|out| String::from_utf8_lossy(&out).to_string())
+}
+
+/// Value of a built-in placeholder, computed only when the template uses it
+fn builtin_value(name: &str, root: Option<&str>, files: &[String]) -> Option<String> {
+    let relative = |path: &str| match root {
+        Some(root) => crate::git::repo_relative(root, path),
+        None => path.to_string(),
+    };
+    match name {
+        "project_name" => Path::new(root?)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string()),
+        "project_path" => root.map(String::from),
+        "branch" => git_text(root?, &["branch", "--show-current"])
+            .map(|b| b.trim().to_string())
+            .filter(|b| !b.is_empty()),
+        "selected_files" => Some(
+            files
+                .iter()
+                .map(|f| format!("- {}", relative(f)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        "selected_file_contents" => Some(
+            files
+                .iter()
+                .map(|file| {
+                    let path = match root {
+                        Some(root) if !Path::new(file).is_absolute() => Path::new(root).join(file),
+                        _ => PathBuf::from(crate::git::resolve_repo(file)),
+                    };
+                    let too_big = std::fs::metadata(&path)
+                        .map(|m| m.len() > MAX_INLINE_FILE_SIZE)
+                        .unwrap_or(false);
+                    let content = if too_big {
+                        "(file too large to include)".to_string()
+                    } else {
+                        std::fs::read_to_string(&path)
+                            .unwrap_or_else(|_| "(unreadable)".to_string())
+                    };
+                    let language = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+                    format!(
+                        "{}\n```{}\n{}\n```",
+                        relative(file),
+                        language,
+                        content.trim_end()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        ),
+        "current_diff" => {
+            let root = root?;
+            // Before the first commit there is no HEAD to diff against
+            let diff = git_text(root, &["diff", "--no-color", "--no-ext-diff", "HEAD"])
+                .or_else(|| git_text(root, &["diff", "--no-color", "--no-ext-diff"]))?;
+            Some(match diff.char_indices().nth(MAX_DIFF_CHARS) {
+                Some((cut, _)) => format!("{}\n…(diff truncated)", &diff[..cut]),
+                None => diff,
+            })
+        }
+        "date" => {
+            let days = crate::now_ms() / 86_400_000;
+            Some(civil_date(days as i64))
+        }
+        _ => None,
+    }
+}
+
+/// YYYY-MM-DD for days since the Unix epoch (Howard Hinnant's civil_from_days)
+fn civil_date(days: i64) -> String {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Fill a template's `{{placeholders}}`. Built-ins (project_name, project_path, branch,
+/// selected_files, selected_file_contents, current_diff, date) come from the context; anything
+/// else must be passed in `variables`. Unfilled placeholders stay in the text and are listed.
+#[tauri::command]
+pub async fn render_prompt(
+    name: String,
+    context: Option<RenderContext>,
+) -> Result<RenderedPrompt, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let template = read_prompt(&prompt_path(&name)?)?;
+        let context = context.unwrap_or_default();
+        let root = context.project.as_deref().map(|p| {
+            crate::git::resolve_repo(p)
+                .trim_end_matches('/')
+                .to_string()
+        });
+
+        let mut values: HashMap<String, String> = HashMap::new();
+        for variable in &template.variables {
+            let value = context.variables.get(variable).cloned().or_else(|| {
+                BUILTIN_VARIABLES
+                    .contains(&variable.as_str())
+                    .then(|| builtin_value(variable, root.as_deref(), &context.selected_files))
+                    .flatten()
+            });
+            if let Some(value) = value {
+                values.insert(variable.clone(), value);
+            }
+        }
+
+        let mut missing = Vec::new();
+        let text = placeholder_re()
+            .replace_all(&template.body, |caps: &regex::Captures| {
+                match values.get(&caps[1]) {
+                    Some(value) => value.clone(),
+                    None => {
+                        if !missing.contains(&caps[1].to_string()) {
+                            missing.push(caps[1].to_string());
+                        }
+                        caps[0].to_string()
+                    }
+                }
+            })
+            .to_string();
+        Ok(RenderedPrompt { text, missing })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
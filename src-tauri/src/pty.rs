@@ -1,5 +1,5 @@
 use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Write};
 use std::sync::{Arc, Mutex};
 use tauri::ipc::Channel;
@@ -9,20 +9,321 @@ pub struct PtyInstance {
     _child: Box<dyn portable_pty::Child + Send + Sync>,
     master: Box<dyn portable_pty::MasterPty + Send>,
     pid: Option<u32>,
+    sink: Arc<Mutex<PtySink>>,
+    /// Directory the shell started in
+    cwd: Option<String>,
+    label: Option<String>,
+    /// Env set from the project settings chosen for this terminal
+    env_set: Option<String>,
+}
+
+/// Output of a PTY restored before the frontend attached is held back, up to this many chunks
+const MAX_BUFFERED_EVENTS: usize = 256;
+
+/// Where a PTY's output goes: a frontend channel, or a buffer until attach_pty
+pub(crate) enum PtySink {
+    Channel(Channel<PtyEvent>),
+    Buffer(VecDeque<PtyEvent>),
+}
+
+impl PtySink {
+    fn send(&mut self, event: PtyEvent) {
+        match self {
+            PtySink::Channel(channel) => {
+                let _ = channel.send(event);
+            }
+            PtySink::Buffer(events) => {
+                if events.len() >= MAX_BUFFERED_EVENTS {
+                    events.pop_front();
+                }
+                events.push_back(event);
+            }
+        }
+    }
+}
+
+/// A live PTY as saved for session restore
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct PtySnapshot {
+    /// Current directory of the foreground process, else where the shell started
+    pub(crate) cwd: Option<String>,
+    pub(crate) label: Option<String>,
+    /// Name of the program running in the shell, if any. Not its arguments, which may hold
+    /// tokens or passwords and would be written to disk.
+    pub(crate) command: Option<String>,
+    #[serde(default)]
+    pub(crate) env_set: Option<String>,
 }
 
 pub struct PtyManager {
     instances: Arc<Mutex<HashMap<u32, PtyInstance>>>,
     next_id: Arc<Mutex<u32>>,
+    diagnostics: crate::diagnostics::DiagnosticsManager,
+    dev_servers: crate::dev_servers::DevServerManager,
 }
 
 impl PtyManager {
-    pub fn new() -> Self {
+    pub fn new(
+        diagnostics: crate::diagnostics::DiagnosticsManager,
+        dev_servers: crate::dev_servers::DevServerManager,
+    ) -> Self {
         Self {
             instances: Arc::new(Mutex::new(HashMap::new())),
             next_id: Arc::new(Mutex::new(1)),
+            diagnostics,
+            dev_servers,
         }
     }
+
+    /// Directory the terminal's shell started in
+    pub(crate) fn start_cwd(&self, id: u32) -> Option<String> {
+        self.instances.lock().unwrap().get(&id)?.cwd.clone()
+    }
+
+    /// Send input to the PTY as if typed; unknown ids are ignored
+    pub(crate) fn write(&self, id: u32, data: &[u8]) -> Result<(), String> {
+        let mut instances = self.instances.lock().unwrap();
+        if let Some(instance) = instances.get_mut(&id) {
+            instance
+                .writer
+                .write_all(data)
+                .map_err(|e| e.to_string())?;
+            instance.writer.flush().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// How many PTYs have a program running in the foreground rather than an idle shell
+    pub(crate) fn busy_count(&self) -> usize {
+        let shells: Vec<u32> = self
+            .instances
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|instance| instance.pid)
+            .collect();
+        shells
+            .into_iter()
+            .filter(|pid| get_foreground_pid(*pid).is_some())
+            .count()
+    }
+
+    /// Process id of the shell running in `id`
+    pub(crate) fn pid(&self, id: u32) -> Option<u32> {
+        self.instances.lock().unwrap().get(&id)?.pid
+    }
+
+    /// Every open PTY, oldest first
+    pub(crate) fn snapshot(&self) -> Vec<PtySnapshot> {
+        let mut instances: Vec<(u32, Option<u32>, PtySnapshot)> = self
+            .instances
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, instance)| {
+                let saved = PtySnapshot {
+                    cwd: instance.cwd.clone(),
+                    label: instance.label.clone(),
+                    command: None,
+                    env_set: instance.env_set.clone(),
+                };
+                (*id, instance.pid, saved)
+            })
+            .collect();
+        instances.sort_by_key(|(id, ..)| *id);
+        // lsof and ps run outside the lock so typing isn't held up
+        instances
+            .into_iter()
+            .map(|(_, pid, mut saved)| {
+                if let Some(pid) = pid {
+                    let foreground = get_foreground_pid(pid);
+                    saved.cwd = process_cwd(foreground.unwrap_or(pid)).ok().or(saved.cwd);
+                    saved.command = foreground.and_then(process_name);
+                }
+                saved
+            })
+            .collect()
+    }
+
+    /// Load an env set into a running shell. The variables go through a file only the user can
+    /// read, which the shell sources and deletes, so their values are never echoed.
+    pub(crate) fn apply_env_set(&self, id: u32, name: &str) -> Result<(), String> {
+        let (pid, cwd) = {
+            let instances = self.instances.lock().unwrap();
+            let instance = instances.get(&id).ok_or("PTY not found")?;
+            (instance.pid, instance.cwd.clone())
+        };
+        if pid.and_then(get_foreground_pid).is_some() {
+            return Err("The terminal is running a command; apply the env set once it finishes".to_string());
+        }
+        let dir = pid.and_then(|pid| process_cwd(pid).ok()).or(cwd);
+        let settings = crate::project_settings::load(
+            dir.as_deref()
+                .and_then(crate::project_settings::project_root_for)
+                .as_deref(),
+        );
+        let mut script = String::new();
+        for (key, value) in settings.env_set(name)? {
+            let valid = key.chars().next().is_some_and(|c| !c.is_ascii_digit())
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid {
+                return Err(format!("{} can't be set from a shell", key));
+            }
+            script.push_str(&format!("export {}='{}'\n", key, value.replace('\'', "'\\''")));
+        }
+
+        let path = std::env::temp_dir().join(format!("ade-env-{}-{}", id, crate::now_ms()));
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(&path)
+            .and_then(|mut file| file.write_all(script.as_bytes()))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        // The leading space keeps it out of history in shells set to ignore such lines
+        let path = path.to_string_lossy();
+        self.write(id, format!(" . '{}'; rm -f '{}'\r", path, path).as_bytes())?;
+        if let Some(instance) = self.instances.lock().unwrap().get_mut(&id) {
+            instance.env_set = Some(name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Start a login shell in a new PTY with the env set `env_set`, else the project's default
+    pub(crate) fn spawn(
+        &self,
+        rows: u16,
+        cols: u16,
+        cwd: Option<String>,
+        label: Option<String>,
+        env_set: Option<String>,
+        sink: PtySink,
+    ) -> Result<u32, String> {
+        let pty_system = NativePtySystem::default();
+
+        let pair = pty_system
+            .openpty(PtySize {
+                rows,
+                cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| format!("openpty failed: {}", e))?;
+
+        let settings = crate::project_settings::load(
+            cwd.as_deref()
+                .and_then(crate::project_settings::project_root_for)
+                .as_deref(),
+        );
+        let shell = settings
+            .shell
+            .clone()
+            .or_else(|| std::env::var("SHELL").ok())
+            .unwrap_or_else(|| "/bin/zsh".to_string());
+        let mut cmd = CommandBuilder::new(&shell);
+        cmd.arg("-l");
+
+        if let Some(dir) = &cwd {
+            cmd.cwd(dir);
+        } else if let Ok(home) = std::env::var("HOME") {
+            cmd.cwd(home);
+        }
+
+        cmd.env("TERM", "xterm-256color");
+        if let Ok(home) = std::env::var("HOME") {
+            cmd.env("HOME", home);
+        }
+        if let Ok(user) = std::env::var("USER") {
+            cmd.env("USER", user);
+        }
+        if let Ok(path) = std::env::var("PATH") {
+            cmd.env("PATH", path);
+        }
+        if let Ok(lang) = std::env::var("LANG") {
+            cmd.env("LANG", lang);
+        }
+        if let Some(env) = settings.env_for(env_set.as_deref())? {
+            for (key, value) in env {
+                cmd.env(key, value);
+            }
+        }
+
+        let child = pair.slave.spawn_command(cmd).map_err(|e| format!("spawn failed: {}", e))?;
+        let child_pid = child.process_id();
+        drop(pair.slave);
+
+        let writer = pair.master.take_writer().map_err(|e| format!("take_writer failed: {}", e))?;
+        let mut reader = pair.master.try_clone_reader().map_err(|e| format!("clone_reader failed: {}", e))?;
+
+        let id = {
+            let mut next = self.next_id.lock().unwrap();
+            let id = *next;
+            *next += 1;
+            id
+        };
+
+        let origin = format!("pty:{}", id);
+        let mut diagnostics = self.diagnostics.terminal_collector(
+            origin.clone(),
+            cwd.clone().unwrap_or_else(crate::get_home_dir),
+            child_pid,
+        );
+        let diagnostics_manager = self.diagnostics.clone();
+        let mut dev_servers = self.dev_servers.detector(Some(id), None);
+        let dev_server_manager = self.dev_servers.clone();
+
+        let sink = Arc::new(Mutex::new(sink));
+        {
+            let mut instances = self.instances.lock().unwrap();
+            instances.insert(
+                id,
+                PtyInstance {
+                    writer,
+                    _child: child,
+                    master: pair.master,
+                    pid: child_pid,
+                    sink: sink.clone(),
+                    cwd,
+                    label,
+                    env_set,
+                },
+            );
+        }
+
+        let instances_ref = self.instances.clone();
+        crate::crash::spawn("terminal", origin.clone(), move || {
+            let mut buf = [0u8; 4096];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        let text = String::from_utf8_lossy(&buf[..n]);
+                        diagnostics.feed(&text);
+                        dev_servers.feed(&text);
+                        sink.lock().unwrap().send(PtyEvent::Output {
+                            data: buf[..n].to_vec(),
+                        });
+                    }
+                    Err(e) => {
+                        sink.lock().unwrap().send(PtyEvent::Error {
+                            message: e.to_string(),
+                        });
+                        break;
+                    }
+                }
+            }
+            let mut instances = instances_ref.lock().unwrap();
+            instances.remove(&id);
+            drop(instances);
+            diagnostics_manager.clear(&origin);
+            dev_server_manager.forget(Some(id), None);
+            sink.lock().unwrap().send(PtyEvent::Exit {});
+            Ok(())
+        });
+
+        Ok(id)
+    }
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -37,117 +338,59 @@ pub enum PtyEvent {
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn create_pty(
     state: tauri::State<'_, PtyManager>,
     rows: u16,
     cols: u16,
     cwd: Option<String>,
+    label: Option<String>,
+    env_set: Option<String>,
+    location: Option<crate::remote::Location>,
     on_event: Channel<PtyEvent>,
 ) -> Result<u32, String> {
-    let pty_system = NativePtySystem::default();
-
-    let pair = pty_system
-        .openpty(PtySize {
-            rows,
-            cols,
-            pixel_width: 0,
-            pixel_height: 0,
-        })
-        .map_err(|e| format!("openpty failed: {}", e))?;
-
-    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
-    let mut cmd = CommandBuilder::new(&shell);
-    cmd.arg("-l");
-
-    if let Some(dir) = cwd {
-        cmd.cwd(dir);
-    } else if let Ok(home) = std::env::var("HOME") {
-        cmd.cwd(home);
-    }
-
-    cmd.env("TERM", "xterm-256color");
-    if let Ok(home) = std::env::var("HOME") {
-        cmd.env("HOME", home);
-    }
-    if let Ok(user) = std::env::var("USER") {
-        cmd.env("USER", user);
-    }
-    if let Ok(path) = std::env::var("PATH") {
-        cmd.env("PATH", path);
-    }
-    if let Ok(lang) = std::env::var("LANG") {
-        cmd.env("LANG", lang);
-    }
-
-    let child = pair.slave.spawn_command(cmd).map_err(|e| format!("spawn failed: {}", e))?;
-    let child_pid = child.process_id();
-    drop(pair.slave);
-
-    let writer = pair.master.take_writer().map_err(|e| format!("take_writer failed: {}", e))?;
-    let mut reader = pair.master.try_clone_reader().map_err(|e| format!("clone_reader failed: {}", e))?;
-
-    let id = {
-        let mut next = state.next_id.lock().unwrap();
-        let id = *next;
-        *next += 1;
-        id
+    let Some(location) = location else {
+        return state.spawn(rows, cols, cwd, label, env_set, PtySink::Channel(on_event));
     };
+    // A local shell that becomes ssh, with `cwd` on the host; exec, so it closes with ssh
+    let command = crate::remote::ssh_command_line(&location, cwd.as_deref())?;
+    let id = state.spawn(rows, cols, None, label, None, PtySink::Channel(on_event))?;
+    state.write(id, format!("exec {}\r", command).as_bytes())?;
+    Ok(id)
+}
 
-    {
-        let mut instances = state.instances.lock().unwrap();
-        instances.insert(
-            id,
-            PtyInstance {
-                writer,
-                _child: child,
-                master: pair.master,
-                pid: child_pid,
-            },
-        );
-    }
-
-    let instances_ref = state.instances.clone();
-    std::thread::spawn(move || {
-        let mut buf = [0u8; 4096];
-        loop {
-            match reader.read(&mut buf) {
-                Ok(0) => break,
-                Ok(n) => {
-                    let _ = on_event.send(PtyEvent::Output {
-                        data: buf[..n].to_vec(),
-                    });
-                }
-                Err(e) => {
-                    let _ = on_event.send(PtyEvent::Error {
-                        message: e.to_string(),
-                    });
-                    break;
-                }
-            }
+/// Start sending a restored PTY's output to `on_event`, beginning with what it printed
+/// while nothing was attached
+#[tauri::command]
+pub fn attach_pty(
+    state: tauri::State<'_, PtyManager>,
+    id: u32,
+    on_event: Channel<PtyEvent>,
+) -> Result<(), String> {
+    let sink = state.instances.lock().unwrap().get(&id).ok_or("PTY not found")?.sink.clone();
+    let mut sink = sink.lock().unwrap();
+    if let PtySink::Buffer(events) = &mut *sink {
+        for event in events.drain(..) {
+            let _ = on_event.send(event);
         }
-        let mut instances = instances_ref.lock().unwrap();
-        instances.remove(&id);
-        let _ = on_event.send(PtyEvent::Exit {});
-    });
-
-    Ok(id)
+    }
+    *sink = PtySink::Channel(on_event);
+    Ok(())
 }
 
 #[tauri::command]
 pub fn write_pty(
     state: tauri::State<'_, PtyManager>,
+    budget: tauri::State<'_, crate::budget::BudgetManager>,
+    activity: tauri::State<'_, crate::activity::ActivityManager>,
     id: u32,
     data: Vec<u8>,
 ) -> Result<(), String> {
-    let mut instances = state.instances.lock().unwrap();
-    if let Some(instance) = instances.get_mut(&id) {
-        instance
-            .writer
-            .write_all(&data)
-            .map_err(|e| e.to_string())?;
-        instance.writer.flush().map_err(|e| e.to_string())?;
+    if budget.input_paused(id) {
+        return Err("Input is paused: the agent went over its budget".to_string());
     }
-    Ok(())
+    activity.observe_input(id, state.pid(id), &data);
+    state.write(id, &data)
 }
 
 #[tauri::command]
@@ -186,7 +429,7 @@ pub fn reattach_pty(
         .map_err(|e| format!("clone_reader failed: {}", e))?;
     drop(instances);
 
-    std::thread::spawn(move || {
+    crate::crash::spawn("terminal", format!("pty:{}", id), move || {
         let mut buf = [0u8; 4096];
         loop {
             match reader.read(&mut buf) {
@@ -204,12 +447,18 @@ pub fn reattach_pty(
                 Err(_) => break,
             }
         }
+        Ok(())
     });
     Ok(())
 }
 
 #[tauri::command]
-pub fn kill_pty(state: tauri::State<'_, PtyManager>, id: u32) -> Result<(), String> {
+pub fn kill_pty(
+    state: tauri::State<'_, PtyManager>,
+    activity: tauri::State<'_, crate::activity::ActivityManager>,
+    id: u32,
+) -> Result<(), String> {
+    activity.forget_pty(id);
     let mut instances = state.instances.lock().unwrap();
     instances.remove(&id);
     Ok(())
@@ -224,9 +473,13 @@ pub fn get_pty_cwd(state: tauri::State<'_, PtyManager>, id: u32) -> Result<Strin
     // On macOS, use lsof to get the CWD of the foreground process group
     // First try to find the foreground child process, fall back to shell PID
     let fg_pid = get_foreground_pid(pid).unwrap_or(pid);
+    drop(instances);
+    process_cwd(fg_pid)
+}
 
+pub(crate) fn process_cwd(pid: u32) -> Result<String, String> {
     let output = std::process::Command::new("/usr/bin/lsof")
-        .args(["-a", "-d", "cwd", "-p", &fg_pid.to_string(), "-Fn"])
+        .args(["-a", "-d", "cwd", "-p", &pid.to_string(), "-Fn"])
         .output()
         .map_err(|e| format!("lsof failed: {}", e))?;
 
@@ -244,7 +497,7 @@ pub fn get_pty_cwd(state: tauri::State<'_, PtyManager>, id: u32) -> Result<Strin
 }
 
 /// Get the foreground process of a shell by finding its child processes
-fn get_foreground_pid(shell_pid: u32) -> Option<u32> {
+pub(crate) fn get_foreground_pid(shell_pid: u32) -> Option<u32> {
     // Use pgrep to find child processes of the shell
     let output = std::process::Command::new("/usr/bin/pgrep")
         .args(["-P", &shell_pid.to_string()])
@@ -260,5 +513,16 @@ fn get_foreground_pid(shell_pid: u32) -> Option<u32> {
     stdout
         .lines()
         .filter_map(|line| line.trim().parse::<u32>().ok())
-        .last()
+        .next_back()
+}
+
+/// Program name of a process, without its path or arguments
+fn process_name(pid: u32) -> Option<String> {
+    let output = std::process::Command::new("/bin/ps")
+        .args(["-o", "comm=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let command = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let name = command.rsplit('/').next().unwrap_or_default().to_string();
+    Some(name).filter(|n| output.status.success() && !n.is_empty())
 }
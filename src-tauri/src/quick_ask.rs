@@ -0,0 +1,252 @@
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+use tauri::ipc::Channel;
+
+const ANTHROPIC_URL: &str = "https://api.anthropic.com/v1/messages";
+const OPENAI_URL: &str = "https://api.openai.com/v1/chat/completions";
+const DEFAULT_ANTHROPIC_MODEL: &str = "claude-haiku-4-5";
+const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
+const DEFAULT_MAX_TOKENS: u32 = 2048;
+/// Time allowed to connect and get response headers; the stream itself has no overall limit
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Per-file and total limits on attached context, in bytes
+const MAX_FILE_BYTES: usize = 100_000;
+const MAX_CONTEXT_BYTES: usize = 400_000;
+
+const SYSTEM_PROMPT: &str = "You answer quick questions from a developer inside their terminal \
+                             IDE. Be brief and direct. When asked for a commit message, reply \
+                             with only the message.";
+
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuickAskProvider {
+    #[default]
+    Anthropic,
+    OpenAI,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct QuickAskOptions {
+    #[serde(default)]
+    provider: QuickAskProvider,
+    model: Option<String>,
+    /// Replaces the default system prompt
+    system: Option<String>,
+    max_tokens: Option<u32>,
+    /// Directory relative context paths are resolved against
+    cwd: Option<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum QuickAskEvent {
+    #[serde(rename = "delta")]
+    Delta { text: String },
+    /// Final event of a successful answer
+    #[serde(rename = "done")]
+    Done {
+        text: String,
+        model: String,
+        input_tokens: Option<u64>,
+        output_tokens: Option<u64>,
+    },
+}
+
+/// The attached files as tagged blocks, skipping binaries and truncating large files
+fn read_context(paths: &[String], cwd: Option<&str>) -> Result<String, String> {
+    let mut context = String::new();
+    for path in paths {
+        let full = match cwd {
+            Some(cwd) if Path::new(path).is_relative() => Path::new(cwd).join(path),
+            _ => Path::new(path).to_path_buf(),
+        };
+        let bytes = std::fs::read(&full).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        if bytes[..bytes.len().min(8000)].contains(&0) {
+            continue;
+        }
+        let remaining = MAX_CONTEXT_BYTES.saturating_sub(context.len());
+        if remaining == 0 {
+            break;
+        }
+        let mut text = String::from_utf8_lossy(&bytes).to_string();
+        let limit = MAX_FILE_BYTES.min(remaining);
+        if text.len() > limit {
+            let mut end = limit;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.truncate(end);
+            text.push_str("\n[truncated]");
+        }
+        context.push_str(&format!("<file path=\"{}\">\n{}\n</file>\n\n", path, text));
+    }
+    Ok(context)
+}
+
+fn api_key(provider: QuickAskProvider) -> Result<String, String> {
+    let (secret, var, label) = match provider {
+        QuickAskProvider::Anthropic => ("anthropic-api-key", "ANTHROPIC_API_KEY", "Anthropic"),
+        QuickAskProvider::OpenAI => ("openai-api-key", "OPENAI_API_KEY", "OpenAI"),
+    };
+    crate::secrets::secret(secret)
+        .or_else(|| std::env::var(var).ok().filter(|k| !k.is_empty()))
+        .ok_or_else(|| format!("No {} API key set. Add one in Settings → AI API", label))
+}
+
+/// The error message from an API error body, falling back to the raw body
+fn api_error(status: reqwest::StatusCode, body: &str) -> String {
+    let message = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|v| v["error"]["message"].as_str().map(String::from))
+        .unwrap_or_else(|| body.trim().to_string());
+    format!("API request failed ({}): {}", status, message)
+}
+
+fn ask(
+    prompt: &str,
+    context_paths: &[String],
+    options: QuickAskOptions,
+    on_delta: &Channel<QuickAskEvent>,
+) -> Result<String, String> {
+    let provider = options.provider;
+    let key = api_key(provider)?;
+    let context = read_context(context_paths, options.cwd.as_deref())?;
+    let content = if context.is_empty() {
+        prompt.to_string()
+    } else {
+        format!("{}{}", context, prompt)
+    };
+    let system = options.system.as_deref().unwrap_or(SYSTEM_PROMPT);
+    let max_tokens = options.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+
+    let client = reqwest::blocking::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(None)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let (request, model) = match provider {
+        QuickAskProvider::Anthropic => {
+            let model = options
+                .model
+                .unwrap_or_else(|| DEFAULT_ANTHROPIC_MODEL.to_string());
+            let body = json!({
+                "model": model,
+                "max_tokens": max_tokens,
+                "system": system,
+                "stream": true,
+                "messages": [{ "role": "user", "content": content }],
+            });
+            let request = client
+                .post(ANTHROPIC_URL)
+                .header("x-api-key", key)
+                .header("anthropic-version", "2023-06-01")
+                .json(&body);
+            (request, model)
+        }
+        QuickAskProvider::OpenAI => {
+            let model = options
+                .model
+                .unwrap_or_else(|| DEFAULT_OPENAI_MODEL.to_string());
+            let body = json!({
+                "model": model,
+                "max_completion_tokens": max_tokens,
+                "stream": true,
+                "stream_options": { "include_usage": true },
+                "messages": [
+                    { "role": "system", "content": system },
+                    { "role": "user", "content": content },
+                ],
+            });
+            (client.post(OPENAI_URL).bearer_auth(key).json(&body), model)
+        }
+    };
+    let response = request
+        .header("Accept", "text/event-stream")
+        .send()
+        .map_err(|e| format!("Failed to reach the API: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        return Err(api_error(status, &response.text().unwrap_or_default()));
+    }
+
+    let mut text = String::new();
+    let mut input_tokens = None;
+    let mut output_tokens = None;
+    let mut error = None;
+    crate::mcp::read_events(std::io::BufReader::new(response), |_, data| {
+        if data == "[DONE]" {
+            return false;
+        }
+        let Ok(event) = serde_json::from_str::<Value>(&data) else {
+            return true;
+        };
+        let delta = match provider {
+            QuickAskProvider::Anthropic => match event["type"].as_str() {
+                Some("message_start") => {
+                    input_tokens = event["message"]["usage"]["input_tokens"].as_u64();
+                    None
+                }
+                Some("content_block_delta") => event["delta"]["text"].as_str(),
+                Some("message_delta") => {
+                    output_tokens = event["usage"]["output_tokens"].as_u64();
+                    None
+                }
+                Some("error") => {
+                    error = event["error"]["message"].as_str().map(String::from);
+                    return false;
+                }
+                _ => None,
+            },
+            QuickAskProvider::OpenAI => {
+                if let Some(usage) = event.get("usage").filter(|u| u.is_object()) {
+                    input_tokens = usage["prompt_tokens"].as_u64();
+                    output_tokens = usage["completion_tokens"].as_u64();
+                }
+                event["choices"][0]["delta"]["content"].as_str()
+            }
+        };
+        if let Some(delta) = delta.filter(|d| !d.is_empty()) {
+            text.push_str(delta);
+            let _ = on_delta.send(QuickAskEvent::Delta {
+                text: delta.to_string(),
+            });
+        }
+        true
+    });
+    if let Some(error) = error {
+        return Err(format!("API error: {}", error));
+    }
+    let _ = on_delta.send(QuickAskEvent::Done {
+        text: text.clone(),
+        model,
+        input_tokens,
+        output_tokens,
+    });
+    Ok(text)
+}
+
+/// Ask the Anthropic or OpenAI API a one-shot question, streaming the answer through
+/// `on_delta`. The files in `context_paths` are attached ahead of the prompt. Returns the
+/// full answer.
+#[tauri::command]
+pub async fn quick_ask(
+    prompt: String,
+    context_paths: Option<Vec<String>>,
+    options: Option<QuickAskOptions>,
+    on_delta: Channel<QuickAskEvent>,
+) -> Result<String, String> {
+    if prompt.trim().is_empty() {
+        return Err("Prompt is empty".to_string());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        ask(
+            &prompt,
+            &context_paths.unwrap_or_default(),
+            options.unwrap_or_default(),
+            &on_delta,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,855 @@
+//! Projects on another machine, reached over SSH. Files are read, written and listed over SFTP
+//! (the sftp subsystem of the system ssh client, so ~/.ssh/config and the agent apply), searches
+//! run grep on the host, and watching polls the tree for changes. Commands that work on files
+//! take an optional `location` naming the host; without one they stay local.
+
+use crate::watcher::WatchEvent;
+use std::collections::HashMap;
+use std::io::{BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::ipc::Channel;
+
+const SSH_OPTIONS: [&str; 8] = [
+    // Nobody can answer a password or host key prompt
+    "-o",
+    "BatchMode=yes",
+    "-o",
+    "ConnectTimeout=15",
+    "-o",
+    "ServerAliveInterval=15",
+    "-o",
+    "ServerAliveCountMax=3",
+];
+/// Files larger than this aren't read into memory
+const MAX_READ: u64 = 50 * 1024 * 1024;
+const CHUNK: u32 = 32 * 1024;
+/// Largest SFTP message accepted: OpenSSH's 256 KiB limit plus room for the header. The length
+/// comes from the wire, so anything bigger is treated as a broken connection, not allocated.
+const MAX_FRAME: usize = 256 * 1024 + 1024;
+/// How often a watched remote directory is compared with its last listing
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+/// A watched tree is listed up to this many files
+const MAX_POLLED_FILES: usize = 20_000;
+/// Changed files up to this size come with their content, as local watch events do
+const MAX_CHANGED_CONTENT: u64 = 1024 * 1024;
+/// Directories never listed when watching or searched, as for local listings
+pub(crate) const SKIPPED_DIRS: [&str; 7] = [
+    "node_modules",
+    ".git",
+    "target",
+    "dist",
+    "__pycache__",
+    ".next",
+    ".cache",
+];
+
+// SFTP version 3 packet types and status codes
+const SSH_FXP_INIT: u8 = 1;
+const SSH_FXP_VERSION: u8 = 2;
+const SSH_FXP_OPEN: u8 = 3;
+const SSH_FXP_CLOSE: u8 = 4;
+const SSH_FXP_READ: u8 = 5;
+const SSH_FXP_WRITE: u8 = 6;
+const SSH_FXP_OPENDIR: u8 = 11;
+const SSH_FXP_READDIR: u8 = 12;
+const SSH_FXP_MKDIR: u8 = 14;
+const SSH_FXP_REALPATH: u8 = 16;
+const SSH_FXP_STAT: u8 = 17;
+const SSH_FXP_STATUS: u8 = 101;
+const SSH_FXP_HANDLE: u8 = 102;
+const SSH_FXP_DATA: u8 = 103;
+const SSH_FXP_NAME: u8 = 104;
+const SSH_FXP_ATTRS: u8 = 105;
+const SSH_FX_OK: u32 = 0;
+const SSH_FX_EOF: u32 = 1;
+const SSH_FX_NO_SUCH_FILE: u32 = 2;
+const SSH_FXF_READ: u32 = 0x01;
+const SSH_FXF_WRITE: u32 = 0x02;
+const SSH_FXF_CREAT: u32 = 0x08;
+const SSH_FXF_TRUNC: u32 = 0x10;
+
+/// Where a command's paths are: a host from ~/.ssh/config, or user@hostname
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Location {
+    pub(crate) host: String,
+}
+
+/// Hosts are passed to ssh as an argument, so one starting with a dash would be an option
+pub(crate) fn check_host(host: &str) -> Result<(), String> {
+    if host.is_empty() || host.starts_with('-') || host.contains(char::is_whitespace) {
+        return Err(format!("Invalid host: {}", host));
+    }
+    Ok(())
+}
+
+fn ssh_program() -> Result<String, String> {
+    crate::which::find("ssh")
+        .map(|path| path.to_string_lossy().to_string())
+        .ok_or_else(|| "ssh is not installed".to_string())
+}
+
+/// A path for a shell on the host, with a leading ~ left unquoted so it still expands
+pub(crate) fn shell_path(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("~/{}", crate::shell_quote(rest)),
+        None if path == "~" => "~".to_string(),
+        None => crate::shell_quote(path),
+    }
+}
+
+/// The command line that opens a login shell on the host in `dir`, for a terminal
+pub(crate) fn ssh_command_line(location: &Location, dir: Option<&str>) -> Result<String, String> {
+    check_host(&location.host)?;
+    let script = format!(
+        "cd {} && exec \"$SHELL\" -l",
+        shell_path(dir.unwrap_or("~"))
+    );
+    Ok(format!(
+        "{} -t {} {}",
+        crate::shell_quote(&ssh_program()?),
+        crate::shell_quote(&location.host),
+        crate::shell_quote(&script)
+    ))
+}
+
+/// Run `script` with the host's shell; its exit code and stdout
+pub(crate) fn exec(location: &Location, script: &str) -> Result<(i32, String), String> {
+    check_host(&location.host)?;
+    let output = Command::new(ssh_program()?)
+        .args(SSH_OPTIONS)
+        .arg("-T")
+        .arg(&location.host)
+        .arg(script)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run ssh: {}", e))?;
+    let code = output.status.code().unwrap_or(-1);
+    // ssh itself exits with 255 when it can't connect
+    if code == 255 {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "Failed to connect to {}: {}",
+            location.host,
+            stderr.trim()
+        ));
+    }
+    Ok((code, String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Attrs {
+    pub(crate) size: Option<u64>,
+    permissions: Option<u32>,
+    /// Seconds since the epoch
+    pub(crate) mtime: Option<u32>,
+}
+
+impl Attrs {
+    pub(crate) fn is_dir(&self) -> bool {
+        self.permissions
+            .is_some_and(|mode| mode & 0o170000 == 0o040000)
+    }
+
+    fn is_symlink(&self) -> bool {
+        self.permissions
+            .is_some_and(|mode| mode & 0o170000 == 0o120000)
+    }
+}
+
+/// Builds the body of a request
+#[derive(Default)]
+struct Packet(Vec<u8>);
+
+impl Packet {
+    fn u32(&mut self, value: u32) -> &mut Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    fn u64(&mut self, value: u64) -> &mut Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    fn bytes(&mut self, value: &[u8]) -> &mut Self {
+        self.u32(value.len() as u32);
+        self.0.extend_from_slice(value);
+        self
+    }
+}
+
+/// Reads the body of a response
+struct Fields<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if self.buf.len() < n {
+            return Err("Malformed SFTP response".to_string());
+        }
+        let (head, rest) = self.buf.split_at(n);
+        self.buf = rest;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], String> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        Ok(String::from_utf8_lossy(self.bytes()?).to_string())
+    }
+
+    fn attrs(&mut self) -> Result<Attrs, String> {
+        let flags = self.u32()?;
+        let mut attrs = Attrs::default();
+        if flags & 0x1 != 0 {
+            attrs.size = Some(self.u64()?);
+        }
+        if flags & 0x2 != 0 {
+            // uid and gid
+            self.take(8)?;
+        }
+        if flags & 0x4 != 0 {
+            attrs.permissions = Some(self.u32()?);
+        }
+        if flags & 0x8 != 0 {
+            let _atime = self.u32()?;
+            attrs.mtime = Some(self.u32()?);
+        }
+        if flags & 0x8000_0000 != 0 {
+            for _ in 0..self.u32()? {
+                self.bytes()?;
+                self.bytes()?;
+            }
+        }
+        Ok(attrs)
+    }
+}
+
+/// The code of a STATUS response
+fn status_code(payload: &[u8]) -> Option<u32> {
+    Fields { buf: payload }.u32().ok()
+}
+
+/// One SFTP session, over an ssh process
+pub(crate) struct Sftp {
+    host: String,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u32,
+    home: String,
+    /// Set once the connection failed; the session is replaced on its next use
+    broken: bool,
+}
+
+impl Drop for Sftp {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Sftp {
+    fn connect(host: &str) -> Result<Sftp, String> {
+        check_host(host)?;
+        let mut child = Command::new(ssh_program()?)
+            .args(SSH_OPTIONS)
+            .args(["-T", "-s", host, "sftp"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run ssh: {}", e))?;
+        let mut sftp = Sftp {
+            host: host.to_string(),
+            stdin: child.stdin.take().unwrap(),
+            stdout: BufReader::new(child.stdout.take().unwrap()),
+            child,
+            next_id: 1,
+            home: String::new(),
+            broken: false,
+        };
+        let handshake = sftp.handshake();
+        if let Err(e) = handshake {
+            // ssh says why it couldn't connect on stderr. It may still be connected, talking
+            // to something that isn't an SFTP server, so stop it rather than wait for it.
+            let _ = sftp.child.kill();
+            let _ = sftp.child.wait();
+            let mut stderr = String::new();
+            if let Some(mut pipe) = sftp.child.stderr.take() {
+                let _ = pipe.read_to_string(&mut stderr);
+            }
+            let reason = stderr
+                .lines()
+                .map(str::trim)
+                .rfind(|line| !line.is_empty())
+                .map(str::to_string)
+                .unwrap_or(e);
+            return Err(format!("Failed to connect to {}: {}", host, reason));
+        }
+        Ok(sftp)
+    }
+
+    fn handshake(&mut self) -> Result<(), String> {
+        let mut init = Packet::default();
+        init.u32(3);
+        self.send(SSH_FXP_INIT, &init.0)?;
+        let (kind, _) = self.receive()?;
+        if kind != SSH_FXP_VERSION {
+            return Err("The host's SFTP server didn't answer".to_string());
+        }
+        self.home = self.realpath(".")?;
+        Ok(())
+    }
+
+    fn send(&mut self, kind: u8, body: &[u8]) -> Result<(), String> {
+        let mut frame = Vec::with_capacity(body.len() + 5);
+        frame.extend_from_slice(&(body.len() as u32 + 1).to_be_bytes());
+        frame.push(kind);
+        frame.extend_from_slice(body);
+        let result = self
+            .stdin
+            .write_all(&frame)
+            .and_then(|_| self.stdin.flush());
+        result.map_err(|e| {
+            self.broken = true;
+            format!("Lost the connection to {}: {}", self.host, e)
+        })
+    }
+
+    fn receive(&mut self) -> Result<(u8, Vec<u8>), String> {
+        let mut read = || -> std::io::Result<(u8, Vec<u8>)> {
+            let mut len = [0u8; 4];
+            self.stdout.read_exact(&mut len)?;
+            let len = u32::from_be_bytes(len) as usize;
+            if len > MAX_FRAME {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("SFTP message of {} bytes", len),
+                ));
+            }
+            let mut frame = vec![0u8; len];
+            self.stdout.read_exact(&mut frame)?;
+            match frame.split_first() {
+                Some((kind, body)) => Ok((*kind, body.to_vec())),
+                None => Err(std::io::ErrorKind::InvalidData.into()),
+            }
+        };
+        read().map_err(|e| {
+            self.broken = true;
+            format!("Lost the connection to {}: {}", self.host, e)
+        })
+    }
+
+    /// Send a request and wait for its response: the response type and the body after the id
+    fn request(&mut self, kind: u8, body: &Packet) -> Result<(u8, Vec<u8>), String> {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        let mut frame = Packet::default();
+        frame.u32(id);
+        frame.0.extend_from_slice(&body.0);
+        self.send(kind, &frame.0)?;
+        let (kind, body) = self.receive()?;
+        let mut fields = Fields { buf: &body };
+        if fields.u32()? != id {
+            self.broken = true;
+            return Err("Out-of-order SFTP response".to_string());
+        }
+        Ok((kind, fields.buf.to_vec()))
+    }
+
+    /// The error for a STATUS response, or the response type that wasn't expected
+    fn failure(&self, kind: u8, body: &[u8], path: &str) -> String {
+        if kind != SSH_FXP_STATUS {
+            return format!("Unexpected SFTP response {} for {}", kind, path);
+        }
+        let mut fields = Fields { buf: body };
+        let code = fields.u32().unwrap_or(u32::MAX);
+        let message = fields.string().unwrap_or_default();
+        match code {
+            SSH_FX_NO_SUCH_FILE => format!("{}:{} not found", self.host, path),
+            _ => format!("{}:{}: {}", self.host, path, message),
+        }
+    }
+
+    /// Expect an OK status
+    fn ok(&mut self, kind: u8, body: &Packet, path: &str) -> Result<(), String> {
+        let (response, payload) = self.request(kind, body)?;
+        let code = status_code(&payload);
+        match (response, code) {
+            (SSH_FXP_STATUS, Some(SSH_FX_OK)) => Ok(()),
+            _ => Err(self.failure(response, &payload, path)),
+        }
+    }
+
+    /// `path` with a leading ~ replaced by the home directory on the host
+    pub(crate) fn resolve(&self, path: &str) -> String {
+        match path.strip_prefix("~/") {
+            Some(rest) => format!("{}/{}", self.home.trim_end_matches('/'), rest),
+            None if path == "~" || path.is_empty() => self.home.clone(),
+            None => path.to_string(),
+        }
+    }
+
+    fn realpath(&mut self, path: &str) -> Result<String, String> {
+        let mut body = Packet::default();
+        body.bytes(path.as_bytes());
+        let (kind, payload) = self.request(SSH_FXP_REALPATH, &body)?;
+        if kind != SSH_FXP_NAME {
+            return Err(self.failure(kind, &payload, path));
+        }
+        let mut fields = Fields { buf: &payload };
+        fields.u32()?;
+        fields.string()
+    }
+
+    /// Attributes of `path`, following symlinks; None when it doesn't exist
+    pub(crate) fn stat(&mut self, path: &str) -> Result<Option<Attrs>, String> {
+        let path = self.resolve(path);
+        let mut body = Packet::default();
+        body.bytes(path.as_bytes());
+        let (kind, payload) = self.request(SSH_FXP_STAT, &body)?;
+        match kind {
+            SSH_FXP_ATTRS => Ok(Some(Fields { buf: &payload }.attrs()?)),
+            SSH_FXP_STATUS if status_code(&payload) == Some(SSH_FX_NO_SUCH_FILE) => Ok(None),
+            _ => Err(self.failure(kind, &payload, &path)),
+        }
+    }
+
+    fn open(&mut self, path: &str, flags: u32) -> Result<Vec<u8>, String> {
+        let mut body = Packet::default();
+        body.bytes(path.as_bytes()).u32(flags).u32(0);
+        let (kind, payload) = self.request(SSH_FXP_OPEN, &body)?;
+        if kind != SSH_FXP_HANDLE {
+            return Err(self.failure(kind, &payload, path));
+        }
+        Ok(Fields { buf: &payload }.bytes()?.to_vec())
+    }
+
+    fn close(&mut self, handle: &[u8], path: &str) -> Result<(), String> {
+        let mut body = Packet::default();
+        body.bytes(handle);
+        self.ok(SSH_FXP_CLOSE, &body, path)
+    }
+
+    pub(crate) fn read(&mut self, path: &str) -> Result<Vec<u8>, String> {
+        let path = self.resolve(path);
+        let size = self
+            .stat(&path)?
+            .ok_or_else(|| format!("{}:{} not found", self.host, path))?
+            .size
+            .unwrap_or(0);
+        if size > MAX_READ {
+            return Err(format!("{}:{} is too large to open", self.host, path));
+        }
+        let handle = self.open(&path, SSH_FXF_READ)?;
+        let mut data = Vec::with_capacity(size as usize);
+        loop {
+            let mut body = Packet::default();
+            body.bytes(&handle).u64(data.len() as u64).u32(CHUNK);
+            let (kind, payload) = self.request(SSH_FXP_READ, &body)?;
+            match kind {
+                SSH_FXP_DATA => data.extend_from_slice(Fields { buf: &payload }.bytes()?),
+                SSH_FXP_STATUS if status_code(&payload) == Some(SSH_FX_EOF) => break,
+                _ => {
+                    let error = self.failure(kind, &payload, &path);
+                    let _ = self.close(&handle, &path);
+                    return Err(error);
+                }
+            }
+            if data.len() as u64 > MAX_READ {
+                let _ = self.close(&handle, &path);
+                return Err(format!("{}:{} is too large to open", self.host, path));
+            }
+        }
+        self.close(&handle, &path)?;
+        Ok(data)
+    }
+
+    /// Replace the file's contents, creating it and its parent directories as needed
+    pub(crate) fn write(&mut self, path: &str, data: &[u8]) -> Result<(), String> {
+        let path = self.resolve(path);
+        if let Some((parent, _)) = path.rsplit_once('/').filter(|(p, _)| !p.is_empty()) {
+            self.mkdir_all(parent)?;
+        }
+        let handle = self.open(&path, SSH_FXF_WRITE | SSH_FXF_CREAT | SSH_FXF_TRUNC)?;
+        let mut offset = 0;
+        for chunk in data.chunks(CHUNK as usize) {
+            let mut body = Packet::default();
+            body.bytes(&handle).u64(offset).bytes(chunk);
+            if let Err(e) = self.ok(SSH_FXP_WRITE, &body, &path) {
+                let _ = self.close(&handle, &path);
+                return Err(e);
+            }
+            offset += chunk.len() as u64;
+        }
+        self.close(&handle, &path)
+    }
+
+    /// Create the directory and any missing parents; its resolved path
+    pub(crate) fn mkdir_all(&mut self, path: &str) -> Result<String, String> {
+        let path = self.resolve(path);
+        let mut current = String::new();
+        for part in path.split('/') {
+            if part.is_empty() {
+                if current.is_empty() && path.starts_with('/') {
+                    current.push('/');
+                }
+                continue;
+            }
+            if !current.is_empty() && !current.ends_with('/') {
+                current.push('/');
+            }
+            current.push_str(part);
+            match self.stat(&current)? {
+                Some(attrs) if attrs.is_dir() => continue,
+                Some(_) => return Err(format!("{}:{} is not a directory", self.host, current)),
+                None => {
+                    let mut body = Packet::default();
+                    body.bytes(current.as_bytes()).u32(0);
+                    self.ok(SSH_FXP_MKDIR, &body, &current)?;
+                }
+            }
+        }
+        Ok(path)
+    }
+
+    /// The entries of a directory, without . and ..; symlinks are followed
+    pub(crate) fn list(&mut self, path: &str) -> Result<Vec<(String, Attrs)>, String> {
+        let path = self.resolve(path);
+        let mut body = Packet::default();
+        body.bytes(path.as_bytes());
+        let (kind, payload) = self.request(SSH_FXP_OPENDIR, &body)?;
+        if kind != SSH_FXP_HANDLE {
+            return Err(self.failure(kind, &payload, &path));
+        }
+        let handle = Fields { buf: &payload }.bytes()?.to_vec();
+        let mut entries = Vec::new();
+        loop {
+            let mut body = Packet::default();
+            body.bytes(&handle);
+            let (kind, payload) = self.request(SSH_FXP_READDIR, &body)?;
+            if kind == SSH_FXP_STATUS && status_code(&payload) == Some(SSH_FX_EOF) {
+                break;
+            }
+            if kind != SSH_FXP_NAME {
+                let error = self.failure(kind, &payload, &path);
+                let _ = self.close(&handle, &path);
+                return Err(error);
+            }
+            let mut fields = Fields { buf: &payload };
+            for _ in 0..fields.u32()? {
+                let name = fields.string()?;
+                let _long_name = fields.bytes()?;
+                let attrs = fields.attrs()?;
+                if name != "." && name != ".." {
+                    entries.push((name, attrs));
+                }
+            }
+        }
+        self.close(&handle, &path)?;
+        for (name, attrs) in entries.iter_mut().filter(|(_, a)| a.is_symlink()) {
+            let target = format!("{}/{}", path.trim_end_matches('/'), name);
+            if let Ok(Some(resolved)) = self.stat(&target) {
+                *attrs = resolved;
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Open SFTP sessions by host, made on first use
+#[derive(Clone)]
+pub struct RemoteManager {
+    sessions: Arc<Mutex<HashMap<String, Arc<Mutex<Sftp>>>>>,
+}
+
+impl RemoteManager {
+    pub fn new() -> Self {
+        Self {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn session(&self, host: &str) -> Result<Arc<Mutex<Sftp>>, String> {
+        if let Some(session) = self.sessions.lock().unwrap().get(host) {
+            return Ok(session.clone());
+        }
+        // Connect outside the lock so other hosts aren't held up
+        let session = Arc::new(Mutex::new(Sftp::connect(host)?));
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(host.to_string(), session.clone());
+        Ok(session)
+    }
+
+    /// Run `f` with the host's session, connecting again once if the connection had dropped.
+    /// Blocks; see `run` for commands.
+    pub(crate) fn with<T>(
+        &self,
+        location: &Location,
+        f: impl Fn(&mut Sftp) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let attempt = || {
+            let session = self.session(&location.host)?;
+            let mut sftp = session.lock().unwrap();
+            let result = f(&mut sftp);
+            if sftp.broken {
+                self.sessions.lock().unwrap().remove(&location.host);
+            }
+            Ok::<_, String>((result, sftp.broken))
+        };
+        match attempt()? {
+            (result, false) => result,
+            // The connection had dropped (say the laptop slept); once more on a new one
+            (_, true) => attempt()?.0,
+        }
+    }
+
+    pub(crate) async fn run<T: Send + 'static>(
+        &self,
+        location: Location,
+        f: impl Fn(&mut Sftp) -> Result<T, String> + Send + 'static,
+    ) -> Result<T, String> {
+        let manager = self.clone();
+        tauri::async_runtime::spawn_blocking(move || manager.with(&location, f))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+}
+
+/// Files under `root` on the host whose contents match, found with grep there. Regexes go
+/// to grep -P, else -E, to narrow down the files the caller then matches exactly.
+pub(crate) fn grep_files(
+    location: &Location,
+    root: &str,
+    query: &str,
+    regex: bool,
+    case_sensitive: bool,
+    whole_word: bool,
+) -> Result<Vec<String>, String> {
+    // -s: unreadable files only show in the exit status, which is then 2
+    let mut flags = String::from("-rlIZs");
+    if !case_sensitive {
+        flags.push('i');
+    }
+    if whole_word {
+        flags.push('w');
+    }
+    let excludes: String = SKIPPED_DIRS
+        .iter()
+        .map(|dir| format!(" --exclude-dir={}", crate::shell_quote(dir)))
+        .collect();
+    let grep = |dialect: &str| {
+        format!(
+            "grep {} {}{} -e {} .",
+            flags,
+            dialect,
+            excludes,
+            crate::shell_quote(query)
+        )
+    };
+    let script = match regex {
+        // Not every grep has -P
+        true => format!(
+            "cd {} && if echo | grep -qP '' 2>/dev/null; then {}; else {}; fi",
+            shell_path(root),
+            grep("-P"),
+            grep("-E")
+        ),
+        false => format!("cd {} && {}", shell_path(root), grep("-F")),
+    };
+    let (code, stdout) = exec(location, &script)?;
+    // 2 is also an unreadable file somewhere, which doesn't undo the matches found elsewhere
+    if code > 1 && stdout.is_empty() {
+        return Err(format!("Search on {} failed", location.host));
+    }
+    let root = root.trim_end_matches('/');
+    let mut files: Vec<String> = stdout
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(|path| format!("{}/{}", root, path.trim_start_matches("./")))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Stops a remote watch when dropped
+pub(crate) struct Poller {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// (size, mtime) of each file under `dir` with one of `extensions` (any when empty). The
+/// session is taken per directory, so file commands to the host go on between them.
+fn list_tree(
+    manager: &RemoteManager,
+    location: &Location,
+    dir: &str,
+    extensions: &[String],
+) -> Result<HashMap<String, (u64, u32)>, String> {
+    let mut files = HashMap::new();
+    let root = manager.with(location, |sftp| Ok(sftp.resolve(dir)))?;
+    let mut pending = vec![root.clone()];
+    while let Some(dir) = pending.pop() {
+        // A subdirectory that can't be read is left out; a dropped connection still fails
+        let entries = manager.with(location, |sftp| match sftp.list(&dir) {
+            Ok(entries) => Ok(Some(entries)),
+            Err(_) if !sftp.broken && dir != root => Ok(None),
+            Err(e) => Err(e),
+        })?;
+        for (name, attrs) in entries.into_iter().flatten() {
+            let path = format!("{}/{}", dir.trim_end_matches('/'), name);
+            if attrs.is_dir() {
+                if !SKIPPED_DIRS.contains(&name.as_str()) {
+                    pending.push(path);
+                }
+                continue;
+            }
+            let wanted = extensions.is_empty()
+                || name
+                    .rsplit_once('.')
+                    .is_some_and(|(_, ext)| extensions.contains(&ext.to_lowercase()));
+            if wanted {
+                files.insert(path, (attrs.size.unwrap_or(0), attrs.mtime.unwrap_or(0)));
+            }
+            if files.len() >= MAX_POLLED_FILES {
+                return Ok(files);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Watch `dir` on the host by listing it every few seconds, sending the same events as a
+/// local watch
+pub(crate) fn poll(
+    manager: RemoteManager,
+    location: Location,
+    dir: String,
+    extensions: Vec<String>,
+    on_event: Channel<WatchEvent>,
+) -> Poller {
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = stop.clone();
+    let extensions: Vec<String> = extensions.iter().map(|e| e.to_lowercase()).collect();
+    crate::crash::spawn(
+        "remote",
+        format!("watch:{}:{}", location.host, dir),
+        move || {
+            let mut known: Option<HashMap<String, (u64, u32)>> = None;
+            let mut failing = false;
+            while !stopped.load(Ordering::Relaxed) {
+                match list_tree(&manager, &location, &dir, &extensions) {
+                    Ok(files) => {
+                        failing = false;
+                        if let Some(known) = &known {
+                            send_changes(&manager, &location, known, &files, &on_event);
+                        }
+                        known = Some(files);
+                    }
+                    // Once per outage rather than on every poll
+                    Err(message) if !failing => {
+                        failing = true;
+                        let _ = on_event.send(WatchEvent::Error { message });
+                    }
+                    Err(_) => {}
+                }
+                let wait = crate::power::scaled(POLL_INTERVAL);
+                let mut waited = Duration::ZERO;
+                while waited < wait && !stopped.load(Ordering::Relaxed) {
+                    std::thread::sleep(Duration::from_millis(250));
+                    waited += Duration::from_millis(250);
+                }
+            }
+            Ok(())
+        },
+    );
+    Poller { stop }
+}
+
+fn send_changes(
+    manager: &RemoteManager,
+    location: &Location,
+    before: &HashMap<String, (u64, u32)>,
+    after: &HashMap<String, (u64, u32)>,
+    on_event: &Channel<WatchEvent>,
+) {
+    for (path, stamp) in after {
+        match before.get(path) {
+            None => {
+                let _ = on_event.send(WatchEvent::Created { path: path.clone() });
+            }
+            Some(previous) if previous != stamp => {
+                let content = match stamp.0 <= MAX_CHANGED_CONTENT {
+                    true => manager
+                        .with(location, |sftp| sftp.read(path))
+                        .map(|data| String::from_utf8_lossy(&data).to_string())
+                        .unwrap_or_default(),
+                    false => String::new(),
+                };
+                let _ = on_event.send(WatchEvent::Changed {
+                    path: path.clone(),
+                    content,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    for path in before.keys().filter(|path| !after.contains_key(*path)) {
+        let _ = on_event.send(WatchEvent::Removed { path: path.clone() });
+    }
+}
+
+/// Host aliases from ~/.ssh/config, without wildcard patterns
+#[tauri::command]
+pub fn list_ssh_hosts() -> Vec<String> {
+    let path = std::path::Path::new(&crate::get_home_dir())
+        .join(".ssh")
+        .join("config");
+    let config = std::fs::read_to_string(path).unwrap_or_default();
+    let mut hosts: Vec<String> = Vec::new();
+    for line in config.lines() {
+        let line = line.trim();
+        let Some((keyword, rest)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        if !keyword.eq_ignore_ascii_case("host") {
+            continue;
+        }
+        for host in rest.split_whitespace() {
+            let pattern = host.contains(['*', '?', '!']);
+            if !pattern && !hosts.iter().any(|h| h == host) {
+                hosts.push(host.to_string());
+            }
+        }
+    }
+    hosts
+}
+
+/// Close the SFTP session to `host`; the next remote command opens a new one
+#[tauri::command]
+pub fn disconnect_remote(state: tauri::State<'_, RemoteManager>, host: String) {
+    state.sessions.lock().unwrap().remove(&host);
+}
@@ -0,0 +1,226 @@
+use crate::git::{git_command, resolve_repo, run_git, run_git_command, toplevel, FileDiff};
+use std::path::Path;
+
+/// The accepted state of the working tree: a shadow commit that starts as the checkpoint taken
+/// before an agent run and absorbs each approved file. Pending changes are the diff from it.
+/// Refs under refs/worktree/ aren't shared, so each linked worktree keeps its own queue.
+const REVIEW_REF: &str = "refs/worktree/ade/review";
+
+#[derive(serde::Serialize)]
+pub struct PendingReviews {
+    /// Checkpoint the queue started from; None when no review has been started
+    checkpoint_id: Option<String>,
+    files: Vec<FileDiff>,
+}
+
+fn review_base(root: &str) -> Option<String> {
+    run_git(root, ["rev-parse", "--verify", "--quiet", REVIEW_REF])
+        .ok()
+        .map(|out| String::from_utf8_lossy(&out).trim().to_string())
+}
+
+/// Diff from the review base to the current working tree
+fn pending_diffs(root: &str, base: &str) -> Result<Vec<FileDiff>, String> {
+    let current = crate::checkpoint::snapshot_tree(root)?;
+    let mut args = crate::git::diff_args(None);
+    args.extend([format!("{}^{{tree}}", base), current]);
+    Ok(crate::git::parse_diff(&run_git(root, &args)?))
+}
+
+/// Both sides of the pending change at `path`, so a rename is approved or reverted as a whole
+fn change_paths(root: &str, base: &str, path: &str) -> Result<Vec<String>, String> {
+    let diffs = pending_diffs(root, base)?;
+    let diff = diffs
+        .iter()
+        .find(|d| d.new_path.as_deref() == Some(path) || d.old_path.as_deref() == Some(path))
+        .ok_or_else(|| format!("No pending change for {}", path))?;
+    let mut paths: Vec<String> = [&diff.old_path, &diff.new_path]
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+    paths.dedup();
+    Ok(paths)
+}
+
+fn pending(root: &str) -> Result<PendingReviews, String> {
+    let Some(base) = review_base(root) else {
+        return Ok(PendingReviews {
+            checkpoint_id: None,
+            files: Vec::new(),
+        });
+    };
+    let checkpoint_id = run_git(
+        root,
+        [
+            "log",
+            "-1",
+            "--format=%(trailers:key=Checkpoint,valueonly)",
+            &base,
+        ],
+    )
+    .ok()
+    .map(|out| String::from_utf8_lossy(&out).trim().to_string())
+    .filter(|id| !id.is_empty());
+    Ok(PendingReviews {
+        checkpoint_id,
+        files: pending_diffs(root, &base)?,
+    })
+}
+
+/// Start a review queue for `root` from a fresh checkpoint. While earlier changes are still
+/// pending the existing queue is kept, so consecutive agent runs accumulate into one changeset.
+pub(crate) fn begin(root: &str) -> Result<(), String> {
+    let root = toplevel(&resolve_repo(root))?;
+    if let Some(base) = review_base(&root) {
+        if !pending_diffs(&root, &base)?.is_empty() {
+            return Ok(());
+        }
+    }
+    let checkpoint = crate::checkpoint::create(&root, "Before agent run")?;
+    // The base is its own commit so approvals can move it without touching the checkpoint
+    let message = format!("Review base\n\nCheckpoint: {}", checkpoint.id);
+    let base = commit_tree(
+        &root,
+        &format!("{}^{{tree}}", checkpoint.commit),
+        None,
+        &message,
+    )?;
+    run_git(&root, ["update-ref", REVIEW_REF, &base]).map(|_| ())
+}
+
+fn commit_tree(
+    root: &str,
+    tree: &str,
+    parent: Option<&str>,
+    message: &str,
+) -> Result<String, String> {
+    let mut cmd = git_command(root);
+    cmd.env("GIT_AUTHOR_NAME", "ADE")
+        .env("GIT_AUTHOR_EMAIL", "ade@localhost")
+        .env("GIT_COMMITTER_NAME", "ADE")
+        .env("GIT_COMMITTER_EMAIL", "ade@localhost")
+        .args(["commit-tree", tree, "-m", message]);
+    if let Some(parent) = parent {
+        cmd.args(["-p", parent]);
+    }
+    Ok(String::from_utf8_lossy(&run_git_command(&mut cmd)?)
+        .trim()
+        .to_string())
+}
+
+/// Take the working tree's version of `paths` into the review base (removing ones that no
+/// longer exist), via a throwaway index
+fn accept_into_base(root: &str, base: &str, paths: &[String]) -> Result<(), String> {
+    let (_, tmp_index) = crate::git::temp_index(root, "review")?;
+    let result = run_git_command(
+        git_command(root)
+            .env("GIT_INDEX_FILE", &tmp_index)
+            .args(["read-tree", base]),
+    )
+    .and_then(|_| {
+        run_git_command(
+            git_command(root)
+                .env("GIT_INDEX_FILE", &tmp_index)
+                .args(["update-index", "--add", "--remove", "--"])
+                .args(paths),
+        )
+    })
+    .and_then(|_| {
+        run_git_command(
+            git_command(root)
+                .env("GIT_INDEX_FILE", &tmp_index)
+                .arg("write-tree"),
+        )
+    });
+    let _ = std::fs::remove_file(&tmp_index);
+    let tree = String::from_utf8_lossy(&result?).trim().to_string();
+
+    // Keep the checkpoint trailer so the queue still knows where it started
+    let message = run_git(root, ["log", "-1", "--format=%B", base])?;
+    let commit = commit_tree(
+        root,
+        &tree,
+        Some(base),
+        String::from_utf8_lossy(&message).trim(),
+    )?;
+    run_git(root, ["update-ref", REVIEW_REF, &commit, base]).map(|_| ())
+}
+
+/// Start reviewing changes in `repo` from now on, e.g. before an agent runs in a terminal;
+/// headless runs started with run_agent begin a review automatically
+#[tauri::command]
+pub async fn begin_review(repo: String) -> Result<PendingReviews, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        begin(&repo)?;
+        pending(&toplevel(&resolve_repo(&repo))?)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Per-file diffs made since the review started that haven't been approved or reverted
+#[tauri::command]
+pub async fn list_pending_reviews(repo: String) -> Result<PendingReviews, String> {
+    tauri::async_runtime::spawn_blocking(move || pending(&toplevel(&resolve_repo(&repo))?))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Accept the change to `path` as it is now. Returns what is still pending.
+#[tauri::command]
+pub async fn approve_change(repo: String, path: String) -> Result<PendingReviews, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let base = review_base(&root).ok_or("No review in progress")?;
+        let path = crate::git::repo_relative(&root, &path);
+        let paths = change_paths(&root, &base, &path)?;
+        accept_into_base(&root, &base, &paths)?;
+        pending(&root)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Put `path` back to its state before the change. The current content is checkpointed and
+/// logged to the audit log first, so a revert can itself be undone. Returns what is still pending.
+#[tauri::command]
+pub async fn revert_change(repo: String, path: String) -> Result<PendingReviews, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = toplevel(&resolve_repo(&repo))?;
+        let base = review_base(&root).ok_or("No review in progress")?;
+        let path = crate::git::repo_relative(&root, &path);
+        let paths = change_paths(&root, &base, &path)?;
+
+        let backup = crate::checkpoint::create(&root, &format!("Before revert of {}", path))?;
+        crate::audit::record(
+            "revert_change",
+            serde_json::json!({
+                "repo": root,
+                "paths": paths,
+                "checkpoint": backup.id,
+            }),
+        );
+
+        for rel in &paths {
+            let target = Path::new(&root).join(rel);
+            match run_git(&root, ["cat-file", "blob", &format!("{}:{}", base, rel)]) {
+                Ok(content) => {
+                    if let Some(parent) = target.parent() {
+                        std::fs::create_dir_all(parent)
+                            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+                    }
+                    std::fs::write(&target, content)
+                        .map_err(|e| format!("Failed to restore {}: {}", rel, e))?;
+                }
+                // Not in the base: the change created it
+                Err(_) if target.exists() => std::fs::remove_file(&target)
+                    .map_err(|e| format!("Failed to remove {}: {}", rel, e))?,
+                Err(_) => {}
+            }
+        }
+        pending(&root)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,997 @@
+use crate::agents::{AgentEvent, AgentRunManager, RunAgentOptions};
+use crate::notifications::AgentSignal;
+use crate::session_changes::SessionChangeTracker;
+use chrono::{Datelike, Local, NaiveDate, TimeZone, Timelike};
+use std::collections::{HashSet, VecDeque};
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+/// How often due schedules are checked
+const TICK: Duration = Duration::from_secs(20);
+/// An occurrence found this long after its time counts as missed (app closed, machine asleep)
+const GRACE_MS: u64 = 2 * 60 * 1000;
+/// Most missed occurrences replayed by the run_all catch-up policy
+const MAX_CATCH_UP_RUNS: usize = 10;
+const MAX_HISTORY: usize = 500;
+/// Output kept per run, from the end
+const MAX_OUTPUT_CHARS: usize = 16 * 1024;
+const DEFAULT_TIMEOUT_SECS: u64 = 60 * 60;
+/// Occurrences are searched this many days ahead; further than that an expression never matches
+const MAX_SEARCH_DAYS: i64 = 366 * 5;
+/// Most occurrences preview_cron lists
+const MAX_PREVIEW: usize = 100;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type")]
+pub enum ScheduledAction {
+    /// Headless agent run, as run_agent
+    #[serde(rename = "agent")]
+    Agent {
+        prompt: String,
+        tool: Option<String>,
+        model: Option<String>,
+        cwd: Option<String>,
+    },
+    #[serde(rename = "shell")]
+    Shell {
+        command: String,
+        cwd: Option<String>,
+    },
+}
+
+/// What to do about occurrences missed while the app wasn't running
+#[derive(Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatchUp {
+    /// Drop them; only log that they were skipped
+    Skip,
+    /// Run once for all of them
+    #[default]
+    RunOnce,
+    /// Run for each, oldest first, up to MAX_CATCH_UP_RUNS
+    RunAll,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Schedule {
+    id: String,
+    name: String,
+    /// Five-field cron expression in local time, or @hourly, @daily, @weekly, @monthly, @yearly
+    cron: String,
+    action: ScheduledAction,
+    #[serde(default = "default_true")]
+    enabled: bool,
+    #[serde(default)]
+    catch_up: CatchUp,
+    timeout_secs: Option<u64>,
+    created_at: u64,
+    /// Latest occurrence that was run or skipped; missed ones are looked for after it
+    last_slot: Option<u64>,
+    /// Filled in by list_schedules
+    #[serde(default, skip_deserializing)]
+    next_run_at: Option<u64>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ScheduleInput {
+    name: String,
+    cron: String,
+    action: ScheduledAction,
+    enabled: Option<bool>,
+    catch_up: Option<CatchUp>,
+    timeout_secs: Option<u64>,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    TimedOut,
+    Skipped,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    Schedule,
+    CatchUp,
+    Manual,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct ScheduleRun {
+    id: String,
+    schedule_id: String,
+    schedule_name: String,
+    trigger: Trigger,
+    /// The occurrence this run is for; None for manual runs
+    scheduled_for: Option<u64>,
+    started_at: u64,
+    finished_at: Option<u64>,
+    status: RunStatus,
+    exit_code: Option<i32>,
+    /// Tail of the combined output (agent text for agent runs)
+    output: String,
+    /// Why the run failed or was skipped
+    error: Option<String>,
+    cost_usd: Option<f64>,
+}
+
+struct CronSpec {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+const MONTH_NAMES: &[&str] = &[
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// Bitmask of the values a cron field matches. `names` map to `first`, `first + 1`, ….
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], first: u32) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let upper = text.to_ascii_uppercase();
+        let parsed = match names.iter().position(|n| *n == upper) {
+            Some(i) => i as u32 + first,
+            None => text
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid value in cron field: {}", field))?,
+        };
+        if parsed < min || parsed > max {
+            return Err(format!("{} is out of range {}-{}", parsed, min, max));
+        }
+        Ok(parsed)
+    };
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("Invalid step in cron field: {}", field))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (value(a)?, value(b)?)
+        } else {
+            let start = value(range)?;
+            // `5/15` means from 5 to the end in steps of 15
+            (start, if part.contains('/') { max } else { start })
+        };
+        if start > end {
+            return Err(format!("Invalid range in cron field: {}", field));
+        }
+        for v in (start..=end).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_cron(expression: &str) -> Result<CronSpec, String> {
+    let expression = match expression.trim() {
+        "@yearly" | "@annually" => "0 0 1 1 *",
+        "@monthly" => "0 0 1 * *",
+        "@weekly" => "0 0 * * 0",
+        "@daily" | "@midnight" => "0 0 * * *",
+        "@hourly" => "0 * * * *",
+        other => other,
+    };
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+    let [minute, hour, day, month, weekday] = fields[..] else {
+        return Err(format!(
+            "Expected 5 cron fields (minute hour day month weekday), got {}",
+            fields.len()
+        ));
+    };
+    let mut weekdays = parse_field(weekday, 0, 7, WEEKDAY_NAMES, 0)?;
+    // Both 0 and 7 are Sunday
+    if weekdays & (1 << 7) != 0 {
+        weekdays |= 1;
+    }
+    Ok(CronSpec {
+        minutes: parse_field(minute, 0, 59, &[], 0)?,
+        hours: parse_field(hour, 0, 23, &[], 0)?,
+        days: parse_field(day, 1, 31, &[], 0)?,
+        months: parse_field(month, 1, 12, MONTH_NAMES, 1)?,
+        weekdays,
+        any_day: day == "*",
+        any_weekday: weekday == "*",
+    })
+}
+
+impl CronSpec {
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        // As in cron: when both are restricted, either one matching is enough
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+
+    /// First occurrence strictly after `after_ms`, in local time. Times skipped by a DST change
+    /// don't occur.
+    fn next_after(&self, after_ms: u64) -> Option<u64> {
+        let after = Local.timestamp_millis_opt(after_ms as i64).single()?;
+        let start_date = after.date_naive();
+        for offset in 0..MAX_SEARCH_DAYS {
+            let date = start_date + chrono::Duration::days(offset);
+            if !self.matches_day(date) {
+                continue;
+            }
+            for hour in (0..24).filter(|h| self.hours & (1 << h) != 0) {
+                if offset == 0 && hour < after.hour() {
+                    continue;
+                }
+                for minute in (0..60).filter(|m| self.minutes & (1 << m) != 0) {
+                    let Some(naive) = date.and_hms_opt(hour, minute, 0) else {
+                        continue;
+                    };
+                    let Some(time) = Local.from_local_datetime(&naive).earliest() else {
+                        continue;
+                    };
+                    let ms = time.timestamp_millis();
+                    if ms > after_ms as i64 {
+                        return Some(ms as u64);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+fn schedules_path() -> PathBuf {
+    crate::ade_path("schedules.json")
+}
+
+fn history_path() -> PathBuf {
+    crate::ade_path("schedule-history.json")
+}
+
+fn load<T: serde::de::DeserializeOwned>(path: &PathBuf) -> Vec<T> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save<T: serde::Serialize>(path: &PathBuf, items: &[T]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(items).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn append_capped(buffer: &mut String, text: &str) {
+    buffer.push_str(text);
+    if buffer.len() > MAX_OUTPUT_CHARS * 2 {
+        let mut cut = buffer.len() - MAX_OUTPUT_CHARS;
+        while !buffer.is_char_boundary(cut) {
+            cut += 1;
+        }
+        buffer.drain(..cut);
+    }
+}
+
+/// Scheduled agents run unattended, so tell the user how each one ended
+fn notify_finished(app: &tauri::AppHandle, schedule: &Schedule, run: &ScheduleRun) {
+    if !matches!(schedule.action, ScheduledAction::Agent { .. }) {
+        return;
+    }
+    let (signal, outcome) = match run.status {
+        RunStatus::Succeeded => (AgentSignal::Finished, "finished"),
+        RunStatus::Failed => (AgentSignal::Error, "failed"),
+        RunStatus::TimedOut => (AgentSignal::Error, "timed out"),
+        _ => return,
+    };
+    let body = run
+        .error
+        .clone()
+        .or_else(|| {
+            run.output
+                .lines()
+                .rev()
+                .find(|l| !l.trim().is_empty())
+                .map(String::from)
+        })
+        .unwrap_or_default();
+    crate::notifications::notify_background(
+        app,
+        signal,
+        &format!("Scheduled agent \"{}\" {}", schedule.name, outcome),
+        &body,
+    );
+}
+
+fn tail(mut text: String) -> String {
+    if text.len() > MAX_OUTPUT_CHARS {
+        let mut cut = text.len() - MAX_OUTPUT_CHARS;
+        while !text.is_char_boundary(cut) {
+            cut += 1;
+        }
+        text.drain(..cut);
+    }
+    text
+}
+
+/// Runs due schedules in the background. Holds its own handles to the agent runner so it can
+/// start runs without a window.
+#[derive(Clone)]
+pub struct SchedulerManager {
+    /// Serializes reads and writes of the schedule and history files
+    files: Arc<Mutex<()>>,
+    /// Schedules with a run in progress; a schedule never overlaps itself
+    running: Arc<Mutex<HashSet<String>>>,
+    agents: AgentRunManager,
+    changes: SessionChangeTracker,
+}
+
+impl SchedulerManager {
+    pub fn new(agents: AgentRunManager, changes: SessionChangeTracker) -> Self {
+        Self {
+            files: Arc::new(Mutex::new(())),
+            running: Arc::new(Mutex::new(HashSet::new())),
+            agents,
+            changes,
+        }
+    }
+
+    /// The directory schedule `id` runs in
+    pub(crate) fn cwd(&self, id: &str) -> Option<String> {
+        let _guard = self.files.lock().unwrap();
+        let schedules: Vec<Schedule> = load(&schedules_path());
+        Some(schedules.into_iter().find(|s| s.id == id)?.action.cwd())
+    }
+
+    fn edit_history(&self, edit: impl FnOnce(&mut Vec<ScheduleRun>)) {
+        let _guard = self.files.lock().unwrap();
+        let path = history_path();
+        let mut history: Vec<ScheduleRun> = load(&path);
+        edit(&mut history);
+        if history.len() > MAX_HISTORY {
+            let excess = history.len() - MAX_HISTORY;
+            history.drain(..excess);
+        }
+        if let Err(e) = save(&path, &history) {
+            tracing::error!(error = %e, "Failed to save schedule history");
+        }
+    }
+
+    fn record(&self, app: &tauri::AppHandle, run: &ScheduleRun) {
+        self.edit_history(
+            |history| match history.iter_mut().find(|r| r.id == run.id) {
+                Some(existing) => *existing = run.clone(),
+                None => history.push(run.clone()),
+            },
+        );
+        let _ = app.emit("schedule-run", run);
+    }
+
+    fn skipped(&self, app: &tauri::AppHandle, schedule: &Schedule, slot: u64, reason: String) {
+        let now = crate::now_ms();
+        self.record(
+            app,
+            &ScheduleRun {
+                id: format!("{}-{}-skip", schedule.id, slot),
+                schedule_id: schedule.id.clone(),
+                schedule_name: schedule.name.clone(),
+                trigger: Trigger::Schedule,
+                scheduled_for: Some(slot),
+                started_at: now,
+                finished_at: Some(now),
+                status: RunStatus::Skipped,
+                exit_code: None,
+                output: String::new(),
+                error: Some(reason),
+                cost_usd: None,
+            },
+        );
+    }
+
+    /// Run `slots` of `schedule` one after another on a background thread. Returns the first
+    /// run's record, or None if the schedule is already running.
+    fn launch(
+        &self,
+        app: &tauri::AppHandle,
+        schedule: Schedule,
+        slots: Vec<(Option<u64>, Trigger)>,
+    ) -> Option<ScheduleRun> {
+        if slots.is_empty() || !self.running.lock().unwrap().insert(schedule.id.clone()) {
+            return None;
+        }
+        let runs: Vec<ScheduleRun> = slots
+            .into_iter()
+            .map(|(slot, trigger)| ScheduleRun {
+                id: format!("{}-{}", schedule.id, slot.unwrap_or_else(crate::now_ms)),
+                schedule_id: schedule.id.clone(),
+                schedule_name: schedule.name.clone(),
+                trigger,
+                scheduled_for: slot,
+                started_at: 0,
+                finished_at: None,
+                status: RunStatus::Running,
+                exit_code: None,
+                output: String::new(),
+                error: None,
+                cost_usd: None,
+            })
+            .collect();
+        let mut first = runs[0].clone();
+        first.started_at = crate::now_ms();
+
+        let manager = self.clone();
+        let app = app.clone();
+        std::thread::spawn(move || {
+            for mut run in runs {
+                run.started_at = crate::now_ms();
+                manager.record(&app, &run);
+                manager.execute(&app, &schedule, &mut run);
+                run.finished_at = Some(crate::now_ms());
+                manager.record(&app, &run);
+                notify_finished(&app, &schedule, &run);
+            }
+            manager.running.lock().unwrap().remove(&schedule.id);
+        });
+        Some(first)
+    }
+
+    fn execute(&self, app: &tauri::AppHandle, schedule: &Schedule, run: &mut ScheduleRun) {
+        let timeout = Duration::from_secs(schedule.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+        // Runs start here rather than through the invoke handler, so the policy is checked here
+        let cwd = schedule.action.cwd();
+        let (command, args) = match &schedule.action {
+            ScheduledAction::Shell { .. } => (
+                "run_command",
+                serde_json::json!({ "program": "sh", "options": { "cwd": cwd } }),
+            ),
+            ScheduledAction::Agent { .. } => (
+                "run_agent",
+                serde_json::json!({ "options": { "cwd": cwd } }),
+            ),
+        };
+        if let Err(e) = crate::policy::ensure_allowed(app, command, args) {
+            run.status = RunStatus::Failed;
+            run.error = Some(e);
+            return;
+        }
+        let result = match &schedule.action {
+            ScheduledAction::Shell { command, cwd } => run_shell(command, cwd.as_deref(), timeout),
+            ScheduledAction::Agent {
+                prompt,
+                tool,
+                model,
+                cwd,
+            } => self.run_agent(prompt, tool, model, cwd, timeout),
+        };
+        match result {
+            Ok(outcome) => {
+                run.exit_code = outcome.exit_code;
+                run.output = tail(outcome.output);
+                run.cost_usd = outcome.cost_usd;
+                run.status = if outcome.timed_out {
+                    RunStatus::TimedOut
+                } else if outcome.exit_code == Some(0) && !outcome.is_error {
+                    RunStatus::Succeeded
+                } else {
+                    RunStatus::Failed
+                };
+            }
+            Err(e) => {
+                run.status = RunStatus::Failed;
+                run.error = Some(e);
+            }
+        }
+    }
+
+    fn run_agent(
+        &self,
+        prompt: &str,
+        tool: &Option<String>,
+        model: &Option<String>,
+        cwd: &Option<String>,
+        timeout: Duration,
+    ) -> Result<Outcome, String> {
+        let output = Arc::new(Mutex::new(Outcome::default()));
+        let (exit_tx, exit_rx) = std::sync::mpsc::channel();
+        let sink_output = output.clone();
+        let sink: crate::agents::AgentEventSink = Arc::new(move |event| {
+            let mut outcome = sink_output.lock().unwrap();
+            match event {
+                AgentEvent::Text { text } => append_capped(&mut outcome.output, &text),
+                AgentEvent::Stderr { text } => {
+                    append_capped(&mut outcome.output, &format!("{}\n", text))
+                }
+                AgentEvent::Result {
+                    is_error, cost_usd, ..
+                } => {
+                    outcome.is_error = is_error;
+                    outcome.cost_usd = cost_usd;
+                }
+                AgentEvent::Exit { code } => {
+                    let _ = exit_tx.send(code);
+                }
+                _ => {}
+            }
+        });
+        let options = RunAgentOptions {
+            cwd: cwd.clone(),
+            model: model.clone(),
+            ..Default::default()
+        };
+        let tool = tool.as_deref().unwrap_or("claude");
+        let run_id = self
+            .agents
+            .start(self.changes.clone(), tool, prompt, options, sink)?;
+        let (exit_code, timed_out) = match exit_rx.recv_timeout(timeout) {
+            Ok(code) => (code, false),
+            Err(_) => {
+                let _ = self.agents.cancel(run_id);
+                (
+                    exit_rx.recv_timeout(Duration::from_secs(10)).ok().flatten(),
+                    true,
+                )
+            }
+        };
+        let mut outcome = std::mem::take(&mut *output.lock().unwrap());
+        outcome.exit_code = exit_code;
+        outcome.timed_out = timed_out;
+        Ok(outcome)
+    }
+
+    /// Start whatever is due. Missed occurrences are handled by each schedule's catch-up policy.
+    fn tick(&self, app: &tauri::AppHandle) {
+        let now = crate::now_ms();
+        let mut launches = Vec::new();
+        let mut skips = Vec::new();
+        {
+            let _guard = self.files.lock().unwrap();
+            let mut schedules: Vec<Schedule> = load(&schedules_path());
+            let mut changed = false;
+            for schedule in schedules.iter_mut().filter(|s| s.enabled) {
+                let Ok(spec) = parse_cron(&schedule.cron) else {
+                    continue;
+                };
+                let mut due: VecDeque<u64> = VecDeque::new();
+                let mut total = 0;
+                let mut cursor = schedule.last_slot.unwrap_or(schedule.created_at);
+                while let Some(next) = spec.next_after(cursor).filter(|&t| t <= now) {
+                    total += 1;
+                    due.push_back(next);
+                    if due.len() > MAX_CATCH_UP_RUNS {
+                        due.pop_front();
+                    }
+                    cursor = next;
+                }
+                let Some(&latest) = due.back() else {
+                    continue;
+                };
+                schedule.last_slot = Some(latest);
+                changed = true;
+
+                let on_time = now - latest <= GRACE_MS;
+                let missed = total - usize::from(on_time);
+                let slots: Vec<(Option<u64>, Trigger)> = match schedule.catch_up {
+                    _ if missed == 0 => vec![(Some(latest), Trigger::Schedule)],
+                    CatchUp::Skip => {
+                        skips.push((
+                            schedule.clone(),
+                            latest,
+                            format!("{} missed occurrence(s) skipped", missed),
+                        ));
+                        if on_time {
+                            vec![(Some(latest), Trigger::Schedule)]
+                        } else {
+                            Vec::new()
+                        }
+                    }
+                    CatchUp::RunOnce => vec![(
+                        Some(latest),
+                        if on_time {
+                            Trigger::Schedule
+                        } else {
+                            Trigger::CatchUp
+                        },
+                    )],
+                    CatchUp::RunAll => {
+                        if total > due.len() {
+                            skips.push((
+                                schedule.clone(),
+                                due[0],
+                                format!("{} older missed occurrence(s) skipped", total - due.len()),
+                            ));
+                        }
+                        due.iter()
+                            .map(|&slot| {
+                                let trigger = if slot == latest && on_time {
+                                    Trigger::Schedule
+                                } else {
+                                    Trigger::CatchUp
+                                };
+                                (Some(slot), trigger)
+                            })
+                            .collect()
+                    }
+                };
+                launches.push((schedule.clone(), slots));
+            }
+            if changed {
+                if let Err(e) = save(&schedules_path(), &schedules) {
+                    tracing::error!(error = %e, "Failed to save schedules");
+                }
+            }
+        }
+
+        for (schedule, slot, reason) in skips {
+            self.skipped(app, &schedule, slot, reason);
+        }
+        for (schedule, slots) in launches {
+            let Some(&(Some(slot), _)) = slots.first() else {
+                continue;
+            };
+            if self.running.lock().unwrap().contains(&schedule.id) {
+                self.skipped(
+                    app,
+                    &schedule,
+                    slot,
+                    "Previous run still in progress".into(),
+                );
+                continue;
+            }
+            self.launch(app, schedule, slots);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Outcome {
+    exit_code: Option<i32>,
+    output: String,
+    timed_out: bool,
+    /// The agent's final result was an error
+    is_error: bool,
+    cost_usd: Option<f64>,
+}
+
+fn run_shell(command: &str, cwd: Option<&str>, timeout: Duration) -> Result<Outcome, String> {
+    let cwd = crate::git::resolve_repo(&cwd.map(String::from).unwrap_or_else(crate::get_home_dir));
+    let mut cmd = std::process::Command::new("sh");
+    // Own process group, so a timeout also kills whatever the command spawned
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    let mut child = cmd
+        .arg("-c")
+        .arg(command)
+        .current_dir(&cwd)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run command: {}", e))?;
+
+    let output = Arc::new(Mutex::new(String::new()));
+    let readers: Vec<_> = [
+        child
+            .stdout
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Read + Send>),
+        child
+            .stderr
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|mut stream| {
+        let output = output.clone();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 8192];
+            while let Ok(n) = stream.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                append_capped(
+                    &mut output.lock().unwrap(),
+                    &String::from_utf8_lossy(&buf[..n]),
+                );
+            }
+        })
+    })
+    .collect();
+
+    let deadline = Instant::now() + timeout;
+    let mut timed_out = false;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(200)),
+            Ok(None) => {
+                timed_out = true;
+                #[cfg(unix)]
+                let _ = std::process::Command::new("kill")
+                    .args(["-KILL", "--", &format!("-{}", child.id())])
+                    .status();
+                let _ = child.kill();
+                break child.wait().ok();
+            }
+            Err(e) => return Err(format!("Failed to wait for command: {}", e)),
+        }
+    };
+    // A background process the command started can keep the pipes open
+    crate::subprocess::join_readers(readers);
+    let output = std::mem::take(&mut *output.lock().unwrap());
+    Ok(Outcome {
+        exit_code: status.and_then(|s| s.code()).filter(|_| !timed_out),
+        output,
+        timed_out,
+        is_error: false,
+        cost_usd: None,
+    })
+}
+
+/// Start checking schedules in the background. Runs left `running` by a previous session are
+/// marked failed first.
+pub fn start(app: &tauri::App) {
+    use tauri::Manager;
+    let app = app.handle().clone();
+    let manager = app.state::<SchedulerManager>().inner().clone();
+    std::thread::spawn(move || {
+        manager.edit_history(|history| {
+            for run in history
+                .iter_mut()
+                .filter(|r| r.status == RunStatus::Running)
+            {
+                run.status = RunStatus::Failed;
+                run.error = Some("Interrupted: the app closed during the run".to_string());
+            }
+        });
+        loop {
+            manager.tick(&app);
+            std::thread::sleep(TICK);
+        }
+    });
+}
+
+impl ScheduledAction {
+    /// Where the action runs: its cwd, or the home directory
+    fn cwd(&self) -> String {
+        let (ScheduledAction::Agent { cwd, .. } | ScheduledAction::Shell { cwd, .. }) = self;
+        crate::git::resolve_repo(&cwd.clone().unwrap_or_else(crate::get_home_dir))
+    }
+}
+
+impl ScheduleInput {
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Schedule name is empty".to_string());
+        }
+        parse_cron(&self.cron)?;
+        let cwd = match &self.action {
+            ScheduledAction::Agent { prompt, cwd, .. } => {
+                if prompt.trim().is_empty() {
+                    return Err("Agent prompt is empty".to_string());
+                }
+                cwd
+            }
+            ScheduledAction::Shell { command, cwd } => {
+                if command.trim().is_empty() {
+                    return Err("Command is empty".to_string());
+                }
+                cwd
+            }
+        };
+        if let Some(cwd) = cwd {
+            if !std::path::Path::new(&crate::git::resolve_repo(cwd)).is_dir() {
+                return Err(format!("Not a directory: {}", cwd));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn with_next_run(mut schedule: Schedule) -> Schedule {
+    schedule.next_run_at = if schedule.enabled {
+        parse_cron(&schedule.cron)
+            .ok()
+            .and_then(|spec| spec.next_after(crate::now_ms()))
+    } else {
+        None
+    };
+    schedule
+}
+
+/// Apply `edit` to the schedule `id` and save
+fn edit_schedule(
+    state: &SchedulerManager,
+    id: &str,
+    edit: impl FnOnce(&mut Schedule) -> Result<(), String>,
+) -> Result<Schedule, String> {
+    let _guard = state.files.lock().unwrap();
+    let mut schedules: Vec<Schedule> = load(&schedules_path());
+    let schedule = schedules
+        .iter_mut()
+        .find(|s| s.id == id)
+        .ok_or_else(|| format!("Schedule not found: {}", id))?;
+    edit(schedule)?;
+    let updated = schedule.clone();
+    save(&schedules_path(), &schedules)?;
+    Ok(with_next_run(updated))
+}
+
+#[tauri::command]
+pub fn list_schedules(state: tauri::State<'_, SchedulerManager>) -> Result<Vec<Schedule>, String> {
+    let _guard = state.files.lock().unwrap();
+    let schedules: Vec<Schedule> = load(&schedules_path());
+    Ok(schedules.into_iter().map(with_next_run).collect())
+}
+
+#[tauri::command]
+pub fn create_schedule(
+    state: tauri::State<'_, SchedulerManager>,
+    schedule: ScheduleInput,
+) -> Result<Schedule, String> {
+    schedule.validate()?;
+    let _guard = state.files.lock().unwrap();
+    let mut schedules: Vec<Schedule> = load(&schedules_path());
+    let now = crate::now_ms();
+    let mut stamp = now;
+    while schedules.iter().any(|s| s.id == format!("sched-{}", stamp)) {
+        stamp += 1;
+    }
+    let created = Schedule {
+        id: format!("sched-{}", stamp),
+        name: schedule.name.trim().to_string(),
+        cron: schedule.cron.trim().to_string(),
+        action: schedule.action,
+        enabled: schedule.enabled.unwrap_or(true),
+        catch_up: schedule.catch_up.unwrap_or_default(),
+        timeout_secs: schedule.timeout_secs,
+        created_at: now,
+        last_slot: None,
+        next_run_at: None,
+    };
+    schedules.push(created.clone());
+    save(&schedules_path(), &schedules)?;
+    Ok(with_next_run(created))
+}
+
+/// Replace a schedule's definition. A changed expression starts counting from now, so the new
+/// timing doesn't trigger catch-up runs.
+#[tauri::command]
+pub fn update_schedule(
+    state: tauri::State<'_, SchedulerManager>,
+    id: String,
+    schedule: ScheduleInput,
+) -> Result<Schedule, String> {
+    schedule.validate()?;
+    edit_schedule(&state, &id, |existing| {
+        if existing.cron != schedule.cron.trim() {
+            existing.last_slot = Some(crate::now_ms());
+        }
+        existing.name = schedule.name.trim().to_string();
+        existing.cron = schedule.cron.trim().to_string();
+        existing.action = schedule.action;
+        if let Some(enabled) = schedule.enabled {
+            existing.enabled = enabled;
+        }
+        if let Some(catch_up) = schedule.catch_up {
+            existing.catch_up = catch_up;
+        }
+        existing.timeout_secs = schedule.timeout_secs;
+        Ok(())
+    })
+}
+
+/// Turn a schedule on or off. Occurrences while it was off are not caught up.
+#[tauri::command]
+pub fn set_schedule_enabled(
+    state: tauri::State<'_, SchedulerManager>,
+    id: String,
+    enabled: bool,
+) -> Result<Schedule, String> {
+    edit_schedule(&state, &id, |schedule| {
+        if enabled && !schedule.enabled {
+            schedule.last_slot = Some(crate::now_ms());
+        }
+        schedule.enabled = enabled;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn delete_schedule(
+    state: tauri::State<'_, SchedulerManager>,
+    id: String,
+) -> Result<(), String> {
+    let _guard = state.files.lock().unwrap();
+    let mut schedules: Vec<Schedule> = load(&schedules_path());
+    let before = schedules.len();
+    schedules.retain(|s| s.id != id);
+    if schedules.len() == before {
+        return Err(format!("Schedule not found: {}", id));
+    }
+    save(&schedules_path(), &schedules)
+}
+
+/// Run a schedule immediately, whether or not it is enabled. Progress arrives as
+/// `schedule-run` events; the returned record is the run as it starts.
+#[tauri::command]
+pub fn run_schedule_now(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SchedulerManager>,
+    id: String,
+) -> Result<ScheduleRun, String> {
+    let schedule = {
+        let _guard = state.files.lock().unwrap();
+        let schedules: Vec<Schedule> = load(&schedules_path());
+        schedules
+            .into_iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| format!("Schedule not found: {}", id))?
+    };
+    state
+        .launch(&app, schedule, vec![(None, Trigger::Manual)])
+        .ok_or_else(|| "The schedule is already running".to_string())
+}
+
+/// Past runs, newest first, optionally for one schedule
+#[tauri::command]
+pub fn get_schedule_history(
+    state: tauri::State<'_, SchedulerManager>,
+    schedule_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ScheduleRun>, String> {
+    let _guard = state.files.lock().unwrap();
+    let history: Vec<ScheduleRun> = load(&history_path());
+    Ok(history
+        .into_iter()
+        .rev()
+        .filter(|r| schedule_id.as_ref().is_none_or(|id| r.schedule_id == *id))
+        .take(limit.unwrap_or(100))
+        .collect())
+}
+
+/// Check a cron expression and list its next occurrences (ms timestamps), at most 100
+#[tauri::command]
+pub fn preview_cron(expression: String, count: Option<usize>) -> Result<Vec<u64>, String> {
+    let spec = parse_cron(&expression)?;
+    let mut times = Vec::new();
+    let mut cursor = crate::now_ms();
+    while times.len() < count.unwrap_or(5).min(MAX_PREVIEW) {
+        let Some(next) = spec.next_after(cursor) else {
+            break;
+        };
+        times.push(next);
+        cursor = next;
+    }
+    Ok(times)
+}
@@ -0,0 +1,178 @@
+//! Screenshots for agent context, taken with the platform's own tool and saved under
+//! ~/.ade/images next to pasted images. Window and region captures let the user pick
+//! interactively; cancelling isn't an error.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+const MAX_DELAY_SECS: u64 = 10;
+
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureMode {
+    /// Every screen
+    #[default]
+    Full,
+    /// A window the user clicks
+    Window,
+    /// A rectangle the user drags
+    Region,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct ScreenshotOptions {
+    mode: Option<CaptureMode>,
+    /// Wait before capturing, e.g. to open a menu; at most 10
+    delay_secs: Option<u64>,
+}
+
+fn command(program: &str, args: &[&str], path: &Path) -> Command {
+    let mut cmd = Command::new(program);
+    cmd.args(args).arg(path);
+    cmd
+}
+
+fn mac_command(mode: CaptureMode, path: &Path) -> Command {
+    // -x: no shutter sound
+    let args: &[&str] = match mode {
+        CaptureMode::Full => &["-x"],
+        CaptureMode::Window => &["-x", "-i", "-w"],
+        CaptureMode::Region => &["-x", "-i", "-s"],
+    };
+    command("screencapture", args, path)
+}
+
+/// The first screenshot tool installed that supports `mode`, preferring the desktop's own;
+/// None when a region selection made up front was cancelled
+fn linux_command(mode: CaptureMode, path: &Path) -> Result<Option<Command>, String> {
+    use CaptureMode::*;
+    let installed = |program: &str| crate::which::find(program).is_some();
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+
+    if installed("gnome-screenshot") {
+        let args: &[&str] = match mode {
+            Full => &["-f"],
+            Window => &["-w", "-f"],
+            Region => &["-a", "-f"],
+        };
+        return Ok(Some(command("gnome-screenshot", args, path)));
+    }
+    if installed("spectacle") {
+        let args: &[&str] = match mode {
+            Full => &["-b", "-n", "-f", "-o"],
+            Window => &["-b", "-n", "-a", "-o"],
+            Region => &["-b", "-n", "-r", "-o"],
+        };
+        return Ok(Some(command("spectacle", args, path)));
+    }
+    if wayland && installed("grim") {
+        match mode {
+            Full => return Ok(Some(command("grim", &[], path))),
+            Region if installed("slurp") => {
+                let output = Command::new("slurp")
+                    .output()
+                    .map_err(|e| format!("Failed to run slurp: {}", e))?;
+                let geometry = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                if !output.status.success() || geometry.is_empty() {
+                    return Ok(None);
+                }
+                return Ok(Some(command("grim", &["-g", &geometry], path)));
+            }
+            _ => {}
+        }
+    }
+    if installed("maim") {
+        // -s: drag a region, or click a window to take it whole
+        let args: &[&str] = match mode {
+            Full => &[],
+            Window | Region => &["-s"],
+        };
+        return Ok(Some(command("maim", args, path)));
+    }
+    if installed("scrot") {
+        let args: &[&str] = match mode {
+            Full => &["-o"],
+            Window | Region => &["-o", "-s"],
+        };
+        return Ok(Some(command("scrot", args, path)));
+    }
+    if installed("import") {
+        // ImageMagick: without -window, a click takes a window and a drag a region
+        let args: &[&str] = match mode {
+            Full => &["-window", "root"],
+            Window | Region => &[],
+        };
+        return Ok(Some(command("import", args, path)));
+    }
+    Err(match wayland {
+        true => "No screenshot tool found; install gnome-screenshot, spectacle or grim and slurp",
+        false => "No screenshot tool found; install gnome-screenshot, spectacle, maim or scrot",
+    }
+    .to_string())
+}
+
+fn windows_command(mode: CaptureMode, path: &Path) -> Result<Command, String> {
+    if mode != CaptureMode::Full {
+        return Err("Only full-screen capture is supported on Windows".to_string());
+    }
+    // The path goes through the environment to stay clear of PowerShell quoting
+    let script = "Add-Type -AssemblyName System.Windows.Forms,System.Drawing; \
+        $b = [System.Windows.Forms.SystemInformation]::VirtualScreen; \
+        $bmp = New-Object System.Drawing.Bitmap $b.Width, $b.Height; \
+        [System.Drawing.Graphics]::FromImage($bmp).CopyFromScreen($b.Left, $b.Top, 0, 0, $bmp.Size); \
+        $bmp.Save($env:ADE_SCREENSHOT, [System.Drawing.Imaging.ImageFormat]::Png)";
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .env("ADE_SCREENSHOT", path);
+    Ok(cmd)
+}
+
+fn capture(mode: CaptureMode, path: &Path) -> Result<Option<PathBuf>, String> {
+    let cmd = if cfg!(target_os = "macos") {
+        Some(mac_command(mode, path))
+    } else if cfg!(windows) {
+        Some(windows_command(mode, path)?)
+    } else {
+        linux_command(mode, path)?
+    };
+    let Some(mut cmd) = cmd else {
+        return Ok(None);
+    };
+    let program = cmd.get_program().to_string_lossy().to_string();
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    let saved = std::fs::metadata(path).is_ok_and(|m| m.len() > 0);
+    match (saved, mode) {
+        (true, _) => Ok(Some(path.to_path_buf())),
+        // Interactive captures leave no file when the user presses Escape
+        (false, CaptureMode::Window | CaptureMode::Region) => Ok(None),
+        (false, CaptureMode::Full) => Err(format!(
+            "Failed to take a screenshot: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
+
+/// Take a screenshot of the screen, a window or a region and save it as a PNG; resolves to its
+/// path, or None when the user cancelled the selection
+#[tauri::command]
+pub async fn capture_screenshot(
+    options: Option<ScreenshotOptions>,
+) -> Result<Option<String>, String> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        let dir = crate::ade_path("images");
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let path = dir.join(format!("screenshot-{}.png", crate::now_ms()));
+        if let Some(delay) = options.delay_secs.filter(|d| *d > 0) {
+            std::thread::sleep(Duration::from_secs(delay.min(MAX_DELAY_SECS)));
+        }
+        let captured = capture(options.mode.unwrap_or_default(), &path)?;
+        Ok(captured.map(|path| path.to_string_lossy().to_string()))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
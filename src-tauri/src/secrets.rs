@@ -0,0 +1,240 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Keychain service the secrets are filed under
+const SERVICE: &str = "com.betterterminal.dev";
+
+/// Secrets handed to agent CLIs as environment variables when the variable isn't already set
+const AGENT_ENV: &[(&str, &str)] = &[
+    ("anthropic-api-key", "ANTHROPIC_API_KEY"),
+    ("openai-api-key", "OPENAI_API_KEY"),
+    ("gemini-api-key", "GEMINI_API_KEY"),
+];
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || name.len() > 64
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(format!("Invalid secret name: {}", name));
+    }
+    Ok(())
+}
+
+/// Run a keychain tool, feeding `input` on stdin. Returns whether it succeeded and its stdout.
+fn run(cmd: &mut Command, input: Option<&str>) -> Result<(bool, String), String> {
+    let program = cmd.get_program().to_string_lossy().to_string();
+    let mut child = cmd
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| format!("Failed to write to {}: {}", program, e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    Ok((
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+    ))
+}
+
+#[cfg(target_os = "macos")]
+fn store(name: &str, value: &str) -> Result<(), String> {
+    if value.contains(['\n', '\r']) {
+        return Err(format!("{} can't contain line breaks", name));
+    }
+    // -U updates an existing item in place. -w goes last with no value, so security prompts
+    // for it (and again to confirm) on stdin rather than taking it in the process list.
+    let (ok, _) = run(
+        Command::new("security").args([
+            "add-generic-password",
+            "-U",
+            "-s",
+            SERVICE,
+            "-a",
+            name,
+            "-w",
+        ]),
+        Some(&format!("{0}\n{0}\n", value)),
+    )?;
+    if !ok {
+        return Err(format!("Failed to store {} in the keychain", name));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn lookup(name: &str) -> Result<Option<String>, String> {
+    let (ok, out) = run(
+        Command::new("security").args(["find-generic-password", "-s", SERVICE, "-a", name, "-w"]),
+        None,
+    )?;
+    Ok(ok.then(|| out.trim_end_matches('\n').to_string()))
+}
+
+#[cfg(target_os = "macos")]
+fn remove(name: &str) -> Result<(), String> {
+    // Fails when there is nothing to delete, which is fine
+    run(
+        Command::new("security").args(["delete-generic-password", "-s", SERVICE, "-a", name]),
+        None,
+    )
+    .map(|_| ())
+}
+
+/// Secret Service (GNOME Keyring, KWallet) through libsecret's secret-tool
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn secret_tool() -> Result<String, String> {
+    crate::check_command_exists("secret-tool".to_string()).map_err(|_| {
+        "secret-tool not found. Install libsecret-tools to store secrets in the keyring".to_string()
+    })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn store(name: &str, value: &str) -> Result<(), String> {
+    let label = format!("Better Terminal: {}", name);
+    // The value goes over stdin so it never shows up in the process list
+    let (ok, _) = run(
+        Command::new(secret_tool()?).args([
+            "store", "--label", &label, "service", SERVICE, "account", name,
+        ]),
+        Some(value),
+    )?;
+    if !ok {
+        return Err(format!("Failed to store {} in the keyring", name));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn lookup(name: &str) -> Result<Option<String>, String> {
+    let (ok, out) = run(
+        Command::new(secret_tool()?).args(["lookup", "service", SERVICE, "account", name]),
+        None,
+    )?;
+    Ok(ok.then_some(out))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn remove(name: &str) -> Result<(), String> {
+    run(
+        Command::new(secret_tool()?).args(["clear", "service", SERVICE, "account", name]),
+        None,
+    )
+    .map(|_| ())
+}
+
+/// DPAPI-encrypted blobs under ~/.ade/secrets, readable only by the current Windows user
+#[cfg(target_os = "windows")]
+fn powershell(script: &str, input: &str) -> Result<(bool, String), String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    run(
+        Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", script])
+            .creation_flags(CREATE_NO_WINDOW),
+        Some(input),
+    )
+}
+
+#[cfg(target_os = "windows")]
+fn store(name: &str, value: &str) -> Result<(), String> {
+    const SCRIPT: &str = "ConvertTo-SecureString ([Console]::In.ReadToEnd()) -AsPlainText -Force \
+                          | ConvertFrom-SecureString";
+    let (ok, encrypted) = powershell(SCRIPT, value)?;
+    if !ok {
+        return Err(format!("Failed to encrypt {}", name));
+    }
+    let path = crate::ade_path("secrets").join(name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(&path, encrypted.trim())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(target_os = "windows")]
+fn lookup(name: &str) -> Result<Option<String>, String> {
+    const SCRIPT: &str = "$s = ConvertTo-SecureString ([Console]::In.ReadToEnd().Trim()); \
+                          [Console]::Out.Write([Runtime.InteropServices.Marshal]::PtrToStringBSTR(\
+                          [Runtime.InteropServices.Marshal]::SecureStringToBSTR($s)))";
+    let Ok(encrypted) = std::fs::read_to_string(crate::ade_path("secrets").join(name)) else {
+        return Ok(None);
+    };
+    let (ok, value) = powershell(SCRIPT, &encrypted)?;
+    if !ok {
+        return Err(format!("Failed to decrypt {}", name));
+    }
+    Ok(Some(value))
+}
+
+#[cfg(target_os = "windows")]
+fn remove(name: &str) -> Result<(), String> {
+    match std::fs::remove_file(crate::ade_path("secrets").join(name)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Failed to delete {}: {}", name, e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The stored secret `name`, if any; failures to reach the keychain count as missing
+pub(crate) fn secret(name: &str) -> Option<String> {
+    validate_name(name).ok()?;
+    lookup(name).ok().flatten().filter(|v| !v.is_empty())
+}
+
+/// Give an agent process the API keys it expects in its environment, unless the user's own
+/// environment already provides them
+pub(crate) fn apply_agent_env(cmd: &mut Command) {
+    for (name, var) in AGENT_ENV {
+        if std::env::var_os(var).is_some() {
+            continue;
+        }
+        if let Some(value) = secret(name) {
+            cmd.env(var, value);
+        }
+    }
+}
+
+/// Save `value` under `name` in the system keychain (Keychain on macOS, Secret Service on
+/// Linux, DPAPI on Windows), replacing any previous value
+#[tauri::command]
+pub async fn store_secret(name: String, value: String) -> Result<(), String> {
+    validate_name(&name)?;
+    if value.is_empty() {
+        return Err("Secret value is empty".to_string());
+    }
+    tauri::async_runtime::spawn_blocking(move || store(&name, &value))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_secret(name: String) -> Result<Option<String>, String> {
+    validate_name(&name)?;
+    tauri::async_runtime::spawn_blocking(move || Ok(lookup(&name)?.filter(|v| !v.is_empty())))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn delete_secret(name: String) -> Result<(), String> {
+    validate_name(&name)?;
+    tauri::async_runtime::spawn_blocking(move || remove(&name))
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,740 @@
+use rusqlite::{params, Connection};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::ipc::Channel;
+
+/// Only source/text files are embedded; everything else is noise for code search
+const TEXT_EXTENSIONS: &[&str] = &[
+    "rs", "ts", "tsx", "js", "jsx", "mjs", "cjs", "py", "go", "java", "kt", "swift", "rb", "php",
+    "c", "h", "cc", "cpp", "hpp", "cs", "scala", "sh", "sql", "md", "toml", "yaml", "yml", "json",
+    "html", "css", "scss", "vue", "svelte",
+];
+const MAX_FILE_SIZE: u64 = 512 * 1024;
+const CHUNK_LINES: usize = 40;
+const CHUNK_OVERLAP: usize = 8;
+const MAX_CHUNK_CHARS: usize = 6000;
+const EMBED_BATCH: usize = 32;
+
+/// Where embeddings come from. API keys are read from an environment variable, never stored.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "provider", rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    /// Local model served by Ollama (e.g. nomic-embed-text)
+    Ollama {
+        endpoint: Option<String>,
+        model: String,
+    },
+    /// Any OpenAI-compatible /v1/embeddings endpoint
+    Openai {
+        endpoint: Option<String>,
+        model: String,
+        api_key_env: Option<String>,
+    },
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum SemanticEvent {
+    #[serde(rename = "progress")]
+    Progress {
+        done: usize,
+        total: usize,
+        path: String,
+    },
+    /// Waiting for low-power mode to end; progress resumes after
+    #[serde(rename = "paused")]
+    Paused {},
+    #[serde(rename = "done")]
+    Done {
+        files: usize,
+        chunks: usize,
+        updated_files: usize,
+    },
+    #[serde(rename = "error")]
+    Error { message: String },
+}
+
+#[derive(serde::Serialize)]
+pub struct SemanticResult {
+    path: String,
+    start_line: u32,
+    end_line: u32,
+    score: f32,
+    snippet: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct SemanticStatus {
+    root: String,
+    enabled: bool,
+    indexing: bool,
+    files: usize,
+    chunks: usize,
+    provider: Option<EmbeddingProvider>,
+}
+
+struct LoadedIndex {
+    graph: Hnsw,
+    /// Maps graph node → chunk row id
+    chunk_ids: Vec<i64>,
+}
+
+pub struct SemanticManager {
+    loaded: Arc<Mutex<HashMap<String, Arc<LoadedIndex>>>>,
+    indexing: Arc<Mutex<HashSet<String>>>,
+}
+
+impl SemanticManager {
+    pub fn new() -> Self {
+        Self {
+            loaded: Arc::new(Mutex::new(HashMap::new())),
+            indexing: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+}
+
+fn db_path(root: &str) -> PathBuf {
+    crate::ade_path(&format!("semantic/{}.sqlite", crate::project_hash(root)))
+}
+
+fn open_db(root: &str) -> Result<Connection, String> {
+    let path = db_path(root);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create dir: {}", e))?;
+    }
+    let conn =
+        Connection::open(&path).map_err(|e| format!("Failed to open semantic index: {}", e))?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+         CREATE TABLE IF NOT EXISTS files (path TEXT PRIMARY KEY, mtime INTEGER NOT NULL);
+         CREATE TABLE IF NOT EXISTS chunks (
+             id INTEGER PRIMARY KEY,
+             path TEXT NOT NULL,
+             start_line INTEGER NOT NULL,
+             end_line INTEGER NOT NULL,
+             content TEXT NOT NULL,
+             vector BLOB NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS chunks_path ON chunks(path);",
+    )
+    .map_err(|e| format!("Failed to initialise semantic index: {}", e))?;
+    Ok(conn)
+}
+
+fn read_provider(conn: &Connection) -> Option<EmbeddingProvider> {
+    conn.query_row("SELECT value FROM meta WHERE key = 'provider'", [], |row| {
+        row.get::<_, String>(0)
+    })
+    .ok()
+    .and_then(|json| serde_json::from_str(&json).ok())
+}
+
+fn embed(provider: &EmbeddingProvider, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(120))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let vectors: Vec<Vec<f32>> = match provider {
+        EmbeddingProvider::Ollama { endpoint, model } => {
+            let url = format!(
+                "{}/api/embed",
+                endpoint
+                    .as_deref()
+                    .unwrap_or("http://localhost:11434")
+                    .trim_end_matches('/')
+            );
+            let resp: serde_json::Value = client
+                .post(&url)
+                .json(&serde_json::json!({ "model": model, "input": inputs }))
+                .send()
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| format!("Ollama embedding request failed: {}", e))?
+                .json()
+                .map_err(|e| format!("Invalid Ollama response: {}", e))?;
+            serde_json::from_value(resp["embeddings"].clone())
+                .map_err(|e| format!("Invalid Ollama response: {}", e))?
+        }
+        EmbeddingProvider::Openai {
+            endpoint,
+            model,
+            api_key_env,
+        } => {
+            let url = format!(
+                "{}/v1/embeddings",
+                endpoint
+                    .as_deref()
+                    .unwrap_or("https://api.openai.com")
+                    .trim_end_matches('/')
+            );
+            let key_var = api_key_env.as_deref().unwrap_or("OPENAI_API_KEY");
+            let key = std::env::var(key_var).map_err(|_| format!("{} not set", key_var))?;
+            let resp: serde_json::Value = client
+                .post(&url)
+                .bearer_auth(key)
+                .json(&serde_json::json!({ "model": model, "input": inputs }))
+                .send()
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| format!("Embedding request failed: {}", e))?
+                .json()
+                .map_err(|e| format!("Invalid embedding response: {}", e))?;
+            resp["data"]
+                .as_array()
+                .ok_or("Invalid embedding response: missing data")?
+                .iter()
+                .map(|d| serde_json::from_value(d["embedding"].clone()))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Invalid embedding response: {}", e))?
+        }
+    };
+
+    if vectors.len() != inputs.len() {
+        return Err(format!(
+            "Expected {} embeddings, got {}",
+            inputs.len(),
+            vectors.len()
+        ));
+    }
+    Ok(vectors.into_iter().map(normalize).collect())
+}
+
+fn normalize(mut v: Vec<f32>) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+    v
+}
+
+fn vector_to_blob(v: &[f32]) -> Vec<u8> {
+    v.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn blob_to_vector(b: &[u8]) -> Vec<f32> {
+    b.chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<(PathBuf, i64)>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        let meta = match entry.metadata() {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+        if meta.is_dir() {
+            if !name.starts_with('.') && !crate::index::SKIP_DIRS.contains(&name.as_str()) {
+                collect_files(&path, files);
+            }
+            continue;
+        }
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if meta.len() > MAX_FILE_SIZE || !TEXT_EXTENSIONS.contains(&ext.as_str()) {
+            continue;
+        }
+        let mtime = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        files.push((path, mtime));
+    }
+}
+
+/// Split a file into overlapping line windows. Returns (start_line, end_line, text), 1-based.
+fn chunk_file(content: &str) -> Vec<(u32, u32, String)> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let end = (start + CHUNK_LINES).min(lines.len());
+        let mut text = lines[start..end].join("\n");
+        if !text.trim().is_empty() {
+            if text.len() > MAX_CHUNK_CHARS {
+                let mut cut = MAX_CHUNK_CHARS;
+                while !text.is_char_boundary(cut) {
+                    cut -= 1;
+                }
+                text.truncate(cut);
+            }
+            chunks.push((start as u32 + 1, end as u32, text));
+        }
+        if end == lines.len() {
+            break;
+        }
+        start = end - CHUNK_OVERLAP;
+    }
+    chunks
+}
+
+fn run_indexing(
+    root: &str,
+    provider: &EmbeddingProvider,
+    on_event: &Channel<SemanticEvent>,
+) -> Result<(usize, usize, usize), String> {
+    let mut conn = open_db(root)?;
+
+    // Switching provider or model invalidates every stored vector
+    let provider_json = serde_json::to_string(provider).map_err(|e| e.to_string())?;
+    let previous: Option<String> = conn
+        .query_row("SELECT value FROM meta WHERE key = 'provider'", [], |row| {
+            row.get(0)
+        })
+        .ok();
+    if previous.as_deref() != Some(provider_json.as_str()) {
+        conn.execute_batch("DELETE FROM chunks; DELETE FROM files;")
+            .map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('provider', ?1)",
+            params![provider_json],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    let mut files = Vec::new();
+    collect_files(Path::new(root), &mut files);
+
+    let known: HashMap<String, i64> = {
+        let mut stmt = conn
+            .prepare("SELECT path, mtime FROM files")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.flatten().collect()
+    };
+
+    // Drop files that no longer exist
+    let present: HashSet<String> = files
+        .iter()
+        .map(|(p, _)| p.to_string_lossy().to_string())
+        .collect();
+    for path in known.keys().filter(|p| !present.contains(*p)) {
+        conn.execute("DELETE FROM chunks WHERE path = ?1", params![path])
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM files WHERE path = ?1", params![path])
+            .map_err(|e| e.to_string())?;
+    }
+
+    let changed: Vec<&(PathBuf, i64)> = files
+        .iter()
+        .filter(|(p, mtime)| known.get(p.to_string_lossy().as_ref()) != Some(mtime))
+        .collect();
+    let total = changed.len();
+
+    for (done, (path, mtime)) in changed.iter().enumerate() {
+        if crate::power::low_power() {
+            let _ = on_event.send(SemanticEvent::Paused {});
+            crate::power::wait_for_full_power();
+        }
+        let path_str = path.to_string_lossy().to_string();
+        let _ = on_event.send(SemanticEvent::Progress {
+            done,
+            total,
+            path: path_str.clone(),
+        });
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => continue, // not UTF-8 or unreadable
+        };
+        let rel = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string();
+        let chunks = chunk_file(&content);
+
+        let mut vectors = Vec::with_capacity(chunks.len());
+        for batch in chunks.chunks(EMBED_BATCH) {
+            // Prefix with the path so the embedding knows where the code lives
+            let inputs: Vec<String> = batch
+                .iter()
+                .map(|(_, _, text)| format!("{}\n{}", rel, text))
+                .collect();
+            vectors.extend(embed(provider, &inputs)?);
+        }
+
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM chunks WHERE path = ?1", params![path_str])
+            .map_err(|e| e.to_string())?;
+        for ((start, end, text), vector) in chunks.iter().zip(vectors.iter()) {
+            tx.execute(
+                "INSERT INTO chunks (path, start_line, end_line, content, vector) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![path_str, start, end, text, vector_to_blob(vector)],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO files (path, mtime) VALUES (?1, ?2)",
+            params![path_str, mtime],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    let chunk_count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    Ok((files.len(), chunk_count as usize, total))
+}
+
+fn load_index(conn: &Connection) -> Result<LoadedIndex, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, vector FROM chunks")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut graph = Hnsw::new(16, 100);
+    let mut chunk_ids = Vec::new();
+    for (id, blob) in rows.flatten() {
+        graph.insert(blob_to_vector(&blob));
+        chunk_ids.push(id);
+    }
+    Ok(LoadedIndex { graph, chunk_ids })
+}
+
+#[tauri::command]
+pub fn semantic_index(
+    state: tauri::State<'_, SemanticManager>,
+    root: String,
+    provider: EmbeddingProvider,
+    on_event: Channel<SemanticEvent>,
+) -> Result<(), String> {
+    if !Path::new(&root).is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+    if !state.indexing.lock().unwrap().insert(root.clone()) {
+        return Err("Semantic indexing already running for this project".to_string());
+    }
+
+    let loaded = state.loaded.clone();
+    let indexing = state.indexing.clone();
+    std::thread::spawn(move || {
+        let result = run_indexing(&root, &provider, &on_event).and_then(|counts| {
+            open_db(&root)
+                .and_then(|conn| load_index(&conn))
+                .map(|index| (counts, index))
+        });
+        match result {
+            Ok(((files, chunks, updated_files), index)) => {
+                loaded.lock().unwrap().insert(root.clone(), Arc::new(index));
+                let _ = on_event.send(SemanticEvent::Done {
+                    files,
+                    chunks,
+                    updated_files,
+                });
+            }
+            Err(message) => {
+                let _ = on_event.send(SemanticEvent::Error { message });
+            }
+        }
+        indexing.lock().unwrap().remove(&root);
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn semantic_search(
+    state: tauri::State<'_, SemanticManager>,
+    root: String,
+    query: String,
+    k: Option<usize>,
+) -> Result<Vec<SemanticResult>, String> {
+    let loaded = state.loaded.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = db_path(&root);
+        if !path.exists() {
+            return Err("Semantic index not enabled for this project".to_string());
+        }
+        let conn = open_db(&root)?;
+        let provider = read_provider(&conn).ok_or("Semantic index has no embedding provider")?;
+
+        let cached = loaded.lock().unwrap().get(&root).cloned();
+        let index = match cached {
+            Some(index) => index,
+            None => {
+                let index = Arc::new(load_index(&conn)?);
+                loaded.lock().unwrap().insert(root.clone(), index.clone());
+                index
+            }
+        };
+
+        let query_vector = embed(&provider, &[query])?
+            .pop()
+            .ok_or("Empty embedding response")?;
+        let k = k.unwrap_or(10);
+        let hits = index.graph.search(&query_vector, k, (k * 4).max(64));
+
+        let mut stmt = conn
+            .prepare("SELECT path, start_line, end_line, content FROM chunks WHERE id = ?1")
+            .map_err(|e| e.to_string())?;
+        let mut results = Vec::new();
+        for (node, distance) in hits {
+            let id = index.chunk_ids[node];
+            if let Ok((path, start_line, end_line, snippet)) = stmt.query_row(params![id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            }) {
+                results.push(SemanticResult {
+                    path,
+                    start_line,
+                    end_line,
+                    score: 1.0 - distance,
+                    snippet,
+                });
+            }
+        }
+        Ok(results)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn semantic_status(
+    state: tauri::State<'_, SemanticManager>,
+    root: String,
+) -> Result<SemanticStatus, String> {
+    let indexing = state.indexing.lock().unwrap().contains(&root);
+    if !db_path(&root).exists() {
+        return Ok(SemanticStatus {
+            root,
+            enabled: false,
+            indexing,
+            files: 0,
+            chunks: 0,
+            provider: None,
+        });
+    }
+    let conn = open_db(&root)?;
+    let count = |table: &str| -> usize {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get::<_, i64>(0)
+        })
+        .unwrap_or(0) as usize
+    };
+    Ok(SemanticStatus {
+        enabled: true,
+        indexing,
+        files: count("files"),
+        chunks: count("chunks"),
+        provider: read_provider(&conn),
+        root,
+    })
+}
+
+/// Opt out: drop the in-memory graph and delete the on-disk vectors
+#[tauri::command]
+pub fn semantic_disable(
+    state: tauri::State<'_, SemanticManager>,
+    root: String,
+) -> Result<(), String> {
+    if state.indexing.lock().unwrap().contains(&root) {
+        return Err("Semantic indexing is running for this project".to_string());
+    }
+    state.loaded.lock().unwrap().remove(&root);
+    let path = db_path(&root);
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to delete index: {}", e))?;
+    }
+    Ok(())
+}
+
+// --- HNSW approximate nearest-neighbour graph over unit vectors (cosine distance) ---
+
+#[derive(Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    node: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then_with(|| self.node.cmp(&other.node))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+struct Hnsw {
+    m: usize,
+    ef_construction: usize,
+    vectors: Vec<Vec<f32>>,
+    /// neighbours[node][layer] → adjacent nodes
+    neighbours: Vec<Vec<Vec<usize>>>,
+    entry: Option<usize>,
+    rng: u64,
+}
+
+impl Hnsw {
+    fn new(m: usize, ef_construction: usize) -> Self {
+        Self {
+            m,
+            ef_construction,
+            vectors: Vec::new(),
+            neighbours: Vec::new(),
+            entry: None,
+            rng: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    fn distance(&self, a: &[f32], node: usize) -> f32 {
+        1.0 - a
+            .iter()
+            .zip(self.vectors[node].iter())
+            .map(|(x, y)| x * y)
+            .sum::<f32>()
+    }
+
+    fn random_level(&mut self) -> usize {
+        // xorshift64*; level ~ -ln(U) / ln(M)
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let r = self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        let u = ((r >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        (-u.ln() / (self.m as f64).ln()).floor() as usize
+    }
+
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entries: &[usize],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Candidate> {
+        let mut visited: HashSet<usize> = entries.iter().copied().collect();
+        // Min-heap of candidates to expand, max-heap of current best results
+        let mut candidates: BinaryHeap<std::cmp::Reverse<Candidate>> = BinaryHeap::new();
+        let mut results: BinaryHeap<Candidate> = BinaryHeap::new();
+        for &node in entries {
+            let c = Candidate {
+                distance: self.distance(query, node),
+                node,
+            };
+            candidates.push(std::cmp::Reverse(c));
+            results.push(c);
+        }
+        while let Some(std::cmp::Reverse(current)) = candidates.pop() {
+            let worst = results.peek().map(|c| c.distance).unwrap_or(f32::MAX);
+            if current.distance > worst && results.len() >= ef {
+                break;
+            }
+            for &next in &self.neighbours[current.node][layer] {
+                if !visited.insert(next) {
+                    continue;
+                }
+                let distance = self.distance(query, next);
+                let worst = results.peek().map(|c| c.distance).unwrap_or(f32::MAX);
+                if results.len() < ef || distance < worst {
+                    let c = Candidate {
+                        distance,
+                        node: next,
+                    };
+                    candidates.push(std::cmp::Reverse(c));
+                    results.push(c);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+        results.into_sorted_vec()
+    }
+
+    fn insert(&mut self, vector: Vec<f32>) {
+        let node = self.vectors.len();
+        let level = self.random_level();
+        self.vectors.push(vector);
+        self.neighbours.push(vec![Vec::new(); level + 1]);
+
+        let entry = match self.entry {
+            Some(e) => e,
+            None => {
+                self.entry = Some(node);
+                return;
+            }
+        };
+        let top = self.neighbours[entry].len() - 1;
+        let query = self.vectors[node].clone();
+
+        // Greedy descent through layers above the new node's level
+        let mut entries = vec![entry];
+        for layer in (level + 1..=top).rev() {
+            entries = vec![self.search_layer(&query, &entries, 1, layer)[0].node];
+        }
+
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&query, &entries, self.ef_construction, layer);
+            let max_links = if layer == 0 { self.m * 2 } else { self.m };
+            let selected: Vec<usize> = found.iter().take(self.m).map(|c| c.node).collect();
+            self.neighbours[node][layer] = selected.clone();
+            for &other in &selected {
+                self.neighbours[other][layer].push(node);
+                if self.neighbours[other][layer].len() > max_links {
+                    // Keep the closest links of the neighbour
+                    let other_vec = self.vectors[other].clone();
+                    let mut links: Vec<Candidate> = self.neighbours[other][layer]
+                        .iter()
+                        .map(|&n| Candidate {
+                            distance: self.distance(&other_vec, n),
+                            node: n,
+                        })
+                        .collect();
+                    links.sort();
+                    links.truncate(max_links);
+                    self.neighbours[other][layer] = links.into_iter().map(|c| c.node).collect();
+                }
+            }
+            entries = found.into_iter().map(|c| c.node).collect();
+        }
+
+        if level > top {
+            self.entry = Some(node);
+        }
+    }
+
+    /// Returns up to k (node, distance) pairs, closest first
+    fn search(&self, query: &[f32], k: usize, ef: usize) -> Vec<(usize, f32)> {
+        let entry = match self.entry {
+            Some(e) => e,
+            None => return Vec::new(),
+        };
+        let query = normalize(query.to_vec());
+        let top = self.neighbours[entry].len() - 1;
+        let mut entries = vec![entry];
+        for layer in (1..=top).rev() {
+            entries = vec![self.search_layer(&query, &entries, 1, layer)[0].node];
+        }
+        self.search_layer(&query, &entries, ef.max(k), 0)
+            .into_iter()
+            .take(k)
+            .map(|c| (c.node, c.distance))
+            .collect()
+    }
+}
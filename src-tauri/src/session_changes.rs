@@ -0,0 +1,483 @@
+use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Finished sessions kept in ~/.ade/session-changes.json; the oldest are dropped first
+const MAX_STORED_SESSIONS: usize = 200;
+/// Untracked files larger than this get no line count
+const MAX_COUNTED_FILE_SIZE: u64 = 1024 * 1024;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct FileChange {
+    /// Relative to the session root
+    path: String,
+    /// added, modified, or deleted
+    change: String,
+    additions: Option<u64>,
+    deletions: Option<u64>,
+    /// When the watcher first saw the file change; None if only git noticed it
+    first_seen_at: Option<u64>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct SessionChanges {
+    session_id: String,
+    root: String,
+    started_at: u64,
+    ended_at: Option<u64>,
+    active: bool,
+    files: Vec<FileChange>,
+}
+
+#[derive(Clone)]
+struct Observed {
+    first_seen_at: u64,
+    created: bool,
+}
+
+struct Tracking {
+    root: String,
+    started_at: u64,
+    /// HEAD when tracking began; None outside a git repo or before the first commit
+    start_head: Option<String>,
+    /// Fingerprints of files already dirty at the start, so earlier edits aren't attributed
+    dirty_at_start: HashMap<String, Option<u64>>,
+    observed: Arc<Mutex<HashMap<String, Observed>>>,
+    _watcher: Option<RecommendedWatcher>,
+}
+
+/// Cloneable handle for starting and finishing tracking from other subsystems (e.g. agent runs)
+#[derive(Clone)]
+pub struct SessionChangeTracker {
+    active: Arc<Mutex<HashMap<String, Tracking>>>,
+}
+
+pub struct SessionChangeManager {
+    tracker: SessionChangeTracker,
+}
+
+impl SessionChangeManager {
+    pub fn new() -> Self {
+        Self {
+            tracker: SessionChangeTracker {
+                active: Arc::new(Mutex::new(HashMap::new())),
+            },
+        }
+    }
+
+    pub fn tracker(&self) -> SessionChangeTracker {
+        self.tracker.clone()
+    }
+}
+
+/// FNV-1a of a file's bytes; None when it doesn't exist
+fn fingerprint(path: &Path) -> Option<u64> {
+    let bytes = std::fs::read(path).ok()?;
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    Some(hash)
+}
+
+fn git_paths(root: &str, args: &[&str]) -> Vec<String> {
+    crate::git::run_git(root, args)
+        .map(|out| {
+            String::from_utf8_lossy(&out)
+                .split('\0')
+                .filter(|p| !p.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn head(root: &str) -> Option<String> {
+    crate::git::run_git(root, ["rev-parse", "--verify", "-q", "HEAD"])
+        .ok()
+        .map(|out| String::from_utf8_lossy(&out).trim().to_string())
+        .filter(|h| !h.is_empty())
+}
+
+/// Tracked files that differ from HEAD plus untracked files, relative to `root`
+fn dirty_paths(root: &str, has_head: bool) -> Vec<String> {
+    let mut paths = git_paths(root, &["ls-files", "--others", "--exclude-standard", "-z"]);
+    if has_head {
+        paths.extend(git_paths(
+            root,
+            &[
+                "diff",
+                "--name-only",
+                "--no-renames",
+                "--relative",
+                "-z",
+                "HEAD",
+            ],
+        ));
+    }
+    paths
+}
+
+fn is_ignored_path(rel: &Path) -> bool {
+    rel.components().any(|c| {
+        let name = c.as_os_str().to_string_lossy();
+        name == ".git" || crate::index::SKIP_DIRS.contains(&name.as_ref())
+    })
+}
+
+fn line_count(path: &Path) -> Option<u64> {
+    let meta = std::fs::metadata(path).ok()?;
+    if meta.len() > MAX_COUNTED_FILE_SIZE {
+        return None;
+    }
+    let content = std::fs::read(path).ok()?;
+    let newlines = content.iter().filter(|b| **b == b'\n').count() as u64;
+    Some(newlines + u64::from(!content.is_empty() && !content.ends_with(b"\n")))
+}
+
+/// What changed since tracking began. In a git repo, git decides which files changed (relative
+/// to the starting HEAD, so commits made during the session still count) and the watcher only
+/// contributes timestamps; elsewhere the watcher's observations are all there is.
+fn collect_changes(tracking: &Tracking) -> Vec<FileChange> {
+    let root = tracking.root.as_str();
+    // Copied so the watcher isn't blocked while git runs
+    let observed = tracking.observed.lock().unwrap().clone();
+    let seen_at = |path: &str| observed.get(path).map(|o| o.first_seen_at);
+    let mut files: BTreeMap<String, FileChange> = BTreeMap::new();
+    let mut add = |path: &str, change: &str| {
+        files.insert(
+            path.to_string(),
+            FileChange {
+                path: path.to_string(),
+                change: change.to_string(),
+                additions: None,
+                deletions: None,
+                first_seen_at: seen_at(path),
+            },
+        );
+    };
+
+    let Some(start_head) = &tracking.start_head else {
+        for (path, seen) in observed.iter() {
+            let untouched = tracking
+                .dirty_at_start
+                .get(path)
+                .is_some_and(|before| *before == fingerprint(&Path::new(root).join(path)));
+            if untouched {
+                continue;
+            }
+            let change = if !Path::new(root).join(path).exists() {
+                "deleted"
+            } else if seen.created {
+                "added"
+            } else {
+                "modified"
+            };
+            add(path, change);
+        }
+        for change in files.values_mut() {
+            if change.change == "added" {
+                change.additions = line_count(&Path::new(root).join(&change.path));
+            }
+        }
+        return files.into_values().collect();
+    };
+
+    let mut changed: Vec<(String, &str)> = Vec::new();
+    let status = git_paths(
+        root,
+        &[
+            "diff",
+            "--name-status",
+            "--no-renames",
+            "--relative",
+            "-z",
+            start_head,
+        ],
+    );
+    for pair in status.chunks(2) {
+        if let [kind, path] = pair {
+            let change = match kind.as_str() {
+                "A" => "added",
+                "D" => "deleted",
+                _ => "modified",
+            };
+            changed.push((path.clone(), change));
+        }
+    }
+    for path in git_paths(root, &["ls-files", "--others", "--exclude-standard", "-z"]) {
+        changed.push((path, "added"));
+    }
+    for (path, change) in &changed {
+        let before = tracking.dirty_at_start.get(path);
+        if before.is_some_and(|before| *before == fingerprint(&Path::new(root).join(path))) {
+            continue;
+        }
+        add(path, change);
+    }
+    // Files that were dirty at the start and have since been put back to match HEAD
+    for (path, before) in &tracking.dirty_at_start {
+        let after = fingerprint(&Path::new(root).join(path));
+        if !changed.iter().any(|(p, _)| p == path) && *before != after {
+            add(
+                path,
+                if after.is_some() {
+                    "modified"
+                } else {
+                    "deleted"
+                },
+            );
+        }
+    }
+
+    if let Ok(out) = crate::git::run_git(
+        root,
+        [
+            "diff",
+            "--numstat",
+            "--no-renames",
+            "--relative",
+            "-z",
+            start_head,
+        ],
+    ) {
+        for record in String::from_utf8_lossy(&out).split('\0') {
+            let mut fields = record.splitn(3, '\t');
+            let (Some(added), Some(deleted), Some(path)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            if let Some(change) = files.get_mut(path) {
+                // Binary files report `-`
+                change.additions = added.parse().ok();
+                change.deletions = deleted.parse().ok();
+            }
+        }
+    }
+    for change in files.values_mut() {
+        if change.change == "added" && change.additions.is_none() {
+            change.additions = line_count(&Path::new(root).join(&change.path));
+        }
+    }
+    files.into_values().collect()
+}
+
+fn store_path() -> std::path::PathBuf {
+    crate::ade_path("session-changes.json")
+}
+
+fn load_store() -> Vec<SessionChanges> {
+    std::fs::read_to_string(store_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_store(sessions: &[SessionChanges]) {
+    let path = store_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string(sessions) {
+        let _ = std::fs::write(&path, json);
+    }
+}
+
+/// (ended_at, number of files changed) of stored sessions that finished after `after`. A
+/// resumed session is stored again when it finishes, with the files of all its runs.
+pub(crate) fn finished_since(after: u64) -> Vec<(u64, usize)> {
+    load_store()
+        .iter()
+        .filter_map(|s| Some((s.ended_at?, s.files.len())))
+        .filter(|(ended_at, _)| *ended_at > after)
+        .collect()
+}
+
+impl SessionChangeTracker {
+    /// Snapshot the dirty files under `root` and start watching it. A session that is
+    /// already being tracked is left as it is.
+    pub(crate) fn begin(&self, session_id: &str, root: &str) -> Result<(), String> {
+        if self.active.lock().unwrap().contains_key(session_id) {
+            return Ok(());
+        }
+        let root = crate::git::resolve_repo(root)
+            .trim_end_matches('/')
+            .to_string();
+        let root_path = std::path::PathBuf::from(&root);
+        if !root_path.is_dir() {
+            return Err(format!("Not a directory: {}", root));
+        }
+        let start_head = head(&root);
+        let dirty_at_start = dirty_paths(&root, start_head.is_some())
+            .into_iter()
+            .map(|path| {
+                let print = fingerprint(&root_path.join(&path));
+                (path, print)
+            })
+            .collect();
+
+        let observed: Arc<Mutex<HashMap<String, Observed>>> = Arc::new(Mutex::new(HashMap::new()));
+        let watch_observed = observed.clone();
+        let watch_root = root_path.clone();
+        let watcher = RecommendedWatcher::new(
+            move |res: Result<notify::Event, notify::Error>| {
+                let Ok(event) = res else {
+                    return;
+                };
+                let created = matches!(event.kind, EventKind::Create(_));
+                if !created && !matches!(event.kind, EventKind::Modify(_) | EventKind::Remove(_)) {
+                    return;
+                }
+                let mut observed = watch_observed.lock().unwrap();
+                for path in event.paths {
+                    let Ok(rel) = path.strip_prefix(&watch_root) else {
+                        continue;
+                    };
+                    if is_ignored_path(rel) || path.is_dir() {
+                        continue;
+                    }
+                    observed
+                        .entry(rel.to_string_lossy().to_string())
+                        .or_insert(Observed {
+                            first_seen_at: crate::now_ms(),
+                            created,
+                        });
+                }
+            },
+            Config::default(),
+        )
+        .and_then(|mut w| w.watch(&root_path, RecursiveMode::Recursive).map(|_| w))
+        .ok(); // git alone still attributes changes in a repo
+
+        self.active.lock().unwrap().insert(
+            session_id.to_string(),
+            Tracking {
+                root,
+                started_at: crate::now_ms(),
+                start_head,
+                dirty_at_start,
+                observed,
+                _watcher: watcher,
+            },
+        );
+        Ok(())
+    }
+
+    /// Re-key a tracked run once its agent reports the real session id
+    pub(crate) fn rename(&self, from: &str, to: &str) {
+        let mut active = self.active.lock().unwrap();
+        if from != to && !active.contains_key(to) {
+            if let Some(tracking) = active.remove(from) {
+                active.insert(to.to_string(), tracking);
+            }
+        }
+    }
+
+    /// Stop tracking and store the result, merged with earlier runs of a resumed session
+    pub(crate) fn finish(&self, session_id: &str) -> Option<SessionChanges> {
+        let tracking = self.active.lock().unwrap().remove(session_id)?;
+        let files = collect_changes(&tracking);
+        let mut store = load_store();
+        let mut record = match store.iter().position(|s| s.session_id == session_id) {
+            Some(index) => store.remove(index),
+            None => SessionChanges {
+                session_id: session_id.to_string(),
+                root: tracking.root.clone(),
+                started_at: tracking.started_at,
+                ended_at: None,
+                active: false,
+                files: Vec::new(),
+            },
+        };
+        for file in files {
+            match record.files.iter_mut().find(|f| f.path == file.path) {
+                Some(existing) => {
+                    let first_seen_at = existing.first_seen_at.or(file.first_seen_at);
+                    *existing = FileChange {
+                        first_seen_at,
+                        ..file
+                    };
+                }
+                None => record.files.push(file),
+            }
+        }
+        record.files.sort_by(|a, b| a.path.cmp(&b.path));
+        record.ended_at = Some(crate::now_ms());
+        store.push(record.clone());
+        if store.len() > MAX_STORED_SESSIONS {
+            let excess = store.len() - MAX_STORED_SESSIONS;
+            store.drain(..excess);
+        }
+        save_store(&store);
+        Some(record)
+    }
+
+    /// Stop tracking without recording anything
+    pub(crate) fn discard(&self, session_id: &str) {
+        self.active.lock().unwrap().remove(session_id);
+    }
+}
+
+/// Attribute changes under `root` to a session from now on, e.g. for an agent running in a
+/// terminal; headless runs started with run_agent are tracked automatically
+#[tauri::command]
+pub fn start_session_tracking(
+    state: tauri::State<'_, SessionChangeManager>,
+    session_id: String,
+    root: String,
+) -> Result<(), String> {
+    state.tracker.begin(&session_id, &root)
+}
+
+#[tauri::command]
+pub async fn stop_session_tracking(
+    state: tauri::State<'_, SessionChangeManager>,
+    session_id: String,
+) -> Result<SessionChanges, String> {
+    let tracker = state.tracker();
+    tauri::async_runtime::spawn_blocking(move || {
+        tracker
+            .finish(&session_id)
+            .ok_or_else(|| format!("Session {} is not being tracked", session_id))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Files a session touched: computed live while it is still running, otherwise as stored
+/// when it finished
+#[tauri::command]
+pub async fn get_session_changes(
+    state: tauri::State<'_, SessionChangeManager>,
+    session_id: String,
+) -> Result<SessionChanges, String> {
+    let tracker = state.tracker();
+    tauri::async_runtime::spawn_blocking(move || {
+        let stored = load_store()
+            .into_iter()
+            .find(|s| s.session_id == session_id);
+        let active = tracker.active.lock().unwrap();
+        let Some(tracking) = active.get(&session_id) else {
+            return stored.ok_or_else(|| format!("No changes recorded for session {}", session_id));
+        };
+        let mut files = stored.map(|s| s.files).unwrap_or_default();
+        for file in collect_changes(tracking) {
+            files.retain(|f| f.path != file.path);
+            files.push(file);
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(SessionChanges {
+            session_id: session_id.clone(),
+            root: tracking.root.clone(),
+            started_at: tracking.started_at,
+            ended_at: None,
+            active: true,
+            files,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,169 @@
+//! Saves open terminals, watched directories and open files when the app quits (and every
+//! minute, in case it doesn't quit cleanly), so the next launch can bring them back.
+
+use crate::pty::{PtyManager, PtySink, PtySnapshot};
+use crate::watcher::{WatchedDirectory, WatcherManager};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct SavedSession {
+    saved_at: u64,
+    ptys: Vec<PtySnapshot>,
+    watchers: Vec<WatchedDirectory>,
+    files: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct RestoredPty {
+    /// Pass to attach_pty to receive its output
+    id: u32,
+    #[serde(flatten)]
+    saved: PtySnapshot,
+    /// Whether `command` was started again in the new shell
+    rerun: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct RestoredSession {
+    saved_at: u64,
+    ptys: Vec<RestoredPty>,
+    /// Directories to watch again; they need the caller's channels
+    watchers: Vec<WatchedDirectory>,
+    /// Files that were open, skipping ones that no longer exist
+    files: Vec<String>,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct RestoreOptions {
+    rows: Option<u16>,
+    cols: Option<u16>,
+    /// Start each terminal's last program again, without the arguments it had. Off by default
+    /// since programs may not be safe to repeat.
+    #[serde(default)]
+    rerun_commands: bool,
+}
+
+#[derive(Clone)]
+pub struct SessionRestoreManager {
+    files: Arc<Mutex<Vec<String>>>,
+    /// The previous run's session, taken off disk at startup so periodic saves can't
+    /// overwrite it before it is restored
+    pending: Arc<Mutex<Option<SavedSession>>>,
+}
+
+fn session_path() -> PathBuf {
+    crate::ade_path("last-session.json")
+}
+
+impl SessionRestoreManager {
+    pub fn new() -> Self {
+        let pending = std::fs::read_to_string(session_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+        Self {
+            files: Arc::new(Mutex::new(Vec::new())),
+            pending: Arc::new(Mutex::new(pending)),
+        }
+    }
+}
+
+/// Write the current session. While nothing is open and the previous session hasn't been
+/// restored, that one is kept instead.
+pub fn save(app: &AppHandle) {
+    let manager = app.state::<SessionRestoreManager>();
+    let session = SavedSession {
+        saved_at: crate::now_ms(),
+        ptys: app.state::<PtyManager>().snapshot(),
+        watchers: app.state::<WatcherManager>().snapshot(),
+        files: manager.files.lock().unwrap().clone(),
+    };
+    let pending = manager.pending.lock().unwrap();
+    let session = match &*pending {
+        Some(previous) if session.ptys.is_empty() && session.files.is_empty() => previous,
+        _ => &session,
+    };
+    let path = session_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string(session) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+pub fn start(app: &tauri::App) {
+    let handle = app.handle().clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(crate::power::scaled(SAVE_INTERVAL));
+        save(&handle);
+    });
+}
+
+/// Files open in the frontend, saved with the session
+#[tauri::command]
+pub fn set_session_files(state: tauri::State<'_, SessionRestoreManager>, files: Vec<String>) {
+    *state.files.lock().unwrap() = files;
+}
+
+/// Reopen the previous run's terminals, each a fresh shell in the directory it was last in.
+/// Their output is held until attach_pty. Watchers and files are returned for the caller to
+/// reopen. Returns None when there is nothing to restore; a session is only restored once.
+#[tauri::command]
+pub fn restore_last_session(
+    state: tauri::State<'_, SessionRestoreManager>,
+    pty: tauri::State<'_, PtyManager>,
+    options: Option<RestoreOptions>,
+) -> Result<Option<RestoredSession>, String> {
+    let Some(session) = state.pending.lock().unwrap().take() else {
+        return Ok(None);
+    };
+    let options = options.unwrap_or_default();
+    let mut ptys = Vec::new();
+    for mut saved in session.ptys {
+        // A folder that's gone falls back to the home directory...
+        saved.cwd = saved.cwd.filter(|cwd| Path::new(cwd).is_dir());
+        // So does an env set that has been removed from the settings
+        let settings = crate::project_settings::load(
+            saved
+                .cwd
+                .as_deref()
+                .and_then(crate::project_settings::project_root_for)
+                .as_deref(),
+        );
+        saved.env_set = saved.env_set.filter(|name| settings.env_set(name).is_ok());
+        let id = pty.spawn(
+            options.rows.unwrap_or(24),
+            options.cols.unwrap_or(80),
+            saved.cwd.clone(),
+            saved.label.clone(),
+            saved.env_set.clone(),
+            PtySink::Buffer(VecDeque::new()),
+        )?;
+        let rerun = options.rerun_commands && saved.command.is_some();
+        if let Some(command) = saved.command.as_ref().filter(|_| rerun) {
+            pty.write(id, format!("{}\r", crate::shell_quote(command)).as_bytes())?;
+        }
+        ptys.push(RestoredPty { id, saved, rerun });
+    }
+    let _ = std::fs::remove_file(session_path());
+    Ok(Some(RestoredSession {
+        saved_at: session.saved_at,
+        ptys,
+        watchers: session
+            .watchers
+            .into_iter()
+            .filter(|w| Path::new(&w.dir).is_dir())
+            .collect(),
+        files: session
+            .files
+            .into_iter()
+            .filter(|f| Path::new(f).is_file())
+            .collect(),
+    }))
+}
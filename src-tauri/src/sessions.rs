@@ -0,0 +1,830 @@
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// First prompts longer than this are cut in session listings
+const PREVIEW_CHARS: usize = 200;
+
+#[derive(Clone, Copy, Default, serde::Serialize)]
+pub struct TokenUsage {
+    pub(crate) input: u64,
+    pub(crate) output: u64,
+    pub(crate) cache_read: u64,
+    pub(crate) cache_creation: u64,
+}
+
+impl TokenUsage {
+    pub(crate) fn from_json(usage: &Value) -> Self {
+        let n = |key: &str| usage.get(key).and_then(Value::as_u64).unwrap_or(0);
+        TokenUsage {
+            input: n("input_tokens"),
+            output: n("output_tokens"),
+            cache_read: n("cache_read_input_tokens"),
+            cache_creation: n("cache_creation_input_tokens"),
+        }
+    }
+
+    fn add(&mut self, other: &TokenUsage) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cache_read += other.cache_read;
+        self.cache_creation += other.cache_creation;
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct ToolCall {
+    id: String,
+    name: String,
+    input: Value,
+}
+
+#[derive(serde::Serialize)]
+pub struct ToolResult {
+    tool_use_id: String,
+    content: String,
+    is_error: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct SessionTurn {
+    uuid: String,
+    /// user, assistant, or tool (a user message that only carries tool results)
+    role: String,
+    timestamp: Option<String>,
+    text: String,
+    tool_calls: Vec<ToolCall>,
+    tool_results: Vec<ToolResult>,
+    model: Option<String>,
+    usage: Option<TokenUsage>,
+    /// Turns from subagents running inside the session
+    is_sidechain: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct AgentSessionSummary {
+    id: String,
+    path: String,
+    cwd: Option<String>,
+    git_branch: Option<String>,
+    started_at: Option<String>,
+    updated_at: Option<String>,
+    /// User and assistant turns, excluding tool results
+    message_count: usize,
+    first_prompt: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct AgentSession {
+    id: String,
+    cwd: Option<String>,
+    git_branch: Option<String>,
+    turns: Vec<SessionTurn>,
+    usage: TokenUsage,
+}
+
+pub(crate) fn projects_dir() -> PathBuf {
+    Path::new(&crate::get_home_dir())
+        .join(".claude")
+        .join("projects")
+}
+
+/// Claude Code names a project's transcript directory after its path with every character
+/// other than ASCII letters and digits replaced by `-`
+pub(crate) fn project_dir(project: &str) -> PathBuf {
+    let project = crate::git::resolve_repo(project);
+    let name: String = project
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    projects_dir().join(name)
+}
+
+/// Plain text of a message's content, which is either a string or a list of blocks
+fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+#[derive(Default)]
+struct Transcript {
+    turns: Vec<SessionTurn>,
+    cwd: Option<String>,
+    git_branch: Option<String>,
+    /// Title Claude Code generated for the conversation
+    summary: Option<String>,
+}
+
+fn parse_transcript(path: &Path) -> Result<Transcript, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut transcript = Transcript::default();
+    // Turn index per assistant message id
+    let mut assistant_turns: std::collections::HashMap<String, usize> =
+        std::collections::HashMap::new();
+    for line in content.lines() {
+        // A session still being written can end in a partial line
+        let Ok(entry) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let kind = entry.get("type").and_then(Value::as_str).unwrap_or("");
+        if kind == "summary" {
+            if let Some(summary) = entry.get("summary").and_then(Value::as_str) {
+                transcript.summary = Some(summary.to_string());
+            }
+            continue;
+        }
+        if (kind != "user" && kind != "assistant")
+            || entry.get("isMeta") == Some(&Value::Bool(true))
+        {
+            continue;
+        }
+        let Some(message) = entry.get("message") else {
+            continue;
+        };
+        let text_of = |key: &str| entry.get(key).and_then(Value::as_str).map(String::from);
+        if transcript.cwd.is_none() {
+            transcript.cwd = text_of("cwd");
+        }
+        if transcript.git_branch.is_none() {
+            transcript.git_branch = text_of("gitBranch");
+        }
+        let content = message.get("content").cloned().unwrap_or(Value::Null);
+        let blocks = content.as_array().cloned().unwrap_or_default();
+        let block_type = |b: &Value| b.get("type").and_then(Value::as_str).map(String::from);
+
+        let tool_calls: Vec<ToolCall> = blocks
+            .iter()
+            .filter(|b| block_type(b).as_deref() == Some("tool_use"))
+            .map(|b| ToolCall {
+                id: b
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string(),
+                name: b
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string(),
+                input: b.get("input").cloned().unwrap_or(Value::Null),
+            })
+            .collect();
+        let tool_results: Vec<ToolResult> = blocks
+            .iter()
+            .filter(|b| block_type(b).as_deref() == Some("tool_result"))
+            .map(|b| ToolResult {
+                tool_use_id: b
+                    .get("tool_use_id")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string(),
+                content: content_text(b.get("content").unwrap_or(&Value::Null)),
+                is_error: b.get("is_error").and_then(Value::as_bool).unwrap_or(false),
+            })
+            .collect();
+        let text = content_text(&content);
+        let usage = message.get("usage").map(TokenUsage::from_json);
+        let message_id = message.get("id").and_then(Value::as_str).map(String::from);
+
+        // Streamed responses are logged one content block per line under the same message id,
+        // sometimes interleaved with tool results; fold them into one turn and keep the final
+        // usage rather than summing repeats
+        let existing = message_id
+            .as_ref()
+            .filter(|_| kind == "assistant")
+            .and_then(|id| assistant_turns.get(id).copied());
+        if let Some(index) = existing {
+            if let Some(turn) = transcript.turns.get_mut(index) {
+                if !text.is_empty() {
+                    if !turn.text.is_empty() {
+                        turn.text.push('\n');
+                    }
+                    turn.text.push_str(&text);
+                }
+                turn.tool_calls.extend(tool_calls);
+                turn.usage = usage.or(turn.usage);
+                continue;
+            }
+        }
+        if let (Some(id), "assistant") = (message_id, kind) {
+            assistant_turns.insert(id, transcript.turns.len());
+        }
+
+        let role = if kind == "user" && !tool_results.is_empty() && text.is_empty() {
+            "tool"
+        } else {
+            kind
+        };
+        let turn = SessionTurn {
+            uuid: text_of("uuid").unwrap_or_default(),
+            role: role.to_string(),
+            timestamp: text_of("timestamp"),
+            text,
+            tool_calls,
+            tool_results,
+            model: message
+                .get("model")
+                .and_then(Value::as_str)
+                .map(String::from),
+            usage,
+            is_sidechain: entry
+                .get("isSidechain")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        };
+        transcript.turns.push(turn);
+    }
+    Ok(transcript)
+}
+
+/// The user's own prompts, leaving out tool results and subagent turns
+fn prompts(turns: &[SessionTurn]) -> impl DoubleEndedIterator<Item = &SessionTurn> {
+    turns
+        .iter()
+        .filter(|t| t.role == "user" && !t.is_sidechain && !t.text.trim().is_empty())
+}
+
+fn preview(turn: &SessionTurn) -> String {
+    let text = turn.text.trim();
+    match text.char_indices().nth(PREVIEW_CHARS) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+/// Transcripts for a project, most recently updated first
+#[tauri::command]
+pub async fn list_agent_sessions(project: String) -> Result<Vec<AgentSessionSummary>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let dir = project_dir(&project);
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            return Ok(Vec::new());
+        };
+        let mut sessions = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let Some(id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let Ok(transcript) = parse_transcript(&path) else {
+                continue;
+            };
+            let turns = &transcript.turns;
+            let first_prompt = prompts(turns).next().map(preview);
+            sessions.push(AgentSessionSummary {
+                id,
+                path: path.to_string_lossy().to_string(),
+                started_at: turns.first().and_then(|t| t.timestamp.clone()),
+                updated_at: turns.last().and_then(|t| t.timestamp.clone()),
+                message_count: turns.iter().filter(|t| t.role != "tool").count(),
+                first_prompt,
+                cwd: transcript.cwd,
+                git_branch: transcript.git_branch,
+            });
+        }
+        // RFC 3339 timestamps in UTC sort correctly as strings
+        sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        Ok(sessions)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Transcript file of a session, looked up across all projects
+fn session_path(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid session id: {}", id));
+    }
+    let file_name = format!("{}.jsonl", id);
+    std::fs::read_dir(projects_dir())
+        .map_err(|_| "No Claude Code sessions found".to_string())?
+        .flatten()
+        .map(|dir| dir.path().join(&file_name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("Session not found: {}", id))
+}
+
+/// A full transcript as structured turns; the session is looked up across all projects
+#[tauri::command]
+pub async fn read_agent_session(id: String) -> Result<AgentSession, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let transcript = parse_transcript(&session_path(&id)?)?;
+        let mut usage = TokenUsage::default();
+        for turn in &transcript.turns {
+            if let Some(turn_usage) = &turn.usage {
+                usage.add(turn_usage);
+            }
+        }
+        Ok(AgentSession {
+            id,
+            cwd: transcript.cwd,
+            git_branch: transcript.git_branch,
+            turns: transcript.turns,
+            usage,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(serde::Serialize)]
+pub struct ResumableSession {
+    id: String,
+    /// Claude Code's title for the conversation, once it has made one
+    summary: Option<String>,
+    first_prompt: Option<String>,
+    last_prompt: Option<String>,
+    git_branch: Option<String>,
+    updated_at: Option<String>,
+    message_count: usize,
+}
+
+/// Sessions in `project` that `claude --resume` can pick up, most recent first. Transcripts
+/// without a prompt of their own (subagent-only or empty) are left out.
+#[tauri::command]
+pub async fn list_resumable_sessions(project: String) -> Result<Vec<ResumableSession>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let Ok(entries) = std::fs::read_dir(project_dir(&project)) else {
+            return Ok(Vec::new());
+        };
+        let mut sessions: Vec<ResumableSession> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+            .filter_map(|path| {
+                let id = path.file_stem()?.to_string_lossy().to_string();
+                let transcript = parse_transcript(&path).ok()?;
+                let turns = &transcript.turns;
+                let first_prompt = prompts(turns).next().map(preview)?;
+                let last_prompt = prompts(turns).next_back().map(preview);
+                Some(ResumableSession {
+                    id,
+                    summary: transcript.summary.clone(),
+                    last_prompt,
+                    first_prompt: Some(first_prompt),
+                    git_branch: transcript.git_branch.clone(),
+                    updated_at: turns.iter().rev().find_map(|t| t.timestamp.clone()),
+                    message_count: turns.iter().filter(|t| t.role != "tool").count(),
+                })
+            })
+            .collect();
+        sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        Ok(sessions)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Type `claude --resume <id>` into a terminal, from the directory the session ran in; Claude
+/// Code only finds a session from its own project
+#[tauri::command]
+pub async fn resume_session_in_pty(
+    pty: tauri::State<'_, crate::pty::PtyManager>,
+    pty_id: u32,
+    session_id: String,
+) -> Result<(), String> {
+    let cwd = {
+        let session_id = session_id.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            parse_transcript(&session_path(&session_id)?).map(|t| t.cwd)
+        })
+        .await
+        .map_err(|e| e.to_string())??
+    };
+    let cwd = cwd
+        .filter(|cwd| Path::new(cwd).is_dir())
+        .ok_or("The session's project folder no longer exists")?;
+    crate::check_command_exists("claude".to_string())
+        .map_err(|_| "claude is not installed".to_string())?;
+    let command = format!(
+        "cd {} && claude --resume {}\r",
+        crate::shell_quote(&cwd),
+        session_id
+    );
+    pty.write(pty_id, command.as_bytes())
+}
+
+/// Tool input shown in an export is cut after this many lines
+const MAX_EXPORT_CODE_LINES: usize = 200;
+/// Tool output shown in an export is cut after this many lines
+const MAX_EXPORT_OUTPUT_LINES: usize = 40;
+
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct ExportOptions {
+    /// Include what each tool returned, collapsed; failed calls always show their error
+    #[serde(default)]
+    tool_output: bool,
+    /// Include turns from subagents
+    #[serde(default)]
+    sidechains: bool,
+    /// Also write the document to this file
+    path: Option<String>,
+}
+
+/// Pieces of an exported session, shared by the markdown and HTML renderers
+enum Block {
+    Title(String),
+    Meta(Vec<(&'static str, String)>),
+    Heading(&'static str),
+    /// Prose as the user or agent wrote it, usually markdown
+    Text(String),
+    /// A tool call and the file, command or query it acted on
+    Tool {
+        name: String,
+        target: Option<String>,
+    },
+    Code {
+        language: &'static str,
+        content: String,
+    },
+    Output {
+        is_error: bool,
+        content: String,
+    },
+}
+
+fn cap_lines(text: &str, max: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() <= max {
+        return text.trim_end().to_string();
+    }
+    format!(
+        "{}\n… {} more lines",
+        lines[..max].join("\n"),
+        lines.len() - max
+    )
+}
+
+fn prefixed(text: &str, prefix: char) -> impl Iterator<Item = String> + '_ {
+    text.lines().map(move |line| format!("{}{}", prefix, line))
+}
+
+fn edit_diff(old: &str, new: &str) -> String {
+    prefixed(old, '-')
+        .chain(prefixed(new, '+'))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn with_thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn tool_blocks(call: &ToolCall, cwd: Option<&str>) -> Vec<Block> {
+    let input = &call.input;
+    let str_of = |key: &str| input.get(key).and_then(Value::as_str);
+    let path = str_of("file_path")
+        .or_else(|| str_of("notebook_path"))
+        .map(|path| match cwd {
+            Some(cwd) => crate::git::repo_relative(cwd, path),
+            None => path.to_string(),
+        });
+    let tool = |target: Option<String>| Block::Tool {
+        name: call.name.clone(),
+        target,
+    };
+    let diff = |content: String| Block::Code {
+        language: "diff",
+        content: cap_lines(&content, MAX_EXPORT_CODE_LINES),
+    };
+    match call.name.as_str() {
+        "Edit" => vec![
+            tool(path),
+            diff(edit_diff(
+                str_of("old_string").unwrap_or(""),
+                str_of("new_string").unwrap_or(""),
+            )),
+        ],
+        "MultiEdit" => {
+            let edits = input.get("edits").and_then(Value::as_array);
+            let hunks: Vec<String> = edits
+                .into_iter()
+                .flatten()
+                .map(|edit| {
+                    let text = |key: &str| edit.get(key).and_then(Value::as_str).unwrap_or("");
+                    edit_diff(text("old_string"), text("new_string"))
+                })
+                .collect();
+            vec![tool(path), diff(hunks.join("\n@@\n"))]
+        }
+        "Write" => vec![
+            tool(path),
+            diff(
+                prefixed(str_of("content").unwrap_or(""), '+')
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+        ],
+        "Bash" => vec![
+            tool(str_of("description").map(String::from)),
+            Block::Code {
+                language: "sh",
+                content: cap_lines(str_of("command").unwrap_or(""), MAX_EXPORT_CODE_LINES),
+            },
+        ],
+        "TodoWrite" => {
+            let todos = input.get("todos").and_then(Value::as_array);
+            let list: Vec<String> = todos
+                .into_iter()
+                .flatten()
+                .map(|todo| {
+                    let done = todo.get("status").and_then(Value::as_str) == Some("completed");
+                    let content = todo.get("content").and_then(Value::as_str).unwrap_or("");
+                    format!("- [{}] {}", if done { "x" } else { " " }, content)
+                })
+                .collect();
+            vec![tool(None), Block::Text(list.join("\n"))]
+        }
+        _ => {
+            let target = path
+                .or_else(|| str_of("pattern").map(String::from))
+                .or_else(|| str_of("url").map(String::from))
+                .or_else(|| str_of("query").map(String::from));
+            if target.is_some() || input.as_object().is_none_or(|o| o.is_empty()) {
+                vec![tool(target)]
+            } else {
+                vec![
+                    tool(None),
+                    Block::Code {
+                        language: "json",
+                        content: cap_lines(
+                            &serde_json::to_string_pretty(input).unwrap_or_default(),
+                            MAX_EXPORT_CODE_LINES,
+                        ),
+                    },
+                ]
+            }
+        }
+    }
+}
+
+fn session_blocks(id: &str, transcript: &Transcript, options: &ExportOptions) -> Vec<Block> {
+    let turns: Vec<&SessionTurn> = transcript
+        .turns
+        .iter()
+        .filter(|t| options.sidechains || !t.is_sidechain)
+        .collect();
+    let results: std::collections::HashMap<&str, &ToolResult> = turns
+        .iter()
+        .flat_map(|t| &t.tool_results)
+        .map(|r| (r.tool_use_id.as_str(), r))
+        .collect();
+
+    let title = turns
+        .iter()
+        .find(|t| t.role == "user" && !t.text.trim().is_empty())
+        .and_then(|t| t.text.trim().lines().next())
+        .map(|line| match line.char_indices().nth(80) {
+            Some((cut, _)) => format!("{}…", &line[..cut]),
+            None => line.to_string(),
+        })
+        .unwrap_or_else(|| format!("Agent session {}", id));
+    let mut meta = vec![("Session", id.to_string())];
+    if let Some(cwd) = &transcript.cwd {
+        meta.push(("Project", cwd.clone()));
+    }
+    if let Some(branch) = &transcript.git_branch {
+        meta.push(("Branch", branch.clone()));
+    }
+    if let Some(started) = turns.first().and_then(|t| t.timestamp.clone()) {
+        meta.push(("Started", started));
+    }
+    let mut models: Vec<&str> = Vec::new();
+    let mut usage = TokenUsage::default();
+    for turn in &turns {
+        if let Some(model) = turn.model.as_deref().filter(|m| !models.contains(m)) {
+            models.push(model);
+        }
+        if let Some(turn_usage) = &turn.usage {
+            usage.add(turn_usage);
+        }
+    }
+    if !models.is_empty() {
+        meta.push(("Model", models.join(", ")));
+    }
+    meta.push((
+        "Tokens",
+        format!(
+            "{} in · {} out",
+            with_thousands(usage.input + usage.cache_creation + usage.cache_read),
+            with_thousands(usage.output)
+        ),
+    ));
+
+    let mut blocks = vec![Block::Title(title), Block::Meta(meta)];
+    let mut last_role = "";
+    for turn in turns {
+        // Tool results are shown under the call that produced them
+        if turn.role == "tool" {
+            continue;
+        }
+        let role = if turn.role == "user" {
+            "User"
+        } else {
+            "Assistant"
+        };
+        if role != last_role {
+            blocks.push(Block::Heading(role));
+            last_role = role;
+        }
+        if !turn.text.trim().is_empty() {
+            blocks.push(Block::Text(turn.text.trim().to_string()));
+        }
+        for call in &turn.tool_calls {
+            blocks.extend(tool_blocks(call, transcript.cwd.as_deref()));
+            let Some(result) = results.get(call.id.as_str()) else {
+                continue;
+            };
+            if (options.tool_output || result.is_error) && !result.content.trim().is_empty() {
+                blocks.push(Block::Output {
+                    is_error: result.is_error,
+                    content: cap_lines(&result.content, MAX_EXPORT_OUTPUT_LINES),
+                });
+            }
+        }
+    }
+    blocks
+}
+
+/// A code fence longer than any backtick run in `content`
+fn fence(content: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in content.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn render_markdown(blocks: &[Block]) -> String {
+    let parts: Vec<String> = blocks
+        .iter()
+        .map(|block| match block {
+            Block::Title(title) => format!("# {}", title),
+            Block::Meta(fields) => fields
+                .iter()
+                .map(|(label, value)| format!("- **{}:** {}", label, value))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Block::Heading(role) => format!("## {}", role),
+            Block::Text(text) => text.clone(),
+            Block::Tool { name, target } => match target {
+                Some(target) => format!("**{}** `{}`", name, target.replace('`', "'")),
+                None => format!("**{}**", name),
+            },
+            Block::Code { language, content } => {
+                let fence = fence(content);
+                format!("{}{}\n{}\n{}", fence, language, content, fence)
+            }
+            Block::Output { is_error, content } => {
+                let fence = fence(content);
+                format!(
+                    "<details><summary>{}</summary>\n\n{}\n{}\n{}\n\n</details>",
+                    if *is_error { "Error" } else { "Output" },
+                    fence,
+                    content,
+                    fence
+                )
+            }
+        })
+        .collect();
+    format!("{}\n", parts.join("\n\n"))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const EXPORT_CSS: &str =
+    "body{font:15px/1.55 -apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;\
+max-width:860px;margin:40px auto;padding:0 20px;color:#1f2328}\
+h2{margin-top:2em;padding-bottom:.3em;border-bottom:1px solid #d1d9e0}\
+.text{white-space:pre-wrap}.tool{margin:1em 0 .4em;font-weight:600}\
+code,pre{font:13px/1.45 ui-monospace,SFMono-Regular,Menlo,monospace}\
+pre{background:#f6f8fa;padding:12px;border-radius:6px;overflow-x:auto}\
+.add{color:#116329;background:#dafbe1}.del{color:#82071e;background:#ffebe9}\
+details.error summary{color:#cf222e}ul.meta{color:#59636e;padding-left:1.2em}";
+
+fn render_html(blocks: &[Block]) -> String {
+    let mut title = String::new();
+    let mut body = String::new();
+    for block in blocks {
+        match block {
+            Block::Title(text) => {
+                title = escape_html(text);
+                body.push_str(&format!("<h1>{}</h1>\n", title));
+            }
+            Block::Meta(fields) => {
+                body.push_str("<ul class=\"meta\">\n");
+                for (label, value) in fields {
+                    body.push_str(&format!(
+                        "<li><strong>{}:</strong> {}</li>\n",
+                        label,
+                        escape_html(value)
+                    ));
+                }
+                body.push_str("</ul>\n");
+            }
+            Block::Heading(role) => body.push_str(&format!("<h2>{}</h2>\n", role)),
+            Block::Text(text) => body.push_str(&format!(
+                "<div class=\"text\">{}</div>\n",
+                escape_html(text)
+            )),
+            Block::Tool { name, target } => body.push_str(&format!(
+                "<div class=\"tool\">{}{}</div>\n",
+                escape_html(name),
+                target
+                    .as_deref()
+                    .map(|t| format!(" <code>{}</code>", escape_html(t)))
+                    .unwrap_or_default()
+            )),
+            Block::Code { language, content } => {
+                let lines: Vec<String> = content
+                    .lines()
+                    .map(|line| {
+                        let class = match (*language, line.chars().next()) {
+                            ("diff", Some('+')) => " class=\"add\"",
+                            ("diff", Some('-')) => " class=\"del\"",
+                            _ => "",
+                        };
+                        if class.is_empty() {
+                            escape_html(line)
+                        } else {
+                            format!("<span{}>{}</span>", class, escape_html(line))
+                        }
+                    })
+                    .collect();
+                body.push_str(&format!("<pre><code>{}</code></pre>\n", lines.join("\n")));
+            }
+            Block::Output { is_error, content } => body.push_str(&format!(
+                "<details{}><summary>{}</summary><pre><code>{}</code></pre></details>\n",
+                if *is_error { " class=\"error\"" } else { "" },
+                if *is_error { "Error" } else { "Output" },
+                escape_html(content)
+            )),
+        }
+    }
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        title, EXPORT_CSS, body
+    )
+}
+
+/// A session as a standalone markdown or HTML document — prompts, replies, tool calls, and edits
+/// as diffs — for attaching to a PR or design doc. Returns the document.
+#[tauri::command]
+pub async fn export_session(
+    session_id: String,
+    format: Option<ExportFormat>,
+    options: Option<ExportOptions>,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let transcript = parse_transcript(&session_path(&session_id)?)?;
+        let options = options.unwrap_or_default();
+        let blocks = session_blocks(&session_id, &transcript, &options);
+        let document = match format.unwrap_or_default() {
+            ExportFormat::Markdown => render_markdown(&blocks),
+            ExportFormat::Html => render_html(&blocks),
+        };
+        if let Some(path) = &options.path {
+            std::fs::write(path, &document)
+                .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        }
+        Ok(document)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,267 @@
+//! System-wide shortcuts, registered through the global-shortcut plugin and handled here so they
+//! work while the app is hidden or in the background. Bindings persist in ~/.ade/shortcuts.json.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+/// (action, description)
+const ACTIONS: [(&str, &str); 3] = [
+    ("toggle-window", "Show or hide the app"),
+    (
+        "new-agent-task-from-clipboard",
+        "Start a new agent task with the clipboard as its prompt",
+    ),
+    (
+        "focus-terminal",
+        "Bring the app forward and focus the terminal",
+    ),
+];
+
+#[derive(Clone, serde::Serialize)]
+pub struct ShortcutBinding {
+    action: String,
+    description: String,
+    /// Like "CommandOrControl+Shift+Space"; None when unbound
+    accelerator: Option<String>,
+    registered: bool,
+    /// Why a saved binding couldn't be registered, usually another app holding it
+    error: Option<String>,
+}
+
+/// Sent to the frontend when a shortcut fires, after the window has been shown
+#[derive(Clone, serde::Serialize)]
+struct ShortcutTriggered {
+    action: String,
+    /// The clipboard's text, for new-agent-task-from-clipboard
+    clipboard: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct ShortcutManager {
+    /// action → accelerator, as saved
+    bindings: Arc<Mutex<HashMap<String, String>>>,
+    /// action → why registering its binding failed
+    errors: Arc<Mutex<HashMap<String, String>>>,
+}
+
+fn bindings_path() -> std::path::PathBuf {
+    crate::ade_path("shortcuts.json")
+}
+
+impl ShortcutManager {
+    pub fn new() -> Self {
+        let bindings = std::fs::read_to_string(bindings_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            bindings: Arc::new(Mutex::new(bindings)),
+            errors: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn save(&self, bindings: &HashMap<String, String>) -> Result<(), String> {
+        let path = bindings_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(bindings).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to save shortcuts: {}", e))
+    }
+
+    /// The action bound to `shortcut`
+    fn action_for(&self, shortcut: &Shortcut) -> Option<String> {
+        self.bindings
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(_, accelerator)| parse(accelerator).is_ok_and(|s| s == *shortcut))
+            .map(|(action, _)| action.clone())
+    }
+
+    fn list(&self, app: &AppHandle) -> Vec<ShortcutBinding> {
+        let bindings = self.bindings.lock().unwrap();
+        let errors = self.errors.lock().unwrap();
+        ACTIONS
+            .iter()
+            .map(|(action, description)| {
+                let accelerator = bindings.get(*action).cloned();
+                ShortcutBinding {
+                    action: action.to_string(),
+                    description: description.to_string(),
+                    registered: accelerator
+                        .as_deref()
+                        .and_then(|a| parse(a).ok())
+                        .is_some_and(|s| app.global_shortcut().is_registered(s)),
+                    accelerator,
+                    error: errors.get(*action).cloned(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn parse(accelerator: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(accelerator).map_err(|e| format!("Invalid shortcut {}: {}", accelerator, e))
+}
+
+fn check_action(action: &str) -> Result<(), String> {
+    match ACTIONS.iter().any(|(a, _)| *a == action) {
+        true => Ok(()),
+        false => Err(format!("Unknown shortcut action: {}", action)),
+    }
+}
+
+fn main_window(app: &AppHandle) -> Option<tauri::WebviewWindow> {
+    app.get_webview_window("main")
+        .or_else(|| app.webview_windows().into_values().next())
+}
+
+fn show(window: &tauri::WebviewWindow) {
+    let _ = window.show();
+    let _ = window.unminimize();
+    let _ = window.set_focus();
+}
+
+fn run_action(app: &AppHandle, action: &str) {
+    let Some(window) = main_window(app) else {
+        return;
+    };
+    if action == "toggle-window" {
+        let front = window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false);
+        match front {
+            true => {
+                let _ = window.hide();
+            }
+            false => show(&window),
+        }
+        return;
+    }
+    show(&window);
+    let clipboard = match action {
+        "new-agent-task-from-clipboard" => {
+            use tauri_plugin_clipboard_manager::ClipboardExt;
+            app.clipboard().read_text().ok()
+        }
+        _ => None,
+    };
+    let _ = app.emit(
+        "global-shortcut",
+        ShortcutTriggered {
+            action: action.to_string(),
+            clipboard,
+        },
+    );
+}
+
+/// The global-shortcut plugin's handler: runs the action bound to a pressed shortcut
+pub fn handle(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    if let Some(action) = app.state::<ShortcutManager>().action_for(shortcut) {
+        run_action(app, &action);
+    }
+}
+
+/// Register the saved bindings. Those another app already holds are left unregistered, with
+/// the error reported by list_shortcuts.
+pub fn start(app: &tauri::App) {
+    let manager = app.state::<ShortcutManager>();
+    let bindings = manager.bindings.lock().unwrap().clone();
+    let mut errors = manager.errors.lock().unwrap();
+    for (action, accelerator) in bindings {
+        let result = parse(&accelerator).and_then(|shortcut| {
+            app.global_shortcut()
+                .register(shortcut)
+                .map_err(|e| format!("{} is taken by another application: {}", accelerator, e))
+        });
+        if let Err(e) = result {
+            errors.insert(action, e);
+        }
+    }
+}
+
+/// Every action with its binding and whether it is registered
+#[tauri::command]
+pub fn list_shortcuts(
+    app: AppHandle,
+    state: tauri::State<'_, ShortcutManager>,
+) -> Vec<ShortcutBinding> {
+    state.list(&app)
+}
+
+/// Bind `action` to `accelerator` (like "CommandOrControl+Shift+Space"), replacing its current
+/// binding. Fails without changing anything when another action uses the shortcut or another
+/// application has registered it.
+#[tauri::command]
+pub fn register_shortcut(
+    app: AppHandle,
+    state: tauri::State<'_, ShortcutManager>,
+    action: String,
+    accelerator: String,
+) -> Result<Vec<ShortcutBinding>, String> {
+    check_action(&action)?;
+    let shortcut = parse(&accelerator)?;
+    let mut bindings = state.bindings.lock().unwrap();
+    let conflict = bindings
+        .iter()
+        .find(|(a, acc)| **a != action && parse(acc).is_ok_and(|s| s == shortcut));
+    if let Some((other, _)) = conflict {
+        return Err(format!("{} is already used for {}", accelerator, other));
+    }
+    let previous = bindings
+        .get(&action)
+        .and_then(|acc| parse(acc).ok())
+        .filter(|s| app.global_shortcut().is_registered(s.clone()));
+    if previous.as_ref() == Some(&shortcut) {
+        drop(bindings);
+        return Ok(state.list(&app));
+    }
+    if let Some(previous) = &previous {
+        let _ = app.global_shortcut().unregister(previous.clone());
+    }
+    if let Err(e) = app.global_shortcut().register(shortcut) {
+        // Put the old binding back
+        if let Some(previous) = previous {
+            let _ = app.global_shortcut().register(previous);
+        }
+        return Err(format!(
+            "{} is taken by another application: {}",
+            accelerator, e
+        ));
+    }
+    bindings.insert(action.clone(), accelerator);
+    state.save(&bindings)?;
+    drop(bindings);
+    state.errors.lock().unwrap().remove(&action);
+    Ok(state.list(&app))
+}
+
+/// Remove the binding of `action`
+#[tauri::command]
+pub fn unregister_shortcut(
+    app: AppHandle,
+    state: tauri::State<'_, ShortcutManager>,
+    action: String,
+) -> Result<Vec<ShortcutBinding>, String> {
+    check_action(&action)?;
+    let mut bindings = state.bindings.lock().unwrap();
+    if let Some(accelerator) = bindings.remove(&action) {
+        if let Ok(shortcut) = parse(&accelerator) {
+            if app.global_shortcut().is_registered(shortcut.clone()) {
+                app.global_shortcut()
+                    .unregister(shortcut)
+                    .map_err(|e| format!("Failed to unregister {}: {}", accelerator, e))?;
+            }
+        }
+        state.save(&bindings)?;
+    }
+    drop(bindings);
+    state.errors.lock().unwrap().remove(&action);
+    Ok(state.list(&app))
+}
@@ -0,0 +1,285 @@
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+#[derive(serde::Serialize)]
+pub struct SlashCommand {
+    /// Path under the commands directory without `.md`, e.g. `frontend/component`
+    name: String,
+    /// What is typed after `/`: the file name; subdirectories only namespace the description
+    command: String,
+    scope: String,
+    path: String,
+    description: Option<String>,
+    argument_hint: Option<String>,
+    model: Option<String>,
+    frontmatter: Map<String, Value>,
+}
+
+/// Starting points offered when creating a command
+const TEMPLATES: &[(&str, &str)] = &[
+    (
+        "basic",
+        "---\ndescription: Describe what this command does\nargument-hint: [what]\n---\n\n$ARGUMENTS\n",
+    ),
+    (
+        "shell-context",
+        "---\ndescription: Describe what this command does\nallowed-tools: Bash(git status:*), Bash(git diff:*)\n---\n\n## Context\n\n- Status: !`git status --short`\n- Diff: !`git diff HEAD`\n\n## Task\n\n$ARGUMENTS\n",
+    ),
+    (
+        "file-review",
+        "---\ndescription: Review a file\nargument-hint: <file>\n---\n\nReview @$1 for bugs, unclear naming, and missing error handling. Suggest concrete fixes.\n",
+    ),
+];
+
+/// `user` (~/.claude/commands) or `project` (<project>/.claude/commands)
+fn commands_dir(scope: &str, project: Option<&str>) -> Result<PathBuf, String> {
+    match scope {
+        "user" => Ok(PathBuf::from(crate::get_home_dir())
+            .join(".claude")
+            .join("commands")),
+        "project" => {
+            let project = project.ok_or("Scope project needs a project path")?;
+            Ok(PathBuf::from(crate::git::resolve_repo(project))
+                .join(".claude")
+                .join("commands"))
+        }
+        _ => Err(format!("Unknown command scope: {}", scope)),
+    }
+}
+
+/// Names are `/`-separated segments of letters, digits, `-` and `_`, so they can never
+/// escape the commands directory
+fn command_path(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let name = name.trim().trim_end_matches(".md");
+    let valid = !name.is_empty()
+        && name.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        });
+    if !valid {
+        return Err(format!("Invalid command name: {}", name));
+    }
+    Ok(dir.join(format!("{}.md", name)))
+}
+
+fn yaml_scalar(raw: &str) -> Value {
+    let raw = raw.trim();
+    let unquoted = raw
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .or_else(|| raw.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')));
+    match (unquoted, raw) {
+        (Some(s), _) => Value::String(s.to_string()),
+        (None, "true") => Value::Bool(true),
+        (None, "false") => Value::Bool(false),
+        (None, _) => Value::String(raw.to_string()),
+    }
+}
+
+/// The flat subset of YAML command files use: `key: value` and `- item` lists under an
+/// empty key. Bracketed values stay strings, since `argument-hint: [file]` is a hint, not a list
+pub(crate) fn parse_frontmatter(content: &str) -> Map<String, Value> {
+    let mut fields = Map::new();
+    let mut lines = content.lines();
+    if lines.next().map(str::trim_end) != Some("---") {
+        return fields;
+    }
+    let mut list_key: Option<String> = None;
+    for line in lines {
+        if line.trim_end() == "---" {
+            break;
+        }
+        if let (Some(key), Some(item)) = (&list_key, line.trim_start().strip_prefix("- ")) {
+            match fields.get_mut(key) {
+                Some(Value::Array(items)) => items.push(yaml_scalar(item)),
+                Some(value) => *value = Value::Array(vec![yaml_scalar(item)]),
+                None => {}
+            }
+            continue;
+        }
+        if line.starts_with([' ', '\t', '#']) {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_string();
+        let value = value.trim();
+        list_key = None;
+        let value = if value.is_empty() {
+            list_key = Some(key.clone());
+            Value::Null
+        } else {
+            yaml_scalar(value)
+        };
+        fields.insert(key, value);
+    }
+    fields
+}
+
+/// Everything after the frontmatter block, or the whole content when there is none
+pub(crate) fn strip_frontmatter(content: &str) -> &str {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return content;
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == "---" {
+            return rest[offset..].trim_start_matches(['\r', '\n']);
+        }
+    }
+    content
+}
+
+fn read_command(scope: &str, dir: &Path, path: &Path) -> Result<SlashCommand, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let frontmatter = parse_frontmatter(&content);
+    let text = |key: &str| {
+        frontmatter
+            .get(key)
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+    let name = path
+        .strip_prefix(dir)
+        .unwrap_or(path)
+        .with_extension("")
+        .to_string_lossy()
+        .replace('\\', "/");
+    Ok(SlashCommand {
+        command: name.rsplit('/').next().unwrap_or(&name).to_string(),
+        scope: scope.to_string(),
+        path: path.to_string_lossy().to_string(),
+        description: text("description"),
+        argument_hint: text("argument-hint"),
+        model: text("model"),
+        frontmatter,
+        name,
+    })
+}
+
+fn collect_commands(scope: &str, dir: &Path, current: &Path, out: &mut Vec<SlashCommand>) {
+    let Ok(entries) = std::fs::read_dir(current) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_commands(scope, dir, &path, out);
+        } else if path.extension().and_then(|e| e.to_str()) == Some("md") {
+            if let Ok(command) = read_command(scope, dir, &path) {
+                out.push(command);
+            }
+        }
+    }
+}
+
+/// Remove directories left empty by a rename or delete, up to the commands directory
+fn prune_empty_dirs(dir: &Path, from: &Path) {
+    let mut current = from.parent();
+    while let Some(parent) = current {
+        if parent == dir || !parent.starts_with(dir) || std::fs::remove_dir(parent).is_err() {
+            break;
+        }
+        current = parent.parent();
+    }
+}
+
+/// User commands, then project commands when a project is given, each sorted by name
+#[tauri::command]
+pub fn list_slash_commands(project: Option<String>) -> Result<Vec<SlashCommand>, String> {
+    let mut commands = Vec::new();
+    for scope in ["user", "project"] {
+        if scope == "project" && project.is_none() {
+            continue;
+        }
+        let dir = commands_dir(scope, project.as_deref())?;
+        let mut scoped = Vec::new();
+        collect_commands(scope, &dir, &dir, &mut scoped);
+        scoped.sort_by(|a, b| a.name.cmp(&b.name));
+        commands.extend(scoped);
+    }
+    Ok(commands)
+}
+
+#[tauri::command]
+pub fn list_slash_command_templates() -> Vec<String> {
+    TEMPLATES.iter().map(|(id, _)| id.to_string()).collect()
+}
+
+/// Create a command file from a template (default `basic`); fails if the name is taken
+#[tauri::command]
+pub fn create_slash_command(
+    scope: String,
+    project: Option<String>,
+    name: String,
+    template: Option<String>,
+) -> Result<SlashCommand, String> {
+    let template = template.unwrap_or_else(|| "basic".to_string());
+    let content = TEMPLATES
+        .iter()
+        .find(|(id, _)| *id == template)
+        .map(|(_, content)| *content)
+        .ok_or_else(|| format!("Unknown command template: {}", template))?;
+    let dir = commands_dir(&scope, project.as_deref())?;
+    let path = command_path(&dir, &name)?;
+    if path.exists() {
+        return Err(format!("Command already exists: {}", path.display()));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    read_command(&scope, &dir, &path)
+}
+
+#[tauri::command]
+pub fn rename_slash_command(
+    scope: String,
+    project: Option<String>,
+    name: String,
+    new_name: String,
+) -> Result<SlashCommand, String> {
+    let dir = commands_dir(&scope, project.as_deref())?;
+    let from = command_path(&dir, &name)?;
+    let to = command_path(&dir, &new_name)?;
+    if !from.is_file() {
+        return Err(format!("Command not found: {}", name));
+    }
+    if to.exists() {
+        return Err(format!("Command already exists: {}", to.display()));
+    }
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::rename(&from, &to).map_err(|e| format!("Failed to rename command: {}", e))?;
+    prune_empty_dirs(&dir, &from);
+    read_command(&scope, &dir, &to)
+}
+
+#[tauri::command]
+pub fn delete_slash_command(
+    scope: String,
+    project: Option<String>,
+    name: String,
+) -> Result<(), String> {
+    let dir = commands_dir(&scope, project.as_deref())?;
+    let path = command_path(&dir, &name)?;
+    if !path.is_file() {
+        return Err(format!("Command not found: {}", name));
+    }
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete command: {}", e))?;
+    prune_empty_dirs(&dir, &path);
+    Ok(())
+}
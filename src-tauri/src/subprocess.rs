@@ -0,0 +1,286 @@
+//! Piped subprocesses for git, package managers and helper tools: things that don't need a
+//! terminal, only their output and exit code.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::ipc::Channel;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// How long a stopped process gets to exit after SIGTERM before it is killed
+const KILL_GRACE: Duration = Duration::from_secs(3);
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+/// How long output is still read once a process has exited. Something it left running in the
+/// background can hold the pipes open indefinitely.
+const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum CommandEvent {
+    /// Always the first event; `run_id` is what cancel_command takes
+    #[serde(rename = "started")]
+    Started { run_id: u32, pid: u32 },
+    /// A line of output
+    #[serde(rename = "stdout")]
+    Stdout { text: String },
+    #[serde(rename = "stderr")]
+    Stderr { text: String },
+    /// Always the last event
+    #[serde(rename = "finished")]
+    Finished {
+        exit_code: Option<i32>,
+        success: bool,
+        cancelled: bool,
+        timed_out: bool,
+        duration_ms: u64,
+    },
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct RunCommandOptions {
+    cwd: Option<String>,
+    /// Added to the app's environment
+    #[serde(default)]
+    env: HashMap<String, String>,
+    /// Milliseconds before the process is stopped; 10 minutes by default, 0 for no limit
+    timeout_ms: Option<u64>,
+}
+
+#[derive(Clone)]
+pub struct CommandManager {
+    running: Arc<Mutex<HashMap<u32, Child>>>,
+    cancelled: Arc<Mutex<Vec<u32>>>,
+    next_id: Arc<Mutex<u32>>,
+}
+
+impl CommandManager {
+    pub fn new() -> Self {
+        Self {
+            running: Arc::new(Mutex::new(HashMap::new())),
+            cancelled: Arc::new(Mutex::new(Vec::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+
+    /// Exit status of a running command, None while it runs
+    fn try_wait(&self, run_id: u32) -> Option<Option<i32>> {
+        let mut running = self.running.lock().unwrap();
+        let child = running.get_mut(&run_id)?;
+        match child.try_wait() {
+            Ok(Some(status)) => Some(status.code()),
+            Ok(None) => None,
+            Err(_) => Some(None),
+        }
+    }
+
+    fn signal(&self, run_id: u32, signal: &str) {
+        if let Some(child) = self.running.lock().unwrap().get_mut(&run_id) {
+            let _ = signal_group(child, signal);
+        }
+    }
+}
+
+/// Send `signal` (TERM or KILL) to the child's process group, which it leads when spawned with
+/// `process_group(0)`, so what it started stops too
+pub(crate) fn signal_group(child: &mut Child, signal: &str) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        // Negative pid: the whole process group
+        let status = std::process::Command::new("kill")
+            .args([&format!("-{}", signal), "--", &format!("-{}", child.id())])
+            .status();
+        if status.is_ok_and(|s| s.success()) {
+            return Ok(());
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = signal;
+    child.kill()
+}
+
+/// Read the child's stdout and stderr on threads, passing each line (with its newline) to
+/// `on_line` along with "stdout" or "stderr". The threads end at EOF.
+pub(crate) fn stream_lines(
+    child: &mut Child,
+    on_line: impl Fn(&'static str, String) + Clone + Send + 'static,
+) -> Vec<JoinHandle<()>> {
+    [
+        child
+            .stdout
+            .take()
+            .map(|s| ("stdout", Box::new(s) as Box<dyn Read + Send>)),
+        child
+            .stderr
+            .take()
+            .map(|s| ("stderr", Box::new(s) as Box<dyn Read + Send>)),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|(stream, reader)| {
+        let on_line = on_line.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                on_line(stream, line + "\n");
+            }
+        })
+    })
+    .collect()
+}
+
+/// Wait for `readers` to finish, giving up on those still going at `OUTPUT_GRACE`
+pub(crate) fn join_readers(readers: Vec<JoinHandle<()>>) {
+    let deadline = Instant::now() + OUTPUT_GRACE;
+    for reader in readers {
+        while !reader.is_finished() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        if reader.is_finished() {
+            let _ = reader.join();
+        }
+    }
+}
+
+/// The program to execute: a path as given, or a bare name looked up the way agent CLIs are,
+/// which also works when the app was launched without the user's PATH
+fn resolve_program(program: &str) -> Result<String, String> {
+    if program.contains('/') {
+        return Ok(program.to_string());
+    }
+    let simple = !program.is_empty()
+        && program
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '+'));
+    if !simple {
+        return Err(format!("Invalid program name: {}", program));
+    }
+    crate::check_command_exists(program.to_string())
+        .map_err(|_| format!("{} is not installed", program))
+}
+
+/// Run `program` with `args`, without a shell, streaming stdout and stderr line by line.
+/// The process is stopped when it runs past the timeout or cancel_command is called with the
+/// run id from the `started` event. Resolves to the exit code once it has exited; None when it
+/// was ended by a signal.
+#[tauri::command]
+pub async fn run_command(
+    state: tauri::State<'_, CommandManager>,
+    program: String,
+    args: Vec<String>,
+    options: Option<RunCommandOptions>,
+    on_event: Channel<CommandEvent>,
+) -> Result<Option<i32>, String> {
+    let options = options.unwrap_or_default();
+    let path = resolve_program(&program)?;
+    let mut cmd = std::process::Command::new(&path);
+    cmd.args(&args)
+        .envs(&options.env)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+    if let Some(cwd) = &options.cwd {
+        cmd.current_dir(cwd);
+    }
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    let child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    let run_id = {
+        let mut next = state.next_id.lock().unwrap();
+        let id = *next;
+        *next += 1;
+        id
+    };
+    // Registered before `started`, so cancel_command can stop it as soon as the id is known
+    let pid = child.id();
+    state.running.lock().unwrap().insert(run_id, child);
+    let _ = on_event.send(CommandEvent::Started { run_id, pid });
+    // Set once `finished` is sent, silencing readers that were given up on
+    let closed = Arc::new(AtomicBool::new(false));
+    let on_line = {
+        let on_event = on_event.clone();
+        let closed = closed.clone();
+        move |stream, text| {
+            if closed.load(Ordering::SeqCst) {
+                return;
+            }
+            let _ = on_event.send(match stream {
+                "stdout" => CommandEvent::Stdout { text },
+                _ => CommandEvent::Stderr { text },
+            });
+        }
+    };
+    let readers = match state.running.lock().unwrap().get_mut(&run_id) {
+        Some(child) => stream_lines(child, on_line),
+        None => Vec::new(),
+    };
+
+    let manager = state.inner().clone();
+    let timeout = match options.timeout_ms {
+        Some(0) => None,
+        Some(ms) => Some(Duration::from_millis(ms)),
+        None => Some(DEFAULT_TIMEOUT),
+    };
+    let started = Instant::now();
+    let (exit_code, timed_out) = tauri::async_runtime::spawn_blocking(move || {
+        let mut stopping: Option<Instant> = None;
+        let mut killed = false;
+        let mut timed_out = false;
+        let exit_code = loop {
+            if let Some(code) = manager.try_wait(run_id) {
+                break code;
+            }
+            if stopping.is_none() && timeout.is_some_and(|t| started.elapsed() >= t) {
+                timed_out = true;
+                manager.signal(run_id, "TERM");
+                stopping = Some(Instant::now());
+            }
+            if stopping.is_none() && manager.cancelled.lock().unwrap().contains(&run_id) {
+                stopping = Some(Instant::now());
+            }
+            if !killed && stopping.is_some_and(|s| s.elapsed() >= KILL_GRACE) {
+                manager.signal(run_id, "KILL");
+                killed = true;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        };
+        manager.running.lock().unwrap().remove(&run_id);
+        join_readers(readers);
+        closed.store(true, Ordering::SeqCst);
+        (exit_code, timed_out)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let cancelled = {
+        let mut cancelled = state.cancelled.lock().unwrap();
+        let was = cancelled.contains(&run_id);
+        cancelled.retain(|id| *id != run_id);
+        was
+    };
+    let _ = on_event.send(CommandEvent::Finished {
+        exit_code,
+        success: exit_code == Some(0) && !cancelled && !timed_out,
+        cancelled,
+        timed_out,
+        duration_ms: started.elapsed().as_millis() as u64,
+    });
+    Ok(exit_code)
+}
+
+/// Stop a command started by run_command: SIGTERM to its process group, then SIGKILL if it
+/// hasn't exited within a few seconds
+#[tauri::command]
+pub fn cancel_command(state: tauri::State<'_, CommandManager>, run_id: u32) -> Result<(), String> {
+    let mut running = state.running.lock().unwrap();
+    let Some(child) = running.get_mut(&run_id) else {
+        return Ok(());
+    };
+    state.cancelled.lock().unwrap().push(run_id);
+    signal_group(child, "TERM").map_err(|e| format!("Failed to stop command: {}", e))
+}
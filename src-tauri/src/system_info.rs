@@ -0,0 +1,171 @@
+//! Facts about the machine for diagnostics, bug reports and defaults that scale with it.
+//! Each probe uses what the platform ships with; what can't be read is None.
+
+const GB: u64 = 1024 * 1024 * 1024;
+
+#[derive(serde::Serialize)]
+pub struct SystemInfo {
+    /// macos, linux or windows
+    os: String,
+    /// Like "14.5" on macOS or "Ubuntu 24.04 LTS" on Linux
+    os_version: Option<String>,
+    kernel: Option<String>,
+    /// x86_64 or aarch64
+    arch: String,
+    cpu_count: usize,
+    total_memory_bytes: Option<u64>,
+    /// Memory available to new processes without swapping, including reclaimable caches
+    free_memory_bytes: Option<u64>,
+    /// The volume measured: the workspace's, else the home directory's
+    disk_path: String,
+    disk_total_bytes: Option<u64>,
+    disk_free_bytes: Option<u64>,
+    app_version: String,
+    suggested: SuggestedDefaults,
+}
+
+/// Defaults sized to the machine, for settings the user hasn't set
+#[derive(serde::Serialize)]
+pub struct SuggestedDefaults {
+    scrollback_lines: u32,
+    index_threads: usize,
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !stdout.is_empty()).then_some(stdout)
+}
+
+fn os_version() -> Option<String> {
+    if cfg!(target_os = "macos") {
+        return run("sw_vers", &["-productVersion"]);
+    }
+    if cfg!(windows) {
+        // "Microsoft Windows [Version 10.0.22631.3880]"
+        return run("cmd", &["/c", "ver"]);
+    }
+    let release = std::fs::read_to_string("/etc/os-release").ok()?;
+    release.lines().find_map(|line| {
+        let value = line.strip_prefix("PRETTY_NAME=")?;
+        Some(value.trim_matches('"').to_string())
+    })
+}
+
+/// (total, available) from /proc/meminfo
+fn linux_memory() -> (Option<u64>, Option<u64>) {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").unwrap_or_default();
+    // Lines look like "MemTotal:       16318480 kB"
+    let field = |name: &str| {
+        meminfo.lines().find_map(|line| {
+            let kb = line.strip_prefix(name)?.trim().trim_end_matches(" kB");
+            kb.trim().parse::<u64>().ok().map(|kb| kb * 1024)
+        })
+    };
+    (field("MemTotal:"), field("MemAvailable:"))
+}
+
+/// (total, available) from sysctl and vm_stat; free, inactive and speculative pages count as
+/// available, as Activity Monitor does
+fn mac_memory() -> (Option<u64>, Option<u64>) {
+    let total = run("sysctl", &["-n", "hw.memsize"]).and_then(|s| s.parse().ok());
+    let available = run("vm_stat", &[]).and_then(|vm_stat| {
+        // "Mach Virtual Memory Statistics: (page size of 16384 bytes)"
+        let page_size: u64 = vm_stat
+            .lines()
+            .next()?
+            .split("page size of ")
+            .nth(1)?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()?;
+        let pages = |name: &str| {
+            vm_stat.lines().find_map(|line| {
+                let count = line.strip_prefix(name)?.trim().trim_end_matches('.');
+                count.parse::<u64>().ok()
+            })
+        };
+        let free = pages("Pages free:")?
+            + pages("Pages inactive:").unwrap_or(0)
+            + pages("Pages speculative:").unwrap_or(0);
+        Some(free * page_size)
+    });
+    (total, available)
+}
+
+fn memory() -> (Option<u64>, Option<u64>) {
+    if cfg!(target_os = "macos") {
+        mac_memory()
+    } else if cfg!(target_os = "linux") {
+        linux_memory()
+    } else {
+        (None, None)
+    }
+}
+
+/// (total, free) bytes of the volume holding `dir`, from `df`
+pub(crate) fn disk_space(dir: &str) -> Option<(u64, u64)> {
+    let output = std::process::Command::new("df")
+        .args(["-Pk", dir])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Filesystem 1024-blocks Used Available Capacity Mounted-on
+    let mut fields = stdout.lines().nth(1)?.split_whitespace().skip(1);
+    let total = fields.next()?.parse::<u64>().ok()?;
+    let available = fields.nth(1)?.parse::<u64>().ok()?;
+    Some((total * 1024, available * 1024))
+}
+
+fn suggested(cpu_count: usize, total_memory: Option<u64>) -> SuggestedDefaults {
+    let memory = total_memory.unwrap_or(8 * GB);
+    SuggestedDefaults {
+        scrollback_lines: match memory {
+            m if m < 8 * GB => 5_000,
+            m if m < 32 * GB => 10_000,
+            _ => 25_000,
+        },
+        // Leave half the cores to the terminals and agents
+        index_threads: (cpu_count / 2).clamp(1, 8),
+    }
+}
+
+/// OS, CPU, memory and disk space of the volume holding `workspace` (the home directory by
+/// default), with defaults suggested for this machine
+#[tauri::command]
+pub async fn get_system_info(
+    app: tauri::AppHandle,
+    workspace: Option<String>,
+) -> Result<SystemInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let cpu_count = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let (total_memory_bytes, free_memory_bytes) = memory();
+        let disk_path = workspace
+            .filter(|dir| std::path::Path::new(dir).is_dir())
+            .unwrap_or_else(crate::get_home_dir);
+        let disk = disk_space(&disk_path);
+        SystemInfo {
+            os: std::env::consts::OS.to_string(),
+            os_version: os_version(),
+            kernel: match cfg!(windows) {
+                true => None,
+                false => run("uname", &["-r"]),
+            },
+            arch: std::env::consts::ARCH.to_string(),
+            cpu_count,
+            total_memory_bytes,
+            free_memory_bytes,
+            disk_total_bytes: disk.map(|(total, _)| total),
+            disk_free_bytes: disk.map(|(_, free)| free),
+            disk_path,
+            app_version: app.package_info().version.to_string(),
+            suggested: suggested(cpu_count, total_memory_bytes),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
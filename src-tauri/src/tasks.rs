@@ -0,0 +1,648 @@
+use crate::dev_servers::{DevServerDetector, DevServerManager};
+use crate::diagnostics::{DiagnosticsCollector, DiagnosticsManager};
+use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::ipc::Channel;
+
+/// Finished runs kept in ~/.ade/task-history.json, newest last
+const MAX_HISTORY: usize = 200;
+const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+#[derive(Clone, serde::Serialize)]
+pub struct Task {
+    /// Unique within the root: the name, or `source:name` when several sources define it
+    pub(crate) id: String,
+    pub(crate) name: String,
+    /// npm (for any JS package manager), make, just, or cargo
+    source: String,
+    /// What run_task executes
+    pub(crate) command: String,
+    /// The script body, alias expansion, or the comment documenting the target
+    description: Option<String>,
+    #[serde(skip)]
+    pub(crate) program: String,
+    #[serde(skip)]
+    pub(crate) args: Vec<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum TaskEvent {
+    #[serde(rename = "started")]
+    Started {
+        run_id: u32,
+        task: String,
+        command: String,
+        started_at: u64,
+    },
+    /// A line of output; in PTY mode, a chunk of terminal output
+    #[serde(rename = "output")]
+    Output {
+        /// stdout, stderr, or pty
+        stream: String,
+        text: String,
+    },
+    /// Always the last event
+    #[serde(rename = "finished")]
+    Finished {
+        exit_code: Option<i32>,
+        success: bool,
+        cancelled: bool,
+        duration_ms: u64,
+    },
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct RunTaskOptions {
+    /// Run in a pseudo-terminal so tools keep their colors and progress output
+    #[serde(default)]
+    pty: bool,
+    rows: Option<u16>,
+    cols: Option<u16>,
+    /// Env set from the project settings, instead of the task's own or the default
+    env_set: Option<String>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct TaskRun {
+    root: String,
+    task: String,
+    command: String,
+    started_at: u64,
+    finished_at: u64,
+    exit_code: Option<i32>,
+    success: bool,
+    cancelled: bool,
+}
+
+enum RunningTask {
+    Piped(Arc<Mutex<std::process::Child>>),
+    Pty(Box<dyn portable_pty::ChildKiller + Send + Sync>),
+}
+
+/// Where a run's output goes: the frontend, the problems list and dev server detection
+#[derive(Clone)]
+struct RunOutput {
+    on_event: Channel<TaskEvent>,
+    diagnostics: Arc<Mutex<DiagnosticsCollector>>,
+    dev_servers: Arc<Mutex<DevServerDetector>>,
+    /// Set once the run has finished, silencing readers that were given up on
+    closed: Arc<AtomicBool>,
+}
+
+impl RunOutput {
+    fn send(&self, stream: &str, text: String) {
+        if self.closed.load(Ordering::SeqCst) {
+            return;
+        }
+        self.diagnostics.lock().unwrap().feed(&text);
+        self.dev_servers.lock().unwrap().feed(&text);
+        let _ = self.on_event.send(TaskEvent::Output {
+            stream: stream.to_string(),
+            text,
+        });
+    }
+}
+
+/// Starts reading the run's output, then blocks until it exits and returns its exit code
+type WaitForOutput = Box<dyn FnOnce() -> Option<i32> + Send>;
+
+/// How often a piped run is checked for having exited
+const EXIT_POLL: Duration = Duration::from_millis(50);
+
+#[derive(Clone)]
+pub struct TaskManager {
+    running: Arc<Mutex<HashMap<u32, RunningTask>>>,
+    cancelled: Arc<Mutex<Vec<u32>>>,
+    next_id: Arc<Mutex<u32>>,
+    history: Arc<Mutex<Vec<TaskRun>>>,
+    diagnostics: DiagnosticsManager,
+    dev_servers: DevServerManager,
+}
+
+fn history_path() -> PathBuf {
+    crate::ade_path("task-history.json")
+}
+
+impl TaskManager {
+    pub fn new(diagnostics: DiagnosticsManager, dev_servers: DevServerManager) -> Self {
+        let history = std::fs::read_to_string(history_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            running: Arc::new(Mutex::new(HashMap::new())),
+            cancelled: Arc::new(Mutex::new(Vec::new())),
+            next_id: Arc::new(Mutex::new(1)),
+            history: Arc::new(Mutex::new(history)),
+            diagnostics,
+            dev_servers,
+        }
+    }
+
+    /// How many tasks are running
+    pub(crate) fn running_count(&self) -> usize {
+        self.running.lock().unwrap().len()
+    }
+
+    fn record(&self, run: TaskRun) {
+        let mut history = self.history.lock().unwrap();
+        history.push(run);
+        if history.len() > MAX_HISTORY {
+            let excess = history.len() - MAX_HISTORY;
+            history.drain(..excess);
+        }
+        let path = history_path();
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string(&*history) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+}
+
+fn read(dir: &Path, file: &str) -> Option<String> {
+    std::fs::read_to_string(dir.join(file)).ok()
+}
+
+/// Chosen by the nearest lockfile, which for a workspace package is the workspace root's
+fn package_manager(dir: &Path) -> &'static str {
+    let lockfiles = [
+        ("pnpm-lock.yaml", "pnpm"),
+        ("yarn.lock", "yarn"),
+        ("bun.lockb", "bun"),
+        ("bun.lock", "bun"),
+        ("package-lock.json", "npm"),
+    ];
+    dir.ancestors()
+        .find_map(|dir| {
+            lockfiles
+                .iter()
+                .find(|(file, _)| dir.join(file).exists())
+                .map(|(_, manager)| *manager)
+        })
+        .unwrap_or("npm")
+}
+
+fn task(
+    source: &str,
+    name: &str,
+    program: &str,
+    args: Vec<String>,
+    description: Option<String>,
+) -> Task {
+    Task {
+        id: name.to_string(),
+        name: name.to_string(),
+        source: source.to_string(),
+        command: format!("{} {}", program, args.join(" ")),
+        description: description.filter(|d| !d.is_empty()),
+        program: program.to_string(),
+        args,
+    }
+}
+
+fn package_scripts(dir: &Path, tasks: &mut Vec<Task>) {
+    let package = read(dir, "package.json").and_then(|s| serde_json::from_str::<Value>(&s).ok());
+    let Some(scripts) = package.as_ref().and_then(|p| p.get("scripts")?.as_object()) else {
+        return;
+    };
+    let manager = package_manager(dir);
+    for (name, body) in scripts {
+        let args = vec!["run".to_string(), name.clone()];
+        let body = body.as_str().map(String::from);
+        tasks.push(task("npm", name, manager, args, body));
+    }
+}
+
+fn makefile_targets(dir: &Path, tasks: &mut Vec<Task>) {
+    let Some(makefile) = ["GNUmakefile", "Makefile", "makefile"]
+        .iter()
+        .find_map(|file| read(dir, file))
+    else {
+        return;
+    };
+    let mut seen: Vec<&str> = Vec::new();
+    for line in makefile.lines() {
+        let Some((target, rest)) = line.split_once(':') else {
+            continue;
+        };
+        // Skip recipes, variable assignments (`X := y`) and special targets like .PHONY
+        let is_target = !target.is_empty()
+            && !line.starts_with(['\t', ' ', '.', '#'])
+            && !rest.starts_with('=')
+            && target
+                .chars()
+                .all(|c| c.is_alphanumeric() || "-_/".contains(c));
+        if !is_target || seen.contains(&target) {
+            continue;
+        }
+        seen.push(target);
+        // `target: deps ## Description` is the common self-documenting convention
+        let description = rest.split_once("##").map(|(_, d)| d.trim().to_string());
+        tasks.push(task(
+            "make",
+            target,
+            "make",
+            vec![target.to_string()],
+            description,
+        ));
+    }
+}
+
+fn justfile_recipes(dir: &Path, tasks: &mut Vec<Task>) {
+    let Some(justfile) = ["justfile", "Justfile", ".justfile"]
+        .iter()
+        .find_map(|file| read(dir, file))
+    else {
+        return;
+    };
+    let mut comment: Option<String> = None;
+    let mut private = false;
+    for line in justfile.lines() {
+        if line.starts_with([' ', '\t']) || line.trim().is_empty() {
+            comment = None;
+            private = false;
+            continue;
+        }
+        if let Some(text) = line.strip_prefix('#') {
+            comment = Some(text.trim().to_string());
+            continue;
+        }
+        if line.starts_with('[') {
+            private |= line.contains("private");
+            continue;
+        }
+        let Some((head, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let name = head
+            .trim_start_matches('@')
+            .split_whitespace()
+            .next()
+            .unwrap_or_default();
+        let is_recipe = !rest.starts_with('=')
+            && !name.is_empty()
+            && !["set", "alias", "export", "import", "mod"].contains(&name)
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || "-_".contains(c));
+        if is_recipe && !private && !name.starts_with('_') {
+            tasks.push(task(
+                "just",
+                name,
+                "just",
+                vec![name.to_string()],
+                comment.take(),
+            ));
+        }
+        comment = None;
+        private = false;
+    }
+}
+
+fn cargo_aliases(dir: &Path, tasks: &mut Vec<Task>) {
+    let Some(config) = ["config.toml", "config"]
+        .iter()
+        .find_map(|file| read(&dir.join(".cargo"), file))
+    else {
+        return;
+    };
+    let mut in_alias = false;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_alias = line == "[alias]";
+            continue;
+        }
+        let Some((name, value)) = line.split_once('=').filter(|_| in_alias) else {
+            continue;
+        };
+        let name = name.trim().trim_matches('"');
+        // Either "build --release" or ["build", "--release"]
+        let expansion = value
+            .trim()
+            .trim_matches(|c| c == '[' || c == ']')
+            .split(',')
+            .map(|part| part.trim().trim_matches('"'))
+            .collect::<Vec<_>>()
+            .join(" ");
+        tasks.push(task(
+            "cargo",
+            name,
+            "cargo",
+            vec![name.to_string()],
+            Some(expansion),
+        ));
+    }
+}
+
+/// Every task defined in `root`: package.json scripts, Makefile targets, justfile recipes and
+/// Cargo aliases
+pub(crate) fn discover(root: &str) -> Vec<Task> {
+    let dir = PathBuf::from(crate::git::resolve_repo(root));
+    let mut tasks = Vec::new();
+    package_scripts(&dir, &mut tasks);
+    makefile_targets(&dir, &mut tasks);
+    justfile_recipes(&dir, &mut tasks);
+    cargo_aliases(&dir, &mut tasks);
+    let names: Vec<String> = tasks.iter().map(|t| t.name.clone()).collect();
+    for task in &mut tasks {
+        if names.iter().filter(|n| **n == task.name).count() > 1 {
+            task.id = format!("{}:{}", task.source, task.name);
+        }
+    }
+    tasks
+}
+
+/// A task by id, or by bare name when that is unambiguous
+pub(crate) fn find(root: &str, id: &str) -> Result<Task, String> {
+    let tasks = discover(root);
+    tasks
+        .iter()
+        .find(|t| t.id == id)
+        .or_else(|| tasks.iter().find(|t| t.name == id))
+        .cloned()
+        .ok_or_else(|| format!("No task named {} in {}", id, root))
+}
+
+#[tauri::command]
+pub async fn list_tasks(root: String) -> Result<Vec<Task>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if !Path::new(&crate::git::resolve_repo(&root)).is_dir() {
+            return Err(format!("Not a directory: {}", root));
+        }
+        Ok(discover(&root))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn spawn_piped(
+    program: &str,
+    task: &Task,
+    cwd: &str,
+    env: &HashMap<String, String>,
+    output: &RunOutput,
+) -> Result<(RunningTask, WaitForOutput), String> {
+    let mut cmd = std::process::Command::new(program);
+    cmd.args(&task.args)
+        .current_dir(cwd)
+        .envs(env)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+    // Its own process group, so cancelling also stops what the runner started
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", task.command, e))?;
+    let readers: Vec<_> = [
+        (
+            "stdout",
+            child
+                .stdout
+                .take()
+                .map(|s| Box::new(s) as Box<dyn Read + Send>),
+        ),
+        (
+            "stderr",
+            child
+                .stderr
+                .take()
+                .map(|s| Box::new(s) as Box<dyn Read + Send>),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(stream, reader)| Some((stream, reader?)))
+    .collect();
+    let child = Arc::new(Mutex::new(child));
+    let waited = child.clone();
+    let output = output.clone();
+    let wait = Box::new(move || {
+        let readers = readers
+            .into_iter()
+            .map(|(stream, reader)| {
+                let output = output.clone();
+                std::thread::spawn(move || {
+                    for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                        output.send(stream, line + "\n");
+                    }
+                })
+            })
+            .collect();
+        // Polled so cancel_task can still signal it in between
+        let code = loop {
+            match waited.lock().unwrap().try_wait() {
+                Ok(Some(status)) => break status.code(),
+                Ok(None) => {}
+                Err(_) => break None,
+            }
+            std::thread::sleep(EXIT_POLL);
+        };
+        crate::subprocess::join_readers(readers);
+        code
+    });
+    Ok((RunningTask::Piped(child), wait))
+}
+
+fn spawn_pty(
+    program: &str,
+    task: &Task,
+    cwd: &str,
+    options: &RunTaskOptions,
+    env: &HashMap<String, String>,
+    output: &RunOutput,
+) -> Result<(RunningTask, WaitForOutput), String> {
+    let pair = NativePtySystem::default()
+        .openpty(PtySize {
+            rows: options.rows.unwrap_or(24),
+            cols: options.cols.unwrap_or(120),
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("openpty failed: {}", e))?;
+    let mut cmd = CommandBuilder::new(program);
+    cmd.args(&task.args);
+    cmd.cwd(cwd);
+    cmd.env("TERM", "xterm-256color");
+    for (key, value) in env {
+        cmd.env(key, value);
+    }
+    let mut child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| format!("Failed to run {}: {}", task.command, e))?;
+    drop(pair.slave);
+    let mut reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| format!("clone_reader failed: {}", e))?;
+    let killer = child.clone_killer();
+    let output = output.clone();
+    let wait = Box::new(move || {
+        let reader = std::thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            // Bytes of a UTF-8 character split across reads
+            let mut pending = Vec::new();
+            while let Ok(n) = reader.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                pending.extend_from_slice(&buf[..n]);
+                let valid = match std::str::from_utf8(&pending) {
+                    Ok(_) => pending.len(),
+                    Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                    Err(_) => pending.len(),
+                };
+                let text = String::from_utf8_lossy(&pending[..valid]).to_string();
+                pending.drain(..valid);
+                output.send("pty", text);
+            }
+        });
+        let status = child.wait().ok();
+        // The reader only sees EOF once the master is closed
+        drop(pair.master);
+        crate::subprocess::join_readers(vec![reader]);
+        status.map(|s| s.exit_code() as i32)
+    });
+    Ok((RunningTask::Pty(killer), wait))
+}
+
+/// Run a task from list_tasks, by id or unambiguous name, streaming its output. Returns a run
+/// id for cancel_task. The last event is always `finished`, and the run is added to the history.
+#[tauri::command]
+pub fn run_task(
+    state: tauri::State<'_, TaskManager>,
+    root: String,
+    task: String,
+    options: Option<RunTaskOptions>,
+    on_event: Channel<TaskEvent>,
+) -> Result<u32, String> {
+    let options = options.unwrap_or_default();
+    let cwd = crate::git::resolve_repo(&root);
+    let task = find(&root, &task)?;
+    let program = crate::check_command_exists(task.program.clone())
+        .map_err(|_| format!("{} is not installed", task.program))?;
+    let settings =
+        crate::project_settings::load(crate::project_settings::project_root_for(&cwd).as_deref());
+    let env_set = options
+        .env_set
+        .as_deref()
+        .or(settings.task_env.get(&task.id).map(String::as_str));
+    let env = settings.env_for(env_set)?.cloned().unwrap_or_default();
+    let run_id = {
+        let mut next = state.next_id.lock().unwrap();
+        let id = *next;
+        *next += 1;
+        id
+    };
+    // Each run's problems replace those of the task's previous run
+    let origin = format!("task:{}:{}", cwd, task.id);
+    state.diagnostics.clear(&origin);
+    let output = RunOutput {
+        on_event: on_event.clone(),
+        diagnostics: Arc::new(Mutex::new(
+            state
+                .diagnostics
+                .task_collector(origin.clone(), cwd.clone()),
+        )),
+        dev_servers: Arc::new(Mutex::new(state.dev_servers.detector(None, Some(run_id)))),
+        closed: Arc::new(AtomicBool::new(false)),
+    };
+    let (running, wait) = if options.pty {
+        spawn_pty(&program, &task, &cwd, &options, &env, &output)?
+    } else {
+        spawn_piped(&program, &task, &cwd, &env, &output)?
+    };
+
+    // Output is only read once wait runs, so it always follows this
+    let started_at = crate::now_ms();
+    let _ = on_event.send(TaskEvent::Started {
+        run_id,
+        task: task.id.clone(),
+        command: task.command.clone(),
+        started_at,
+    });
+    state.running.lock().unwrap().insert(run_id, running);
+
+    let manager = state.inner().clone();
+    crate::crash::spawn("tasks", format!("task:{}", run_id), move || {
+        let exit_code = wait();
+        output.closed.store(true, Ordering::SeqCst);
+        output.diagnostics.lock().unwrap().finish();
+        manager.dev_servers.forget(None, Some(run_id));
+        manager.running.lock().unwrap().remove(&run_id);
+        let cancelled = {
+            let mut cancelled = manager.cancelled.lock().unwrap();
+            let was = cancelled.contains(&run_id);
+            cancelled.retain(|id| *id != run_id);
+            was
+        };
+        let finished_at = crate::now_ms();
+        let success = exit_code == Some(0) && !cancelled;
+        manager.record(TaskRun {
+            root,
+            task: task.id,
+            command: task.command,
+            started_at,
+            finished_at,
+            exit_code,
+            success,
+            cancelled,
+        });
+        let _ = on_event.send(TaskEvent::Finished {
+            exit_code,
+            success,
+            cancelled,
+            duration_ms: finished_at.saturating_sub(started_at),
+        });
+        Ok(())
+    });
+    Ok(run_id)
+}
+
+#[tauri::command]
+pub fn cancel_task(state: tauri::State<'_, TaskManager>, run_id: u32) -> Result<(), String> {
+    let mut running = state.running.lock().unwrap();
+    let Some(task) = running.get_mut(&run_id) else {
+        return Ok(());
+    };
+    state.cancelled.lock().unwrap().push(run_id);
+    match task {
+        RunningTask::Piped(child) => {
+            crate::subprocess::signal_group(&mut child.lock().unwrap(), "TERM")
+                .map_err(|e| format!("Failed to stop task: {}", e))?
+        }
+        RunningTask::Pty(killer) => killer
+            .kill()
+            .map_err(|e| format!("Failed to stop task: {}", e))?,
+    }
+    Ok(())
+}
+
+/// Finished runs, newest first, optionally only those in `root`
+#[tauri::command]
+pub fn task_history(
+    state: tauri::State<'_, TaskManager>,
+    root: Option<String>,
+    limit: Option<usize>,
+) -> Vec<TaskRun> {
+    state
+        .history
+        .lock()
+        .unwrap()
+        .iter()
+        .rev()
+        .filter(|run| root.as_ref().is_none_or(|root| run.root == *root))
+        .take(limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+        .cloned()
+        .collect()
+}
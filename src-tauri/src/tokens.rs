@@ -0,0 +1,129 @@
+use std::sync::OnceLock;
+use tiktoken_rs::CoreBPE;
+
+/// Files above this size are skipped rather than tokenized (likely generated or binary)
+const MAX_FILE_SIZE: u64 = 20 * 1024 * 1024;
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    Cl100kBase,
+    O200kBase,
+}
+
+#[derive(serde::Serialize)]
+pub struct FileTokens {
+    path: String,
+    tokens: Option<usize>,
+    error: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct TokenCount {
+    total: usize,
+    encoding: Encoding,
+    /// True when the model's real tokenizer isn't public and a stand-in encoding was used
+    approximate: bool,
+    files: Vec<FileTokens>,
+}
+
+// Building the BPE tables takes ~100ms, so each encoding is loaded once on first use
+static CL100K: OnceLock<CoreBPE> = OnceLock::new();
+static O200K: OnceLock<CoreBPE> = OnceLock::new();
+
+fn bpe(encoding: Encoding) -> Result<&'static CoreBPE, String> {
+    let cell = match encoding {
+        Encoding::Cl100kBase => &CL100K,
+        Encoding::O200kBase => &O200K,
+    };
+    if let Some(bpe) = cell.get() {
+        return Ok(bpe);
+    }
+    let loaded = match encoding {
+        Encoding::Cl100kBase => tiktoken_rs::cl100k_base(),
+        Encoding::O200kBase => tiktoken_rs::o200k_base(),
+    }
+    .map_err(|e| format!("Failed to load tokenizer: {}", e))?;
+    Ok(cell.get_or_init(|| loaded))
+}
+
+/// Pick the encoding for a model name. Returns (encoding, approximate).
+pub fn encoding_for_model(model: Option<&str>) -> (Encoding, bool) {
+    let model = model.unwrap_or("").to_lowercase();
+    if model.starts_with("gpt-4o")
+        || model.starts_with("gpt-4.1")
+        || model.starts_with("gpt-5")
+        || model.starts_with("o1")
+        || model.starts_with("o3")
+        || model.starts_with("o4")
+    {
+        (Encoding::O200kBase, false)
+    } else if model.starts_with("gpt-4") || model.starts_with("gpt-3.5") {
+        (Encoding::Cl100kBase, false)
+    } else {
+        // Claude and other models don't publish their tokenizer; cl100k is a close stand-in
+        (Encoding::Cl100kBase, true)
+    }
+}
+
+/// Count tokens in `text` with the given encoding
+pub fn count(text: &str, encoding: Encoding) -> Result<usize, String> {
+    Ok(bpe(encoding)?.encode_ordinary(text).len())
+}
+
+#[tauri::command]
+pub async fn count_tokens(
+    paths: Option<Vec<String>>,
+    text: Option<String>,
+    model: Option<String>,
+) -> Result<TokenCount, String> {
+    let (encoding, approximate) = encoding_for_model(model.as_deref());
+    let mut total = 0;
+
+    if let Some(text) = &text {
+        total += count(text, encoding)?;
+    }
+
+    let mut files = Vec::new();
+    for path in paths.unwrap_or_default() {
+        let resolved = if path.starts_with("~/") {
+            let home = crate::get_home_dir();
+            path.replacen("~", &home, 1)
+        } else {
+            path.clone()
+        };
+        let result = std::fs::metadata(&resolved)
+            .map_err(|e| format!("Failed to read {}: {}", resolved, e))
+            .and_then(|meta| {
+                if meta.len() > MAX_FILE_SIZE {
+                    Err(format!("File too large ({} bytes)", meta.len()))
+                } else {
+                    std::fs::read_to_string(&resolved)
+                        .map_err(|e| format!("Failed to read {}: {}", resolved, e))
+                }
+            })
+            .and_then(|content| count(&content, encoding));
+        match result {
+            Ok(tokens) => {
+                total += tokens;
+                files.push(FileTokens {
+                    path,
+                    tokens: Some(tokens),
+                    error: None,
+                });
+            }
+            Err(e) => files.push(FileTokens {
+                path,
+                tokens: None,
+                error: Some(e),
+            }),
+        }
+    }
+
+    Ok(TokenCount {
+        total,
+        encoding,
+        approximate,
+        files,
+    })
+}
@@ -0,0 +1,233 @@
+//! Installing the tools onboarding asks for through the user's package managers, so an
+//! "Install" button can do the install instead of showing instructions.
+
+use crate::which::ToolVersion;
+use tauri::ipc::Channel;
+
+/// (tool, executable it provides, [(method, arguments to the package manager)]), methods in
+/// order of preference
+type Recipe = (
+    &'static str,
+    &'static str,
+    &'static [(&'static str, &'static [&'static str])],
+);
+
+const RECIPES: &[Recipe] = &[
+    (
+        "claude",
+        "claude",
+        &[
+            ("npm", &["install", "-g", "@anthropic-ai/claude-code"]),
+            ("brew", &["install", "--cask", "claude-code"]),
+        ],
+    ),
+    (
+        "codex",
+        "codex",
+        &[
+            ("npm", &["install", "-g", "@openai/codex"]),
+            ("brew", &["install", "codex"]),
+        ],
+    ),
+    (
+        "gemini",
+        "gemini",
+        &[
+            ("npm", &["install", "-g", "@google/gemini-cli"]),
+            ("brew", &["install", "gemini-cli"]),
+        ],
+    ),
+    (
+        "aider",
+        "aider",
+        &[
+            ("pipx", &["install", "aider-chat"]),
+            ("brew", &["install", "aider"]),
+        ],
+    ),
+    ("ollama", "ollama", &[("brew", &["install", "ollama"])]),
+    ("gh", "gh", &[("brew", &["install", "gh"])]),
+    ("node", "node", &[("brew", &["install", "node"])]),
+    (
+        "ripgrep",
+        "rg",
+        &[
+            ("brew", &["install", "ripgrep"]),
+            ("cargo", &["install", "ripgrep"]),
+        ],
+    ),
+    (
+        "just",
+        "just",
+        &[
+            ("brew", &["install", "just"]),
+            ("cargo", &["install", "just"]),
+        ],
+    ),
+    (
+        "cloudflared",
+        "cloudflared",
+        &[("brew", &["install", "cloudflared"])],
+    ),
+    (
+        "ngrok",
+        "ngrok",
+        &[("brew", &["install", "--cask", "ngrok"])],
+    ),
+    (
+        "terminal-notifier",
+        "terminal-notifier",
+        &[("brew", &["install", "terminal-notifier"])],
+    ),
+];
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum InstallEvent {
+    #[serde(rename = "started")]
+    Started {
+        tool: String,
+        method: String,
+        command: String,
+    },
+    /// A line of installer output
+    #[serde(rename = "output")]
+    Output {
+        /// stdout or stderr
+        stream: String,
+        text: String,
+    },
+    /// The installer finished; checking that the tool now runs
+    #[serde(rename = "verifying")]
+    Verifying { exit_code: Option<i32> },
+    /// Always the last event, unless the installer couldn't be started
+    #[serde(rename = "finished")]
+    Finished { success: bool, message: String },
+}
+
+#[derive(serde::Serialize)]
+pub struct InstallMethods {
+    tool: String,
+    /// Every method the tool can be installed with
+    methods: Vec<String>,
+    /// Those whose package manager is installed
+    available: Vec<String>,
+}
+
+fn recipe(tool: &str) -> Result<&'static Recipe, String> {
+    RECIPES
+        .iter()
+        .find(|(name, ..)| *name == tool)
+        .ok_or_else(|| format!("Don't know how to install {}", tool))
+}
+
+/// The ways each known tool can be installed, and which of them work on this machine
+#[tauri::command]
+pub async fn list_install_methods() -> Result<Vec<InstallMethods>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        RECIPES
+            .iter()
+            .map(|(tool, _, methods)| InstallMethods {
+                tool: tool.to_string(),
+                methods: methods.iter().map(|(m, _)| m.to_string()).collect(),
+                available: methods
+                    .iter()
+                    .filter(|(m, _)| crate::which::find(m).is_some())
+                    .map(|(m, _)| m.to_string())
+                    .collect(),
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Install `name` with `method` (brew, npm, cargo or pipx), or the first one available,
+/// streaming the installer's output. Afterwards the tool is looked up again and run with
+/// `--version`; returns where it was found and its version, or an error when the install or
+/// that check failed.
+#[tauri::command]
+pub async fn install_tool(
+    name: String,
+    method: Option<String>,
+    on_event: Channel<InstallEvent>,
+) -> Result<ToolVersion, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (tool, binary, methods) = recipe(&name)?;
+        let (method, args) = match &method {
+            Some(method) => methods
+                .iter()
+                .find(|(m, _)| m == method)
+                .ok_or_else(|| format!("{} can't be installed with {}", tool, method))?,
+            None => methods
+                .iter()
+                .find(|(m, _)| crate::which::find(m).is_some())
+                .ok_or_else(|| {
+                    let names: Vec<&str> = methods.iter().map(|(m, _)| *m).collect();
+                    format!("Installing {} needs one of: {}", tool, names.join(", "))
+                })?,
+        };
+        let program =
+            crate::which::find(method).ok_or_else(|| format!("{} is not installed", method))?;
+        let command = format!("{} {}", method, args.join(" "));
+
+        let mut child = std::process::Command::new(&program)
+            .args(*args)
+            .env("PATH", crate::which::full_path())
+            // Keep installers from waiting on prompts nobody can answer
+            .env("NONINTERACTIVE", "1")
+            .env("HOMEBREW_NO_ENV_HINTS", "1")
+            .env("npm_config_fund", "false")
+            .env("npm_config_audit", "false")
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run {}: {}", command, e))?;
+        let _ = on_event.send(InstallEvent::Started {
+            tool: tool.to_string(),
+            method: method.to_string(),
+            command: command.clone(),
+        });
+        let on_line = {
+            let on_event = on_event.clone();
+            move |stream: &str, text| {
+                let _ = on_event.send(InstallEvent::Output {
+                    stream: stream.to_string(),
+                    text,
+                });
+            }
+        };
+        for reader in crate::subprocess::stream_lines(&mut child, on_line) {
+            let _ = reader.join();
+        }
+        let exit_code = child.wait().ok().and_then(|s| s.code());
+        let _ = on_event.send(InstallEvent::Verifying { exit_code });
+
+        // The install may have added the tool, or a new directory to the login shell's PATH
+        crate::which::invalidate();
+        let installed = crate::which::tool_version(binary);
+        let result = match (exit_code, &installed.path) {
+            (Some(0), Some(_)) => Ok(installed),
+            (Some(0), None) => Err(format!(
+                "{} finished, but {} can't be found; open a new terminal and check your PATH",
+                command, binary
+            )),
+            (code, _) => Err(format!(
+                "{} failed (exit code {})",
+                command,
+                code.map_or("none".to_string(), |c| c.to_string())
+            )),
+        };
+        let _ = on_event.send(InstallEvent::Finished {
+            success: result.is_ok(),
+            message: match &result {
+                Ok(_) => format!("Installed {}", tool),
+                Err(e) => e.clone(),
+            },
+        });
+        result
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
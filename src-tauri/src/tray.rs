@@ -0,0 +1,209 @@
+//! The tray (menubar) icon: how many agents, tasks and terminals are busy, and a menu to pause
+//! the agents, reopen a recent project or quit while the window is hidden. Counts are polled
+//! from the managers; the menu is rebuilt only when something it shows has changed.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::menu::{ContextMenu, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Emitter, Manager};
+
+const TRAY_ID: &str = "main";
+const POLL: Duration = Duration::from_secs(3);
+const RECENT_PROJECTS: usize = 8;
+/// Menu ids of recent projects are this followed by the project's path
+const PROJECT_PREFIX: &str = "project:";
+
+/// What the tray shows
+#[derive(Clone, Default, PartialEq)]
+struct TrayStatus {
+    agents: usize,
+    /// Of `agents`, those stopped by "Pause All Agents"
+    paused_agents: usize,
+    tasks: usize,
+    busy_terminals: usize,
+    /// (path, name)
+    projects: Vec<(String, String)>,
+}
+
+/// Last status applied to the tray, so polling only touches it on changes
+static SHOWN: Mutex<Option<TrayStatus>> = Mutex::new(None);
+
+/// Sent to the frontend when a project is picked from the tray menu, after showing the window
+#[derive(Clone, serde::Serialize)]
+struct TrayOpenProject {
+    path: String,
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {}", noun),
+        _ => format!("{} {}s", count, noun),
+    }
+}
+
+impl TrayStatus {
+    fn read(app: &AppHandle) -> Self {
+        let (agents, paused_agents) = app.state::<crate::agents::AgentRunManager>().counts();
+        Self {
+            agents,
+            paused_agents,
+            tasks: app.state::<crate::tasks::TaskManager>().running_count(),
+            busy_terminals: app.state::<crate::pty::PtyManager>().busy_count(),
+            projects: app
+                .state::<crate::projects::ProjectManager>()
+                .recent(RECENT_PROJECTS)
+                .into_iter()
+                .map(|p| (p.path, p.name))
+                .collect(),
+        }
+    }
+
+    /// Like "2 agents running (1 paused), 1 task running, 3 terminals busy"
+    fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.agents > 0 {
+            let mut agents = format!("{} running", plural(self.agents, "agent"));
+            if self.paused_agents > 0 {
+                agents += &format!(" ({} paused)", self.paused_agents);
+            }
+            parts.push(agents);
+        }
+        if self.tasks > 0 {
+            parts.push(format!("{} running", plural(self.tasks, "task")));
+        }
+        if self.busy_terminals > 0 {
+            parts.push(format!("{} busy", plural(self.busy_terminals, "terminal")));
+        }
+        match parts.is_empty() {
+            true => "Idle".to_string(),
+            false => parts.join(", "),
+        }
+    }
+
+    /// Shown next to the icon: how many agents and tasks are running, nothing when none are
+    fn title(&self) -> Option<String> {
+        let running = self.agents + self.tasks;
+        (running > 0).then(|| running.to_string())
+    }
+}
+
+fn build_menu(app: &AppHandle, status: &TrayStatus) -> tauri::Result<impl ContextMenu + 'static> {
+    let summary = MenuItem::with_id(app, "status", status.summary(), false, None::<&str>)?;
+    let agents = match status.paused_agents {
+        0 => MenuItem::with_id(
+            app,
+            "pause-agents",
+            "Pause All Agents",
+            status.agents > 0,
+            None::<&str>,
+        )?,
+        _ => MenuItem::with_id(app, "resume-agents", "Resume Agents", true, None::<&str>)?,
+    };
+    let projects = Submenu::with_id(
+        app,
+        "recent-projects",
+        "Open Recent Project",
+        !status.projects.is_empty(),
+    )?;
+    for (path, name) in &status.projects {
+        let id = format!("{}{}", PROJECT_PREFIX, path);
+        projects.append(&MenuItem::with_id(app, id, name, true, None::<&str>)?)?;
+    }
+    let show = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, Some("CmdOrCtrl+Q"))?;
+    Menu::with_items(
+        app,
+        &[
+            &summary,
+            &PredefinedMenuItem::separator(app)?,
+            &agents,
+            &projects,
+            &show,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )
+}
+
+/// Bring the tray up to date with the managers
+fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let status = TrayStatus::read(app);
+    let mut shown = SHOWN.lock().unwrap();
+    if shown.as_ref() == Some(&status) {
+        return;
+    }
+    if let Ok(menu) = build_menu(app, &status) {
+        let _ = tray.set_menu(Some(menu));
+    }
+    let _ = tray.set_tooltip(Some(format!("Better Terminal: {}", status.summary())));
+    let _ = tray.set_title(status.title());
+    *shown = Some(status);
+}
+
+fn show_window(app: &AppHandle) {
+    let window = app
+        .get_webview_window("main")
+        .or_else(|| app.webview_windows().into_values().next());
+    if let Some(window) = window {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id: &str = event.id().as_ref();
+    match id {
+        "pause-agents" => {
+            let _ = app.state::<crate::agents::AgentRunManager>().pause_all();
+        }
+        "resume-agents" => {
+            let _ = app.state::<crate::agents::AgentRunManager>().resume_all();
+        }
+        "show" => show_window(app),
+        "quit" => app.exit(0),
+        _ => {
+            if let Some(path) = id.strip_prefix(PROJECT_PREFIX) {
+                show_window(app);
+                let _ = app.emit(
+                    "tray-open-project",
+                    TrayOpenProject {
+                        path: path.to_string(),
+                    },
+                );
+            }
+            return;
+        }
+    }
+    refresh(app);
+}
+
+/// Create the tray icon and keep it updated
+pub fn start(app: &tauri::App) {
+    let handle = app.handle().clone();
+    let status = TrayStatus::read(&handle);
+    let Ok(menu) = build_menu(&handle, &status) else {
+        return;
+    };
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(format!("Better Terminal: {}", status.summary()))
+        .menu(&menu)
+        .on_menu_event(on_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    let Ok(tray) = builder.build(app) else {
+        return;
+    };
+    let _ = tray.set_title(status.title());
+    *SHOWN.lock().unwrap() = Some(status);
+    std::thread::spawn(move || loop {
+        // Counting busy terminals runs pgrep per PTY
+        std::thread::sleep(crate::power::scaled(POLL));
+        refresh(&handle);
+    });
+}
@@ -0,0 +1,304 @@
+//! Public URLs for local ports through cloudflared quick tunnels or ngrok, for sharing a dev
+//! server or receiving webhooks. The provider's CLI does the work; its output is watched for the
+//! URL it was given.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TunnelProvider {
+    Cloudflared,
+    Ngrok,
+}
+
+impl TunnelProvider {
+    fn program(self) -> &'static str {
+        match self {
+            TunnelProvider::Cloudflared => "cloudflared",
+            TunnelProvider::Ngrok => "ngrok",
+        }
+    }
+
+    fn command(self, path: &str, port: u16) -> Command {
+        let mut cmd = Command::new(path);
+        match self {
+            TunnelProvider::Cloudflared => cmd.args([
+                "tunnel",
+                "--no-autoupdate",
+                "--url",
+                &format!("http://localhost:{}", port),
+            ]),
+            // JSON logs on stdout, so the URL and errors can be picked out reliably
+            TunnelProvider::Ngrok => cmd.args([
+                "http",
+                &port.to_string(),
+                "--log",
+                "stdout",
+                "--log-format",
+                "json",
+            ]),
+        };
+        cmd
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TunnelStatus {
+    /// Waiting for the provider to hand out a URL
+    Starting,
+    Ready,
+    /// The provider exited on its own
+    Failed,
+    Stopped,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct Tunnel {
+    id: u32,
+    provider: TunnelProvider,
+    port: u16,
+    status: TunnelStatus,
+    url: Option<String>,
+    error: Option<String>,
+    started_at: u64,
+}
+
+struct Entry {
+    tunnel: Tunnel,
+    child: Option<Child>,
+}
+
+#[derive(Clone)]
+pub struct TunnelManager {
+    tunnels: Arc<Mutex<HashMap<u32, Entry>>>,
+    next_id: Arc<Mutex<u32>>,
+}
+
+impl TunnelManager {
+    pub fn new() -> Self {
+        Self {
+            tunnels: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+
+    /// Change a tunnel and tell the frontend; false once it has been stopped
+    fn update(&self, app: &AppHandle, id: u32, change: impl FnOnce(&mut Tunnel)) -> bool {
+        let tunnel = {
+            let mut tunnels = self.tunnels.lock().unwrap();
+            let Some(entry) = tunnels.get_mut(&id) else {
+                return false;
+            };
+            change(&mut entry.tunnel);
+            entry.tunnel.clone()
+        };
+        let _ = app.emit("tunnel-changed", tunnel);
+        true
+    }
+
+    /// Exit status of the provider, None while it runs (or when stopped)
+    fn try_wait(&self, id: u32) -> Option<std::process::ExitStatus> {
+        let mut tunnels = self.tunnels.lock().unwrap();
+        let child = tunnels.get_mut(&id)?.child.as_mut()?;
+        child.try_wait().ok().flatten()
+    }
+
+    fn is_stopped(&self, id: u32) -> bool {
+        !self.tunnels.lock().unwrap().contains_key(&id)
+    }
+}
+
+static PUBLIC_URL: OnceLock<Regex> = OnceLock::new();
+
+/// What a line of the provider's output says: Ok(url) once the tunnel is up, Err(message) for
+/// an error, None for anything else
+fn parse_line(provider: TunnelProvider, line: &str) -> Option<Result<String, String>> {
+    match provider {
+        TunnelProvider::Cloudflared => {
+            // "2024-01-01T00:00:00Z ERR Failed to ... error=\"...\""
+            if let Some((_, message)) = line.split_once(" ERR ") {
+                return Some(Err(message.trim().to_string()));
+            }
+            let url = PUBLIC_URL
+                .get_or_init(|| Regex::new(r"https://[a-z0-9-]+\.trycloudflare\.com").unwrap());
+            // Not the api.trycloudflare.com it asks for one
+            let m = url.find(line)?;
+            (m.as_str() != "https://api.trycloudflare.com").then(|| Ok(m.as_str().to_string()))
+        }
+        TunnelProvider::Ngrok => {
+            let log: serde_json::Value = serde_json::from_str(line).ok()?;
+            if log["msg"] == "started tunnel" {
+                return log["url"].as_str().map(|url| Ok(url.to_string()));
+            }
+            let error = log["err"].as_str().filter(|e| *e != "<nil>")?;
+            if !matches!(log["lvl"].as_str(), Some("eror" | "crit")) {
+                return None;
+            }
+            Some(Err(error
+                .lines()
+                .next()
+                .unwrap_or(error)
+                .trim()
+                .to_string()))
+        }
+    }
+}
+
+/// The provider to use when none was asked for: the first installed
+fn installed_provider() -> Result<TunnelProvider, String> {
+    [TunnelProvider::Cloudflared, TunnelProvider::Ngrok]
+        .into_iter()
+        .find(|provider| crate::which::find(provider.program()).is_some())
+        .ok_or_else(|| "Neither cloudflared nor ngrok is installed".to_string())
+}
+
+/// Expose localhost:`port` at a public URL through `provider` (whichever of cloudflared and
+/// ngrok is installed when not given). The URL arrives in a tunnel-changed event once the
+/// provider has one.
+#[tauri::command]
+pub fn start_tunnel(
+    app: AppHandle,
+    state: tauri::State<'_, TunnelManager>,
+    port: u16,
+    provider: Option<TunnelProvider>,
+) -> Result<Tunnel, String> {
+    let provider = match provider {
+        Some(provider) => provider,
+        None => installed_provider()?,
+    };
+    let path = crate::which::find(provider.program())
+        .ok_or_else(|| format!("{} is not installed", provider.program()))?;
+    let child = provider
+        .command(&path.to_string_lossy(), port)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", provider.program(), e))?;
+
+    let id = {
+        let mut next = state.next_id.lock().unwrap();
+        let id = *next;
+        *next += 1;
+        id
+    };
+    let tunnel = Tunnel {
+        id,
+        provider,
+        port,
+        status: TunnelStatus::Starting,
+        url: None,
+        error: None,
+        started_at: crate::now_ms(),
+    };
+    state.tunnels.lock().unwrap().insert(
+        id,
+        Entry {
+            tunnel: tunnel.clone(),
+            child: Some(child),
+        },
+    );
+
+    let manager = state.inner().clone();
+    let on_line = {
+        let manager = manager.clone();
+        let app = app.clone();
+        move |_stream, line: String| match parse_line(provider, &line) {
+            Some(Ok(url)) => {
+                manager.update(&app, id, |tunnel| {
+                    tunnel.status = TunnelStatus::Ready;
+                    tunnel.url = Some(url);
+                    tunnel.error = None;
+                });
+            }
+            Some(Err(error)) => {
+                manager.update(&app, id, |tunnel| tunnel.error = Some(error));
+            }
+            None => {}
+        }
+    };
+    // Read once the tunnel is in the map, so a URL printed right away isn't dropped
+    let readers = {
+        let mut tunnels = state.tunnels.lock().unwrap();
+        let child = tunnels.get_mut(&id).and_then(|entry| entry.child.as_mut());
+        child
+            .map(|child| crate::subprocess::stream_lines(child, on_line))
+            .unwrap_or_default()
+    };
+
+    crate::crash::spawn("tunnel", format!("tunnel:{}", id), move || {
+        let status = loop {
+            if let Some(status) = manager.try_wait(id) {
+                break status;
+            }
+            if manager.is_stopped(id) {
+                return Ok(());
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        };
+        for reader in readers {
+            let _ = reader.join();
+        }
+        manager.update(&app, id, |tunnel| {
+            tunnel.status = TunnelStatus::Failed;
+            tunnel.url = None;
+            if tunnel.error.is_none() {
+                tunnel.error = Some(format!("{} exited with {}", provider.program(), status));
+            }
+        });
+        Ok(())
+    });
+    Ok(tunnel)
+}
+
+/// Every tunnel, including failed ones until they are stopped, oldest first
+#[tauri::command]
+pub fn list_tunnels(state: tauri::State<'_, TunnelManager>) -> Vec<Tunnel> {
+    let mut tunnels: Vec<Tunnel> = state
+        .tunnels
+        .lock()
+        .unwrap()
+        .values()
+        .map(|entry| entry.tunnel.clone())
+        .collect();
+    tunnels.sort_by_key(|t| t.id);
+    tunnels
+}
+
+#[tauri::command]
+pub fn stop_tunnel(app: AppHandle, state: tauri::State<'_, TunnelManager>, id: u32) {
+    let Some(mut entry) = state.tunnels.lock().unwrap().remove(&id) else {
+        return;
+    };
+    if let Some(mut child) = entry.child.take() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    entry.tunnel.status = TunnelStatus::Stopped;
+    entry.tunnel.url = None;
+    let _ = app.emit("tunnel-changed", entry.tunnel);
+}
+
+/// End every tunnel, when the app exits
+pub fn stop_all(app: &AppHandle) {
+    use tauri::Manager;
+    let tunnels: Vec<Entry> = app
+        .state::<TunnelManager>()
+        .tunnels
+        .lock()
+        .unwrap()
+        .drain()
+        .map(|(_, entry)| entry)
+        .collect();
+    for mut child in tunnels.into_iter().filter_map(|entry| entry.child) {
+        let _ = child.kill();
+    }
+}
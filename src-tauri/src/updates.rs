@@ -0,0 +1,233 @@
+//! New-version checks against the project's GitHub releases, and downloading the installer for
+//! this platform. Installing stays with the user (or Homebrew); the app only fetches the file.
+
+use crate::downloads;
+use crate::which::Semver;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use tauri::ipc::Channel;
+
+const REPO: &str = "alvin-reyes/better-agentic-ide";
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(serde::Deserialize)]
+struct GhRelease {
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    html_url: String,
+    published_at: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    assets: Vec<GhAsset>,
+}
+
+#[derive(Clone, serde::Deserialize)]
+struct GhAsset {
+    name: String,
+    browser_download_url: String,
+    size: u64,
+}
+
+#[derive(serde::Serialize)]
+pub struct UpdateInfo {
+    current_version: String,
+    latest_version: String,
+    update_available: bool,
+    release_name: Option<String>,
+    /// Markdown
+    notes: Option<String>,
+    published_at: Option<String>,
+    /// The release page
+    release_url: String,
+    /// The installer for this OS and architecture, when the release has one
+    download_url: Option<String>,
+    asset_name: Option<String>,
+    asset_size: Option<u64>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum DownloadEvent {
+    #[serde(rename = "started")]
+    Started { total_bytes: Option<u64> },
+    #[serde(rename = "progress")]
+    Progress {
+        downloaded_bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    #[serde(rename = "finished")]
+    Finished { path: String },
+}
+
+fn client() -> Result<reqwest::blocking::Client, String> {
+    reqwest::blocking::Client::builder()
+        // GitHub's API rejects requests without one
+        .user_agent(concat!("better-terminal/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Whether `candidate` is a later version than `current`; a pre-release comes before its release
+fn is_newer(candidate: &Semver, current: &Semver) -> bool {
+    let key = |v: &Semver| (v.major, v.minor, v.patch, v.pre.is_none());
+    match key(candidate).cmp(&key(current)) {
+        // Pre-releases of the same version, like beta.2 after beta.1
+        std::cmp::Ordering::Equal => candidate.pre > current.pre,
+        ordering => ordering.is_gt(),
+    }
+}
+
+/// The newest published release, counting pre-releases only when asked
+fn latest_release(include_prereleases: bool) -> Result<GhRelease, String> {
+    let url = format!("https://api.github.com/repos/{}/releases?per_page=20", REPO);
+    let response = client()?
+        .get(&url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .map_err(|e| format!("Failed to check for updates: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to check for updates: {}",
+            response.status()
+        ));
+    }
+    let releases: Vec<GhRelease> = response
+        .json()
+        .map_err(|e| format!("Failed to parse releases: {}", e))?;
+    // Listed newest first by date, but a patch release of an older line can come after
+    let mut latest: Option<(Semver, GhRelease)> = None;
+    for release in releases {
+        if release.draft || (release.prerelease && !include_prereleases) {
+            continue;
+        }
+        let Some(version) = Semver::parse(&release.tag_name) else {
+            continue;
+        };
+        if latest.as_ref().is_none_or(|(v, _)| is_newer(&version, v)) {
+            latest = Some((version, release));
+        }
+    }
+    latest
+        .map(|(_, release)| release)
+        .ok_or_else(|| "No releases found".to_string())
+}
+
+/// The installer a user on this platform would pick from the release
+fn platform_asset(assets: &[GhAsset]) -> Option<GhAsset> {
+    let arch_names: &[&str] = match std::env::consts::ARCH {
+        "aarch64" => &["aarch64", "arm64"],
+        _ => &["x64", "x86_64", "amd64"],
+    };
+    let extensions: &[&str] = match std::env::consts::OS {
+        "macos" => &[".dmg"],
+        "windows" => &["-setup.exe", ".msi"],
+        _ if crate::which::find("dpkg").is_some() => &[".deb", ".AppImage"],
+        _ => &[".AppImage", ".rpm"],
+    };
+    extensions.iter().find_map(|ext| {
+        assets
+            .iter()
+            .find(|asset| {
+                asset.name.ends_with(ext) && arch_names.iter().any(|arch| asset.name.contains(arch))
+            })
+            .cloned()
+    })
+}
+
+/// Compare the running version with the newest release on GitHub; includes the release notes
+/// and the download for this platform
+#[tauri::command]
+pub async fn check_for_updates(
+    app: tauri::AppHandle,
+    include_prereleases: Option<bool>,
+) -> Result<UpdateInfo, String> {
+    let current_version = app.package_info().version.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let release = latest_release(include_prereleases.unwrap_or(false))?;
+        let latest = Semver::parse(&release.tag_name);
+        let update_available = match (&latest, Semver::parse(&current_version)) {
+            (Some(latest), Some(current)) => is_newer(latest, &current),
+            _ => false,
+        };
+        let asset = platform_asset(&release.assets);
+        Ok(UpdateInfo {
+            current_version,
+            latest_version: release.tag_name.trim_start_matches('v').to_string(),
+            update_available,
+            release_name: release.name.filter(|n| !n.is_empty()),
+            notes: release.body.filter(|b| !b.is_empty()),
+            published_at: release.published_at,
+            release_url: release.html_url,
+            download_url: asset.as_ref().map(|a| a.browser_download_url.clone()),
+            asset_name: asset.as_ref().map(|a| a.name.clone()),
+            asset_size: asset.map(|a| a.size),
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// ~/Downloads when it exists, else ~/.ade/updates
+fn download_dir() -> PathBuf {
+    let downloads = PathBuf::from(crate::get_home_dir()).join("Downloads");
+    match downloads.is_dir() {
+        true => downloads,
+        false => crate::ade_path("updates"),
+    }
+}
+
+/// Download `url`, a download_url from check_for_updates, streaming progress. Resolves to where
+/// the installer was saved; a download cut off partway resumes on the next attempt.
+#[tauri::command]
+pub async fn download_update(
+    url: String,
+    on_event: Channel<DownloadEvent>,
+) -> Result<String, String> {
+    let prefix = format!("https://github.com/{}/releases/download/", REPO);
+    let Some(file_name) = url
+        .strip_prefix(&prefix)
+        .and_then(|rest| rest.rsplit('/').next())
+        .filter(|name| !name.is_empty() && !name.contains(['\\', '/']) && *name != "..")
+    else {
+        return Err(format!("Not a release download of {}: {}", REPO, url));
+    };
+    let file_name = file_name.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let dir = download_dir();
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let path = dir.join(&file_name);
+
+        let mut send = |event| match event {
+            downloads::DownloadEvent::Started { total_bytes, .. } => {
+                let _ = on_event.send(DownloadEvent::Started { total_bytes });
+            }
+            downloads::DownloadEvent::Progress {
+                downloaded_bytes,
+                total_bytes,
+            } => {
+                let _ = on_event.send(DownloadEvent::Progress {
+                    downloaded_bytes,
+                    total_bytes,
+                });
+            }
+            _ => {}
+        };
+        let never_cancelled = AtomicBool::new(false);
+        downloads::fetch(&url, &path, None, true, &never_cancelled, &mut send)?;
+
+        let path = path.to_string_lossy().to_string();
+        let _ = on_event.send(DownloadEvent::Finished { path: path.clone() });
+        Ok(path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
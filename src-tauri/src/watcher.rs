@@ -18,7 +18,19 @@ pub enum WatchEvent {
 }
 
 struct WatcherEntry {
-    _watcher: RecommendedWatcher,
+    _watcher: Option<RecommendedWatcher>,
+    _poller: Option<crate::remote::Poller>,
+    /// The host, for a directory on another machine
+    location: Option<crate::remote::Location>,
+    dir: String,
+    extensions: Vec<String>,
+}
+
+/// A watched directory as saved for session restore
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct WatchedDirectory {
+    pub(crate) dir: String,
+    extensions: Vec<String>,
 }
 
 pub struct WatcherManager {
@@ -33,21 +45,79 @@ impl WatcherManager {
             next_id: Arc::new(Mutex::new(1)),
         }
     }
+
+    fn insert(&self, entry: WatcherEntry) -> u32 {
+        let id = {
+            let mut next = self.next_id.lock().unwrap();
+            let id = *next;
+            *next += 1;
+            id
+        };
+        self.watchers.lock().unwrap().insert(id, entry);
+        id
+    }
+
+    /// Every watched local directory, oldest first
+    pub(crate) fn snapshot(&self) -> Vec<WatchedDirectory> {
+        let watchers = self.watchers.lock().unwrap();
+        let mut ids: Vec<&u32> = watchers
+            .iter()
+            .filter(|(_, entry)| entry.location.is_none())
+            .map(|(id, _)| id)
+            .collect();
+        ids.sort();
+        ids.into_iter()
+            .map(|id| WatchedDirectory {
+                dir: watchers[id].dir.clone(),
+                extensions: watchers[id].extensions.clone(),
+            })
+            .collect()
+    }
 }
 
+/// Watch `dir` for changes to files with `extensions` (all files when empty). With a
+/// `location`, the directory is on that host and is polled every few seconds.
 #[tauri::command]
 pub fn watch_directory(
     state: tauri::State<'_, WatcherManager>,
+    remote: tauri::State<'_, crate::remote::RemoteManager>,
     dir: String,
     extensions: Vec<String>,
+    location: Option<crate::remote::Location>,
     on_event: Channel<WatchEvent>,
 ) -> Result<u32, String> {
+    if let Some(location) = location {
+        crate::remote::check_host(&location.host)?;
+        let poller = crate::remote::poll(
+            remote.inner().clone(),
+            location.clone(),
+            dir.clone(),
+            extensions.clone(),
+            on_event,
+        );
+        return Ok(state.insert(WatcherEntry {
+            _watcher: None,
+            _poller: Some(poller),
+            location: Some(location),
+            dir,
+            extensions,
+        }));
+    }
+
     let watch_path = PathBuf::from(&dir);
     if !watch_path.is_dir() {
         return Err(format!("Not a directory: {}", dir));
     }
 
     let ext_set: Vec<String> = extensions.iter().map(|e| e.to_lowercase()).collect();
+    let settings_root = crate::project_settings::project_root_for(&dir);
+    let ignore: Vec<glob::Pattern> = crate::project_settings::load(settings_root.as_deref())
+        .watcher
+        .ignore
+        .iter()
+        .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+        .collect();
+    let root = watch_path.clone();
     let channel = on_event.clone();
 
     let mut watcher = RecommendedWatcher::new(
@@ -58,6 +128,10 @@ pub fn watch_directory(
                         .paths
                         .iter()
                         .filter(|p| {
+                            let relative = p.strip_prefix(&root).unwrap_or(p);
+                            if ignore.iter().any(|pattern| pattern.matches_path(relative)) {
+                                return false;
+                            }
                             if ext_set.is_empty() {
                                 return true;
                             }
@@ -112,19 +186,13 @@ pub fn watch_directory(
         .watch(&watch_path, RecursiveMode::Recursive)
         .map_err(|e| format!("Failed to watch {}: {}", dir, e))?;
 
-    let id = {
-        let mut next = state.next_id.lock().unwrap();
-        let id = *next;
-        *next += 1;
-        id
-    };
-
-    {
-        let mut watchers = state.watchers.lock().unwrap();
-        watchers.insert(id, WatcherEntry { _watcher: watcher });
-    }
-
-    Ok(id)
+    Ok(state.insert(WatcherEntry {
+        _watcher: Some(watcher),
+        _poller: None,
+        location: None,
+        dir,
+        extensions,
+    }))
 }
 
 #[tauri::command]
@@ -0,0 +1,253 @@
+//! Local webhook receivers: a small HTTP server on localhost that answers every request under a
+//! path prefix with 200 and passes it on as an event. Pointed at by a tunnel, `stripe listen`
+//! or a GitHub webhook, it makes webhook-driven code testable without another tool.
+
+use crate::preview_proxy::{read_head, respond};
+use std::collections::HashMap;
+use std::io::{BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::ipc::Channel;
+
+const MAX_BODY: u64 = 10 * 1024 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum WebhookEvent {
+    #[serde(rename = "request")]
+    Request {
+        method: String,
+        /// Without the query string
+        path: String,
+        query: Option<String>,
+        headers: Vec<(String, String)>,
+        /// The body as text, when it is UTF-8
+        body: Option<String>,
+        /// The body, base64, when it isn't
+        body_base64: Option<String>,
+        /// The body parsed, when it is JSON
+        json: Option<serde_json::Value>,
+        /// github or stripe, told apart by their headers
+        source: Option<String>,
+        /// The GitHub event header, or a Stripe payload's `type`
+        event_type: Option<String>,
+        received_at: u64,
+    },
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct WebhookListenerInfo {
+    id: u32,
+    port: u16,
+    path_prefix: String,
+    /// Where to point the sender
+    url: String,
+}
+
+struct WebhookListener {
+    info: WebhookListenerInfo,
+    stop: Arc<AtomicBool>,
+}
+
+#[derive(Clone)]
+pub struct WebhookManager {
+    listeners: Arc<Mutex<HashMap<u32, WebhookListener>>>,
+    next_id: Arc<Mutex<u32>>,
+}
+
+impl WebhookManager {
+    pub fn new() -> Self {
+        Self {
+            listeners: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+}
+
+/// Who sent a request, and what kind of event it is
+fn classify(
+    headers: &[(String, String)],
+    json: Option<&serde_json::Value>,
+) -> (Option<String>, Option<String>) {
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.clone())
+    };
+    if let Some(event) = header("X-GitHub-Event") {
+        return (Some("github".to_string()), Some(event));
+    }
+    if header("Stripe-Signature").is_some() {
+        let event = json.and_then(|j| j["type"].as_str()).map(str::to_string);
+        return (Some("stripe".to_string()), event);
+    }
+    (None, None)
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    path_prefix: &str,
+    on_event: &Channel<WebhookEvent>,
+) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let Some(request) = read_head(&mut reader)? else {
+        return Ok(());
+    };
+    let (path, query) = match request.path.split_once('?') {
+        Some((path, query)) => (path.to_string(), Some(query.to_string())),
+        None => (request.path.clone(), None),
+    };
+    if !path.starts_with(path_prefix) {
+        respond(reader.get_mut(), "404 Not Found", "text/plain", "Not found");
+        return Ok(());
+    }
+    if request
+        .header("transfer-encoding")
+        .is_some_and(|te| te.eq_ignore_ascii_case("chunked"))
+    {
+        respond(
+            reader.get_mut(),
+            "411 Length Required",
+            "text/plain",
+            "Chunked request bodies aren't supported",
+        );
+        return Ok(());
+    }
+    let length: u64 = request
+        .header("content-length")
+        .and_then(|len| len.parse().ok())
+        .unwrap_or(0);
+    if length > MAX_BODY {
+        respond(
+            reader.get_mut(),
+            "413 Payload Too Large",
+            "text/plain",
+            "Body too large",
+        );
+        return Ok(());
+    }
+    // curl asks before sending larger bodies, and waits a second for an answer otherwise
+    if request
+        .header("expect")
+        .is_some_and(|e| e.eq_ignore_ascii_case("100-continue"))
+    {
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+    }
+    let mut body = Vec::with_capacity(length as usize);
+    (&mut reader).take(length).read_to_end(&mut body)?;
+    respond(
+        reader.get_mut(),
+        "200 OK",
+        "application/json",
+        r#"{"ok":true}"#,
+    );
+
+    let json = serde_json::from_slice::<serde_json::Value>(&body).ok();
+    let (source, event_type) = classify(&request.headers, json.as_ref());
+    let (body, body_base64) = match String::from_utf8(body) {
+        Ok(text) => (Some(text), None),
+        Err(e) => (None, Some(crate::base64_encode(e.as_bytes()))),
+    };
+    let _ = on_event.send(WebhookEvent::Request {
+        method: request.method,
+        path,
+        query,
+        headers: request.headers,
+        body,
+        body_base64,
+        json,
+        source,
+        event_type,
+        received_at: crate::now_ms(),
+    });
+    Ok(())
+}
+
+/// Listen on localhost:`port` (any free port when 0) and pass each request under `path_prefix`
+/// ("/" when not given) on as an event; everything else gets a 404
+#[tauri::command]
+pub fn start_webhook_listener(
+    state: tauri::State<'_, WebhookManager>,
+    port: u16,
+    path_prefix: Option<String>,
+    on_event: Channel<WebhookEvent>,
+) -> Result<WebhookListenerInfo, String> {
+    let mut path_prefix = path_prefix.unwrap_or_else(|| "/".to_string());
+    if !path_prefix.starts_with('/') {
+        path_prefix.insert(0, '/');
+    }
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Failed to listen on port {}: {}", port, e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to listen on port {}: {}", port, e))?
+        .port();
+
+    let id = {
+        let mut next = state.next_id.lock().unwrap();
+        let id = *next;
+        *next += 1;
+        id
+    };
+    let stop = Arc::new(AtomicBool::new(false));
+    let info = WebhookListenerInfo {
+        id,
+        port,
+        url: format!("http://localhost:{}{}", port, path_prefix),
+        path_prefix: path_prefix.clone(),
+    };
+    state.listeners.lock().unwrap().insert(
+        id,
+        WebhookListener {
+            info: info.clone(),
+            stop: stop.clone(),
+        },
+    );
+
+    let path_prefix = Arc::new(path_prefix);
+    crate::crash::spawn("webhook", format!("webhook:{}", id), move || {
+        for stream in listener.incoming() {
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+            let Ok(stream) = stream else { continue };
+            let path_prefix = path_prefix.clone();
+            let on_event = on_event.clone();
+            std::thread::spawn(move || {
+                let _ = handle_connection(stream, &path_prefix, &on_event);
+            });
+        }
+        Ok(())
+    });
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn list_webhook_listeners(state: tauri::State<'_, WebhookManager>) -> Vec<WebhookListenerInfo> {
+    let mut listeners: Vec<WebhookListenerInfo> = state
+        .listeners
+        .lock()
+        .unwrap()
+        .values()
+        .map(|listener| listener.info.clone())
+        .collect();
+    listeners.sort_by_key(|l| l.id);
+    listeners
+}
+
+#[tauri::command]
+pub fn stop_webhook_listener(state: tauri::State<'_, WebhookManager>, id: u32) {
+    let Some(listener) = state.listeners.lock().unwrap().remove(&id) else {
+        return;
+    };
+    listener.stop.store(true, Ordering::Relaxed);
+    // Wake the accept loop so it sees the flag
+    let _ = TcpStream::connect(("127.0.0.1", listener.info.port));
+}
@@ -0,0 +1,285 @@
+//! WebSocket connections held by the backend for the frontend: dev servers' HMR channels,
+//! debugging protocols and the like, without the webview's origin rules. Each connection runs
+//! on its own thread, which alternates between sending what's queued and reading with a short
+//! timeout.
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::ipc::Channel;
+use tungstenite::client::IntoClientRequest;
+use tungstenite::http::HeaderValue;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a read waits before queued messages get their turn
+const POLL: Duration = Duration::from_millis(25);
+/// How long to wait for the server to answer our close
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+enum Outgoing {
+    Message(Message),
+    Close,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct WsConnectOptions {
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    /// Offered in Sec-WebSocket-Protocol, like "vite-hmr"
+    #[serde(default)]
+    protocols: Vec<String>,
+}
+
+/// A string is sent as a text message, an array of bytes as a binary one
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+pub enum WsData {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum WsEvent {
+    #[serde(rename = "open")]
+    Open {
+        /// The subprotocol the server picked
+        protocol: Option<String>,
+    },
+    #[serde(rename = "text")]
+    Text { data: String },
+    #[serde(rename = "binary")]
+    Binary { data: Vec<u8> },
+    #[serde(rename = "error")]
+    Error { message: String },
+    /// Always the last event
+    #[serde(rename = "closed")]
+    Closed { code: Option<u16>, reason: String },
+}
+
+#[derive(Clone)]
+pub struct WebSocketManager {
+    /// Queues to each open connection's thread
+    connections: Arc<Mutex<HashMap<u32, Sender<Outgoing>>>>,
+    next_id: Arc<Mutex<u32>>,
+}
+
+impl WebSocketManager {
+    pub fn new() -> Self {
+        Self {
+            connections: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+
+    fn queue(&self, id: u32, outgoing: Outgoing) -> Result<(), String> {
+        let connections = self.connections.lock().unwrap();
+        let sender = connections
+            .get(&id)
+            .ok_or_else(|| format!("WebSocket {} is not open", id))?;
+        sender
+            .send(outgoing)
+            .map_err(|_| format!("WebSocket {} is closing", id))
+    }
+}
+
+fn connect(url: &str, options: &WsConnectOptions) -> Result<(Socket, Option<String>), String> {
+    let mut request = url
+        .into_client_request()
+        .map_err(|e| format!("Invalid WebSocket URL {}: {}", url, e))?;
+    let headers = request.headers_mut();
+    for (name, value) in &options.headers {
+        let name = tungstenite::http::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| format!("Invalid header {}: {}", name, e))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| format!("Invalid header value for {}: {}", name, e))?;
+        headers.insert(name, value);
+    }
+    if !options.protocols.is_empty() {
+        let protocols = HeaderValue::from_str(&options.protocols.join(", "))
+            .map_err(|e| format!("Invalid protocol: {}", e))?;
+        headers.insert("Sec-WebSocket-Protocol", protocols);
+    }
+
+    let uri = request.uri();
+    let host = uri.host().unwrap_or_default().trim_matches(['[', ']']);
+    let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+        Some("wss") => 443,
+        _ => 80,
+    });
+    let addrs = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?;
+    let mut last_error = format!("Failed to resolve {}", host);
+    let stream = addrs
+        .into_iter()
+        .find_map(
+            |addr| match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                Ok(stream) => Some(stream),
+                Err(e) => {
+                    last_error = format!("Failed to connect to {}: {}", url, e);
+                    None
+                }
+            },
+        )
+        .ok_or(last_error)?;
+    // The handshake itself shouldn't hang either
+    let _ = stream.set_read_timeout(Some(CONNECT_TIMEOUT));
+
+    let (socket, response) = tungstenite::client_tls_with_config(request, stream, None, None)
+        .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+    let protocol = response
+        .headers()
+        .get("Sec-WebSocket-Protocol")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+    let tcp = match socket.get_ref() {
+        MaybeTlsStream::Plain(stream) => Some(stream),
+        MaybeTlsStream::Rustls(stream) => Some(stream.get_ref()),
+        _ => None,
+    };
+    if let Some(tcp) = tcp {
+        tcp.set_read_timeout(Some(POLL))
+            .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+    }
+    Ok((socket, protocol))
+}
+
+/// Pump one connection until it closes; the close code and reason, if the server sent them
+fn run(
+    mut socket: Socket,
+    outgoing: Receiver<Outgoing>,
+    on_event: &Channel<WsEvent>,
+) -> Result<(Option<u16>, String), String> {
+    let mut close = (None, String::new());
+    let mut closing: Option<Instant> = None;
+    loop {
+        while closing.is_none() {
+            let result = match outgoing.try_recv() {
+                Ok(Outgoing::Message(message)) => socket.send(message),
+                // ws_close, or the manager's entry went away
+                Ok(Outgoing::Close) | Err(TryRecvError::Disconnected) => {
+                    closing = Some(Instant::now());
+                    socket.close(None)
+                }
+                Err(TryRecvError::Empty) => break,
+            };
+            match result {
+                Ok(()) => {}
+                Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                    return Ok(close);
+                }
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        let event = match socket.read() {
+            Ok(Message::Text(text)) => WsEvent::Text {
+                data: text.as_str().to_string(),
+            },
+            Ok(Message::Binary(data)) => WsEvent::Binary {
+                data: data.to_vec(),
+            },
+            Ok(Message::Close(frame)) => {
+                if let Some(frame) = frame {
+                    close = (Some(frame.code.into()), frame.reason.as_str().to_string());
+                }
+                // The reply is queued; reading on sends it and then ends the connection
+                continue;
+            }
+            // Pings are answered by tungstenite
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                // Send any pong or close reply waiting on a write
+                let _ = socket.flush();
+                if closing.is_some_and(|since| since.elapsed() > CLOSE_TIMEOUT) {
+                    return Ok(close);
+                }
+                continue;
+            }
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                return Ok(close);
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+        if on_event.send(event).is_err() {
+            // Nobody is listening any more
+            let _ = socket.close(None);
+            let _ = socket.flush();
+            return Ok(close);
+        }
+    }
+}
+
+/// Open a WebSocket to a ws:// or wss:// URL. Resolves to its id once connected; messages and
+/// the close arrive on `on_event`.
+#[tauri::command]
+pub async fn ws_connect(
+    state: tauri::State<'_, WebSocketManager>,
+    url: String,
+    options: Option<WsConnectOptions>,
+    on_event: Channel<WsEvent>,
+) -> Result<u32, String> {
+    if !url.starts_with("ws://") && !url.starts_with("wss://") {
+        return Err(format!("Not a WebSocket URL: {}", url));
+    }
+    let options = options.unwrap_or_default();
+    let (socket, protocol) = tauri::async_runtime::spawn_blocking(move || connect(&url, &options))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let id = {
+        let mut next = state.next_id.lock().unwrap();
+        let id = *next;
+        *next += 1;
+        id
+    };
+    let (sender, receiver) = mpsc::channel();
+    state.connections.lock().unwrap().insert(id, sender);
+    let _ = on_event.send(WsEvent::Open { protocol });
+
+    let connections = state.connections.clone();
+    crate::crash::spawn("websocket", format!("ws:{}", id), move || {
+        let result = run(socket, receiver, &on_event);
+        connections.lock().unwrap().remove(&id);
+        let (code, reason) = match result {
+            Ok(close) => close,
+            Err(message) => {
+                let _ = on_event.send(WsEvent::Error { message });
+                (None, String::new())
+            }
+        };
+        let _ = on_event.send(WsEvent::Closed { code, reason });
+        Ok(())
+    });
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn ws_send(
+    state: tauri::State<'_, WebSocketManager>,
+    id: u32,
+    data: WsData,
+) -> Result<(), String> {
+    let message = match data {
+        WsData::Text(text) => Message::text(text),
+        WsData::Binary(bytes) => Message::binary(bytes),
+    };
+    state.queue(id, Outgoing::Message(message))
+}
+
+/// Start a clean close; `closed` follows on the connection's channel
+#[tauri::command]
+pub fn ws_close(state: tauri::State<'_, WebSocketManager>, id: u32) -> Result<(), String> {
+    state.queue(id, Outgoing::Close)
+}
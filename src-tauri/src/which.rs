@@ -0,0 +1,391 @@
+//! Finding the programs the app runs. Launched from Finder or a desktop launcher, the app doesn't
+//! get the PATH the user's shell sets up, so lookups also search the login shell's PATH
+//! (captured once) and the usual install locations. Results are cached: hits until the file
+//! goes away, misses for a short while so newly installed tools are found.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const MISS_TTL: Duration = Duration::from_secs(30);
+pub(crate) const SHELL_TIMEOUT: Duration = Duration::from_secs(10);
+/// Brackets the PATH in the login shell's output, which startup files may print to as well
+const PATH_MARKER: &str = "__ADE_PATH__";
+
+struct Lookup {
+    path: Option<PathBuf>,
+    checked_at: Instant,
+    /// Probed on request; the outer None means not probed yet
+    version: Option<Option<String>>,
+}
+
+#[derive(Default)]
+struct Cache {
+    lookups: HashMap<String, Lookup>,
+}
+
+static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+/// The login shell's PATH, None until captured. Apart from CACHE, and captured outside any
+/// lock, since starting the shell can take up to SHELL_TIMEOUT.
+static LOGIN_PATH: Mutex<Option<Vec<PathBuf>>> = Mutex::new(None);
+/// Held while capturing, so callers wait for one shell rather than each starting their own
+static CAPTURING: Mutex<()> = Mutex::new(());
+
+fn cache() -> &'static Mutex<Cache> {
+    CACHE.get_or_init(|| Mutex::new(Cache::default()))
+}
+
+#[derive(serde::Serialize)]
+pub struct ResolvedCommand {
+    path: String,
+    version: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct Semver {
+    pub(crate) major: u64,
+    pub(crate) minor: u64,
+    pub(crate) patch: u64,
+    /// Pre-release and build suffix, like "beta.2"
+    pub(crate) pre: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct ToolVersion {
+    tool: String,
+    pub(crate) path: Option<String>,
+    /// As reported, when the tool is installed and answered `--version` in time
+    version: Option<String>,
+    /// `version` parsed, when it is one; a missing patch reads as 0
+    semver: Option<Semver>,
+}
+
+static SEMVER_RE: OnceLock<regex::Regex> = OnceLock::new();
+
+impl Semver {
+    pub(crate) fn parse(version: &str) -> Option<Semver> {
+        let re = SEMVER_RE.get_or_init(|| {
+            regex::Regex::new(r"^v?(\d+)\.(\d+)(?:\.(\d+))?(?:[-+]([0-9A-Za-z.+-]+))?$").unwrap()
+        });
+        let caps = re.captures(version.trim())?;
+        Some(Semver {
+            major: caps[1].parse().ok()?,
+            minor: caps[2].parse().ok()?,
+            patch: caps.get(3).map_or(Some(0), |m| m.as_str().parse().ok())?,
+            pre: caps.get(4).map(|m| m.as_str().to_string()),
+        })
+    }
+}
+
+/// Run `script` in `shell` as a login shell; its stdout and how long the shell took, or None
+/// when it failed or timed out
+pub(crate) fn run_login_shell(shell: &str, script: &str) -> Option<(String, Duration)> {
+    let started = Instant::now();
+    let mut child = std::process::Command::new(shell)
+        .args(["-lc", script])
+        .env("HOME", crate::get_home_dir())
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdout = child.stdout.take()?;
+    let out = std::thread::spawn(move || {
+        let mut buf = String::new();
+        let _ = stdout.read_to_string(&mut buf);
+        buf
+    });
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => break,
+            Ok(None) if started.elapsed() < SHELL_TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(20))
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+    Some((out.join().unwrap_or_default(), started.elapsed()))
+}
+
+fn capture_login_path() -> Vec<PathBuf> {
+    if cfg!(windows) {
+        return Vec::new();
+    }
+    let shells = std::env::var("SHELL")
+        .into_iter()
+        .chain(["/bin/zsh", "/bin/bash", "/bin/sh"].map(String::from));
+    let script = format!("printf '{0}%s{0}' \"$PATH\"", PATH_MARKER);
+    for shell in shells.filter(|s| Path::new(s).is_file()) {
+        let Some((out, _)) = run_login_shell(&shell, &script) else {
+            continue;
+        };
+        if let Some(path) = out.split(PATH_MARKER).nth(1) {
+            return std::env::split_paths(path).collect();
+        }
+    }
+    Vec::new()
+}
+
+fn captured_login_path() -> Option<Vec<PathBuf>> {
+    LOGIN_PATH.lock().unwrap().clone()
+}
+
+/// The PATH the user's login shell sets up, captured the first time it's needed
+pub(crate) fn login_path() -> Vec<PathBuf> {
+    if let Some(path) = captured_login_path() {
+        return path;
+    }
+    let _capturing = CAPTURING.lock().unwrap();
+    // Captured by whoever held CAPTURING before
+    if let Some(path) = captured_login_path() {
+        return path;
+    }
+    let path = capture_login_path();
+    *LOGIN_PATH.lock().unwrap() = Some(path.clone());
+    path
+}
+
+/// Capture the login PATH in the background, so the first lookup doesn't wait for the shell
+pub fn start(_app: &tauri::App) {
+    crate::crash::spawn("which", "login-path".to_string(), || {
+        login_path();
+        Ok(())
+    });
+}
+
+/// The login shell's PATH followed by the app's, for children that look up programs themselves,
+/// like an npm script whose shebang runs `env node`
+pub(crate) fn full_path() -> std::ffi::OsString {
+    let app_path = std::env::var_os("PATH").unwrap_or_default();
+    let dirs = login_path()
+        .into_iter()
+        .chain(std::env::split_paths(&app_path).collect::<Vec<_>>());
+    std::env::join_paths(dirs).unwrap_or(app_path)
+}
+
+/// Forget cached lookups and the captured login PATH, e.g. after the user installed something
+/// or changed their shell setup
+pub(crate) fn invalidate() {
+    *cache().lock().unwrap() = Cache::default();
+    *LOGIN_PATH.lock().unwrap() = None;
+}
+
+/// Where tools commonly get installed, for when neither PATH has them
+fn fallback_dirs() -> Vec<PathBuf> {
+    let home = PathBuf::from(crate::get_home_dir());
+    let mut dirs: Vec<PathBuf> = if cfg!(windows) {
+        vec![
+            home.join("AppData").join("Roaming").join("npm"),
+            home.join(".cargo").join("bin"),
+            home.join("scoop").join("shims"),
+        ]
+    } else {
+        [
+            ".local/bin",
+            ".cargo/bin",
+            "bin",
+            ".npm-global/bin",
+            ".bun/bin",
+        ]
+        .iter()
+        .map(|dir| home.join(dir))
+        .chain(
+            [
+                "/usr/local/bin",
+                "/opt/homebrew/bin",
+                "/home/linuxbrew/.linuxbrew/bin",
+                "/snap/bin",
+                "/usr/bin",
+                "/bin",
+            ]
+            .map(PathBuf::from),
+        )
+        .collect()
+    };
+    // Every installed nvm version
+    let nvm = home.join(".nvm/versions/node/*/bin");
+    if let Ok(paths) = glob::glob(&nvm.to_string_lossy()) {
+        dirs.extend(paths.flatten());
+    }
+    dirs
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(meta) = std::fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        meta.is_file() && meta.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    meta.is_file()
+}
+
+/// File names `command` may have: itself, and on Windows with each PATHEXT extension
+fn candidates(command: &str) -> Vec<String> {
+    if !cfg!(windows) || Path::new(command).extension().is_some() {
+        return vec![command.to_string()];
+    }
+    let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    std::iter::once(command.to_string())
+        .chain(
+            pathext
+                .split(';')
+                .filter(|ext| !ext.is_empty())
+                .map(|ext| format!("{}{}", command, ext.to_lowercase())),
+        )
+        .collect()
+}
+
+/// Ask `where`, which also knows about App Paths registrations
+fn windows_where(command: &str) -> Option<PathBuf> {
+    let output = std::process::Command::new("where")
+        .arg(command)
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first = stdout.lines().next()?.trim();
+    (output.status.success() && !first.is_empty()).then(|| PathBuf::from(first))
+}
+
+fn search(command: &str) -> Option<PathBuf> {
+    if command.contains(['/', '\\']) {
+        let path = PathBuf::from(command);
+        return is_executable(&path).then_some(path);
+    }
+    let names = candidates(command);
+    let app_path = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let find_in = |dirs: Vec<PathBuf>| {
+        dirs.into_iter().find_map(|dir| {
+            names
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|path| is_executable(path))
+        })
+    };
+    let found = match captured_login_path() {
+        // The login shell's PATH first: it reflects the user's choices, like the active nvm
+        // version
+        Some(login_path) => find_in([login_path, app_path, fallback_dirs()].concat()),
+        // Not captured yet: look where no shell has to start first
+        None => find_in([app_path, fallback_dirs()].concat()).or_else(|| find_in(login_path())),
+    };
+    match found {
+        None if cfg!(windows) => windows_where(command),
+        found => found,
+    }
+}
+
+/// Full path of `command`, from the cache when possible
+pub(crate) fn find(command: &str) -> Option<PathBuf> {
+    {
+        let cache = cache().lock().unwrap();
+        if let Some(lookup) = cache.lookups.get(command) {
+            let fresh = match &lookup.path {
+                Some(path) => is_executable(path),
+                None => lookup.checked_at.elapsed() < MISS_TTL,
+            };
+            if fresh {
+                return lookup.path.clone();
+            }
+        }
+    }
+    let path = search(command);
+    cache().lock().unwrap().lookups.insert(
+        command.to_string(),
+        Lookup {
+            path: path.clone(),
+            checked_at: Instant::now(),
+            version: None,
+        },
+    );
+    path
+}
+
+/// Version `command` reports, probed once per lookup
+pub(crate) fn version(command: &str) -> Option<String> {
+    let path = find(command)?;
+    if let Some(version) = cache()
+        .lock()
+        .unwrap()
+        .lookups
+        .get(command)
+        .and_then(|lookup| lookup.version.clone())
+    {
+        return version;
+    }
+    let version = crate::agents::probe_version(&path.to_string_lossy());
+    if let Some(lookup) = cache().lock().unwrap().lookups.get_mut(command) {
+        lookup.version = Some(version.clone());
+    }
+    version
+}
+
+/// Where `command` is installed and, with `version`, what version it reports. `refresh` drops
+/// cached results first.
+#[tauri::command]
+pub async fn resolve_command(
+    command: String,
+    version: Option<bool>,
+    refresh: Option<bool>,
+) -> Result<ResolvedCommand, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if refresh.unwrap_or(false) {
+            invalidate();
+        }
+        let path = find(&command).ok_or_else(|| format!("{} is not installed", command))?;
+        Ok(ResolvedCommand {
+            path: path.to_string_lossy().to_string(),
+            version: version
+                .unwrap_or(false)
+                .then(|| self::version(&command))
+                .flatten(),
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Where `tool` is installed and the version it reports
+pub(crate) fn tool_version(tool: &str) -> ToolVersion {
+    let path = find(tool);
+    let version = path.as_ref().and_then(|_| version(tool));
+    ToolVersion {
+        tool: tool.to_string(),
+        path: path.map(|p| p.to_string_lossy().to_string()),
+        semver: version.as_deref().and_then(Semver::parse),
+        version,
+    }
+}
+
+/// Path and version of each of `tools`, probed side by side so onboarding needs one round trip.
+/// Each probe is bounded by the `--version` timeout; a tool that doesn't answer has no version.
+#[tauri::command]
+pub async fn get_tool_versions(tools: Vec<String>) -> Result<Vec<ToolVersion>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        // Captured once up front, rather than by whichever probe gets there first
+        login_path();
+        std::thread::scope(|scope| {
+            let probes: Vec<_> = tools
+                .iter()
+                .map(|tool| scope.spawn(move || tool_version(tool)))
+                .collect();
+            probes
+                .into_iter()
+                .filter_map(|probe| probe.join().ok())
+                .collect()
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,215 @@
+use crate::claude_md::toml_value;
+use crate::tasks::Task;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+#[derive(serde::Serialize)]
+pub struct WorkspacePackage {
+    name: String,
+    path: String,
+    /// Relative to the workspace root
+    relative_path: String,
+    /// npm (for npm, yarn and pnpm workspaces) or cargo
+    kind: String,
+    version: Option<String>,
+    /// What list_tasks finds in the package: package.json scripts, make and just targets,
+    /// Cargo aliases
+    scripts: Vec<Task>,
+}
+
+fn read(dir: &Path, file: &str) -> Option<String> {
+    std::fs::read_to_string(dir.join(file)).ok()
+}
+
+/// `key = [...]` from a TOML `[section]`, which may span several lines
+fn toml_array(toml: &str, section: &str, key: &str) -> Vec<String> {
+    let header = format!("[{}]", section);
+    let mut in_section = false;
+    let mut array: Option<String> = None;
+    // Comments are dropped line by line, before the lines of an array are joined
+    let lines = toml
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim());
+    for line in lines {
+        if let Some(array) = array.as_mut() {
+            array.push_str(line);
+        } else if line.starts_with('[') {
+            in_section = line == header;
+            continue;
+        } else if let Some((k, v)) = line.split_once('=').filter(|_| in_section) {
+            if k.trim() != key {
+                continue;
+            }
+            array = Some(v.trim().to_string());
+        } else {
+            continue;
+        }
+        if array.as_deref().is_some_and(|a| a.contains(']')) {
+            break;
+        }
+    }
+    let array = array.unwrap_or_default();
+    let inner = array
+        .trim_start_matches('[')
+        .split(']')
+        .next()
+        .unwrap_or_default();
+    inner
+        .split(',')
+        .map(|item| item.trim().trim_matches(|c| c == '"' || c == '\''))
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Entries of the `packages:` list in pnpm-workspace.yaml
+fn pnpm_packages(yaml: &str) -> Vec<String> {
+    let mut packages = Vec::new();
+    let mut in_packages = false;
+    for line in yaml.lines() {
+        if !line.starts_with([' ', '\t', '-']) && !line.trim().is_empty() {
+            in_packages = line.trim_end() == "packages:";
+            continue;
+        }
+        if let Some(item) = line.trim().strip_prefix('-').filter(|_| in_packages) {
+            let item = item.split(" #").next().unwrap_or_default().trim();
+            packages.push(item.trim_matches(|c| c == '"' || c == '\'').to_string());
+        }
+    }
+    packages
+}
+
+/// Directories under `root` matching the member globs and none of the exclusions (given
+/// separately, or as `!pattern` members) that contain `manifest`
+fn expand_members(
+    root: &Path,
+    members: &[String],
+    exclude: &[String],
+    manifest: &str,
+) -> Vec<PathBuf> {
+    let exclude: Vec<glob::Pattern> = members
+        .iter()
+        .filter_map(|m| m.strip_prefix('!'))
+        .chain(exclude.iter().map(String::as_str))
+        .filter_map(|pattern| glob::Pattern::new(pattern.trim_end_matches('/')).ok())
+        .collect();
+    let mut dirs = Vec::new();
+    for member in members.iter().filter(|m| !m.starts_with('!')) {
+        let pattern = root.join(member.trim_end_matches('/'));
+        let Ok(paths) = glob::glob(&pattern.to_string_lossy()) else {
+            continue;
+        };
+        for dir in paths.flatten() {
+            let Ok(relative) = dir.strip_prefix(root) else {
+                continue;
+            };
+            let skipped = relative
+                .components()
+                .any(|c| c.as_os_str() == "node_modules")
+                || exclude.iter().any(|p| p.matches_path(relative));
+            if !skipped && dir.join(manifest).is_file() && !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+    dirs
+}
+
+fn npm_members(root: &Path) -> Vec<PathBuf> {
+    let package: Value = read(root, "package.json")
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or(Value::Null);
+    // An array, or yarn's { "packages": [...] }
+    let workspaces = package.get("workspaces");
+    let globs = workspaces
+        .and_then(|w| w.get("packages"))
+        .or(workspaces)
+        .and_then(Value::as_array);
+    let mut members: Vec<String> = globs
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(String::from)
+        .collect();
+    if let Some(yaml) = read(root, "pnpm-workspace.yaml") {
+        members.extend(pnpm_packages(&yaml));
+    }
+    expand_members(root, &members, &[], "package.json")
+}
+
+fn cargo_members(root: &Path) -> Vec<PathBuf> {
+    let Some(cargo) = read(root, "Cargo.toml").filter(|c| c.contains("[workspace]")) else {
+        return Vec::new();
+    };
+    let members = toml_array(&cargo, "workspace", "members");
+    let exclude = toml_array(&cargo, "workspace", "exclude");
+    let mut dirs = expand_members(root, &members, &exclude, "Cargo.toml");
+    // A root package is a member of its own workspace
+    if cargo.contains("[package]") {
+        dirs.insert(0, root.to_path_buf());
+    }
+    dirs
+}
+
+fn package(root: &Path, dir: &Path, kind: &str) -> WorkspacePackage {
+    let (name, version) = match kind {
+        "cargo" => {
+            let cargo = read(dir, "Cargo.toml").unwrap_or_default();
+            (
+                toml_value(&cargo, "package", "name"),
+                // `version.workspace = true` and `version = { workspace = true }` are inherited
+                toml_value(&cargo, "package", "version").filter(|v| !v.starts_with('{')),
+            )
+        }
+        _ => {
+            let package: Value = read(dir, "package.json")
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or(Value::Null);
+            let field = |key: &str| package.get(key).and_then(Value::as_str).map(String::from);
+            (field("name"), field("version"))
+        }
+    };
+    let relative = dir.strip_prefix(root).unwrap_or(dir).to_string_lossy();
+    let path = dir.to_string_lossy().to_string();
+    WorkspacePackage {
+        name: name.unwrap_or_else(|| {
+            dir.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        }),
+        relative_path: if relative.is_empty() {
+            ".".to_string()
+        } else {
+            relative.to_string()
+        },
+        scripts: crate::tasks::discover(&path),
+        path,
+        kind: kind.to_string(),
+        version,
+    }
+}
+
+/// Packages of the npm, yarn or pnpm workspace and the Cargo workspace rooted at `root`, by
+/// path. Empty when `root` isn't a workspace root.
+#[tauri::command]
+pub async fn list_workspace_packages(root: String) -> Result<Vec<WorkspacePackage>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = PathBuf::from(crate::git::resolve_repo(&root));
+        if !root.is_dir() {
+            return Err(format!("Not a directory: {}", root.display()));
+        }
+        let mut packages: Vec<WorkspacePackage> = npm_members(&root)
+            .iter()
+            .map(|dir| package(&root, dir, "npm"))
+            .chain(
+                cargo_members(&root)
+                    .iter()
+                    .map(|dir| package(&root, dir, "cargo")),
+            )
+            .collect();
+        packages.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        Ok(packages)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
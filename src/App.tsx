@@ -25,6 +25,8 @@ import { useKeybindings } from "./hooks/useKeybindings";
 import { hasActiveProcess, stopIdlePolling } from "./hooks/useTerminal";
 import { invoke } from "@tauri-apps/api/core";
 import { getCurrentWindow } from "@tauri-apps/api/window";
+import { listen } from "@tauri-apps/api/event";
+import { setSessionFiles } from "./lib/sessionRestore";
 
 export default function App() {
   const scratchpadRef = useRef<ScratchpadHandle>(null);
@@ -40,7 +42,10 @@ export default function App() {
   const [confirmDialog, setConfirmDialog] = useState<{
     title: string;
     message: string;
+    confirmLabel?: string;
+    cancelLabel?: string;
     onConfirm: () => void;
+    onCancel?: () => void;
   } | null>(null);
   const { tabs, activeTabId, getActivePtyId, closeTab, closePane } = useTabStore();
   const activeTab = tabs.find((t) => t.id === activeTabId);
@@ -75,6 +80,30 @@ export default function App() {
     };
   }, []);
 
+  // Report open editor files so the backend can save them with the session
+  useEffect(() => {
+    let last = "";
+    const report = (tabs: ReturnType<typeof useTabStore.getState>["tabs"]) => {
+      const files = tabs.flatMap((t) => (t.type === "editor" && t.editorFilePath ? [t.editorFilePath] : []));
+      const key = files.join("\n");
+      if (key === last) return;
+      last = key;
+      setSessionFiles(files).catch(() => {});
+    };
+    report(useTabStore.getState().tabs);
+    return useTabStore.subscribe((state) => report(state.tabs));
+  }, []);
+
+  // Agents can open files through the built-in MCP server
+  useEffect(() => {
+    const unlisten = listen<{ path: string }>("ide-open-file", (event) => {
+      useTabStore.getState().addEditorTab(event.payload.path);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   // Listen for pane zoom toggle
   useEffect(() => {
     const handler = () => setZoomedPane((prev) => !prev);
@@ -119,11 +148,52 @@ export default function App() {
     };
   }, []);
 
-  // Request notification permission on startup
+  // Clicking a native notification brings back the pane it was about
   useEffect(() => {
-    if ("Notification" in window && Notification.permission === "default") {
-      Notification.requestPermission();
-    }
+    const win = getCurrentWindow();
+    const unlistenFocusPane = listen<{ pane_id: string }>("focus-pane", async (event) => {
+      if (!useTabStore.getState().focusPane(event.payload.pane_id)) return;
+      await win.unminimize();
+      await win.show();
+      await win.setFocus();
+    });
+    return () => {
+      unlistenFocusPane.then((fn) => fn());
+    };
+  }, []);
+
+  // An agent over its token/cost budget is paused until the user decides
+  useEffect(() => {
+    const unlisten = listen<{
+      key: string;
+      label: string;
+      scope: "task" | "day";
+      spent: { tokens: number; cost_usd: number };
+      today: { tokens: number; cost_usd: number };
+    }>("budget-exceeded", (event) => {
+      // Every window gets the event; one prompt is enough
+      if (getCurrentWindow().label !== "main") return;
+      const { key, label, scope, spent, today } = event.payload;
+      const used = scope === "task" ? spent : today;
+      const what = scope === "task" ? "its task budget" : "today's budget";
+      setConfirmDialog({
+        title: "Agent paused: budget exceeded",
+        message: `${label} went over ${what} (${used.tokens.toLocaleString()} tokens, ~$${used.cost_usd.toFixed(2)}). Let it continue?`,
+        confirmLabel: "Continue",
+        cancelLabel: "Stop Agent",
+        onConfirm: () => {
+          invoke("resume_budget_run", { key }).catch(() => {});
+          setConfirmDialog(null);
+        },
+        onCancel: () => {
+          invoke("stop_budget_run", { key }).catch(() => {});
+          setConfirmDialog(null);
+        },
+      });
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
   // Listen for dashboard toggle from command palette
@@ -358,8 +428,10 @@ export default function App() {
         <ConfirmDialog
           title={confirmDialog.title}
           message={confirmDialog.message}
+          confirmLabel={confirmDialog.confirmLabel}
+          cancelLabel={confirmDialog.cancelLabel}
           onConfirm={confirmDialog.onConfirm}
-          onCancel={() => setConfirmDialog(null)}
+          onCancel={confirmDialog.onCancel ?? (() => setConfirmDialog(null))}
         />
       )}
       {/* Toast notification */}
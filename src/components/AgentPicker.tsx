@@ -29,13 +29,13 @@ export default function AgentPicker({ onClose }: AgentPickerProps) {
   useEffect(() => {
     const detect = async () => {
       const installed = new Set<Provider>();
-      for (const p of PROVIDERS) {
-        try {
-          await invoke<string>("check_command_exists", { command: p.id });
-          installed.add(p.id);
-        } catch {
-          // not installed
+      try {
+        const tools = await invoke<{ id: string; installed: boolean }[]>("detect_agent_tools");
+        for (const p of PROVIDERS) {
+          if (tools.some((t) => t.id === p.id && t.installed)) installed.add(p.id);
         }
+      } catch {
+        // detection failed; treat as nothing installed
       }
       setInstalledProviders(installed);
       // If current default isn't installed, switch to first installed
@@ -111,6 +111,12 @@ export default function AgentPicker({ onClose }: AgentPickerProps) {
       cmd = cmd.replace(/^claude /, "claude --dangerously-skip-permissions ");
     }
 
+    // Checkpoint the workspace first so the agent's changes can be reviewed or reverted
+    const cwd = await invoke<string>("get_pty_cwd", { id: ptyId }).catch(() => null);
+    if (cwd) {
+      await invoke("create_checkpoint", { repo: cwd, label: `Before ${profile.name}` }).catch(() => {});
+    }
+
     const data = Array.from(new TextEncoder().encode(cmd + "\r"));
     await invoke("write_pty", { id: ptyId, data }).catch(() => {});
 
@@ -125,6 +131,11 @@ export default function AgentPicker({ onClose }: AgentPickerProps) {
       );
     }
 
+    // Claude Code sessions are held to the token/cost budgets through their transcripts
+    if (activeProvider === "claude" && cwd) {
+      invoke("watch_agent_budget", { ptyId, project: cwd, label: profile.name }).catch(() => {});
+    }
+
     // Save selected provider as default
     if (activeProvider !== defaultProvider) {
       setDefaultProvider(activeProvider);
@@ -1,6 +1,14 @@
 import { useState, useEffect, useRef, useMemo } from "react";
 import { useTabStore } from "../stores/tabStore";
 import { useSettingsStore } from "../stores/settingsStore";
+import { invoke } from "@tauri-apps/api/core";
+
+interface ResumableSession {
+  id: string;
+  summary: string | null;
+  first_prompt: string | null;
+  last_prompt: string | null;
+}
 
 interface PaletteItem {
   id: string;
@@ -25,6 +33,17 @@ export default function CommandPalette({ onClose, onToggleScratchpad, onOpenAgen
   const inputRef = useRef<HTMLInputElement>(null);
   const listRef = useRef<HTMLDivElement>(null);
   const { tabs, activeTabId, addTab, setActiveTab } = useTabStore();
+  const [resumable, setResumable] = useState<ResumableSession[]>([]);
+
+  // Claude sessions from the active terminal's project, offered as one-click resumes
+  useEffect(() => {
+    const ptyId = useTabStore.getState().getActivePtyId();
+    if (ptyId === null) return;
+    invoke<string>("get_pty_cwd", { id: ptyId })
+      .then((cwd) => invoke<ResumableSession[]>("list_resumable_sessions", { project: cwd }))
+      .then((sessions) => setResumable(sessions.slice(0, 10)))
+      .catch(() => {});
+  }, []);
 
   const items = useMemo<PaletteItem[]>(() => {
     const actions: PaletteItem[] = [
@@ -144,8 +163,24 @@ export default function CommandPalette({ onClose, onToggleScratchpad, onOpenAgen
       });
     });
 
+    resumable.forEach((session) => {
+      const title = session.summary ?? session.last_prompt ?? session.first_prompt ?? session.id;
+      actions.push({
+        id: `resume-${session.id}`,
+        label: `Resume Claude: ${title.split("\n")[0].slice(0, 80)}`,
+        category: "Sessions",
+        action: () => {
+          const ptyId = useTabStore.getState().getActivePtyId();
+          if (ptyId !== null) {
+            invoke("resume_session_in_pty", { ptyId, sessionId: session.id }).catch(() => {});
+          }
+          onClose();
+        },
+      });
+    });
+
     return actions;
-  }, [tabs, activeTabId, addTab, setActiveTab, onClose, onToggleScratchpad, onOpenAgentPicker, onTogglePreview, onToggleFileBrowser]);
+  }, [tabs, activeTabId, addTab, setActiveTab, onClose, onToggleScratchpad, onOpenAgentPicker, onTogglePreview, onToggleFileBrowser, resumable]);
 
   const filtered = useMemo(() => {
     if (!query) return items;
@@ -195,6 +230,7 @@ export default function CommandPalette({ onClose, onToggleScratchpad, onOpenAgen
       case "Panels": return "#bc8cff";
       case "Themes": return "#d29922";
       case "Recording": return "#ff7b72";
+      case "Sessions": return "#39c5cf";
       default: return "var(--text-muted)";
     }
   };
@@ -834,7 +834,7 @@ export default function SettingsPanel() {
                       onBlur={(e) => { e.currentTarget.style.borderColor = "var(--border)"; }}
                     />
                     <p style={{ fontSize: "11px", color: "var(--text-muted)", marginTop: "6px" }}>
-                      Required for Orchestrator Mode. Get your key at console.anthropic.com
+                      Required for Orchestrator Mode. Stored in the system keychain and passed to agent runs. Get your key at console.anthropic.com
                     </p>
                   </div>
 
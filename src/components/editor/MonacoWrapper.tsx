@@ -1,6 +1,7 @@
 import { useRef, useCallback, useEffect } from "react";
 import Editor, { type OnMount } from "@monaco-editor/react";
 import type { editor as monacoEditor } from "monaco-editor";
+import { invoke } from "@tauri-apps/api/core";
 
 const EXT_TO_LANGUAGE: Record<string, string> = {
   ts: "typescript", tsx: "typescript",
@@ -23,6 +24,8 @@ const EXT_TO_LANGUAGE: Record<string, string> = {
   proto: "protobuf",
 };
 
+const SEVERITY: Record<number, string> = { 8: "error", 4: "warning", 2: "info", 1: "hint" };
+
 function detectLanguage(filePath: string): string {
   const ext = filePath.split(".").pop()?.toLowerCase() ?? "";
   const basename = filePath.split("/").pop()?.toLowerCase() ?? "";
@@ -39,6 +42,8 @@ interface MonacoWrapperProps {
 
 export default function MonacoWrapper({ filePath, content, onChange, onSave }: MonacoWrapperProps) {
   const editorRef = useRef<monacoEditor.IStandaloneCodeEditor | null>(null);
+  const filePathRef = useRef(filePath);
+  filePathRef.current = filePath;
   const language = detectLanguage(filePath);
 
   const handleMount: OnMount = useCallback((editor, monaco) => {
@@ -49,6 +54,43 @@ export default function MonacoWrapper({ filePath, content, onChange, onSave }: M
       keybindings: [monaco.KeyMod.CtrlCmd | monaco.KeyCode.KeyS],
       run: () => onSave(),
     });
+
+    // Share selection and diagnostics with agents via the built-in MCP server
+    let selectionTimer: ReturnType<typeof setTimeout> | undefined;
+    editor.onDidChangeCursorSelection((e) => {
+      clearTimeout(selectionTimer);
+      selectionTimer = setTimeout(() => {
+        const text = editor.getModel()?.getValueInRange(e.selection) ?? "";
+        invoke("set_ide_selection", {
+          selection: text
+            ? {
+                path: filePathRef.current,
+                text,
+                start_line: e.selection.startLineNumber,
+                end_line: e.selection.endLineNumber,
+              }
+            : null,
+        }).catch(() => {});
+      }, 200);
+    });
+    const markersListener = monaco.editor.onDidChangeMarkers((uris) => {
+      const model = editor.getModel();
+      if (!model || !uris.some((uri) => uri.toString() === model.uri.toString())) return;
+      const diagnostics = monaco.editor.getModelMarkers({ resource: model.uri }).map((m) => ({
+        line: m.startLineNumber,
+        column: m.startColumn,
+        severity: SEVERITY[m.severity] ?? "info",
+        message: m.message,
+        source: m.source ?? null,
+      }));
+      invoke("set_ide_diagnostics", { path: filePathRef.current, diagnostics }).catch(() => {});
+    });
+    editor.onDidDispose(() => {
+      clearTimeout(selectionTimer);
+      markersListener.dispose();
+      invoke("set_ide_diagnostics", { path: filePathRef.current, diagnostics: [] }).catch(() => {});
+    });
+
     editor.focus();
   }, [onSave]);
 
@@ -73,22 +73,33 @@ function checkIdleTransition(paneId: string) {
       .then(({ useAgentTrackerStore }) => {
         if (!instances.has(paneId)) return; // Pane destroyed during async import
         const session = useAgentTrackerStore.getState().getActiveSession(paneId);
-        if (session) {
-          useAgentTrackerStore.getState().endSession(paneId);
-          sendNotification(`${session.agentIcon} ${session.agentName} finished`, "Agent completed its task");
-
-          // Update orchestrator task status if this pane was dispatched by orchestrator
-          import("../stores/orchestratorStore").then(({ useOrchestratorStore }) => {
-            const store = useOrchestratorStore.getState();
-            for (const orchSession of store.sessions) {
-              const task = orchSession.tasks.find((t) => t.paneId === paneId && t.status === "running");
-              if (task) {
-                store.updateTaskStatus(orchSession.id, task.id, "completed");
-                break;
-              }
-            }
-          }).catch(() => {});
+        if (!session) return;
+        const label = `${session.agentIcon} ${session.agentName}`;
+        const { signal, line } = classifyIdleOutput(paneId);
+
+        // A question means the agent is waiting, not done — keep the session running
+        if (signal === "needs_input") {
+          sendNotification(paneId, signal, `${label} needs your input`, line ?? "Agent is waiting for an answer");
+          return;
+        }
+        useAgentTrackerStore.getState().endSession(paneId);
+        if (signal === "error") {
+          sendNotification(paneId, signal, `${label} hit an error`, line ?? "Agent stopped with an error");
+        } else {
+          sendNotification(paneId, signal, `${label} finished`, "Agent completed its task");
         }
+
+        // Update orchestrator task status if this pane was dispatched by orchestrator
+        import("../stores/orchestratorStore").then(({ useOrchestratorStore }) => {
+          const store = useOrchestratorStore.getState();
+          for (const orchSession of store.sessions) {
+            const task = orchSession.tasks.find((t) => t.paneId === paneId && t.status === "running");
+            if (task) {
+              store.updateTaskStatus(orchSession.id, task.id, signal === "error" ? "failed" : "completed");
+              break;
+            }
+          }
+        }).catch(() => {});
       })
       .catch(() => {})
       .finally(() => {
@@ -97,19 +108,57 @@ function checkIdleTransition(paneId: string) {
   }
 }
 
-function sendNotification(title: string, body: string) {
-  // System notification
-  if ("Notification" in window && Notification.permission === "granted") {
-    new Notification(title, { body, silent: false });
-  } else if ("Notification" in window && Notification.permission !== "denied") {
-    Notification.requestPermission().then((perm) => {
-      if (perm === "granted") new Notification(title, { body, silent: false });
-    });
+type AgentSignal = "finished" | "error" | "needs_input";
+
+// Agent CLIs end on a prompt like these when they stop to ask something
+const QUESTION_PATTERNS = [
+  /\(y\/n\)|\[y\/n\]/i,
+  /\bdo you want\b|\bwould you like\b|\bshould I\b/i,
+  /\ballow\b.*\?|\bproceed\?/i,
+  /❯\s*1\.\s*yes/i,
+];
+const ERROR_PATTERNS = [/\bAPI Error\b/, /^\s*(?:✗|⎿)?\s*Error:/, /\brate limit(?:ed)?\b/i];
+
+// Look at the bottom of the screen to tell a question or error apart from a normal finish
+function classifyIdleOutput(paneId: string): { signal: AgentSignal; line?: string } {
+  const inst = instances.get(paneId);
+  if (!inst) return { signal: "finished" };
+  const buffer = inst.term.buffer.active;
+  const lines: string[] = [];
+  for (let y = buffer.baseY + buffer.cursorY; y >= 0 && lines.length < 15; y--) {
+    const text = buffer.getLine(y)?.translateToString(true).trim();
+    if (text) lines.push(text);
+  }
+  // Box-drawing borders from TUI prompts carry no meaning
+  const clean = (text: string) => text.replace(/^[│┃|>\s]+|[│┃|\s]+$/g, "");
+  for (const text of lines) {
+    if (QUESTION_PATTERNS.some((re) => re.test(clean(text)))) return { signal: "needs_input", line: clean(text) };
+  }
+  for (const text of lines) {
+    if (ERROR_PATTERNS.some((re) => re.test(clean(text)))) return { signal: "error", line: clean(text) };
   }
-  // Dispatch custom event for in-app notification
+  return { signal: "finished" };
+}
+
+// Native notification while the app is in the background (the backend skips it when focused),
+// plus the in-app toast
+function sendNotification(paneId: string, signal: AgentSignal, title: string, body: string) {
+  invoke("notify_agent_event", { paneId, signal, title, body }).catch(() => {});
   window.dispatchEvent(new CustomEvent("agent-notification", { detail: { title, body } }));
 }
 
+// A PTY error in a pane running an agent is worth surfacing even without an idle transition
+function notifyPtyError(paneId: string, message?: string) {
+  import("../stores/agentTrackerStore")
+    .then(({ useAgentTrackerStore }) => {
+      const session = useAgentTrackerStore.getState().getActiveSession(paneId);
+      if (!session) return;
+      useAgentTrackerStore.getState().endSession(paneId);
+      sendNotification(paneId, "error", `${session.agentIcon} ${session.agentName} hit an error`, message ?? "Terminal error");
+    })
+    .catch(() => {});
+}
+
 // Poll for idle transitions every 2 seconds
 const idleCheckInterval = setInterval(() => {
   lastActivity.forEach((_ts, paneId) => {
@@ -138,6 +187,7 @@ function destroyInstance(paneId: string) {
   const inst = instances.get(paneId);
   if (!inst) return;
   if (inst.ptyId !== null) {
+    invoke("unwatch_agent_budget", { ptyId: inst.ptyId }).catch(() => {});
     invoke("kill_pty", { id: inst.ptyId });
   }
   inst.term.dispose();
@@ -221,6 +271,7 @@ async function createReattachedInstance(
       term.writeln("\r\n\x1b[38;5;241m[Process exited]\x1b[0m");
     } else if (event.type === "error") {
       term.writeln(`\r\n\x1b[31m[Error: ${event.message}]\x1b[0m`);
+      notifyPtyError(paneId, event.message);
     }
   };
 
@@ -432,6 +483,7 @@ async function createInstance(paneId: string, setPtyId: (paneId: string, ptyId:
       term.writeln("\r\n\x1b[38;5;241m[Process exited]\x1b[0m");
     } else if (event.type === "error") {
       term.writeln(`\r\n\x1b[31m[Error: ${event.message}]\x1b[0m`);
+      notifyPtyError(paneId, event.message);
     }
   };
 
@@ -0,0 +1,36 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** attachments: ~/.ade/attachments; project: <root>/.ade/attachments; none: leave in place */
+export type CopyInto = "attachments" | "project" | "none";
+
+export interface IngestOptions {
+  copy_into?: CopyInto;
+  /** Required to copy into the project; files already inside it are referenced in place */
+  root?: string;
+  /** Bytes; 20 MB by default */
+  max_size?: number;
+}
+
+export type AttachmentKind = "image" | "document" | "text" | "directory" | "other";
+
+export interface Attachment {
+  name: string;
+  original_path: string;
+  /** Where it is now, after any copy */
+  path: string;
+  relative_path: string | null;
+  mime: string;
+  kind: AttachmentKind;
+  size: number;
+  /** What to insert into a prompt: "@relative/path" inside the project, else the absolute path */
+  reference: string;
+}
+
+export interface IngestResult {
+  accepted: Attachment[];
+  rejected: { path: string; reason: string }[];
+}
+
+export function ingestDroppedFiles(paths: string[], options?: IngestOptions): Promise<IngestResult> {
+  return invoke<IngestResult>("ingest_dropped_files", { paths, options });
+}
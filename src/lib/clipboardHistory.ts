@@ -0,0 +1,52 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+export type ClipContent =
+  | { type: "text"; text: string }
+  /** path: the PNG under ~/.ade/clipboard */
+  | { type: "image"; width: number; height: number; path: string };
+
+export interface ClipItem {
+  id: number;
+  content: ClipContent;
+  /** The start of the text, or "Image 1920×1080" */
+  preview: string;
+  copied_at: number;
+}
+
+export interface ClipboardSettings {
+  enabled: boolean;
+  max_items: number;
+  /** Regexes of text never to record, on top of the built-in secret patterns */
+  exclude_patterns: string[];
+}
+
+/** Newest first */
+export function getClipboardHistory(): Promise<ClipItem[]> {
+  return invoke<ClipItem[]>("get_clipboard_history");
+}
+
+/**
+ * Paste an item into a terminal as if typed (images as their file path), or put it back on the
+ * system clipboard when no terminal is given.
+ */
+export function pasteHistoryItem(id: number, ptyId?: number): Promise<void> {
+  return invoke("paste_history_item", { id, ptyId });
+}
+
+export function clearClipboardHistory(): Promise<void> {
+  return invoke("clear_clipboard_history");
+}
+
+export function getClipboardSettings(): Promise<ClipboardSettings> {
+  return invoke<ClipboardSettings>("get_clipboard_settings");
+}
+
+export function setClipboardSettings(settings: ClipboardSettings): Promise<ClipboardSettings> {
+  return invoke<ClipboardSettings>("set_clipboard_settings", { settings });
+}
+
+/** Fires when something new is copied */
+export function onClipboardHistoryChanged(handler: () => void): Promise<UnlistenFn> {
+  return listen("clipboard-history-changed", () => handler());
+}
@@ -0,0 +1,66 @@
+import { invoke, Channel } from "@tauri-apps/api/core";
+
+/** Operations that only run after request_confirmation and execute_confirmed */
+export type DestructiveOp =
+  /** A file or directory and everything under it */
+  | { type: "delete_path"; path: string }
+  | {
+      type: "force_push";
+      repo: string;
+      /** origin when not given */
+      remote?: string;
+      branch?: string;
+      set_upstream?: boolean;
+    }
+  /** A process the app didn't start; TERM when no signal is given */
+  | { type: "kill_process"; pid: number; signal?: "TERM" | "INT" | "HUP" | "KILL" }
+  /** Whatever listens on a port: TERM, then KILL */
+  | { type: "free_port"; port: number }
+  /** A read-only project made writable again */
+  | { type: "make_writable"; path: string }
+  /** A JSON merge patch for the "policy" settings; the user's when root is not given */
+  | { type: "set_policy"; root?: string; patch: unknown };
+
+export interface Confirmation {
+  token: string;
+  op: DestructiveOp;
+  /** What will happen, to show the user before going ahead */
+  summary: string;
+  /** Unix ms after which the token no longer works */
+  expires_at: number;
+}
+
+export type ConfirmedOutcome =
+  | { type: "deleted"; path: string }
+  | { type: "pushed"; messages: string[] }
+  | { type: "killed"; pid: number; signal: string }
+  | { type: "freed"; port: number; pids: number[] }
+  | { type: "made_writable"; path: string }
+  | { type: "policy_set"; settings: unknown };
+
+export type PushProgress =
+  | { type: "progress"; phase: string; percent: number; current: number; total: number }
+  | { type: "message"; text: string };
+
+/** Describe `op` and get a token to carry it out with, good for a minute */
+export function requestConfirmation(op: DestructiveOp): Promise<Confirmation> {
+  return invoke<Confirmation>("request_confirmation", { op });
+}
+
+/**
+ * Carry out a confirmed operation. Fails when the token has expired or been used, or when
+ * the target changed since confirmation was asked for.
+ */
+export function executeConfirmed(
+  token: string,
+  onProgress?: (event: PushProgress) => void,
+): Promise<ConfirmedOutcome> {
+  const onEvent = new Channel<PushProgress>();
+  if (onProgress) onEvent.onmessage = onProgress;
+  return invoke<ConfirmedOutcome>("execute_confirmed", { token, onEvent });
+}
+
+/** Delete a file, symlink or empty directory; anything else needs a delete_path confirmation */
+export function deletePath(path: string): Promise<void> {
+  return invoke("delete_path", { path });
+}
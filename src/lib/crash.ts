@@ -0,0 +1,25 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+export interface BackendError {
+  /** The part of the backend that failed, like "terminal" or "tasks" */
+  subsystem: string;
+  /** What it was working on, like "pty:3" or "task:12" */
+  context: string | null;
+  message: string;
+  /** A panic rather than an error the thread returned */
+  panic: boolean;
+  /** The crash report on disk, for panics */
+  report_path: string | null;
+  timestamp: number;
+}
+
+/** Panics and thread errors since the app started, oldest first */
+export function getBackendErrors(): Promise<BackendError[]> {
+  return invoke<BackendError[]>("get_backend_errors");
+}
+
+/** Fires when a backend thread panics or fails, e.g. a terminal whose output stopped */
+export function onBackendError(handler: (error: BackendError) => void): Promise<UnlistenFn> {
+  return listen<BackendError>("backend-error", (event) => handler(event.payload));
+}
@@ -0,0 +1,26 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+/** A parsed ade:// link */
+export type DeepLink =
+  | { type: "open"; path: string; line: number | null; column: number | null; is_dir: boolean }
+  /** Any app or web page can open a link: confirm before running `command` */
+  | { type: "run-task"; root: string; task: string; command: string }
+  | { type: "invalid"; url: string; error: string };
+
+/**
+ * Links received before the frontend was listening, such as the one that launched the app.
+ * Call once, after subscribing with onDeepLink.
+ */
+export function takePendingDeepLinks(): Promise<DeepLink[]> {
+  return invoke<DeepLink[]>("take_pending_deep_links");
+}
+
+/** Rejects when the link can't be followed */
+export function parseDeepLink(url: string): Promise<DeepLink> {
+  return invoke<DeepLink>("parse_deep_link", { url });
+}
+
+export function onDeepLink(handler: (link: DeepLink) => void): Promise<UnlistenFn> {
+  return listen<DeepLink>("deep-link", (event) => handler(event.payload));
+}
@@ -0,0 +1,20 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+/** A dev server announced in a terminal's or task's output */
+export interface DevServer {
+  port: number;
+  url: string;
+  pty_id: number | null;
+  task_run_id: number | null;
+  detected_at: number;
+}
+
+/** Detected servers that still accept connections, newest first */
+export function listDevServers(): Promise<DevServer[]> {
+  return invoke<DevServer[]>("list_dev_servers");
+}
+
+export function onDevServerDetected(handler: (server: DevServer) => void): Promise<UnlistenFn> {
+  return listen<DevServer>("dev-server-detected", (event) => handler(event.payload));
+}
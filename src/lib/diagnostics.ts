@@ -0,0 +1,31 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+/** An error or warning found in task or terminal output */
+export interface Diagnostic {
+  file: string;
+  line: number;
+  column: number | null;
+  severity: "error" | "warning";
+  message: string;
+  /** E0308, TS2322, the eslint rule, or the Python exception type */
+  code: string | null;
+  tool: "rustc" | "tsc" | "eslint" | "pytest";
+  /** `task:<root>:<task>` or `pty:<id>` */
+  origin: string;
+}
+
+export function getDiagnostics(root: string): Promise<Diagnostic[]> {
+  return invoke<Diagnostic[]>("get_diagnostics", { root });
+}
+
+export function clearDiagnostics(root: string): Promise<void> {
+  return invoke("clear_diagnostics", { root });
+}
+
+/** Calls `onChange` whenever problems in files under `root` are added or removed */
+export function onDiagnosticsChanged(root: string, onChange: () => void): Promise<UnlistenFn> {
+  return listen<{ origin: string; files: string[] }>("diagnostics-changed", (event) => {
+    if (event.payload.files.some((file) => file.startsWith(root))) onChange();
+  });
+}
@@ -0,0 +1,61 @@
+import { invoke, Channel } from "@tauri-apps/api/core";
+
+export interface Container {
+  id: string;
+  name: string;
+  image: string;
+  /** created, running, paused, restarting, exited or dead */
+  state: string;
+  /** As docker ps shows it, e.g. "Up 2 hours" */
+  status: string;
+  ports: string;
+  created_at: string;
+  compose_project: string | null;
+  compose_service: string | null;
+}
+
+export type ContainerLogEvent =
+  | { type: "stdout"; text: string }
+  | { type: "stderr"; text: string }
+  /** Always last */
+  | { type: "finished"; error: string | null };
+
+export interface ContainerLogsOptions {
+  /** 500 by default */
+  tail?: number;
+  /** Keep streaming new lines until stopped */
+  follow?: boolean;
+  /** A timestamp (RFC 3339) or duration ("10m") */
+  since?: string;
+  timestamps?: boolean;
+}
+
+/** Every container, running or not, running ones first */
+export function listContainers(): Promise<Container[]> {
+  return invoke<Container[]>("list_containers");
+}
+
+/** Resolves to an id for stopContainerLogs */
+export function containerLogs(
+  id: string,
+  onEvent: (event: ContainerLogEvent) => void,
+  options?: ContainerLogsOptions,
+): Promise<number> {
+  const channel = new Channel<ContainerLogEvent>();
+  channel.onmessage = onEvent;
+  return invoke<number>("container_logs", { id, options, onEvent: channel });
+}
+
+export function stopContainerLogs(id: number): Promise<void> {
+  return invoke("stop_container_logs", { id });
+}
+
+/** Copy a local file or directory to the absolute path `dest` in the container */
+export function copyToContainer(id: string, src: string, dest: string): Promise<void> {
+  return invoke("copy_to_container", { id, src, dest });
+}
+
+/** The command for a terminal with a shell inside the container */
+export function execShellCommand(id: string, shell = "sh"): string {
+  return `docker exec -it ${id} ${shell}`;
+}
@@ -0,0 +1,20 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface DoctorFix {
+  label: string;
+  /** Shell command to run in a terminal */
+  command: string | null;
+  url: string | null;
+}
+
+export interface DoctorCheck {
+  id: "git" | "node" | "claude" | "shell" | "path" | "watch-limit" | "disk";
+  title: string;
+  status: "pass" | "warn" | "fail";
+  detail: string;
+  fix: DoctorFix | null;
+}
+
+export function runDoctor(): Promise<DoctorCheck[]> {
+  return invoke<DoctorCheck[]>("run_doctor");
+}
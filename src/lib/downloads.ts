@@ -0,0 +1,37 @@
+import { invoke, Channel } from "@tauri-apps/api/core";
+
+export interface DownloadOptions {
+  /** Hex SHA-256 the file must match */
+  sha256?: string;
+  /** Continue from a partial download left by an earlier attempt; true by default */
+  resume?: boolean;
+  /** Replace the destination if it exists */
+  overwrite?: boolean;
+}
+
+export type DownloadEvent =
+  | { type: "started"; total_bytes: number | null; resumed_from: number }
+  | { type: "progress"; downloaded_bytes: number; total_bytes: number | null }
+  | { type: "finished"; path: string; sha256: string }
+  | { type: "failed"; message: string }
+  | { type: "cancelled" };
+
+/**
+ * Start a download to `dest`, a file path or a directory to save into. Resolves to its id for
+ * cancelDownload; progress and the outcome arrive as events.
+ */
+export function downloadFile(
+  url: string,
+  dest: string,
+  options: DownloadOptions | undefined,
+  onEvent: (event: DownloadEvent) => void,
+): Promise<number> {
+  const channel = new Channel<DownloadEvent>();
+  channel.onmessage = onEvent;
+  return invoke<number>("download_file", { url, dest, options, onEvent: channel });
+}
+
+/** What was fetched so far is kept, so downloading again resumes */
+export function cancelDownload(id: number): Promise<void> {
+  return invoke("cancel_download", { id });
+}
@@ -0,0 +1,34 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface EditorInfo {
+  /** What open_in_editor and the `editor` setting take, e.g. "vscode" or "nvim" */
+  name: string;
+  program: string;
+  /** Runs in a terminal of the app rather than its own window */
+  terminal: boolean;
+  installed: boolean;
+}
+
+export interface OpenInEditorOptions {
+  /** Defaults to the `editor` setting, else the system default app */
+  editor?: string;
+  line?: number;
+  column?: number;
+}
+
+export type OpenedIn =
+  | { type: "external"; editor: string }
+  /** Attach with attach_pty to show the editor */
+  | { type: "terminal"; editor: string; pty_id: number };
+
+export function revealInFileManager(path: string): Promise<void> {
+  return invoke("reveal_in_file_manager", { path });
+}
+
+export function openInEditor(path: string, options?: OpenInEditorOptions): Promise<OpenedIn> {
+  return invoke<OpenedIn>("open_in_editor", { path, options });
+}
+
+export function listEditors(): Promise<EditorInfo[]> {
+  return invoke<EditorInfo[]>("list_editors");
+}
@@ -0,0 +1,38 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** One line of a .env file. Values come back masked (null); send them back as null to keep them. */
+export type EnvLine =
+  | {
+      type: "entry";
+      /** Position in the file as read; omit for new lines */
+      line?: number | null;
+      key: string;
+      value: string | null;
+      export?: boolean;
+      masked?: boolean;
+    }
+  /** `text` is null for a commented-out assignment, whose variable is `key` */
+  | { type: "comment"; line?: number | null; text: string | null; key?: string | null }
+  | { type: "blank" }
+  /** Not valid dotenv syntax; kept as it is, text withheld */
+  | { type: "invalid"; line: number };
+
+export interface EnvFile {
+  path: string;
+  exists: boolean;
+  lines: EnvLine[];
+}
+
+export function readEnvFile(path: string): Promise<EnvFile> {
+  return invoke<EnvFile>("read_env_file", { path });
+}
+
+/** Replaces the file with `entries`, in order, and returns it re-read */
+export function writeEnvFile(path: string, entries: EnvLine[]): Promise<EnvFile> {
+  return invoke<EnvFile>("write_env_file", { path, entries });
+}
+
+/** Only call on an explicit user action; the value is a secret */
+export function revealEnvValue(path: string, key: string): Promise<string | null> {
+  return invoke<string | null>("reveal_env_value", { path, key });
+}
@@ -0,0 +1,12 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** A running terminal, or a task in a project by its list_tasks id */
+export type EnvTarget = { pty: number } | { task: { root: string; task: string } };
+
+/**
+ * Load an env set (project settings `env_sets`) into a running terminal, or make it the one a
+ * task runs with. For a task, null goes back to the default env set.
+ */
+export function applyEnvProfile(target: EnvTarget, profile: string | null): Promise<void> {
+  return invoke("apply_env_profile", { target, profile });
+}
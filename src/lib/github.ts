@@ -0,0 +1,82 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** Keychain entry the REST API token is read from; without one, the gh CLI's login is used */
+export const GITHUB_TOKEN_SECRET = "github-token";
+
+export interface IssueFilters {
+  /** open (the default), closed or all */
+  state?: "open" | "closed" | "all";
+  /** Issues with every one of these labels */
+  labels?: string[];
+  /** A login, "none" or "*" */
+  assignee?: string;
+  creator?: string;
+  mentioned?: string;
+  /** A milestone number, "none" or "*" */
+  milestone?: string;
+  sort?: "created" | "updated" | "comments";
+  direction?: "asc" | "desc";
+  /** ISO 8601 */
+  since?: string;
+  /** 30 by default, at most 100 */
+  per_page?: number;
+  page?: number;
+  include_pull_requests?: boolean;
+  /** Skip the one-minute cache */
+  refresh?: boolean;
+}
+
+export interface Issue {
+  number: number;
+  title: string;
+  state: "open" | "closed";
+  author: string;
+  labels: string[];
+  assignees: string[];
+  comment_count: number;
+  url: string;
+  is_pull_request: boolean;
+  body: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface IssueComment {
+  author: string;
+  body: string;
+  url: string;
+  created_at: string;
+}
+
+export interface IssueDetail extends Issue {
+  /** Oldest first */
+  comments: IssueComment[];
+}
+
+export function setGithubToken(token: string): Promise<void> {
+  return invoke("store_secret", { name: GITHUB_TOKEN_SECRET, value: token });
+}
+
+/** `repo` is a local checkout or owner/name */
+export function listIssues(repo: string, filters?: IssueFilters): Promise<Issue[]> {
+  return invoke<Issue[]>("github_list_issues", { repo, filters });
+}
+
+export function getIssue(repo: string, number: number, refresh?: boolean): Promise<IssueDetail> {
+  return invoke<IssueDetail>("github_get_issue", { repo, number, refresh });
+}
+
+/** An issue and its conversation as a task for an agent */
+export function issueTaskPrompt(issue: IssueDetail): string {
+  const kind = issue.is_pull_request ? "pull request" : "issue";
+  const parts = [
+    `Work on GitHub ${kind} #${issue.number}: ${issue.title}`,
+    issue.url,
+    `Opened by @${issue.author}${issue.labels.length ? ` · labels: ${issue.labels.join(", ")}` : ""}`,
+    issue.body.trim() || "(no description)",
+  ];
+  for (const comment of issue.comments) {
+    parts.push(`--- @${comment.author} (${comment.created_at}):\n${comment.body.trim()}`);
+  }
+  return parts.join("\n\n");
+}
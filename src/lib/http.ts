@@ -0,0 +1,37 @@
+import { invoke, Channel } from "@tauri-apps/api/core";
+
+export interface HttpOptions {
+  headers?: Record<string, string>;
+  body?: string;
+  /** For the whole request, body included; 30 by default */
+  timeout_secs?: number;
+  /** Body bytes read before stopping; 10 MB by default */
+  max_bytes?: number;
+}
+
+export type HttpEvent =
+  /** headers: repeated headers are joined with ", " */
+  | { type: "response"; status: number; url: string; headers: Record<string, string> }
+  | { type: "chunk"; data: number[] };
+
+export interface HttpSummary {
+  status: number;
+  /** After redirects */
+  url: string;
+  bytes: number;
+  /** The body went past max_bytes and was cut off */
+  truncated: boolean;
+  duration_ms: number;
+}
+
+/** Fetch without CORS limits, streaming the body; HTTP error statuses resolve normally */
+export function httpRequest(
+  method: string,
+  url: string,
+  options: HttpOptions | undefined,
+  onEvent: (event: HttpEvent) => void,
+): Promise<HttpSummary> {
+  const onChunk = new Channel<HttpEvent>();
+  onChunk.onmessage = onEvent;
+  return invoke<HttpSummary>("http_request", { method, url, options, onChunk });
+}
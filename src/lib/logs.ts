@@ -0,0 +1,27 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+export interface LogEntry {
+  /** RFC 3339 */
+  timestamp: string;
+  level: LogLevel;
+  /** Module that logged it */
+  target: string;
+  message: string;
+  /** Structured fields besides the message, like error */
+  fields: Record<string, unknown>;
+}
+
+/** Newest entries at `level` or more severe, oldest first (200 by default) */
+export function getRecentLogs(level?: LogLevel, limit?: number): Promise<LogEntry[]> {
+  return invoke<LogEntry[]>("get_recent_logs", { level, limit });
+}
+
+export function getLogLevel(): Promise<LogLevel> {
+  return invoke<LogLevel>("get_log_level");
+}
+
+export function setLogLevel(level: LogLevel): Promise<LogLevel> {
+  return invoke<LogLevel>("set_log_level", { level });
+}
@@ -0,0 +1,55 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/**
+ * Limits set under "policy" in .ade/settings.json, user-wide or per project; a project's
+ * can only add to the user's. Changing them takes a set_policy confirmation:
+ * {
+ *   "policy": {
+ *     "disabled_commands": ["run_command"],
+ *     "allowed_programs": ["npm", "cargo"],
+ *     "workspace_only_writes": true
+ *   }
+ * }
+ */
+export interface EffectivePolicy {
+  /** The project whose limits are added; null when only user settings apply */
+  project: string | null;
+  disabled_commands: string[];
+  /**
+   * Programs run_command may start (scheduled shell commands count as sh); any when null.
+   * Tasks, terminals and tool installs aren't limited by it: disable those commands instead.
+   */
+  allowed_programs: string[] | null;
+  workspace_only_writes: boolean;
+  /** Set with setProjectReadOnly rather than in settings */
+  read_only: boolean;
+}
+
+export type PolicyRule =
+  | "disabled_commands"
+  | "allowed_programs"
+  | "workspace_only_writes"
+  | "read_only"
+  | "invalid_policy";
+
+/** What a command refused by the policy rejects with, instead of an error string */
+export interface PermissionDenied {
+  type: "permission_denied";
+  command: string;
+  rule: PolicyRule;
+  message: string;
+  project: string | null;
+}
+
+export function isPermissionDenied(error: unknown): error is PermissionDenied {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    (error as { type?: unknown }).type === "permission_denied"
+  );
+}
+
+/** The policy commands are checked against: `root`'s, or the last opened project's */
+export function getEffectivePolicy(root?: string): Promise<EffectivePolicy> {
+  return invoke<EffectivePolicy>("get_effective_policy", { root });
+}
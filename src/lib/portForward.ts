@@ -0,0 +1,38 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+/** reconnecting: dropped and waiting to retry; failed: gave up on an error retrying won't fix */
+export type ForwardStatus = "connecting" | "connected" | "reconnecting" | "failed" | "stopped";
+
+export interface PortForward {
+  id: number;
+  host: string;
+  remote_port: number;
+  local_port: number;
+  status: ForwardStatus;
+  /** Why the last connection ended */
+  error: string | null;
+  reconnects: number;
+  connected_at: number | null;
+}
+
+/**
+ * Forward localhost:`localPort` (the remote port by default) to `remotePort` on `host`, a name
+ * from ~/.ssh/config or user@hostname
+ */
+export function forwardPort(host: string, remotePort: number, localPort?: number): Promise<PortForward> {
+  return invoke<PortForward>("forward_port", { host, remotePort, localPort });
+}
+
+/** Every forward, including failed ones until they are stopped, oldest first */
+export function listForwards(): Promise<PortForward[]> {
+  return invoke<PortForward[]>("list_forwards");
+}
+
+export function stopForward(id: number): Promise<void> {
+  return invoke("stop_forward", { id });
+}
+
+export function onPortForwardChanged(handler: (forward: PortForward) => void): Promise<UnlistenFn> {
+  return listen<PortForward>("port-forward-changed", (event) => handler(event.payload));
+}
@@ -0,0 +1,26 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+/** auto: low-power mode while on battery */
+export type LowPowerMode = "auto" | "on" | "off";
+
+export interface PowerStatus {
+  source: "ac" | "battery" | "unknown";
+  battery_percent: number | null;
+  mode: LowPowerMode;
+  /** Whether background work is throttled now */
+  low_power: boolean;
+}
+
+export function getPowerStatus(): Promise<PowerStatus> {
+  return invoke<PowerStatus>("get_power_status");
+}
+
+export function setLowPowerMode(mode: LowPowerMode): Promise<PowerStatus> {
+  return invoke<PowerStatus>("set_low_power_mode", { mode });
+}
+
+/** Fires when the power source, battery level or low-power state changes */
+export function onPowerStatusChanged(handler: (status: PowerStatus) => void): Promise<UnlistenFn> {
+  return listen<PowerStatus>("power-status-changed", (event) => handler(event.payload));
+}
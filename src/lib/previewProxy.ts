@@ -0,0 +1,27 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface PreviewProxyOptions {
+  /** Reload the previewed page when files under this directory change */
+  watch_dir?: string;
+}
+
+export interface PreviewProxyInfo {
+  id: number;
+  /** Where the proxy listens */
+  port: number;
+  /** The dev server it forwards to */
+  target_port: number;
+  url: string;
+}
+
+/**
+ * Put a proxy in front of the dev server on `port` that the preview pane can always frame.
+ * Starting one for a port that already has a proxy returns that one.
+ */
+export function startPreviewProxy(port: number, options?: PreviewProxyOptions): Promise<PreviewProxyInfo> {
+  return invoke<PreviewProxyInfo>("start_preview_proxy", { port, options });
+}
+
+export function stopPreviewProxy(id: number): Promise<void> {
+  return invoke("stop_preview_proxy", { id });
+}
@@ -0,0 +1,55 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface ProcessInfo {
+  pid: number;
+  ppid: number;
+  user: string;
+  name: string;
+  command: string;
+  cpu_percent: number;
+  memory_bytes: number;
+  elapsed_secs: number;
+  /** Started by the app, directly or through a terminal, task or agent */
+  spawned_by_app: boolean;
+}
+
+export interface ListProcessesOptions {
+  /** A pid, or part of the command line */
+  filter?: string;
+  spawned_by_app?: boolean;
+}
+
+export type KillSignal = "TERM" | "INT" | "HUP" | "KILL";
+
+export type KillOutcome =
+  | { type: "killed"; pid: number; signal: KillSignal }
+  /** Ask the user, then call killProcess again with `token` (or executeConfirmed) */
+  | { type: "confirm"; pid: number; command: string; user: string; token: string };
+
+export function listProcesses(options?: ListProcessesOptions): Promise<ProcessInfo[]> {
+  return invoke<ProcessInfo[]>("list_processes", { options });
+}
+
+export function killProcess(pid: number, signal?: KillSignal, token?: string): Promise<KillOutcome> {
+  return invoke<KillOutcome>("kill_process", { pid, signal, token });
+}
+
+export interface PortUsage {
+  port: number;
+  /** Accepting connections, even when the owner isn't visible */
+  in_use: boolean;
+  processes: ProcessInfo[];
+}
+
+export type FreePortOutcome =
+  | { type: "freed"; port: number; pids: number[] }
+  /** Show the processes, then call freePort again with `token`, or executeConfirmed */
+  | { type: "confirm"; port: number; processes: ProcessInfo[]; token: string };
+
+export function getPortUsage(port: number): Promise<PortUsage> {
+  return invoke<PortUsage>("get_port_usage", { port });
+}
+
+export function freePort(port: number, token?: string): Promise<FreePortOutcome> {
+  return invoke<FreePortOutcome>("free_port", { port, token });
+}
@@ -0,0 +1,47 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** A project in the registry kept under ~/.ade/projects.json */
+export interface RecentProject {
+  path: string;
+  /** Display name; the folder name unless renamed */
+  name: string;
+  last_opened: number;
+  pinned: boolean;
+  /** Changes to its files, git state and terminals are refused */
+  read_only: boolean;
+  branch: string | null;
+  /** False when the folder has been moved or deleted */
+  exists: boolean;
+}
+
+/** Pinned projects first, then the rest by when they were last opened */
+export function listRecentProjects(): Promise<RecentProject[]> {
+  return invoke<RecentProject[]>("list_recent_projects");
+}
+
+export function recordProjectOpen(path: string): Promise<void> {
+  return invoke("record_project_open", { path });
+}
+
+export function pinProject(path: string, pinned = true): Promise<void> {
+  return invoke("pin_project", { path, pinned });
+}
+
+/**
+ * While read-only, commands that would change the project's files, git state or terminals
+ * reject with a PermissionDenied (rule "read_only"); reading, searching and viewing still work.
+ * Making a read-only project writable again fails here: it takes a make_writable confirmation.
+ */
+export function setProjectReadOnly(path: string, readOnly: boolean): Promise<void> {
+  return invoke("set_project_read_only", { path, readOnly });
+}
+
+/** An empty name goes back to the folder name */
+export function renameProject(path: string, name: string): Promise<void> {
+  return invoke("rename_project", { path, name });
+}
+
+/** Fails for a read-only project until it is made writable */
+export function removeRecent(path: string): Promise<void> {
+  return invoke("remove_recent", { path });
+}
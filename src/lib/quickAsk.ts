@@ -0,0 +1,42 @@
+import { invoke, Channel } from "@tauri-apps/api/core";
+
+export interface QuickAskOptions {
+  provider?: "anthropic" | "openai";
+  model?: string;
+  system?: string;
+  maxTokens?: number;
+  /** Directory relative context paths are resolved against */
+  cwd?: string;
+}
+
+type QuickAskEvent =
+  | { type: "delta"; text: string }
+  | { type: "done"; text: string; model: string; input_tokens: number | null; output_tokens: number | null };
+
+/**
+ * One-shot question straight to the model API, without starting an agent CLI.
+ * `onDelta` receives the answer as it streams; resolves with the full answer.
+ */
+export async function quickAsk(
+  prompt: string,
+  contextPaths: string[] = [],
+  options: QuickAskOptions = {},
+  onDelta?: (text: string) => void,
+): Promise<string> {
+  const channel = new Channel<QuickAskEvent>();
+  channel.onmessage = (event) => {
+    if (event.type === "delta") onDelta?.(event.text);
+  };
+  return invoke<string>("quick_ask", {
+    prompt,
+    contextPaths,
+    options: {
+      provider: options.provider,
+      model: options.model,
+      system: options.system,
+      max_tokens: options.maxTokens,
+      cwd: options.cwd,
+    },
+    onDelta: channel,
+  });
+}
@@ -0,0 +1,21 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/**
+ * A project on another machine. Pass as `location` to list_directory, read_file,
+ * write_text_file, create_directory, read_file_base64, search_text, watch_directory and
+ * create_pty; without one they stay local.
+ */
+export interface Location {
+  /** A host from ~/.ssh/config, or user@hostname */
+  host: string;
+}
+
+/** Host aliases from ~/.ssh/config, without wildcard patterns */
+export function listSshHosts(): Promise<string[]> {
+  return invoke<string[]>("list_ssh_hosts");
+}
+
+/** Close the SFTP session to `host`; the next remote command opens a new one */
+export function disconnectRemote(host: string): Promise<void> {
+  return invoke("disconnect_remote", { host });
+}
@@ -0,0 +1,15 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** window and region let the user pick interactively */
+export type CaptureMode = "full" | "window" | "region";
+
+export interface ScreenshotOptions {
+  mode?: CaptureMode;
+  /** Wait before capturing, e.g. to open a menu; at most 10 */
+  delay_secs?: number;
+}
+
+/** Path of the saved PNG, or null when the user cancelled the selection */
+export function captureScreenshot(options?: ScreenshotOptions): Promise<string | null> {
+  return invoke<string | null>("capture_screenshot", { options });
+}
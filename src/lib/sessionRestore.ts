@@ -0,0 +1,48 @@
+import { invoke, Channel } from "@tauri-apps/api/core";
+
+export interface RestoredPty {
+  /** Pass to attachPty to receive its output */
+  id: number;
+  cwd: string | null;
+  label: string | null;
+  /** Program that was running, without its arguments */
+  command: string | null;
+  /** Whether `command` was started again in the new shell */
+  rerun: boolean;
+}
+
+export interface RestoredSession {
+  saved_at: number;
+  ptys: RestoredPty[];
+  watchers: { dir: string; extensions: string[] }[];
+  files: string[];
+}
+
+type PtyEvent =
+  | { type: "output"; data: number[] }
+  | { type: "exit" }
+  | { type: "error"; message: string };
+
+/**
+ * Reopen the terminals of the previous run as fresh shells in their last directories.
+ * Resolves with null when there is nothing to restore.
+ */
+export function restoreLastSession(
+  options: { rows?: number; cols?: number; rerunCommands?: boolean } = {},
+): Promise<RestoredSession | null> {
+  return invoke<RestoredSession | null>("restore_last_session", {
+    options: { rows: options.rows, cols: options.cols, rerun_commands: options.rerunCommands ?? false },
+  });
+}
+
+/** Receive a restored PTY's output, starting with anything printed before attaching */
+export function attachPty(id: number, onEvent: (event: PtyEvent) => void): Promise<void> {
+  const channel = new Channel<PtyEvent>();
+  channel.onmessage = onEvent;
+  return invoke("attach_pty", { id, onEvent: channel });
+}
+
+/** Files saved with the session, reopened by restoreLastSession */
+export function setSessionFiles(files: string[]): Promise<void> {
+  return invoke("set_session_files", { files });
+}
@@ -0,0 +1,38 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+export type ShortcutAction = "toggle-window" | "new-agent-task-from-clipboard" | "focus-terminal";
+
+export interface ShortcutBinding {
+  action: ShortcutAction;
+  description: string;
+  /** e.g. "CommandOrControl+Shift+Space"; null when unbound */
+  accelerator: string | null;
+  registered: boolean;
+  /** Why a saved binding couldn't be registered at startup */
+  error: string | null;
+}
+
+export interface ShortcutTriggered {
+  action: ShortcutAction;
+  /** Clipboard text, for new-agent-task-from-clipboard */
+  clipboard: string | null;
+}
+
+export function listShortcuts(): Promise<ShortcutBinding[]> {
+  return invoke<ShortcutBinding[]>("list_shortcuts");
+}
+
+/** Rejects when another action or another application already uses the shortcut */
+export function registerShortcut(action: ShortcutAction, accelerator: string): Promise<ShortcutBinding[]> {
+  return invoke<ShortcutBinding[]>("register_shortcut", { action, accelerator });
+}
+
+export function unregisterShortcut(action: ShortcutAction): Promise<ShortcutBinding[]> {
+  return invoke<ShortcutBinding[]>("unregister_shortcut", { action });
+}
+
+/** Fires after the window has been brought forward; toggle-window is handled entirely in Rust */
+export function onGlobalShortcut(handler: (event: ShortcutTriggered) => void): Promise<UnlistenFn> {
+  return listen<ShortcutTriggered>("global-shortcut", (event) => handler(event.payload));
+}
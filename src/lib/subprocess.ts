@@ -0,0 +1,74 @@
+import { invoke, Channel } from "@tauri-apps/api/core";
+
+export type CommandEvent =
+  /** Always first; pass `run_id` to cancelCommand */
+  | { type: "started"; run_id: number; pid: number }
+  | { type: "stdout"; text: string }
+  | { type: "stderr"; text: string }
+  /** Always last */
+  | {
+      type: "finished";
+      exit_code: number | null;
+      success: boolean;
+      cancelled: boolean;
+      timed_out: boolean;
+      duration_ms: number;
+    };
+
+export interface RunCommandOptions {
+  cwd?: string;
+  /** Added to the app's environment */
+  env?: Record<string, string>;
+  /** 10 minutes by default; 0 for no limit */
+  timeout_ms?: number;
+}
+
+/** Runs `program` without a shell. Resolves to the exit code, or null if it was killed. */
+export function runCommand(
+  program: string,
+  args: string[],
+  onEvent: (event: CommandEvent) => void,
+  options?: RunCommandOptions,
+): Promise<number | null> {
+  const channel = new Channel<CommandEvent>();
+  channel.onmessage = onEvent;
+  return invoke<number | null>("run_command", { program, args, options, onEvent: channel });
+}
+
+export function cancelCommand(runId: number): Promise<void> {
+  return invoke("cancel_command", { runId });
+}
+
+export interface ResolvedCommand {
+  path: string;
+  /** Only when requested */
+  version: string | null;
+}
+
+/** Rejects when the program isn't installed. `refresh` skips cached lookups, e.g. after installing it. */
+export function resolveCommand(
+  command: string,
+  options?: { version?: boolean; refresh?: boolean },
+): Promise<ResolvedCommand> {
+  return invoke<ResolvedCommand>("resolve_command", { command, ...options });
+}
+
+export interface Semver {
+  major: number;
+  minor: number;
+  patch: number;
+  pre: string | null;
+}
+
+export interface ToolVersion {
+  tool: string;
+  /** null when not installed */
+  path: string | null;
+  version: string | null;
+  semver: Semver | null;
+}
+
+/** Resolves and version-probes all `tools` concurrently, in one call */
+export function getToolVersions(tools: string[]): Promise<ToolVersion[]> {
+  return invoke<ToolVersion[]>("get_tool_versions", { tools });
+}
@@ -0,0 +1,23 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface SystemInfo {
+  os: "macos" | "linux" | "windows" | string;
+  os_version: string | null;
+  kernel: string | null;
+  arch: string;
+  cpu_count: number;
+  total_memory_bytes: number | null;
+  /** Available without swapping, including reclaimable caches */
+  free_memory_bytes: number | null;
+  /** The volume measured: the workspace's, else the home directory's */
+  disk_path: string;
+  disk_total_bytes: number | null;
+  disk_free_bytes: number | null;
+  app_version: string;
+  /** Defaults sized to this machine, for settings the user hasn't set */
+  suggested: { scrollback_lines: number; index_threads: number };
+}
+
+export function getSystemInfo(workspace?: string): Promise<SystemInfo> {
+  return invoke<SystemInfo>("get_system_info", { workspace });
+}
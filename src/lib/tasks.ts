@@ -0,0 +1,67 @@
+import { invoke, Channel } from "@tauri-apps/api/core";
+
+/** A package.json script, Makefile target, justfile recipe or Cargo alias */
+export interface Task {
+  /** The name, or `source:name` when several sources define it */
+  id: string;
+  name: string;
+  source: "npm" | "make" | "just" | "cargo";
+  command: string;
+  description: string | null;
+}
+
+export type TaskEvent =
+  | { type: "started"; run_id: number; task: string; command: string; started_at: number }
+  | { type: "output"; stream: "stdout" | "stderr" | "pty"; text: string }
+  | {
+      type: "finished";
+      exit_code: number | null;
+      success: boolean;
+      cancelled: boolean;
+      duration_ms: number;
+    };
+
+export interface TaskRun {
+  root: string;
+  task: string;
+  command: string;
+  started_at: number;
+  finished_at: number;
+  exit_code: number | null;
+  success: boolean;
+  cancelled: boolean;
+}
+
+export interface RunTaskOptions {
+  /** Run in a pseudo-terminal; output arrives as raw terminal chunks */
+  pty?: boolean;
+  rows?: number;
+  cols?: number;
+  /** Env set from the project settings, instead of the task's own or the default */
+  env_set?: string;
+}
+
+export function listTasks(root: string): Promise<Task[]> {
+  return invoke<Task[]>("list_tasks", { root });
+}
+
+/** Resolves to the run id once the task has started; `finished` is always the last event */
+export function runTask(
+  root: string,
+  task: string,
+  onEvent: (event: TaskEvent) => void,
+  options?: RunTaskOptions,
+): Promise<number> {
+  const channel = new Channel<TaskEvent>();
+  channel.onmessage = onEvent;
+  return invoke<number>("run_task", { root, task, options, onEvent: channel });
+}
+
+export function cancelTask(runId: number): Promise<void> {
+  return invoke("cancel_task", { runId });
+}
+
+/** Finished runs, newest first */
+export function taskHistory(root?: string, limit?: number): Promise<TaskRun[]> {
+  return invoke<TaskRun[]>("task_history", { root, limit });
+}
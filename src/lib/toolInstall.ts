@@ -0,0 +1,33 @@
+import { invoke, Channel } from "@tauri-apps/api/core";
+import type { ToolVersion } from "./subprocess";
+
+export type InstallMethod = "brew" | "npm" | "cargo" | "pipx";
+
+export type InstallEvent =
+  | { type: "started"; tool: string; method: InstallMethod; command: string }
+  | { type: "output"; stream: "stdout" | "stderr"; text: string }
+  /** The installer exited; the tool is being looked up and version-checked */
+  | { type: "verifying"; exit_code: number | null }
+  | { type: "finished"; success: boolean; message: string };
+
+export interface InstallMethods {
+  tool: string;
+  methods: InstallMethod[];
+  /** Methods whose package manager is installed */
+  available: InstallMethod[];
+}
+
+export function listInstallMethods(): Promise<InstallMethods[]> {
+  return invoke<InstallMethods[]>("list_install_methods");
+}
+
+/** Without `method`, uses the first available one. Resolves once the installed tool has been verified. */
+export function installTool(
+  name: string,
+  onEvent: (event: InstallEvent) => void,
+  method?: InstallMethod,
+): Promise<ToolVersion> {
+  const channel = new Channel<InstallEvent>();
+  channel.onmessage = onEvent;
+  return invoke<ToolVersion>("install_tool", { name, method, onEvent: channel });
+}
@@ -0,0 +1,17 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+/** Stops every running headless agent; resolves to how many were paused */
+export function pauseAllAgents(): Promise<number> {
+  return invoke<number>("pause_all_agents");
+}
+
+/** Continues the agents pauseAllAgents stopped; resolves to how many were resumed */
+export function resumeAllAgents(): Promise<number> {
+  return invoke<number>("resume_all_agents");
+}
+
+/** Fires when a recent project is picked from the tray menu, after the window has been shown */
+export function onTrayOpenProject(handler: (path: string) => void): Promise<UnlistenFn> {
+  return listen<{ path: string }>("tray-open-project", (event) => handler(event.payload.path));
+}
@@ -0,0 +1,38 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+export type TunnelProvider = "cloudflared" | "ngrok";
+
+/** starting: waiting for the provider's URL; failed: the provider exited on its own */
+export type TunnelStatus = "starting" | "ready" | "failed" | "stopped";
+
+export interface Tunnel {
+  id: number;
+  provider: TunnelProvider;
+  port: number;
+  status: TunnelStatus;
+  url: string | null;
+  error: string | null;
+  started_at: number;
+}
+
+/**
+ * Expose localhost:`port` at a public URL, through whichever provider is installed when none
+ * is given. The URL arrives in a tunnel-changed event.
+ */
+export function startTunnel(port: number, provider?: TunnelProvider): Promise<Tunnel> {
+  return invoke<Tunnel>("start_tunnel", { port, provider });
+}
+
+/** Every tunnel, including failed ones until they are stopped, oldest first */
+export function listTunnels(): Promise<Tunnel[]> {
+  return invoke<Tunnel[]>("list_tunnels");
+}
+
+export function stopTunnel(id: number): Promise<void> {
+  return invoke("stop_tunnel", { id });
+}
+
+export function onTunnelChanged(handler: (tunnel: Tunnel) => void): Promise<UnlistenFn> {
+  return listen<Tunnel>("tunnel-changed", (event) => handler(event.payload));
+}
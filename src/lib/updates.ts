@@ -0,0 +1,32 @@
+import { invoke, Channel } from "@tauri-apps/api/core";
+
+export interface UpdateInfo {
+  current_version: string;
+  latest_version: string;
+  update_available: boolean;
+  release_name: string | null;
+  /** Markdown */
+  notes: string | null;
+  published_at: string | null;
+  release_url: string;
+  /** Installer for this OS and architecture, if the release has one */
+  download_url: string | null;
+  asset_name: string | null;
+  asset_size: number | null;
+}
+
+export type DownloadEvent =
+  | { type: "started"; total_bytes: number | null }
+  | { type: "progress"; downloaded_bytes: number; total_bytes: number | null }
+  | { type: "finished"; path: string };
+
+export function checkForUpdates(includePrereleases = false): Promise<UpdateInfo> {
+  return invoke<UpdateInfo>("check_for_updates", { includePrereleases });
+}
+
+/** Saves to ~/Downloads; resolves to the installer's path */
+export function downloadUpdate(url: string, onEvent: (event: DownloadEvent) => void): Promise<string> {
+  const channel = new Channel<DownloadEvent>();
+  channel.onmessage = onEvent;
+  return invoke<string>("download_update", { url, onEvent: channel });
+}
@@ -0,0 +1,50 @@
+import { invoke, Channel } from "@tauri-apps/api/core";
+
+export type WebhookEvent = {
+  type: "request";
+  method: string;
+  /** Without the query string */
+  path: string;
+  query: string | null;
+  headers: [string, string][];
+  /** The body as text, when it is UTF-8 */
+  body: string | null;
+  /** The body, base64, when it isn't */
+  body_base64: string | null;
+  /** The body parsed, when it is JSON */
+  json: unknown | null;
+  source: "github" | "stripe" | null;
+  /** The GitHub event header, or a Stripe payload's `type` */
+  event_type: string | null;
+  received_at: number;
+};
+
+export interface WebhookListenerInfo {
+  id: number;
+  port: number;
+  path_prefix: string;
+  /** Where to point the sender */
+  url: string;
+}
+
+/**
+ * Listen on localhost:`port` (any free port when 0) and pass each request under `pathPrefix`
+ * on to `onEvent`; every request gets a 200 straight away
+ */
+export function startWebhookListener(
+  port: number,
+  onEvent: (event: WebhookEvent) => void,
+  pathPrefix?: string,
+): Promise<WebhookListenerInfo> {
+  const channel = new Channel<WebhookEvent>();
+  channel.onmessage = onEvent;
+  return invoke<WebhookListenerInfo>("start_webhook_listener", { port, pathPrefix, onEvent: channel });
+}
+
+export function listWebhookListeners(): Promise<WebhookListenerInfo[]> {
+  return invoke<WebhookListenerInfo[]>("list_webhook_listeners");
+}
+
+export function stopWebhookListener(id: number): Promise<void> {
+  return invoke("stop_webhook_listener", { id });
+}
@@ -0,0 +1,39 @@
+import { invoke, Channel } from "@tauri-apps/api/core";
+
+export interface WsConnectOptions {
+  headers?: Record<string, string>;
+  /** Offered in Sec-WebSocket-Protocol, like "vite-hmr" */
+  protocols?: string[];
+}
+
+export type WsEvent =
+  | { type: "open"; protocol: string | null }
+  | { type: "text"; data: string }
+  | { type: "binary"; data: number[] }
+  | { type: "error"; message: string }
+  /** Always the last event */
+  | { type: "closed"; code: number | null; reason: string };
+
+/**
+ * Open a WebSocket from the backend, free of the webview's origin rules. Resolves to its id once
+ * connected; messages and the close arrive as events.
+ */
+export function wsConnect(
+  url: string,
+  options: WsConnectOptions | undefined,
+  onEvent: (event: WsEvent) => void,
+): Promise<number> {
+  const channel = new Channel<WsEvent>();
+  channel.onmessage = onEvent;
+  return invoke<number>("ws_connect", { url, options, onEvent: channel });
+}
+
+/** A string is sent as a text message, an array of bytes as a binary one */
+export function wsSend(id: number, data: string | number[]): Promise<void> {
+  return invoke("ws_send", { id, data });
+}
+
+/** Start a clean close; a "closed" event follows */
+export function wsClose(id: number): Promise<void> {
+  return invoke("ws_close", { id });
+}
@@ -0,0 +1,19 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Task } from "./tasks";
+
+/** A member of an npm/yarn/pnpm or Cargo workspace */
+export interface WorkspacePackage {
+  name: string;
+  path: string;
+  /** Relative to the workspace root; "." for a root Cargo package */
+  relative_path: string;
+  kind: "npm" | "cargo";
+  version: string | null;
+  /** Tasks runnable in the package, as list_tasks returns them */
+  scripts: Task[];
+}
+
+/** Empty when `root` isn't a workspace root */
+export function listWorkspacePackages(root: string): Promise<WorkspacePackage[]> {
+  return invoke<WorkspacePackage[]>("list_workspace_packages", { root });
+}
@@ -0,0 +1,46 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Location } from "./remote";
+
+export interface DiffLine {
+  kind: "context" | "add" | "delete";
+  old_line: number | null;
+  new_line: number | null;
+  content: string;
+  /** Set on the last line of a file that has no trailing newline */
+  no_newline: boolean;
+}
+
+export interface DiffHunk {
+  old_start: number;
+  old_lines: number;
+  new_start: number;
+  new_lines: number;
+  header: string;
+  lines: DiffLine[];
+}
+
+export interface FileDiff {
+  /** null when the file doesn't exist yet */
+  old_path: string | null;
+  new_path: string | null;
+  status: "added" | "deleted" | "renamed" | "copied" | "modified" | "unchanged";
+  binary: boolean;
+  hunks: DiffHunk[];
+}
+
+/**
+ * What write_text_file would change, without writing anything: the diff between the file as
+ * it is now and `content`.
+ */
+export function previewWrite(path: string, content: string, location?: Location): Promise<FileDiff> {
+  return invoke<FileDiff>("write_text_file", { path, content, location, dryRun: true });
+}
+
+/** Lines a diff adds and removes, e.g. for "wants to change 14 lines" */
+export function changedLines(diff: FileDiff): { added: number; removed: number } {
+  const lines = diff.hunks.flatMap((hunk) => hunk.lines);
+  return {
+    added: lines.filter((line) => line.kind === "add").length,
+    removed: lines.filter((line) => line.kind === "delete").length,
+  };
+}
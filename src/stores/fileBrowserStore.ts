@@ -8,6 +8,7 @@ export interface FileEntry {
   size: number;
   extension: string | null;
   isHidden: boolean;
+  gitStatus: string | null;
 }
 
 export interface TreeNode {
@@ -58,6 +59,7 @@ function mapEntry(raw: Record<string, unknown>): FileEntry {
     size: raw.size as number,
     extension: (raw.extension as string | null) ?? null,
     isHidden: raw.is_hidden as boolean,
+    gitStatus: (raw.git_status as string | null) ?? null,
   };
 }
 
@@ -135,7 +137,7 @@ export const useFileBrowserStore = create<FileBrowserStore>((set, get) => ({
 
   loadDirectory: async (path) => {
     try {
-      const raw = await invoke<Record<string, unknown>[]>("list_directory", { path });
+      const raw = await invoke<Record<string, unknown>[]>("list_directory", { path, gitStatus: true });
       return raw.map(mapEntry);
     } catch {
       return [];
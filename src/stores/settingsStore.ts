@@ -1,4 +1,5 @@
 import { create } from "zustand";
+import { invoke } from "@tauri-apps/api/core";
 import type { Provider } from "../data/agentProfiles";
 
 export interface ThemeColors {
@@ -333,8 +334,26 @@ function loadSettings(): Partial<Settings> {
   }
 }
 
+/** Keychain entry holding the Anthropic API key; agent runs also read it as ANTHROPIC_API_KEY */
+const ANTHROPIC_KEY_SECRET = "anthropic-api-key";
+
 function persistSettings(s: Settings) {
-  localStorage.setItem(SETTINGS_KEY, JSON.stringify(s));
+  // The API key lives in the system keychain, never in localStorage
+  const { anthropicApiKey: _apiKey, ...rest } = s;
+  localStorage.setItem(SETTINGS_KEY, JSON.stringify(rest));
+}
+
+let apiKeyTimer: ReturnType<typeof setTimeout> | null = null;
+
+function saveApiKey(key: string) {
+  if (apiKeyTimer) clearTimeout(apiKeyTimer);
+  apiKeyTimer = setTimeout(() => {
+    const trimmed = key.trim();
+    const save = trimmed
+      ? invoke("store_secret", { name: ANTHROPIC_KEY_SECRET, value: trimmed })
+      : invoke("delete_secret", { name: ANTHROPIC_KEY_SECRET });
+    save.catch((e) => console.error("Failed to save API key:", e));
+  }, 500);
 }
 
 function loadWorkspaces(): WorkspacePreset[] {
@@ -439,7 +458,7 @@ export const useSettingsStore = create<SettingsStore>((set, get) => ({
 
   setAnthropicApiKey: (key) => {
     set({ anthropicApiKey: key });
-    persistSettings(get());
+    saveApiKey(key);
   },
   setOrchestratorModel: (model) => {
     set({ orchestratorModel: model });
@@ -486,6 +505,19 @@ export const useSettingsStore = create<SettingsStore>((set, get) => ({
   },
 }));
 
+// Move a key saved by older versions out of localStorage, otherwise load it from the keychain
+if (saved.anthropicApiKey) {
+  invoke("store_secret", { name: ANTHROPIC_KEY_SECRET, value: saved.anthropicApiKey })
+    .then(() => persistSettings(useSettingsStore.getState()))
+    .catch((e) => console.error("Failed to move API key to the keychain:", e));
+} else {
+  invoke<string | null>("get_secret", { name: ANTHROPIC_KEY_SECRET })
+    .then((key) => {
+      if (key) useSettingsStore.setState({ anthropicApiKey: key });
+    })
+    .catch(() => {});
+}
+
 export function applyThemeToDOM(colors: ThemeColors) {
   const root = document.documentElement;
   root.style.setProperty("--bg-primary", colors.bgPrimary);
@@ -47,6 +47,7 @@ interface TabStore {
   renameTab: (id: string, name: string) => void;
 
   setActivePaneInTab: (tabId: string, paneId: string) => void;
+  focusPane: (paneId: string) => boolean;
   setPtyId: (paneId: string, ptyId: number) => void;
   splitPane: (tabId: string, paneId: string, direction: SplitDirection, initialCwd?: string | null) => void;
   closePane: (tabId: string, paneId: string) => void;
@@ -304,6 +305,17 @@ export const useTabStore = create<TabStore>((set, get) => {
         ),
       })),
 
+    // Select the tab holding paneId and make it that tab's active pane; false if this window has no such pane
+    focusPane: (paneId) => {
+      const tab = get().tabs.find((t) => findPane(t.root, paneId));
+      if (!tab) return false;
+      set((s) => ({
+        activeTabId: tab.id,
+        tabs: s.tabs.map((t) => (t.id === tab.id ? { ...t, activePaneId: paneId } : t)),
+      }));
+      return true;
+    },
+
     setPtyId: (paneId, ptyId) =>
       set((s) => ({
         tabs: s.tabs.map((t) => ({